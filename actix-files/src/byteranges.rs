@@ -0,0 +1,417 @@
+use std::{
+    collections::hash_map::RandomState,
+    fmt,
+    hash::{BuildHasher, Hasher},
+    pin::Pin,
+    task::{Context, Poll},
+    time::{SystemTime, UNIX_EPOCH},
+    vec,
+};
+
+use actix_web::{
+    dev::{BodySize, MessageBody},
+    http::header::ContentRangeSpec,
+};
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+
+use crate::{error::MultipartByteRangesError, range::HttpRange};
+
+/// Builder for a `206 Partial Content` response covering multiple byte ranges.
+///
+/// Produces the `multipart/byteranges` content type (including a generated boundary), the
+/// `Content-Range` of each part, and a streaming body that frames the data of each range as
+/// described in [RFC 7233 §4.1](https://datatracker.ietf.org/doc/html/rfc7233#section-4.1).
+///
+/// # Examples
+/// ```
+/// use std::io;
+///
+/// use actix_files::{HttpRange, MultipartByteRanges};
+/// use actix_web::{dev::AnyBody, http::StatusCode, HttpResponse};
+/// use bytes::Bytes;
+/// use futures_util::{future, stream};
+///
+/// let data = Bytes::from_static(b"0123456789");
+/// let ranges = HttpRange::parse("bytes=0-1,5-6", data.len() as u64).unwrap();
+///
+/// let multipart =
+///     MultipartByteRanges::new(&ranges, data.len() as u64, &mime::TEXT_PLAIN).unwrap();
+/// assert_eq!(multipart.content_ranges().next().unwrap().to_string(), "bytes 0-1/10");
+///
+/// let content_type = multipart.content_type();
+/// let body = multipart.into_body(move |range| {
+///     let start = range.start as usize;
+///     let end = start + range.length as usize;
+///     stream::once(future::ready(Ok::<_, io::Error>(data.slice(start..end))))
+/// });
+///
+/// let res = HttpResponse::build(StatusCode::PARTIAL_CONTENT)
+///     .content_type(content_type)
+///     .body(AnyBody::from_message(body));
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultipartByteRanges {
+    ranges: Vec<HttpRange>,
+    total_length: u64,
+    part_type: String,
+    boundary: String,
+}
+
+impl MultipartByteRanges {
+    /// Creates a multipart response for `ranges` of a representation that is `total_length`
+    /// bytes long and of type `content_type`.
+    ///
+    /// Returns an error if `ranges` is empty or any range is empty or extends past
+    /// `total_length`; ranges returned by [`HttpRange::parse`] are always satisfiable. A random
+    /// boundary is generated; use [`with_boundary`](Self::with_boundary) to override it.
+    pub fn new(
+        ranges: &[HttpRange],
+        total_length: u64,
+        content_type: &mime::Mime,
+    ) -> Result<Self, MultipartByteRangesError> {
+        if ranges.is_empty() {
+            return Err(MultipartByteRangesError::NoRanges);
+        }
+
+        let satisfiable = ranges.iter().all(|range| {
+            range.length > 0
+                && range
+                    .start
+                    .checked_add(range.length)
+                    .map_or(false, |end| end <= total_length)
+        });
+
+        if !satisfiable {
+            return Err(MultipartByteRangesError::UnsatisfiableRange);
+        }
+
+        Ok(Self {
+            ranges: ranges.to_vec(),
+            total_length,
+            part_type: content_type.to_string(),
+            boundary: generate_boundary(),
+        })
+    }
+
+    /// Replaces the generated boundary.
+    ///
+    /// Per RFC 2046, the boundary must be 1 to 70 characters long, consist only of letters,
+    /// digits, spaces and the characters `'()+_,-./:=?`, and must not end with a space. It must
+    /// also not occur within any part.
+    pub fn with_boundary(
+        mut self,
+        boundary: impl Into<String>,
+    ) -> Result<Self, MultipartByteRangesError> {
+        let boundary = boundary.into();
+
+        if !is_valid_boundary(&boundary) {
+            return Err(MultipartByteRangesError::InvalidBoundary);
+        }
+
+        self.boundary = boundary;
+        Ok(self)
+    }
+
+    /// Returns the boundary delimiting each part.
+    pub fn boundary(&self) -> &str {
+        &self.boundary
+    }
+
+    /// Returns the ranges that make up this response, in order.
+    pub fn ranges(&self) -> &[HttpRange] {
+        &self.ranges
+    }
+
+    /// Returns the `multipart/byteranges` content type to send with the response.
+    pub fn content_type(&self) -> mime::Mime {
+        // boundaries may contain characters that are not allowed in an unquoted parameter value
+        let needs_quotes = self.boundary.bytes().any(|b| b" ()+,/:=?".contains(&b));
+
+        let content_type = if needs_quotes {
+            format!("multipart/byteranges; boundary=\"{}\"", self.boundary)
+        } else {
+            format!("multipart/byteranges; boundary={}", self.boundary)
+        };
+
+        content_type.parse().unwrap()
+    }
+
+    /// Returns the `Content-Range` of each part, in the same order as [`ranges`](Self::ranges).
+    pub fn content_ranges(&self) -> impl Iterator<Item = ContentRangeSpec> + '_ {
+        let total_length = self.total_length;
+
+        self.ranges
+            .iter()
+            .map(move |range| content_range(range, total_length))
+    }
+
+    /// Returns the exact length of the body, including delimiters and part headers.
+    pub fn content_length(&self) -> u64 {
+        let parts: u64 = self
+            .ranges
+            .iter()
+            .enumerate()
+            .map(|(idx, range)| self.part_head(idx, range).len() as u64 + range.length)
+            .sum();
+
+        parts + self.closing_delimiter().len() as u64
+    }
+
+    /// Converts into a streaming body.
+    ///
+    /// `source` is called once per range, in order, and must return a stream yielding exactly
+    /// `range.length` bytes starting at `range.start`.
+    pub fn into_body<F, S, E>(self, source: F) -> MultipartByteRangesBody<F, S>
+    where
+        F: FnMut(HttpRange) -> S,
+        S: Stream<Item = Result<Bytes, E>>,
+    {
+        let size = self.content_length();
+
+        let parts = self
+            .ranges
+            .iter()
+            .enumerate()
+            .map(|(idx, range)| (Bytes::from(self.part_head(idx, range)), *range))
+            .collect::<Vec<_>>();
+
+        MultipartByteRangesBody {
+            size,
+            parts: parts.into_iter(),
+            closing: Some(Bytes::from(self.closing_delimiter())),
+            source,
+            current: None,
+        }
+    }
+
+    /// Delimiter and headers that precede the data of the part at `idx`.
+    fn part_head(&self, idx: usize, range: &HttpRange) -> String {
+        // every delimiter after the first is preceded by the CRLF ending the previous part
+        let crlf = if idx == 0 { "" } else { "\r\n" };
+
+        format!(
+            "{}--{}\r\nContent-Type: {}\r\nContent-Range: {}\r\n\r\n",
+            crlf,
+            self.boundary,
+            self.part_type,
+            content_range(range, self.total_length)
+        )
+    }
+
+    fn closing_delimiter(&self) -> String {
+        format!("\r\n--{}--\r\n", self.boundary)
+    }
+}
+
+/// Streaming `multipart/byteranges` body.
+///
+/// Created by [`MultipartByteRanges::into_body`].
+pub struct MultipartByteRangesBody<F, S> {
+    size: u64,
+    parts: vec::IntoIter<(Bytes, HttpRange)>,
+    closing: Option<Bytes>,
+    source: F,
+    current: Option<S>,
+}
+
+impl<F, S> fmt::Debug for MultipartByteRangesBody<F, S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MultipartByteRangesBody")
+            .field("size", &self.size)
+            .field("remaining_parts", &self.parts.len())
+            .finish()
+    }
+}
+
+impl<F, S, E> MessageBody for MultipartByteRangesBody<F, S>
+where
+    F: FnMut(HttpRange) -> S + Unpin,
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+{
+    type Error = E;
+
+    fn size(&self) -> BodySize {
+        BodySize::Sized(self.size)
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        if let Some(ref mut stream) = this.current {
+            match ready!(Pin::new(stream).poll_next(cx)) {
+                Some(res) => return Poll::Ready(Some(res)),
+                None => this.current = None,
+            }
+        }
+
+        match this.parts.next() {
+            Some((head, range)) => {
+                this.current = Some((this.source)(range));
+                Poll::Ready(Some(Ok(head)))
+            }
+            None => Poll::Ready(this.closing.take().map(Ok)),
+        }
+    }
+}
+
+fn content_range(range: &HttpRange, total_length: u64) -> ContentRangeSpec {
+    ContentRangeSpec::Bytes {
+        range: Some((range.start, range.start + range.length - 1)),
+        instance_length: Some(total_length),
+    }
+}
+
+/// Checks `boundary` against the `boundary` production of RFC 2046 §5.1.1.
+fn is_valid_boundary(boundary: &str) -> bool {
+    let bytes = boundary.as_bytes();
+
+    !bytes.is_empty()
+        && bytes.len() <= 70
+        && bytes[bytes.len() - 1] != b' '
+        && bytes
+            .iter()
+            .all(|&b| b.is_ascii_alphanumeric() || b == b' ' || b"'()+_,-./:=?".contains(&b))
+}
+
+fn generate_boundary() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|dur| dur.as_nanos())
+        .unwrap_or_default();
+
+    // each `RandomState` is seeded with fresh random keys
+    let mut hi = RandomState::new().build_hasher();
+    hi.write_u128(nanos);
+    let mut lo = RandomState::new().build_hasher();
+    lo.write_u128(nanos);
+
+    format!("{:016x}{:016x}", hi.finish(), lo.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{body, Error};
+    use futures_util::stream;
+
+    use super::*;
+
+    const DATA: &[u8] = b"0123456789abcdefghij";
+
+    fn source(range: HttpRange) -> impl Stream<Item = Result<Bytes, Error>> + Unpin {
+        let start = range.start as usize;
+        let end = start + range.length as usize;
+        stream::iter(vec![Ok(Bytes::from_static(&DATA[start..end]))])
+    }
+
+    #[test]
+    fn headers() {
+        let ranges = HttpRange::parse("bytes=0-4,10-", DATA.len() as u64).unwrap();
+        let multipart = MultipartByteRanges::new(&ranges, DATA.len() as u64, &mime::TEXT_PLAIN)
+            .unwrap()
+            .with_boundary("THIS_STRING_SEPARATES")
+            .unwrap();
+
+        assert_eq!(multipart.boundary(), "THIS_STRING_SEPARATES");
+        assert_eq!(
+            multipart.content_type().to_string(),
+            "multipart/byteranges; boundary=THIS_STRING_SEPARATES"
+        );
+
+        let content_ranges = multipart
+            .content_ranges()
+            .map(|spec| spec.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(content_ranges, vec!["bytes 0-4/20", "bytes 10-19/20"]);
+    }
+
+    #[test]
+    fn generated_boundary() {
+        let ranges = HttpRange::parse("bytes=0-1,3-4", DATA.len() as u64).unwrap();
+        let a =
+            MultipartByteRanges::new(&ranges, DATA.len() as u64, &mime::TEXT_PLAIN).unwrap();
+        let b =
+            MultipartByteRanges::new(&ranges, DATA.len() as u64, &mime::TEXT_PLAIN).unwrap();
+
+        assert_eq!(a.boundary().len(), 32);
+        assert_ne!(a.boundary(), b.boundary());
+        assert!(a.content_type().get_param("boundary").is_some());
+    }
+
+    #[test]
+    fn invalid_ranges() {
+        let len = DATA.len() as u64;
+
+        let err = MultipartByteRanges::new(&[], len, &mime::TEXT_PLAIN).unwrap_err();
+        assert_eq!(err, MultipartByteRangesError::NoRanges);
+
+        let empty = HttpRange {
+            start: 0,
+            length: 0,
+        };
+        let err = MultipartByteRanges::new(&[empty], len, &mime::TEXT_PLAIN).unwrap_err();
+        assert_eq!(err, MultipartByteRangesError::UnsatisfiableRange);
+
+        let past_end = HttpRange {
+            start: 15,
+            length: 10,
+        };
+        let err = MultipartByteRanges::new(&[past_end], len, &mime::TEXT_PLAIN).unwrap_err();
+        assert_eq!(err, MultipartByteRangesError::UnsatisfiableRange);
+    }
+
+    #[test]
+    fn invalid_boundary() {
+        let ranges = HttpRange::parse("bytes=0-1", DATA.len() as u64).unwrap();
+        let multipart =
+            MultipartByteRanges::new(&ranges, DATA.len() as u64, &mime::TEXT_PLAIN).unwrap();
+
+        for boundary in &["", "trailing ", "semi;colon", "quo\"te", "new\r\nline"] {
+            let err = multipart.clone().with_boundary(*boundary).unwrap_err();
+            assert_eq!(err, MultipartByteRangesError::InvalidBoundary);
+        }
+
+        let err = multipart.clone().with_boundary("a".repeat(71)).unwrap_err();
+        assert_eq!(err, MultipartByteRangesError::InvalidBoundary);
+
+        assert!(multipart.clone().with_boundary("a".repeat(70)).is_ok());
+        assert!(multipart
+            .with_boundary("gc0p4Jq0M2Yt08j'()+_,-./:=? x")
+            .is_ok());
+    }
+
+    #[actix_rt::test]
+    async fn body() {
+        let ranges = HttpRange::parse("bytes=0-4,10-", DATA.len() as u64).unwrap();
+        let multipart = MultipartByteRanges::new(&ranges, DATA.len() as u64, &mime::TEXT_PLAIN)
+            .unwrap()
+            .with_boundary("SEP")
+            .unwrap();
+        let len = multipart.content_length();
+
+        let body = multipart.into_body(source);
+        assert_eq!(body.size(), BodySize::Sized(len));
+
+        let bytes = body::to_bytes(body).await.ok().unwrap();
+        assert_eq!(
+            bytes,
+            Bytes::from_static(
+                b"--SEP\r\n\
+                Content-Type: text/plain\r\n\
+                Content-Range: bytes 0-4/20\r\n\
+                \r\n\
+                01234\r\n\
+                --SEP\r\n\
+                Content-Type: text/plain\r\n\
+                Content-Range: bytes 10-19/20\r\n\
+                \r\n\
+                abcdefghij\r\n\
+                --SEP--\r\n"
+            )
+        );
+        assert_eq!(bytes.len() as u64, len);
+    }
+}
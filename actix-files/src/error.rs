@@ -40,3 +40,33 @@ impl ResponseError for UriSegmentError {
         StatusCode::BAD_REQUEST
     }
 }
+
+/// Errors which can occur when building a `multipart/byteranges` response.
+#[derive(Display, Debug, PartialEq)]
+pub enum MultipartByteRangesError {
+    /// No ranges were given.
+    #[display(fmt = "No byte ranges to send")]
+    NoRanges,
+
+    /// A range is empty or extends past the end of the representation.
+    #[display(fmt = "Byte range is not satisfiable")]
+    UnsatisfiableRange,
+
+    /// The boundary is empty, longer than 70 characters, or contains characters not allowed
+    /// by RFC 2046.
+    #[display(fmt = "Invalid multipart boundary")]
+    InvalidBoundary,
+}
+
+impl std::error::Error for MultipartByteRangesError {}
+
+/// Return `RangeNotSatisfiable` for range errors and `InternalServerError` otherwise
+impl ResponseError for MultipartByteRangesError {
+    fn status_code(&self) -> StatusCode {
+        match self {
+            MultipartByteRangesError::NoRanges
+            | MultipartByteRangesError::UnsatisfiableRange => StatusCode::RANGE_NOT_SATISFIABLE,
+            MultipartByteRangesError::InvalidBoundary => StatusCode::INTERNAL_SERVER_ERROR,
+        }
+    }
+}
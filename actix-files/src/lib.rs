@@ -23,6 +23,7 @@ use actix_web::{
 use mime_guess::from_ext;
 use std::path::Path;
 
+mod byteranges;
 mod chunked;
 mod directory;
 mod encoding;
@@ -33,8 +34,10 @@ mod path_buf;
 mod range;
 mod service;
 
+pub use crate::byteranges::{MultipartByteRanges, MultipartByteRangesBody};
 pub use crate::chunked::ChunkedReadFile;
 pub use crate::directory::Directory;
+pub use crate::error::MultipartByteRangesError;
 pub use crate::files::Files;
 pub use crate::named::NamedFile;
 pub use crate::range::HttpRange;
@@ -56,6 +56,12 @@ pub async fn test_server<F: ServiceFactory<TcpStream>>(factory: F) -> TestServer
     test_server_with_addr(tcp, factory).await
 }
 
+/// Start [`test server`](test_server()) listening on the IPv6 loopback address `[::1]`.
+pub async fn test_server_ipv6<F: ServiceFactory<TcpStream>>(factory: F) -> TestServer {
+    let tcp = net::TcpListener::bind("[::1]:0").unwrap();
+    test_server_with_addr(tcp, factory).await
+}
+
 /// Start [`test server`](test_server()) on a concrete Address
 pub async fn test_server_with_addr<F: ServiceFactory<TcpStream>>(
     tcp: net::TcpListener,
@@ -132,20 +138,12 @@ impl TestServer {
 
     /// Construct test server url
     pub fn url(&self, uri: &str) -> String {
-        if uri.starts_with('/') {
-            format!("http://localhost:{}{}", self.addr.port(), uri)
-        } else {
-            format!("http://localhost:{}/{}", self.addr.port(), uri)
-        }
+        server_url("http", self.addr, uri)
     }
 
     /// Construct test HTTPS server URL.
     pub fn surl(&self, uri: &str) -> String {
-        if uri.starts_with('/') {
-            format!("https://localhost:{}{}", self.addr.port(), uri)
-        } else {
-            format!("https://localhost:{}/{}", self.addr.port(), uri)
-        }
+        server_url("https", self.addr, uri)
     }
 
     /// Create `GET` request
@@ -279,3 +277,51 @@ pub fn unused_addr() -> net::SocketAddr {
     let tcp = net::TcpListener::from(socket);
     tcp.local_addr().unwrap()
 }
+
+/// Bind listener accepting IPv4 and IPv6 connections on a random, unused port.
+///
+/// The listener's local address is the unspecified IPv6 address `[::]`. Use with
+/// [`test_server_with_addr`].
+pub fn dual_stack_listener() -> net::TcpListener {
+    let addr: net::SocketAddr = "[::]:0".parse().unwrap();
+    let socket = Socket::new(Domain::IPV6, Type::STREAM, Some(Protocol::TCP)).unwrap();
+    socket.set_only_v6(false).unwrap();
+    socket.bind(&addr.into()).unwrap();
+    socket.listen(1024).unwrap();
+    net::TcpListener::from(socket)
+}
+
+/// Format IP address as URI host, enclosing IPv6 addresses in brackets.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+/// use actix_http_test::uri_host;
+///
+/// assert_eq!(uri_host(IpAddr::V4(Ipv4Addr::LOCALHOST)), "127.0.0.1");
+/// assert_eq!(uri_host(IpAddr::V6(Ipv6Addr::LOCALHOST)), "[::1]");
+/// ```
+pub fn uri_host(ip: net::IpAddr) -> String {
+    match ip {
+        net::IpAddr::V4(ip) => ip.to_string(),
+        net::IpAddr::V6(ip) => format!("[{}]", ip),
+    }
+}
+
+/// Construct URL of a test server listening on `addr`.
+///
+/// IPv4 and unspecified (dual-stack) addresses are reached through `localhost`; other IPv6
+/// addresses are used directly, e.g. `http://[::1]:8080/path`.
+pub fn server_url(scheme: &str, addr: net::SocketAddr, path: &str) -> String {
+    let host = match addr {
+        net::SocketAddr::V6(addr) if !addr.ip().is_unspecified() => {
+            uri_host(net::IpAddr::V6(*addr.ip()))
+        }
+        _ => "localhost".to_owned(),
+    };
+
+    if path.starts_with('/') {
+        format!("{}://{}:{}{}", scheme, host, addr.port(), path)
+    } else {
+        format!("{}://{}:{}/{}", scheme, host, addr.port(), path)
+    }
+}
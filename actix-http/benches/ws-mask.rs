@@ -0,0 +1,85 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+
+fn bench_apply_mask(c: &mut Criterion) {
+    let mut group = c.benchmark_group("apply_mask");
+
+    let mask = [0x6d, 0xb6, 0xb2, 0x80];
+
+    for &size in &[16, 1024, 65_536] {
+        // offset by one byte to exercise unaligned prefix handling
+        let mut buf = vec![0xaa; size + 1];
+
+        group.bench_with_input(BenchmarkId::new("Original", size), &size, |b, _| {
+            b.iter(|| _original::apply_mask(black_box(&mut buf[1..]), black_box(mask)));
+        });
+
+        group.bench_with_input(BenchmarkId::new("New", size), &size, |b, _| {
+            b.iter(|| _new::apply_mask(black_box(&mut buf[1..]), black_box(mask)));
+        });
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_apply_mask);
+criterion_main!(benches);
+
+mod _new {
+    fn apply_mask_fallback(buf: &mut [u8], mask: [u8; 4]) {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte ^= mask[i & 3];
+        }
+    }
+
+    pub fn apply_mask(buf: &mut [u8], mask: [u8; 4]) {
+        let (prefix, words, suffix) = unsafe { buf.align_to_mut::<u64>() };
+        apply_mask_fallback(prefix, mask);
+
+        let head = prefix.len() & 3;
+        let mask = [
+            mask[head],
+            mask[(head + 1) & 3],
+            mask[(head + 2) & 3],
+            mask[(head + 3) & 3],
+        ];
+
+        let mask_u64 = u64::from_ne_bytes([
+            mask[0], mask[1], mask[2], mask[3], mask[0], mask[1], mask[2], mask[3],
+        ]);
+
+        for word in words.iter_mut() {
+            *word ^= mask_u64;
+        }
+
+        apply_mask_fallback(suffix, mask);
+    }
+}
+
+mod _original {
+    fn apply_mask_fallback(buf: &mut [u8], mask: [u8; 4]) {
+        for (i, byte) in buf.iter_mut().enumerate() {
+            *byte ^= mask[i & 3];
+        }
+    }
+
+    pub fn apply_mask(buf: &mut [u8], mask: [u8; 4]) {
+        let mask_u32 = u32::from_ne_bytes(mask);
+
+        let (prefix, words, suffix) = unsafe { buf.align_to_mut::<u32>() };
+        apply_mask_fallback(prefix, mask);
+        let head = prefix.len() & 3;
+        let mask_u32 = if head > 0 {
+            if cfg!(target_endian = "big") {
+                mask_u32.rotate_left(8 * head as u32)
+            } else {
+                mask_u32.rotate_right(8 * head as u32)
+            }
+        } else {
+            mask_u32
+        };
+        for word in words.iter_mut() {
+            *word ^= mask_u32;
+        }
+        apply_mask_fallback(suffix, mask_u32.to_ne_bytes());
+    }
+}
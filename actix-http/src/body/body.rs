@@ -9,7 +9,7 @@ use std::{
 use bytes::{Bytes, BytesMut};
 use futures_core::{ready, Stream};
 
-use crate::error::Error;
+use crate::{error::Error, header::HeaderMap};
 
 use super::{BodySize, BodyStream, MessageBody, MessageBodyMapErr, SizedStream};
 
@@ -84,6 +84,16 @@ impl MessageBody for AnyBody {
             },
         }
     }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        match self.get_mut() {
+            AnyBody::Message(body) => Pin::new(body).poll_trailers(cx),
+            _ => Poll::Ready(Ok(None)),
+        }
+    }
 }
 
 impl PartialEq for AnyBody {
@@ -230,4 +240,15 @@ impl MessageBody for BoxAnyBody {
             None => Poll::Ready(None),
         }
     }
+
+    fn poll_trailers(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        // TODO: MSRV 1.51: poll_map_err
+        match ready!(self.0.as_mut().poll_trailers(cx)) {
+            Ok(trailers) => Poll::Ready(Ok(trailers)),
+            Err(err) => Poll::Ready(Err(Error::new_body().with_cause(err))),
+        }
+    }
 }
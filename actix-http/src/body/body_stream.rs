@@ -8,7 +8,7 @@ use bytes::Bytes;
 use futures_core::{ready, Stream};
 use pin_project_lite::pin_project;
 
-use super::{BodySize, MessageBody};
+use super::{coalesce::Coalesce, BodySize, MessageBody};
 
 pin_project! {
     /// Streaming response wrapper.
@@ -17,6 +17,7 @@ pin_project! {
     pub struct BodyStream<S> {
         #[pin]
         stream: S,
+        coalesce: Coalesce,
     }
 }
 
@@ -26,7 +27,80 @@ where
     E: Into<Box<dyn StdError>> + 'static,
 {
     pub fn new(stream: S) -> Self {
-        BodyStream { stream }
+        BodyStream {
+            stream,
+            coalesce: Coalesce::default(),
+        }
+    }
+}
+
+impl<S> BodyStream<S> {
+    /// Merge chunks that are immediately available until they total at least `chunk_size` bytes.
+    ///
+    /// Useful for streams producing many tiny chunks, e.g. server-sent events, which would
+    /// otherwise each be written with their own chunked transfer encoding frame. Buffered data is
+    /// sent as soon as the stream is not ready, so no latency is added. Zero disables coalescing.
+    pub fn coalesce(mut self, chunk_size: usize) -> Self {
+        self.coalesce = Coalesce::new(chunk_size);
+        self
+    }
+}
+
+impl<S, T, E, F, E2> BodyStream<TryStreamMap<S, F>>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: Into<Bytes>,
+    F: FnMut(E) -> E2,
+    E2: Into<Box<dyn StdError>> + 'static,
+{
+    /// Create body from stream of fallible chunks, converting its errors with `f`.
+    ///
+    /// Chunks can be of any type convertible into `Bytes`, and errors of any type, e.g. errors of
+    /// a database or RPC client stream that do not implement `std::error::Error`.
+    ///
+    /// ```
+    /// use std::io;
+    ///
+    /// use actix_http::body::BodyStream;
+    /// use futures_util::stream;
+    ///
+    /// let rows = stream::iter(vec![Ok("row 1\n"), Err("connection lost")]);
+    /// let body = BodyStream::try_from_stream(rows, |err| io::Error::new(io::ErrorKind::Other, err));
+    /// ```
+    pub fn try_from_stream(stream: S, f: F) -> Self {
+        BodyStream::new(TryStreamMap { stream, f })
+    }
+}
+
+pin_project! {
+    /// Stream converting the chunks and errors of a fallible stream, created by
+    /// [`BodyStream::try_from_stream`].
+    pub struct TryStreamMap<S, F> {
+        #[pin]
+        stream: S,
+        f: F,
+    }
+}
+
+impl<S, T, E, F, E2> Stream for TryStreamMap<S, F>
+where
+    S: Stream<Item = Result<T, E>>,
+    T: Into<Bytes>,
+    F: FnMut(E) -> E2,
+{
+    type Item = Result<Bytes, E2>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+
+        match ready!(this.stream.poll_next(cx)) {
+            Some(Ok(chunk)) => Poll::Ready(Some(Ok(chunk.into()))),
+            Some(Err(err)) => Poll::Ready(Some(Err((this.f)(err)))),
+            None => Poll::Ready(None),
+        }
     }
 }
 
@@ -47,19 +121,11 @@ where
     /// ended on a zero-length chunk, but rather proceed until the underlying
     /// [`Stream`] ends.
     fn poll_next(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
-        loop {
-            let stream = self.as_mut().project().stream;
-
-            let chunk = match ready!(stream.poll_next(cx)) {
-                Some(Ok(ref bytes)) if bytes.is_empty() => continue,
-                opt => opt,
-            };
-
-            return Poll::Ready(chunk);
-        }
+        let this = self.project();
+        this.coalesce.poll_next(this.stream, cx)
     }
 }
 
@@ -118,6 +184,76 @@ mod tests {
     #[display(fmt = "stream error")]
     struct StreamErr;
 
+    #[actix_rt::test]
+    async fn try_from_stream() {
+        let body = BodyStream::try_from_stream(
+            stream::iter(vec![Ok("1"), Ok(""), Ok("2")]),
+            |err: &str| StreamErr2(err.to_owned()),
+        );
+        assert_eq!(to_bytes(body).await.ok(), Some(Bytes::from("12")));
+
+        let body = BodyStream::try_from_stream(
+            stream::iter(vec![Ok(vec![b'1']), Err("oops")]),
+            |err| StreamErr2(err.to_owned()),
+        );
+        assert_eq!(to_bytes(body).await.unwrap_err().0, "oops");
+    }
+
+    #[derive(Debug, Display, Error)]
+    #[display(fmt = "stream error: {}", _0)]
+    struct StreamErr2(#[error(not(source))] String);
+
+    #[actix_rt::test]
+    async fn coalesce_chunks() {
+        let body = BodyStream::new(stream::iter(
+            ["1", "", "22", "333", "4"]
+                .iter()
+                .map(|&v| Ok::<_, Infallible>(Bytes::from(v))),
+        ))
+        .coalesce(4);
+        pin!(body);
+
+        assert_eq!(
+            poll_fn(|cx| body.as_mut().poll_next(cx))
+                .await
+                .unwrap()
+                .ok(),
+            Some(Bytes::from("122333")),
+        );
+        assert_eq!(
+            poll_fn(|cx| body.as_mut().poll_next(cx))
+                .await
+                .unwrap()
+                .ok(),
+            Some(Bytes::from("4")),
+        );
+        assert!(poll_fn(|cx| body.as_mut().poll_next(cx)).await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn coalesce_flushes_when_pending() {
+        use futures_util::StreamExt as _;
+
+        let body = BodyStream::new(
+            stream::iter(
+                ["1", "2"]
+                    .iter()
+                    .map(|&v| Ok::<_, Infallible>(Bytes::from(v))),
+            )
+            .chain(stream::pending()),
+        )
+        .coalesce(1024);
+        pin!(body);
+
+        assert_eq!(
+            poll_fn(|cx| body.as_mut().poll_next(cx))
+                .await
+                .unwrap()
+                .ok(),
+            Some(Bytes::from("12")),
+        );
+    }
+
     #[actix_rt::test]
     async fn stream_immediate_error() {
         let body = BodyStream::new(stream::once(async { Err(StreamErr) }));
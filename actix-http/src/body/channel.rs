@@ -0,0 +1,234 @@
+use std::{
+    error::Error as StdError,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use derive_more::{Display, Error};
+use tokio::sync::mpsc;
+
+use super::{BodySize, MessageBody};
+
+/// Creates a bounded channel for streaming a response body.
+///
+/// The [`Sender`] half can be moved into a spawned task that produces body chunks while the
+/// [`Receiver`] half is used as the response body. At most `capacity` chunks are buffered; once
+/// the buffer is full, [`Sender::send`] waits until the response writer has caught up.
+///
+/// The body is finished when [`Sender::close`] is called. If all senders are dropped without
+/// closing the body, it yields an [`IncompleteBody`] error after the chunks sent so far; this
+/// aborts the response instead of passing a truncated body off as complete.
+///
+/// # Panics
+/// Panics if `capacity` is zero.
+///
+/// # Examples
+/// ```
+/// use std::convert::Infallible;
+/// use actix_http::{body, Response};
+/// use bytes::Bytes;
+///
+/// # async fn test_channel() {
+/// let (tx, rx) = body::channel::<Infallible>(8);
+///
+/// actix_rt::spawn(async move {
+///     for chunk in ["hello", " ", "world"].iter() {
+///         if tx.send(Bytes::from(*chunk)).await.is_err() {
+///             return;
+///         }
+///     }
+///
+///     tx.close(Ok(())).await.ok();
+/// });
+///
+/// let res = Response::ok().set_body(rx);
+/// # }
+/// ```
+pub fn channel<E>(capacity: usize) -> (Sender<E>, Receiver<E>) {
+    let (tx, rx) = mpsc::channel(capacity);
+    (Sender { tx }, Receiver { rx, done: false })
+}
+
+enum Chunk<E> {
+    Data(Bytes),
+    Close(Result<(), E>),
+}
+
+/// Error returned by [`Sender`] when the body [`Receiver`] has been dropped.
+///
+/// This usually means that the response was aborted, e.g. because the client disconnected.
+#[derive(Debug, Display, Error)]
+#[display(fmt = "Body receiver has been dropped.")]
+pub struct SendError;
+
+/// Error yielded by [`Receiver`] when all senders have been dropped without closing the body.
+#[derive(Debug, Display, Error)]
+#[display(fmt = "Body senders have been dropped without closing the body.")]
+pub struct IncompleteBody;
+
+/// Sending half of a body [`channel`].
+pub struct Sender<E> {
+    tx: mpsc::Sender<Chunk<E>>,
+}
+
+impl<E> Clone for Sender<E> {
+    fn clone(&self) -> Self {
+        Sender {
+            tx: self.tx.clone(),
+        }
+    }
+}
+
+impl<E> Sender<E> {
+    /// Sends a body chunk, waiting for buffer capacity if the channel is full.
+    pub async fn send(&self, chunk: Bytes) -> Result<(), SendError> {
+        self.tx
+            .send(Chunk::Data(chunk))
+            .await
+            .map_err(|_| SendError)
+    }
+
+    /// Attempts to send a body chunk without waiting.
+    ///
+    /// Returns the chunk back if the channel is full or closed.
+    pub fn try_send(&self, chunk: Bytes) -> Result<(), Bytes> {
+        self.tx
+            .try_send(Chunk::Data(chunk))
+            .map_err(|err| match err {
+                mpsc::error::TrySendError::Full(Chunk::Data(chunk))
+                | mpsc::error::TrySendError::Closed(Chunk::Data(chunk)) => chunk,
+                _ => unreachable!(),
+            })
+    }
+
+    /// Finishes the body.
+    ///
+    /// Passing `Ok(())` ends the body normally once all previously sent chunks are written.
+    /// Passing an error causes the body to yield that error after previously sent chunks; this
+    /// aborts the response.
+    pub async fn close(self, res: Result<(), E>) -> Result<(), SendError> {
+        self.tx.send(Chunk::Close(res)).await.map_err(|_| SendError)
+    }
+
+    /// Returns true if the body receiver has been dropped.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+}
+
+/// Receiving half of a body [`channel`]. Implements [`MessageBody`].
+pub struct Receiver<E> {
+    rx: mpsc::Receiver<Chunk<E>>,
+    done: bool,
+}
+
+impl<E> Unpin for Receiver<E> {}
+
+impl<E> MessageBody for Receiver<E>
+where
+    E: Into<Box<dyn StdError>> + 'static,
+{
+    type Error = Box<dyn StdError>;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        loop {
+            if this.done {
+                return Poll::Ready(None);
+            }
+
+            let res = match this.rx.poll_recv(cx) {
+                Poll::Ready(Some(Chunk::Data(chunk))) if chunk.is_empty() => continue,
+                Poll::Ready(Some(Chunk::Data(chunk))) => Some(Ok(chunk)),
+                Poll::Ready(Some(Chunk::Close(res))) => {
+                    this.done = true;
+                    this.rx.close();
+                    res.err().map(|err| Err(err.into()))
+                }
+                Poll::Ready(None) => {
+                    this.done = true;
+                    Some(Err(IncompleteBody.into()))
+                }
+                Poll::Pending => return Poll::Pending,
+            };
+
+            return Poll::Ready(res);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io;
+
+    use actix_utils::future::poll_fn;
+
+    use super::*;
+    use crate::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn streams_chunks() {
+        let (tx, rx) = channel::<io::Error>(1);
+
+        actix_rt::spawn(async move {
+            tx.send(Bytes::from_static(b"1")).await.unwrap();
+            tx.send(Bytes::new()).await.unwrap();
+            tx.send(Bytes::from_static(b"23")).await.unwrap();
+            tx.close(Ok(())).await.unwrap();
+        });
+
+        assert_eq!(to_bytes(rx).await.unwrap(), Bytes::from_static(b"123"));
+    }
+
+    #[actix_rt::test]
+    async fn fails_when_senders_dropped() {
+        let (tx, mut rx) = channel::<io::Error>(2);
+
+        tx.send(Bytes::from_static(b"1")).await.unwrap();
+        let tx2 = tx.clone();
+        drop(tx);
+        drop(tx2);
+
+        let chunk = poll_fn(|cx| Pin::new(&mut rx).poll_next(cx)).await;
+        assert_eq!(chunk.unwrap().unwrap(), Bytes::from_static(b"1"));
+
+        let err = poll_fn(|cx| Pin::new(&mut rx).poll_next(cx)).await;
+        assert!(err.unwrap().unwrap_err().is::<IncompleteBody>());
+
+        let end = poll_fn(|cx| Pin::new(&mut rx).poll_next(cx)).await;
+        assert!(end.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn close_with_error() {
+        let (tx, rx) = channel(2);
+
+        tx.send(Bytes::from_static(b"1")).await.unwrap();
+        tx.close(Err(io::Error::new(io::ErrorKind::Other, "producer failed")))
+            .await
+            .unwrap();
+
+        assert!(to_bytes(rx).await.is_err());
+    }
+
+    #[actix_rt::test]
+    async fn backpressure() {
+        let (tx, rx) = channel::<io::Error>(1);
+
+        tx.try_send(Bytes::from_static(b"1")).unwrap();
+        assert_eq!(tx.try_send(Bytes::from_static(b"2")).unwrap_err(), "2");
+
+        drop(rx);
+        assert!(tx.is_closed());
+        assert!(tx.send(Bytes::from_static(b"3")).await.is_err());
+    }
+}
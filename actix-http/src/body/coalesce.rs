@@ -0,0 +1,88 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::{Bytes, BytesMut};
+use futures_core::Stream;
+
+/// Chunk coalescing state shared by streaming body types.
+///
+/// Small chunks that are immediately available from the underlying stream are merged into a
+/// buffer until it reaches the preferred chunk size. Buffered data is yielded as soon as the
+/// stream is not ready, so coalescing never delays data that the stream is waiting on.
+#[derive(Debug, Default)]
+pub(super) struct Coalesce {
+    chunk_size: usize,
+    buf: BytesMut,
+    done: bool,
+}
+
+impl Coalesce {
+    pub(super) fn new(chunk_size: usize) -> Self {
+        Coalesce {
+            chunk_size,
+            buf: BytesMut::new(),
+            done: false,
+        }
+    }
+
+    /// Poll next chunk from `stream`, skipping empty chunks and merging small ones.
+    ///
+    /// Errors are returned immediately; any data buffered at that point is discarded.
+    pub(super) fn poll_next<S, E>(
+        &mut self,
+        mut stream: Pin<&mut S>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, E>>>
+    where
+        S: Stream<Item = Result<Bytes, E>>,
+    {
+        if self.done {
+            return Poll::Ready(None);
+        }
+
+        loop {
+            match stream.as_mut().poll_next(cx) {
+                Poll::Ready(Some(Ok(bytes))) if bytes.is_empty() => continue,
+
+                Poll::Ready(Some(Ok(bytes))) => {
+                    if self.buf.is_empty() && bytes.len() >= self.chunk_size {
+                        return Poll::Ready(Some(Ok(bytes)));
+                    }
+
+                    self.buf.extend_from_slice(&bytes);
+
+                    if self.buf.len() >= self.chunk_size {
+                        return Poll::Ready(Some(Ok(self.buf.split().freeze())));
+                    }
+                }
+
+                Poll::Ready(Some(Err(err))) => {
+                    self.buf.clear();
+                    return Poll::Ready(Some(Err(err)));
+                }
+
+                Poll::Ready(None) => {
+                    self.done = true;
+                    return Poll::Ready(self.flush().map(Ok));
+                }
+
+                Poll::Pending => {
+                    return match self.flush() {
+                        Some(bytes) => Poll::Ready(Some(Ok(bytes))),
+                        None => Poll::Pending,
+                    };
+                }
+            }
+        }
+    }
+
+    fn flush(&mut self) -> Option<Bytes> {
+        if self.buf.is_empty() {
+            None
+        } else {
+            Some(self.buf.split().freeze())
+        }
+    }
+}
@@ -0,0 +1,128 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project::pin_project;
+
+use crate::{error::Error, header::HeaderMap};
+
+use super::{Body, BodySize, MessageBody};
+
+/// Body that is one of two body types, without boxing either of them.
+///
+/// Useful for services and middleware that usually respond with an inner body type but sometimes
+/// substitute their own, e.g. an error response. Errors of both bodies are converted into
+/// [`Error`].
+///
+/// ```
+/// use actix_http::body::{Body, EitherBody};
+///
+/// fn respond(authorized: bool, body: String) -> EitherBody<String> {
+///     if authorized {
+///         EitherBody::new(body)
+///     } else {
+///         EitherBody::right(Body::from("unauthorized"))
+///     }
+/// }
+/// ```
+#[pin_project(project = EitherBodyProj)]
+#[derive(Debug, Clone)]
+pub enum EitherBody<L, R = Body> {
+    /// A body of type `L`.
+    Left(#[pin] L),
+
+    /// A body of type `R`.
+    Right(#[pin] R),
+}
+
+impl<L> EitherBody<L, Body> {
+    /// Create left variant with default right type.
+    pub fn new(body: L) -> Self {
+        EitherBody::Left(body)
+    }
+}
+
+impl<L, R> EitherBody<L, R> {
+    /// Create left variant.
+    pub fn left(body: L) -> Self {
+        EitherBody::Left(body)
+    }
+
+    /// Create right variant.
+    pub fn right(body: R) -> Self {
+        EitherBody::Right(body)
+    }
+}
+
+impl<L, R> MessageBody for EitherBody<L, R>
+where
+    L: MessageBody,
+    L::Error: Into<Error>,
+    R: MessageBody,
+    R::Error: Into<Error>,
+{
+    type Error = Error;
+
+    fn size(&self) -> BodySize {
+        match self {
+            EitherBody::Left(ref body) => body.size(),
+            EitherBody::Right(ref body) => body.size(),
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        // TODO: MSRV 1.51: poll_map_err
+        let item = match self.project() {
+            EitherBodyProj::Left(body) => {
+                ready!(body.poll_next(cx)).map(|res| res.map_err(Into::into))
+            }
+            EitherBodyProj::Right(body) => {
+                ready!(body.poll_next(cx)).map(|res| res.map_err(Into::into))
+            }
+        };
+
+        Poll::Ready(item)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let res = match self.project() {
+            EitherBodyProj::Left(body) => {
+                ready!(body.poll_trailers(cx)).map_err(Into::into)
+            }
+            EitherBodyProj::Right(body) => {
+                ready!(body.poll_trailers(cx)).map_err(Into::into)
+            }
+        };
+
+        Poll::Ready(res)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn test_either_body() {
+        let body = EitherBody::new("left");
+        assert_eq!(body.size(), BodySize::Sized(4));
+        assert_eq!(to_bytes(body).await.unwrap(), "left");
+
+        let body = EitherBody::<&str>::right(Body::Empty);
+        assert_eq!(body.size(), BodySize::Empty);
+        assert!(to_bytes(body).await.unwrap().is_empty());
+
+        let body = EitherBody::<String, Bytes>::right(Bytes::from_static(b"right"));
+        assert_eq!(to_bytes(body).await.unwrap(), "right");
+    }
+}
@@ -0,0 +1,273 @@
+use std::{
+    error::Error as StdError,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+use pin_project_lite::pin_project;
+
+use crate::header::HeaderMap;
+
+use super::{BodySize, MessageBody};
+
+/// A frame of a body or payload: a chunk of data or the trailer fields ending it.
+#[derive(Debug, Clone)]
+pub enum Frame {
+    /// Chunk of data.
+    Data(Bytes),
+
+    /// Trailer fields, sent after all data.
+    Trailers(HeaderMap),
+}
+
+impl Frame {
+    /// Returns true if this is a data frame.
+    pub fn is_data(&self) -> bool {
+        matches!(self, Frame::Data(_))
+    }
+
+    /// Returns true if this is a trailers frame.
+    pub fn is_trailers(&self) -> bool {
+        matches!(self, Frame::Trailers(_))
+    }
+
+    /// Returns data of a data frame.
+    pub fn data_ref(&self) -> Option<&Bytes> {
+        match self {
+            Frame::Data(data) => Some(data),
+            Frame::Trailers(_) => None,
+        }
+    }
+
+    /// Converts into data of a data frame.
+    pub fn into_data(self) -> Result<Bytes, Self> {
+        match self {
+            Frame::Data(data) => Ok(data),
+            frame => Err(frame),
+        }
+    }
+
+    /// Converts into trailer fields of a trailers frame.
+    pub fn into_trailers(self) -> Result<HeaderMap, Self> {
+        match self {
+            Frame::Trailers(trailers) => Ok(trailers),
+            frame => Err(frame),
+        }
+    }
+}
+
+impl From<Bytes> for Frame {
+    fn from(data: Bytes) -> Self {
+        Frame::Data(data)
+    }
+}
+
+impl From<HeaderMap> for Frame {
+    fn from(trailers: HeaderMap) -> Self {
+        Frame::Trailers(trailers)
+    }
+}
+
+pin_project! {
+    /// Body produced by a stream of [`Frame`]s.
+    ///
+    /// Data frames are streamed with chunked transfer encoding. A trailers frame ends the body and
+    /// is sent as its trailer fields; frames after it are not polled.
+    ///
+    /// ```
+    /// use actix_http::{body::{Frame, FrameBody}, header::HeaderMap};
+    /// use bytes::Bytes;
+    /// use futures_util::stream;
+    ///
+    /// let frames = stream::iter(vec![
+    ///     Ok::<_, std::io::Error>(Frame::Data(Bytes::from_static(b"message"))),
+    ///     Ok(Frame::Trailers(HeaderMap::new())),
+    /// ]);
+    /// let body = FrameBody::new(frames);
+    /// ```
+    pub struct FrameBody<S> {
+        #[pin]
+        stream: S,
+        trailers: Option<HeaderMap>,
+        done: bool,
+    }
+}
+
+impl<S, E> FrameBody<S>
+where
+    S: Stream<Item = Result<Frame, E>>,
+    E: Into<Box<dyn StdError>> + 'static,
+{
+    /// Create body from stream of frames.
+    pub fn new(stream: S) -> Self {
+        FrameBody {
+            stream,
+            trailers: None,
+            done: false,
+        }
+    }
+}
+
+impl<S, E> MessageBody for FrameBody<S>
+where
+    S: Stream<Item = Result<Frame, E>>,
+    E: Into<Box<dyn StdError>> + 'static,
+{
+    type Error = E;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let mut this = self.project();
+
+        loop {
+            if *this.done {
+                return Poll::Ready(None);
+            }
+
+            match ready!(this.stream.as_mut().poll_next(cx)) {
+                // empty chunks would end chunked bodies
+                Some(Ok(Frame::Data(data))) if data.is_empty() => continue,
+                Some(Ok(Frame::Data(data))) => return Poll::Ready(Some(Ok(data))),
+                Some(Ok(Frame::Trailers(trailers))) => {
+                    *this.trailers = Some(trailers);
+                    *this.done = true;
+                }
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => *this.done = true,
+            }
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(self.project().trailers.take()))
+    }
+}
+
+pin_project! {
+    /// Stream of the [`Frame`]s of a body, ending with a trailers frame if the body has trailers.
+    pub struct Frames<B> {
+        #[pin]
+        body: B,
+        data_done: bool,
+        done: bool,
+    }
+}
+
+impl<B: MessageBody> Frames<B> {
+    /// Create stream of frames of body.
+    pub fn new(body: B) -> Self {
+        Frames {
+            body,
+            data_done: false,
+            done: false,
+        }
+    }
+
+    /// Returns size of the wrapped body.
+    pub fn size(&self) -> BodySize {
+        self.body.size()
+    }
+}
+
+impl<B: MessageBody> Stream for Frames<B> {
+    type Item = Result<Frame, B::Error>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let mut this = self.project();
+
+        if *this.done {
+            return Poll::Ready(None);
+        }
+
+        if !*this.data_done {
+            match ready!(this.body.as_mut().poll_next(cx)) {
+                Some(Ok(data)) => return Poll::Ready(Some(Ok(Frame::Data(data)))),
+                Some(Err(err)) => {
+                    *this.done = true;
+                    return Poll::Ready(Some(Err(err)));
+                }
+                None => *this.data_done = true,
+            }
+        }
+
+        let res = ready!(this.body.poll_trailers(cx));
+        *this.done = true;
+
+        match res {
+            Ok(Some(trailers)) => Poll::Ready(Some(Ok(Frame::Trailers(trailers)))),
+            Ok(None) => Poll::Ready(None),
+            Err(err) => Poll::Ready(Some(Err(err))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::convert::Infallible;
+
+    use futures_util::stream::{self, StreamExt as _};
+
+    use super::*;
+    use crate::header::{HeaderName, HeaderValue};
+
+    fn trailers() -> HeaderMap {
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            HeaderName::from_static("grpc-status"),
+            HeaderValue::from_static("0"),
+        );
+        trailers
+    }
+
+    #[actix_rt::test]
+    async fn test_frame_body_round_trip() {
+        let frames = stream::iter(vec![
+            Ok::<_, Infallible>(Frame::Data(Bytes::from_static(b"abc"))),
+            Ok(Frame::Data(Bytes::new())),
+            Ok(Frame::Data(Bytes::from_static(b"def"))),
+            Ok(Frame::Trailers(trailers())),
+            Ok(Frame::Data(Bytes::from_static(b"ignored"))),
+        ]);
+
+        let body = FrameBody::new(frames);
+        assert_eq!(body.size(), BodySize::Stream);
+
+        let mut frames = Frames::new(body);
+        let frame = frames.next().await.unwrap().unwrap();
+        assert_eq!(frame.data_ref().unwrap(), "abc");
+        let frame = frames.next().await.unwrap().unwrap();
+        assert_eq!(frame.data_ref().unwrap(), "def");
+
+        let frame = frames.next().await.unwrap().unwrap();
+        assert!(frame.is_trailers());
+        let trailers = frame.into_trailers().unwrap();
+        assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+
+        assert!(frames.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_frames_without_trailers() {
+        let mut frames = Frames::new("hello");
+        assert_eq!(
+            frames.next().await.unwrap().unwrap().into_data().unwrap(),
+            "hello"
+        );
+        assert!(frames.next().await.is_none());
+        assert!(frames.next().await.is_none());
+    }
+}
@@ -0,0 +1,201 @@
+use std::{
+    pin::Pin,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use bytes::Bytes;
+use futures_core::ready;
+use pin_project::{pin_project, pinned_drop};
+
+use crate::header::HeaderMap;
+
+use super::{BodySize, MessageBody};
+
+/// How a body wrapped in [`Instrumented`] ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BodyOutcome {
+    /// Body was streamed to the end.
+    Completed,
+
+    /// Body produced an error.
+    Failed,
+
+    /// Body was dropped before it ended, e.g. because the peer disconnected.
+    Cancelled,
+}
+
+/// Statistics of a body wrapped in [`Instrumented`], passed to its callback once it ends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct BodyStats {
+    /// Number of bytes produced by the body.
+    pub bytes: u64,
+
+    /// Time from wrapping the body until it produced its first chunk, if it produced any.
+    pub first_chunk: Option<Duration>,
+
+    /// Time from wrapping the body until it ended.
+    pub duration: Duration,
+
+    /// How the body ended.
+    pub outcome: BodyOutcome,
+}
+
+/// Body counting the bytes produced by the wrapped body and timing it.
+///
+/// The callback is called exactly once, when the body ends, fails or is dropped, so access logs and
+/// metrics can report the actual size of streaming responses.
+///
+/// ```
+/// use actix_http::body::{BodyOutcome, Instrumented};
+///
+/// let body = Instrumented::new("hello world", |stats| {
+///     if stats.outcome == BodyOutcome::Completed {
+///         log::info!("sent {} bytes in {:?}", stats.bytes, stats.duration);
+///     }
+/// });
+/// ```
+#[pin_project(PinnedDrop)]
+pub struct Instrumented<B, F>
+where
+    F: FnOnce(&BodyStats),
+{
+    #[pin]
+    body: B,
+    on_end: Option<F>,
+    bytes: u64,
+    start: Instant,
+    first_chunk: Option<Duration>,
+}
+
+impl<B, F> Instrumented<B, F>
+where
+    B: MessageBody,
+    F: FnOnce(&BodyStats),
+{
+    /// Wrap body, calling `on_end` with its statistics once it ends.
+    pub fn new(body: B, on_end: F) -> Self {
+        Instrumented {
+            body,
+            on_end: Some(on_end),
+            bytes: 0,
+            start: Instant::now(),
+            first_chunk: None,
+        }
+    }
+}
+
+impl<B, F> Instrumented<B, F>
+where
+    F: FnOnce(&BodyStats),
+{
+    fn finish(self: Pin<&mut Self>, outcome: BodyOutcome) {
+        let this = self.project();
+
+        if let Some(on_end) = this.on_end.take() {
+            on_end(&BodyStats {
+                bytes: *this.bytes,
+                first_chunk: *this.first_chunk,
+                duration: this.start.elapsed(),
+                outcome,
+            });
+        }
+    }
+}
+
+impl<B, F> MessageBody for Instrumented<B, F>
+where
+    B: MessageBody,
+    F: FnOnce(&BodyStats),
+{
+    type Error = B::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.as_mut().project();
+
+        match ready!(this.body.poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                if this.first_chunk.is_none() {
+                    *this.first_chunk = Some(this.start.elapsed());
+                }
+                *this.bytes += chunk.len() as u64;
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Some(Err(err)) => {
+                self.finish(BodyOutcome::Failed);
+                Poll::Ready(Some(Err(err)))
+            }
+            // trailers may still follow, but all bytes are produced
+            None => {
+                self.finish(BodyOutcome::Completed);
+                Poll::Ready(None)
+            }
+        }
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.project().body.poll_trailers(cx)
+    }
+}
+
+#[pinned_drop]
+impl<B, F> PinnedDrop for Instrumented<B, F>
+where
+    F: FnOnce(&BodyStats),
+{
+    fn drop(self: Pin<&mut Self>) {
+        self.finish(BodyOutcome::Cancelled);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use actix_utils::future::poll_fn;
+
+    use super::*;
+    use crate::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn test_instrumented() {
+        let stats = Rc::new(Cell::new(None));
+
+        let stats2 = stats.clone();
+        let body = Instrumented::new("hello", move |s: &BodyStats| stats2.set(Some(*s)));
+        assert_eq!(body.size(), BodySize::Sized(5));
+        assert_eq!(to_bytes(body).await.unwrap(), "hello");
+
+        let s = stats.take().unwrap();
+        assert_eq!(s.bytes, 5);
+        assert!(s.first_chunk.is_some());
+        assert_eq!(s.outcome, BodyOutcome::Completed);
+
+        // dropped before the end
+        let stats2 = stats.clone();
+        let body =
+            Instrumented::new(vec![b'x'; 10], move |s: &BodyStats| stats2.set(Some(*s)));
+        let mut body = Box::pin(body);
+        poll_fn(|cx| body.as_mut().poll_next(cx))
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(stats.get().is_none());
+        drop(body);
+
+        let s = stats.take().unwrap();
+        assert_eq!(s.bytes, 10);
+        assert_eq!(s.outcome, BodyOutcome::Cancelled);
+    }
+}
@@ -2,6 +2,7 @@
 
 use std::{
     convert::Infallible,
+    error::Error as StdError,
     mem,
     pin::Pin,
     task::{Context, Poll},
@@ -11,9 +12,9 @@ use bytes::{Bytes, BytesMut};
 use futures_core::ready;
 use pin_project_lite::pin_project;
 
-use crate::error::Error;
+use crate::header::HeaderMap;
 
-use super::BodySize;
+use super::{Body, BodySize};
 
 /// An interface for response bodies.
 pub trait MessageBody {
@@ -27,8 +28,70 @@ pub trait MessageBody {
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Bytes, Self::Error>>>;
+
+    /// Attempt to pull out trailer fields sent after the body.
+    ///
+    /// Called once [`poll_next`](Self::poll_next) returned `None`. Trailers are sent with chunked
+    /// transfer encoding over HTTP/1 and as a trailing `HEADERS` frame over HTTP/2; they are dropped
+    /// for bodies of known size over HTTP/1. Servers only send response trailers to clients that
+    /// signalled support with `TE: trailers`; announcing them up front with a
+    /// [`Trailer`](crate::header::Trailer) header is left to the response. Returns no trailers by
+    /// default.
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(Ok(None))
+    }
 }
 
+/// Combinators for [`MessageBody`] types.
+pub trait MessageBodyExt: MessageBody {
+    /// Converts the first error produced by the body using given function.
+    ///
+    /// The body is expected to end after an error, so the function is called at most once.
+    fn map_err<F, E>(self, f: F) -> MessageBodyMapErr<Self, F>
+    where
+        Self: Sized,
+        F: FnOnce(Self::Error) -> E,
+    {
+        MessageBodyMapErr::new(self, f)
+    }
+
+    /// Boxes the body into [`Body::Message`](super::Body::Message), boxing its errors too.
+    fn boxed(self) -> Body
+    where
+        Self: Sized + 'static,
+        Self::Error: Into<Box<dyn StdError + 'static>>,
+    {
+        Body::from_message(self)
+    }
+
+    /// Calls given function with a reference to every chunk produced by the body.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_http::body::{to_bytes, MessageBodyExt as _};
+    ///
+    /// # async fn test_inspect() {
+    /// let mut len = 0;
+    /// let body = "hello world".inspect(|chunk| len += chunk.len());
+    ///
+    /// to_bytes(body).await.unwrap();
+    /// assert_eq!(len, 11);
+    /// # }
+    /// ```
+    fn inspect<F>(self, f: F) -> Inspect<Self, F>
+    where
+        Self: Sized,
+        F: FnMut(&Bytes),
+    {
+        Inspect { body: self, f }
+    }
+}
+
+impl<B: MessageBody> MessageBodyExt for B {}
+
 impl MessageBody for () {
     type Error = Infallible;
 
@@ -46,8 +109,7 @@ impl MessageBody for () {
 
 impl<B> MessageBody for Box<B>
 where
-    B: MessageBody + Unpin,
-    B::Error: Into<Error>,
+    B: MessageBody + Unpin + ?Sized,
 {
     type Error = B::Error;
 
@@ -61,12 +123,18 @@ where
     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
         Pin::new(self.get_mut().as_mut()).poll_next(cx)
     }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Pin::new(self.get_mut().as_mut()).poll_trailers(cx)
+    }
 }
 
 impl<B> MessageBody for Pin<Box<B>>
 where
-    B: MessageBody,
-    B::Error: Into<Error>,
+    B: MessageBody + ?Sized,
 {
     type Error = B::Error;
 
@@ -75,10 +143,17 @@ where
     }
 
     fn poll_next(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
-        self.as_mut().poll_next(cx)
+        self.get_mut().as_mut().poll_next(cx)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.get_mut().as_mut().poll_trailers(cx)
     }
 }
 
@@ -182,7 +257,8 @@ impl MessageBody for String {
 }
 
 pin_project! {
-    pub(crate) struct MessageBodyMapErr<B, F> {
+    /// Body converting errors of the wrapped body, created by [`MessageBodyExt::map_err`].
+    pub struct MessageBodyMapErr<B, F> {
         #[pin]
         body: B,
         mapper: Option<F>,
@@ -229,4 +305,63 @@ where
             None => Poll::Ready(None),
         }
     }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        let this = self.project();
+
+        match ready!(this.body.poll_trailers(cx)) {
+            Ok(trailers) => Poll::Ready(Ok(trailers)),
+            // the body already ended with a mapped error; there are no trailers after it
+            Err(err) => match this.mapper.take() {
+                Some(f) => Poll::Ready(Err((f)(err))),
+                None => Poll::Ready(Ok(None)),
+            },
+        }
+    }
+}
+
+pin_project! {
+    /// Body calling a function with every chunk of the wrapped body, created by
+    /// [`MessageBodyExt::inspect`].
+    pub struct Inspect<B, F> {
+        #[pin]
+        body: B,
+        f: F,
+    }
+}
+
+impl<B, F> MessageBody for Inspect<B, F>
+where
+    B: MessageBody,
+    F: FnMut(&Bytes),
+{
+    type Error = B::Error;
+
+    fn size(&self) -> BodySize {
+        self.body.size()
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.project();
+
+        let item = ready!(this.body.poll_next(cx));
+        if let Some(Ok(ref chunk)) = item {
+            (this.f)(chunk);
+        }
+
+        Poll::Ready(item)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.project().body.poll_trailers(cx)
+    }
 }
@@ -10,15 +10,25 @@ use futures_core::ready;
 #[allow(clippy::module_inception)]
 mod body;
 mod body_stream;
+mod channel;
+mod coalesce;
+mod either;
+mod frame;
+mod instrumented;
 mod message_body;
+mod reader;
 mod response_body;
 mod size;
 mod sized_stream;
 
 pub use self::body::{AnyBody, Body, BoxAnyBody};
-pub use self::body_stream::BodyStream;
-pub use self::message_body::MessageBody;
-pub(crate) use self::message_body::MessageBodyMapErr;
+pub use self::body_stream::{BodyStream, TryStreamMap};
+pub use self::channel::{channel, IncompleteBody, Receiver, SendError, Sender};
+pub use self::either::EitherBody;
+pub use self::frame::{Frame, FrameBody, Frames};
+pub use self::instrumented::{BodyOutcome, BodyStats, Instrumented};
+pub use self::message_body::{Inspect, MessageBody, MessageBodyExt, MessageBodyMapErr};
+pub use self::reader::{from_read, ReaderBody};
 pub use self::response_body::ResponseBody;
 pub use self::size::BodySize;
 pub use self::sized_stream::SizedStream;
@@ -69,7 +79,7 @@ pub async fn to_bytes<B: MessageBody>(body: B) -> Result<Bytes, B::Error> {
 
 #[cfg(test)]
 mod tests {
-    use std::pin::Pin;
+    use std::{convert::Infallible, pin::Pin};
 
     use actix_rt::pin;
     use actix_utils::future::poll_fn;
@@ -260,4 +270,38 @@ mod tests {
         let bytes = to_bytes(body).await.unwrap();
         assert_eq!(bytes, b"123"[..]);
     }
+
+    #[actix_rt::test]
+    async fn test_combinators() {
+        let mut chunks = Vec::new();
+        let body =
+            Bytes::from_static(b"abc").inspect(|chunk| chunks.push(chunk.clone()));
+        assert_eq!(body.size(), BodySize::Sized(3));
+        assert_eq!(to_bytes(body).await.unwrap(), "abc");
+        assert_eq!(chunks, vec![Bytes::from_static(b"abc")]);
+
+        let stream = futures_util::stream::iter(vec![
+            Ok(Bytes::from_static(b"abc")),
+            Err(std::io::Error::new(std::io::ErrorKind::Other, "oops")),
+        ]);
+        let body = BodyStream::new(stream).map_err(|err| err.to_string());
+        assert_eq!(to_bytes(body).await.unwrap_err(), "oops");
+
+        let body = "boxed".boxed();
+        assert!(matches!(body, Body::Message(_)));
+        assert_eq!(body.size(), BodySize::Sized(5));
+        assert_eq!(to_bytes(body).await.unwrap(), "boxed");
+    }
+
+    #[actix_rt::test]
+    async fn test_infallible_boxed_bodies() {
+        let body: Box<dyn MessageBody<Error = Infallible> + Unpin> = Box::new("boxed");
+        assert_eq!(body.size(), BodySize::Sized(5));
+        assert_eq!(to_bytes(body).await.unwrap(), "boxed");
+
+        let body: Pin<Box<dyn MessageBody<Error = Infallible>>> =
+            Box::pin(Bytes::from_static(b"pinned"));
+        assert_eq!(body.size(), BodySize::Sized(6));
+        assert_eq!(to_bytes(body).await.unwrap(), "pinned");
+    }
 }
@@ -0,0 +1,133 @@
+use std::{
+    io,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use actix_codec::{AsyncRead, ReadBuf};
+use bytes::{Bytes, BytesMut};
+use futures_core::ready;
+
+use super::{BodySize, MessageBody};
+
+/// Create body streaming from an [`AsyncRead`], e.g. a file, pipe or child process' stdout, in
+/// chunks of up to `chunk_size` bytes.
+///
+/// The length of the body is unknown, so it is sent with chunked transfer encoding. Use
+/// [`ReaderBody::new`] for readers of known length.
+///
+/// ```
+/// use actix_http::{body, Response};
+///
+/// let res = Response::ok().set_body(body::from_read(&b"hello world"[..], 4096));
+/// ```
+pub fn from_read<R: AsyncRead + Unpin>(reader: R, chunk_size: usize) -> ReaderBody<R> {
+    ReaderBody::new(reader, None).chunk_size(chunk_size)
+}
+
+/// Body read from an [`AsyncRead`] without buffering it in memory.
+///
+/// With a known length, data is sent as-is like [`SizedStream`](super::SizedStream); otherwise
+/// chunked transfer encoding is used like [`BodyStream`](super::BodyStream). The reader is read in
+/// chunks of up to 8 KiB by default until it reaches EOF.
+pub struct ReaderBody<R> {
+    reader: Option<R>,
+    len: Option<u64>,
+    chunk_size: usize,
+    buf: BytesMut,
+}
+
+impl<R: AsyncRead + Unpin> ReaderBody<R> {
+    const CHUNK_SIZE: usize = 8192;
+
+    /// Create body from reader producing `len` bytes, or an unknown number of bytes if `None`.
+    pub fn new(reader: R, len: Option<u64>) -> Self {
+        ReaderBody {
+            reader: Some(reader),
+            len,
+            chunk_size: Self::CHUNK_SIZE,
+            buf: BytesMut::new(),
+        }
+    }
+
+    /// Set maximum size of chunks read from the reader. Zero is treated as one byte.
+    pub fn chunk_size(mut self, chunk_size: usize) -> Self {
+        self.chunk_size = chunk_size.max(1);
+        self
+    }
+}
+
+impl<R: AsyncRead + Unpin> MessageBody for ReaderBody<R> {
+    type Error = io::Error;
+
+    fn size(&self) -> BodySize {
+        match self.len {
+            Some(len) => BodySize::Sized(len),
+            None => BodySize::Stream,
+        }
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        let reader = match this.reader {
+            Some(ref mut reader) => reader,
+            None => return Poll::Ready(None),
+        };
+
+        this.buf.resize(this.chunk_size, 0);
+        let mut buf = ReadBuf::new(&mut this.buf);
+
+        match ready!(Pin::new(reader).poll_read(cx, &mut buf)) {
+            Ok(()) if buf.filled().is_empty() => {
+                this.reader = None;
+                Poll::Ready(None)
+            }
+            Ok(()) => {
+                let n = buf.filled().len();
+                Poll::Ready(Some(Ok(this.buf.split_to(n).freeze())))
+            }
+            Err(err) => {
+                this.reader = None;
+                Poll::Ready(Some(Err(err)))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn test_reader_body() {
+        let data = vec![b'x'; 20_000];
+
+        let body = ReaderBody::new(&data[..], Some(20_000));
+        assert_eq!(body.size(), BodySize::Sized(20_000));
+        assert_eq!(to_bytes(body).await.unwrap(), data);
+
+        let body = ReaderBody::new(&b""[..], None);
+        assert_eq!(body.size(), BodySize::Stream);
+        assert!(to_bytes(body).await.unwrap().is_empty());
+    }
+
+    #[actix_rt::test]
+    async fn test_from_read() {
+        let body = from_read(&b"hello world"[..], 4);
+        assert_eq!(body.size(), BodySize::Stream);
+        actix_rt::pin!(body);
+
+        let mut chunks = Vec::new();
+        while let Some(chunk) =
+            actix_utils::future::poll_fn(|cx| body.as_mut().poll_next(cx)).await
+        {
+            chunks.push(chunk.unwrap());
+        }
+        assert_eq!(chunks, vec!["hell", "o wo", "rld"]);
+    }
+}
@@ -8,7 +8,7 @@ use bytes::Bytes;
 use futures_core::{ready, Stream};
 use pin_project::pin_project;
 
-use crate::error::Error;
+use crate::{error::Error, header::HeaderMap};
 
 use super::{Body, BodySize, MessageBody};
 
@@ -63,6 +63,18 @@ where
     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
         Stream::poll_next(self, cx)
     }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        match self.project() {
+            ResponseBodyProj::Body(body) => {
+                Poll::Ready(ready!(body.poll_trailers(cx)).map_err(Into::into))
+            }
+            ResponseBodyProj::Other(body) => Pin::new(body).poll_trailers(cx),
+        }
+    }
 }
 
 impl<B> Stream for ResponseBody<B>
@@ -5,10 +5,10 @@ use std::{
 };
 
 use bytes::Bytes;
-use futures_core::{ready, Stream};
+use futures_core::Stream;
 use pin_project_lite::pin_project;
 
-use super::{BodySize, MessageBody};
+use super::{coalesce::Coalesce, BodySize, MessageBody};
 
 pin_project! {
     /// Known sized streaming response wrapper.
@@ -19,6 +19,7 @@ pin_project! {
         size: u64,
         #[pin]
         stream: S,
+        coalesce: Coalesce,
     }
 }
 
@@ -28,7 +29,21 @@ where
     E: Into<Box<dyn StdError>> + 'static,
 {
     pub fn new(size: u64, stream: S) -> Self {
-        SizedStream { size, stream }
+        SizedStream {
+            size,
+            stream,
+            coalesce: Coalesce::default(),
+        }
+    }
+}
+
+impl<S> SizedStream<S> {
+    /// Merge chunks that are immediately available until they total at least `chunk_size` bytes.
+    ///
+    /// See [`BodyStream::coalesce`](super::BodyStream::coalesce). Zero disables coalescing.
+    pub fn coalesce(mut self, chunk_size: usize) -> Self {
+        self.coalesce = Coalesce::new(chunk_size);
+        self
     }
 }
 
@@ -49,19 +64,11 @@ where
     /// ended on a zero-length chunk, but rather proceed until the underlying
     /// [`Stream`] ends.
     fn poll_next(
-        mut self: Pin<&mut Self>,
+        self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
-        loop {
-            let stream = self.as_mut().project().stream;
-
-            let chunk = match ready!(stream.poll_next(cx)) {
-                Some(Ok(ref bytes)) if bytes.is_empty() => continue,
-                val => val,
-            };
-
-            return Poll::Ready(chunk);
-        }
+        let this = self.project();
+        this.coalesce.poll_next(this.stream, cx)
     }
 }
 
@@ -119,4 +126,35 @@ mod tests {
 
         assert_eq!(to_bytes(body).await.ok(), Some(Bytes::from("12")));
     }
+
+    #[actix_rt::test]
+    async fn coalesce_chunks() {
+        let body = SizedStream::new(
+            6,
+            stream::iter(
+                ["1", "22", "", "333"]
+                    .iter()
+                    .map(|&v| Ok::<_, Infallible>(Bytes::from(v))),
+            ),
+        )
+        .coalesce(3);
+
+        pin!(body);
+
+        assert_eq!(
+            poll_fn(|cx| body.as_mut().poll_next(cx))
+                .await
+                .unwrap()
+                .ok(),
+            Some(Bytes::from("122")),
+        );
+
+        assert_eq!(
+            poll_fn(|cx| body.as_mut().poll_next(cx))
+                .await
+                .unwrap()
+                .ok(),
+            Some(Bytes::from("333")),
+        );
+    }
 }
@@ -5,11 +5,14 @@ use actix_service::{IntoServiceFactory, Service, ServiceFactory};
 
 use crate::{
     body::{AnyBody, MessageBody},
-    config::{KeepAlive, ServiceConfig},
+    config::{
+        Admission, BodyLengthMismatch, CrlfHandling, DuplicateHeaders, Http10Expect,
+        KeepAlive, ServiceConfig,
+    },
     h1::{self, ExpectHandler, H1Service, UpgradeHandler},
     h2::H2Service,
     service::HttpService,
-    ConnectCallback, Extensions, Request, Response,
+    ConnectCallback, Extensions, Fifo, Prioritizer, Request, RequestHead, Response,
 };
 
 /// A HTTP service builder
@@ -22,6 +25,18 @@ pub struct HttpServiceBuilder<T, S, X = ExpectHandler, U = UpgradeHandler> {
     client_disconnect: u64,
     secure: bool,
     local_addr: Option<net::SocketAddr>,
+    max_in_flight: usize,
+    retry_after: u64,
+    on_overload: Option<Rc<dyn Fn(&RequestHead)>>,
+    upgrade_predicate: Option<Rc<dyn Fn(&RequestHead) -> bool>>,
+    http10_expect: Http10Expect,
+    h2_sanitize_headers: bool,
+    body_poll_timeout: u64,
+    default_charset: bool,
+    crlf_handling: CrlfHandling,
+    duplicate_headers: DuplicateHeaders,
+    body_length_mismatch: BodyLengthMismatch,
+    prioritizer: Rc<dyn Prioritizer>,
     expect: X,
     upgrade: Option<U>,
     on_connect_ext: Option<Rc<ConnectCallback<T>>>,
@@ -43,6 +58,18 @@ where
             client_disconnect: 0,
             secure: false,
             local_addr: None,
+            max_in_flight: 0,
+            retry_after: 1,
+            on_overload: None,
+            upgrade_predicate: None,
+            http10_expect: Http10Expect::default(),
+            h2_sanitize_headers: true,
+            body_poll_timeout: 0,
+            default_charset: false,
+            crlf_handling: CrlfHandling::default(),
+            duplicate_headers: DuplicateHeaders::default(),
+            body_length_mismatch: BodyLengthMismatch::default(),
+            prioritizer: Rc::new(Fifo),
             expect: ExpectHandler,
             upgrade: None,
             on_connect_ext: None,
@@ -98,6 +125,21 @@ where
         self
     }
 
+    /// Set response body poll timeout in milliseconds.
+    ///
+    /// Defines how long a response body may stay pending without producing a chunk. If the
+    /// timeout expires, e.g. because a body stream never wakes its task, the error is logged
+    /// along with the request method and path, and the connection is closed. Only applies to
+    /// HTTP/1.x connections.
+    ///
+    /// To disable timeout set value to 0.
+    ///
+    /// By default body poll timeout is set to 0.
+    pub fn body_poll_timeout(mut self, val: u64) -> Self {
+        self.body_poll_timeout = val;
+        self
+    }
+
     /// Set server connection disconnect timeout in milliseconds.
     ///
     /// Defines a timeout for disconnect connection. If a disconnect procedure does not complete
@@ -111,6 +153,40 @@ where
         self
     }
 
+    /// Set maximum number of requests processed concurrently by each worker.
+    ///
+    /// Requests received while the limit is reached are not passed to the service and are
+    /// responded to with `503 Service Unavailable` and a `Retry-After` header instead.
+    ///
+    /// To disable limit set value to 0.
+    ///
+    /// By default there is no limit.
+    pub fn max_in_flight(mut self, val: usize) -> Self {
+        self.max_in_flight = val;
+        self
+    }
+
+    /// Set `Retry-After` value, in seconds, of responses to requests rejected due to the
+    /// [in-flight limit](Self::max_in_flight).
+    ///
+    /// By default retry after is set to 1 second.
+    pub fn overload_retry_after(mut self, secs: u64) -> Self {
+        self.retry_after = secs;
+        self
+    }
+
+    /// Sets the callback to be run for each request rejected due to the
+    /// [in-flight limit](Self::max_in_flight).
+    ///
+    /// Useful for recording overload metrics.
+    pub fn on_overload<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&RequestHead) + 'static,
+    {
+        self.on_overload = Some(Rc::new(f));
+        self
+    }
+
     /// Provide service for `EXPECT: 100-Continue` support.
     ///
     /// Service get called with request that contains `EXPECT` header.
@@ -129,6 +205,18 @@ where
             client_disconnect: self.client_disconnect,
             secure: self.secure,
             local_addr: self.local_addr,
+            max_in_flight: self.max_in_flight,
+            retry_after: self.retry_after,
+            on_overload: self.on_overload,
+            upgrade_predicate: self.upgrade_predicate,
+            http10_expect: self.http10_expect,
+            h2_sanitize_headers: self.h2_sanitize_headers,
+            body_poll_timeout: self.body_poll_timeout,
+            default_charset: self.default_charset,
+            crlf_handling: self.crlf_handling,
+            duplicate_headers: self.duplicate_headers,
+            body_length_mismatch: self.body_length_mismatch,
+            prioritizer: self.prioritizer,
             expect: expect.into_factory(),
             upgrade: self.upgrade,
             on_connect_ext: self.on_connect_ext,
@@ -153,6 +241,18 @@ where
             client_disconnect: self.client_disconnect,
             secure: self.secure,
             local_addr: self.local_addr,
+            max_in_flight: self.max_in_flight,
+            retry_after: self.retry_after,
+            on_overload: self.on_overload,
+            upgrade_predicate: self.upgrade_predicate,
+            http10_expect: self.http10_expect,
+            h2_sanitize_headers: self.h2_sanitize_headers,
+            body_poll_timeout: self.body_poll_timeout,
+            default_charset: self.default_charset,
+            crlf_handling: self.crlf_handling,
+            duplicate_headers: self.duplicate_headers,
+            body_length_mismatch: self.body_length_mismatch,
+            prioritizer: self.prioritizer,
             expect: self.expect,
             upgrade: Some(upgrade.into_factory()),
             on_connect_ext: self.on_connect_ext,
@@ -160,11 +260,106 @@ where
         }
     }
 
+    /// Sets the predicate that decides which requests are handed to the
+    /// [upgrade service](Self::upgrade).
+    ///
+    /// By default, only requests with an `Upgrade: websocket` header and `CONNECT` requests are
+    /// handed to the upgrade service. Setting a predicate replaces this check, allowing other
+    /// upgrade-style protocols (e.g. `Upgrade: custom/1`) to be handled. Has no effect when no
+    /// upgrade service is set. Only applies to HTTP/1.x connections.
+    pub fn upgrade_predicate<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&RequestHead) -> bool + 'static,
+    {
+        self.upgrade_predicate = Some(Rc::new(f));
+        self
+    }
+
+    /// Set handling of `Expect: 100-continue` headers on HTTP/1.0 requests.
+    ///
+    /// HTTP/1.0 clients never receive `100 Continue`. By default the expect service is still
+    /// called for these requests; see [`Http10Expect`] for the alternatives.
+    pub fn http10_expect(mut self, val: Http10Expect) -> Self {
+        self.http10_expect = val;
+        self
+    }
+
+    /// Set whether connection-specific headers are removed from HTTP/2 responses.
+    ///
+    /// HTTP/2 forbids connection-specific headers; sending a response containing them resets the
+    /// stream. When enabled, `Connection`, `Keep-Alive`, `Proxy-Connection`, `Transfer-Encoding`,
+    /// `Upgrade` and `TE` headers, as well as any headers named in the `Connection` header, are
+    /// removed from responses. This is useful when forwarding upstream HTTP/1.x responses.
+    ///
+    /// When disabled, only `Connection` and `Transfer-Encoding` headers are removed.
+    ///
+    /// By default sanitization is enabled.
+    pub fn h2_sanitize_headers(mut self, enabled: bool) -> Self {
+        self.h2_sanitize_headers = enabled;
+        self
+    }
+
+    /// Append `; charset=utf-8` to response content types that lack a charset parameter.
+    ///
+    /// Applies to `text/*` and `application/json` content types of responses sent by this
+    /// service. Content types that already specify a charset are left unchanged.
+    ///
+    /// By default content types are left unchanged.
+    pub fn default_charset(mut self, enabled: bool) -> Self {
+        self.default_charset = enabled;
+        self
+    }
+
+    /// Set handling of HTTP/1.x response header values and reason phrases containing CR or LF
+    /// characters.
+    ///
+    /// By default such responses are replaced by `500 Internal Server Error` responses; see
+    /// [`CrlfHandling`] for the alternative.
+    pub fn crlf_handling(mut self, val: CrlfHandling) -> Self {
+        self.crlf_handling = val;
+        self
+    }
+
+    /// Set handling of requests with duplicated `Host`, `Content-Length` or `Authorization`
+    /// headers.
+    ///
+    /// By default such requests are rejected with `400 Bad Request`; see [`DuplicateHeaders`] for
+    /// the alternatives.
+    pub fn duplicate_headers(mut self, val: DuplicateHeaders) -> Self {
+        self.duplicate_headers = val;
+        self
+    }
+
+    /// Set handling of HTTP/1.x response bodies producing more or fewer bytes than their declared
+    /// size.
+    ///
+    /// By default excess bytes are dropped; see [`BodyLengthMismatch`] for the alternatives.
+    pub fn body_length_mismatch(mut self, val: BodyLengthMismatch) -> Self {
+        self.body_length_mismatch = val;
+        self
+    }
+
+    /// Set the prioritizer choosing which waiting HTTP/2 stream of a connection is passed to the
+    /// service next.
+    ///
+    /// Streams wait while the service is not ready. By default they are serviced in the order
+    /// they were received; see [`Prioritizer`].
+    pub fn prioritizer<P>(mut self, prioritizer: P) -> Self
+    where
+        P: Prioritizer + 'static,
+    {
+        self.prioritizer = Rc::new(prioritizer);
+        self
+    }
+
     /// Sets the callback to be run on connection establishment.
     ///
     /// Has mutable access to a data container that will be merged into request extensions.
     /// This enables transport layer data (like client certificates) to be accessed in middleware
     /// and handlers.
+    ///
+    /// The callback is run a second time to fill the [`ConnectionData`](crate::ConnectionData)
+    /// that is added to the extensions of every request on the connection.
     pub fn on_connect_ext<F>(mut self, f: F) -> Self
     where
         F: Fn(&T, &mut Extensions) + 'static,
@@ -182,13 +377,7 @@ where
         S::InitError: fmt::Debug,
         S::Response: Into<Response<B>>,
     {
-        let cfg = ServiceConfig::new(
-            self.keep_alive,
-            self.client_timeout,
-            self.client_disconnect,
-            self.secure,
-            self.local_addr,
-        );
+        let cfg = self.service_config();
 
         H1Service::with_config(cfg, service.into_factory())
             .expect(self.expect)
@@ -207,13 +396,7 @@ where
         B: MessageBody + 'static,
         B::Error: Into<Box<dyn StdError>>,
     {
-        let cfg = ServiceConfig::new(
-            self.keep_alive,
-            self.client_timeout,
-            self.client_disconnect,
-            self.secure,
-            self.local_addr,
-        );
+        let cfg = self.service_config();
 
         H2Service::with_config(cfg, service.into_factory())
             .on_connect_ext(self.on_connect_ext)
@@ -230,17 +413,36 @@ where
         B: MessageBody + 'static,
         B::Error: Into<Box<dyn StdError>>,
     {
-        let cfg = ServiceConfig::new(
-            self.keep_alive,
-            self.client_timeout,
-            self.client_disconnect,
-            self.secure,
-            self.local_addr,
-        );
+        let cfg = self.service_config();
 
         HttpService::with_config(cfg, service.into_factory())
             .expect(self.expect)
             .upgrade(self.upgrade)
             .on_connect_ext(self.on_connect_ext)
     }
+
+    fn service_config(&self) -> ServiceConfig {
+        ServiceConfig::builder(
+            self.keep_alive,
+            self.client_timeout,
+            self.client_disconnect,
+            self.secure,
+            self.local_addr,
+        )
+        .admission(Admission::new(
+            self.max_in_flight,
+            self.retry_after,
+            self.on_overload.clone(),
+        ))
+        .upgrade_predicate(self.upgrade_predicate.clone())
+        .http10_expect(self.http10_expect)
+        .h2_sanitize_headers(self.h2_sanitize_headers)
+        .body_poll_timeout(self.body_poll_timeout)
+        .default_charset(self.default_charset)
+        .crlf_handling(self.crlf_handling)
+        .duplicate_headers(self.duplicate_headers)
+        .body_length_mismatch(self.body_length_mismatch)
+        .prioritizer(self.prioritizer.clone())
+        .finish()
+    }
 }
@@ -0,0 +1,108 @@
+//! TCP connector binding sockets to a network interface.
+
+use std::{
+    convert::TryFrom as _,
+    io,
+    net::{IpAddr, SocketAddr},
+    os::unix::io::{FromRawFd as _, IntoRawFd as _},
+    rc::Rc,
+};
+
+use actix_rt::net::TcpStream;
+use actix_service::Service;
+use actix_tls::connect::{
+    Connect as TcpConnect, ConnectError as TcpConnectError, Connection as TcpConnection,
+    Resolver,
+};
+use futures_core::future::LocalBoxFuture;
+use http::Uri;
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::net::TcpSocket;
+
+use super::config::ConnectorConfig;
+
+/// TCP connector opening connections with sockets bound to a network interface and, optionally,
+/// a local address.
+#[derive(Clone)]
+pub(crate) struct BindConnector {
+    resolver: Resolver,
+    interface: Rc<str>,
+    local_address: Option<IpAddr>,
+}
+
+impl BindConnector {
+    pub(crate) fn new(resolver: Resolver) -> Self {
+        Self {
+            resolver,
+            interface: "".into(),
+            local_address: None,
+        }
+    }
+
+    /// Takes interface and local address from the finished connector configuration.
+    pub(crate) fn configure(self, config: &ConnectorConfig) -> Self {
+        Self {
+            resolver: self.resolver,
+            interface: config.interface.clone().unwrap_or(self.interface),
+            local_address: config.local_address,
+        }
+    }
+}
+
+impl Service<TcpConnect<Uri>> for BindConnector {
+    type Response = TcpConnection<Uri, TcpStream>;
+    type Error = TcpConnectError;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::always_ready!();
+
+    fn call(&self, req: TcpConnect<Uri>) -> Self::Future {
+        let resolver = self.resolver.clone();
+        let interface = self.interface.clone();
+        let local_address = self.local_address;
+
+        Box::pin(async move {
+            // requests with addresses set are not resolved again
+            let req = resolver.call(req).await?;
+
+            // connections only keep the host of the uri they were opened for
+            let uri = Uri::try_from(format!("{}:{}", req.hostname(), req.port()))
+                .map_err(|_| TcpConnectError::InvalidInput)?;
+            let addrs = req.addrs().collect::<Vec<_>>();
+
+            let mut err = None;
+
+            // connect to the addresses in order, returning the last error if none succeeds
+            for addr in addrs {
+                match connect(addr, &interface, local_address).await {
+                    Ok(stream) => {
+                        return Ok(TcpConnection::new(stream, uri));
+                    }
+                    Err(e) => err = Some(e),
+                }
+            }
+
+            Err(err.map_or(TcpConnectError::NoRecords, TcpConnectError::Io))
+        })
+    }
+}
+
+async fn connect(
+    addr: SocketAddr,
+    interface: &str,
+    local_address: Option<IpAddr>,
+) -> io::Result<TcpStream> {
+    let socket =
+        Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+    socket.bind_device(Some(interface.as_bytes()))?;
+
+    if let Some(ip) = local_address {
+        socket.bind(&SocketAddr::new(ip, 0).into())?;
+    }
+
+    socket.set_nonblocking(true)?;
+
+    // SAFETY: file descriptor is owned by the socket and released to the tokio socket.
+    let socket = unsafe { TcpSocket::from_raw_fd(socket.into_raw_fd()) };
+    socket.connect(addr).await
+}
@@ -1,6 +1,16 @@
 use std::net::IpAddr;
+use std::rc::Rc;
 use std::time::Duration;
 
+use crate::body::BodySize;
+use crate::message::RequestHeadType;
+
+use super::{DnsCache, HttpProtocol, IpVersion, Proxy, SendRequestError};
+
+/// Hook called with the outgoing request head and body size before a request is sent.
+pub(crate) type RequestHook =
+    Rc<dyn Fn(&mut RequestHeadType, BodySize) -> Result<(), SendRequestError>>;
+
 const DEFAULT_H2_CONN_WINDOW: u32 = 1024 * 1024 * 2; // 2MB
 const DEFAULT_H2_STREAM_WINDOW: u32 = 1024 * 1024; // 1MB
 
@@ -11,11 +21,23 @@ pub(crate) struct ConnectorConfig {
     pub(crate) handshake_timeout: Duration,
     pub(crate) conn_lifetime: Duration,
     pub(crate) conn_keep_alive: Duration,
+    pub(crate) conn_validation: bool,
     pub(crate) disconnect_timeout: Option<Duration>,
     pub(crate) limit: usize,
+    pub(crate) limit_per_host: usize,
+    pub(crate) acquire_timeout: Option<Duration>,
     pub(crate) conn_window_size: u32,
     pub(crate) stream_window_size: u32,
     pub(crate) local_address: Option<IpAddr>,
+    pub(crate) interface: Option<Rc<str>>,
+    pub(crate) ip_version: Option<IpVersion>,
+    pub(crate) proxy: Option<Proxy>,
+    pub(crate) protocol: HttpProtocol,
+    pub(crate) dns_cache: Option<DnsCache>,
+    pub(crate) max_head_size: usize,
+    pub(crate) max_headers: usize,
+    pub(crate) capture_raw_head: bool,
+    pub(crate) request_hook: Option<RequestHook>,
 }
 
 impl Default for ConnectorConfig {
@@ -25,11 +47,23 @@ impl Default for ConnectorConfig {
             handshake_timeout: Duration::from_secs(5),
             conn_lifetime: Duration::from_secs(75),
             conn_keep_alive: Duration::from_secs(15),
+            conn_validation: true,
             disconnect_timeout: Some(Duration::from_millis(3000)),
             limit: 100,
+            limit_per_host: 0,
+            acquire_timeout: None,
             conn_window_size: DEFAULT_H2_CONN_WINDOW,
             stream_window_size: DEFAULT_H2_STREAM_WINDOW,
             local_address: None,
+            interface: None,
+            ip_version: None,
+            proxy: None,
+            protocol: HttpProtocol::Negotiate,
+            dns_cache: None,
+            max_head_size: 131_072,
+            max_headers: 96,
+            capture_raw_head: false,
+            request_hook: None,
         }
     }
 }
@@ -1,25 +1,36 @@
 use std::{
+    cell::Cell,
+    future::Future as _,
     io,
     ops::{Deref, DerefMut},
     pin::Pin,
+    rc::Rc,
     task::{Context, Poll},
     time,
 };
 
 use actix_codec::{AsyncRead, AsyncWrite, Framed, ReadBuf};
-use actix_rt::task::JoinHandle;
+use actix_rt::{
+    task::JoinHandle,
+    time::{self as rt_time, Instant},
+};
+use actix_utils::future::poll_fn;
 use bytes::Bytes;
 use futures_core::future::LocalBoxFuture;
 use h2::client::SendRequest;
 
 use crate::h1::ClientCodec;
+use crate::header::CONTENT_LENGTH;
 use crate::message::{RequestHeadType, ResponseHead};
 use crate::payload::Payload;
 use crate::{body::MessageBody, Error};
 
+use super::config::RequestHook;
 use super::error::SendRequestError;
+use super::limits::{LimitedPayload, PayloadLimits};
 use super::pool::Acquired;
-use super::{h1proto, h2proto};
+use super::proxy::Proxy;
+use super::{h1proto, h2proto, RequestTimeout};
 
 /// Trait alias for types impl [tokio::io::AsyncRead] and [tokio::io::AsyncWrite].
 pub trait ConnectionIo: AsyncRead + AsyncWrite + Unpin + 'static {}
@@ -56,6 +67,21 @@ impl<Io: ConnectionIo> H1Connection<Io> {
             .release(ConnectionInnerType::H1(io), self.created);
     }
 
+    /// Returns proxy that requests on this connection are sent to.
+    pub(super) fn proxy(&self) -> Option<&Proxy> {
+        self.acquired.proxy()
+    }
+
+    /// Returns hook called before requests are sent on this connection.
+    pub(super) fn request_hook(&self) -> Option<&RequestHook> {
+        self.acquired.request_hook()
+    }
+
+    /// Returns codec for sending requests on this connection.
+    pub(super) fn codec(&self) -> ClientCodec {
+        self.acquired.h1_codec()
+    }
+
     fn io_pin_mut(self: Pin<&mut Self>) -> Pin<&mut Io> {
         Pin::new(self.get_mut().io.as_mut().unwrap())
     }
@@ -147,15 +173,38 @@ impl<Io: ConnectionIo> H2Connection<Io> {
         self.acquired
             .release(ConnectionInnerType::H2(io), self.created);
     }
+
+    /// Returns hook called before requests are sent on this connection.
+    pub(super) fn request_hook(&self) -> Option<&RequestHook> {
+        self.acquired.request_hook()
+    }
+}
+
+impl<Io: ConnectionIo> Drop for H2Connection<Io> {
+    fn drop(&mut self) {
+        // requests on a shared connection end without closing it
+        if self.io.is_some() {
+            self.release();
+        }
+    }
 }
 
 /// `H2ConnectionInner` has two parts: `SendRequest` and `Connection`.
 ///
 /// `Connection` is spawned as an async task on runtime and `H2ConnectionInner` holds a handle
 /// for this task. Therefore, it can wake up and quit the task when SendRequest is dropped.
+///
+/// Clones are handles to the same connection, used by concurrent requests.
+#[derive(Clone)]
 pub(super) struct H2ConnectionInner {
+    sender: SendRequest<Bytes>,
+    task: Rc<H2ConnectionTask>,
+}
+
+struct H2ConnectionTask {
     handle: JoinHandle<()>,
     sender: SendRequest<Bytes>,
+    max_streams: Rc<Cell<usize>>,
 }
 
 impl H2ConnectionInner {
@@ -163,16 +212,42 @@ impl H2ConnectionInner {
         sender: SendRequest<Bytes>,
         connection: h2::client::Connection<Io>,
     ) -> Self {
-        let handle = actix_rt::spawn(async move {
-            let _ = connection.await;
+        let max_streams = Rc::new(Cell::new(connection.max_concurrent_send_streams()));
+
+        let handle = actix_rt::spawn({
+            let max_streams = max_streams.clone();
+            let mut connection = Box::pin(connection);
+
+            // track the peer's stream limit, which changes with its settings
+            poll_fn(move |cx| {
+                let res = connection.as_mut().poll(cx);
+                max_streams.set(connection.max_concurrent_send_streams());
+                res.map(|_| ())
+            })
+        });
+
+        let task = Rc::new(H2ConnectionTask {
+            handle,
+            sender: sender.clone(),
+            max_streams,
         });
 
-        Self { handle, sender }
+        Self { sender, task }
+    }
+
+    /// Returns maximum number of concurrent streams the peer allows.
+    pub(super) fn max_streams(&self) -> usize {
+        self.task.max_streams.get()
+    }
+
+    /// Returns true if both handles are to the same connection.
+    pub(super) fn is_same(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.task, &other.task)
     }
 }
 
-/// Cancel spawned connection task on drop.
-impl Drop for H2ConnectionInner {
+/// Cancel spawned connection task on drop of the last handle.
+impl Drop for H2ConnectionTask {
     fn drop(&mut self) {
         if self
             .sender
@@ -261,20 +336,63 @@ where
         RB::Error: Into<Error>,
     {
         Box::pin(async move {
-            match self {
-                Connection::Tcp(ConnectionType::H1(conn)) => {
-                    h1proto::send_request(conn, head.into(), body).await
-                }
-                Connection::Tls(ConnectionType::H1(conn)) => {
-                    h1proto::send_request(conn, head.into(), body).await
+            let head = head.into();
+            let limits = head.as_ref().extensions().get::<PayloadLimits>().copied();
+            let timeout = head.as_ref().extensions().get::<RequestTimeout>().copied();
+            let started = Instant::now();
+
+            let send = async move {
+                match self {
+                    Connection::Tcp(ConnectionType::H1(conn)) => {
+                        h1proto::send_request(conn, head, body).await
+                    }
+                    Connection::Tls(ConnectionType::H1(conn)) => {
+                        h1proto::send_request(conn, head, body).await
+                    }
+                    // plain connections use HTTP/2 with prior knowledge
+                    Connection::Tcp(ConnectionType::H2(conn)) => {
+                        h2proto::send_request(conn, head, body).await
+                    }
+                    Connection::Tls(ConnectionType::H2(conn)) => {
+                        h2proto::send_request(conn, head, body).await
+                    }
                 }
-                Connection::Tls(ConnectionType::H2(conn)) => {
-                    h2proto::send_request(conn, head.into(), body).await
+            };
+
+            // dropping the request on timeout closes HTTP/1 connections and resets HTTP/2 streams
+            let (head, payload) = match timeout {
+                Some(timeout) => rt_time::timeout(timeout.timeout, send)
+                    .await
+                    .map_err(|_| SendRequestError::Timeout)??,
+                None => send.await?,
+            };
+
+            let deadline = timeout
+                .filter(|timeout| timeout.include_body)
+                .map(|timeout| started + timeout.timeout);
+
+            // apply response payload limits and deadline of the request
+            let payload: Payload = match payload {
+                Payload::Stream(stream) if limits.is_some() || deadline.is_some() => {
+                    let length = head
+                        .headers
+                        .get(CONTENT_LENGTH)
+                        .and_then(|len| len.to_str().ok())
+                        .and_then(|len| len.parse().ok());
+
+                    let mut payload =
+                        LimitedPayload::new(stream, limits.unwrap_or_default(), length);
+
+                    if let Some(deadline) = deadline {
+                        payload = payload.deadline(deadline);
+                    }
+
+                    Payload::Stream(Box::pin(payload))
                 }
-                _ => unreachable!(
-                    "Plain Tcp connection can be used only in Http1 protocol"
-                ),
-            }
+                payload => payload,
+            };
+
+            Ok((head, payload))
         })
     }
 
@@ -288,22 +406,25 @@ where
     > {
         Box::pin(async move {
             match self {
-                Connection::Tcp(ConnectionType::H1(ref _conn)) => {
-                    let (head, framed) = h1proto::open_tunnel(self, head.into()).await?;
+                Connection::Tcp(ConnectionType::H1(ref conn)) => {
+                    let head = match conn.proxy() {
+                        Some(proxy) => proxy.prepare_request(head.into()),
+                        None => head.into(),
+                    };
+                    let (head, framed) = h1proto::open_tunnel(self, head).await?;
                     Ok((head, framed))
                 }
                 Connection::Tls(ConnectionType::H1(ref _conn)) => {
                     let (head, framed) = h1proto::open_tunnel(self, head.into()).await?;
                     Ok((head, framed))
                 }
-                Connection::Tls(ConnectionType::H2(mut conn)) => {
+                Connection::Tcp(ConnectionType::H2(mut conn)) => {
                     conn.release();
                     Err(SendRequestError::TunnelNotSupported)
                 }
-                Connection::Tcp(ConnectionType::H2(_)) => {
-                    unreachable!(
-                        "Plain Tcp connection can be used only in Http1 protocol"
-                    )
+                Connection::Tls(ConnectionType::H2(mut conn)) => {
+                    conn.release();
+                    Err(SendRequestError::TunnelNotSupported)
                 }
             }
         })
@@ -1,11 +1,13 @@
 use std::{
     fmt,
     future::Future,
-    net::IpAddr,
+    io,
+    net::{IpAddr, SocketAddr},
     pin::Pin,
     rc::Rc,
+    sync::Arc,
     task::{Context, Poll},
-    time::Duration,
+    time::{Duration, Instant},
 };
 
 use actix_rt::{
@@ -15,18 +17,24 @@ use actix_rt::{
 use actix_service::Service;
 use actix_tls::connect::{
     new_connector, Connect as TcpConnect, ConnectError as TcpConnectError,
-    Connection as TcpConnection, Resolver,
+    Connection as TcpConnection, Resolve, Resolver,
 };
 use futures_core::{future::LocalBoxFuture, ready};
 use http::Uri;
 use pin_project::pin_project;
 
+use crate::body::BodySize;
+use crate::message::RequestHeadType;
+
 use super::config::ConnectorConfig;
 use super::connection::{Connection, ConnectionIo};
-use super::error::ConnectError;
-use super::pool::ConnectionPool;
-use super::Connect;
+use super::dns::DnsCache;
+use super::error::{ConnectError, SendRequestError};
+use super::pool::{ConnectionPool, PoolStats};
+use super::proxy::{target_port, Proxy};
+use super::tls::{CertVerifier, TlsOptions};
 use super::Protocol;
+use super::{Connect, HttpProtocol, IpVersion, Timings};
 
 #[cfg(feature = "openssl")]
 use actix_tls::connect::ssl::openssl::SslConnector as OpensslConnector;
@@ -57,11 +65,28 @@ enum SslConnector {
 /// ```
 pub struct Connector<T> {
     connector: T,
+    resolver: Resolver,
+    custom_resolver: bool,
     config: ConnectorConfig,
     #[allow(dead_code)]
     ssl: SslConnector,
+    tls_options: TlsOptions,
+    transport: Option<TransportFactory>,
+    /// Applies the finished configuration to the TCP connector.
+    configure: fn(T, &ConnectorConfig) -> T,
 }
 
+/// Creates the service opening connections through a custom transport.
+type TransportFactory = Box<
+    dyn FnOnce(
+        &ConnectorConfig,
+    ) -> actix_service::boxed::RcService<
+        Connect,
+        (Box<dyn ConnectionIo>, Protocol),
+        ConnectError,
+    >,
+>;
+
 impl Connector<()> {
     #[allow(clippy::new_ret_no_self, clippy::let_unit_value)]
     pub fn new() -> Connector<
@@ -71,16 +96,27 @@ impl Connector<()> {
                 Error = actix_tls::connect::ConnectError,
             > + Clone,
     > {
+        let resolver = resolver::resolver();
+        let tls_options = TlsOptions::default();
+
         Connector {
-            ssl: Self::build_ssl(vec![b"h2".to_vec(), b"http/1.1".to_vec()]),
-            connector: new_connector(resolver::resolver()),
+            ssl: Self::build_ssl(
+                vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+                &tls_options,
+            ),
+            connector: new_connector(resolver.clone()),
+            resolver,
+            custom_resolver: false,
             config: ConnectorConfig::default(),
+            tls_options,
+            transport: None,
+            configure: |connector, _| connector,
         }
     }
 
     // Build Ssl connector with openssl, based on supplied alpn protocols
     #[cfg(feature = "openssl")]
-    fn build_ssl(protocols: Vec<Vec<u8>>) -> SslConnector {
+    fn build_ssl(protocols: Vec<Vec<u8>>, tls_options: &TlsOptions) -> SslConnector {
         use actix_tls::connect::ssl::openssl::SslMethod;
         use bytes::{BufMut, BytesMut};
 
@@ -94,12 +130,13 @@ impl Connector<()> {
         let _ = ssl
             .set_alpn_protos(&alpn)
             .map_err(|e| error!("Can not set alpn protocol: {:?}", e));
+        tls_options.sessions.install(&mut ssl);
         SslConnector::Openssl(ssl.build())
     }
 
     // Build Ssl connector with rustls, based on supplied alpn protocols
     #[cfg(all(not(feature = "openssl"), feature = "rustls"))]
-    fn build_ssl(protocols: Vec<Vec<u8>>) -> SslConnector {
+    fn build_ssl(protocols: Vec<Vec<u8>>, _: &TlsOptions) -> SslConnector {
         let mut config = ClientConfig::new();
         config.set_protocols(&protocols);
         config.root_store.add_server_trust_anchors(
@@ -110,7 +147,7 @@ impl Connector<()> {
 
     // ssl turned off, provides empty ssl connector
     #[cfg(not(any(feature = "openssl", feature = "rustls")))]
-    fn build_ssl(_: Vec<Vec<u8>>) -> SslConnector {
+    fn build_ssl(_: Vec<Vec<u8>>, _: &TlsOptions) -> SslConnector {
         SslConnector::None
     }
 }
@@ -128,10 +165,66 @@ impl<S> Connector<S> {
     {
         Connector {
             connector,
+            resolver: self.resolver,
+            custom_resolver: self.custom_resolver,
             config: self.config,
             ssl: self.ssl,
+            tls_options: self.tls_options,
+            transport: self.transport,
+            configure: |connector, _| connector,
+        }
+    }
+
+    /// Bind sockets of outgoing connections to a network interface, e.g. `eth1`.
+    ///
+    /// Useful on multi-homed hosts to select the interface traffic egresses from, regardless of
+    /// the routing table. Replaces the TCP connector, like [`connector`](Self::connector). Sockets
+    /// are also bound to the local address set with [`local_address`](Self::local_address).
+    ///
+    /// Binding to an interface requires the `CAP_NET_RAW` capability on Linux before 5.7.
+    #[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+    pub fn interface(
+        mut self,
+        interface: &str,
+    ) -> Connector<
+        impl Service<
+                TcpConnect<Uri>,
+                Response = TcpConnection<Uri, TcpStream>,
+                Error = TcpConnectError,
+            > + Clone,
+    > {
+        self.config.interface = Some(interface.into());
+
+        let connector = super::bind::BindConnector::new(self.resolver.clone());
+        Connector {
+            configure: super::bind::BindConnector::configure,
+            ..self.connector(connector)
         }
     }
+
+    /// Open connections of all requests through a custom transport.
+    ///
+    /// The transport is called with each connect request and returns any IO type, e.g. an
+    /// in-memory duplex stream for hermetic tests or a stream to a TLS-terminating sidecar. It
+    /// replaces the TCP and TLS connectors, resolver and proxy: the IO is used as-is for both
+    /// `http` and `https` URIs, speaking HTTP/1 unless HTTP/2 is selected with
+    /// [`protocol`](Connector::protocol) or per request. Connections are still pooled and time
+    /// out after the connect [timeout](Connector::timeout).
+    pub fn transport<T, Io>(mut self, transport: T) -> Self
+    where
+        T: Service<Connect, Response = Io, Error = ConnectError> + 'static,
+        T::Future: 'static,
+        Io: ConnectionIo,
+    {
+        self.transport = Some(Box::new(move |config: &ConnectorConfig| {
+            actix_service::boxed::rc_service(TransportService {
+                transport,
+                protocol: config.protocol,
+                timeout: config.timeout,
+            })
+        }));
+        self
+    }
 }
 
 impl<S, Io> Connector<S>
@@ -190,7 +283,7 @@ where
                 unimplemented!("actix-http:client: supported versions http/1.1, http/2")
             }
         };
-        self.ssl = Connector::build_ssl(versions);
+        self.ssl = Connector::build_ssl(versions, &self.tls_options);
         self
     }
 
@@ -221,6 +314,28 @@ where
         self
     }
 
+    /// Set number of simultaneous connections to a single host, identified by the authority part
+    /// of the request URI.
+    ///
+    /// Connections counted against the per-host limit also count against the total [limit].
+    /// If limit is 0, connections are only limited by the total limit.
+    /// By default there is no per-host limit.
+    ///
+    /// [limit]: Self::limit
+    pub fn limit_per_host(mut self, limit: usize) -> Self {
+        self.config.limit_per_host = limit;
+        self
+    }
+
+    /// Set max time to wait for a free connection when a connection limit has been reached.
+    ///
+    /// Requests that wait longer fail with [`ConnectError::AcquireTimeout`].
+    /// By default requests wait until a connection is free.
+    pub fn acquire_timeout(mut self, timeout: Duration) -> Self {
+        self.config.acquire_timeout = Some(timeout);
+        self
+    }
+
     /// Set keep-alive period for opened connection.
     ///
     /// Keep-alive period is the period between connection usage. If
@@ -242,6 +357,17 @@ where
         self
     }
 
+    /// Set whether idle HTTP/1 connections are checked before reuse.
+    ///
+    /// The check is a non-blocking read that drops connections the server already closed, so
+    /// requests do not race its keep-alive timeout. Idle connections are also closed as soon as
+    /// they exceed keep-alive or lifetime period.
+    /// Enabled by default.
+    pub fn conn_validation(mut self, enabled: bool) -> Self {
+        self.config.conn_validation = enabled;
+        self
+    }
+
     /// Set server connection disconnect timeout in milliseconds.
     ///
     /// Defines a timeout for disconnect connection. If a disconnect procedure does not complete
@@ -256,33 +382,204 @@ where
     }
 
     /// Set local IP Address the connector would use for establishing connection.
+    ///
+    /// Unless set with [`ip_version`](Self::ip_version), connections are only opened to addresses
+    /// of the same IP version as the local address.
     pub fn local_address(mut self, addr: IpAddr) -> Self {
         self.config.local_address = Some(addr);
         self
     }
 
+    /// Only connect to addresses of the given IP version.
+    ///
+    /// Host names are resolved by the connector and addresses of the other version are skipped.
+    /// Connecting fails with [`ConnectError::NoRecords`] if the host has no address of this
+    /// version. Can be overridden per request with [`Connect::ip_version`].
+    pub fn ip_version(mut self, ip_version: IpVersion) -> Self {
+        self.config.ip_version = Some(ip_version);
+        self
+    }
+
+    /// Set HTTP protocol used for connections. Default is [`HttpProtocol::Negotiate`].
+    ///
+    /// [`HttpProtocol::Http2`] sends requests over plain connections with HTTP/2 prior
+    /// knowledge. Over TLS, the protocols of the selection are offered with ALPN and connecting
+    /// fails with [`ConnectError::ProtocolNotNegotiated`] if the server picks another one.
+    /// Can be overridden per request with [`Connect::protocol`].
+    ///
+    /// Like [`max_http_version`](Self::max_http_version), this replaces the TLS connector with a
+    /// default one offering the matching protocols; custom TLS connectors have to be set after.
+    pub fn protocol(mut self, protocol: HttpProtocol) -> Self {
+        self.config.protocol = protocol;
+        self.ssl = Connector::build_ssl(protocol.alpn(), &self.tls_options);
+        self
+    }
+
+    /// Use `server_name` instead of `host` for SNI and server certificate verification of TLS
+    /// connections to `host`.
+    pub fn server_name(
+        mut self,
+        host: impl Into<String>,
+        server_name: impl Into<String>,
+    ) -> Self {
+        self.tls_options
+            .server_names
+            .insert(host.into(), server_name.into());
+        self
+    }
+
+    /// Accept server certificates that fail verification, e.g. self-signed or expired ones.
+    ///
+    /// # Warning
+    /// Connections accepting invalid certificates are open to man-in-the-middle attacks. Only
+    /// use this for testing or combined with [`pin_certificate`](Self::pin_certificate).
+    pub fn danger_accept_invalid_certs(mut self, accept_invalid_certs: bool) -> Self {
+        self.tls_options.accept_invalid_certs = accept_invalid_certs;
+        self
+    }
+
+    /// Pin DER-encoded server certificate.
+    ///
+    /// Once a certificate is pinned, TLS connections are only established to servers presenting
+    /// one of the pinned certificates. Pinning is checked in addition to verification.
+    pub fn pin_certificate(mut self, der: impl Into<Vec<u8>>) -> Self {
+        self.tls_options.pinned_certs.push(der.into());
+        self
+    }
+
+    /// Verify server certificates with a custom function instead of the TLS connector's default
+    /// verification.
+    ///
+    /// The function is called with the server name and the DER-encoded certificate chain,
+    /// end-entity certificate first, and returns true if the certificates are accepted.
+    pub fn verify_server_cert<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&str, &[&[u8]]) -> bool + Send + Sync + 'static,
+    {
+        self.tls_options.verifier = Some(Arc::new(f) as Arc<CertVerifier>);
+        self
+    }
+
+    /// Set maximum size of HTTP/1 response heads in bytes. Set to 128 KiB by default.
+    ///
+    /// Responses with larger heads fail with
+    /// [`ParseError::TooLarge`](crate::error::ParseError::TooLarge) instead of being buffered.
+    pub fn max_response_head_size(mut self, size: usize) -> Self {
+        self.config.max_head_size = size;
+        self
+    }
+
+    /// Set maximum number of HTTP/1 response headers. Set to 96 by default.
+    ///
+    /// Responses with more headers fail with
+    /// [`ParseError::TooLarge`](crate::error::ParseError::TooLarge).
+    pub fn max_response_headers(mut self, count: usize) -> Self {
+        self.config.max_headers = count;
+        self
+    }
+
+    /// Keep the bytes of HTTP/1 response heads as received, e.g. for debugging interoperability
+    /// issues. Disabled by default.
+    ///
+    /// The bytes are available as [`RawResponseHead`](crate::h1::RawResponseHead) in the response
+    /// head's extensions.
+    pub fn capture_response_head(mut self, enabled: bool) -> Self {
+        self.config.capture_raw_head = enabled;
+        self
+    }
+
+    /// Set number of TLS sessions cached for resumption. Zero disables session resumption.
+    ///
+    /// Repeated connections to the same servers resume cached sessions using session tickets or
+    /// IDs instead of doing full handshakes. Set to 32 by default.
+    ///
+    /// Sessions of openssl connectors set with [`ssl`](Self::ssl) are not cached. For rustls
+    /// connectors set with [`rustls`](Self::rustls), the config's session storage is replaced.
+    pub fn tls_session_cache(mut self, size: usize) -> Self {
+        self.tls_options.session_cache_size = Some(size);
+        #[cfg(feature = "openssl")]
+        self.tls_options.sessions.set_capacity(size);
+        self
+    }
+
+    /// Use custom resolver for host names, e.g. a static host map or service discovery.
+    ///
+    /// Host names are resolved before connecting with the TCP connector, so this applies to
+    /// custom connectors set with [`connector`](Connector::connector) too. Requests with an
+    /// address set in [`Connect::addr`] are not resolved.
+    pub fn resolver(mut self, resolver: impl Resolve + 'static) -> Self {
+        self.resolver = Resolver::new_custom(resolver);
+        self.custom_resolver = true;
+        self
+    }
+
+    /// Cache resolved addresses of host names in the given cache.
+    ///
+    /// Host names are resolved through the cache with the resolver set with
+    /// [`resolver`](Self::resolver) or the default one. The cache can be shared by connectors.
+    pub fn dns_cache(mut self, cache: DnsCache) -> Self {
+        self.config.dns_cache = Some(cache);
+        self
+    }
+
+    /// Send requests through an upstream HTTP or SOCKS5 proxy.
+    ///
+    /// For HTTP proxies, plaintext requests are sent to the proxy in absolute-form; for secure
+    /// requests a tunnel to the host is opened with a `CONNECT` request. Connections through
+    /// SOCKS5 proxies are opened with a SOCKS5 `CONNECT` command. See [`Proxy`] for details.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.config.proxy = Some(proxy);
+        self
+    }
+
+    /// Set hook called with the outgoing request head and body size right before a request is
+    /// encoded, e.g. for signing requests or adding dynamic authorization headers.
+    ///
+    /// The hook is called after a connection is acquired, once the head is prepared for sending:
+    /// over HTTP/1 the `Host` header is set and requests to plaintext proxies are in absolute-form.
+    /// Headers added with [`RequestHeadType::headers_mut`] are sent with the request. Requests for
+    /// which the hook returns an error fail without being sent.
+    pub fn on_request<F>(mut self, hook: F) -> Self
+    where
+        F: Fn(&mut RequestHeadType, BodySize) -> Result<(), SendRequestError> + 'static,
+    {
+        self.config.request_hook = Some(Rc::new(hook));
+        self
+    }
+
     /// Finish configuration process and create connector service.
     /// The Connector builder always concludes by calling `finish()` last in
     /// its combinator chain.
     pub fn finish(self) -> ConnectorService<S, Io> {
-        let local_address = self.config.local_address;
-        let timeout = self.config.timeout;
+        let connector = (self.configure)(self.connector, &self.config);
+
+        // cached addresses are resolved up front like with a custom resolver
+        let (resolver, custom_resolver) = match self.config.dns_cache {
+            Some(ref cache) => (cache.resolver(self.resolver), true),
+            None => (self.resolver, self.custom_resolver),
+        };
 
         let tcp_service_inner =
-            TcpConnectorInnerService::new(self.connector, timeout, local_address);
+            TcpConnectorInnerService::new(connector, resolver, &self.config)
+                .resolve(custom_resolver);
 
         #[allow(clippy::redundant_clone)]
         let tcp_service = TcpConnectorService {
             service: tcp_service_inner.clone(),
+            protocol: self.config.protocol,
         };
 
+        let tls_options = Arc::new(self.tls_options);
+
         let tls_service = match self.ssl {
             SslConnector::None => None,
             #[cfg(feature = "openssl")]
             SslConnector::Openssl(tls) => {
                 const H2: &[u8] = b"h2";
 
-                use actix_tls::connect::ssl::openssl::{OpensslConnector, SslStream};
+                use actix_tls::connect::ssl::openssl::SslStream;
+
+                use super::tls::openssl::OpensslTlsConnector;
 
                 impl<Io: ConnectionIo> IntoConnectionIo for TcpConnection<Uri, SslStream<Io>> {
                     fn into_connection_io(self) -> (Box<dyn ConnectionIo>, Protocol) {
@@ -301,9 +598,14 @@ where
 
                 let handshake_timeout = self.config.handshake_timeout;
 
+                // openssl connectors can not be reconfigured; the negotiated protocol is checked
                 let tls_service = TlsConnectorService {
-                    tcp_service: tcp_service_inner,
-                    tls_service: OpensslConnector::service(tls),
+                    tcp_service: tcp_service_inner.tunnel(),
+                    tls_services: TlsServices::new(|_| {
+                        OpensslTlsConnector::new(tls.clone(), tls_options.clone())
+                    }),
+                    protocol: self.config.protocol,
+                    tls_options: tls_options.clone(),
                     timeout: handshake_timeout,
                 };
 
@@ -335,9 +637,32 @@ where
 
                 let handshake_timeout = self.config.handshake_timeout;
 
+                let tls = match tls_options.session_cache_size {
+                    Some(size) => super::tls::rustls::with_session_cache(&tls, size),
+                    None => tls,
+                };
+
+                let tls = if tls_options.custom_verification() {
+                    super::tls::rustls::with_verifier(&tls, tls_options.clone())
+                } else {
+                    tls
+                };
+
                 let tls_service = TlsConnectorService {
-                    tcp_service: tcp_service_inner,
-                    tls_service: RustlsConnector::service(tls),
+                    tcp_service: tcp_service_inner.tunnel(),
+                    tls_services: TlsServices::new(|protocol| {
+                        let config = match protocol {
+                            HttpProtocol::Negotiate => tls.clone(),
+                            protocol => {
+                                let mut config = (*tls).clone();
+                                config.set_protocols(&protocol.alpn());
+                                Arc::new(config)
+                            }
+                        };
+                        RustlsConnector::service(config)
+                    }),
+                    protocol: self.config.protocol,
+                    tls_options: tls_options.clone(),
                     timeout: handshake_timeout,
                 };
 
@@ -345,15 +670,72 @@ where
             }
         };
 
-        let tcp_config = self.config.no_disconnect_timeout();
+        // connections of all requests are opened through the transport and pooled like tls ones
+        let transport = self.transport.is_some();
+        let tls_service = match self.transport {
+            Some(factory) => Some(factory(&self.config)),
+            None => tls_service,
+        };
+
+        let mut tcp_config = self.config.no_disconnect_timeout();
+
+        // only requests to http proxies are sent in absolute-form
+        if tcp_config.proxy.as_ref().map_or(false, Proxy::is_socks5) {
+            tcp_config.proxy = None;
+        }
 
         let tcp_pool = ConnectionPool::new(tcp_service, tcp_config);
 
-        let tls_config = self.config;
-        let tls_pool = tls_service
-            .map(move |tls_service| ConnectionPool::new(tls_service, tls_config));
+        // both pools record into the same statistics handle
+        let stats = tcp_pool.stats();
+        // tls connections are tunneled through the proxy, requests are sent as usual
+        let mut tls_config = self.config;
+        tls_config.proxy = None;
+        let tls_pool = tls_service.map(move |tls_service| {
+            ConnectionPool::with_stats(tls_service, tls_config, stats)
+        });
+
+        ConnectorServicePriv {
+            tcp_pool,
+            tls_pool,
+            transport,
+        }
+    }
+}
+
+/// Service opening connections through a custom transport, see [`Connector::transport`].
+struct TransportService<T> {
+    transport: T,
+    protocol: HttpProtocol,
+    timeout: Duration,
+}
+
+impl<T, Io> Service<Connect> for TransportService<T>
+where
+    T: Service<Connect, Response = Io, Error = ConnectError>,
+    T::Future: 'static,
+    Io: ConnectionIo,
+{
+    type Response = (Box<dyn ConnectionIo>, Protocol);
+    type Error = ConnectError;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(transport);
 
-        ConnectorServicePriv { tcp_pool, tls_pool }
+    fn call(&self, req: Connect) -> Self::Future {
+        let protocol = match req.protocol.unwrap_or(self.protocol) {
+            HttpProtocol::Http2 => Protocol::Http2,
+            _ => Protocol::Http1,
+        };
+        let fut = self.transport.call(req);
+        let timeout = self.timeout;
+
+        Box::pin(async move {
+            match actix_rt::time::timeout(timeout, fut).await {
+                Ok(io) => Ok((Box::new(io?) as Box<dyn ConnectionIo>, protocol)),
+                Err(_) => Err(ConnectError::Timeout),
+            }
+        })
     }
 }
 
@@ -361,6 +743,7 @@ where
 #[derive(Clone)]
 pub struct TcpConnectorService<S: Clone> {
     service: S,
+    protocol: HttpProtocol,
 }
 
 impl<S, Io> Service<Connect> for TcpConnectorService<S>
@@ -376,8 +759,15 @@ where
     actix_service::forward_ready!(service);
 
     fn call(&self, req: Connect) -> Self::Future {
+        // plain connections use HTTP/1.1 unless HTTP/2 is known to be supported
+        let protocol = match req.protocol.unwrap_or(self.protocol) {
+            HttpProtocol::Http2 => Protocol::Http2,
+            HttpProtocol::Http1 | HttpProtocol::Negotiate => Protocol::Http1,
+        };
+
         TcpConnectorFuture {
             fut: self.service.call(req),
+            protocol,
         }
     }
 }
@@ -386,6 +776,7 @@ where
 pub struct TcpConnectorFuture<Fut> {
     #[pin]
     fut: Fut,
+    protocol: Protocol,
 }
 
 impl<Fut, Io> Future for TcpConnectorFuture<Fut>
@@ -395,10 +786,12 @@ where
     type Output = Result<(Io, Protocol), ConnectError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.project()
-            .fut
+        let this = self.project();
+        let protocol = *this.protocol;
+
+        this.fut
             .poll(cx)
-            .map_ok(|res| (res.into_parts().0, Protocol::Http1))
+            .map_ok(|res| (res.into_parts().0, protocol))
     }
 }
 
@@ -408,10 +801,37 @@ struct TlsConnectorService<S, St> {
     /// tcp connection is canceled on `TcpConnectorInnerService`'s timeout setting.
     tcp_service: S,
     /// tls connection is canceled on `TlsConnectorService`'s timeout setting.
-    tls_service: St,
+    tls_services: TlsServices<St>,
+    protocol: HttpProtocol,
+    tls_options: Arc<TlsOptions>,
     timeout: Duration,
 }
 
+/// Tls services offering the protocols of each `HttpProtocol` selection with ALPN.
+struct TlsServices<St> {
+    negotiate: St,
+    http1: St,
+    http2: St,
+}
+
+impl<St> TlsServices<St> {
+    fn new(f: impl Fn(HttpProtocol) -> St) -> Self {
+        Self {
+            negotiate: f(HttpProtocol::Negotiate),
+            http1: f(HttpProtocol::Http1),
+            http2: f(HttpProtocol::Http2),
+        }
+    }
+
+    fn get(&self, protocol: HttpProtocol) -> &St {
+        match protocol {
+            HttpProtocol::Negotiate => &self.negotiate,
+            HttpProtocol::Http1 => &self.http1,
+            HttpProtocol::Http2 => &self.http2,
+        }
+    }
+}
+
 impl<S, St, Io> Service<Connect> for TlsConnectorService<S, St>
 where
     S: Service<Connect, Response = TcpConnection<Uri, Io>, Error = ConnectError>
@@ -427,18 +847,30 @@ where
 
     fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
         ready!(self.tcp_service.poll_ready(cx))?;
-        ready!(self.tls_service.poll_ready(cx))?;
+        ready!(self.tls_services.negotiate.poll_ready(cx))?;
+        ready!(self.tls_services.http1.poll_ready(cx))?;
+        ready!(self.tls_services.http2.poll_ready(cx))?;
         Poll::Ready(Ok(()))
     }
 
     fn call(&self, req: Connect) -> Self::Future {
+        let protocol = req.protocol.unwrap_or(self.protocol);
+        let server_name = req
+            .uri
+            .host()
+            .and_then(|host| self.tls_options.server_names.get(host))
+            .cloned();
+        let timings = req.timings.clone();
         let fut = self.tcp_service.call(req);
-        let tls_service = self.tls_service.clone();
+        let tls_service = self.tls_services.get(protocol).clone();
         let timeout = self.timeout;
 
         TlsConnectorFuture::TcpConnect {
             fut,
             tls_service: Some(tls_service),
+            protocol,
+            server_name,
+            timings,
             timeout,
         }
     }
@@ -451,11 +883,16 @@ enum TlsConnectorFuture<S, Fut1, Fut2> {
         #[pin]
         fut: Fut1,
         tls_service: Option<S>,
+        protocol: HttpProtocol,
+        server_name: Option<String>,
+        timings: Option<Timings>,
         timeout: Duration,
     },
     TlsConnect {
         #[pin]
         fut: Fut2,
+        protocol: HttpProtocol,
+        timings: Option<(Timings, Instant)>,
         #[pin]
         timeout: Sleep,
     },
@@ -486,46 +923,119 @@ where
             TlsConnectorProj::TcpConnect {
                 fut,
                 tls_service,
+                protocol,
+                server_name,
+                timings,
                 timeout,
             } => {
-                let res = ready!(fut.poll(cx))?;
+                let mut res = ready!(fut.poll(cx))?;
+
+                // tls connector takes SNI and the name to verify from the connection's uri
+                if let Some(server_name) = server_name.take() {
+                    let uri = format!("https://{}/", server_name)
+                        .parse::<Uri>()
+                        .map_err(|_| {
+                            ConnectError::Io(io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                "invalid TLS server name",
+                            ))
+                        })?;
+                    res = TcpConnection::new(res.into_parts().0, uri);
+                }
+
                 let fut = tls_service
                     .take()
                     .expect("TlsConnectorFuture polled after complete")
                     .call(res);
+                let protocol = *protocol;
+                let timings = timings.take().map(|timings| (timings, Instant::now()));
                 let timeout = sleep(*timeout);
-                self.set(TlsConnectorFuture::TlsConnect { fut, timeout });
+                self.set(TlsConnectorFuture::TlsConnect {
+                    fut,
+                    protocol,
+                    timings,
+                    timeout,
+                });
                 self.poll(cx)
             }
-            TlsConnectorProj::TlsConnect { fut, timeout } => match fut.poll(cx)? {
-                Poll::Ready(res) => Poll::Ready(Ok(res.into_connection_io())),
+            TlsConnectorProj::TlsConnect {
+                fut,
+                protocol,
+                timings,
+                timeout,
+            } => match fut.poll(cx)? {
+                Poll::Ready(res) => {
+                    if let Some((timings, start)) = timings.take() {
+                        timings.update(|timings| timings.tls = Some(start.elapsed()));
+                    }
+
+                    let (io, proto) = res.into_connection_io();
+
+                    match (*protocol, proto) {
+                        (HttpProtocol::Http1, Protocol::Http2)
+                        | (HttpProtocol::Http2, Protocol::Http1) => Poll::Ready(Err(
+                            ConnectError::ProtocolNotNegotiated(*protocol),
+                        )),
+                        _ => Poll::Ready(Ok((io, proto))),
+                    }
+                }
                 Poll::Pending => timeout.poll(cx).map(|_| Err(ConnectError::Timeout)),
             },
         }
     }
 }
 
+/// Returns socket address of a uri whose host is an IP address.
+fn ip_literal_addr(uri: &Uri) -> Option<SocketAddr> {
+    let host = uri.host()?;
+    let host = host
+        .strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host);
+    let ip = host.parse::<IpAddr>().ok()?;
+    Some(SocketAddr::new(ip, target_port(uri)))
+}
+
 /// service for establish tcp connection.
 /// operation is canceled when timeout limit reached.
 #[derive(Clone)]
 pub struct TcpConnectorInnerService<S: Clone> {
     service: S,
+    resolver: Resolver,
     timeout: Duration,
     local_address: Option<std::net::IpAddr>,
+    ip_version: Option<IpVersion>,
+    proxy: Option<Proxy>,
+    tunnel: bool,
+    resolve: bool,
 }
 
 impl<S: Clone> TcpConnectorInnerService<S> {
-    fn new(
-        service: S,
-        timeout: Duration,
-        local_address: Option<std::net::IpAddr>,
-    ) -> Self {
+    fn new(service: S, resolver: Resolver, config: &ConnectorConfig) -> Self {
         Self {
             service,
-            timeout,
-            local_address,
+            resolver,
+            timeout: config.timeout,
+            local_address: config.local_address,
+            ip_version: config.ip_version,
+            proxy: config.proxy.clone(),
+            tunnel: false,
+            resolve: false,
         }
     }
+
+    /// Always resolve host names with own resolver instead of the wrapped service's.
+    fn resolve(mut self, resolve: bool) -> Self {
+        self.resolve = resolve;
+        self
+    }
+
+    /// Open a `CONNECT` tunnel to the host when connecting through a proxy.
+    #[cfg(any(feature = "openssl", feature = "rustls"))]
+    fn tunnel(mut self) -> Self {
+        self.tunnel = true;
+        self
+    }
 }
 
 impl<S, Io> Service<Connect> for TcpConnectorInnerService<S>
@@ -536,46 +1046,169 @@ where
             Error = TcpConnectError,
         > + Clone
         + 'static,
+    Io: ConnectionIo,
 {
     type Response = S::Response;
     type Error = ConnectError;
-    type Future = TcpConnectorInnerFuture<S::Future>;
+    type Future = TcpConnectorInnerFuture<S::Future, Io>;
 
     actix_service::forward_ready!(service);
 
-    fn call(&self, req: Connect) -> Self::Future {
-        let mut req = TcpConnect::new(req.uri).set_addr(req.addr);
+    fn call(&self, mut req: Connect) -> Self::Future {
+        // ip literal hosts, e.g. bracketed IPv6 addresses, are connected to without resolving
+        if req.addr.is_none() {
+            req.addr = ip_literal_addr(&req.uri);
+        }
+
+        // a socket bound to a local address can only connect to addresses of the same version
+        let ip_version = req
+            .ip_version
+            .or(self.ip_version)
+            .or_else(|| self.local_address.as_ref().map(IpVersion::of));
+
+        // connect to the proxy instead of the host; the connection keeps the host's uri so tls
+        // handshake through a tunnel is done against the host
+        let (mut conn_req, target) = match self.proxy {
+            Some(ref proxy) => (
+                TcpConnect::new(proxy.uri().clone()).set_port(proxy.port()),
+                Some((proxy.clone(), req.uri, req.addr)),
+            ),
+            None => (TcpConnect::new(req.uri).set_addr(req.addr), None),
+        };
 
         if let Some(local_addr) = self.local_address {
-            req = req.set_local_addr(local_addr);
+            conn_req = conn_req.set_local_addr(local_addr);
         }
 
-        TcpConnectorInnerFuture {
-            fut: self.service.call(req),
+        // addresses are resolved up front when they have to be filtered by version or when a
+        // custom resolver is used
+        let unresolved = target.is_some() || req.addr.is_none();
+        let filter = match ip_version {
+            Some(ip_version) if unresolved => Some(ip_version),
+            _ => None,
+        };
+        let resolve = filter.is_some() || (self.resolve && unresolved);
+
+        // connect time of direct connections includes resolving the host name
+        if target.is_none() && !resolve {
+            return TcpConnectorInnerFuture::Direct {
+                fut: self.service.call(conn_req),
+                timings: req.timings.map(|timings| (timings, Instant::now())),
+                timeout: sleep(self.timeout),
+            };
+        }
+
+        let service = self.service.clone();
+        let resolver = self.resolver.clone();
+        let tunnel = self.tunnel;
+        let timings = req.timings;
+
+        TcpConnectorInnerFuture::Boxed {
+            fut: Box::pin(async move {
+                if resolve {
+                    let start = Instant::now();
+                    let resolved = resolver.call(conn_req).await?;
+                    if let Some(ref timings) = timings {
+                        timings.update(|timings| timings.dns = Some(start.elapsed()));
+                    }
+
+                    let addrs = resolved
+                        .addrs()
+                        .filter(|addr| filter.map_or(true, |v| v.matches(addr)))
+                        .collect::<Vec<_>>();
+
+                    if addrs.is_empty() {
+                        return Err(ConnectError::NoRecords);
+                    }
+
+                    conn_req = resolved.set_addrs(addrs);
+                }
+
+                let start = Instant::now();
+                let conn = service.call(conn_req).await?;
+
+                let conn = match target {
+                    None => conn,
+                    Some((proxy, uri, addr)) => {
+                        // socks5 proxies without remote dns are sent the resolved address
+                        let addr = match addr {
+                            Some(addr) => Some(addr.ip()),
+                            None if proxy.resolves_locally() => {
+                                let req = TcpConnect::new(uri.clone())
+                                    .set_port(target_port(&uri));
+                                let resolved = resolver.call(req).await?;
+                                let addr = resolved
+                                    .addrs()
+                                    .find(|addr| {
+                                        ip_version.map_or(true, |v| v.matches(addr))
+                                    })
+                                    .ok_or(ConnectError::NoRecords)?;
+                                Some(addr.ip())
+                            }
+                            None => None,
+                        };
+
+                        let (mut io, _) = conn.into_parts();
+                        proxy.connect(&mut io, &uri, addr, tunnel).await?;
+
+                        TcpConnection::new(io, uri)
+                    }
+                };
+
+                if let Some(timings) = timings {
+                    timings.update(|timings| timings.connect = Some(start.elapsed()));
+                }
+
+                Ok(conn)
+            }),
             timeout: sleep(self.timeout),
         }
     }
 }
 
-#[pin_project]
-pub struct TcpConnectorInnerFuture<Fut> {
-    #[pin]
-    fut: Fut,
-    #[pin]
-    timeout: Sleep,
+#[pin_project(project = TcpConnectorInnerProj)]
+pub enum TcpConnectorInnerFuture<Fut, Io> {
+    Direct {
+        #[pin]
+        fut: Fut,
+        timings: Option<(Timings, Instant)>,
+        #[pin]
+        timeout: Sleep,
+    },
+    Boxed {
+        fut: LocalBoxFuture<'static, Result<TcpConnection<Uri, Io>, ConnectError>>,
+        #[pin]
+        timeout: Sleep,
+    },
 }
 
-impl<Fut, Io> Future for TcpConnectorInnerFuture<Fut>
+impl<Fut, Io> Future for TcpConnectorInnerFuture<Fut, Io>
 where
     Fut: Future<Output = Result<TcpConnection<Uri, Io>, TcpConnectError>>,
 {
     type Output = Result<TcpConnection<Uri, Io>, ConnectError>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        let this = self.project();
-        match this.fut.poll(cx) {
-            Poll::Ready(res) => Poll::Ready(res.map_err(ConnectError::from)),
-            Poll::Pending => this.timeout.poll(cx).map(|_| Err(ConnectError::Timeout)),
+        match self.project() {
+            TcpConnectorInnerProj::Direct {
+                fut,
+                timings,
+                timeout,
+            } => match fut.poll(cx) {
+                Poll::Ready(res) => {
+                    if let Some((timings, start)) = timings.take() {
+                        timings
+                            .update(|timings| timings.connect = Some(start.elapsed()));
+                    }
+                    Poll::Ready(res.map_err(ConnectError::from))
+                }
+                Poll::Pending => timeout.poll(cx).map(|_| Err(ConnectError::Timeout)),
+            },
+            TcpConnectorInnerProj::Boxed { fut, timeout } => match fut.as_mut().poll(cx)
+            {
+                Poll::Ready(res) => Poll::Ready(res),
+                Poll::Pending => timeout.poll(cx).map(|_| Err(ConnectError::Timeout)),
+            },
         }
     }
 }
@@ -607,6 +1240,21 @@ where
 {
     tcp_pool: ConnectionPool<S1, Io1>,
     tls_pool: Option<ConnectionPool<S2, Io2>>,
+    /// Connections of all requests are opened by the tls pool's service.
+    transport: bool,
+}
+
+impl<S1, S2, Io1, Io2> ConnectorServicePriv<S1, S2, Io1, Io2>
+where
+    S1: Service<Connect, Response = (Io1, Protocol), Error = ConnectError>,
+    S2: Service<Connect, Response = (Io2, Protocol), Error = ConnectError>,
+    Io1: ConnectionIo,
+    Io2: ConnectionIo,
+{
+    /// Returns handle to per-host statistics of the connector's connection pools.
+    pub fn pool_stats(&self) -> PoolStats {
+        self.tcp_pool.stats()
+    }
 }
 
 impl<S1, S2, Io1, Io2> Service<Connect> for ConnectorServicePriv<S1, S2, Io1, Io2>
@@ -633,12 +1281,16 @@ where
     }
 
     fn call(&self, req: Connect) -> Self::Future {
-        match req.uri.scheme_str() {
-            Some("https") | Some("wss") => match self.tls_pool {
+        let tls = self.transport
+            || matches!(req.uri.scheme_str(), Some("https") | Some("wss"));
+
+        if tls {
+            match self.tls_pool {
                 None => ConnectorServiceFuture::SslIsNotSupported,
                 Some(ref pool) => ConnectorServiceFuture::Tls(pool.call(req)),
-            },
-            _ => ConnectorServiceFuture::Tcp(self.tcp_pool.call(req)),
+            }
+        } else {
+            ConnectorServiceFuture::Tcp(self.tcp_pool.call(req))
         }
     }
 }
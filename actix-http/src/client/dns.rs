@@ -0,0 +1,299 @@
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    error::Error as StdError,
+    fmt, io,
+    net::{IpAddr, SocketAddr},
+    rc::Rc,
+    time::{Duration, Instant},
+};
+
+use actix_service::Service as _;
+use actix_tls::connect::{Connect as TcpConnect, Resolve, Resolver};
+use futures_core::future::LocalBoxFuture;
+
+/// Cache of resolved host addresses shared by connectors.
+///
+/// Addresses are cached per host name for the [TTL](Self::ttl), and failed lookups are cached for
+/// the [negative TTL](Self::negative_ttl), so frequent requests to the same hosts do not hit the
+/// resolver every time. Once [`max_entries`](Self::max_entries) hosts are cached, expired entries
+/// are dropped first, then the entry closest to expiry.
+///
+/// Cloned handles share the same cache, e.g. for clearing it while connectors use it.
+///
+/// ```
+/// use std::time::Duration;
+/// use actix_http::client::{Connector, DnsCache};
+///
+/// let cache = DnsCache::new()
+///     .ttl(Duration::from_secs(30))
+///     .max_entries(512);
+///
+/// let connector = Connector::new().dns_cache(cache.clone()).finish();
+///
+/// // forget all cached addresses, e.g. after a network change
+/// cache.clear();
+/// ```
+#[derive(Clone)]
+pub struct DnsCache(Rc<DnsCacheInner>);
+
+struct DnsCacheInner {
+    ttl: Duration,
+    negative_ttl: Duration,
+    max_entries: usize,
+    entries: RefCell<HashMap<String, Entry>>,
+}
+
+struct Entry {
+    result: Result<Rc<[IpAddr]>, Rc<str>>,
+    expires: Instant,
+}
+
+impl Default for DnsCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DnsCache {
+    /// Create cache keeping addresses for 60 seconds, failed lookups for 5 seconds and up to 1024
+    /// hosts.
+    pub fn new() -> Self {
+        DnsCache(Rc::new(DnsCacheInner {
+            ttl: Duration::from_secs(60),
+            negative_ttl: Duration::from_secs(5),
+            max_entries: 1024,
+            entries: RefCell::new(HashMap::new()),
+        }))
+    }
+
+    /// Set time addresses are cached for.
+    pub fn ttl(self, ttl: Duration) -> Self {
+        self.configure(|inner| inner.ttl = ttl)
+    }
+
+    /// Set time failed lookups are cached for. Zero disables negative caching.
+    pub fn negative_ttl(self, ttl: Duration) -> Self {
+        self.configure(|inner| inner.negative_ttl = ttl)
+    }
+
+    /// Set maximum number of cached hosts.
+    pub fn max_entries(self, max: usize) -> Self {
+        self.configure(|inner| inner.max_entries = max)
+    }
+
+    /// Remove all cached entries.
+    pub fn clear(&self) {
+        self.0.entries.borrow_mut().clear();
+    }
+
+    /// Remove cached entry of host.
+    pub fn remove(&self, host: &str) {
+        self.0.entries.borrow_mut().remove(host);
+    }
+
+    /// Returns number of cached hosts, including expired entries not removed yet.
+    pub fn len(&self) -> usize {
+        self.0.entries.borrow().len()
+    }
+
+    /// Returns true if no hosts are cached.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    fn configure(mut self, f: impl FnOnce(&mut DnsCacheInner)) -> Self {
+        match Rc::get_mut(&mut self.0) {
+            Some(inner) => f(inner),
+            None => log::error!("DnsCache can not be configured after it is shared"),
+        }
+        self
+    }
+
+    /// Wrap resolver, looking up hosts through the cache.
+    pub(crate) fn resolver(&self, resolver: Resolver) -> Resolver {
+        Resolver::new_custom(CachingResolver {
+            cache: self.clone(),
+            resolver,
+        })
+    }
+
+    fn get(&self, host: &str, now: Instant) -> Option<Result<Rc<[IpAddr]>, Rc<str>>> {
+        let entries = self.0.entries.borrow();
+        let entry = entries.get(host)?;
+
+        if entry.expires > now {
+            Some(entry.result.clone())
+        } else {
+            None
+        }
+    }
+
+    fn insert(&self, host: &str, result: Result<Rc<[IpAddr]>, Rc<str>>, now: Instant) {
+        let ttl = match result {
+            Ok(_) => self.0.ttl,
+            Err(_) => self.0.negative_ttl,
+        };
+
+        if ttl == Duration::from_secs(0) || self.0.max_entries == 0 {
+            return;
+        }
+
+        let mut entries = self.0.entries.borrow_mut();
+
+        if !entries.contains_key(host) && entries.len() >= self.0.max_entries {
+            entries.retain(|_, entry| entry.expires > now);
+
+            if entries.len() >= self.0.max_entries {
+                let oldest = entries
+                    .iter()
+                    .min_by_key(|(_, entry)| entry.expires)
+                    .map(|(host, _)| host.clone());
+
+                if let Some(host) = oldest {
+                    entries.remove(&host);
+                }
+            }
+        }
+
+        entries.insert(
+            host.to_owned(),
+            Entry {
+                result,
+                expires: now + ttl,
+            },
+        );
+    }
+}
+
+impl fmt::Debug for DnsCache {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DnsCache")
+            .field("ttl", &self.0.ttl)
+            .field("negative_ttl", &self.0.negative_ttl)
+            .field("max_entries", &self.0.max_entries)
+            .field("len", &self.len())
+            .finish()
+    }
+}
+
+/// Resolver looking up hosts through a [`DnsCache`] before the wrapped resolver.
+struct CachingResolver {
+    cache: DnsCache,
+    resolver: Resolver,
+}
+
+impl Resolve for CachingResolver {
+    fn lookup<'a>(
+        &'a self,
+        host: &'a str,
+        port: u16,
+    ) -> LocalBoxFuture<'a, Result<Vec<SocketAddr>, Box<dyn StdError>>> {
+        Box::pin(async move {
+            let result = match self.cache.get(host, Instant::now()) {
+                Some(result) => result,
+                None => {
+                    let req = TcpConnect::new(host.to_owned()).set_port(port);
+
+                    let result = match self.resolver.call(req).await {
+                        Ok(res) => {
+                            let ips =
+                                res.addrs().map(|addr| addr.ip()).collect::<Vec<_>>();
+                            Ok(Rc::from(ips))
+                        }
+                        Err(err) => Err(Rc::from(err.to_string())),
+                    };
+
+                    self.cache.insert(host, result.clone(), Instant::now());
+                    result
+                }
+            };
+
+            match result {
+                Ok(ips) => Ok(ips.iter().map(|ip| SocketAddr::new(*ip, port)).collect()),
+                Err(msg) => Err(io::Error::new(io::ErrorKind::Other, &*msg).into()),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    struct CountingResolver(Rc<Cell<usize>>);
+
+    impl Resolve for CountingResolver {
+        fn lookup<'a>(
+            &'a self,
+            host: &'a str,
+            port: u16,
+        ) -> LocalBoxFuture<'a, Result<Vec<SocketAddr>, Box<dyn StdError>>> {
+            self.0.set(self.0.get() + 1);
+
+            Box::pin(async move {
+                match host {
+                    "localhost" => Ok(vec![SocketAddr::from(([127, 0, 0, 1], port))]),
+                    _ => Err("no such host".into()),
+                }
+            })
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_dns_cache() {
+        let count = Rc::new(Cell::new(0));
+
+        let cache = DnsCache::new().max_entries(1);
+        let resolver = CachingResolver {
+            cache: cache.clone(),
+            resolver: Resolver::new_custom(CountingResolver(count.clone())),
+        };
+
+        let res = resolver.lookup("localhost", 80).await.unwrap();
+        assert_eq!(res, vec![SocketAddr::from(([127, 0, 0, 1], 80))]);
+        // cached addresses are used with the requested port
+        let res = resolver.lookup("localhost", 8080).await.unwrap();
+        assert_eq!(res, vec![SocketAddr::from(([127, 0, 0, 1], 8080))]);
+        assert_eq!(count.get(), 1);
+
+        // failed lookups are cached too; full cache evicts an entry
+        assert!(resolver.lookup("unknown", 80).await.is_err());
+        assert!(resolver.lookup("unknown", 80).await.is_err());
+        assert_eq!(count.get(), 2);
+        assert_eq!(cache.len(), 1);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        assert!(resolver.lookup("unknown", 80).await.is_err());
+        assert_eq!(count.get(), 3);
+    }
+
+    #[actix_rt::test]
+    async fn test_dns_cache_expiry() {
+        let count = Rc::new(Cell::new(0));
+
+        let cache = DnsCache::new()
+            .ttl(Duration::from_millis(50))
+            .negative_ttl(Duration::from_secs(0));
+        let resolver = CachingResolver {
+            cache: cache.clone(),
+            resolver: Resolver::new_custom(CountingResolver(count.clone())),
+        };
+
+        resolver.lookup("localhost", 80).await.unwrap();
+        resolver.lookup("localhost", 80).await.unwrap();
+        assert_eq!(count.get(), 1);
+
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+        resolver.lookup("localhost", 80).await.unwrap();
+        assert_eq!(count.get(), 2);
+
+        // negative caching disabled
+        assert!(resolver.lookup("unknown", 80).await.is_err());
+        assert!(resolver.lookup("unknown", 80).await.is_err());
+        assert_eq!(count.get(), 4);
+    }
+}
@@ -5,8 +5,9 @@ use derive_more::{Display, From};
 #[cfg(feature = "openssl")]
 use actix_tls::accept::openssl::SslError;
 
+use super::HttpProtocol;
 use crate::error::{Error, ParseError};
-use crate::http::Error as HttpError;
+use crate::http::{header::HeaderValue, Error as HttpError, StatusCode};
 
 /// A set of errors that can occur while connecting to an HTTP host
 #[derive(Debug, Display, From)]
@@ -37,6 +38,25 @@ pub enum ConnectError {
     #[display(fmt = "Timeout while establishing connection")]
     Timeout,
 
+    /// Waiting for a free connection took too long
+    #[display(fmt = "Timeout while waiting for a free connection")]
+    AcquireTimeout,
+
+    /// Proxy refused to open a tunnel to the host
+    #[display(fmt = "Proxy responded to CONNECT request with status: {}", _0)]
+    #[from(ignore)]
+    ProxyTunnel(StatusCode),
+
+    /// SOCKS5 proxy handshake failed
+    #[display(fmt = "SOCKS5 proxy error: {}", _0)]
+    #[from(ignore)]
+    Socks5(&'static str),
+
+    /// Server did not negotiate the required HTTP protocol with ALPN
+    #[display(fmt = "Server did not negotiate required protocol: {:?}", _0)]
+    #[from(ignore)]
+    ProtocolNotNegotiated(HttpProtocol),
+
     /// Connector has been disconnected
     #[display(fmt = "Internal error: connector has been disconnected")]
     Disconnected,
@@ -126,6 +146,13 @@ pub enum SendRequestError {
 
 impl std::error::Error for SendRequestError {}
 
+/// Allows `?` on results of infallible bodies and services.
+impl From<std::convert::Infallible> for SendRequestError {
+    fn from(err: std::convert::Infallible) -> Self {
+        match err {}
+    }
+}
+
 /// A set of errors that can occur during freezing a request
 #[derive(Debug, Display, From)]
 #[non_exhaustive]
@@ -154,3 +181,60 @@ impl From<FreezeRequestError> for SendRequestError {
         }
     }
 }
+
+/// A set of errors that can occur while opening a WebSocket connection
+#[derive(Debug, Display, From)]
+#[non_exhaustive]
+pub enum WsConnectError {
+    /// Invalid response status
+    #[display(fmt = "Invalid response status: {}", _0)]
+    InvalidResponseStatus(StatusCode),
+
+    /// Invalid upgrade header
+    #[display(fmt = "Invalid upgrade header")]
+    InvalidUpgradeHeader,
+
+    /// Invalid connection header
+    #[display(fmt = "Invalid connection header")]
+    InvalidConnectionHeader(HeaderValue),
+
+    /// Missing Connection header
+    #[display(fmt = "Missing Connection header")]
+    MissingConnectionHeader,
+
+    /// Missing Sec-Websocket-Accept header
+    #[display(fmt = "Missing Sec-Websocket-Accept header")]
+    MissingWebSocketAcceptHeader,
+
+    /// Invalid challenge response
+    #[display(fmt = "Invalid challenge response")]
+    InvalidChallengeResponse([u8; 28], HeaderValue),
+
+    /// Protocol error
+    #[display(fmt = "{}", _0)]
+    Protocol(crate::ws::ProtocolError),
+
+    /// Send request error
+    #[display(fmt = "{}", _0)]
+    SendRequest(SendRequestError),
+}
+
+impl std::error::Error for WsConnectError {}
+
+impl From<InvalidUrl> for WsConnectError {
+    fn from(err: InvalidUrl) -> Self {
+        WsConnectError::SendRequest(err.into())
+    }
+}
+
+impl From<ConnectError> for WsConnectError {
+    fn from(err: ConnectError) -> Self {
+        WsConnectError::SendRequest(err.into())
+    }
+}
+
+impl From<HttpError> for WsConnectError {
+    fn from(err: HttpError) -> Self {
+        WsConnectError::SendRequest(err.into())
+    }
+}
@@ -2,9 +2,11 @@ use std::{
     io::Write,
     pin::Pin,
     task::{Context, Poll},
+    time::Instant,
 };
 
 use actix_codec::Framed;
+use actix_rt::time::timeout;
 use actix_utils::future::poll_fn;
 use bytes::buf::BufMut;
 use bytes::{Bytes, BytesMut};
@@ -13,7 +15,7 @@ use futures_util::SinkExt as _;
 
 use crate::h1;
 use crate::http::{
-    header::{HeaderMap, IntoHeaderValue, EXPECT, HOST},
+    header::{IntoHeaderValue, EXPECT, HOST},
     StatusCode,
 };
 use crate::message::{RequestHeadType, ResponseHead};
@@ -22,10 +24,11 @@ use crate::{error::PayloadError, Error};
 
 use super::connection::{ConnectionIo, H1Connection};
 use super::error::{ConnectError, SendRequestError};
+use super::{ExpectContinue, Timings, Trailers};
 use crate::body::{BodySize, MessageBody};
 
 pub(crate) async fn send_request<Io, B>(
-    io: H1Connection<Io>,
+    mut io: H1Connection<Io>,
     mut head: RequestHeadType,
     body: B,
 ) -> Result<(ResponseHead, Payload), SendRequestError>
@@ -47,28 +50,46 @@ where
             };
 
             match wrt.get_mut().split().freeze().try_into_value() {
-                Ok(value) => match head {
-                    RequestHeadType::Owned(ref mut head) => {
-                        head.headers.insert(HOST, value);
-                    }
-                    RequestHeadType::Rc(_, ref mut extra_headers) => {
-                        let headers = extra_headers.get_or_insert(HeaderMap::new());
-                        headers.insert(HOST, value);
-                    }
-                },
+                Ok(value) => {
+                    head.headers_mut().insert(HOST, value);
+                }
                 Err(e) => log::error!("Can not set HOST header {}", e),
             }
         }
     }
 
+    // requests through a plaintext proxy are sent in absolute-form
+    if let Some(proxy) = io.proxy() {
+        head = proxy.prepare_request(head);
+    }
+
+    // trailers are sent after the last chunk of a chunked body
+    let trailers = head.as_ref().extensions().get::<Trailers>().cloned();
+    let timings = head.as_ref().extensions().get::<Timings>().cloned();
+    let expect = head.as_ref().extensions().get::<ExpectContinue>().copied();
+    let start = Instant::now();
+    let size = match trailers {
+        Some(_) => BodySize::Stream,
+        None => body.size(),
+    };
+
+    // the hook sees the head as it is encoded, e.g. for signing the request
+    if let Some(hook) = io.request_hook() {
+        if let Err(err) = hook(&mut head, size) {
+            io.on_release(true);
+            return Err(err);
+        }
+    }
+
     // create Framed and prepare sending request
-    let mut framed = Framed::new(io, h1::ClientCodec::default());
+    let codec = io.codec();
+    let mut framed = Framed::new(io, codec);
 
     // Check EXPECT header and enable expect handle flag accordingly.
     //
     // RFC: https://tools.ietf.org/html/rfc7231#section-5.1.1
     let is_expect = if head.as_ref().headers.contains_key(EXPECT) {
-        match body.size() {
+        match size {
             BodySize::None | BodySize::Empty | BodySize::Sized(0) => {
                 let keep_alive = framed.codec_ref().keepalive();
                 framed.io_mut().on_release(keep_alive);
@@ -83,40 +104,70 @@ where
         false
     };
 
-    framed.send((head, body.size()).into()).await?;
+    framed.send((head, size).into()).await?;
+    let mut written = Instant::now();
 
     let mut pin_framed = Pin::new(&mut framed);
 
     // special handle for EXPECT request.
     let (do_send, mut res_head) = if is_expect {
-        let head = poll_fn(|cx| pin_framed.as_mut().poll_next(cx))
-            .await
-            .ok_or(ConnectError::Disconnected)??;
+        let interim = poll_fn(|cx| pin_framed.as_mut().poll_next(cx));
+
+        let head = match expect {
+            Some(expect) => timeout(expect.timeout, interim).await.ok(),
+            None => Some(interim.await),
+        };
 
-        // return response head in case status code is not continue
-        // and current head would be used as final response head.
-        (head.status == StatusCode::CONTINUE, Some(head))
+        match head {
+            Some(head) => {
+                let head = head.ok_or(ConnectError::Disconnected)??;
+
+                // return response head in case status code is not continue
+                // and current head would be used as final response head.
+                (head.status == StatusCode::CONTINUE, Some(head))
+            }
+            // server did not respond in time; send body anyway
+            None => (true, None),
+        }
     } else {
         (true, None)
     };
 
     if do_send {
         // send request body
-        match body.size() {
+        match size {
             BodySize::None | BodySize::Empty | BodySize::Sized(0) => {}
-            _ => send_body(body, pin_framed.as_mut()).await?,
+            _ => send_body(body, trailers.as_ref(), pin_framed.as_mut()).await?,
         };
+        written = Instant::now();
 
-        // read response and init read body
-        let head = poll_fn(|cx| pin_framed.as_mut().poll_next(cx))
-            .await
-            .ok_or(ConnectError::Disconnected)??;
+        // read response and init read body; a late interim response is skipped
+        let head = loop {
+            let head = poll_fn(|cx| pin_framed.as_mut().poll_next(cx))
+                .await
+                .ok_or(ConnectError::Disconnected)??;
+
+            if !is_expect || head.status != StatusCode::CONTINUE {
+                break head;
+            }
+        };
 
         res_head = Some(head);
+    } else {
+        // request body was not sent; connection can not be reused
+        pin_framed.codec_mut().force_close();
     }
 
     let head = res_head.unwrap();
 
+    if let Some(timings) = timings {
+        timings.update(|timings| {
+            timings.write = Some(written - start);
+            timings.first_byte = Some(written.elapsed());
+        });
+        head.extensions_mut().insert(timings);
+    }
+
     match pin_framed.codec_ref().message_type() {
         h1::MessageType::None => {
             let keep_alive = pin_framed.codec_ref().keepalive();
@@ -124,7 +175,12 @@ where
 
             Ok((head, Payload::None))
         }
-        _ => Ok((head, Payload::Stream(Box::pin(PlStream::new(framed))))),
+        _ => {
+            let trailers = Trailers::new();
+            head.extensions_mut().insert(trailers.clone());
+            let payload = PlStream::new(framed, trailers);
+            Ok((head, Payload::Stream(Box::pin(payload))))
+        }
     }
 }
 
@@ -150,6 +206,7 @@ where
 /// send request body to the peer
 pub(crate) async fn send_body<Io, B>(
     body: B,
+    trailers: Option<&Trailers>,
     mut framed: Pin<&mut Framed<Io, h1::ClientCodec>>,
 ) -> Result<(), SendRequestError>
 where
@@ -169,6 +226,16 @@ where
                 Some(Err(err)) => return Err(err.into().into()),
                 None => {
                     eof = true;
+                    // trailers set on the request take precedence over the body's
+                    let trailers = match trailers.and_then(Trailers::get) {
+                        Some(trailers) => Some(trailers),
+                        None => poll_fn(|cx| body.as_mut().poll_trailers(cx))
+                            .await
+                            .map_err(Into::<Error>::into)?,
+                    };
+                    if let Some(trailers) = trailers {
+                        framed.codec_mut().set_trailers(trailers);
+                    }
                     framed.as_mut().write(h1::Message::Chunk(None))?;
                 }
             }
@@ -198,13 +265,17 @@ where
 pub(crate) struct PlStream<Io: ConnectionIo> {
     #[pin]
     framed: Framed<H1Connection<Io>, h1::ClientPayloadCodec>,
+    trailers: Trailers,
 }
 
 impl<Io: ConnectionIo> PlStream<Io> {
-    fn new(framed: Framed<H1Connection<Io>, h1::ClientCodec>) -> Self {
+    fn new(
+        framed: Framed<H1Connection<Io>, h1::ClientCodec>,
+        trailers: Trailers,
+    ) -> Self {
         let framed = framed.into_map_codec(|codec| codec.into_payload_codec());
 
-        PlStream { framed }
+        PlStream { framed, trailers }
     }
 }
 
@@ -220,6 +291,9 @@ impl<Io: ConnectionIo> Stream for PlStream<Io> {
         match ready!(this.framed.as_mut().next_item(cx)?) {
             Some(Some(chunk)) => Poll::Ready(Some(Ok(chunk))),
             Some(None) => {
+                if let Some(trailers) = this.framed.codec_mut().take_trailers() {
+                    this.trailers.set(trailers);
+                }
                 let keep_alive = this.framed.codec_ref().keepalive();
                 this.framed.io_mut().on_release(keep_alive);
                 Poll::Ready(None)
@@ -1,7 +1,13 @@
-use std::future::Future;
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Instant,
+};
 
 use actix_utils::future::poll_fn;
 use bytes::Bytes;
+use futures_core::{ready, Stream};
 use h2::{
     client::{Builder, Connection, SendRequest},
     SendStream,
@@ -11,6 +17,7 @@ use http::{request::Request, Method, Version};
 
 use crate::{
     body::{BodySize, MessageBody},
+    error::PayloadError,
     header::HeaderMap,
     message::{RequestHeadType, ResponseHead},
     payload::Payload,
@@ -21,11 +28,12 @@ use super::{
     config::ConnectorConfig,
     connection::{ConnectionIo, H2Connection},
     error::SendRequestError,
+    Timings, Trailers,
 };
 
 pub(crate) async fn send_request<Io, B>(
     mut io: H2Connection<Io>,
-    head: RequestHeadType,
+    mut head: RequestHeadType,
     body: B,
 ) -> Result<(ResponseHead, Payload), SendRequestError>
 where
@@ -37,10 +45,22 @@ where
 
     let head_req = head.as_ref().method == Method::HEAD;
     let length = body.size();
-    let eof = matches!(
-        length,
-        BodySize::None | BodySize::Empty | BodySize::Sized(0)
-    );
+    let trailers = head.as_ref().extensions().get::<Trailers>().cloned();
+    let timings = head.as_ref().extensions().get::<Timings>().cloned();
+    let start = Instant::now();
+    let eof = trailers.is_none()
+        && matches!(
+            length,
+            BodySize::None | BodySize::Empty | BodySize::Sized(0)
+        );
+
+    // the hook sees the head as it is encoded, e.g. for signing the request
+    if let Some(hook) = io.request_hook() {
+        if let Err(err) = hook(&mut head, length) {
+            io.on_release(false);
+            return Err(err);
+        }
+    }
 
     let mut req = Request::new(());
     *req.uri_mut() = head.as_ref().uri.clone();
@@ -109,12 +129,21 @@ where
 
     let resp = match io.send_request(req, eof) {
         Ok((fut, send)) => {
-            io.on_release(false);
-
             if !eof {
-                send_body(body, send).await?;
+                send_body(body, trailers, send).await?;
             }
-            fut.await.map_err(SendRequestError::from)?
+
+            let written = Instant::now();
+            let resp = fut.await.map_err(SendRequestError::from)?;
+
+            if let Some(ref timings) = timings {
+                timings.update(|timings| {
+                    timings.write = Some(written - start);
+                    timings.first_byte = Some(written.elapsed());
+                });
+            }
+
+            resp
         }
         Err(e) => {
             io.on_release(e.is_io());
@@ -123,16 +152,36 @@ where
     };
 
     let (parts, body) = resp.into_parts();
-    let payload = if head_req { Payload::None } else { body.into() };
 
     let mut head = ResponseHead::new(parts.status);
     head.version = parts.version;
     head.headers = parts.headers.into();
+
+    if let Some(timings) = timings {
+        head.extensions_mut().insert(timings);
+    }
+
+    let payload: Payload = if head_req {
+        Payload::None
+    } else {
+        let trailers = Trailers::new();
+        head.extensions_mut().insert(trailers.clone());
+
+        // the stream is counted against the connection's limit until the payload is read
+        let payload = H2Payload {
+            payload: crate::h2::Payload::new(body),
+            trailers,
+            conn: Some(io),
+        };
+        Payload::Stream(Box::pin(payload))
+    };
+
     Ok((head, payload))
 }
 
 async fn send_body<B>(
     body: B,
+    trailers: Option<Trailers>,
     mut send: SendStream<Bytes>,
 ) -> Result<(), SendRequestError>
 where
@@ -150,7 +199,18 @@ where
                 }
                 Some(Err(e)) => return Err(e.into().into()),
                 None => {
-                    if let Err(e) = send.send_data(Bytes::new(), true) {
+                    // trailers set on the request take precedence over the body's
+                    let trailers = match trailers.as_ref().and_then(Trailers::get) {
+                        Some(trailers) => Some(trailers),
+                        None => poll_fn(|cx| body.as_mut().poll_trailers(cx))
+                            .await
+                            .map_err(Into::<Error>::into)?,
+                    };
+                    let res = match trailers {
+                        Some(trailers) => send.send_trailers(trailers.into()),
+                        None => send.send_data(Bytes::new(), true),
+                    };
+                    if let Err(e) = res {
                         return Err(e.into());
                     }
                     send.reserve_capacity(0);
@@ -181,6 +241,42 @@ where
     }
 }
 
+/// Response payload setting the response's trailers once read to the end.
+///
+/// Holds the connection until the payload ends, releasing its stream back to the pool.
+struct H2Payload<Io: ConnectionIo> {
+    payload: crate::h2::Payload,
+    trailers: Trailers,
+    conn: Option<H2Connection<Io>>,
+}
+
+impl<Io: ConnectionIo> Stream for H2Payload<Io> {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        let item = match ready!(Pin::new(&mut this.payload).poll_next(cx)) {
+            None => match ready!(this.payload.poll_trailers(cx)) {
+                Ok(Some(trailers)) => {
+                    this.trailers.set(trailers.into());
+                    None
+                }
+                Ok(None) => None,
+                Err(err) => Some(Err(err)),
+            },
+            Some(Ok(chunk)) => return Poll::Ready(Some(Ok(chunk))),
+            item => item,
+        };
+
+        this.conn = None;
+        Poll::Ready(item)
+    }
+}
+
 pub(crate) fn handshake<Io: ConnectionIo>(
     io: Io,
     config: &ConnectorConfig,
@@ -0,0 +1,218 @@
+use std::{
+    future::Future,
+    pin::Pin,
+    task::{Context, Poll},
+    time::Duration,
+};
+
+use actix_rt::time::{sleep, sleep_until, Instant, Sleep};
+use bytes::Bytes;
+use futures_core::{ready, Stream};
+
+use crate::{error::PayloadError, payload::PayloadStream};
+
+/// Limits applied while streaming a response payload.
+///
+/// Inserted into the request head's extensions. Payloads over the size limit are ended with
+/// [`PayloadError::TooLarge`], and payloads not producing a chunk within the idle timeout are
+/// ended with [`PayloadError::IdleTimeout`]. Both are unlimited by default.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PayloadLimits {
+    /// Maximum size of the payload in bytes.
+    pub max_size: Option<u64>,
+
+    /// Maximum time to wait for the next chunk of the payload, including the first one.
+    pub idle_timeout: Option<Duration>,
+}
+
+impl PayloadLimits {
+    /// Create limits allowing payloads of any size and idle time.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set maximum size of the payload in bytes.
+    pub fn max_size(mut self, max_size: u64) -> Self {
+        self.max_size = Some(max_size);
+        self
+    }
+
+    /// Set maximum time to wait for the next chunk of the payload.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+}
+
+/// Payload stream enforcing [`PayloadLimits`].
+pub(crate) struct LimitedPayload {
+    stream: PayloadStream,
+    remaining: Option<u64>,
+    idle_timeout: Option<Duration>,
+    idle: Option<Pin<Box<Sleep>>>,
+    deadline: Option<Pin<Box<Sleep>>>,
+    oversized: bool,
+    done: bool,
+}
+
+impl LimitedPayload {
+    /// Wrap payload stream. Payloads with a declared length over the size limit fail on first poll.
+    pub(crate) fn new(
+        stream: PayloadStream,
+        limits: PayloadLimits,
+        length: Option<u64>,
+    ) -> Self {
+        let oversized =
+            matches!((limits.max_size, length), (Some(max), Some(len)) if len > max);
+
+        Self {
+            stream,
+            remaining: limits.max_size,
+            idle_timeout: limits.idle_timeout,
+            idle: limits.idle_timeout.map(|dur| Box::pin(sleep(dur))),
+            deadline: None,
+            oversized,
+            done: false,
+        }
+    }
+
+    /// End payload with [`PayloadError::Timeout`] if not read to the end by the deadline.
+    pub(crate) fn deadline(mut self, deadline: Instant) -> Self {
+        self.deadline = Some(Box::pin(sleep_until(deadline)));
+        self
+    }
+
+    fn fail(&mut self, err: PayloadError) -> Poll<Option<Result<Bytes, PayloadError>>> {
+        self.done = true;
+        Poll::Ready(Some(Err(err)))
+    }
+}
+
+impl Stream for LimitedPayload {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        if this.done {
+            return Poll::Ready(None);
+        }
+
+        if this.oversized {
+            return this.fail(PayloadError::TooLarge);
+        }
+
+        if let Some(ref mut deadline) = this.deadline {
+            if deadline.as_mut().poll(cx).is_ready() {
+                return this.fail(PayloadError::Timeout);
+            }
+        }
+
+        match this.stream.as_mut().poll_next(cx) {
+            Poll::Ready(Some(Ok(chunk))) => {
+                if let Some(ref mut remaining) = this.remaining {
+                    match remaining.checked_sub(chunk.len() as u64) {
+                        Some(rem) => *remaining = rem,
+                        None => return this.fail(PayloadError::TooLarge),
+                    }
+                }
+
+                if let (Some(idle), Some(dur)) = (this.idle.as_mut(), this.idle_timeout)
+                {
+                    idle.as_mut().reset(Instant::now() + dur);
+                }
+
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            Poll::Ready(item) => {
+                this.done = item.is_none();
+                Poll::Ready(item)
+            }
+            Poll::Pending => match this.idle.as_mut() {
+                Some(idle) => {
+                    ready!(idle.as_mut().poll(cx));
+                    this.fail(PayloadError::IdleTimeout)
+                }
+                None => Poll::Pending,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::stream::{self, StreamExt as _};
+
+    use super::*;
+
+    fn payload(chunks: &[&'static [u8]]) -> PayloadStream {
+        let chunks = chunks
+            .iter()
+            .map(|chunk| Ok(Bytes::from_static(chunk)))
+            .collect::<Vec<_>>();
+        Box::pin(stream::iter(chunks))
+    }
+
+    #[actix_rt::test]
+    async fn test_size_limit() {
+        let limits = PayloadLimits::new().max_size(8);
+
+        let mut pl = LimitedPayload::new(payload(&[b"abcd", b"efgh"]), limits, None);
+        assert_eq!(pl.next().await.unwrap().unwrap(), "abcd");
+        assert_eq!(pl.next().await.unwrap().unwrap(), "efgh");
+        assert!(pl.next().await.is_none());
+
+        let mut pl = LimitedPayload::new(payload(&[b"abcd", b"efghi"]), limits, None);
+        assert_eq!(pl.next().await.unwrap().unwrap(), "abcd");
+        assert!(matches!(pl.next().await, Some(Err(PayloadError::TooLarge))));
+        assert!(pl.next().await.is_none());
+
+        // declared length is checked before reading
+        let mut pl = LimitedPayload::new(payload(&[b"abcd"]), limits, Some(9));
+        assert!(matches!(pl.next().await, Some(Err(PayloadError::TooLarge))));
+        assert!(pl.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_idle_timeout() {
+        let limits = PayloadLimits::new().idle_timeout(Duration::from_millis(50));
+
+        let mut pl = LimitedPayload::new(payload(&[b"abcd"]), limits, None);
+        assert_eq!(pl.next().await.unwrap().unwrap(), "abcd");
+        assert!(pl.next().await.is_none());
+
+        let mut pl = LimitedPayload::new(
+            Box::pin(stream::pending::<Result<Bytes, PayloadError>>()),
+            limits,
+            None,
+        );
+        assert!(matches!(
+            pl.next().await,
+            Some(Err(PayloadError::IdleTimeout))
+        ));
+        assert!(pl.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn test_deadline() {
+        let deadline = Instant::now() + Duration::from_millis(50);
+
+        let mut pl =
+            LimitedPayload::new(payload(&[b"abcd"]), PayloadLimits::new(), None)
+                .deadline(deadline);
+        assert_eq!(pl.next().await.unwrap().unwrap(), "abcd");
+        assert!(pl.next().await.is_none());
+
+        let mut pl = LimitedPayload::new(
+            Box::pin(stream::pending::<Result<Bytes, PayloadError>>()),
+            PayloadLimits::new(),
+            None,
+        )
+        .deadline(deadline);
+        assert!(matches!(pl.next().await, Some(Err(PayloadError::Timeout))));
+        assert!(pl.next().await.is_none());
+    }
+}
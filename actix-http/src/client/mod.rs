@@ -1,26 +1,285 @@
 //! HTTP client.
 
+use std::{
+    cell::{Cell, RefCell},
+    net::{IpAddr, SocketAddr},
+    rc::Rc,
+    time::Duration,
+};
+
 use http::Uri;
 
+use crate::header::HeaderMap;
+
+#[cfg(any(target_os = "android", target_os = "fuchsia", target_os = "linux"))]
+mod bind;
 mod config;
 mod connection;
 mod connector;
+mod dns;
 mod error;
 mod h1proto;
 mod h2proto;
+mod limits;
 mod pool;
+mod proxy;
+mod tls;
+mod ws;
 
 pub use actix_tls::connect::{
     Connect as TcpConnect, ConnectError as TcpConnectError, Connection as TcpConnection,
+    Resolve,
 };
 
 pub use self::connection::{Connection, ConnectionIo};
 pub use self::connector::{Connector, ConnectorService};
-pub use self::error::{ConnectError, FreezeRequestError, InvalidUrl, SendRequestError};
+pub use self::dns::DnsCache;
+pub use self::error::{
+    ConnectError, FreezeRequestError, InvalidUrl, SendRequestError, WsConnectError,
+};
+pub use self::limits::PayloadLimits;
+pub use self::pool::{HostStats, PoolStats};
+pub use self::proxy::Proxy;
+pub use self::tls::CertVerifier;
+pub use self::ws::{ws_connect, ws_connect_with};
 pub use crate::Protocol;
 
 #[derive(Clone)]
 pub struct Connect {
     pub uri: Uri,
     pub addr: Option<std::net::SocketAddr>,
+    /// IP version of addresses to connect to, overrides the connector's setting.
+    pub ip_version: Option<IpVersion>,
+    /// HTTP protocol to use, overrides the connector's setting.
+    pub protocol: Option<HttpProtocol>,
+    timings: Option<Timings>,
+}
+
+impl Connect {
+    /// Create connect request for the given URI.
+    pub fn new(uri: Uri) -> Self {
+        Self {
+            uri,
+            addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
+        }
+    }
+
+    /// Record resolve, connect and TLS handshake times of a new connection in `timings`.
+    pub fn set_timings(&mut self, timings: Timings) {
+        self.timings = Some(timings);
+    }
+
+    /// Returns handle the times of a new connection are recorded in.
+    pub fn timings(&self) -> Option<&Timings> {
+        self.timings.as_ref()
+    }
+}
+
+/// IP version of the addresses client connections are opened to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum IpVersion {
+    /// Only connect to IPv4 addresses.
+    V4,
+
+    /// Only connect to IPv6 addresses.
+    V6,
+}
+
+impl IpVersion {
+    /// Returns IP version of the address.
+    pub fn of(addr: &IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(_) => IpVersion::V4,
+            IpAddr::V6(_) => IpVersion::V6,
+        }
+    }
+
+    /// Returns true if the socket address is of this IP version.
+    pub fn matches(self, addr: &SocketAddr) -> bool {
+        IpVersion::of(&addr.ip()) == self
+    }
+}
+
+/// HTTP protocol selection of client connections.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HttpProtocol {
+    /// Use HTTP/1.1 over plain connections and the protocol negotiated with ALPN over TLS.
+    Negotiate,
+
+    /// Only use HTTP/1.1.
+    Http1,
+
+    /// Only use HTTP/2; plain connections use HTTP/2 with prior knowledge.
+    Http2,
+}
+
+impl Default for HttpProtocol {
+    fn default() -> Self {
+        HttpProtocol::Negotiate
+    }
+}
+
+impl HttpProtocol {
+    /// Returns ALPN protocols offered over TLS, in order of preference.
+    pub(crate) fn alpn(self) -> Vec<Vec<u8>> {
+        match self {
+            HttpProtocol::Negotiate => vec![b"h2".to_vec(), b"http/1.1".to_vec()],
+            HttpProtocol::Http1 => vec![b"http/1.1".to_vec()],
+            HttpProtocol::Http2 => vec![b"h2".to_vec()],
+        }
+    }
+}
+
+/// Limits how long requests with an `Expect: 100-continue` header wait for the interim
+/// `100 Continue` response before sending their body.
+///
+/// Inserted into the request head's extensions. The body is sent once the timeout elapses without
+/// a response, and not at all if the server responds with a final status, e.g. `417 Expectation
+/// Failed` or `413 Payload Too Large`. Without a timeout, requests wait until the server responds.
+///
+/// Expectations are only handled over HTTP/1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExpectContinue {
+    /// Time to wait for the interim response.
+    pub timeout: Duration,
+}
+
+impl ExpectContinue {
+    /// Create expectation waiting for the interim response up to the given time.
+    pub fn new(timeout: Duration) -> Self {
+        Self { timeout }
+    }
+}
+
+/// Deadline of a request, covering sending it and receiving the response head.
+///
+/// Inserted into the request head's extensions. Unlike the connector's timeout, which only covers
+/// opening connections, the deadline starts once a connection is acquired. Requests without a
+/// response head by then fail with [`SendRequestError::Timeout`], and their connection is closed
+/// over HTTP/1 while only the stream is reset over HTTP/2.
+///
+/// If the deadline includes the body, payloads not read to the end by then are ended with
+/// [`PayloadError::Timeout`](crate::error::PayloadError::Timeout).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequestTimeout {
+    /// Time from sending the request until the deadline.
+    pub timeout: Duration,
+
+    /// Whether the deadline also covers reading the response payload.
+    pub include_body: bool,
+}
+
+impl RequestTimeout {
+    /// Create deadline covering the request and response head, but not the payload.
+    pub fn new(timeout: Duration) -> Self {
+        Self {
+            timeout,
+            include_body: false,
+        }
+    }
+
+    /// Set whether the deadline also covers reading the response payload.
+    pub fn include_body(mut self, include_body: bool) -> Self {
+        self.include_body = include_body;
+        self
+    }
+}
+
+/// Trailer fields sent after a request or response body.
+///
+/// Cloned handles share the same fields, so request trailers can still be set while the body is
+/// streamed. Requests with trailers are sent with chunked transfer encoding over HTTP/1.
+///
+/// Set request trailers by inserting a handle into the request head's extensions. Response
+/// trailers are available from the handle in the response head's extensions once the payload is
+/// read to the end.
+#[derive(Debug, Clone, Default)]
+pub struct Trailers(Rc<RefCell<Option<HeaderMap>>>);
+
+impl Trailers {
+    /// Create handle without trailer fields.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set trailer fields.
+    pub fn set(&self, trailers: HeaderMap) {
+        *self.0.borrow_mut() = Some(trailers);
+    }
+
+    /// Returns copy of trailer fields, if set.
+    pub fn get(&self) -> Option<HeaderMap> {
+        self.0.borrow().clone()
+    }
+}
+
+/// Timing breakdown of a client request.
+///
+/// Steps that were not taken have no duration, e.g. connection steps of requests sent over a
+/// pooled connection. Cloned handles share the same timings.
+///
+/// Timings are opt-in. They are recorded for requests with a handle in the request head's
+/// extensions and for connections opened with a handle set with [`Connect::set_timings`]. The
+/// handle is also inserted into the response head's extensions.
+#[derive(Debug, Clone, Default)]
+pub struct Timings(Rc<Cell<TimingsInner>>);
+
+#[derive(Debug, Clone, Copy, Default)]
+pub(crate) struct TimingsInner {
+    pub(crate) acquire: Option<Duration>,
+    pub(crate) dns: Option<Duration>,
+    pub(crate) connect: Option<Duration>,
+    pub(crate) tls: Option<Duration>,
+    pub(crate) write: Option<Duration>,
+    pub(crate) first_byte: Option<Duration>,
+}
+
+impl Timings {
+    /// Create handle without recorded timings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Time spent acquiring a connection from the pool, including waiting for the connection
+    /// limit and opening a new connection.
+    pub fn acquire(&self) -> Option<Duration> {
+        self.0.get().acquire
+    }
+
+    /// Time spent resolving the host name.
+    ///
+    /// Only recorded when the host name is resolved before connecting, i.e. with a custom
+    /// resolver or an IP version filter. Otherwise resolving is part of the connect time.
+    pub fn dns(&self) -> Option<Duration> {
+        self.0.get().dns
+    }
+
+    /// Time spent opening the TCP connection, including proxy handshakes.
+    pub fn connect(&self) -> Option<Duration> {
+        self.0.get().connect
+    }
+
+    /// Time spent on the TLS handshake.
+    pub fn tls(&self) -> Option<Duration> {
+        self.0.get().tls
+    }
+
+    /// Time spent writing the request head and body.
+    pub fn write(&self) -> Option<Duration> {
+        self.0.get().write
+    }
+
+    /// Time from writing the request until the response head is received.
+    pub fn first_byte(&self) -> Option<Duration> {
+        self.0.get().first_byte
+    }
+
+    pub(crate) fn update(&self, f: impl FnOnce(&mut TimingsInner)) {
+        let mut timings = self.0.get();
+        f(&mut timings);
+        self.0.set(timings);
+    }
 }
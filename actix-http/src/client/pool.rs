@@ -1,7 +1,7 @@
 //! Client connection pooling keyed on the authority part of the connection URI.
 
 use std::{
-    cell::RefCell,
+    cell::{Cell, RefCell},
     collections::VecDeque,
     future::Future,
     io,
@@ -14,31 +14,158 @@ use std::{
 };
 
 use actix_codec::{AsyncRead, AsyncWrite, ReadBuf};
-use actix_rt::time::{sleep, Sleep};
+use actix_rt::time::{sleep, sleep_until, timeout, Sleep};
 use actix_service::Service;
 use ahash::AHashMap;
 use futures_core::future::LocalBoxFuture;
+use futures_util::future::{select, Either};
 use http::uri::Authority;
 use pin_project::pin_project;
-use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tokio::sync::{Notify, OwnedSemaphorePermit, Semaphore};
 
-use super::config::ConnectorConfig;
+use crate::h1::ClientCodec;
+
+use super::config::{ConnectorConfig, RequestHook};
 use super::connection::{
     ConnectionInnerType, ConnectionIo, ConnectionType, H2ConnectionInner,
 };
 use super::error::ConnectError;
 use super::h2proto::handshake;
+use super::proxy::Proxy;
 use super::Connect;
-use super::Protocol;
+use super::{HttpProtocol, IpVersion, Protocol};
 
+/// Connections are pooled per host, HTTP protocol selection and IP version.
 #[derive(Hash, Eq, PartialEq, Clone, Debug)]
 pub struct Key {
     authority: Authority,
+    protocol: HttpProtocol,
+    ip_version: Option<IpVersion>,
+}
+
+/// Connection statistics of a single host, as returned by [`PoolStats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HostStats {
+    /// Number of idle connections kept in the pool.
+    pub idle: usize,
+
+    /// Number of connections currently in use, including connections being established.
+    ///
+    /// HTTP/2 connections shared by concurrent requests are counted once per request.
+    pub in_flight: usize,
+
+    /// Number of requests waiting for the pool's connection limit.
+    pub waiters: usize,
+
+    /// Number of connections that have been acquired from the pool.
+    pub acquired: u64,
+
+    /// Sum of the time taken to acquire connections.
+    ///
+    /// Acquire time includes waiting for the connection limit, checking idle connections and
+    /// establishing new connections.
+    pub total_acquire_latency: Duration,
+
+    /// Longest time taken to acquire a connection.
+    pub max_acquire_latency: Duration,
+}
+
+impl HostStats {
+    fn record_acquire(&mut self, latency: Duration) {
+        self.acquired += 1;
+        self.total_acquire_latency += latency;
+        self.max_acquire_latency = self.max_acquire_latency.max(latency);
+    }
+
+    /// Returns mean time taken to acquire a connection, or `None` if none have been acquired.
+    pub fn mean_acquire_latency(&self) -> Option<Duration> {
+        if self.acquired == 0 {
+            return None;
+        }
+
+        let nanos = self.total_acquire_latency.as_nanos() / u128::from(self.acquired);
+        Some(Duration::from_nanos(nanos as u64))
+    }
+}
+
+/// Handle to per-host statistics of a client connection pool.
+///
+/// Handles are cheap to clone and always reflect the current state of the pool, so one can be
+/// kept around, e.g. to periodically report metrics or alert on pool exhaustion.
+///
+/// # Examples
+/// ```
+/// use actix_http::client::Connector;
+///
+/// # actix_rt::System::new().block_on(async {
+/// let connector = Connector::new().finish();
+/// let stats = connector.pool_stats();
+///
+/// let host = stats.host(&"example.com:443".parse().unwrap());
+/// assert_eq!(host.in_flight, 0);
+/// # });
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct PoolStats {
+    hosts: Rc<RefCell<AHashMap<Authority, HostStats>>>,
+}
+
+impl PoolStats {
+    /// Returns statistics of connections to given authority.
+    ///
+    /// Hosts that have never been connected to report zeroed statistics.
+    pub fn host(&self, authority: &Authority) -> HostStats {
+        self.hosts
+            .borrow()
+            .get(authority)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Returns statistics of all hosts that have been connected to.
+    pub fn hosts(&self) -> Vec<(Authority, HostStats)> {
+        self.hosts
+            .borrow()
+            .iter()
+            .map(|(authority, stats)| (authority.clone(), *stats))
+            .collect()
+    }
+
+    /// Returns number of requests, across all hosts, waiting for the pool's connection limit.
+    pub fn waiters(&self) -> usize {
+        self.hosts
+            .borrow()
+            .values()
+            .map(|stats| stats.waiters)
+            .sum()
+    }
+
+    fn update(&self, key: &Key, f: impl FnOnce(&mut HostStats)) {
+        let mut hosts = self.hosts.borrow_mut();
+
+        match hosts.get_mut(&key.authority) {
+            Some(stats) => f(stats),
+            None => f(hosts.entry(key.authority.clone()).or_default()),
+        }
+    }
+}
+
+/// Counts a request as waiting for a permit until dropped.
+struct Waiter<'a> {
+    stats: &'a PoolStats,
+    key: &'a Key,
+}
+
+impl<'a> Waiter<'a> {
+    fn new(stats: &'a PoolStats, key: &'a Key) -> Self {
+        stats.update(key, |stats| stats.waiters += 1);
+        Waiter { stats, key }
+    }
 }
 
-impl From<Authority> for Key {
-    fn from(authority: Authority) -> Key {
-        Key { authority }
+impl Drop for Waiter<'_> {
+    fn drop(&mut self) {
+        self.stats.update(self.key, |stats| stats.waiters -= 1);
     }
 }
 
@@ -61,25 +188,153 @@ impl<Io> ConnectionPoolInner<Io>
 where
     Io: AsyncWrite + Unpin + 'static,
 {
-    fn new(config: ConnectorConfig) -> Self {
+    fn new(config: ConnectorConfig, stats: PoolStats) -> Self {
         let permits = Arc::new(Semaphore::new(config.limit));
         let available = RefCell::new(AHashMap::default());
+        let host_permits = RefCell::new(AHashMap::default());
 
         Self(Rc::new(ConnectionPoolInnerPriv {
             config,
             available,
+            shared: RefCell::new(AHashMap::default()),
+            shared_notify: Notify::new(),
             permits,
+            host_permits,
+            stats,
+            sweeping: Cell::new(false),
         }))
     }
 
-    /// spawn a async for graceful shutdown h1 Io type with a timeout.
-    fn close(&self, conn: ConnectionInnerType<Io>) {
-        if let Some(timeout) = self.config.disconnect_timeout {
-            if let ConnectionInnerType::H1(io) = conn {
-                actix_rt::spawn(CloseConnection::new(io, timeout));
+    /// Spawn task that closes idle connections once they exceed keep-alive or lifetime, unless
+    /// one is running already. The task ends when no idle connections are left.
+    fn schedule_sweep(&self) {
+        if self.sweeping.replace(true) {
+            return;
+        }
+
+        let pool = Rc::downgrade(&self.0);
+
+        actix_rt::spawn(async move {
+            loop {
+                let next = match pool.upgrade() {
+                    Some(pool) => pool.evict_expired(Instant::now()),
+                    None => return,
+                };
+
+                match next {
+                    Some(deadline) => sleep_until(deadline.into()).await,
+                    None => return,
+                }
+            }
+        });
+    }
+
+    /// Acquire owned permits for a connection to given host.
+    ///
+    /// Waits for the per-host limit first so requests to a busy host do not hold on to permits
+    /// of the total limit.
+    async fn acquire_permits(&self, key: &Key) -> Result<Permits, ConnectError> {
+        let host_permit = match self.config.limit_per_host {
+            0 => None,
+            limit => {
+                let permits = {
+                    let mut host_permits = self.host_permits.borrow_mut();
+
+                    match host_permits.get(&key.authority) {
+                        Some(permits) => permits.clone(),
+                        None => {
+                            // remove semaphores of hosts without held or awaited permits, so they
+                            // do not accumulate for every host ever connected to
+                            host_permits
+                                .retain(|_, permits| Arc::strong_count(permits) > 1);
+
+                            let permits = Arc::new(Semaphore::new(limit));
+                            host_permits.insert(key.authority.clone(), permits.clone());
+                            permits
+                        }
+                    }
+                };
+
+                Some(permits.acquire_owned().await.map_err(semaphore_closed)?)
+            }
+        };
+
+        let permit = self
+            .permits
+            .clone()
+            .acquire_owned()
+            .await
+            .map_err(semaphore_closed)?;
+
+        Ok(Permits {
+            permit,
+            host_permit,
+        })
+    }
+
+    /// Acquire permits for a new connection, unless an HTTP/2 connection in use has capacity for
+    /// the request, including connections shared while waiting for the permits.
+    async fn acquire_permits_or_shared(
+        &self,
+        key: &Key,
+    ) -> Result<Either<Permits, SharedStream>, ConnectError> {
+        loop {
+            let notified = self.shared_notify.notified();
+
+            if let Some(stream) = self.acquire_shared(key) {
+                return Ok(Either::Right(stream));
+            }
+
+            let permits = self.acquire_permits(key);
+            actix_rt::pin!(notified, permits);
+
+            match select(permits, notified).await {
+                Either::Left((permits, _)) => return permits.map(Either::Left),
+                Either::Right(_) => continue,
             }
         }
     }
+
+    /// Take a stream of an HTTP/2 connection in use that has capacity for another request.
+    fn acquire_shared(&self, key: &Key) -> Option<SharedStream> {
+        let mut shared = self.shared.borrow_mut();
+        let conns = shared.get_mut(key)?;
+        let now = Instant::now();
+
+        let conn = conns.iter_mut().find(|conn| {
+            conn.created + self.config.conn_lifetime > now
+                && conn.streams < conn.conn.max_streams()
+        })?;
+        conn.streams += 1;
+
+        Some(SharedStream {
+            conn: conn.conn.clone(),
+            created: conn.created,
+            permits: conn.permits.clone(),
+        })
+    }
+
+    /// Share HTTP/2 connection taken by a request with concurrent requests.
+    fn share(
+        &self,
+        key: &Key,
+        conn: &H2ConnectionInner,
+        created: Instant,
+        permits: &Rc<Permits>,
+    ) {
+        self.shared
+            .borrow_mut()
+            .entry(key.clone())
+            .or_default()
+            .push(SharedConnection {
+                conn: conn.clone(),
+                created,
+                permits: permits.clone(),
+                streams: 1,
+            });
+
+        self.shared_notify.notify_waiters();
+    }
 }
 
 impl<Io> Clone for ConnectionPoolInner<Io>
@@ -111,9 +366,14 @@ where
         // remove and drop all Io types.
         if Rc::strong_count(&self.0) == 1 {
             self.permits.close();
+            self.host_permits
+                .borrow()
+                .values()
+                .for_each(|permits| permits.close());
             std::mem::take(&mut *self.available.borrow_mut())
                 .into_iter()
-                .for_each(|(_, conns)| {
+                .for_each(|(key, conns)| {
+                    self.stats.update(&key, |stats| stats.idle -= conns.len());
                     conns.into_iter().for_each(|pooled| self.close(pooled.conn))
                 });
         }
@@ -126,7 +386,64 @@ where
 {
     config: ConnectorConfig,
     available: RefCell<AHashMap<Key, VecDeque<PooledConnection<Io>>>>,
+    shared: RefCell<AHashMap<Key, Vec<SharedConnection>>>,
+    shared_notify: Notify,
     permits: Arc<Semaphore>,
+    host_permits: RefCell<AHashMap<Authority, Arc<Semaphore>>>,
+    stats: PoolStats,
+    sweeping: Cell<bool>,
+}
+
+impl<Io> ConnectionPoolInnerPriv<Io>
+where
+    Io: AsyncWrite + Unpin + 'static,
+{
+    /// spawn a async for graceful shutdown h1 Io type with a timeout.
+    fn close(&self, conn: ConnectionInnerType<Io>) {
+        if let Some(timeout) = self.config.disconnect_timeout {
+            if let ConnectionInnerType::H1(io) = conn {
+                actix_rt::spawn(CloseConnection::new(io, timeout));
+            }
+        }
+    }
+
+    /// Close idle connections that exceeded keep-alive or lifetime.
+    ///
+    /// Returns when the next of the remaining idle connections expires, or `None` after marking
+    /// sweeping as done if there are none.
+    fn evict_expired(&self, now: Instant) -> Option<Instant> {
+        let mut next = None::<Instant>;
+        let mut expired = Vec::new();
+
+        self.available.borrow_mut().retain(|key, conns| {
+            let mut idle = VecDeque::with_capacity(conns.len());
+
+            for conn in conns.drain(..) {
+                let expires = conn.expires(&self.config);
+
+                if expires <= now {
+                    self.stats.update(key, |stats| stats.idle -= 1);
+                    expired.push(conn.conn);
+                } else {
+                    next = Some(next.map_or(expires, |next| next.min(expires)));
+                    idle.push_back(conn);
+                }
+            }
+
+            *conns = idle;
+            !conns.is_empty()
+        });
+
+        for conn in expired {
+            self.close(conn);
+        }
+
+        if next.is_none() {
+            self.sweeping.set(false);
+        }
+
+        next
+    }
 }
 
 impl<S, Io> ConnectionPool<S, Io>
@@ -144,10 +461,24 @@ where
     /// Any requests beyond limit would be wait in fifo order and get notified in async manner
     /// by [`tokio::sync::Semaphore`]
     pub(crate) fn new(connector: S, config: ConnectorConfig) -> Self {
-        let inner = ConnectionPoolInner::new(config);
+        Self::with_stats(connector, config, PoolStats::default())
+    }
+
+    /// Construct a new connection pool that records statistics into given handle.
+    pub(crate) fn with_stats(
+        connector: S,
+        config: ConnectorConfig,
+        stats: PoolStats,
+    ) -> Self {
+        let inner = ConnectionPoolInner::new(config, stats);
 
         Self { connector, inner }
     }
+
+    /// Returns handle to per-host statistics of this pool.
+    pub fn stats(&self) -> PoolStats {
+        self.inner.stats.clone()
+    }
 }
 
 impl<S, Io> Service<Connect> for ConnectionPool<S, Io>
@@ -168,19 +499,49 @@ where
         let inner = self.inner.clone();
 
         Box::pin(async move {
+            let start = Instant::now();
+            let timings = req.timings.clone();
+
             let key = if let Some(authority) = req.uri.authority() {
-                authority.clone().into()
+                Key {
+                    authority: authority.clone(),
+                    protocol: req.protocol.unwrap_or(inner.config.protocol),
+                    ip_version: req.ip_version.or(inner.config.ip_version),
+                }
             } else {
                 return Err(ConnectError::Unresolved);
             };
 
-            // acquire an owned permit and carry it with connection
-            let permit = inner.permits.clone().acquire_owned().await.map_err(|_| {
-                ConnectError::Io(io::Error::new(
-                    io::ErrorKind::Other,
-                    "failed to acquire semaphore on client connection pool",
-                ))
-            })?;
+            // acquire owned permits and carry them with connection; requests sharing HTTP/2
+            // connections in use share their permits too.
+            let waiter = Waiter::new(&inner.stats, &key);
+            let permits = inner.acquire_permits_or_shared(&key);
+            let permits = match inner.config.acquire_timeout {
+                Some(dur) => timeout(dur, permits)
+                    .await
+                    .map_err(|_| ConnectError::AcquireTimeout)??,
+                None => permits.await?,
+            };
+            drop(waiter);
+
+            let permits = match permits {
+                Either::Left(permits) => Rc::new(permits),
+                Either::Right(stream) => {
+                    let stats = inner.stats.clone();
+                    let acquired = Acquired::new(key.clone(), inner, stream.permits);
+                    let conn =
+                        ConnectionType::from_h2(stream.conn, stream.created, acquired);
+
+                    let latency = start.elapsed();
+                    stats.update(&key, |stats| stats.record_acquire(latency));
+
+                    if let Some(timings) = timings {
+                        timings.update(|timings| timings.acquire = Some(latency));
+                    }
+
+                    return Ok(conn);
+                }
+            };
 
             let conn = {
                 let mut conn = None;
@@ -192,29 +553,28 @@ where
                     let now = Instant::now();
 
                     while let Some(mut c) = conns.pop_front() {
-                        let config = &inner.config;
-                        let idle_dur = now - c.used;
-                        let age = now - c.created;
-                        let conn_ineligible = idle_dur > config.conn_keep_alive
-                            || age > config.conn_lifetime;
+                        inner.stats.update(&key, |stats| stats.idle -= 1);
 
-                        if conn_ineligible {
+                        if c.expires(&inner.config) <= now {
                             // drop connections that are too old
                             inner.close(c.conn);
                         } else {
                             // check if the connection is still usable
-                            if let ConnectionInnerType::H1(ref mut io) = c.conn {
-                                let check = ConnectionCheckFuture { io };
-                                match check.await {
-                                    ConnectionState::Tainted => {
-                                        inner.close(c.conn);
-                                        continue;
+                            match c.conn {
+                                ConnectionInnerType::H1(ref mut io)
+                                    if inner.config.conn_validation =>
+                                {
+                                    let check = ConnectionCheckFuture { io };
+                                    match check.await {
+                                        ConnectionState::Tainted => {
+                                            inner.close(c.conn);
+                                            continue;
+                                        }
+                                        ConnectionState::Skip => continue,
+                                        ConnectionState::Live => conn = Some(c),
                                     }
-                                    ConnectionState::Skip => continue,
-                                    ConnectionState::Live => conn = Some(c),
                                 }
-                            } else {
-                                conn = Some(c);
+                                _ => conn = Some(c),
                             }
 
                             break;
@@ -227,13 +587,12 @@ where
 
             // construct acquired. It's used to put Io type back to pool/ close the Io type.
             // permit is carried with the whole lifecycle of Acquired.
-            let acquired = Acquired { key, inner, permit };
+            let stats = inner.stats.clone();
+            let acquired = Acquired::new(key.clone(), inner.clone(), permits.clone());
 
             // match the connection and spawn new one if did not get anything.
-            match conn {
-                Some(conn) => {
-                    Ok(ConnectionType::from_pool(conn.conn, conn.created, acquired))
-                }
+            let (conn, created) = match conn {
+                Some(conn) => (conn.conn, conn.created),
                 None => {
                     let (io, proto) = connector.call(req).await?;
 
@@ -241,19 +600,41 @@ where
                     assert!(proto != Protocol::Http3);
 
                     if proto == Protocol::Http1 {
-                        Ok(ConnectionType::from_h1(io, Instant::now(), acquired))
+                        (ConnectionInnerType::H1(io), Instant::now())
                     } else {
-                        let config = &acquired.inner.config;
-                        let (sender, connection) = handshake(io, config).await?;
-                        let inner = H2ConnectionInner::new(sender, connection);
-                        Ok(ConnectionType::from_h2(inner, Instant::now(), acquired))
+                        let (sender, connection) = handshake(io, &inner.config).await?;
+                        let conn = H2ConnectionInner::new(sender, connection);
+                        (ConnectionInnerType::H2(conn), Instant::now())
                     }
                 }
+            };
+
+            // HTTP/2 connections are shared until all their requests are done
+            if let ConnectionInnerType::H2(ref conn) = conn {
+                inner.share(&key, conn, created, &permits);
+            }
+
+            let conn = ConnectionType::from_pool(conn, created, acquired);
+
+            let latency = start.elapsed();
+            stats.update(&key, |stats| stats.record_acquire(latency));
+
+            if let Some(timings) = timings {
+                timings.update(|timings| timings.acquire = Some(latency));
             }
+
+            Ok(conn)
         })
     }
 }
 
+fn semaphore_closed<E>(_: E) -> ConnectError {
+    ConnectError::Io(io::Error::new(
+        io::ErrorKind::Other,
+        "failed to acquire semaphore on client connection pool",
+    ))
+}
+
 /// Type for check the connection and determine if it's usable.
 struct ConnectionCheckFuture<'a, Io> {
     io: &'a mut Io,
@@ -296,12 +677,40 @@ where
     }
 }
 
+/// Permits of the pool's connection limits, held while a connection is in use.
+struct Permits {
+    permit: OwnedSemaphorePermit,
+    host_permit: Option<OwnedSemaphorePermit>,
+}
+
+/// Stream of a shared HTTP/2 connection taken by a request.
+struct SharedStream {
+    conn: H2ConnectionInner,
+    created: Instant,
+    permits: Rc<Permits>,
+}
+
+/// HTTP/2 connection in use, taken by concurrent requests up to the peer's stream limit.
+struct SharedConnection {
+    conn: H2ConnectionInner,
+    created: Instant,
+    permits: Rc<Permits>,
+    streams: usize,
+}
+
 struct PooledConnection<Io> {
     conn: ConnectionInnerType<Io>,
     used: Instant,
     created: Instant,
 }
 
+impl<Io> PooledConnection<Io> {
+    /// Returns when connection exceeds keep-alive or lifetime.
+    fn expires(&self, config: &ConnectorConfig) -> Instant {
+        (self.used + config.conn_keep_alive).min(self.created + config.conn_lifetime)
+    }
+}
+
 #[pin_project]
 struct CloseConnection<Io> {
     io: Io,
@@ -345,13 +754,49 @@ where
     key: Key,
     /// handle to connection pool.
     inner: ConnectionPoolInner<Io>,
-    /// permit for limit concurrent in-flight connection for a Client object.
-    permit: OwnedSemaphorePermit,
+    /// permits for limit concurrent in-flight connections for a Client object and to the host,
+    /// shared by requests on the same HTTP/2 connection.
+    permits: Rc<Permits>,
 }
 
 impl<Io: ConnectionIo> Acquired<Io> {
+    fn new(key: Key, inner: ConnectionPoolInner<Io>, permits: Rc<Permits>) -> Self {
+        inner.stats.update(&key, |stats| stats.in_flight += 1);
+
+        Acquired {
+            key,
+            inner,
+            permits,
+        }
+    }
+
+    /// Returns proxy that requests on the connection are sent to.
+    pub(super) fn proxy(&self) -> Option<&Proxy> {
+        self.inner.config.proxy.as_ref()
+    }
+
+    /// Returns hook called before requests are sent on the connection.
+    pub(super) fn request_hook(&self) -> Option<&RequestHook> {
+        self.inner.config.request_hook.as_ref()
+    }
+
+    /// Returns HTTP/1 codec decoding responses within the configured limits.
+    pub(super) fn h1_codec(&self) -> ClientCodec {
+        ClientCodec::default()
+            .max_head_size(self.inner.config.max_head_size)
+            .max_headers(self.inner.config.max_headers)
+            .capture_raw_head(self.inner.config.capture_raw_head)
+    }
+
     /// Close the IO.
     pub(super) fn close(&self, conn: ConnectionInnerType<Io>) {
+        if let ConnectionInnerType::H2(ref conn) = conn {
+            // stop sharing the connection, requests using it still hold their handles
+            if let Some(conns) = self.inner.shared.borrow_mut().get_mut(&self.key) {
+                conns.retain(|shared| !shared.conn.is_same(conn));
+            }
+        }
+
         self.inner.close(conn);
     }
 
@@ -359,6 +804,32 @@ impl<Io: ConnectionIo> Acquired<Io> {
     pub(super) fn release(&self, conn: ConnectionInnerType<Io>, created: Instant) {
         let Acquired { key, inner, .. } = self;
 
+        if let ConnectionInnerType::H2(ref conn) = conn {
+            let mut shared = inner.shared.borrow_mut();
+
+            let conns = match shared.get_mut(key) {
+                Some(conns) => conns,
+                // connection was closed by another request
+                None => return,
+            };
+
+            let idx = match conns.iter().position(|shared| shared.conn.is_same(conn)) {
+                Some(idx) => idx,
+                None => return,
+            };
+
+            // connection stays shared while other requests use it
+            conns[idx].streams -= 1;
+            if conns[idx].streams > 0 {
+                return;
+            }
+
+            conns.swap_remove(idx);
+            if conns.is_empty() {
+                shared.remove(key);
+            }
+        }
+
         inner
             .available
             .borrow_mut()
@@ -370,7 +841,21 @@ impl<Io: ConnectionIo> Acquired<Io> {
                 used: Instant::now(),
             });
 
-        let _ = &self.permit;
+        inner.stats.update(key, |stats| stats.idle += 1);
+        inner.schedule_sweep();
+
+        let _ = (&self.permits.permit, &self.permits.host_permit);
+    }
+}
+
+impl<Io> Drop for Acquired<Io>
+where
+    Io: AsyncWrite + Unpin + 'static,
+{
+    fn drop(&mut self) {
+        self.inner
+            .stats
+            .update(&self.key, |stats| stats.in_flight -= 1);
     }
 }
 
@@ -473,6 +958,9 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("http://localhost"),
             addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -496,6 +984,130 @@ mod test {
         assert!(now.elapsed() >= Duration::from_millis(100));
     }
 
+    #[actix_rt::test]
+    async fn test_pool_limit_per_host() {
+        let connector = TestPoolConnector {
+            generated: Rc::new(Cell::new(0)),
+        };
+
+        let config = ConnectorConfig {
+            limit_per_host: 1,
+            acquire_timeout: Some(Duration::from_millis(100)),
+            ..Default::default()
+        };
+
+        let pool = super::ConnectionPool::new(connector, config);
+
+        let req = Connect {
+            uri: Uri::from_static("http://localhost"),
+            addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
+        };
+
+        let conn = pool.call(req.clone()).await.unwrap();
+
+        // other hosts are not affected by the limit
+        let other = pool
+            .call(Connect {
+                uri: Uri::from_static("http://127.0.0.1"),
+                addr: None,
+                ip_version: None,
+                protocol: None,
+                timings: None,
+            })
+            .await
+            .unwrap();
+        release(other);
+
+        let now = Instant::now();
+        match pool.call(req.clone()).await {
+            Err(ConnectError::AcquireTimeout) => {}
+            _ => panic!("expected acquire timeout"),
+        }
+        assert!(now.elapsed() >= Duration::from_millis(100));
+        assert_eq!(pool.stats().waiters(), 0);
+
+        release(conn);
+        let conn = pool.call(req).await.unwrap();
+        release(conn);
+
+        // permits of hosts without connections in use are removed
+        let conn = pool
+            .call(Connect {
+                uri: Uri::from_static("http://example.com"),
+                addr: None,
+                ip_version: None,
+                protocol: None,
+                timings: None,
+            })
+            .await
+            .unwrap();
+        assert_eq!(pool.inner.host_permits.borrow().len(), 1);
+        release(conn);
+    }
+
+    #[actix_rt::test]
+    async fn test_pool_stats() {
+        let connector = TestPoolConnector {
+            generated: Rc::new(Cell::new(0)),
+        };
+
+        let config = ConnectorConfig {
+            limit: 1,
+            ..Default::default()
+        };
+
+        let pool = super::ConnectionPool::new(connector, config);
+        let stats = pool.stats();
+        let authority = Authority::from_static("localhost");
+
+        let req = Connect {
+            uri: Uri::from_static("http://localhost"),
+            addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
+        };
+
+        let conn = pool.call(req.clone()).await.unwrap();
+
+        let host = stats.host(&authority);
+        assert_eq!(host.in_flight, 1);
+        assert_eq!(host.idle, 0);
+        assert_eq!(host.acquired, 1);
+        assert!(host.mean_acquire_latency().is_some());
+
+        // second request waits for the connection limit
+        let mut waiting = pool.call(req);
+        assert!(futures_util::future::lazy(|cx| waiting.as_mut().poll(cx))
+            .await
+            .is_pending());
+        assert_eq!(stats.host(&authority).waiters, 1);
+        assert_eq!(stats.waiters(), 1);
+
+        release(conn);
+
+        let host = stats.host(&authority);
+        assert_eq!(host.in_flight, 0);
+        assert_eq!(host.idle, 1);
+
+        // idle connection is reused
+        let conn = waiting.await.unwrap();
+
+        let host = stats.host(&authority);
+        assert_eq!(host.in_flight, 1);
+        assert_eq!(host.idle, 0);
+        assert_eq!(host.waiters, 0);
+        assert_eq!(host.acquired, 2);
+        assert!(host.max_acquire_latency >= host.mean_acquire_latency().unwrap());
+
+        drop(conn);
+        assert_eq!(stats.host(&authority).in_flight, 0);
+        assert_eq!(stats.hosts().len(), 1);
+    }
+
     #[actix_rt::test]
     async fn test_pool_keep_alive() {
         let generated = Rc::new(Cell::new(0));
@@ -513,6 +1125,9 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("http://localhost"),
             addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -526,13 +1141,14 @@ mod test {
         actix_rt::time::sleep(Duration::from_millis(1500)).await;
         actix_rt::task::yield_now().await;
 
-        let conn = pool.call(req).await.unwrap();
-        // Note: spawned recycle connection is not ran yet.
-        // This is tokio current thread runtime specific behavior.
-        assert_eq!(2, generated_clone.get());
+        // expired idle connection is closed without waiting for the next request
+        assert_eq!(0, generated_clone.get());
+        assert_eq!(
+            pool.stats().host(&Authority::from_static("localhost")).idle,
+            0
+        );
 
-        // yield task so the old connection is properly dropped.
-        actix_rt::task::yield_now().await;
+        let conn = pool.call(req).await.unwrap();
         assert_eq!(1, generated_clone.get());
 
         release(conn);
@@ -555,6 +1171,9 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("http://localhost"),
             addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -568,18 +1187,49 @@ mod test {
         actix_rt::time::sleep(Duration::from_millis(1500)).await;
         actix_rt::task::yield_now().await;
 
-        let conn = pool.call(req).await.unwrap();
-        // Note: spawned recycle connection is not ran yet.
-        // This is tokio current thread runtime specific behavior.
-        assert_eq!(2, generated_clone.get());
+        // expired idle connection is closed without waiting for the next request
+        assert_eq!(0, generated_clone.get());
+        assert_eq!(
+            pool.stats().host(&Authority::from_static("localhost")).idle,
+            0
+        );
 
-        // yield task so the old connection is properly dropped.
-        actix_rt::task::yield_now().await;
+        let conn = pool.call(req).await.unwrap();
         assert_eq!(1, generated_clone.get());
 
         release(conn);
     }
 
+    #[actix_rt::test]
+    async fn test_pool_no_validation() {
+        let generated = Rc::new(Cell::new(0));
+        let generated_clone = generated.clone();
+
+        let connector = TestPoolConnector { generated };
+
+        let config = ConnectorConfig {
+            conn_validation: false,
+            ..Default::default()
+        };
+
+        let pool = super::ConnectionPool::new(connector, config);
+
+        let req = Connect {
+            uri: Uri::from_static("http://localhost"),
+            addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
+        };
+
+        let conn = pool.call(req.clone()).await.unwrap();
+        release(conn);
+
+        let conn = pool.call(req).await.unwrap();
+        assert_eq!(1, generated_clone.get());
+        release(conn);
+    }
+
     #[actix_rt::test]
     async fn test_pool_authority_key() {
         let generated = Rc::new(Cell::new(0));
@@ -594,6 +1244,9 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("https://crates.io"),
             addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -607,14 +1260,30 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("https://google.com"),
             addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
         assert_eq!(2, generated_clone.get());
         release(conn);
-        let conn = pool.call(req).await.unwrap();
+        let conn = pool.call(req.clone()).await.unwrap();
         assert_eq!(2, generated_clone.get());
         release(conn);
+
+        // connections of other IP versions are not reused
+        let req = Connect {
+            ip_version: Some(IpVersion::V6),
+            ..req
+        };
+
+        let conn = pool.call(req.clone()).await.unwrap();
+        assert_eq!(3, generated_clone.get());
+        release(conn);
+        let conn = pool.call(req).await.unwrap();
+        assert_eq!(3, generated_clone.get());
+        release(conn);
     }
 
     #[actix_rt::test]
@@ -631,6 +1300,9 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("https://crates.io"),
             addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
         };
 
         let conn = pool.call(req.clone()).await.unwrap();
@@ -640,6 +1312,9 @@ mod test {
         let req = Connect {
             uri: Uri::from_static("https://google.com"),
             addr: None,
+            ip_version: None,
+            protocol: None,
+            timings: None,
         };
         let conn = pool.call(req.clone()).await.unwrap();
         assert_eq!(2, generated_clone.get());
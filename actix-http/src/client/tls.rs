@@ -0,0 +1,357 @@
+//! Server name, certificate verification and session resumption options of client TLS
+//! connections.
+
+use std::sync::Arc;
+
+use ahash::AHashMap;
+
+/// Custom verification of server certificates.
+///
+/// Called with the server name and the DER-encoded certificate chain presented by the server,
+/// end-entity certificate first. Returns true if the chain is accepted.
+pub type CertVerifier = dyn Fn(&str, &[&[u8]]) -> bool + Send + Sync;
+
+#[derive(Clone, Default)]
+pub(crate) struct TlsOptions {
+    /// Server names used for SNI and certificate verification, by host.
+    pub(crate) server_names: AHashMap<String, String>,
+    pub(crate) accept_invalid_certs: bool,
+    /// DER-encoded end-entity certificates servers may present.
+    pub(crate) pinned_certs: Vec<Vec<u8>>,
+    pub(crate) verifier: Option<Arc<CertVerifier>>,
+    /// Number of sessions cached for resumption; TLS connector's setting if not set.
+    pub(crate) session_cache_size: Option<usize>,
+    #[cfg(feature = "openssl")]
+    pub(crate) sessions: openssl::SessionCache,
+}
+
+impl TlsOptions {
+    /// Returns true if server certificates are not only verified by the TLS connector.
+    #[allow(dead_code)]
+    pub(crate) fn custom_verification(&self) -> bool {
+        self.accept_invalid_certs
+            || !self.pinned_certs.is_empty()
+            || self.verifier.is_some()
+    }
+
+    /// Decides if a certificate chain, end-entity certificate first, is accepted.
+    ///
+    /// `verified` is the result of the TLS connector's default verification.
+    #[allow(dead_code)]
+    pub(crate) fn verify(
+        &self,
+        server_name: &str,
+        chain: &[&[u8]],
+        verified: bool,
+    ) -> bool {
+        let verified = match self.verifier {
+            Some(ref verifier) => verifier(server_name, chain),
+            None => verified || self.accept_invalid_certs,
+        };
+
+        verified
+            && (self.pinned_certs.is_empty()
+                || chain.first().map_or(false, |cert| {
+                    self.pinned_certs.iter().any(|pinned| pinned == cert)
+                }))
+    }
+}
+
+#[cfg(feature = "openssl")]
+pub(crate) mod openssl {
+    use std::{
+        collections::VecDeque,
+        io,
+        pin::Pin,
+        sync::{Arc, Mutex},
+    };
+
+    use actix_rt::net::ActixStream;
+    use actix_service::Service;
+    use actix_tls::connect::{
+        ssl::openssl::{SslConnector, SslStream},
+        Connection as TcpConnection,
+    };
+    use futures_core::future::LocalBoxFuture;
+    use http::Uri;
+    use tls_openssl::ssl::{
+        NameType, SslContextBuilder, SslSession, SslSessionCacheMode, SslVerifyMode,
+    };
+
+    use super::TlsOptions;
+
+    /// Client sessions by server name, resumed by new connections to the same servers.
+    ///
+    /// Sessions are added by the new session callback installed on connectors, so session
+    /// tickets sent after TLS 1.3 handshakes are cached too.
+    #[derive(Clone)]
+    pub(crate) struct SessionCache(Arc<Mutex<SessionCacheInner>>);
+
+    struct SessionCacheInner {
+        capacity: usize,
+        sessions: VecDeque<(String, SslSession)>,
+    }
+
+    impl Default for SessionCache {
+        fn default() -> Self {
+            Self(Arc::new(Mutex::new(SessionCacheInner {
+                capacity: 32,
+                sessions: VecDeque::new(),
+            })))
+        }
+    }
+
+    impl SessionCache {
+        pub(crate) fn set_capacity(&self, capacity: usize) {
+            let mut inner = self.0.lock().unwrap();
+            inner.capacity = capacity;
+            let len = inner.sessions.len();
+            inner.sessions.drain(..len.saturating_sub(capacity));
+        }
+
+        fn get(&self, server_name: &str) -> Option<SslSession> {
+            let inner = self.0.lock().unwrap();
+            inner
+                .sessions
+                .iter()
+                .rev()
+                .find(|(name, _)| name == server_name)
+                .map(|(_, session)| session.clone())
+        }
+
+        fn insert(&self, server_name: &str, session: SslSession) {
+            let mut inner = self.0.lock().unwrap();
+            if inner.capacity == 0 {
+                return;
+            }
+
+            inner.sessions.retain(|(name, _)| name != server_name);
+            if inner.sessions.len() >= inner.capacity {
+                inner.sessions.pop_front();
+            }
+            inner.sessions.push_back((server_name.to_owned(), session));
+        }
+
+        /// Cache client sessions of connections made with the built connector.
+        pub(crate) fn install(&self, builder: &mut SslContextBuilder) {
+            let cache = self.clone();
+            builder.set_session_cache_mode(SslSessionCacheMode::CLIENT);
+            builder.set_new_session_callback(move |ssl, session| {
+                if let Some(name) = ssl.servername(NameType::HOST_NAME) {
+                    cache.insert(name, session);
+                }
+            });
+        }
+    }
+
+    /// Openssl connector service resuming cached sessions and verifying server certificates
+    /// with [`TlsOptions`].
+    #[derive(Clone)]
+    pub(crate) struct OpensslTlsConnector {
+        connector: SslConnector,
+        options: Arc<TlsOptions>,
+    }
+
+    impl OpensslTlsConnector {
+        pub(crate) fn new(connector: SslConnector, options: Arc<TlsOptions>) -> Self {
+            Self { connector, options }
+        }
+    }
+
+    impl<Io> Service<TcpConnection<Uri, Io>> for OpensslTlsConnector
+    where
+        Io: ActixStream + 'static,
+    {
+        type Response = TcpConnection<Uri, SslStream<Io>>;
+        type Error = io::Error;
+        type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+        actix_service::always_ready!();
+
+        fn call(&self, conn: TcpConnection<Uri, Io>) -> Self::Future {
+            let connector = self.connector.clone();
+            let options = self.options.clone();
+
+            Box::pin(async move {
+                let host = conn.host().to_owned();
+                let (io, uri) = conn.into_parts();
+
+                let mut config = connector.configure().map_err(other)?;
+
+                if let Some(session) = options.sessions.get(&host) {
+                    // SAFETY: sessions are only cached by the connector built with these options
+                    unsafe { config.set_session(&session).map_err(other)? };
+                }
+
+                if options.custom_verification() {
+                    let options = options.clone();
+                    let server_name = host.clone();
+
+                    config.set_verify_callback(
+                        SslVerifyMode::PEER,
+                        move |verified, ctx| {
+                            // callback runs for every certificate of the chain, end-entity one last
+                            if ctx.error_depth() != 0 {
+                                return verified
+                                    || options.accept_invalid_certs
+                                    || options.verifier.is_some();
+                            }
+
+                            let chain = ctx
+                                .chain()
+                                .map(|chain| {
+                                    chain
+                                        .iter()
+                                        .filter_map(|cert| cert.to_der().ok())
+                                        .collect::<Vec<_>>()
+                                })
+                                .unwrap_or_default();
+                            let chain =
+                                chain.iter().map(Vec::as_slice).collect::<Vec<_>>();
+
+                            options.verify(&server_name, &chain, verified)
+                        },
+                    );
+                }
+
+                let ssl = config.into_ssl(&host).map_err(other)?;
+                let mut stream = SslStream::new(ssl, io).map_err(other)?;
+                Pin::new(&mut stream).connect().await.map_err(other)?;
+
+                Ok(TcpConnection::new(stream, uri))
+            })
+        }
+    }
+
+    fn other<E>(err: E) -> io::Error
+    where
+        E: Into<Box<dyn std::error::Error + Send + Sync>>,
+    {
+        io::Error::new(io::ErrorKind::Other, err)
+    }
+}
+
+#[cfg(feature = "rustls")]
+pub(crate) mod rustls {
+    use std::sync::Arc;
+
+    use tls_rustls::{
+        Certificate, ClientConfig, ClientSessionMemoryCache, NoClientSessionStorage,
+        RootCertStore, ServerCertVerified, ServerCertVerifier, TLSError, WebPKIVerifier,
+    };
+
+    use super::TlsOptions;
+
+    /// Returns copy of the config caching up to `size` sessions for resumption.
+    pub(crate) fn with_session_cache(
+        config: &Arc<ClientConfig>,
+        size: usize,
+    ) -> Arc<ClientConfig> {
+        let mut config = (**config).clone();
+        if size == 0 {
+            config.session_persistence = Arc::new(NoClientSessionStorage {});
+            config.enable_tickets = false;
+        } else {
+            config.session_persistence = ClientSessionMemoryCache::new(size);
+        }
+        Arc::new(config)
+    }
+
+    /// Returns copy of the config verifying server certificates with [`TlsOptions`].
+    pub(crate) fn with_verifier(
+        config: &Arc<ClientConfig>,
+        options: Arc<TlsOptions>,
+    ) -> Arc<ClientConfig> {
+        let mut config = (**config).clone();
+        config
+            .dangerous()
+            .set_certificate_verifier(Arc::new(Verifier {
+                default: WebPKIVerifier::new(),
+                options,
+            }));
+        Arc::new(config)
+    }
+
+    struct Verifier {
+        default: WebPKIVerifier,
+        options: Arc<TlsOptions>,
+    }
+
+    impl ServerCertVerifier for Verifier {
+        fn verify_server_cert(
+            &self,
+            roots: &RootCertStore,
+            presented_certs: &[Certificate],
+            dns_name: webpki::DNSNameRef<'_>,
+            ocsp_response: &[u8],
+        ) -> Result<ServerCertVerified, TLSError> {
+            let verified = self.default.verify_server_cert(
+                roots,
+                presented_certs,
+                dns_name,
+                ocsp_response,
+            );
+
+            let chain = presented_certs
+                .iter()
+                .map(|cert| cert.0.as_slice())
+                .collect::<Vec<_>>();
+
+            if self
+                .options
+                .verify(dns_name.into(), &chain, verified.is_ok())
+            {
+                Ok(ServerCertVerified::assertion())
+            } else {
+                verified.and(Err(TLSError::General(
+                    "server certificate rejected".to_owned(),
+                )))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let chain: &[&[u8]] = &[b"leaf", b"root"];
+
+        let opts = TlsOptions::default();
+        assert!(!opts.custom_verification());
+        assert!(opts.verify("example.com", chain, true));
+        assert!(!opts.verify("example.com", chain, false));
+
+        let opts = TlsOptions {
+            accept_invalid_certs: true,
+            ..Default::default()
+        };
+        assert!(opts.custom_verification());
+        assert!(opts.verify("example.com", chain, false));
+
+        let opts = TlsOptions {
+            accept_invalid_certs: true,
+            pinned_certs: vec![b"other".to_vec()],
+            ..Default::default()
+        };
+        assert!(!opts.verify("example.com", chain, true));
+
+        let opts = TlsOptions {
+            pinned_certs: vec![b"other".to_vec(), b"leaf".to_vec()],
+            ..Default::default()
+        };
+        assert!(opts.verify("example.com", chain, true));
+        assert!(!opts.verify("example.com", chain, false));
+
+        let verifier =
+            |name: &str, chain: &[&[u8]]| name == "example.com" && chain.len() == 2;
+        let opts = TlsOptions {
+            verifier: Some(Arc::new(verifier) as Arc<CertVerifier>),
+            ..Default::default()
+        };
+        assert!(opts.verify("example.com", chain, false));
+        assert!(!opts.verify("example.org", chain, true));
+    }
+}
@@ -0,0 +1,212 @@
+use std::convert::TryFrom;
+
+use actix_codec::Framed;
+use actix_service::Service;
+use http::{
+    header::{self, HeaderValue},
+    Method, StatusCode, Uri,
+};
+
+use super::{
+    error::{ConnectError, InvalidUrl, WsConnectError},
+    Connect, Connection, ConnectionIo, Connector, HttpProtocol,
+};
+use crate::{
+    message::{ConnectionType, RequestHead, ResponseHead},
+    ws,
+};
+
+/// Open a WebSocket connection to `uri` using a default [`Connector`].
+///
+/// See [`ws_connect_with`] for details.
+///
+/// # Examples
+/// ```no_run
+/// # #[actix_rt::main]
+/// # async fn main() {
+/// use actix_http::{client, ws};
+/// use futures_util::{SinkExt as _, StreamExt as _};
+///
+/// let (_res, mut framed) = client::ws_connect("ws://localhost:8080/ws").await.unwrap();
+///
+/// framed.send(ws::Message::Text("hello".into())).await.unwrap();
+/// let frame = framed.next().await.unwrap().unwrap();
+/// # }
+/// ```
+pub async fn ws_connect<U>(
+    uri: U,
+) -> Result<(ResponseHead, Framed<impl ConnectionIo, ws::Codec>), WsConnectError>
+where
+    Uri: TryFrom<U>,
+    <Uri as TryFrom<U>>::Error: Into<http::Error>,
+{
+    let uri = Uri::try_from(uri).map_err(|err| InvalidUrl::HttpError(err.into()))?;
+    let connector = Connector::new().finish();
+
+    ws_connect_with(&connector, uri).await
+}
+
+/// Open a WebSocket connection to `uri` through `connector`.
+///
+/// The connection is established by the connector, making use of its TLS and timeout settings,
+/// and the HTTP/1.1 upgrade handshake is performed over it. Upgraded connections are never
+/// returned to the connector's pool. The `Sec-WebSocket-Accept` header of the response is
+/// validated against the generated key.
+///
+/// Returns the handshake response head and the upgraded connection framed with a client mode
+/// [`ws::Codec`].
+pub async fn ws_connect_with<S, A, B>(
+    connector: &S,
+    uri: Uri,
+) -> Result<(ResponseHead, Framed<Connection<A, B>, ws::Codec>), WsConnectError>
+where
+    S: Service<Connect, Response = Connection<A, B>, Error = ConnectError>,
+    A: ConnectionIo,
+    B: ConnectionIo,
+{
+    let host = match uri.host() {
+        Some(host) => host,
+        None => return Err(InvalidUrl::MissingHost.into()),
+    };
+
+    match uri.scheme_str() {
+        Some("http") | Some("ws") | Some("https") | Some("wss") => {}
+        Some(_) => return Err(InvalidUrl::UnknownScheme.into()),
+        None => return Err(InvalidUrl::MissingScheme.into()),
+    }
+
+    let host = match uri.port_u16() {
+        Some(port) => format!("{}:{}", host, port),
+        None => host.to_owned(),
+    };
+
+    // a base64-encoded value that, when decoded, is 16 bytes in length (RFC 6455)
+    let key = base64::encode(rand::random::<[u8; 16]>());
+
+    let mut head = RequestHead::default();
+    head.method = Method::GET;
+    head.uri = uri.clone();
+    head.set_connection_type(ConnectionType::Upgrade);
+
+    let headers = head.headers_mut();
+    headers.insert(
+        header::HOST,
+        HeaderValue::try_from(host).map_err(http::Error::from)?,
+    );
+    headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+    headers.insert(
+        header::SEC_WEBSOCKET_VERSION,
+        HeaderValue::from_static("13"),
+    );
+    headers.insert(
+        header::SEC_WEBSOCKET_KEY,
+        HeaderValue::try_from(key.as_str()).unwrap(),
+    );
+
+    let conn = connector
+        .call(Connect {
+            uri,
+            addr: None,
+            ip_version: None,
+            // websocket handshakes are only supported over HTTP/1.1
+            protocol: Some(HttpProtocol::Http1),
+            timings: None,
+        })
+        .await?;
+    let (head, framed) = conn.open_tunnel(head).await?;
+
+    verify_response(&head, &key)?;
+
+    Ok((
+        head,
+        framed.into_map_codec(|_| ws::Codec::new().client_mode()),
+    ))
+}
+
+fn verify_response(head: &ResponseHead, key: &str) -> Result<(), WsConnectError> {
+    if head.status != StatusCode::SWITCHING_PROTOCOLS {
+        return Err(WsConnectError::InvalidResponseStatus(head.status));
+    }
+
+    let has_upgrade = head
+        .headers
+        .get(header::UPGRADE)
+        .and_then(|hdr| hdr.to_str().ok())
+        .map_or(false, |hdr| hdr.to_ascii_lowercase().contains("websocket"));
+
+    if !has_upgrade {
+        return Err(WsConnectError::InvalidUpgradeHeader);
+    }
+
+    match head.headers.get(header::CONNECTION) {
+        Some(conn) => {
+            let has_upgrade = conn
+                .to_str()
+                .map_or(false, |conn| conn.to_ascii_lowercase().contains("upgrade"));
+
+            if !has_upgrade {
+                return Err(WsConnectError::InvalidConnectionHeader(conn.clone()));
+            }
+        }
+        None => return Err(WsConnectError::MissingConnectionHeader),
+    }
+
+    match head.headers.get(header::SEC_WEBSOCKET_ACCEPT) {
+        Some(accept) => {
+            let encoded = ws::hash_key(key.as_bytes());
+
+            if accept.as_bytes() != encoded {
+                return Err(WsConnectError::InvalidChallengeResponse(
+                    encoded,
+                    accept.clone(),
+                ));
+            }
+        }
+        None => return Err(WsConnectError::MissingWebSocketAcceptHeader),
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn response(key: &str) -> ResponseHead {
+        let mut head = ResponseHead::new(StatusCode::SWITCHING_PROTOCOLS);
+        head.headers
+            .insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        head.headers
+            .insert(header::CONNECTION, HeaderValue::from_static("Upgrade"));
+        head.headers.insert(
+            header::SEC_WEBSOCKET_ACCEPT,
+            HeaderValue::from_bytes(&ws::hash_key(key.as_bytes())).unwrap(),
+        );
+        head
+    }
+
+    #[test]
+    fn verify() {
+        let key = "dGhlIHNhbXBsZSBub25jZQ==";
+        assert!(verify_response(&response(key), key).is_ok());
+
+        assert!(matches!(
+            verify_response(&response(key), "AAAAAAAAAAAAAAAAAAAAAA=="),
+            Err(WsConnectError::InvalidChallengeResponse(..))
+        ));
+
+        let mut head = response(key);
+        head.headers.remove(header::SEC_WEBSOCKET_ACCEPT);
+        assert!(matches!(
+            verify_response(&head, key),
+            Err(WsConnectError::MissingWebSocketAcceptHeader)
+        ));
+
+        let mut head = response(key);
+        head.status = StatusCode::OK;
+        assert!(matches!(
+            verify_response(&head, key),
+            Err(WsConnectError::InvalidResponseStatus(StatusCode::OK))
+        ));
+    }
+}
@@ -9,8 +9,14 @@ use actix_rt::{
     time::{interval, sleep_until, Instant, Sleep},
 };
 use bytes::BytesMut;
+use http::{header::RETRY_AFTER, StatusCode};
 use time::OffsetDateTime;
 
+use crate::{
+    body::AnyBody, header::HeaderValue, message::RequestHead, Fifo, Prioritizer,
+    Response,
+};
+
 /// "Sun, 06 Nov 1994 08:49:37 GMT".len()
 const DATE_VALUE_LENGTH: usize = 29;
 
@@ -41,6 +47,106 @@ impl From<Option<usize>> for KeepAlive {
     }
 }
 
+/// Handling of `Expect: 100-continue` headers on HTTP/1.0 requests.
+///
+/// HTTP/1.0 clients do not understand interim responses, so `100 Continue` is never sent to them.
+/// Some clients still advertise the header on HTTP/1.0 requests and then send the body without
+/// waiting.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Http10Expect {
+    /// Call the expect service but do not send `100 Continue`. The request body is read as usual.
+    ///
+    /// This is the default.
+    Skip,
+
+    /// Ignore the header and pass requests directly to the service.
+    Ignore,
+
+    /// Respond with `417 Expectation Failed` and close the connection.
+    Reject,
+}
+
+impl Default for Http10Expect {
+    fn default() -> Self {
+        Http10Expect::Skip
+    }
+}
+
+/// Handling of HTTP/1.x response header values and reason phrases containing CR or LF characters.
+///
+/// Such values would allow splitting responses. [`HeaderValue`](crate::http::HeaderValue)s can
+/// only contain these characters when built with unchecked constructors in release builds.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum CrlfHandling {
+    /// Send `500 Internal Server Error` instead of the response and log the offending header or
+    /// reason phrase.
+    ///
+    /// This is the default.
+    Reject,
+
+    /// Drop offending header values and replace custom reason phrases by the canonical reason of
+    /// the status code.
+    Sanitize,
+}
+
+impl Default for CrlfHandling {
+    fn default() -> Self {
+        CrlfHandling::Reject
+    }
+}
+
+/// Handling of requests with duplicated `Host`, `Content-Length` or `Authorization` headers.
+///
+/// These headers must occur at most once. Proxies and applications resolving duplicates
+/// differently is a common way of bypassing checks, so the policy is applied to HTTP/1.x and
+/// HTTP/2 requests alike. HTTP/1.x requests with differing `Content-Length` values are always
+/// rejected.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DuplicateHeaders {
+    /// Respond with `400 Bad Request`.
+    ///
+    /// This is the default.
+    Reject,
+
+    /// Keep the first value of the header.
+    FirstWins,
+
+    /// Keep the last value of the header.
+    LastWins,
+}
+
+impl Default for DuplicateHeaders {
+    fn default() -> Self {
+        DuplicateHeaders::Reject
+    }
+}
+
+/// Handling of HTTP/1.x response bodies producing more or fewer bytes than their declared size,
+/// e.g. a `SizedStream` with a wrong length.
+///
+/// Sending such a body as-is would desynchronize the connection, so excess bytes are never sent.
+/// Bodies ending early always close the connection, as their response can not be completed.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum BodyLengthMismatch {
+    /// Drop excess bytes and keep the connection. Close the connection of bodies ending early.
+    ///
+    /// This is the default.
+    Truncate,
+
+    /// Fail dispatching with [`DispatchError::BodyLength`](crate::error::DispatchError), closing
+    /// the connection.
+    Error,
+
+    /// Log a warning, end the response and close the connection.
+    LogAndClose,
+}
+
+impl Default for BodyLengthMismatch {
+    fn default() -> Self {
+        BodyLengthMismatch::Truncate
+    }
+}
+
 /// Http service configuration
 pub struct ServiceConfig(Rc<Inner>);
 
@@ -52,6 +158,181 @@ struct Inner {
     secure: bool,
     local_addr: Option<std::net::SocketAddr>,
     date_service: DateService,
+    admission: Admission,
+    upgrade_predicate: Option<Rc<dyn Fn(&RequestHead) -> bool>>,
+    http10_expect: Http10Expect,
+    h2_sanitize_headers: bool,
+    body_poll_timeout: Option<Duration>,
+    default_charset: bool,
+    crlf_handling: CrlfHandling,
+    duplicate_headers: DuplicateHeaders,
+    body_length_mismatch: BodyLengthMismatch,
+    prioritizer: Rc<dyn Prioritizer>,
+}
+
+/// Builder of [`ServiceConfig`], created with [`ServiceConfig::builder`].
+pub(crate) struct ServiceConfigBuilder {
+    keep_alive: KeepAlive,
+    client_timeout: u64,
+    client_disconnect: u64,
+    secure: bool,
+    local_addr: Option<net::SocketAddr>,
+    admission: Admission,
+    upgrade_predicate: Option<Rc<dyn Fn(&RequestHead) -> bool>>,
+    http10_expect: Http10Expect,
+    h2_sanitize_headers: bool,
+    body_poll_timeout: u64,
+    default_charset: bool,
+    crlf_handling: CrlfHandling,
+    duplicate_headers: DuplicateHeaders,
+    body_length_mismatch: BodyLengthMismatch,
+    prioritizer: Rc<dyn Prioritizer>,
+}
+
+impl ServiceConfigBuilder {
+    /// Limit on concurrently processed requests.
+    pub(crate) fn admission(mut self, admission: Admission) -> Self {
+        self.admission = admission;
+        self
+    }
+
+    /// Predicate selecting requests handed to the upgrade service.
+    pub(crate) fn upgrade_predicate(
+        mut self,
+        predicate: Option<Rc<dyn Fn(&RequestHead) -> bool>>,
+    ) -> Self {
+        self.upgrade_predicate = predicate;
+        self
+    }
+
+    /// Handling of `Expect: 100-continue` on HTTP/1.0 requests.
+    pub(crate) fn http10_expect(mut self, http10_expect: Http10Expect) -> Self {
+        self.http10_expect = http10_expect;
+        self
+    }
+
+    /// Strip connection-specific headers from HTTP/2 responses.
+    pub(crate) fn h2_sanitize_headers(mut self, enabled: bool) -> Self {
+        self.h2_sanitize_headers = enabled;
+        self
+    }
+
+    /// Body poll timeout in milliseconds. Zero disables the timeout.
+    pub(crate) fn body_poll_timeout(mut self, timeout: u64) -> Self {
+        self.body_poll_timeout = timeout;
+        self
+    }
+
+    /// Add default charset to text content types.
+    pub(crate) fn default_charset(mut self, enabled: bool) -> Self {
+        self.default_charset = enabled;
+        self
+    }
+
+    /// Handling of CR and LF in response header values and reason phrases.
+    pub(crate) fn crlf_handling(mut self, crlf_handling: CrlfHandling) -> Self {
+        self.crlf_handling = crlf_handling;
+        self
+    }
+
+    /// Handling of duplicated request headers.
+    pub(crate) fn duplicate_headers(
+        mut self,
+        duplicate_headers: DuplicateHeaders,
+    ) -> Self {
+        self.duplicate_headers = duplicate_headers;
+        self
+    }
+
+    /// Handling of response bodies not matching their declared size.
+    pub(crate) fn body_length_mismatch(mut self, mismatch: BodyLengthMismatch) -> Self {
+        self.body_length_mismatch = mismatch;
+        self
+    }
+
+    /// Prioritizer choosing the next waiting HTTP/2 stream to service.
+    pub(crate) fn prioritizer(mut self, prioritizer: Rc<dyn Prioritizer>) -> Self {
+        self.prioritizer = prioritizer;
+        self
+    }
+
+    /// Create `ServiceConfig` instance.
+    pub(crate) fn finish(self) -> ServiceConfig {
+        let (keep_alive, ka_enabled) = match self.keep_alive {
+            KeepAlive::Timeout(val) => (val as u64, true),
+            KeepAlive::Os => (0, true),
+            KeepAlive::Disabled => (0, false),
+        };
+        let keep_alive = if ka_enabled && keep_alive > 0 {
+            Some(Duration::from_secs(keep_alive))
+        } else {
+            None
+        };
+
+        ServiceConfig(Rc::new(Inner {
+            keep_alive,
+            ka_enabled,
+            client_timeout: self.client_timeout,
+            client_disconnect: self.client_disconnect,
+            secure: self.secure,
+            local_addr: self.local_addr,
+            date_service: DateService::new(),
+            admission: self.admission,
+            upgrade_predicate: self.upgrade_predicate,
+            http10_expect: self.http10_expect,
+            h2_sanitize_headers: self.h2_sanitize_headers,
+            body_poll_timeout: if self.body_poll_timeout > 0 {
+                Some(Duration::from_millis(self.body_poll_timeout))
+            } else {
+                None
+            },
+            default_charset: self.default_charset,
+            crlf_handling: self.crlf_handling,
+            duplicate_headers: self.duplicate_headers,
+            body_length_mismatch: self.body_length_mismatch,
+            prioritizer: self.prioritizer,
+        }))
+    }
+}
+
+/// Limit on the number of requests that are processed concurrently.
+pub(crate) struct Admission {
+    max_in_flight: usize,
+    retry_after: u64,
+    on_overload: Option<Rc<dyn Fn(&RequestHead)>>,
+    in_flight: Cell<usize>,
+}
+
+impl Admission {
+    /// Create admission limit. A `max_in_flight` of zero disables the limit.
+    pub(crate) fn new(
+        max_in_flight: usize,
+        retry_after: u64,
+        on_overload: Option<Rc<dyn Fn(&RequestHead)>>,
+    ) -> Self {
+        Admission {
+            max_in_flight,
+            retry_after,
+            on_overload,
+            in_flight: Cell::new(0),
+        }
+    }
+}
+
+impl Default for Admission {
+    fn default() -> Self {
+        Admission::new(0, 1, None)
+    }
+}
+
+/// Guard for an admitted request. Releases its in-flight slot on drop.
+pub(crate) struct InFlight(ServiceConfig);
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        let in_flight = &(self.0).0.admission.in_flight;
+        in_flight.set(in_flight.get() - 1);
+    }
 }
 
 impl Clone for ServiceConfig {
@@ -75,26 +356,41 @@ impl ServiceConfig {
         secure: bool,
         local_addr: Option<net::SocketAddr>,
     ) -> ServiceConfig {
-        let (keep_alive, ka_enabled) = match keep_alive {
-            KeepAlive::Timeout(val) => (val as u64, true),
-            KeepAlive::Os => (0, true),
-            KeepAlive::Disabled => (0, false),
-        };
-        let keep_alive = if ka_enabled && keep_alive > 0 {
-            Some(Duration::from_secs(keep_alive))
-        } else {
-            None
-        };
+        Self::builder(
+            keep_alive,
+            client_timeout,
+            client_disconnect,
+            secure,
+            local_addr,
+        )
+        .finish()
+    }
 
-        ServiceConfig(Rc::new(Inner {
+    /// Create builder of a `ServiceConfig` with further options.
+    pub(crate) fn builder(
+        keep_alive: KeepAlive,
+        client_timeout: u64,
+        client_disconnect: u64,
+        secure: bool,
+        local_addr: Option<net::SocketAddr>,
+    ) -> ServiceConfigBuilder {
+        ServiceConfigBuilder {
             keep_alive,
-            ka_enabled,
             client_timeout,
             client_disconnect,
             secure,
             local_addr,
-            date_service: DateService::new(),
-        }))
+            admission: Admission::default(),
+            upgrade_predicate: None,
+            http10_expect: Http10Expect::default(),
+            h2_sanitize_headers: true,
+            body_poll_timeout: 0,
+            default_charset: false,
+            crlf_handling: CrlfHandling::default(),
+            duplicate_headers: DuplicateHeaders::default(),
+            body_length_mismatch: BodyLengthMismatch::default(),
+            prioritizer: Rc::new(Fifo),
+        }
     }
 
     /// Returns true if connection is secure (HTTPS)
@@ -165,6 +461,94 @@ impl ServiceConfig {
         self.keep_alive().map(|ka| self.now() + ka)
     }
 
+    /// Returns true if request should be handed to the upgrade service.
+    ///
+    /// Uses the configured upgrade predicate if there is one. Otherwise, `stream` is returned; it
+    /// indicates whether the request was detected as an upgrade (WebSocket or `CONNECT`) request
+    /// while decoding.
+    pub(crate) fn is_upgrade(&self, req: &RequestHead, stream: bool) -> bool {
+        match self.0.upgrade_predicate {
+            Some(ref predicate) => predicate(req),
+            None => stream,
+        }
+    }
+
+    /// Returns how `Expect: 100-continue` headers on HTTP/1.0 requests are handled.
+    #[inline]
+    pub fn http10_expect(&self) -> Http10Expect {
+        self.0.http10_expect
+    }
+
+    /// Returns true if connection-specific headers are removed from HTTP/2 responses.
+    #[inline]
+    pub fn h2_sanitize_headers(&self) -> bool {
+        self.0.h2_sanitize_headers
+    }
+
+    /// Returns the time a response body may stay pending without producing a chunk before the
+    /// connection is aborted.
+    #[inline]
+    pub fn body_poll_timeout(&self) -> Option<Duration> {
+        self.0.body_poll_timeout
+    }
+
+    /// Returns true if `; charset=utf-8` is appended to textual response content types that lack
+    /// a charset.
+    #[inline]
+    pub fn default_charset(&self) -> bool {
+        self.0.default_charset
+    }
+
+    /// Returns how HTTP/1.x response header values and reason phrases containing CR or LF
+    /// characters are handled.
+    #[inline]
+    pub fn crlf_handling(&self) -> CrlfHandling {
+        self.0.crlf_handling
+    }
+
+    /// Returns how requests with duplicated singleton headers are handled.
+    #[inline]
+    pub fn duplicate_headers(&self) -> DuplicateHeaders {
+        self.0.duplicate_headers
+    }
+
+    /// Returns how HTTP/1.x response bodies not matching their declared size are handled.
+    #[inline]
+    pub fn body_length_mismatch(&self) -> BodyLengthMismatch {
+        self.0.body_length_mismatch
+    }
+
+    /// Prioritizer choosing the next waiting HTTP/2 stream to service.
+    pub(crate) fn prioritizer(&self) -> &dyn Prioritizer {
+        &*self.0.prioritizer
+    }
+
+    /// Acquire in-flight slot for request.
+    ///
+    /// Returns `Err` with a `503 Service Unavailable` response when the in-flight limit is
+    /// reached and the request should not be passed to the service.
+    pub(crate) fn admit(
+        &self,
+        req: &RequestHead,
+    ) -> Result<InFlight, Response<AnyBody>> {
+        let admission = &self.0.admission;
+        let in_flight = admission.in_flight.get();
+
+        if admission.max_in_flight != 0 && in_flight >= admission.max_in_flight {
+            if let Some(ref on_overload) = admission.on_overload {
+                on_overload(req);
+            }
+
+            let mut res = Response::new(StatusCode::SERVICE_UNAVAILABLE);
+            res.headers_mut()
+                .insert(RETRY_AFTER, HeaderValue::from(admission.retry_after));
+            return Err(res);
+        }
+
+        admission.in_flight.set(in_flight + 1);
+        Ok(InFlight(self.clone()))
+    }
+
     #[inline]
     pub(crate) fn now(&self) -> Instant {
         self.0.date_service.now()
@@ -330,6 +714,35 @@ mod tests {
 
     use actix_rt::{task::yield_now, time::sleep};
 
+    #[actix_rt::test]
+    async fn test_admission_limit() {
+        let overloaded = Rc::new(Cell::new(0));
+        let overloaded2 = overloaded.clone();
+
+        let settings = ServiceConfig::builder(KeepAlive::Os, 0, 0, false, None)
+            .admission(Admission::new(
+                1,
+                30,
+                Some(Rc::new(move |_: &RequestHead| {
+                    overloaded2.set(overloaded2.get() + 1)
+                })),
+            ))
+            .finish();
+
+        let head = RequestHead::default();
+
+        let guard = settings.admit(&head).unwrap();
+
+        let res = settings.admit(&head).err().unwrap();
+        assert_eq!(res.status(), StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(res.headers().get(RETRY_AFTER).unwrap(), "30");
+        assert_eq!(overloaded.get(), 1);
+
+        drop(guard);
+        assert!(settings.admit(&head).is_ok());
+        assert_eq!(overloaded.get(), 1);
+    }
+
     #[actix_rt::test]
     async fn test_date_service_update() {
         let settings = ServiceConfig::new(KeepAlive::Os, 0, 0, false, None);
@@ -0,0 +1,151 @@
+use std::{
+    cell::{Cell, RefCell},
+    fmt,
+    rc::Rc,
+};
+
+use crate::ResponseHead;
+
+/// Limit on the number of responses that are compressed concurrently.
+///
+/// Compressing large response bodies is CPU intensive. A budget shared by all responses handled
+/// on a worker ensures that a few huge compressible responses can not starve other requests of
+/// CPU time. Responses that would exceed the budget are sent using identity encoding instead.
+///
+/// Budgets are not thread-safe and are meant to be created once per worker.
+///
+/// # Examples
+/// ```
+/// use actix_http::encoding::CompressionBudget;
+///
+/// let budget = CompressionBudget::new(8).on_exceeded(|head| {
+///     log::info!("not compressing response with status {}", head.status);
+/// });
+/// assert_eq!(budget.active(), 0);
+/// ```
+#[derive(Clone)]
+pub struct CompressionBudget(Rc<Inner>);
+
+struct Inner {
+    max_concurrent: usize,
+    active: Cell<usize>,
+    on_exceeded: RefCell<Option<Rc<dyn Fn(&ResponseHead)>>>,
+}
+
+impl CompressionBudget {
+    /// Create budget that allows at most `max_concurrent` responses to be compressed at once.
+    pub fn new(max_concurrent: usize) -> Self {
+        CompressionBudget(Rc::new(Inner {
+            max_concurrent,
+            active: Cell::new(0),
+            on_exceeded: RefCell::new(None),
+        }))
+    }
+
+    /// Sets the callback to be run for each response that is not compressed because the budget
+    /// is exhausted.
+    ///
+    /// Useful for recording metrics. The callback is shared with all clones of this budget and
+    /// replaces any previously set callback.
+    pub fn on_exceeded<F>(self, f: F) -> Self
+    where
+        F: Fn(&ResponseHead) + 'static,
+    {
+        *self.0.on_exceeded.borrow_mut() = Some(Rc::new(f));
+        self
+    }
+
+    /// Returns number of responses that are currently being compressed.
+    pub fn active(&self) -> usize {
+        self.0.active.get()
+    }
+
+    /// Acquire budget for compressing a response.
+    ///
+    /// Returns `None`, after running the exceeded callback, if the budget is exhausted.
+    pub(super) fn try_acquire(&self, head: &ResponseHead) -> Option<CompressionPermit> {
+        let active = self.0.active.get();
+
+        if active >= self.0.max_concurrent {
+            // clone the callback out so it may set a new one without a borrow conflict
+            let on_exceeded = self.0.on_exceeded.borrow().clone();
+            if let Some(on_exceeded) = on_exceeded {
+                on_exceeded(head);
+            }
+
+            return None;
+        }
+
+        self.0.active.set(active + 1);
+        Some(CompressionPermit(self.clone()))
+    }
+}
+
+impl fmt::Debug for CompressionBudget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompressionBudget")
+            .field("max_concurrent", &self.0.max_concurrent)
+            .field("active", &self.0.active.get())
+            .finish()
+    }
+}
+
+/// Guard for a response that is being compressed. Releases its budget on drop.
+pub(super) struct CompressionPermit(CompressionBudget);
+
+impl Drop for CompressionPermit {
+    fn drop(&mut self) {
+        let active = &(self.0).0.active;
+        active.set(active.get() - 1);
+    }
+}
+
+#[cfg(all(test, feature = "compress-gzip"))]
+mod tests {
+    use super::*;
+    use crate::{
+        body::{Body, ResponseBody},
+        encoding::Encoder,
+        http::{
+            header::{ContentEncoding, CONTENT_ENCODING},
+            StatusCode,
+        },
+    };
+
+    fn compress(
+        head: &mut ResponseHead,
+        budget: &CompressionBudget,
+    ) -> ResponseBody<Encoder<Body>> {
+        let body = ResponseBody::Body(Body::from_slice(&b"hello world ".repeat(16)));
+        Encoder::response_with_budget(ContentEncoding::Gzip, head, body, Some(budget))
+    }
+
+    #[test]
+    fn falls_back_to_identity() {
+        let exceeded = Rc::new(Cell::new(0));
+        let exceeded2 = exceeded.clone();
+
+        // callback set after cloning still applies to the clone
+        let budget = CompressionBudget::new(1);
+        let _ = budget
+            .clone()
+            .on_exceeded(move |_| exceeded2.set(exceeded2.get() + 1));
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body = compress(&mut head, &budget);
+        assert_eq!(head.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(budget.active(), 1);
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let _ = compress(&mut head, &budget);
+        assert!(!head.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(exceeded.get(), 1);
+
+        drop(body);
+        assert_eq!(budget.active(), 0);
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let _ = compress(&mut head, &budget);
+        assert_eq!(head.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+    }
+}
@@ -0,0 +1,308 @@
+use std::borrow::Cow;
+
+use bytes::Bytes;
+use mime::Mime;
+
+use crate::{
+    http::header::{ContentEncoding, HeaderValue, CONTENT_TYPE},
+    ResponseHead,
+};
+
+use super::{negotiate, SUPPORTED_ENCODINGS};
+
+/// Compression settings used by [`Encoder`](super::Encoder).
+///
+/// Higher levels produce smaller bodies at the cost of more CPU time per response. Levels out
+/// of range for an algorithm are clamped to the nearest supported value.
+///
+/// # Examples
+/// ```
+/// use actix_http::encoding::CompressionConfig;
+///
+/// let config = CompressionConfig::new()
+///     .gzip_level(6)
+///     .brotli_quality(5)
+///     .zstd_level(9)
+///     .min_size(1024)
+///     .skip_content_type(mime::IMAGE_STAR);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CompressionConfig {
+    pub(super) gzip_level: u32,
+    pub(super) brotli_quality: u32,
+    pub(super) brotli_window: u32,
+    pub(super) zstd_level: i32,
+    pub(super) min_size: u64,
+    pub(super) flush_chunks: bool,
+    pub(super) blocking_threshold: usize,
+    skip_content_types: Vec<Mime>,
+    preference: Cow<'static, [ContentEncoding]>,
+    zstd_dictionaries: Vec<(Mime, String, Bytes)>,
+}
+
+impl CompressionConfig {
+    /// Create config with the default levels, which favor speed over compression ratio.
+    ///
+    /// Gzip and deflate use level 1, brotli uses quality 3 with a 4 MiB window (22 bits) and zstd
+    /// uses level 3. Bodies smaller than 64 bytes are not compressed.
+    pub const fn new() -> Self {
+        CompressionConfig {
+            gzip_level: 1,
+            brotli_quality: 3,
+            brotli_window: 22,
+            zstd_level: 3,
+            min_size: 64,
+            flush_chunks: false,
+            blocking_threshold: 1024,
+            skip_content_types: Vec::new(),
+            preference: Cow::Borrowed(SUPPORTED_ENCODINGS),
+            zstd_dictionaries: Vec::new(),
+        }
+    }
+
+    /// Set gzip and deflate compression level, from 0 (no compression) to 9.
+    pub fn gzip_level(mut self, level: u32) -> Self {
+        self.gzip_level = level.min(9);
+        self
+    }
+
+    /// Set brotli quality, from 0 to 11.
+    pub fn brotli_quality(mut self, quality: u32) -> Self {
+        self.brotli_quality = quality.min(11);
+        self
+    }
+
+    /// Set base 2 logarithm of the brotli window size, from 10 to 24.
+    ///
+    /// Larger windows improve compression of large bodies but increase memory use per response.
+    pub fn brotli_window(mut self, window: u32) -> Self {
+        self.brotli_window = window.max(10).min(24);
+        self
+    }
+
+    /// Set zstd compression level, from 1 to 22.
+    pub fn zstd_level(mut self, level: i32) -> Self {
+        self.zstd_level = level.max(1).min(22);
+        self
+    }
+
+    /// Set size in bytes below which bodies are sent uncompressed.
+    ///
+    /// Compressing small bodies wastes CPU time and can make them larger due to framing
+    /// overhead. Only applies to bodies whose size is known up front; streaming bodies are always
+    /// compressed.
+    pub fn min_size(mut self, min_size: u64) -> Self {
+        self.min_size = min_size;
+        self
+    }
+
+    /// Flush compressed data after each chunk of the body.
+    ///
+    /// By default, compressors buffer data until enough is available to compress efficiently.
+    /// For streaming responses like server-sent events or long-polling, this holds events back
+    /// until later ones arrive. With flushing enabled, every chunk is sent as soon as it is
+    /// compressed, at the cost of compression ratio.
+    pub fn flush_chunks(mut self, flush: bool) -> Self {
+        self.flush_chunks = flush;
+        self
+    }
+
+    /// Set chunk size in bytes from which compression runs on the blocking thread pool.
+    ///
+    /// Smaller chunks are compressed inline on the worker thread, avoiding the overhead of
+    /// offloading. Compressing large chunks inline can delay other connections handled by the
+    /// worker. Use `usize::MAX` to always compress inline and 0 to always offload. Bodies of
+    /// known size below the threshold are compressed up front and keep a known size.
+    ///
+    /// Defaults to 1024 bytes.
+    pub fn blocking_threshold(mut self, threshold: usize) -> Self {
+        self.blocking_threshold = threshold;
+        self
+    }
+
+    /// Never compress responses with content types matching `pattern`.
+    ///
+    /// Useful for content that is already compressed, like images, video and archives. Patterns
+    /// may use `*` as subtype to match all subtypes of a type, e.g. `image/*`. Parameters of
+    /// patterns and content types are ignored.
+    pub fn skip_content_type(mut self, pattern: Mime) -> Self {
+        self.skip_content_types.push(pattern);
+        self
+    }
+
+    /// Set order of preference of encodings used when a client accepts several encodings with
+    /// equal q-values.
+    ///
+    /// Encodings left out of `order` are never chosen by [`negotiate`](Self::negotiate), and
+    /// encodings not enabled by crate features are ignored. Defaults to
+    /// [`SUPPORTED_ENCODINGS`].
+    ///
+    /// ```
+    /// use actix_http::{encoding::CompressionConfig, http::header::ContentEncoding};
+    ///
+    /// let config = CompressionConfig::new()
+    ///     .encoding_preference(&[ContentEncoding::Gzip, ContentEncoding::Br]);
+    ///
+    /// let encoding = config.negotiate("br, gzip");
+    /// ```
+    pub fn encoding_preference(mut self, order: &[ContentEncoding]) -> Self {
+        self.preference = order
+            .iter()
+            .filter(|enc| SUPPORTED_ENCODINGS.contains(enc))
+            .copied()
+            .collect();
+        self
+    }
+
+    /// Chooses the content encoding of a response from the value of a request's
+    /// `Accept-Encoding` header, using the configured encoding preference.
+    ///
+    /// See [`negotiate`](super::negotiate) for details.
+    pub fn negotiate(&self, accept_encoding: &str) -> Option<ContentEncoding> {
+        negotiate(accept_encoding, &self.preference)
+    }
+
+    /// Compress responses with content types matching `pattern` using a zstd dictionary, for
+    /// requests from clients that have it.
+    ///
+    /// Dictionaries greatly improve compression of small, repetitive bodies like JSON API
+    /// responses, but clients need the same dictionary to decompress them, e.g. with
+    /// [`Decoder::zstd_dictionary`](super::Decoder::zstd_dictionary). Clients list the IDs of the
+    /// dictionaries they have in a `Dictionary-ID` request header, e.g. `Dictionary-ID: "api-v1"`.
+    /// The dictionary is only used for requests listing its `id`, in which case the response has
+    /// a `Dictionary-ID` header with the ID; other responses are compressed without a dictionary.
+    ///
+    /// Patterns are matched like [`skip_content_type`](Self::skip_content_type); the first
+    /// matching dictionary the client has is used. Has no effect unless the `compress-zstd`
+    /// feature is enabled.
+    pub fn zstd_dictionary(
+        mut self,
+        pattern: Mime,
+        id: impl Into<String>,
+        dictionary: impl Into<Bytes>,
+    ) -> Self {
+        self.zstd_dictionaries
+            .push((pattern, id.into(), dictionary.into()));
+        self
+    }
+
+    /// Returns true if the content type of the response matches one of the skipped patterns.
+    pub(super) fn is_skipped(&self, head: &ResponseHead) -> bool {
+        if self.skip_content_types.is_empty() {
+            return false;
+        }
+
+        match content_type(head) {
+            Some(mime) => self
+                .skip_content_types
+                .iter()
+                .any(|pattern| mime_matches(pattern, &mime)),
+            None => false,
+        }
+    }
+
+    /// Returns ID and zstd dictionary configured for the content type of the response, if its
+    /// ID is listed in the request's `Dictionary-ID` header.
+    pub(super) fn zstd_dictionary_for(
+        &self,
+        head: &ResponseHead,
+        dictionary_ids: Option<&HeaderValue>,
+    ) -> Option<(&str, &[u8])> {
+        if self.zstd_dictionaries.is_empty() {
+            return None;
+        }
+
+        let ids = dictionary_ids?.to_str().ok()?;
+        let mime = content_type(head)?;
+
+        self.zstd_dictionaries
+            .iter()
+            .filter(|(pattern, _, _)| mime_matches(pattern, &mime))
+            .find(|(_, id, _)| {
+                ids.split(',')
+                    .any(|listed| listed.trim().trim_matches('"') == id)
+            })
+            .map(|(_, id, dictionary)| (id.as_str(), dictionary.as_ref()))
+    }
+}
+
+fn content_type(head: &ResponseHead) -> Option<Mime> {
+    head.headers()
+        .get(&CONTENT_TYPE)
+        .and_then(|val| val.to_str().ok())
+        .and_then(|val| val.parse::<Mime>().ok())
+}
+
+fn mime_matches(pattern: &Mime, mime: &Mime) -> bool {
+    (pattern.type_() == mime::STAR || pattern.type_() == mime.type_())
+        && (pattern.subtype() == mime::STAR || pattern.subtype() == mime.subtype())
+}
+
+impl Default for CompressionConfig {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clamps_levels() {
+        let config = CompressionConfig::new()
+            .gzip_level(12)
+            .brotli_quality(20)
+            .brotli_window(4)
+            .zstd_level(-5);
+
+        assert_eq!(config.gzip_level, 9);
+        assert_eq!(config.brotli_quality, 11);
+        assert_eq!(config.brotli_window, 10);
+        assert_eq!(config.zstd_level, 1);
+    }
+
+    #[test]
+    fn skip_content_types() {
+        use crate::http::{HeaderValue, StatusCode};
+
+        let config = CompressionConfig::new()
+            .skip_content_type(mime::IMAGE_STAR)
+            .skip_content_type("application/zip".parse().unwrap());
+
+        let head = |content_type: &'static str| {
+            let mut head = ResponseHead::new(StatusCode::OK);
+            head.headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static(content_type));
+            head
+        };
+
+        assert!(config.is_skipped(&head("image/png")));
+        assert!(config.is_skipped(&head("application/zip")));
+        assert!(config.is_skipped(&head("Application/ZIP; foo=bar")));
+        assert!(!config.is_skipped(&head("application/json")));
+        assert!(!config.is_skipped(&head("text/html; charset=utf-8")));
+        assert!(!config.is_skipped(&ResponseHead::new(StatusCode::OK)));
+    }
+
+    #[cfg(all(feature = "compress-gzip", feature = "compress-brotli"))]
+    #[test]
+    fn encoding_preference() {
+        let config = CompressionConfig::new();
+        assert_eq!(config.negotiate("gzip, br"), Some(ContentEncoding::Br));
+
+        let config = config.encoding_preference(&[
+            ContentEncoding::Gzip,
+            ContentEncoding::Identity,
+            ContentEncoding::Br,
+        ]);
+        assert_eq!(config.negotiate("gzip, br"), Some(ContentEncoding::Gzip));
+        assert_eq!(
+            config.negotiate("br;q=0.5, gzip;q=0.1"),
+            Some(ContentEncoding::Br)
+        );
+
+        let config = config.encoding_preference(&[ContentEncoding::Gzip]);
+        assert_eq!(config.negotiate("br"), Some(ContentEncoding::Identity));
+    }
+}
@@ -8,7 +8,7 @@ use std::{
 };
 
 use actix_rt::task::{spawn_blocking, JoinHandle};
-use bytes::Bytes;
+use bytes::{Bytes, BytesMut};
 use futures_core::{ready, Stream};
 
 #[cfg(feature = "compress-brotli")]
@@ -21,89 +21,168 @@ use flate2::write::{GzDecoder, ZlibDecoder};
 use zstd::stream::write::Decoder as ZstdDecoder;
 
 use crate::{
-    encoding::Writer,
+    encoding::{LimitExceeded, Writer},
     error::{BlockingError, PayloadError},
-    http::header::{ContentEncoding, HeaderMap, CONTENT_ENCODING},
+    http::header::{
+        split_comma_delimited, ContentEncoding, HeaderMap, CONTENT_ENCODING,
+    },
 };
 
 const MAX_CHUNK_SIZE_DECODE_IN_PLACE: usize = 2049;
 
+/// Maximum number of content codings applied to a payload that are decoded.
+const MAX_STACKED_ENCODINGS: usize = 3;
+
+/// Stream adaptor decompressing a stream of bytes.
+///
+/// Used for request and client response payloads, but can wrap any stream of byte chunks whose
+/// error type can be created from a [`PayloadError`]. Chunks are passed through unchanged if the
+/// encoding is not enabled by crate features or is identity.
 pub struct Decoder<S> {
     decoder: Option<ContentDecoder>,
     stream: S,
     eof: bool,
+    unsupported: bool,
     fut: Option<JoinHandle<Result<(Option<Bytes>, ContentDecoder), io::Error>>>,
 }
 
-impl<S> Decoder<S>
-where
-    S: Stream<Item = Result<Bytes, PayloadError>>,
-{
+impl<S> Decoder<S> {
     /// Construct a decoder.
     #[inline]
     pub fn new(stream: S, encoding: ContentEncoding) -> Decoder<S> {
-        let decoder = match encoding {
-            #[cfg(feature = "compress-brotli")]
-            ContentEncoding::Br => Some(ContentDecoder::Br(Box::new(
-                BrotliDecoder::new(Writer::new()),
-            ))),
-            #[cfg(feature = "compress-gzip")]
-            ContentEncoding::Deflate => Some(ContentDecoder::Deflate(Box::new(
-                ZlibDecoder::new(Writer::new()),
-            ))),
-            #[cfg(feature = "compress-gzip")]
-            ContentEncoding::Gzip => Some(ContentDecoder::Gzip(Box::new(
-                GzDecoder::new(Writer::new()),
-            ))),
-            #[cfg(feature = "compress-zstd")]
-            ContentEncoding::Zstd => Some(ContentDecoder::Zstd(Box::new(
-                ZstdDecoder::new(Writer::new()).expect(
-                    "Failed to create zstd decoder. This is a bug. \
-                         Please report it to the actix-web repository.",
-                ),
-            ))),
-            _ => None,
-        };
-
         Decoder {
-            decoder,
+            decoder: ContentDecoder::new(encoding),
             stream,
             fut: None,
             eof: false,
+            unsupported: false,
         }
     }
 
     /// Construct decoder based on headers.
+    ///
+    /// Payloads with several content codings, e.g. `Content-Encoding: gzip, br`, are decoded in
+    /// reverse order of application. If more than three codings are applied, or one of them is
+    /// unknown or not enabled by crate features, the stream yields
+    /// [`PayloadError::UnsupportedEncoding`]. Payloads with a single unknown coding are passed
+    /// through unchanged.
     #[inline]
     pub fn from_headers(stream: S, headers: &HeaderMap) -> Decoder<S> {
         // check content-encoding
-        let encoding = headers
-            .get(&CONTENT_ENCODING)
-            .and_then(|val| val.to_str().ok())
-            .map(ContentEncoding::from)
-            .unwrap_or(ContentEncoding::Identity);
+        let codings = headers
+            .get_all(&CONTENT_ENCODING)
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(split_comma_delimited)
+            .filter(|coding| !coding.eq_ignore_ascii_case("identity"))
+            .take(MAX_STACKED_ENCODINGS + 1)
+            .collect::<Vec<_>>();
+
+        match codings.as_slice() {
+            [] => Self::new(stream, ContentEncoding::Identity),
+            [coding] => Self::new(stream, ContentEncoding::from(*coding)),
+            codings => {
+                let decoders = if codings.len() > MAX_STACKED_ENCODINGS {
+                    None
+                } else {
+                    codings
+                        .iter()
+                        .rev()
+                        .map(|coding| match ContentEncoding::from(*coding) {
+                            ContentEncoding::Identity => None,
+                            encoding => ContentDecoder::new(encoding),
+                        })
+                        .collect::<Option<Vec<_>>>()
+                };
+
+                Decoder {
+                    unsupported: decoders.is_none(),
+                    decoder: decoders.map(ContentDecoder::Stacked),
+                    stream,
+                    fut: None,
+                    eof: false,
+                }
+            }
+        }
+    }
+
+    /// Returns true if the payload is decoded, i.e. its encoding is known and enabled.
+    #[inline]
+    pub fn is_decoding(&self) -> bool {
+        self.decoder.is_some()
+    }
+
+    /// Decode zstd payloads using the given dictionary.
+    ///
+    /// Needed for payloads compressed with a dictionary, e.g. by servers using
+    /// [`CompressionConfig::zstd_dictionary`](super::CompressionConfig::zstd_dictionary). Has no
+    /// effect when the payload is not zstd encoded. Errors if the dictionary is invalid.
+    pub fn zstd_dictionary(mut self, dictionary: &[u8]) -> io::Result<Self> {
+        if let Some(ref mut decoder) = self.decoder {
+            decoder.set_zstd_dictionary(dictionary)?;
+        }
 
-        Self::new(stream, encoding)
+        Ok(self)
+    }
+
+    /// Set maximum total size of decoded payload.
+    ///
+    /// Once decoded data would exceed this size, the stream yields
+    /// [`PayloadError::DecodeOverflow`]. Has no effect when the payload is not encoded.
+    ///
+    /// By default there is no limit.
+    pub fn max_size(mut self, limit: usize) -> Self {
+        if let Some(ref mut decoder) = self.decoder {
+            decoder.for_each_writer(&mut |writer| writer.max_size(limit));
+        }
+        self
+    }
+
+    /// Set maximum size of data decoded from a single chunk of the underlying stream.
+    ///
+    /// Once data decoded from one chunk would exceed this size, the stream yields
+    /// [`PayloadError::DecodeOverflow`]. Has no effect when the payload is not encoded.
+    ///
+    /// By default there is no limit.
+    pub fn max_chunk_size(mut self, limit: usize) -> Self {
+        if let Some(ref mut decoder) = self.decoder {
+            decoder.for_each_writer(&mut |writer| writer.max_chunk_size(limit));
+        }
+        self
     }
 }
 
-impl<S> Stream for Decoder<S>
+impl<S, E> Stream for Decoder<S>
 where
-    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    E: From<PayloadError>,
 {
-    type Item = Result<Bytes, PayloadError>;
+    type Item = Result<Bytes, E>;
 
     fn poll_next(
         mut self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Self::Item>> {
+        if self.unsupported {
+            self.unsupported = false;
+            self.eof = true;
+            return Poll::Ready(Some(Err(PayloadError::UnsupportedEncoding.into())));
+        }
+
         loop {
             if let Some(ref mut fut) = self.fut {
-                let (chunk, decoder) =
-                    ready!(Pin::new(fut).poll(cx)).map_err(|_| BlockingError)??;
+                let res = ready!(Pin::new(fut).poll(cx))
+                    .map_err(|_| PayloadError::from(BlockingError))?;
+                self.fut.take();
+
+                let (chunk, decoder) = match res {
+                    Ok(res) => res,
+                    Err(err) => {
+                        self.eof = true;
+                        return Poll::Ready(Some(Err(decode_error(err).into())));
+                    }
+                };
 
                 self.decoder = Some(decoder);
-                self.fut.take();
 
                 if let Some(chunk) = chunk {
                     return Poll::Ready(Some(Ok(chunk)));
@@ -120,7 +199,15 @@ where
                 Some(Ok(chunk)) => {
                     if let Some(mut decoder) = self.decoder.take() {
                         if chunk.len() < MAX_CHUNK_SIZE_DECODE_IN_PLACE {
-                            let chunk = decoder.feed_data(chunk)?;
+                            let chunk = match decoder.feed_data(chunk) {
+                                Ok(chunk) => chunk,
+                                Err(err) => {
+                                    self.eof = true;
+                                    return Poll::Ready(Some(Err(
+                                        decode_error(err).into()
+                                    )));
+                                }
+                            };
                             self.decoder = Some(decoder);
 
                             if let Some(chunk) = chunk {
@@ -146,7 +233,7 @@ where
                         match decoder.feed_eof() {
                             Ok(Some(res)) => Poll::Ready(Some(Ok(res))),
                             Ok(None) => Poll::Ready(None),
-                            Err(err) => Poll::Ready(Some(Err(err.into()))),
+                            Err(err) => Poll::Ready(Some(Err(decode_error(err).into()))),
                         }
                     } else {
                         Poll::Ready(None)
@@ -168,11 +255,113 @@ enum ContentDecoder {
     // argument, and we use `spawn_blocking` in `Decoder::poll_next` that require `FnOnce() -> R + Send + 'static`
     #[cfg(feature = "compress-zstd")]
     Zstd(Box<ZstdDecoder<'static, Writer>>),
+    /// Decoders for stacked content codings, in order of decoding.
+    Stacked(Vec<ContentDecoder>),
+}
+
+/// Maps decoding I/O error to payload error, detecting exceeded writer limits.
+fn decode_error(err: io::Error) -> PayloadError {
+    if err.get_ref().map_or(false, |err| err.is::<LimitExceeded>()) {
+        PayloadError::DecodeOverflow
+    } else {
+        err.into()
+    }
 }
 
 impl ContentDecoder {
+    fn new(encoding: ContentEncoding) -> Option<Self> {
+        match encoding {
+            #[cfg(feature = "compress-brotli")]
+            ContentEncoding::Br => Some(ContentDecoder::Br(Box::new(
+                BrotliDecoder::new(Writer::new()),
+            ))),
+            #[cfg(feature = "compress-gzip")]
+            ContentEncoding::Deflate => Some(ContentDecoder::Deflate(Box::new(
+                ZlibDecoder::new(Writer::new()),
+            ))),
+            #[cfg(feature = "compress-gzip")]
+            ContentEncoding::Gzip => Some(ContentDecoder::Gzip(Box::new(
+                GzDecoder::new(Writer::new()),
+            ))),
+            #[cfg(feature = "compress-zstd")]
+            ContentEncoding::Zstd => Some(ContentDecoder::Zstd(Box::new(
+                ZstdDecoder::new(Writer::new()).expect(
+                    "Failed to create zstd decoder. This is a bug. \
+                         Please report it to the actix-web repository.",
+                ),
+            ))),
+            _ => None,
+        }
+    }
+
+    fn for_each_writer(&mut self, f: &mut dyn FnMut(&mut Writer)) {
+        match self {
+            #[cfg(feature = "compress-brotli")]
+            ContentDecoder::Br(ref mut decoder) => f(decoder.get_mut()),
+            #[cfg(feature = "compress-gzip")]
+            ContentDecoder::Gzip(ref mut decoder) => f(decoder.get_mut()),
+            #[cfg(feature = "compress-gzip")]
+            ContentDecoder::Deflate(ref mut decoder) => f(decoder.get_mut()),
+            #[cfg(feature = "compress-zstd")]
+            ContentDecoder::Zstd(ref mut decoder) => f(decoder.get_mut()),
+            ContentDecoder::Stacked(ref mut decoders) => {
+                for decoder in decoders {
+                    decoder.for_each_writer(f);
+                }
+            }
+        }
+    }
+
+    #[allow(unused_variables, unreachable_patterns)]
+    fn set_zstd_dictionary(&mut self, dictionary: &[u8]) -> io::Result<()> {
+        match self {
+            #[cfg(feature = "compress-zstd")]
+            ContentDecoder::Zstd(ref mut decoder) => {
+                let limits = decoder.get_mut();
+                let mut writer = Writer::new();
+                writer.max_size(limits.remaining);
+                writer.max_chunk_size(limits.max_chunk);
+
+                **decoder = ZstdDecoder::with_dictionary(writer, dictionary)?;
+            }
+            ContentDecoder::Stacked(ref mut decoders) => {
+                for decoder in decoders {
+                    decoder.set_zstd_dictionary(dictionary)?;
+                }
+            }
+            _ => {}
+        }
+
+        Ok(())
+    }
+
     fn feed_eof(&mut self) -> io::Result<Option<Bytes>> {
         match self {
+            ContentDecoder::Stacked(ref mut decoders) => {
+                let mut pending = None;
+
+                // finish each decoder after feeding it the remaining output of the previous one
+                for decoder in decoders {
+                    let mut buf = BytesMut::new();
+
+                    if let Some(chunk) = pending.take() {
+                        if let Some(chunk) = decoder.feed_data(chunk)? {
+                            buf.extend_from_slice(&chunk);
+                        }
+                    }
+
+                    if let Some(chunk) = decoder.feed_eof()? {
+                        buf.extend_from_slice(&chunk);
+                    }
+
+                    if !buf.is_empty() {
+                        pending = Some(buf.freeze());
+                    }
+                }
+
+                Ok(pending)
+            }
+
             #[cfg(feature = "compress-brotli")]
             ContentDecoder::Br(ref mut decoder) => match decoder.flush() {
                 Ok(()) => {
@@ -231,6 +420,19 @@ impl ContentDecoder {
 
     fn feed_data(&mut self, data: Bytes) -> io::Result<Option<Bytes>> {
         match self {
+            ContentDecoder::Stacked(ref mut decoders) => {
+                let mut chunk = data;
+
+                for decoder in decoders {
+                    chunk = match decoder.feed_data(chunk)? {
+                        Some(chunk) => chunk,
+                        None => return Ok(None),
+                    };
+                }
+
+                Ok(Some(chunk))
+            }
+
             #[cfg(feature = "compress-brotli")]
             ContentDecoder::Br(ref mut decoder) => match decoder.write_all(&data) {
                 Ok(_) => {
@@ -293,3 +495,119 @@ impl ContentDecoder {
         }
     }
 }
+
+#[cfg(all(test, feature = "compress-gzip"))]
+mod tests {
+    use flate2::{write::GzEncoder, Compression};
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+
+    fn gzip(data: &[u8]) -> Bytes {
+        let mut enc = GzEncoder::new(Vec::new(), Compression::default());
+        enc.write_all(data).unwrap();
+        Bytes::from(enc.finish().unwrap())
+    }
+
+    fn decoder(
+        chunks: Vec<Bytes>,
+    ) -> Decoder<impl Stream<Item = Result<Bytes, PayloadError>> + Unpin> {
+        Decoder::new(
+            stream::iter(chunks.into_iter().map(Ok)),
+            ContentEncoding::Gzip,
+        )
+    }
+
+    async fn decode<S>(mut decoder: Decoder<S>) -> Result<Vec<u8>, PayloadError>
+    where
+        S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+    {
+        let mut buf = Vec::new();
+        while let Some(chunk) = decoder.next().await {
+            buf.extend_from_slice(&chunk?);
+        }
+        Ok(buf)
+    }
+
+    #[actix_rt::test]
+    async fn max_size() {
+        let data = vec![b'a'; 10_000];
+        let encoded = gzip(&data);
+
+        let res = decode(decoder(vec![encoded.clone()]).max_size(10_000)).await;
+        assert_eq!(res.unwrap(), data);
+
+        let res = decode(decoder(vec![encoded]).max_size(9_999)).await;
+        assert!(matches!(res, Err(PayloadError::DecodeOverflow)));
+    }
+
+    #[actix_rt::test]
+    async fn max_chunk_size() {
+        let data = vec![b'a'; 10_000];
+        let encoded = gzip(&data);
+        let (first, second) = encoded.split_at(encoded.len() / 2);
+        let chunks = vec![
+            Bytes::copy_from_slice(first),
+            Bytes::copy_from_slice(second),
+        ];
+
+        let res = decode(decoder(chunks).max_size(10_000).max_chunk_size(10_000)).await;
+        assert_eq!(res.unwrap(), data);
+
+        let res = decode(decoder(vec![encoded]).max_chunk_size(1_000)).await;
+        assert!(matches!(res, Err(PayloadError::DecodeOverflow)));
+    }
+
+    fn headers(content_encoding: &'static str) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            CONTENT_ENCODING,
+            crate::http::HeaderValue::from_static(content_encoding),
+        );
+        headers
+    }
+
+    #[actix_rt::test]
+    async fn stacked_encodings() {
+        let data = vec![b'a'; 10_000];
+        let encoded = gzip(&gzip(&data));
+        let (first, second) = encoded.split_at(encoded.len() / 2);
+        let chunks = vec![
+            Ok(Bytes::copy_from_slice(first)),
+            Ok(Bytes::copy_from_slice(second)),
+        ];
+
+        let decoder = Decoder::from_headers(
+            stream::iter(chunks),
+            &headers("gzip, identity, gzip"),
+        )
+        .max_size(10_000);
+        assert!(decoder.is_decoding());
+        assert_eq!(decode(decoder).await.unwrap(), data);
+
+        let decoder = Decoder::from_headers(
+            stream::iter(vec![Ok(encoded)]),
+            &headers("gzip, gzip"),
+        )
+        .max_size(9_999);
+        assert!(matches!(
+            decode(decoder).await,
+            Err(PayloadError::DecodeOverflow)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn unsupported_stacked_encodings() {
+        for &content_encoding in &["gzip, compress", "gzip, gzip, gzip, gzip"] {
+            let decoder = Decoder::from_headers(
+                stream::iter(vec![Ok(gzip(b"data"))]),
+                &headers(content_encoding),
+            );
+            assert!(!decoder.is_decoding());
+            assert!(matches!(
+                decode(decoder).await,
+                Err(PayloadError::UnsupportedEncoding)
+            ));
+        }
+    }
+}
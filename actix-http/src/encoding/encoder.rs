@@ -11,7 +11,7 @@ use std::{
 use actix_rt::task::{spawn_blocking, JoinHandle};
 use bytes::Bytes;
 use derive_more::Display;
-use futures_core::ready;
+use futures_core::{ready, Stream};
 use pin_project::pin_project;
 
 #[cfg(feature = "compress-brotli")]
@@ -26,24 +26,24 @@ use zstd::stream::write::Encoder as ZstdEncoder;
 use crate::{
     body::{Body, BodySize, BoxAnyBody, MessageBody, ResponseBody},
     http::{
-        header::{ContentEncoding, CONTENT_ENCODING},
+        header::{ContentEncoding, HeaderMap, HeaderName, CONTENT_ENCODING},
         HeaderValue, StatusCode,
     },
     Error, ResponseHead,
 };
 
-use super::Writer;
+use super::{
+    budget::{CompressionBudget, CompressionPermit},
+    CompressionConfig, Writer,
+};
 use crate::error::BlockingError;
 
-const MAX_CHUNK_SIZE_ENCODE_IN_PLACE: usize = 1024;
-
 #[pin_project]
 pub struct Encoder<B> {
-    eof: bool,
     #[pin]
     body: EncoderBody<B>,
-    encoder: Option<ContentEncoder>,
-    fut: Option<JoinHandle<Result<ContentEncoder, io::Error>>>,
+    state: EncoderState,
+    permit: Option<CompressionPermit>,
 }
 
 impl<B: MessageBody> Encoder<B> {
@@ -52,22 +52,108 @@ impl<B: MessageBody> Encoder<B> {
         head: &mut ResponseHead,
         body: ResponseBody<B>,
     ) -> ResponseBody<Encoder<B>> {
-        let can_encode = !(head.headers().contains_key(&CONTENT_ENCODING)
+        Self::response_with_budget(encoding, head, body, None)
+    }
+
+    /// Like [`response`](Self::response), but falls back to identity encoding when compressing
+    /// the response would exceed the given `budget`.
+    pub fn response_with_budget(
+        encoding: ContentEncoding,
+        head: &mut ResponseHead,
+        body: ResponseBody<B>,
+        budget: Option<&CompressionBudget>,
+    ) -> ResponseBody<Encoder<B>> {
+        Self::response_with_config(
+            encoding,
+            head,
+            body,
+            budget,
+            &CompressionConfig::default(),
+        )
+    }
+
+    /// Like [`response_with_budget`](Self::response_with_budget), but compresses the response
+    /// using the levels in `config`.
+    ///
+    /// Zstd dictionaries of `config` are not used.
+    pub fn response_with_config(
+        encoding: ContentEncoding,
+        head: &mut ResponseHead,
+        body: ResponseBody<B>,
+        budget: Option<&CompressionBudget>,
+        config: &CompressionConfig,
+    ) -> ResponseBody<Encoder<B>> {
+        Self::response_with_dictionary_ids(encoding, head, body, budget, config, None)
+    }
+
+    /// Like [`response_with_config`](Self::response_with_config), but uses a zstd dictionary of
+    /// `config` if its ID is listed in `dictionary_ids`, the value of the request's
+    /// `Dictionary-ID` header.
+    ///
+    /// See [`CompressionConfig::zstd_dictionary`] for details.
+    pub fn response_with_dictionary_ids(
+        encoding: ContentEncoding,
+        head: &mut ResponseHead,
+        body: ResponseBody<B>,
+        budget: Option<&CompressionBudget>,
+        config: &CompressionConfig,
+        dictionary_ids: Option<&HeaderValue>,
+    ) -> ResponseBody<Encoder<B>> {
+        let mut can_encode = !(head.headers().contains_key(&CONTENT_ENCODING)
             || head.status == StatusCode::SWITCHING_PROTOCOLS
             || head.status == StatusCode::NO_CONTENT
             || encoding == ContentEncoding::Identity
-            || encoding == ContentEncoding::Auto);
+            || encoding == ContentEncoding::Auto
+            || config.is_skipped(head));
+
+        let dictionary = if encoding == ContentEncoding::Zstd {
+            config.zstd_dictionary_for(head, dictionary_ids)
+        } else {
+            None
+        };
+        let dictionary_id = dictionary.map(|(id, _)| id);
+        let dictionary = dictionary.map(|(_, dictionary)| dictionary);
+
+        let size = match body {
+            ResponseBody::Body(ref b) => b.size(),
+            ResponseBody::Other(ref b) => b.size(),
+        };
+        if let BodySize::Sized(len) = size {
+            if len < config.min_size {
+                can_encode = false;
+            }
+        }
+
+        let permit = match budget {
+            Some(budget) if can_encode => {
+                let permit = budget.try_acquire(head);
+                can_encode = permit.is_some();
+                permit
+            }
+            _ => None,
+        };
 
         let body = match body {
             ResponseBody::Other(b) => match b {
                 Body::None => return ResponseBody::Other(Body::None),
                 Body::Empty => return ResponseBody::Other(Body::Empty),
                 Body::Bytes(buf) => {
-                    if can_encode {
-                        EncoderBody::Bytes(buf)
-                    } else {
+                    if !can_encode {
                         return ResponseBody::Other(Body::Bytes(buf));
                     }
+
+                    // small bodies are compressed up front so they keep a known size and are
+                    // sent with `Content-Length` instead of chunked
+                    if buf.len() < config.blocking_threshold {
+                        if let Some(buf) = ContentEncoder::encode_all(
+                            encoding, &buf, config, dictionary,
+                        ) {
+                            update_head(encoding, dictionary_id, head);
+                            return ResponseBody::Other(Body::Bytes(buf));
+                        }
+                    }
+
+                    EncoderBody::Bytes(buf)
                 }
                 Body::Message(stream) => EncoderBody::BoxedStream(stream),
             },
@@ -76,23 +162,21 @@ impl<B: MessageBody> Encoder<B> {
 
         if can_encode {
             // Modify response body only if encoder is not None
-            if let Some(enc) = ContentEncoder::encoder(encoding) {
-                update_head(encoding, head);
+            if let Some(enc) = ContentEncoder::encoder(encoding, config, dictionary) {
+                update_head(encoding, dictionary_id, head);
                 head.no_chunking(false);
                 return ResponseBody::Body(Encoder {
                     body,
-                    eof: false,
-                    fut: None,
-                    encoder: Some(enc),
+                    state: EncoderState::new(Some(enc), config),
+                    permit,
                 });
             }
         }
 
         ResponseBody::Body(Encoder {
             body,
-            eof: false,
-            fut: None,
-            encoder: None,
+            state: EncoderState::new(None, config),
+            permit: None,
         })
     }
 }
@@ -146,6 +230,21 @@ where
             }
         }
     }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        match self.project() {
+            EncoderBodyProj::Bytes(_) => Poll::Ready(Ok(None)),
+            EncoderBodyProj::Stream(b) => {
+                Poll::Ready(ready!(b.poll_trailers(cx)).map_err(EncoderError::Body))
+            }
+            EncoderBodyProj::BoxedStream(ref mut b) => Poll::Ready(
+                ready!(b.as_pin_mut().poll_trailers(cx)).map_err(EncoderError::Boxed),
+            ),
+        }
+    }
 }
 
 impl<B> MessageBody for Encoder<B>
@@ -156,10 +255,10 @@ where
     type Error = EncoderError<B::Error>;
 
     fn size(&self) -> BodySize {
-        if self.encoder.is_none() {
-            self.body.size()
-        } else {
+        if self.state.is_encoding() {
             BodySize::Stream
+        } else {
+            self.body.size()
         }
     }
 
@@ -167,75 +266,213 @@ where
         self: Pin<&mut Self>,
         cx: &mut Context<'_>,
     ) -> Poll<Option<Result<Bytes, Self::Error>>> {
-        let mut this = self.project();
+        let this = self.project();
+        let mut body = this.body;
+
+        let res = ready!(this.state.poll_next(cx, |cx| body.as_mut().poll_next(cx)));
+
+        // compression is complete; release budget
+        if this.state.eof {
+            this.permit.take();
+        }
+
+        Poll::Ready(res)
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        self.project().body.poll_trailers(cx)
+    }
+}
+
+/// Stream adaptor compressing a stream of bytes, e.g. for re-encoding proxied payloads.
+///
+/// Unlike [`Encoder`], no headers are inspected or modified. Chunks are passed through
+/// unchanged if the encoding is not enabled by crate features or is identity.
+///
+/// ```
+/// use actix_http::{encoding::StreamEncoder, http::header::ContentEncoding};
+/// use bytes::Bytes;
+/// use futures_util::stream;
+///
+/// let chunks = stream::iter(vec![Ok::<_, std::io::Error>(Bytes::from("hello world"))]);
+/// let encoded = StreamEncoder::new(chunks, ContentEncoding::Gzip);
+/// ```
+#[pin_project]
+pub struct StreamEncoder<S> {
+    #[pin]
+    stream: S,
+    state: EncoderState,
+}
+
+impl<S> StreamEncoder<S> {
+    /// Create encoder compressing `stream` with the default compression levels.
+    pub fn new(stream: S, encoding: ContentEncoding) -> Self {
+        Self::with_config(stream, encoding, &CompressionConfig::default())
+    }
+
+    /// Create encoder compressing `stream` with the levels in `config`.
+    ///
+    /// Size thresholds and content types of `config` do not apply.
+    pub fn with_config(
+        stream: S,
+        encoding: ContentEncoding,
+        config: &CompressionConfig,
+    ) -> Self {
+        StreamEncoder {
+            stream,
+            state: EncoderState::new(
+                ContentEncoder::encoder(encoding, config, None),
+                config,
+            ),
+        }
+    }
+
+    /// Returns true if the stream is compressed, i.e. its encoding is enabled.
+    pub fn is_encoding(&self) -> bool {
+        self.state.is_encoding()
+    }
+}
+
+impl<S, E> Stream for StreamEncoder<S>
+where
+    S: Stream<Item = Result<Bytes, E>>,
+{
+    type Item = Result<Bytes, EncoderError<E>>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        let mut stream = this.stream;
+
+        this.state.poll_next(cx, |cx| {
+            stream
+                .as_mut()
+                .poll_next(cx)
+                .map(|res| res.map(|res| res.map_err(EncoderError::Body)))
+        })
+    }
+}
+
+/// Compression of a stream of chunks, shared by [`Encoder`] and [`StreamEncoder`].
+struct EncoderState {
+    eof: bool,
+    encoder: Option<ContentEncoder>,
+    fut: Option<JoinHandle<Result<ContentEncoder, io::Error>>>,
+    flush: bool,
+    blocking_threshold: usize,
+}
+
+impl EncoderState {
+    fn new(encoder: Option<ContentEncoder>, config: &CompressionConfig) -> Self {
+        EncoderState {
+            eof: false,
+            flush: encoder.is_some() && config.flush_chunks,
+            encoder,
+            fut: None,
+            blocking_threshold: config.blocking_threshold,
+        }
+    }
+
+    fn is_encoding(&self) -> bool {
+        self.encoder.is_some() || self.fut.is_some()
+    }
+
+    /// Compress chunks produced by `poll_chunk`; chunks are passed through if not encoding.
+    ///
+    /// Chunks at least `blocking_threshold` bytes long are compressed on the blocking thread pool.
+    fn poll_next<E>(
+        &mut self,
+        cx: &mut Context<'_>,
+        mut poll_chunk: impl FnMut(
+            &mut Context<'_>,
+        ) -> Poll<Option<Result<Bytes, EncoderError<E>>>>,
+    ) -> Poll<Option<Result<Bytes, EncoderError<E>>>> {
         loop {
-            if *this.eof {
+            if self.eof {
                 return Poll::Ready(None);
             }
 
-            if let Some(ref mut fut) = this.fut {
+            if let Some(ref mut fut) = self.fut {
                 let mut encoder = ready!(Pin::new(fut).poll(cx))
                     .map_err(|_| EncoderError::Blocking(BlockingError))?
                     .map_err(EncoderError::Io)?;
 
                 let chunk = encoder.take();
-                *this.encoder = Some(encoder);
-                this.fut.take();
+                self.encoder = Some(encoder);
+                self.fut.take();
 
                 if !chunk.is_empty() {
                     return Poll::Ready(Some(Ok(chunk)));
                 }
             }
 
-            let result = ready!(this.body.as_mut().poll_next(cx));
-
-            match result {
+            match ready!(poll_chunk(cx)) {
                 Some(Err(err)) => return Poll::Ready(Some(Err(err))),
 
-                Some(Ok(chunk)) => {
-                    if let Some(mut encoder) = this.encoder.take() {
-                        if chunk.len() < MAX_CHUNK_SIZE_ENCODE_IN_PLACE {
-                            encoder.write(&chunk).map_err(EncoderError::Io)?;
-                            let chunk = encoder.take();
-                            *this.encoder = Some(encoder);
+                Some(Ok(chunk)) => match self.encoder.take() {
+                    Some(mut encoder) if chunk.len() < self.blocking_threshold => {
+                        encoder
+                            .write_chunk(&chunk, self.flush)
+                            .map_err(EncoderError::Io)?;
+                        let chunk = encoder.take();
+                        self.encoder = Some(encoder);
 
-                            if !chunk.is_empty() {
-                                return Poll::Ready(Some(Ok(chunk)));
-                            }
-                        } else {
-                            *this.fut = Some(spawn_blocking(move || {
-                                encoder.write(&chunk)?;
-                                Ok(encoder)
-                            }));
+                        if !chunk.is_empty() {
+                            return Poll::Ready(Some(Ok(chunk)));
                         }
-                    } else {
-                        return Poll::Ready(Some(Ok(chunk)));
                     }
-                }
+
+                    Some(mut encoder) => {
+                        let flush = self.flush;
+                        self.fut = Some(spawn_blocking(move || {
+                            encoder.write_chunk(&chunk, flush)?;
+                            Ok(encoder)
+                        }));
+                    }
+
+                    None => return Poll::Ready(Some(Ok(chunk))),
+                },
 
                 None => {
-                    if let Some(encoder) = this.encoder.take() {
-                        let chunk = encoder.finish().map_err(EncoderError::Io)?;
-                        if chunk.is_empty() {
-                            return Poll::Ready(None);
-                        } else {
-                            *this.eof = true;
-                            return Poll::Ready(Some(Ok(chunk)));
+                    self.eof = true;
+
+                    return match self.encoder.take() {
+                        Some(encoder) => {
+                            let chunk = encoder.finish().map_err(EncoderError::Io)?;
+                            if chunk.is_empty() {
+                                Poll::Ready(None)
+                            } else {
+                                Poll::Ready(Some(Ok(chunk)))
+                            }
                         }
-                    } else {
-                        return Poll::Ready(None);
-                    }
+                        None => Poll::Ready(None),
+                    };
                 }
             }
         }
     }
 }
 
-fn update_head(encoding: ContentEncoding, head: &mut ResponseHead) {
+fn update_head(
+    encoding: ContentEncoding,
+    dictionary_id: Option<&str>,
+    head: &mut ResponseHead,
+) {
     head.headers_mut().insert(
         CONTENT_ENCODING,
         HeaderValue::from_static(encoding.as_str()),
     );
+
+    // tell the client which dictionary to decompress the response with
+    if let Some(id) = dictionary_id.and_then(|id| HeaderValue::from_str(id).ok()) {
+        head.headers_mut()
+            .insert(HeaderName::from_static("dictionary-id"), id);
+    }
 }
 
 enum ContentEncoder {
@@ -252,31 +489,60 @@ enum ContentEncoder {
 }
 
 impl ContentEncoder {
-    fn encoder(encoding: ContentEncoding) -> Option<Self> {
+    #[allow(unused_variables)]
+    fn encoder(
+        encoding: ContentEncoding,
+        config: &CompressionConfig,
+        zstd_dictionary: Option<&[u8]>,
+    ) -> Option<Self> {
         match encoding {
             #[cfg(feature = "compress-gzip")]
             ContentEncoding::Deflate => Some(ContentEncoder::Deflate(ZlibEncoder::new(
                 Writer::new(),
-                flate2::Compression::fast(),
+                flate2::Compression::new(config.gzip_level),
             ))),
             #[cfg(feature = "compress-gzip")]
             ContentEncoding::Gzip => Some(ContentEncoder::Gzip(GzEncoder::new(
                 Writer::new(),
-                flate2::Compression::fast(),
+                flate2::Compression::new(config.gzip_level),
             ))),
             #[cfg(feature = "compress-brotli")]
             ContentEncoding::Br => {
-                Some(ContentEncoder::Br(BrotliEncoder::new(Writer::new(), 3)))
+                let mut params = brotli2::CompressParams::new();
+                params
+                    .quality(config.brotli_quality)
+                    .lgwin(config.brotli_window);
+                Some(ContentEncoder::Br(BrotliEncoder::from_params(
+                    Writer::new(),
+                    &params,
+                )))
             }
             #[cfg(feature = "compress-zstd")]
             ContentEncoding::Zstd => {
-                let encoder = ZstdEncoder::new(Writer::new(), 3).ok()?;
+                let encoder = ZstdEncoder::with_dictionary(
+                    Writer::new(),
+                    config.zstd_level,
+                    zstd_dictionary.unwrap_or(&[]),
+                )
+                .ok()?;
                 Some(ContentEncoder::Zstd(encoder))
             }
             _ => None,
         }
     }
 
+    /// Compresses complete body at once.
+    fn encode_all(
+        encoding: ContentEncoding,
+        data: &[u8],
+        config: &CompressionConfig,
+        zstd_dictionary: Option<&[u8]>,
+    ) -> Option<Bytes> {
+        let mut encoder = Self::encoder(encoding, config, zstd_dictionary)?;
+        encoder.write(data).ok()?;
+        encoder.finish().ok()
+    }
+
     #[inline]
     pub(crate) fn take(&mut self) -> Bytes {
         match *self {
@@ -316,6 +582,30 @@ impl ContentEncoder {
         }
     }
 
+    /// Compresses chunk, flushing compressed data to the writer if `flush` is true.
+    fn write_chunk(&mut self, data: &[u8], flush: bool) -> Result<(), io::Error> {
+        self.write(data)?;
+
+        if flush {
+            self.flush()?;
+        }
+
+        Ok(())
+    }
+
+    fn flush(&mut self) -> Result<(), io::Error> {
+        match *self {
+            #[cfg(feature = "compress-brotli")]
+            ContentEncoder::Br(ref mut encoder) => encoder.flush(),
+            #[cfg(feature = "compress-gzip")]
+            ContentEncoder::Gzip(ref mut encoder) => encoder.flush(),
+            #[cfg(feature = "compress-gzip")]
+            ContentEncoder::Deflate(ref mut encoder) => encoder.flush(),
+            #[cfg(feature = "compress-zstd")]
+            ContentEncoder::Zstd(ref mut encoder) => encoder.flush(),
+        }
+    }
+
     fn write(&mut self, data: &[u8]) -> Result<(), io::Error> {
         match *self {
             #[cfg(feature = "compress-brotli")]
@@ -386,3 +676,286 @@ impl<E: StdError + 'static> From<EncoderError<E>> for crate::Error {
         crate::Error::new_encoder().with_cause(err)
     }
 }
+
+#[cfg(all(test, feature = "compress-gzip"))]
+mod tests {
+    use std::{convert::Infallible, io::Read as _};
+
+    use super::*;
+
+    fn gunzip(data: &[u8]) -> Vec<u8> {
+        let mut buf = Vec::new();
+        flate2::read::GzDecoder::new(data)
+            .read_to_end(&mut buf)
+            .unwrap();
+        buf
+    }
+
+    #[test]
+    fn skips_tiny_bodies() {
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body: ResponseBody<Body> =
+            ResponseBody::Other(Body::from_slice(b"hello world"));
+        let body = Encoder::response(ContentEncoding::Gzip, &mut head, body);
+
+        assert!(!head.headers().contains_key(CONTENT_ENCODING));
+        assert!(matches!(body, ResponseBody::Other(Body::Bytes(_))));
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body = ResponseBody::Body(Body::from_slice(b"hello world"));
+        let body = Encoder::response(ContentEncoding::Gzip, &mut head, body);
+
+        assert!(!head.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(body.size(), BodySize::Sized(11));
+    }
+
+    #[test]
+    fn small_bodies_keep_size() {
+        let data = b"hello world ".repeat(16);
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body: ResponseBody<Body> = ResponseBody::Other(Body::from_slice(&data));
+        let body = Encoder::response(ContentEncoding::Gzip, &mut head, body);
+
+        assert_eq!(head.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        match body {
+            ResponseBody::Other(Body::Bytes(buf)) => {
+                assert!(buf.len() < data.len());
+                assert_eq!(gunzip(&buf), data);
+            }
+            _ => panic!("expected compressed bytes"),
+        }
+
+        // larger bodies are compressed while streaming
+        let data = b"hello world ".repeat(128);
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body: ResponseBody<Body> = ResponseBody::Other(Body::from_slice(&data));
+        let body = Encoder::response(ContentEncoding::Gzip, &mut head, body);
+
+        assert_eq!(head.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(body.size(), BodySize::Stream);
+    }
+
+    #[test]
+    fn compression_level() {
+        let data = b"hello world ".repeat(16);
+
+        let encode = |config: CompressionConfig| {
+            let mut head = ResponseHead::new(StatusCode::OK);
+            let body: ResponseBody<Body> = ResponseBody::Other(Body::from_slice(&data));
+            match Encoder::response_with_config(
+                ContentEncoding::Gzip,
+                &mut head,
+                body,
+                None,
+                &config,
+            ) {
+                ResponseBody::Other(Body::Bytes(buf)) => buf,
+                _ => panic!("expected compressed bytes"),
+            }
+        };
+
+        let stored = encode(CompressionConfig::new().gzip_level(0));
+        let best = encode(CompressionConfig::new().gzip_level(9));
+
+        assert!(best.len() < stored.len());
+        assert_eq!(gunzip(&stored), data);
+        assert_eq!(gunzip(&best), data);
+    }
+
+    #[test]
+    fn min_size() {
+        let data = b"hello world ".repeat(16);
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body: ResponseBody<Body> = ResponseBody::Other(Body::from_slice(&data));
+        let config = CompressionConfig::new().min_size(1024);
+        let body = Encoder::response_with_config(
+            ContentEncoding::Gzip,
+            &mut head,
+            body,
+            None,
+            &config,
+        );
+
+        assert!(!head.headers().contains_key(CONTENT_ENCODING));
+        assert_eq!(body.size(), BodySize::Sized(data.len() as u64));
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body: ResponseBody<Body> =
+            ResponseBody::Other(Body::from_slice(b"hello world"));
+        let config = CompressionConfig::new().min_size(0);
+        let body = Encoder::response_with_config(
+            ContentEncoding::Gzip,
+            &mut head,
+            body,
+            None,
+            &config,
+        );
+
+        assert_eq!(head.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        match body {
+            ResponseBody::Other(Body::Bytes(buf)) => {
+                assert_eq!(gunzip(&buf), b"hello world")
+            }
+            _ => panic!("expected compressed bytes"),
+        }
+    }
+
+    #[actix_rt::test]
+    async fn stream_encoder() {
+        use futures_util::{stream, StreamExt as _, TryStreamExt as _};
+
+        let data = b"hello world ".repeat(256);
+        let chunks = data
+            .chunks(1000)
+            .map(|chunk| Ok::<_, Infallible>(Bytes::copy_from_slice(chunk)))
+            .collect::<Vec<_>>();
+
+        let encoder = StreamEncoder::new(stream::iter(chunks), ContentEncoding::Gzip);
+        assert!(encoder.is_encoding());
+
+        let encoded = encoder.try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(gunzip(&encoded.concat()), data);
+
+        let chunks = vec![Ok::<_, Infallible>(Bytes::from_static(b"plain"))];
+        let encoder =
+            StreamEncoder::new(stream::iter(chunks), ContentEncoding::Identity);
+        assert!(!encoder.is_encoding());
+        assert_eq!(
+            encoder.map(Result::unwrap).collect::<Vec<_>>().await,
+            vec![Bytes::from_static(b"plain")]
+        );
+    }
+
+    #[actix_rt::test]
+    async fn flush_chunks() {
+        use std::io::Write as _;
+
+        use futures_util::{stream, StreamExt as _};
+
+        let chunks = vec![
+            Ok::<_, Infallible>(Bytes::from_static(b"data: event 1\n\n")),
+            Ok(Bytes::from_static(b"data: event 2\n\n")),
+        ];
+        let config = CompressionConfig::new().flush_chunks(true);
+        let mut encoder = StreamEncoder::with_config(
+            stream::iter(chunks),
+            ContentEncoding::Gzip,
+            &config,
+        );
+
+        // each event can be decompressed as soon as its chunk is received
+        let mut received = Vec::new();
+        let mut decoder = flate2::write::GzDecoder::new(Vec::new());
+        for event in &[&b"data: event 1\n\n"[..], &b"data: event 2\n\n"[..]] {
+            let chunk = encoder.next().await.unwrap().unwrap();
+            decoder.write_all(&chunk).unwrap();
+            decoder.flush().unwrap();
+            received.extend_from_slice(event);
+            assert_eq!(decoder.get_ref(), &received);
+        }
+    }
+
+    #[cfg(feature = "compress-zstd")]
+    #[actix_rt::test]
+    async fn zstd_dictionary() {
+        use futures_util::{stream, TryStreamExt as _};
+
+        use crate::{
+            encoding::Decoder, error::PayloadError, http::header::CONTENT_TYPE,
+        };
+
+        let dictionary = br#"{"id":0,"name":"","tags":[],"active":false}"#.repeat(8);
+        let data = br#"{"id":1,"name":"one","tags":["a"],"active":true}"#.repeat(4);
+
+        let config = CompressionConfig::new().zstd_dictionary(
+            mime::APPLICATION_JSON,
+            "json-v1",
+            dictionary.clone(),
+        );
+
+        let encode = |dictionary_ids: Option<&'static str>| {
+            let mut head = ResponseHead::new(StatusCode::OK);
+            head.headers_mut()
+                .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+            let body: ResponseBody<Body> = ResponseBody::Other(Body::from_slice(&data));
+            let dictionary_ids = dictionary_ids.map(HeaderValue::from_static);
+
+            match Encoder::response_with_dictionary_ids(
+                ContentEncoding::Zstd,
+                &mut head,
+                body,
+                None,
+                &config,
+                dictionary_ids.as_ref(),
+            ) {
+                ResponseBody::Other(Body::Bytes(buf)) => {
+                    (head.headers().get("dictionary-id").cloned(), buf)
+                }
+                _ => panic!("expected compressed bytes"),
+            }
+        };
+
+        // clients without the dictionary get responses compressed without it
+        for ids in &[None, Some("\"other\"")] {
+            let (id, encoded) = encode(*ids);
+            assert!(id.is_none());
+
+            let chunks = stream::iter(vec![Ok::<_, PayloadError>(encoded)]);
+            let decoder = Decoder::new(chunks, ContentEncoding::Zstd);
+            let decoded = decoder.try_collect::<Vec<_>>().await.unwrap();
+            assert_eq!(decoded.concat(), data);
+        }
+
+        let (id, encoded) = encode(Some("\"other\", \"json-v1\""));
+        assert_eq!(id.unwrap(), "json-v1");
+
+        let chunks = stream::iter(vec![Ok::<_, PayloadError>(encoded)]);
+        let decoder = Decoder::new(chunks, ContentEncoding::Zstd)
+            .zstd_dictionary(&dictionary)
+            .unwrap();
+        let decoded = decoder.try_collect::<Vec<_>>().await.unwrap();
+        assert_eq!(decoded.concat(), data);
+    }
+
+    #[test]
+    fn blocking_threshold() {
+        let data = b"hello world ".repeat(1024);
+
+        // large bodies are compressed up front when they are below the threshold
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body: ResponseBody<Body> = ResponseBody::Other(Body::from_slice(&data));
+        let config = CompressionConfig::new().blocking_threshold(usize::MAX);
+        let body = Encoder::response_with_config(
+            ContentEncoding::Gzip,
+            &mut head,
+            body,
+            None,
+            &config,
+        );
+
+        match body {
+            ResponseBody::Other(Body::Bytes(buf)) => assert_eq!(gunzip(&buf), data),
+            _ => panic!("expected compressed bytes"),
+        }
+
+        // small bodies are streamed when they are not
+        let data = b"hello world ".repeat(16);
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let body: ResponseBody<Body> = ResponseBody::Other(Body::from_slice(&data));
+        let config = CompressionConfig::new().blocking_threshold(0);
+        let body = Encoder::response_with_config(
+            ContentEncoding::Gzip,
+            &mut head,
+            body,
+            None,
+            &config,
+        );
+
+        assert_eq!(head.headers().get(CONTENT_ENCODING).unwrap(), "gzip");
+        assert_eq!(body.size(), BodySize::Stream);
+    }
+}
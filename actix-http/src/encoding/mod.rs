@@ -3,24 +3,50 @@
 use std::io;
 
 use bytes::{Bytes, BytesMut};
+use derive_more::{Display, Error};
 
+mod budget;
+mod config;
 mod decoder;
 mod encoder;
+mod negotiate;
 
+pub use self::budget::CompressionBudget;
+pub use self::config::CompressionConfig;
 pub use self::decoder::Decoder;
-pub use self::encoder::Encoder;
+pub use self::encoder::{Encoder, EncoderError, StreamEncoder};
+pub use self::negotiate::{negotiate, SUPPORTED_ENCODINGS};
+
+/// Marker error returned by [`Writer`] when one of its limits is exceeded.
+#[derive(Debug, Display, Error)]
+#[display(fmt = "Decoded payload reached size limit.")]
+pub(self) struct LimitExceeded;
 
 pub(self) struct Writer {
     buf: BytesMut,
+    remaining: usize,
+    max_chunk: usize,
 }
 
 impl Writer {
     fn new() -> Writer {
         Writer {
             buf: BytesMut::with_capacity(8192),
+            remaining: usize::MAX,
+            max_chunk: usize::MAX,
         }
     }
 
+    /// Limit total number of bytes that can be written.
+    fn max_size(&mut self, limit: usize) {
+        self.remaining = limit;
+    }
+
+    /// Limit number of bytes that can be buffered between calls to `take`.
+    fn max_chunk_size(&mut self, limit: usize) {
+        self.max_chunk = limit;
+    }
+
     fn take(&mut self) -> Bytes {
         self.buf.split().freeze()
     }
@@ -28,6 +54,11 @@ impl Writer {
 
 impl io::Write for Writer {
     fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if buf.len() > self.remaining || self.buf.len() + buf.len() > self.max_chunk {
+            return Err(io::Error::new(io::ErrorKind::Other, LimitExceeded));
+        }
+
+        self.remaining -= buf.len();
         self.buf.extend_from_slice(buf);
         Ok(buf.len())
     }
@@ -0,0 +1,221 @@
+use std::convert::TryFrom;
+
+use crate::header::{
+    q, split_comma_delimited, split_parameters, ContentEncoding, Quality,
+};
+
+/// Encodings that responses can be compressed with, in default order of server preference.
+///
+/// Only contains encodings enabled by this crate's `compress-*` features.
+pub const SUPPORTED_ENCODINGS: &[ContentEncoding] = &[
+    #[cfg(feature = "compress-brotli")]
+    ContentEncoding::Br,
+    #[cfg(feature = "compress-gzip")]
+    ContentEncoding::Gzip,
+    #[cfg(feature = "compress-gzip")]
+    ContentEncoding::Deflate,
+    #[cfg(feature = "compress-zstd")]
+    ContentEncoding::Zstd,
+];
+
+/// Chooses the content encoding of a response from the value of a request's `Accept-Encoding`
+/// header, following [RFC 7231 §5.3.4].
+///
+/// Of the `supported` encodings, the one with the highest q-value is chosen; ties are broken by
+/// the order of `supported`. Encodings not listed by the client get the q-value of `*`, if
+/// present, and are otherwise not acceptable. Compressed encodings are preferred over identity
+/// when their q-values are equal.
+///
+/// Identity encoding is always supported and acceptable unless it is excluded, with
+/// `identity;q=0` or `*;q=0`. It is only chosen over an acceptable compressed encoding when
+/// given a higher q-value, explicitly or through `*`. Returns `None` if no encoding is
+/// acceptable, in which case a `406 Not Acceptable` response may be sent.
+///
+/// Elements with invalid q-values and unknown codings are ignored.
+///
+/// ```
+/// use actix_http::{encoding::negotiate, http::header::ContentEncoding};
+///
+/// let supported = [ContentEncoding::Br, ContentEncoding::Gzip];
+///
+/// assert_eq!(
+///     negotiate("gzip, br;q=0.8, *;q=0.1", &supported),
+///     Some(ContentEncoding::Gzip),
+/// );
+/// assert_eq!(negotiate("deflate", &supported), Some(ContentEncoding::Identity));
+/// assert_eq!(negotiate("deflate, identity;q=0", &supported), None);
+/// ```
+///
+/// [RFC 7231 §5.3.4]: https://datatracker.ietf.org/doc/html/rfc7231#section-5.3.4
+pub fn negotiate(
+    accept_encoding: &str,
+    supported: &[ContentEncoding],
+) -> Option<ContentEncoding> {
+    let mut listed = Vec::new();
+    let mut wildcard = None;
+    let mut identity = None;
+
+    for item in split_comma_delimited(accept_encoding) {
+        let (coding, params) = split_parameters(item);
+
+        let mut quality = Some(Quality::default());
+        for (name, val) in params {
+            if name.eq_ignore_ascii_case("q") {
+                quality = parse_quality(&val);
+            }
+        }
+
+        let quality = match quality {
+            Some(quality) => quality,
+            None => continue,
+        };
+
+        if coding == "*" {
+            wildcard = Some(quality);
+        } else if coding.eq_ignore_ascii_case("identity") {
+            identity = Some(quality);
+        } else if let Some(enc) = parse_coding(coding) {
+            listed.push((enc, quality));
+        }
+    }
+
+    let zero = q(0u16);
+
+    let quality_of = |enc: ContentEncoding| {
+        listed
+            .iter()
+            .find(|(listed, _)| *listed == enc)
+            .map(|(_, quality)| *quality)
+            .or(wildcard)
+            .unwrap_or(zero)
+    };
+
+    let mut best: Option<(ContentEncoding, Quality)> = None;
+
+    for &enc in supported {
+        if matches!(enc, ContentEncoding::Identity | ContentEncoding::Auto) {
+            continue;
+        }
+
+        let quality = quality_of(enc);
+
+        if quality > zero && best.map_or(true, |(_, best)| quality > best) {
+            best = Some((enc, quality));
+        }
+    }
+
+    // identity is acceptable unless excluded but only preferred when given a higher q-value
+    let identity = identity.or(wildcard);
+
+    match best {
+        Some((enc, quality))
+            if identity.map_or(true, |identity| quality >= identity) =>
+        {
+            Some(enc)
+        }
+        _ if identity.map_or(true, |identity| identity > zero) => {
+            Some(ContentEncoding::Identity)
+        }
+        _ => None,
+    }
+}
+
+/// Parses coding names of compressed encodings, including the `x-gzip` alias.
+fn parse_coding(coding: &str) -> Option<ContentEncoding> {
+    if coding.eq_ignore_ascii_case("br") {
+        Some(ContentEncoding::Br)
+    } else if coding.eq_ignore_ascii_case("gzip")
+        || coding.eq_ignore_ascii_case("x-gzip")
+    {
+        Some(ContentEncoding::Gzip)
+    } else if coding.eq_ignore_ascii_case("deflate") {
+        Some(ContentEncoding::Deflate)
+    } else if coding.eq_ignore_ascii_case("zstd") {
+        Some(ContentEncoding::Zstd)
+    } else {
+        None
+    }
+}
+
+/// Parses q-value with at most three decimal places.
+fn parse_quality(val: &str) -> Option<Quality> {
+    if val.is_empty() || val.len() > 5 {
+        return None;
+    }
+
+    let value = val.parse::<f32>().ok()?;
+    Quality::try_from(value).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ALL: &[ContentEncoding] = &[
+        ContentEncoding::Br,
+        ContentEncoding::Gzip,
+        ContentEncoding::Deflate,
+        ContentEncoding::Zstd,
+    ];
+
+    #[test]
+    fn highest_quality_wins() {
+        assert_eq!(
+            negotiate("gzip;q=0.5, deflate;q=0.9, br;q=0.1", ALL),
+            Some(ContentEncoding::Deflate)
+        );
+        assert_eq!(
+            negotiate("zstd, gzip", ALL),
+            Some(ContentEncoding::Gzip),
+            "ties are broken by server order"
+        );
+        assert_eq!(
+            negotiate("GZIP;Q=1, x-gzip", &[ContentEncoding::Gzip]),
+            Some(ContentEncoding::Gzip)
+        );
+    }
+
+    #[test]
+    fn wildcard() {
+        assert_eq!(negotiate("*", ALL), Some(ContentEncoding::Br));
+        assert_eq!(
+            negotiate("br;q=0, *;q=0.5", ALL),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(negotiate("*;q=0", ALL), None);
+        assert_eq!(
+            negotiate("*;q=0, identity", ALL),
+            Some(ContentEncoding::Identity)
+        );
+    }
+
+    #[test]
+    fn identity() {
+        assert_eq!(negotiate("", ALL), Some(ContentEncoding::Identity));
+        assert_eq!(negotiate("compress", ALL), Some(ContentEncoding::Identity));
+        assert_eq!(
+            negotiate("gzip", &[ContentEncoding::Br]),
+            Some(ContentEncoding::Identity)
+        );
+        assert_eq!(
+            negotiate("gzip;q=0.5, identity", ALL),
+            Some(ContentEncoding::Identity)
+        );
+        assert_eq!(
+            negotiate("gzip, identity", ALL),
+            Some(ContentEncoding::Gzip)
+        );
+        assert_eq!(
+            negotiate("gzip, identity;q=0", &[ContentEncoding::Br]),
+            None
+        );
+    }
+
+    #[test]
+    fn invalid_quality() {
+        assert_eq!(
+            negotiate("br;q=2, gzip;q=0.0001, deflate;q=x, zstd;q=0.5", ALL),
+            Some(ContentEncoding::Zstd)
+        );
+    }
+}
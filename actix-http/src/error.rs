@@ -271,14 +271,34 @@ pub enum PayloadError {
     #[display(fmt = "Can not decode content-encoding.")]
     EncodingCorrupted,
 
+    /// Content codings applied to the payload are not supported.
+    #[display(fmt = "Unsupported content-encoding.")]
+    UnsupportedEncoding,
+
     /// Payload reached size limit.
     #[display(fmt = "Payload reached size limit.")]
     Overflow,
 
+    /// Decoded payload reached size limit.
+    #[display(fmt = "Decoded payload reached size limit.")]
+    DecodeOverflow,
+
     /// Payload length is unknown.
     #[display(fmt = "Payload length is unknown.")]
     UnknownLength,
 
+    /// Payload exceeded the client's size limit.
+    #[display(fmt = "Payload exceeded size limit.")]
+    TooLarge,
+
+    /// No payload chunk was received within the client's idle timeout.
+    #[display(fmt = "Payload idle timeout elapsed.")]
+    IdleTimeout,
+
+    /// Payload was not read to the end before the deadline of the client's request.
+    #[display(fmt = "Request deadline elapsed while reading payload.")]
+    Timeout,
+
     /// HTTP/2 payload error.
     #[display(fmt = "{}", _0)]
     Http2Payload(h2::Error),
@@ -294,8 +314,13 @@ impl std::error::Error for PayloadError {
             PayloadError::Incomplete(None) => None,
             PayloadError::Incomplete(Some(err)) => Some(err as &dyn std::error::Error),
             PayloadError::EncodingCorrupted => None,
+            PayloadError::UnsupportedEncoding => None,
             PayloadError::Overflow => None,
+            PayloadError::DecodeOverflow => None,
             PayloadError::UnknownLength => None,
+            PayloadError::TooLarge => None,
+            PayloadError::IdleTimeout => None,
+            PayloadError::Timeout => None,
             PayloadError::Http2Payload(err) => Some(err as &dyn std::error::Error),
             PayloadError::Io(err) => Some(err as &dyn std::error::Error),
         }
@@ -368,6 +393,14 @@ pub enum DispatchError {
     #[display(fmt = "The first request did not complete within the specified timeout")]
     SlowRequestTimeout,
 
+    /// Response body did not produce a chunk within the specified timeout.
+    #[display(fmt = "Response body did not make progress within the specified timeout")]
+    BodyTimeout,
+
+    /// Response body produced more or fewer bytes than its declared size.
+    #[display(fmt = "Response body size does not match its declared size")]
+    BodyLength,
+
     /// Disconnect timeout. Makes sense for ssl streams.
     #[display(fmt = "Connection shutdown timeout")]
     DisconnectTimeout,
@@ -11,6 +11,7 @@ use super::{Message, MessageType};
 use crate::body::BodySize;
 use crate::config::ServiceConfig;
 use crate::error::{ParseError, PayloadError};
+use crate::header::HeaderMap;
 use crate::message::{ConnectionType, RequestHeadType, ResponseHead};
 
 bitflags! {
@@ -18,6 +19,27 @@ bitflags! {
         const HEAD              = 0b0000_0001;
         const KEEPALIVE_ENABLED = 0b0000_1000;
         const STREAM            = 0b0001_0000;
+        const CAPTURE_RAW_HEAD  = 0b0010_0000;
+    }
+}
+
+/// Bytes of a response head as received, i.e. the status line and headers including the empty
+/// line ending the head.
+///
+/// Inserted into the extensions of response heads decoded by a [`ClientCodec`] with
+/// [raw head capture](ClientCodec::capture_raw_head) enabled.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RawResponseHead(Bytes);
+
+impl RawResponseHead {
+    /// Returns bytes of the response head.
+    pub fn as_bytes(&self) -> &Bytes {
+        &self.0
+    }
+
+    /// Convert into bytes of the response head.
+    pub fn into_bytes(self) -> Bytes {
+        self.0
     }
 }
 
@@ -33,14 +55,17 @@ pub struct ClientPayloadCodec {
 
 struct ClientCodecInner {
     config: ServiceConfig,
-    decoder: decoder::MessageDecoder<ResponseHead>,
+    max_head_size: usize,
+    max_headers: usize,
     payload: Option<PayloadDecoder>,
     version: Version,
     ctype: ConnectionType,
+    trailers: Option<HeaderMap>,
 
     // encoder part
     flags: Flags,
     encoder: encoder::MessageEncoder<RequestHeadType>,
+    send_trailers: Option<HeaderMap>,
 }
 
 impl Default for ClientCodec {
@@ -62,17 +87,45 @@ impl ClientCodec {
         ClientCodec {
             inner: ClientCodecInner {
                 config,
-                decoder: decoder::MessageDecoder::default(),
+                max_head_size: decoder::MAX_BUFFER_SIZE,
+                max_headers: decoder::MAX_HEADERS,
                 payload: None,
                 version: Version::HTTP_11,
                 ctype: ConnectionType::Close,
+                trailers: None,
 
                 flags,
                 encoder: encoder::MessageEncoder::default(),
+                send_trailers: None,
             },
         }
     }
 
+    /// Set maximum size of response heads in bytes. Set to 128 KiB by default.
+    ///
+    /// Decoding larger response heads fails with [`ParseError::TooLarge`].
+    pub fn max_head_size(mut self, size: usize) -> Self {
+        self.inner.max_head_size = size;
+        self
+    }
+
+    /// Set maximum number of response headers. Set to 96 by default.
+    ///
+    /// Decoding response heads with more headers fails with [`ParseError::TooLarge`].
+    pub fn max_headers(mut self, count: usize) -> Self {
+        self.inner.max_headers = count;
+        self
+    }
+
+    /// Store the bytes of decoded response heads in their extensions as [`RawResponseHead`], e.g.
+    /// for debugging interoperability issues. Disabled by default.
+    ///
+    /// The bytes are shared with the parsed headers, so capturing does not copy them.
+    pub fn capture_raw_head(mut self, enabled: bool) -> Self {
+        self.inner.flags.set(Flags::CAPTURE_RAW_HEAD, enabled);
+        self
+    }
+
     /// Check if request is upgrade
     pub fn upgrade(&self) -> bool {
         self.inner.ctype == ConnectionType::Upgrade
@@ -94,6 +147,18 @@ impl ClientCodec {
         }
     }
 
+    /// Close connection after the current response, e.g. if the request body was not sent.
+    pub fn force_close(&mut self) {
+        self.inner.ctype = ConnectionType::Close;
+    }
+
+    /// Set trailer fields sent after the last chunk of the request body.
+    ///
+    /// Trailers are only sent with chunked transfer encoding.
+    pub fn set_trailers(&mut self, trailers: HeaderMap) {
+        self.inner.send_trailers = Some(trailers);
+    }
+
     /// Convert message codec to a payload codec
     pub fn into_payload_codec(self) -> ClientPayloadCodec {
         ClientPayloadCodec { inner: self.inner }
@@ -106,6 +171,11 @@ impl ClientPayloadCodec {
         self.inner.ctype == ConnectionType::KeepAlive
     }
 
+    /// Take trailer fields received after the last chunk of the response body.
+    pub fn take_trailers(&mut self) -> Option<HeaderMap> {
+        self.inner.trailers.take()
+    }
+
     /// Transform payload codec to a message codec
     pub fn into_message_codec(self) -> ClientCodec {
         ClientCodec { inner: self.inner }
@@ -119,7 +189,15 @@ impl Decoder for ClientCodec {
     fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
         debug_assert!(!self.inner.payload.is_some(), "Payload decoder is set");
 
-        if let Some((req, payload)) = self.inner.decoder.decode(src)? {
+        let (max_size, max_headers) = (self.inner.max_head_size, self.inner.max_headers);
+
+        if let Some((req, payload, raw)) =
+            ResponseHead::decode_raw(src, max_size, max_headers)?
+        {
+            if self.inner.flags.contains(Flags::CAPTURE_RAW_HEAD) {
+                req.extensions_mut().insert(RawResponseHead(raw));
+            }
+
             if let Some(ctype) = req.ctype() {
                 // do not use peer's keep-alive
                 self.inner.ctype = if ctype == ConnectionType::KeepAlive {
@@ -165,7 +243,11 @@ impl Decoder for ClientPayloadCodec {
                 Some(Some(chunk))
             }
             Some(PayloadItem::Eof) => {
-                self.inner.payload.take();
+                self.inner.trailers = self
+                    .inner
+                    .payload
+                    .take()
+                    .and_then(|mut payload| payload.take_trailers());
                 Some(None)
             }
             None => None,
@@ -216,9 +298,10 @@ impl Encoder<Message<(RequestHeadType, BodySize)>> for ClientCodec {
             Message::Chunk(Some(bytes)) => {
                 self.inner.encoder.encode_chunk(bytes.as_ref(), dst)?;
             }
-            Message::Chunk(None) => {
-                self.inner.encoder.encode_eof(dst)?;
-            }
+            Message::Chunk(None) => match self.inner.send_trailers.take() {
+                Some(trailers) => self.inner.encoder.encode_trailers(&trailers, dst)?,
+                None => self.inner.encoder.encode_eof(dst)?,
+            },
         }
         Ok(())
     }
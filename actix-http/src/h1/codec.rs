@@ -11,6 +11,8 @@ use super::{Message, MessageType};
 use crate::body::BodySize;
 use crate::config::ServiceConfig;
 use crate::error::ParseError;
+use crate::header::{Header, HeaderMap, Te, CONTENT_TYPE};
+use crate::helpers::{dedup_singleton_headers, with_default_charset};
 use crate::message::ConnectionType;
 use crate::request::Request;
 use crate::response::Response;
@@ -20,6 +22,7 @@ bitflags! {
         const HEAD              = 0b0000_0001;
         const KEEPALIVE_ENABLED = 0b0000_0010;
         const STREAM            = 0b0000_0100;
+        const TRAILERS          = 0b0000_1000;
     }
 }
 
@@ -28,12 +31,14 @@ pub struct Codec {
     config: ServiceConfig,
     decoder: decoder::MessageDecoder<Request>,
     payload: Option<PayloadDecoder>,
+    payload_trailers: Option<HeaderMap>,
     version: Version,
     ctype: ConnectionType,
 
     // encoder part
     flags: Flags,
     encoder: encoder::MessageEncoder<Response<()>>,
+    trailers: Option<HeaderMap>,
 }
 
 impl Default for Codec {
@@ -64,9 +69,11 @@ impl Codec {
             flags,
             decoder: decoder::MessageDecoder::default(),
             payload: None,
+            payload_trailers: None,
             version: Version::HTTP_11,
             ctype: ConnectionType::Close,
             encoder: encoder::MessageEncoder::default(),
+            trailers: None,
         }
     }
 
@@ -100,10 +107,34 @@ impl Codec {
         }
     }
 
+    /// Check if last response is to a `HEAD` request, i.e. sent without body.
+    #[inline]
+    pub(crate) fn is_head(&self) -> bool {
+        self.flags.contains(Flags::HEAD)
+    }
+
+    /// Check if last request signalled it accepts trailer fields with `TE: trailers`.
+    #[inline]
+    pub(crate) fn accepts_trailers(&self) -> bool {
+        self.flags.contains(Flags::TRAILERS)
+    }
+
     #[inline]
     pub fn config(&self) -> &ServiceConfig {
         &self.config
     }
+
+    /// Set trailer fields sent after the last chunk of the current response body.
+    ///
+    /// Trailers are only sent with chunked transfer encoding.
+    pub fn set_trailers(&mut self, trailers: HeaderMap) {
+        self.trailers = Some(trailers);
+    }
+
+    /// Take trailer fields received after the last chunk of the current request body.
+    pub fn take_trailers(&mut self) -> Option<HeaderMap> {
+        self.payload_trailers.take()
+    }
 }
 
 impl Decoder for Codec {
@@ -115,14 +146,29 @@ impl Decoder for Codec {
             Ok(match payload.decode(src)? {
                 Some(PayloadItem::Chunk(chunk)) => Some(Message::Chunk(Some(chunk))),
                 Some(PayloadItem::Eof) => {
-                    self.payload.take();
+                    self.payload_trailers = self
+                        .payload
+                        .take()
+                        .and_then(|mut payload| payload.take_trailers());
                     Some(Message::Chunk(None))
                 }
                 None => None,
             })
-        } else if let Some((req, payload)) = self.decoder.decode(src)? {
+        } else if let Some((mut req, payload)) = self.decoder.decode(src)? {
+            let policy = self.config.duplicate_headers();
+            dedup_singleton_headers(&mut req.head_mut().headers, policy).map_err(
+                |name| {
+                    log::debug!("duplicate {} header", name);
+                    ParseError::Header
+                },
+            )?;
+
             let head = req.head();
             self.flags.set(Flags::HEAD, head.method == Method::HEAD);
+            self.flags.set(
+                Flags::TRAILERS,
+                Te::parse(&req).map_or(false, |te| te.accepts_trailers()),
+            );
             self.version = head.version;
             self.ctype = head.connection_type();
             if self.ctype == ConnectionType::KeepAlive
@@ -158,6 +204,16 @@ impl Encoder<Message<(Response<()>, BodySize)>> for Codec {
                 // set response version
                 res.head_mut().version = self.version;
 
+                if self.config.default_charset() {
+                    let ct = res
+                        .headers()
+                        .get(CONTENT_TYPE)
+                        .and_then(with_default_charset);
+                    if let Some(ct) = ct {
+                        res.headers_mut().insert(CONTENT_TYPE, ct);
+                    }
+                }
+
                 // connection status
                 self.ctype = if let Some(ct) = res.head().ctype() {
                     if ct == ConnectionType::KeepAlive {
@@ -185,9 +241,10 @@ impl Encoder<Message<(Response<()>, BodySize)>> for Codec {
             Message::Chunk(Some(bytes)) => {
                 self.encoder.encode_chunk(bytes.as_ref(), dst)?;
             }
-            Message::Chunk(None) => {
-                self.encoder.encode_eof(dst)?;
-            }
+            Message::Chunk(None) => match self.trailers.take() {
+                Some(trailers) => self.encoder.encode_trailers(&trailers, dst)?,
+                None => self.encoder.encode_eof(dst)?,
+            },
         }
         Ok(())
     }
@@ -237,4 +294,24 @@ mod tests {
         assert_eq!(*req.method(), Method::POST);
         assert!(req.chunked().unwrap());
     }
+
+    #[actix_rt::test]
+    async fn test_duplicate_headers() {
+        let mut codec = Codec::default();
+
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+             host: a.example\r\n\
+             host: b.example\r\n\r\n",
+        );
+        assert!(matches!(codec.decode(&mut buf), Err(ParseError::Header)));
+
+        // duplicates are rejected even if the values are equal
+        let mut buf = BytesMut::from(
+            "POST /test HTTP/1.1\r\n\
+             content-length: 4\r\n\
+             content-length: 4\r\n\r\n",
+        );
+        assert!(matches!(codec.decode(&mut buf), Err(ParseError::Header)));
+    }
 }
@@ -15,7 +15,7 @@ use crate::message::{ConnectionType, ResponseHead};
 use crate::request::Request;
 
 pub(crate) const MAX_BUFFER_SIZE: usize = 131_072;
-const MAX_HEADERS: usize = 96;
+pub(crate) const MAX_HEADERS: usize = 96;
 
 /// Incoming message decoder
 pub(crate) struct MessageDecoder<T: MessageType>(PhantomData<T>);
@@ -88,9 +88,12 @@ pub(crate) trait MessageType: Sized {
                     header::CONTENT_LENGTH => {
                         if let Ok(s) = value.to_str() {
                             if let Ok(len) = s.parse::<u64>() {
-                                if len != 0 {
-                                    content_length = Some(len);
+                                // differing lengths make the message length ambiguous
+                                if content_length.map_or(false, |prev| prev != len) {
+                                    debug!("conflicting Content-Length headers");
+                                    return Err(ParseError::Header);
                                 }
+                                content_length = Some(len);
                             } else {
                                 debug!("illegal Content-Length: {:?}", s);
                                 return Err(ParseError::Header);
@@ -156,7 +159,7 @@ pub(crate) trait MessageType: Sized {
             )))
         } else if has_upgrade_websocket {
             Ok(PayloadLength::UpgradeWebSocket)
-        } else if let Some(len) = content_length {
+        } else if let Some(len) = content_length.filter(|&len| len != 0) {
             // Content-Length
             Ok(PayloadLength::Payload(PayloadType::Payload(
                 PayloadDecoder::length(len),
@@ -259,14 +262,53 @@ impl MessageType for ResponseHead {
     }
 
     fn decode(src: &mut BytesMut) -> Result<Option<(Self, PayloadType)>, ParseError> {
-        let mut headers: [HeaderIndex; MAX_HEADERS] = EMPTY_HEADER_INDEX_ARRAY;
+        Self::decode_limited(src, MAX_BUFFER_SIZE, MAX_HEADERS)
+    }
+}
 
-        let (len, ver, status, h_len) = {
-            let mut parsed: [httparse::Header<'_>; MAX_HEADERS] = EMPTY_HEADER_ARRAY;
+impl ResponseHead {
+    /// Decode response head of at most `max_size` bytes with at most `max_headers` headers.
+    pub(crate) fn decode_limited(
+        src: &mut BytesMut,
+        max_size: usize,
+        max_headers: usize,
+    ) -> Result<Option<(Self, PayloadType)>, ParseError> {
+        let res = Self::decode_raw(src, max_size, max_headers)?;
+        Ok(res.map(|(head, payload, _)| (head, payload)))
+    }
+
+    /// Decode response head like [`decode_limited`](Self::decode_limited), also returning the
+    /// bytes of the head as received.
+    pub(crate) fn decode_raw(
+        src: &mut BytesMut,
+        max_size: usize,
+        max_headers: usize,
+    ) -> Result<Option<(Self, PayloadType, Bytes)>, ParseError> {
+        // heads with more headers than fit the stack arrays are parsed into heap buffers
+        let mut headers_buf;
+        let mut parsed_buf;
+        let mut headers_arr: [HeaderIndex; MAX_HEADERS] = EMPTY_HEADER_INDEX_ARRAY;
+        let mut parsed_arr: [httparse::Header<'_>; MAX_HEADERS] = EMPTY_HEADER_ARRAY;
+
+        let (headers, parsed) = if max_headers <= MAX_HEADERS {
+            (
+                &mut headers_arr[..max_headers],
+                &mut parsed_arr[..max_headers],
+            )
+        } else {
+            headers_buf = vec![EMPTY_HEADER_INDEX; max_headers];
+            parsed_buf = vec![httparse::EMPTY_HEADER; max_headers];
+            (&mut headers_buf[..], &mut parsed_buf[..])
+        };
 
-            let mut res = httparse::Response::new(&mut parsed);
-            match res.parse(src)? {
-                httparse::Status::Complete(len) => {
+        let (len, ver, status, h_len) = {
+            let mut res = httparse::Response::new(parsed);
+            match res.parse(src) {
+                Ok(httparse::Status::Complete(len)) if len > max_size => {
+                    debug!("response head of {} bytes exceeds limit", len);
+                    return Err(ParseError::TooLarge);
+                }
+                Ok(httparse::Status::Complete(len)) => {
                     let version = if res.version.unwrap() == 1 {
                         Version::HTTP_11
                     } else {
@@ -274,18 +316,23 @@ impl MessageType for ResponseHead {
                     };
                     let status = StatusCode::from_u16(res.code.unwrap())
                         .map_err(|_| ParseError::Status)?;
-                    HeaderIndex::record(src, res.headers, &mut headers);
+                    HeaderIndex::record(src, res.headers, headers);
 
                     (len, version, status, res.headers.len())
                 }
-                httparse::Status::Partial => {
-                    return if src.len() >= MAX_BUFFER_SIZE {
-                        error!("MAX_BUFFER_SIZE unprocessed data reached, closing");
+                Ok(httparse::Status::Partial) => {
+                    return if src.len() >= max_size {
+                        error!("response head size limit reached, closing");
                         Err(ParseError::TooLarge)
                     } else {
                         Ok(None)
                     }
                 }
+                Err(httparse::Error::TooManyHeaders) => {
+                    debug!("response head has more than {} headers", max_headers);
+                    return Err(ParseError::TooLarge);
+                }
+                Err(err) => return Err(err.into()),
             }
         };
 
@@ -293,7 +340,8 @@ impl MessageType for ResponseHead {
         msg.version = ver;
 
         // convert headers
-        let length = msg.set_headers(&src.split_to(len).freeze(), &headers[..h_len])?;
+        let raw = src.split_to(len).freeze();
+        let length = msg.set_headers(&raw, &headers[..h_len])?;
 
         // message payload
         let decoder = if let PayloadLength::Payload(pl) = length {
@@ -311,7 +359,7 @@ impl MessageType for ResponseHead {
             }
         };
 
-        Ok(Some((msg, decoder)))
+        Ok(Some((msg, decoder, raw)))
     }
 }
 
@@ -361,26 +409,37 @@ pub enum PayloadItem {
 ///
 /// If a message body does not include a Transfer-Encoding, it *should*
 /// include a Content-Length header.
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone)]
 pub struct PayloadDecoder {
     kind: Kind,
+    trailers: Option<HeaderMap>,
 }
 
 impl PayloadDecoder {
     pub fn length(x: u64) -> PayloadDecoder {
         PayloadDecoder {
             kind: Kind::Length(x),
+            trailers: None,
         }
     }
 
     pub fn chunked() -> PayloadDecoder {
         PayloadDecoder {
             kind: Kind::Chunked(ChunkedState::Size, 0),
+            trailers: None,
         }
     }
 
     pub fn eof() -> PayloadDecoder {
-        PayloadDecoder { kind: Kind::Eof }
+        PayloadDecoder {
+            kind: Kind::Eof,
+            trailers: None,
+        }
+    }
+
+    /// Take trailer fields sent after the last chunk of a chunked payload.
+    pub fn take_trailers(&mut self) -> Option<HeaderMap> {
+        self.trailers.take()
     }
 }
 
@@ -417,8 +476,7 @@ enum ChunkedState {
     Body,
     BodyCr,
     BodyLf,
-    EndCr,
-    EndLf,
+    Trailers,
     End,
 }
 
@@ -452,7 +510,7 @@ impl Decoder for PayloadDecoder {
                 loop {
                     let mut buf = None;
                     // advances the chunked state
-                    *state = match state.step(src, size, &mut buf) {
+                    *state = match state.step(src, size, &mut buf, &mut self.trailers) {
                         Poll::Pending => return Ok(None),
                         Poll::Ready(Ok(state)) => state,
                         Poll::Ready(Err(e)) => return Err(e),
@@ -498,6 +556,7 @@ impl ChunkedState {
         body: &mut BytesMut,
         size: &mut u64,
         buf: &mut Option<Bytes>,
+        trailers: &mut Option<HeaderMap>,
     ) -> Poll<Result<ChunkedState, io::Error>> {
         use self::ChunkedState::*;
         match *self {
@@ -508,8 +567,7 @@ impl ChunkedState {
             Body => ChunkedState::read_body(body, size, buf),
             BodyCr => ChunkedState::read_body_cr(body),
             BodyLf => ChunkedState::read_body_lf(body),
-            EndCr => ChunkedState::read_end_cr(body),
-            EndLf => ChunkedState::read_end_lf(body),
+            Trailers => ChunkedState::read_trailers(body, trailers),
             End => Poll::Ready(Ok(ChunkedState::End)),
         }
     }
@@ -570,7 +628,7 @@ impl ChunkedState {
     ) -> Poll<Result<ChunkedState, io::Error>> {
         match byte!(rdr) {
             b'\n' if *size > 0 => Poll::Ready(Ok(ChunkedState::Body)),
-            b'\n' if *size == 0 => Poll::Ready(Ok(ChunkedState::EndCr)),
+            b'\n' if *size == 0 => Poll::Ready(Ok(ChunkedState::Trailers)),
             _ => Poll::Ready(Err(io::Error::new(
                 io::ErrorKind::InvalidInput,
                 "Invalid chunk size LF",
@@ -624,23 +682,59 @@ impl ChunkedState {
             ))),
         }
     }
-    fn read_end_cr(rdr: &mut BytesMut) -> Poll<Result<ChunkedState, io::Error>> {
-        match byte!(rdr) {
-            b'\r' => Poll::Ready(Ok(ChunkedState::EndLf)),
-            _ => Poll::Ready(Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Invalid chunk end CR",
-            ))),
-        }
-    }
-    fn read_end_lf(rdr: &mut BytesMut) -> Poll<Result<ChunkedState, io::Error>> {
-        match byte!(rdr) {
-            b'\n' => Poll::Ready(Ok(ChunkedState::End)),
-            _ => Poll::Ready(Err(io::Error::new(
-                io::ErrorKind::InvalidInput,
-                "Invalid chunk end LF",
-            ))),
+    /// Read trailer section after the last chunk, ending with an empty line.
+    fn read_trailers(
+        rdr: &mut BytesMut,
+        trailers: &mut Option<HeaderMap>,
+    ) -> Poll<Result<ChunkedState, io::Error>> {
+        let mut parsed: [httparse::Header<'_>; MAX_HEADERS] = EMPTY_HEADER_ARRAY;
+
+        let (len, map) = match httparse::parse_headers(rdr, &mut parsed) {
+            Ok(httparse::Status::Complete((len, parsed))) => {
+                let mut map = HeaderMap::with_capacity(parsed.len());
+
+                for header in parsed {
+                    let name = HeaderName::from_bytes(header.name.as_bytes());
+                    let value = HeaderValue::from_bytes(header.value);
+
+                    match (name, value) {
+                        (Ok(name), Ok(value)) => map.append(name, value),
+                        _ => {
+                            return Poll::Ready(Err(io::Error::new(
+                                io::ErrorKind::InvalidInput,
+                                "Invalid chunked trailer field",
+                            )))
+                        }
+                    }
+                }
+
+                (len, map)
+            }
+            Ok(httparse::Status::Partial) => {
+                return if rdr.len() >= MAX_BUFFER_SIZE {
+                    Poll::Ready(Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "Chunked trailers too large",
+                    )))
+                } else {
+                    Poll::Pending
+                };
+            }
+            Err(_) => {
+                return Poll::Ready(Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "Invalid chunked trailers",
+                )))
+            }
+        };
+
+        rdr.advance(len);
+
+        if !map.is_empty() {
+            *trailers = Some(map);
         }
+
+        Poll::Ready(Ok(ChunkedState::End))
     }
 }
 
@@ -1015,6 +1109,17 @@ mod tests {
         expect_parse_err!(&mut buf);
     }
 
+    #[test]
+    fn test_headers_content_length_conflict() {
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+             content-length: 0\r\n\
+             content-length: 5\r\n\r\n",
+        );
+
+        expect_parse_err!(&mut buf);
+    }
+
     #[test]
     fn test_invalid_header() {
         let mut buf = BytesMut::from(
@@ -1197,10 +1302,6 @@ mod tests {
         let msg = pl.decode(&mut buf).unwrap().unwrap();
         assert_eq!(msg.chunk().as_ref(), b"li");
 
-        //trailers
-        //buf.feed_data("test: test\r\n");
-        //not_ready!(reader.parse(&mut buf, &mut readbuf));
-
         buf.extend(b"ne\r\n0\r\n");
         let msg = pl.decode(&mut buf).unwrap().unwrap();
         assert_eq!(msg.chunk().as_ref(), b"ne");
@@ -1208,6 +1309,36 @@ mod tests {
 
         buf.extend(b"\r\n");
         assert!(pl.decode(&mut buf).unwrap().unwrap().eof());
+        assert!(pl.take_trailers().is_none());
+    }
+
+    #[test]
+    fn test_parse_chunked_payload_trailers() {
+        let mut buf = BytesMut::from(
+            "GET /test HTTP/1.1\r\n\
+            transfer-encoding: chunked\r\n\
+            \r\n",
+        );
+
+        let mut reader = MessageDecoder::<Request>::default();
+        let (_, pl) = reader.decode(&mut buf).unwrap().unwrap();
+        let mut pl = pl.unwrap();
+
+        buf.extend(b"4\r\ndata\r\n0\r\ngrpc-status: 0\r\n");
+        let msg = pl.decode(&mut buf).unwrap().unwrap();
+        assert_eq!(msg.chunk().as_ref(), b"data");
+        assert!(pl.decode(&mut buf).unwrap().is_none());
+
+        buf.extend(b"grpc-message: ok\r\n\r\n");
+        assert!(pl.decode(&mut buf).unwrap().unwrap().eof());
+
+        let trailers = pl.take_trailers().unwrap();
+        assert_eq!(trailers.get("grpc-status").unwrap(), "0");
+        assert_eq!(trailers.get("grpc-message").unwrap(), "ok");
+
+        let mut buf = BytesMut::from("0\r\ninvalid\r\n\r\n");
+        let mut pl = PayloadDecoder::chunked();
+        assert!(pl.decode(&mut buf).is_err());
     }
 
     #[test]
@@ -1243,4 +1374,48 @@ mod tests {
         let chunk = pl.decode(&mut buf).unwrap().unwrap();
         assert_eq!(chunk, PayloadItem::Chunk(Bytes::from_static(b"test data")));
     }
+
+    #[test]
+    fn test_response_head_limits() {
+        let head = "HTTP/1.1 200 OK\r\nx-a: 1\r\nx-b: 2\r\nx-c: 3\r\n\r\n";
+
+        let mut buf = BytesMut::from(head);
+        let (res, _) = ResponseHead::decode_limited(&mut buf, head.len(), 3)
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.headers().len(), 3);
+
+        let mut buf = BytesMut::from(head);
+        let res = ResponseHead::decode_limited(&mut buf, head.len() - 1, 3);
+        assert!(matches!(res, Err(ParseError::TooLarge)));
+
+        let mut buf = BytesMut::from(head);
+        let res = ResponseHead::decode_limited(&mut buf, head.len(), 2);
+        assert!(matches!(res, Err(ParseError::TooLarge)));
+
+        // incomplete heads are rejected once the buffered data reaches the limit
+        let mut buf = BytesMut::from(&head[..20]);
+        let res = ResponseHead::decode_limited(&mut buf, 32, 3);
+        assert!(matches!(res, Ok(None)));
+        let mut buf = BytesMut::from(&head[..32]);
+        let res = ResponseHead::decode_limited(&mut buf, 32, 3);
+        assert!(matches!(res, Err(ParseError::TooLarge)));
+
+        // more headers than fit on the stack
+        let mut head = String::from("HTTP/1.1 200 OK\r\n");
+        for i in 0..MAX_HEADERS + 4 {
+            head.push_str(&format!("x-{}: {}\r\n", i, i));
+        }
+        head.push_str("\r\n");
+
+        let mut buf = BytesMut::from(head.as_str());
+        let res = ResponseHead::decode_limited(&mut buf, MAX_BUFFER_SIZE, MAX_HEADERS);
+        assert!(matches!(res, Err(ParseError::TooLarge)));
+
+        let mut buf = BytesMut::from(head.as_str());
+        let (res, _) = ResponseHead::decode_limited(&mut buf, MAX_BUFFER_SIZE, 128)
+            .unwrap()
+            .unwrap();
+        assert_eq!(res.headers().len(), MAX_HEADERS + 4);
+    }
 }
@@ -15,15 +15,16 @@ use actix_service::Service;
 use bitflags::bitflags;
 use bytes::{Buf, BytesMut};
 use futures_core::ready;
-use log::{error, trace};
+use log::{error, trace, warn};
 use pin_project::pin_project;
 
 use crate::{
     body::{AnyBody, BodySize, MessageBody},
-    config::ServiceConfig,
+    config::{BodyLengthMismatch, Http10Expect, InFlight, ServiceConfig},
     error::{DispatchError, ParseError, PayloadError},
+    helpers::check_crlf,
     service::HttpFlow,
-    OnConnectData, Request, Response, StatusCode,
+    ConnectionType, OnConnectData, Request, Response, StatusCode, Version,
 };
 
 use super::{
@@ -43,6 +44,7 @@ bitflags! {
         const SHUTDOWN           = 0b0000_0100;
         const READ_DISCONNECT    = 0b0000_1000;
         const WRITE_DISCONNECT   = 0b0001_0000;
+        const BODY_EOF           = 0b0010_0000;
     }
 }
 
@@ -111,13 +113,28 @@ where
 
     #[pin]
     state: State<S, B, X>,
+    in_flight: Option<InFlight>,
     payload: Option<PayloadSender>,
+
+    /// Pipelined messages waiting to be handled.
+    ///
+    /// Always processed in FIFO order since responses to pipelined requests must be sent in the
+    /// same order that the requests were received, so the configured
+    /// [`Prioritizer`](crate::Prioritizer) is not consulted. See RFC 7230 §6.3.2.
     messages: VecDeque<DispatcherMessage>,
 
     ka_expire: Instant,
     #[pin]
     ka_timer: Option<Sleep>,
 
+    /// Fires when the response body stays pending for longer than the body poll timeout.
+    #[pin]
+    body_timer: Option<Sleep>,
+    /// Method and path of the request being responded to, for body poll timeout logging.
+    response_target: Option<String>,
+    /// Bytes of the response body left to send, for bodies of known size.
+    body_remaining: Option<u64>,
+
     io: Option<T>,
     read_buf: BytesMut,
     write_buf: BytesMut,
@@ -130,6 +147,14 @@ enum DispatcherMessage {
     Error(Response<()>),
 }
 
+/// Close connection once the current response is written, dropping pipelined messages. Used when
+/// the response was cut short and the connection can not be reused.
+fn close_after_response(flags: &mut Flags, messages: &mut VecDeque<DispatcherMessage>) {
+    flags.remove(Flags::KEEPALIVE);
+    flags.insert(Flags::SHUTDOWN);
+    messages.clear();
+}
+
 #[pin_project(project = StateProj)]
 enum State<S, B, X>
 where
@@ -209,6 +234,7 @@ where
                 write_buf: BytesMut::with_capacity(HW_BUFFER_SIZE),
                 payload: None,
                 state: State::None,
+                in_flight: None,
                 error: None,
                 messages: VecDeque::new(),
                 io: Some(io),
@@ -219,6 +245,9 @@ where
                 peer_addr,
                 ka_expire,
                 ka_timer,
+                body_timer: None,
+                response_target: None,
+                body_remaining: None,
             }),
 
             #[cfg(test)]
@@ -299,11 +328,23 @@ where
 
     fn send_response_inner(
         self: Pin<&mut Self>,
-        message: Response<()>,
+        mut message: Response<()>,
         body: &impl MessageBody,
     ) -> Result<BodySize, DispatchError> {
-        let size = body.size();
+        let mut size = body.size();
         let mut this = self.project();
+
+        // header values and reason phrase must not be able to split the response
+        let handling = this.codec.config().crlf_handling();
+        if let Err(name) = check_crlf(message.head_mut(), handling) {
+            error!(
+                "Response {} contains CR or LF characters; sending 500 response instead",
+                name
+            );
+            message = Response::with_body(StatusCode::INTERNAL_SERVER_ERROR, ());
+            size = BodySize::Empty;
+        }
+
         this.codec
             .encode(Message::Item((message, size)), &mut this.write_buf)
             .map_err(|err| {
@@ -315,6 +356,11 @@ where
 
         this.flags.set(Flags::KEEPALIVE, this.codec.keepalive());
 
+        *this.body_remaining = match size {
+            BodySize::Sized(len) if !this.codec.is_head() => Some(len),
+            _ => None,
+        };
+
         Ok(size)
     }
 
@@ -360,37 +406,39 @@ where
             let mut this = self.as_mut().project();
             match this.state.as_mut().project() {
                 // no future is in InnerDispatcher state. pop next message.
-                StateProj::None => match this.messages.pop_front() {
-                    // handle request message.
-                    Some(DispatcherMessage::Item(req)) => {
-                        // Handle `EXPECT: 100-Continue` header
-                        if req.head().expect() {
+                StateProj::None => {
+                    // previous request is finished so release its in-flight slot.
+                    *this.in_flight = None;
+
+                    match this.messages.pop_front() {
+                        // handle request message.
+                        Some(DispatcherMessage::Item(req)) => {
+                            let req = match self.as_mut().admit(req)? {
+                                Some(req) => req,
+                                None => continue,
+                            };
+
                             // set InnerDispatcher state and continue loop to poll it.
-                            let task = this.flow.expect.call(req);
-                            this.state.set(State::ExpectCall(task));
-                        } else {
-                            // the same as expect call.
-                            let task = this.flow.service.call(req);
-                            this.state.set(State::ServiceCall(task));
-                        };
-                    }
+                            self.as_mut().call_service(req)?;
+                        }
 
-                    // handle error message.
-                    Some(DispatcherMessage::Error(res)) => {
-                        // send_response would update InnerDispatcher state to SendPayload or
-                        // None(If response body is empty).
-                        // continue loop to poll it.
-                        self.as_mut().send_error_response(res, AnyBody::Empty)?;
-                    }
+                        // handle error message.
+                        Some(DispatcherMessage::Error(res)) => {
+                            // send_response would update InnerDispatcher state to SendPayload
+                            // or None(If response body is empty).
+                            // continue loop to poll it.
+                            self.as_mut().send_error_response(res, AnyBody::Empty)?;
+                        }
 
-                    // return with upgrade request and poll it exclusively.
-                    Some(DispatcherMessage::Upgrade(req)) => {
-                        return Ok(PollResponse::Upgrade(req));
-                    }
+                        // return with upgrade request and poll it exclusively.
+                        Some(DispatcherMessage::Upgrade(req)) => {
+                            return Ok(PollResponse::Upgrade(req));
+                        }
 
-                    // all messages are dealt with.
-                    None => return Ok(PollResponse::DoNothing),
-                },
+                        // all messages are dealt with.
+                        None => return Ok(PollResponse::DoNothing),
+                    }
+                }
                 StateProj::ServiceCall(fut) => match fut.poll(cx) {
                     // service call resolved. send response.
                     Poll::Ready(Ok(res)) => {
@@ -421,28 +469,134 @@ where
                     // keep populate writer buffer until buffer size limit hit,
                     // get blocked or finished.
                     while this.write_buf.len() < super::payload::MAX_BUFFER_SIZE {
+                        // body is exhausted; end it with its trailers, if any and the
+                        // client sent `TE: trailers`.
+                        if this.flags.contains(Flags::BODY_EOF) {
+                            match stream.as_mut().poll_trailers(cx) {
+                                Poll::Ready(Ok(trailers)) => {
+                                    this.flags.remove(Flags::BODY_EOF);
+                                    if let Some(trailers) = trailers {
+                                        if this.codec.accepts_trailers() {
+                                            this.codec.set_trailers(trailers);
+                                        }
+                                    }
+                                    this.codec.encode(
+                                        Message::Chunk(None),
+                                        &mut this.write_buf,
+                                    )?;
+                                    // payload stream finished.
+                                    // set state to None and handle next message
+                                    this.state.set(State::None);
+                                    continue 'res;
+                                }
+                                Poll::Ready(Err(err)) => {
+                                    return Err(DispatchError::Body(err.into()))
+                                }
+                                Poll::Pending => return Ok(PollResponse::DoNothing),
+                            }
+                        }
+
                         match stream.as_mut().poll_next(cx) {
                             Poll::Ready(Some(Ok(item))) => {
+                                this.body_timer.set(None);
+
+                                let mut close = false;
+                                if let Some(ref mut remaining) = this.body_remaining {
+                                    match remaining.checked_sub(item.len() as u64) {
+                                        Some(rem) => *remaining = rem,
+                                        None => {
+                                            // the encoder drops bytes over the declared size
+                                            *remaining = 0;
+                                            match this
+                                                .codec
+                                                .config()
+                                                .body_length_mismatch()
+                                            {
+                                                BodyLengthMismatch::Truncate => {}
+                                                BodyLengthMismatch::Error => {
+                                                    return Err(
+                                                        DispatchError::BodyLength,
+                                                    )
+                                                }
+                                                BodyLengthMismatch::LogAndClose => {
+                                                    warn!("Response body is longer than its declared size; closing connection");
+                                                    close = true;
+                                                }
+                                            }
+                                        }
+                                    }
+                                }
+
                                 this.codec.encode(
                                     Message::Chunk(Some(item)),
                                     &mut this.write_buf,
                                 )?;
+
+                                if close {
+                                    this.codec.encode(
+                                        Message::Chunk(None),
+                                        &mut this.write_buf,
+                                    )?;
+                                    close_after_response(this.flags, this.messages);
+                                    this.state.set(State::None);
+                                    continue 'res;
+                                }
                             }
 
                             Poll::Ready(None) => {
-                                this.codec
-                                    .encode(Message::Chunk(None), &mut this.write_buf)?;
-                                // payload stream finished.
-                                // set state to None and handle next message
-                                this.state.set(State::None);
-                                continue 'res;
+                                this.body_timer.set(None);
+
+                                // the response can not be completed; never reuse the connection
+                                if matches!(*this.body_remaining, Some(rem) if rem > 0) {
+                                    match this.codec.config().body_length_mismatch() {
+                                        BodyLengthMismatch::Error => {
+                                            return Err(DispatchError::BodyLength)
+                                        }
+                                        BodyLengthMismatch::LogAndClose => {
+                                            warn!("Response body is shorter than its declared size; closing connection");
+                                        }
+                                        BodyLengthMismatch::Truncate => {}
+                                    }
+
+                                    close_after_response(this.flags, this.messages);
+                                    this.state.set(State::None);
+                                    continue 'res;
+                                }
+
+                                this.flags.insert(Flags::BODY_EOF);
                             }
 
                             Poll::Ready(Some(Err(err))) => {
                                 return Err(DispatchError::Body(err.into()))
                             }
 
-                            Poll::Pending => return Ok(PollResponse::DoNothing),
+                            Poll::Pending => {
+                                if let Some(timeout) =
+                                    this.codec.config().body_poll_timeout()
+                                {
+                                    if this.body_timer.is_none() {
+                                        this.body_timer.set(Some(sleep_until(
+                                            Instant::now() + timeout,
+                                        )));
+                                    }
+
+                                    let timer =
+                                        this.body_timer.as_mut().as_pin_mut().unwrap();
+                                    if timer.poll(cx).is_ready() {
+                                        error!(
+                                            "Response body of {} made no progress within {:?}",
+                                            this.response_target
+                                                .as_deref()
+                                                .unwrap_or("unknown request"),
+                                            timeout
+                                        );
+                                        this.body_timer.set(None);
+                                        return Err(DispatchError::BodyTimeout);
+                                    }
+                                }
+
+                                return Ok(PollResponse::DoNothing);
+                            }
                         }
                     }
                     // buffer is beyond max size.
@@ -489,8 +643,10 @@ where
                     // expect resolved. write continue to buffer and set InnerDispatcher state
                     // to service call.
                     Poll::Ready(Ok(req)) => {
-                        this.write_buf
-                            .extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n");
+                        if req.head().version != Version::HTTP_10 {
+                            this.write_buf
+                                .extend_from_slice(b"HTTP/1.1 100 Continue\r\n\r\n");
+                        }
                         let fut = this.flow.service.call(req);
                         this.state.set(State::ServiceCall(fut));
                     }
@@ -509,23 +665,83 @@ where
         }
     }
 
-    fn handle_request(
+    /// Acquire in-flight slot for request.
+    ///
+    /// Returns the request back when it is admitted. Otherwise, the overload response is sent
+    /// and `None` is returned.
+    fn admit(
+        mut self: Pin<&mut Self>,
+        req: Request,
+    ) -> Result<Option<Request>, DispatchError> {
+        let this = self.as_mut().project();
+
+        match this.codec.config().admit(req.head()) {
+            Ok(in_flight) => {
+                *this.in_flight = Some(in_flight);
+                Ok(Some(req))
+            }
+            Err(res) => {
+                let (res, body) = res.replace_body(());
+                self.send_error_response(res, body)?;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Set state for calling the service with request.
+    ///
+    /// Requests with an `Expect: 100-continue` header are passed to the expect service first.
+    /// HTTP/1.0 requests are handled according to the configured [`Http10Expect`] setting.
+    fn call_service(
         mut self: Pin<&mut Self>,
         req: Request,
-        cx: &mut Context<'_>,
     ) -> Result<(), DispatchError> {
-        // Handle `EXPECT: 100-Continue` header
         let mut this = self.as_mut().project();
-        if req.head().expect() {
-            // set dispatcher state so the future is pinned.
+
+        if this.codec.config().body_poll_timeout().is_some() {
+            *this.response_target =
+                Some(format!("{} {}", req.head().method, req.head().uri.path()));
+        }
+
+        // Handle `EXPECT: 100-Continue` header
+        let expect = req.head().expect()
+            && (req.head().version != Version::HTTP_10
+                || match this.codec.config().http10_expect() {
+                    Http10Expect::Skip => true,
+                    Http10Expect::Ignore => false,
+                    Http10Expect::Reject => {
+                        let (mut res, body) =
+                            Response::new(StatusCode::EXPECTATION_FAILED)
+                                .replace_body(());
+                        res.head_mut().set_connection_type(ConnectionType::Close);
+                        return self.send_error_response(res, body);
+                    }
+                });
+
+        if expect {
             let task = this.flow.expect.call(req);
             this.state.set(State::ExpectCall(task));
         } else {
-            // the same as above.
             let task = this.flow.service.call(req);
             this.state.set(State::ServiceCall(task));
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(
+        mut self: Pin<&mut Self>,
+        req: Request,
+        cx: &mut Context<'_>,
+    ) -> Result<(), DispatchError> {
+        let req = match self.as_mut().admit(req)? {
+            Some(req) => req,
+            None => return Ok(()),
         };
 
+        // set dispatcher state so the future is pinned.
+        self.as_mut().call_service(req)?;
+
         // eagerly poll the future for once(or twice if expect is resolved immediately).
         loop {
             match self.as_mut().project().state.project() {
@@ -533,7 +749,9 @@ where
                     match fut.poll(cx) {
                         // expect is resolved. continue loop and poll the service call branch.
                         Poll::Ready(Ok(req)) => {
-                            self.as_mut().send_continue();
+                            if req.head().version != Version::HTTP_10 {
+                                self.as_mut().send_continue();
+                            }
                             let mut this = self.as_mut().project();
                             let task = this.flow.service.call(req);
                             this.state.set(State::ServiceCall(task));
@@ -572,6 +790,8 @@ where
                         }
                     };
                 }
+                // request was rejected and its error response has been sent.
+                StateProj::None | StateProj::SendErrorPayload(_) => return Ok(()),
                 _ => unreachable!(
                     "State must be set to ServiceCall or ExceptCall in handle_request"
                 ),
@@ -604,16 +824,22 @@ where
                             // merge on_connect_ext data into request extensions
                             this.on_connect_data.merge_into(&mut req);
 
-                            match this.codec.message_type() {
-                                // Request is upgradable. add upgrade message and break.
-                                // everything remain in read buffer would be handed to
-                                // upgraded Request.
-                                MessageType::Stream if this.flow.upgrade.is_some() => {
-                                    this.messages
-                                        .push_back(DispatcherMessage::Upgrade(req));
-                                    break;
-                                }
+                            let msg_type = this.codec.message_type();
+
+                            // Request is upgradable. add upgrade message and break.
+                            // everything remain in read buffer would be handed to
+                            // upgraded Request.
+                            if this.flow.upgrade.is_some()
+                                && this.codec.config().is_upgrade(
+                                    req.head(),
+                                    msg_type == MessageType::Stream,
+                                )
+                            {
+                                this.messages.push_back(DispatcherMessage::Upgrade(req));
+                                break;
+                            }
 
+                            match msg_type {
                                 // Request is not upgradable.
                                 MessageType::Payload | MessageType::Stream => {
                                     /*
@@ -660,6 +886,9 @@ where
                         }
                         Message::Chunk(None) => {
                             if let Some(mut payload) = this.payload.take() {
+                                if let Some(trailers) = this.codec.take_trailers() {
+                                    payload.feed_trailers(trailers);
+                                }
                                 payload.feed_eof();
                             } else {
                                 error!("Internal server error: unexpected eof");
@@ -1035,7 +1264,7 @@ where
 
 #[cfg(test)]
 mod tests {
-    use std::str;
+    use std::{str, time::Duration};
 
     use actix_service::fn_service;
     use actix_utils::future::{ready, Ready};
@@ -1044,11 +1273,12 @@ mod tests {
 
     use super::*;
     use crate::{
+        config::CrlfHandling,
         error::Error,
         h1::{ExpectHandler, UpgradeHandler},
-        http::Method,
+        http::{HeaderName, HeaderValue, Method},
         test::{TestBuffer, TestSeqBuffer},
-        HttpMessage, KeepAlive,
+        HttpMessage, KeepAlive, RequestHead,
     };
 
     fn find_slice(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
@@ -1374,6 +1604,272 @@ mod tests {
         .await;
     }
 
+    #[actix_rt::test]
+    async fn test_expect_http10() {
+        fn dispatch(http10_expect: Http10Expect) -> impl Future<Output = String> {
+            lazy(move |cx| {
+                let mut buf = TestSeqBuffer::empty();
+                let cfg = ServiceConfig::builder(KeepAlive::Disabled, 0, 0, false, None)
+                    .http10_expect(http10_expect)
+                    .finish();
+
+                let services =
+                    HttpFlow::new(echo_payload_service(), ExpectHandler, None);
+
+                let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+                    buf.clone(),
+                    cfg,
+                    services,
+                    OnConnectData::default(),
+                    None,
+                );
+
+                buf.extend_read_buf(
+                    "\
+                    POST /upload HTTP/1.0\r\n\
+                    Content-Length: 5\r\n\
+                    Expect: 100-continue\r\n\
+                    \r\n\
+                    12345\
+                    ",
+                );
+
+                actix_rt::pin!(h1);
+
+                for _ in 0..3 {
+                    if h1.as_mut().poll(cx).is_ready() {
+                        break;
+                    }
+                }
+
+                let res = buf.write_buf().to_vec();
+                String::from_utf8(res).unwrap()
+            })
+        }
+
+        for &http10_expect in &[Http10Expect::Skip, Http10Expect::Ignore] {
+            let res = dispatch(http10_expect).await;
+            assert!(!res.contains("100 Continue"), "{}", res);
+            assert!(res.contains("200 OK"), "{}", res);
+            assert!(res.ends_with("12345"), "{}", res);
+        }
+
+        let res = dispatch(Http10Expect::Reject).await;
+        assert!(!res.contains("100 Continue"), "{}", res);
+        assert!(res.contains("417 Expectation Failed"), "{}", res);
+    }
+
+    #[actix_rt::test]
+    async fn test_body_poll_timeout() {
+        let mut buf = TestSeqBuffer::empty();
+        let cfg = ServiceConfig::builder(KeepAlive::Disabled, 0, 0, false, None)
+            .body_poll_timeout(50)
+            .finish();
+
+        // body stream that never produces a chunk nor wakes the task
+        let services = HttpFlow::new(
+            fn_service(|_req: Request| {
+                let body =
+                    crate::body::BodyStream::new(futures_util::stream::pending::<
+                        Result<Bytes, Error>,
+                    >());
+                ready(Ok::<_, Error>(
+                    Response::ok().set_body(AnyBody::from_message(body)),
+                ))
+            }),
+            ExpectHandler,
+            None,
+        );
+
+        let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+            buf.clone(),
+            cfg,
+            services,
+            OnConnectData::default(),
+            None,
+        );
+
+        buf.extend_read_buf("GET /stalled HTTP/1.1\r\n\r\n");
+
+        actix_rt::pin!(h1);
+
+        lazy(|cx| assert!(h1.as_mut().poll(cx).is_pending())).await;
+
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+
+        lazy(|cx| match h1.as_mut().poll(cx) {
+            Poll::Ready(Err(DispatchError::BodyTimeout)) => {}
+            res => panic!("unexpected result: {:?}", res),
+        })
+        .await;
+    }
+
+    #[actix_rt::test]
+    async fn test_crlf_handling() {
+        fn dispatch(
+            crlf_handling: CrlfHandling,
+            inject: fn(&mut Response<AnyBody>),
+        ) -> impl Future<Output = Vec<u8>> {
+            lazy(move |cx| {
+                let buf = TestBuffer::new("GET / HTTP/1.1\r\n\r\n");
+                let cfg = ServiceConfig::builder(KeepAlive::Disabled, 0, 0, false, None)
+                    .crlf_handling(crlf_handling)
+                    .finish();
+
+                let services = HttpFlow::new(
+                    fn_service(move |_: Request| {
+                        let mut res =
+                            Response::with_body(StatusCode::OK, AnyBody::from("body"));
+                        inject(&mut res);
+                        ready(Ok::<_, Error>(res))
+                    }),
+                    ExpectHandler,
+                    None,
+                );
+
+                let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+                    buf,
+                    cfg,
+                    services,
+                    OnConnectData::default(),
+                    None,
+                );
+
+                actix_rt::pin!(h1);
+                assert!(h1.as_mut().poll(cx).is_ready());
+
+                match h1.project().inner.project() {
+                    DispatcherStateProj::Normal(inner) => {
+                        let mut res =
+                            inner.project().io.take().unwrap().write_buf.to_vec();
+                        stabilize_date_header(&mut res);
+                        res
+                    }
+                    _ => unreachable!(),
+                }
+            })
+        }
+
+        fn inject_reason(res: &mut Response<AnyBody>) {
+            res.head_mut().reason = Some("OK\r\nx-injected: 1");
+        }
+
+        fn inject_header(res: &mut Response<AnyBody>) {
+            // SAFETY: only the CR and LF characters under test are invalid
+            let value = unsafe {
+                HeaderValue::from_maybe_shared_unchecked("a\r\nx-injected: 1")
+            };
+            res.headers_mut()
+                .insert(HeaderName::from_static("x-value"), value);
+        }
+
+        // unchecked header values are only validated in debug builds
+        let injects: &[fn(&mut Response<AnyBody>)] = if cfg!(debug_assertions) {
+            &[inject_reason]
+        } else {
+            &[inject_reason, inject_header]
+        };
+
+        for &inject in injects {
+            let res = dispatch(CrlfHandling::Reject, inject).await;
+            assert_eq!(
+                str::from_utf8(&res).unwrap(),
+                "\
+            HTTP/1.1 500 Internal Server Error\r\n\
+            content-length: 0\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            "
+            );
+
+            let res = dispatch(CrlfHandling::Sanitize, inject).await;
+            assert_eq!(
+                str::from_utf8(&res).unwrap(),
+                "\
+            HTTP/1.1 200 OK\r\n\
+            content-length: 4\r\n\
+            connection: close\r\n\
+            date: Thu, 01 Jan 1970 12:34:56 UTC\r\n\r\n\
+            body\
+            "
+            );
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_body_length_mismatch() {
+        fn dispatch(
+            policy: BodyLengthMismatch,
+            declared: u64,
+        ) -> impl Future<Output = (Result<(), DispatchError>, String)> {
+            lazy(move |cx| {
+                let buf =
+                    TestSeqBuffer::new("GET /1 HTTP/1.1\r\n\r\nGET /2 HTTP/1.1\r\n\r\n");
+                let cfg = ServiceConfig::builder(KeepAlive::Os, 0, 0, false, None)
+                    .body_length_mismatch(policy)
+                    .finish();
+
+                let services = HttpFlow::new(
+                    fn_service(move |_: Request| {
+                        let stream = futures_util::stream::once(ready(Ok::<_, Error>(
+                            Bytes::from_static(b"hello"),
+                        )));
+                        let body = crate::body::SizedStream::new(declared, stream);
+                        ready(Ok::<_, Error>(
+                            Response::ok().set_body(AnyBody::from_message(body)),
+                        ))
+                    }),
+                    ExpectHandler,
+                    None,
+                );
+
+                let h1 = Dispatcher::<_, _, _, _, UpgradeHandler>::new(
+                    buf.clone(),
+                    cfg,
+                    services,
+                    OnConnectData::default(),
+                    None,
+                );
+
+                actix_rt::pin!(h1);
+
+                let mut res = Ok(());
+                for _ in 0..5 {
+                    if let Poll::Ready(r) = h1.as_mut().poll(cx) {
+                        res = r;
+                        break;
+                    }
+                }
+
+                let written = String::from_utf8(buf.write_buf().to_vec()).unwrap();
+                (res, written)
+            })
+        }
+
+        // excess bytes are dropped and the connection is kept
+        let (res, written) = dispatch(BodyLengthMismatch::Truncate, 3).await;
+        assert!(res.is_ok());
+        assert_eq!(written.matches("HTTP/1.1 200 OK").count(), 2, "{}", written);
+        assert!(!written.contains("hello"), "{}", written);
+
+        let (res, written) = dispatch(BodyLengthMismatch::LogAndClose, 3).await;
+        assert!(res.is_ok());
+        assert_eq!(written.matches("HTTP/1.1 200 OK").count(), 1, "{}", written);
+        assert!(written.ends_with("\r\n\r\nhel"), "{}", written);
+
+        let (res, _) = dispatch(BodyLengthMismatch::Error, 3).await;
+        assert!(matches!(res, Err(DispatchError::BodyLength)));
+
+        // short bodies always close the connection
+        let (res, written) = dispatch(BodyLengthMismatch::Truncate, 10).await;
+        assert!(res.is_ok());
+        assert_eq!(written.matches("HTTP/1.1 200 OK").count(), 1, "{}", written);
+        assert!(written.ends_with("\r\n\r\nhello"), "{}", written);
+
+        let (res, _) = dispatch(BodyLengthMismatch::Error, 10).await;
+        assert!(matches!(res, Err(DispatchError::BodyLength)));
+    }
+
     #[actix_rt::test]
     async fn test_upgrade() {
         struct TestUpgrade;
@@ -1426,4 +1922,78 @@ mod tests {
         })
         .await;
     }
+
+    #[actix_rt::test]
+    async fn test_upgrade_predicate() {
+        struct TestUpgrade;
+
+        impl<T> Service<(Request, Framed<T, Codec>)> for TestUpgrade {
+            type Response = ();
+            type Error = Error;
+            type Future = Ready<Result<Self::Response, Self::Error>>;
+
+            actix_service::always_ready!();
+
+            fn call(&self, (req, _framed): (Request, Framed<T, Codec>)) -> Self::Future {
+                assert_eq!(req.headers().get("upgrade").unwrap(), "custom/1");
+                ready(Ok(()))
+            }
+        }
+
+        fn dispatch(req: &'static str) -> impl Future<Output = bool> {
+            lazy(move |cx| {
+                let mut buf = TestSeqBuffer::empty();
+                let cfg = ServiceConfig::builder(KeepAlive::Disabled, 0, 0, false, None)
+                    .upgrade_predicate(Some(Rc::new(|req: &RequestHead| {
+                        req.headers()
+                            .get("upgrade")
+                            .map_or(false, |val| val == "custom/1")
+                    })))
+                    .finish();
+
+                let services =
+                    HttpFlow::new(ok_service(), ExpectHandler, Some(TestUpgrade));
+
+                let h1 = Dispatcher::<_, _, _, _, TestUpgrade>::new(
+                    buf.clone(),
+                    cfg,
+                    services,
+                    OnConnectData::default(),
+                    None,
+                );
+
+                buf.extend_read_buf(req);
+
+                actix_rt::pin!(h1);
+
+                let _ = h1.as_mut().poll(cx);
+                matches!(&h1.inner, DispatcherState::Upgrade(_))
+            })
+        }
+
+        assert!(
+            dispatch(
+                "\
+                GET /custom HTTP/1.1\r\n\
+                Connection: Upgrade\r\n\
+                Upgrade: custom/1\r\n\
+                \r\n\
+                "
+            )
+            .await
+        );
+
+        // predicate replaces default websocket detection
+        assert!(
+            !dispatch(
+                "\
+                GET /ws HTTP/1.1\r\n\
+                Connection: Upgrade\r\n\
+                Upgrade: websocket\r\n\
+                \r\n\
+                "
+            )
+            .await
+        );
+    }
 }
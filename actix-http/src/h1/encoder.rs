@@ -2,7 +2,7 @@ use std::io::Write;
 use std::marker::PhantomData;
 use std::ptr::copy_nonoverlapping;
 use std::slice::from_raw_parts_mut;
-use std::{cmp, io};
+use std::{cmp, fmt, io};
 
 use bytes::{BufMut, BytesMut};
 
@@ -287,11 +287,20 @@ impl MessageType for RequestHeadType {
     fn encode_status(&mut self, dst: &mut BytesMut) -> io::Result<()> {
         let head = self.as_ref();
         dst.reserve(256 + head.headers.len() * AVERAGE_HEADER_SIZE);
+
+        // requests sent to a proxy carry the full URI as request target
+        let path = head.uri.path_and_query().map(|u| u.as_str()).unwrap_or("/");
+        let target: &dyn fmt::Display = if head.absolute_form() {
+            &head.uri
+        } else {
+            &path
+        };
+
         write!(
             helpers::MutWriter(dst),
             "{} {} {}",
             head.method,
-            head.uri.path_and_query().map(|u| u.as_str()).unwrap_or("/"),
+            target,
             match head.version {
                 Version::HTTP_09 => "HTTP/0.9",
                 Version::HTTP_10 => "HTTP/1.0",
@@ -320,6 +329,15 @@ impl<T: MessageType> MessageEncoder<T> {
         self.te.encode_eof(buf)
     }
 
+    /// Encode eof followed by trailer fields
+    pub fn encode_trailers(
+        &mut self,
+        trailers: &HeaderMap,
+        buf: &mut BytesMut,
+    ) -> io::Result<()> {
+        self.te.encode_trailers(trailers, buf)
+    }
+
     pub fn encode(
         &mut self,
         dst: &mut BytesMut,
@@ -469,6 +487,34 @@ impl TransferEncoding {
             }
         }
     }
+
+    /// Encode eof followed by trailer fields.
+    ///
+    /// Trailers can only be sent with chunked transfer encoding and are dropped otherwise.
+    pub fn encode_trailers(
+        &mut self,
+        trailers: &HeaderMap,
+        buf: &mut BytesMut,
+    ) -> io::Result<()> {
+        match self.kind {
+            TransferEncodingKind::Chunked(ref mut eof) => {
+                if !*eof {
+                    *eof = true;
+                    buf.extend_from_slice(b"0\r\n");
+                    for (name, value) in trailers.iter() {
+                        buf.reserve(name.as_str().len() + value.len() + 4);
+                        buf.extend_from_slice(name.as_str().as_bytes());
+                        buf.extend_from_slice(b": ");
+                        buf.extend_from_slice(value.as_bytes());
+                        buf.extend_from_slice(b"\r\n");
+                    }
+                    buf.extend_from_slice(b"\r\n");
+                }
+                Ok(())
+            }
+            _ => self.encode_eof(buf),
+        }
+    }
 }
 
 /// # Safety
@@ -530,6 +576,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_chunked_te_trailers() {
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            HeaderName::from_static("grpc-status"),
+            HeaderValue::from_static("0"),
+        );
+
+        let mut bytes = BytesMut::new();
+        let mut enc = TransferEncoding::chunked();
+        assert!(!enc.encode(b"test", &mut bytes).unwrap());
+        enc.encode_trailers(&trailers, &mut bytes).unwrap();
+        assert_eq!(
+            bytes.split().freeze(),
+            Bytes::from_static(b"4\r\ntest\r\n0\r\ngrpc-status: 0\r\n\r\n")
+        );
+
+        let mut enc = TransferEncoding::length(4);
+        assert!(enc.encode(b"test", &mut bytes).unwrap());
+        enc.encode_trailers(&trailers, &mut bytes).unwrap();
+        assert_eq!(bytes.split().freeze(), Bytes::from_static(b"test"));
+    }
+
     #[actix_rt::test]
     async fn test_camel_case() {
         let mut bytes = BytesMut::with_capacity(2048);
@@ -648,4 +717,48 @@ mod tests {
         assert!(!data.contains("content-length: 0\r\n"));
         assert!(!data.contains("transfer-encoding: chunked\r\n"));
     }
+
+    #[actix_rt::test]
+    async fn test_multi_status() {
+        for &status in &[StatusCode::MULTI_STATUS, StatusCode::ALREADY_REPORTED] {
+            let mut bytes = BytesMut::with_capacity(2048);
+
+            let mut res = Response::with_body(status, ());
+            res.headers_mut().insert(DATE, HeaderValue::from_static(""));
+
+            let _ = res.encode_status(&mut bytes);
+            let _ = res.encode_headers(
+                &mut bytes,
+                Version::HTTP_11,
+                BodySize::Sized(64),
+                ConnectionType::KeepAlive,
+                &ServiceConfig::default(),
+            );
+            let data =
+                String::from_utf8(Vec::from(bytes.split().freeze().as_ref())).unwrap();
+            assert!(data.starts_with(&format!("HTTP/1.1 {}\r\n", status)));
+            assert!(data.contains("content-length: 64\r\n"));
+        }
+    }
+
+    #[test]
+    fn test_absolute_form() {
+        let mut bytes = BytesMut::new();
+
+        let mut head = RequestHead::default();
+        head.uri = http::Uri::from_static("http://example.com/path?query");
+        let mut head = RequestHeadType::Owned(head);
+        head.encode_status(&mut bytes).unwrap();
+        assert_eq!(&bytes.split()[..], b"GET /path?query HTTP/1.1");
+
+        let mut head = RequestHead::default();
+        head.uri = http::Uri::from_static("http://example.com/path?query");
+        head.set_absolute_form();
+        let mut head = RequestHeadType::Owned(head);
+        head.encode_status(&mut bytes).unwrap();
+        assert_eq!(
+            &bytes.split()[..],
+            b"GET http://example.com/path?query HTTP/1.1"
+        );
+    }
 }
@@ -12,7 +12,7 @@ mod service;
 mod upgrade;
 mod utils;
 
-pub use self::client::{ClientCodec, ClientPayloadCodec};
+pub use self::client::{ClientCodec, ClientPayloadCodec, RawResponseHead};
 pub use self::codec::Codec;
 pub use self::dispatcher::Dispatcher;
 pub use self::expect::ExpectHandler;
@@ -9,6 +9,7 @@ use bytes::Bytes;
 use futures_core::Stream;
 
 use crate::error::PayloadError;
+use crate::header::HeaderMap;
 
 /// max buffer size 32k
 pub(crate) const MAX_BUFFER_SIZE: usize = 32_768;
@@ -85,6 +86,13 @@ impl Payload {
     ) -> Poll<Option<Result<Bytes, PayloadError>>> {
         self.inner.borrow_mut().readany(cx)
     }
+
+    /// Take trailer fields sent after the last chunk of a chunked payload.
+    ///
+    /// Trailers are available once the payload has ended.
+    pub fn take_trailers(&mut self) -> Option<HeaderMap> {
+        self.inner.borrow_mut().trailers.take()
+    }
 }
 
 impl Stream for Payload {
@@ -125,6 +133,13 @@ impl PayloadSender {
         }
     }
 
+    #[inline]
+    pub fn feed_trailers(&mut self, trailers: HeaderMap) {
+        if let Some(shared) = self.inner.upgrade() {
+            shared.borrow_mut().trailers = Some(trailers);
+        }
+    }
+
     #[inline]
     pub fn need_read(&self, cx: &mut Context<'_>) -> PayloadStatus {
         // we check need_read only if Payload (other side) is alive,
@@ -149,6 +164,7 @@ struct Inner {
     err: Option<PayloadError>,
     need_read: bool,
     items: VecDeque<Bytes>,
+    trailers: Option<HeaderMap>,
     task: Option<Waker>,
     io_task: Option<Waker>,
 }
@@ -160,6 +176,7 @@ impl Inner {
             len: 0,
             err: None,
             items: VecDeque::new(),
+            trailers: None,
             need_read: true,
             task: None,
             io_task: None,
@@ -276,4 +293,29 @@ mod tests {
             poll_fn(|cx| payload.readany(cx)).await.unwrap().unwrap()
         );
     }
+
+    #[actix_rt::test]
+    async fn test_trailers() {
+        let (mut sender, mut payload) = Payload::create(false);
+
+        sender.feed_data(Bytes::from("data"));
+        let mut trailers = HeaderMap::new();
+        trailers.insert(
+            crate::header::HeaderName::from_static("grpc-status"),
+            crate::header::HeaderValue::from_static("0"),
+        );
+        sender.feed_trailers(trailers);
+        sender.feed_eof();
+
+        assert_eq!(
+            Bytes::from("data"),
+            poll_fn(|cx| payload.readany(cx)).await.unwrap().unwrap()
+        );
+        assert!(poll_fn(|cx| payload.readany(cx)).await.is_none());
+        assert_eq!(
+            payload.take_trailers().unwrap().get("grpc-status").unwrap(),
+            "0"
+        );
+        assert!(payload.take_trailers().is_none());
+    }
 }
@@ -1,5 +1,6 @@
 use std::{
     cmp,
+    collections::VecDeque,
     error::Error as StdError,
     future::Future,
     marker::PhantomData,
@@ -13,17 +14,21 @@ use actix_codec::{AsyncRead, AsyncWrite};
 use actix_service::Service;
 use actix_utils::future::poll_fn;
 use bytes::{Bytes, BytesMut};
-use futures_core::ready;
 use h2::server::{Connection, SendResponse};
-use http::header::{HeaderValue, CONNECTION, CONTENT_LENGTH, DATE, TRANSFER_ENCODING};
+use http::header::{
+    HeaderName, HeaderValue, CONNECTION, CONTENT_LENGTH, CONTENT_TYPE, DATE, TE,
+    TRANSFER_ENCODING, UPGRADE,
+};
 use log::{error, trace};
 use pin_project_lite::pin_project;
 
 use crate::{
     body::{AnyBody, BodySize, MessageBody},
     config::ServiceConfig,
+    header::{split_comma_delimited, Header, Te},
+    helpers::{dedup_singleton_headers, with_default_charset},
     service::HttpFlow,
-    OnConnectData, Payload, Request, Response, ResponseHead,
+    OnConnectData, Payload, Prioritizer, Request, Response, ResponseHead,
 };
 
 const CHUNK_SIZE: usize = 16_384;
@@ -36,6 +41,8 @@ pin_project! {
         on_connect_data: OnConnectData,
         config: ServiceConfig,
         peer_addr: Option<net::SocketAddr>,
+        pending: VecDeque<(Request, SendResponse<Bytes>)>,
+        closed: bool,
         _phantom: PhantomData<B>,
     }
 }
@@ -54,6 +61,8 @@ impl<T, S, B, X, U> Dispatcher<T, S, B, X, U> {
             peer_addr,
             connection,
             on_connect_data,
+            pending: VecDeque::new(),
+            closed: false,
             _phantom: PhantomData,
         }
     }
@@ -77,10 +86,24 @@ where
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
 
-        while let Some((req, tx)) =
-            ready!(Pin::new(&mut this.connection).poll_accept(cx)?)
-        {
-            let (parts, body) = req.into_parts();
+        // accept new streams; they wait in the queue until the service is ready to take them
+        while !this.closed {
+            let item = match Pin::new(&mut this.connection).poll_accept(cx) {
+                Poll::Ready(item) => item,
+                Poll::Pending => break,
+            };
+
+            let (req, tx) = match item {
+                Some(item) => item?,
+                None => {
+                    // connection is closed; streams already accepted are still handed to the
+                    // service once it is ready
+                    this.closed = true;
+                    break;
+                }
+            };
+
+            let (mut parts, body) = req.into_parts();
             let pl = crate::h2::Payload::new(body);
             let pl = Payload::<crate::payload::PayloadStream>::H2(pl);
             let mut req = Request::with_payload(pl);
@@ -92,23 +115,71 @@ where
             head.headers = parts.headers.into();
             head.peer_addr = this.peer_addr;
 
+            // expose extended CONNECT `:protocol` pseudo-header
+            if let Some(protocol) = parts.extensions.remove::<h2::ext::Protocol>() {
+                req.head().extensions_mut().insert(protocol);
+            }
+
+            // reject requests with duplicated singleton headers
+            let policy = this.config.duplicate_headers();
+            if let Err(name) =
+                dedup_singleton_headers(&mut req.head_mut().headers, policy)
+            {
+                trace!("Duplicate {} header in HTTP/2 request", name);
+                spawn_response(Response::bad_request(), tx, this.config.clone());
+                continue;
+            }
+
             // merge on_connect_ext data into request extensions
             this.on_connect_data.merge_into(&mut req);
 
-            let fut = this.flow.service.call(req);
+            this.pending.push_back((req, tx));
+        }
+
+        while !this.pending.is_empty() {
+            let ready = match this.flow.service.poll_ready(cx) {
+                Poll::Ready(ready) => ready,
+                Poll::Pending => break,
+            };
+
+            let idx = next_stream(this.config.prioritizer(), &this.pending);
+            let (req, tx) = this.pending.remove(idx).unwrap();
             let config = this.config.clone();
 
+            if let Err(err) = ready {
+                let res: Response<AnyBody> = err.into();
+                spawn_response(res, tx, config);
+                continue;
+            }
+
+            // reject request when in-flight limit is reached
+            let in_flight = match config.admit(req.head()) {
+                Ok(in_flight) => in_flight,
+                Err(res) => {
+                    spawn_response(res, tx, config);
+                    continue;
+                }
+            };
+
+            // trailers are only sent to clients that ask for them with `TE: trailers`
+            let trailers = Te::parse(&req).map_or(false, |te| te.accepts_trailers());
+
+            let fut = this.flow.service.call(req);
+
             // multiplex request handling with spawn task
             actix_rt::spawn(async move {
                 // resolve service call and send response.
                 let res = match fut.await {
-                    Ok(res) => handle_response(res.into(), tx, config).await,
+                    Ok(res) => handle_response(res.into(), tx, config, trailers).await,
                     Err(err) => {
                         let res: Response<AnyBody> = err.into();
-                        handle_response(res, tx, config).await
+                        handle_response(res, tx, config, trailers).await
                     }
                 };
 
+                // response is sent; release in-flight slot.
+                drop(in_flight);
+
                 // log error.
                 if let Err(err) = res {
                     match err {
@@ -124,10 +195,51 @@ where
             });
         }
 
-        Poll::Ready(Ok(()))
+        if this.closed && this.pending.is_empty() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+/// Returns index of the waiting stream to service next, as chosen by the prioritizer.
+fn next_stream<T>(
+    prioritizer: &dyn Prioritizer,
+    pending: &VecDeque<(Request, T)>,
+) -> usize {
+    if pending.len() < 2 {
+        return 0;
+    }
+
+    let heads = pending
+        .iter()
+        .map(|(req, _)| req.head())
+        .collect::<Vec<_>>();
+    let idx = prioritizer.next(&heads);
+
+    if idx < pending.len() {
+        idx
+    } else {
+        0
     }
 }
 
+/// Sends a response generated by the dispatcher without calling the service.
+fn spawn_response(
+    res: Response<AnyBody>,
+    tx: SendResponse<Bytes>,
+    config: ServiceConfig,
+) {
+    actix_rt::spawn(async move {
+        if let Err(DispatchError::SendResponse(err)) =
+            handle_response(res, tx, config, false).await
+        {
+            trace!("Error sending HTTP/2 response: {:?}", err)
+        }
+    });
+}
+
 enum DispatchError {
     SendResponse(h2::Error),
     SendData(h2::Error),
@@ -138,6 +250,7 @@ async fn handle_response<B>(
     res: Response<B>,
     mut tx: SendResponse<Bytes>,
     config: ServiceConfig,
+    accepts_trailers: bool,
 ) -> Result<(), DispatchError>
 where
     B: MessageBody,
@@ -192,10 +305,19 @@ where
         }
     }
 
-    // response body streaming finished. send end of stream and return.
-    stream
-        .send_data(Bytes::new(), true)
-        .map_err(DispatchError::SendData)?;
+    // response body streaming finished. send trailers or end of stream and return.
+    let trailers = poll_fn(|cx| body.as_mut().poll_trailers(cx))
+        .await
+        .map_err(|err| DispatchError::ResponseBody(err.into()))?;
+
+    match trailers {
+        Some(trailers) if accepts_trailers => stream
+            .send_trailers(trailers.into())
+            .map_err(DispatchError::SendData)?,
+        _ => stream
+            .send_data(Bytes::new(), true)
+            .map_err(DispatchError::SendData)?,
+    }
 
     Ok(())
 }
@@ -239,11 +361,22 @@ fn prepare_response(
         }
     };
 
+    let sanitize = config.h2_sanitize_headers();
+
+    // headers named in `Connection` are connection-specific as well
+    let listed = if sanitize {
+        head.headers
+            .get_all(CONNECTION)
+            .filter_map(|val| val.to_str().ok())
+            .flat_map(split_comma_delimited)
+            .collect::<Vec<_>>()
+    } else {
+        Vec::new()
+    };
+
     // copy headers
     for (key, value) in head.headers.iter() {
         match *key {
-            // TODO: consider skipping other headers according to:
-            //       https://tools.ietf.org/html/rfc7540#section-8.1.2.2
             // omit HTTP/1.x only headers
             CONNECTION | TRANSFER_ENCODING => continue,
             CONTENT_LENGTH if skip_len => continue,
@@ -251,6 +384,23 @@ fn prepare_response(
             _ => {}
         }
 
+        // omit connection-specific headers, see:
+        // https://tools.ietf.org/html/rfc7540#section-8.1.2.2
+        if sanitize && is_connection_specific(key, &listed) {
+            trace!(
+                "Removing connection-specific header from HTTP/2 response: {}",
+                key
+            );
+            continue;
+        }
+
+        if *key == CONTENT_TYPE && config.default_charset() {
+            if let Some(ct) = with_default_charset(value) {
+                res.headers_mut().append(key, ct);
+                continue;
+            }
+        }
+
         res.headers_mut().append(key, value.clone());
     }
 
@@ -267,3 +417,118 @@ fn prepare_response(
 
     res
 }
+
+/// Returns true if header must not be sent in HTTP/2 messages.
+fn is_connection_specific(name: &HeaderName, listed: &[&str]) -> bool {
+    matches!(*name, UPGRADE | TE)
+        || name == "keep-alive"
+        || name == "proxy-connection"
+        || listed.iter().any(|listed| name == listed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{config::KeepAlive, http::StatusCode};
+
+    fn response_head() -> ResponseHead {
+        let mut head = ResponseHead::new(StatusCode::OK);
+        let headers = &mut head.headers;
+        headers.insert(CONNECTION, HeaderValue::from_static("keep-alive, x-hop"));
+        headers.insert(
+            HeaderName::from_static("keep-alive"),
+            HeaderValue::from_static("timeout=5"),
+        );
+        headers.insert(TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+        headers.insert(UPGRADE, HeaderValue::from_static("h2c"));
+        headers.insert(
+            HeaderName::from_static("x-hop"),
+            HeaderValue::from_static("1"),
+        );
+        headers.insert(
+            HeaderName::from_static("x-end"),
+            HeaderValue::from_static("2"),
+        );
+        head
+    }
+
+    #[actix_rt::test]
+    async fn sanitize_headers() {
+        let config = ServiceConfig::default();
+        let res = prepare_response(config, &response_head(), &mut BodySize::Stream);
+
+        let names = res
+            .headers()
+            .keys()
+            .map(|name| name.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(names, vec!["x-end", "date"]);
+    }
+
+    #[actix_rt::test]
+    async fn sanitize_headers_disabled() {
+        let config = ServiceConfig::builder(KeepAlive::Timeout(5), 0, 0, false, None)
+            .h2_sanitize_headers(false)
+            .finish();
+        let res = prepare_response(config, &response_head(), &mut BodySize::Stream);
+
+        assert!(!res.headers().contains_key(CONNECTION));
+        assert!(!res.headers().contains_key(TRANSFER_ENCODING));
+        assert!(res.headers().contains_key(UPGRADE));
+        assert!(res.headers().contains_key("keep-alive"));
+        assert!(res.headers().contains_key("x-hop"));
+    }
+
+    #[actix_rt::test]
+    async fn default_charset() {
+        let config = ServiceConfig::builder(KeepAlive::Timeout(5), 0, 0, false, None)
+            .default_charset(true)
+            .finish();
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        head.headers
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        let res = prepare_response(config, &head, &mut BodySize::Stream);
+        assert_eq!(
+            res.headers().get(CONTENT_TYPE).unwrap(),
+            "text/plain; charset=utf-8"
+        );
+
+        let res =
+            prepare_response(ServiceConfig::default(), &head, &mut BodySize::Stream);
+        assert_eq!(res.headers().get(CONTENT_TYPE).unwrap(), "text/plain");
+    }
+
+    #[test]
+    fn prioritized_stream() {
+        struct GetFirst;
+
+        impl Prioritizer for GetFirst {
+            fn next(&self, pending: &[&crate::RequestHead]) -> usize {
+                pending
+                    .iter()
+                    .position(|head| head.method == crate::Method::GET)
+                    .unwrap_or(pending.len())
+            }
+        }
+
+        let req = |method| {
+            let mut req = Request::new();
+            req.head_mut().method = method;
+            (req, ())
+        };
+
+        let mut pending = VecDeque::new();
+        pending.push_back(req(crate::Method::POST));
+        assert_eq!(next_stream(&GetFirst, &pending), 0);
+
+        pending.push_back(req(crate::Method::POST));
+        pending.push_back(req(crate::Method::GET));
+        assert_eq!(next_stream(&crate::Fifo, &pending), 0);
+        assert_eq!(next_stream(&GetFirst, &pending), 2);
+
+        // out of range selects the first stream
+        pending.pop_back();
+        assert_eq!(next_stream(&GetFirst, &pending), 0);
+    }
+}
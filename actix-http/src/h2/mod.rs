@@ -5,16 +5,33 @@ use std::{
     task::{Context, Poll},
 };
 
+use actix_codec::{AsyncRead, AsyncWrite};
 use bytes::Bytes;
 use futures_core::{ready, Stream};
-use h2::RecvStream;
+use h2::{
+    server::{Builder, Handshake},
+    RecvStream,
+};
 
 mod dispatcher;
 mod service;
 
 pub use self::dispatcher::Dispatcher;
 pub use self::service::H2Service;
-use crate::error::PayloadError;
+use crate::{body::Frame, error::PayloadError};
+
+/// Start HTTP/2 server handshake.
+///
+/// Extended CONNECT ([RFC 8441]) is enabled so that WebSocket handshakes can be performed over
+/// HTTP/2 streams.
+///
+/// [RFC 8441]: https://datatracker.ietf.org/doc/html/rfc8441
+pub(crate) fn handshake<T>(io: T) -> Handshake<T, Bytes>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    Builder::new().enable_connect_protocol().handshake(io)
+}
 
 /// HTTP/2 peer stream.
 pub struct Payload {
@@ -25,6 +42,32 @@ impl Payload {
     pub(crate) fn new(stream: RecvStream) -> Self {
         Self { stream }
     }
+
+    /// Poll trailer fields received after the payload, if any.
+    pub(crate) fn poll_trailers(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<http::HeaderMap>, PayloadError>> {
+        self.stream.poll_trailers(cx).map_err(Into::into)
+    }
+
+    /// Poll next frame of the payload: its data chunks, followed by its trailer fields if any.
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame, PayloadError>>> {
+        if let Some(res) = ready!(Pin::new(&mut *self).poll_next(cx)) {
+            return Poll::Ready(Some(res.map(Frame::Data)));
+        }
+
+        match ready!(self.poll_trailers(cx)) {
+            Ok(Some(trailers)) => {
+                Poll::Ready(Some(Ok(Frame::Trailers(trailers.into()))))
+            }
+            Ok(None) => Poll::Ready(None),
+            Err(err) => Poll::Ready(Some(Err(err))),
+        }
+    }
 }
 
 impl Stream for Payload {
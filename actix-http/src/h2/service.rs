@@ -17,7 +17,7 @@ use actix_service::{
 use actix_utils::future::ready;
 use bytes::Bytes;
 use futures_core::{future::LocalBoxFuture, ready};
-use h2::server::{handshake as h2_handshake, Handshake as H2Handshake};
+use h2::server::Handshake as H2Handshake;
 use log::error;
 
 use crate::{
@@ -292,7 +292,7 @@ where
                 Some(self.cfg.clone()),
                 addr,
                 on_connect_data,
-                h2_handshake(io),
+                super::handshake(io),
             ),
         }
     }
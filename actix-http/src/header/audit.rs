@@ -0,0 +1,324 @@
+use std::{fmt, rc::Rc};
+
+use super::{
+    HeaderName, HeaderValue, CONTENT_SECURITY_POLICY, CONTENT_TYPE,
+    STRICT_TRANSPORT_SECURITY,
+};
+use crate::ResponseHead;
+
+/// Security header requirement a response does not meet.
+#[derive(Debug, Clone, PartialEq)]
+#[non_exhaustive]
+pub enum SecurityViolation {
+    /// `Strict-Transport-Security` header is missing.
+    MissingHsts,
+
+    /// `Content-Security-Policy` header is missing on an HTML response.
+    MissingCsp,
+
+    /// Forbidden header is present, e.g. `X-Powered-By`.
+    ForbiddenHeader(HeaderName),
+}
+
+impl fmt::Display for SecurityViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SecurityViolation::MissingHsts => {
+                f.write_str("missing Strict-Transport-Security header")
+            }
+            SecurityViolation::MissingCsp => {
+                f.write_str("missing Content-Security-Policy header on HTML response")
+            }
+            SecurityViolation::ForbiddenHeader(name) => {
+                write!(f, "forbidden header `{}`", name)
+            }
+        }
+    }
+}
+
+/// Audits response heads against a security header baseline.
+///
+/// The default baseline requires a `Strict-Transport-Security` header, a
+/// `Content-Security-Policy` header on HTML responses, and forbids the `X-Powered-By` and
+/// deprecated `Expect-CT` headers.
+///
+/// Violations are reported to the [violation callback](Self::on_violation) and returned from
+/// [`audit`](Self::audit). With [`fix`](Self::fix) enabled, forbidden headers are removed and
+/// missing headers are inserted where a value is configured.
+///
+/// ```
+/// use actix_http::{
+///     http::{header::{SecurityAudit, SecurityViolation, HeaderValue}, StatusCode},
+///     ResponseHead,
+/// };
+///
+/// let audit = SecurityAudit::new()
+///     .hsts_value(HeaderValue::from_static("max-age=31536000"))
+///     .fix(true);
+///
+/// let mut head = ResponseHead::new(StatusCode::OK);
+/// head.headers_mut().insert(
+///     "x-powered-by".parse().unwrap(),
+///     HeaderValue::from_static("php"),
+/// );
+///
+/// let violations = audit.audit(&mut head);
+/// assert_eq!(violations.len(), 2);
+/// assert!(head.headers().contains_key("strict-transport-security"));
+/// assert!(!head.headers().contains_key("x-powered-by"));
+/// ```
+#[derive(Clone)]
+pub struct SecurityAudit {
+    require_hsts: bool,
+    require_csp: bool,
+    forbidden: Vec<HeaderName>,
+    hsts_value: Option<HeaderValue>,
+    csp_value: Option<HeaderValue>,
+    fix: bool,
+    on_violation: Option<Rc<dyn Fn(&ResponseHead, &SecurityViolation)>>,
+}
+
+impl Default for SecurityAudit {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SecurityAudit {
+    /// Create audit with the default baseline.
+    pub fn new() -> Self {
+        SecurityAudit {
+            require_hsts: true,
+            require_csp: true,
+            forbidden: vec![
+                HeaderName::from_static("x-powered-by"),
+                HeaderName::from_static("expect-ct"),
+            ],
+            hsts_value: None,
+            csp_value: None,
+            fix: false,
+            on_violation: None,
+        }
+    }
+
+    /// Require `Strict-Transport-Security` header. Enabled by default.
+    pub fn require_hsts(mut self, val: bool) -> Self {
+        self.require_hsts = val;
+        self
+    }
+
+    /// Require `Content-Security-Policy` header on HTML responses. Enabled by default.
+    pub fn require_csp(mut self, val: bool) -> Self {
+        self.require_csp = val;
+        self
+    }
+
+    /// Forbid header in addition to `X-Powered-By` and `Expect-CT`.
+    pub fn forbid_header(mut self, name: HeaderName) -> Self {
+        if !self.forbidden.contains(&name) {
+            self.forbidden.push(name);
+        }
+        self
+    }
+
+    /// Allow previously forbidden header, e.g. `Expect-CT`.
+    pub fn allow_header(mut self, name: HeaderName) -> Self {
+        self.forbidden.retain(|forbidden| *forbidden != name);
+        self
+    }
+
+    /// Set `Strict-Transport-Security` value inserted when [fixing](Self::fix) responses.
+    pub fn hsts_value(mut self, val: HeaderValue) -> Self {
+        self.hsts_value = Some(val);
+        self
+    }
+
+    /// Set `Content-Security-Policy` value inserted when [fixing](Self::fix) HTML responses.
+    pub fn csp_value(mut self, val: HeaderValue) -> Self {
+        self.csp_value = Some(val);
+        self
+    }
+
+    /// Fix violations instead of only reporting them. Disabled by default.
+    ///
+    /// Forbidden headers are removed. Missing headers are inserted only if a value is set with
+    /// [`hsts_value`](Self::hsts_value) or [`csp_value`](Self::csp_value).
+    pub fn fix(mut self, val: bool) -> Self {
+        self.fix = val;
+        self
+    }
+
+    /// Sets the callback to be run for each violation found.
+    ///
+    /// Useful for recording metrics or logging policy violations.
+    pub fn on_violation<F>(mut self, f: F) -> Self
+    where
+        F: Fn(&ResponseHead, &SecurityViolation) + 'static,
+    {
+        self.on_violation = Some(Rc::new(f));
+        self
+    }
+
+    /// Audit response head, fixing it if enabled.
+    ///
+    /// Returns found violations, including fixed ones.
+    pub fn audit(&self, head: &mut ResponseHead) -> Vec<SecurityViolation> {
+        let mut violations = Vec::new();
+
+        for name in &self.forbidden {
+            if head.headers().contains_key(name) {
+                violations.push(SecurityViolation::ForbiddenHeader(name.clone()));
+            }
+        }
+
+        if self.require_hsts && !head.headers().contains_key(STRICT_TRANSPORT_SECURITY) {
+            violations.push(SecurityViolation::MissingHsts);
+        }
+
+        if self.require_csp
+            && is_html(head)
+            && !head.headers().contains_key(CONTENT_SECURITY_POLICY)
+        {
+            violations.push(SecurityViolation::MissingCsp);
+        }
+
+        if let Some(ref on_violation) = self.on_violation {
+            for violation in &violations {
+                on_violation(head, violation);
+            }
+        }
+
+        if self.fix {
+            for violation in &violations {
+                match violation {
+                    SecurityViolation::ForbiddenHeader(name) => {
+                        head.headers_mut().remove(name);
+                    }
+                    SecurityViolation::MissingHsts => {
+                        if let Some(ref val) = self.hsts_value {
+                            head.headers_mut()
+                                .insert(STRICT_TRANSPORT_SECURITY, val.clone());
+                        }
+                    }
+                    SecurityViolation::MissingCsp => {
+                        if let Some(ref val) = self.csp_value {
+                            head.headers_mut()
+                                .insert(CONTENT_SECURITY_POLICY, val.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        violations
+    }
+}
+
+impl fmt::Debug for SecurityAudit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SecurityAudit")
+            .field("require_hsts", &self.require_hsts)
+            .field("require_csp", &self.require_csp)
+            .field("forbidden", &self.forbidden)
+            .field("hsts_value", &self.hsts_value)
+            .field("csp_value", &self.csp_value)
+            .field("fix", &self.fix)
+            .finish()
+    }
+}
+
+fn is_html(head: &ResponseHead) -> bool {
+    head.headers()
+        .get(CONTENT_TYPE)
+        .and_then(|ct| ct.to_str().ok())
+        .and_then(|ct| ct.split(';').next())
+        .map_or(false, |mime| {
+            let mime = mime.trim();
+            mime.eq_ignore_ascii_case("text/html")
+                || mime.eq_ignore_ascii_case("application/xhtml+xml")
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+    use crate::http::StatusCode;
+
+    #[test]
+    fn test_audit() {
+        let audit = SecurityAudit::new();
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        head.headers_mut().insert(
+            CONTENT_TYPE,
+            HeaderValue::from_static("text/html; charset=utf-8"),
+        );
+        head.headers_mut().insert(
+            HeaderName::from_static("expect-ct"),
+            HeaderValue::from_static("max-age=0"),
+        );
+
+        let violations = audit.audit(&mut head);
+        assert_eq!(
+            violations,
+            vec![
+                SecurityViolation::ForbiddenHeader(HeaderName::from_static("expect-ct")),
+                SecurityViolation::MissingHsts,
+                SecurityViolation::MissingCsp,
+            ]
+        );
+        // reporting only
+        assert!(head.headers().contains_key("expect-ct"));
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        head.headers_mut().insert(
+            STRICT_TRANSPORT_SECURITY,
+            HeaderValue::from_static("max-age=60"),
+        );
+        head.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        assert!(audit.audit(&mut head).is_empty());
+
+        let audit = SecurityAudit::new()
+            .require_hsts(false)
+            .allow_header(HeaderName::from_static("expect-ct"));
+        let mut head = ResponseHead::new(StatusCode::OK);
+        head.headers_mut().insert(
+            HeaderName::from_static("expect-ct"),
+            HeaderValue::from_static("max-age=0"),
+        );
+        assert!(audit.audit(&mut head).is_empty());
+    }
+
+    #[test]
+    fn test_audit_fix() {
+        let count = Rc::new(Cell::new(0));
+        let count2 = count.clone();
+
+        let audit = SecurityAudit::new()
+            .csp_value(HeaderValue::from_static("default-src 'self'"))
+            .fix(true)
+            .on_violation(move |_, _| count2.set(count2.get() + 1));
+
+        let mut head = ResponseHead::new(StatusCode::OK);
+        head.headers_mut()
+            .insert(CONTENT_TYPE, HeaderValue::from_static("text/html"));
+        head.headers_mut().insert(
+            HeaderName::from_static("x-powered-by"),
+            HeaderValue::from_static("php"),
+        );
+
+        assert_eq!(audit.audit(&mut head).len(), 3);
+        assert_eq!(count.get(), 3);
+
+        assert!(!head.headers().contains_key("x-powered-by"));
+        assert_eq!(
+            head.headers().get(CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'self'"
+        );
+        // no value to insert
+        assert!(!head.headers().contains_key(STRICT_TRANSPORT_SECURITY));
+    }
+}
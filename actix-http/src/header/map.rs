@@ -1,6 +1,6 @@
 //! A multi-value [`HeaderMap`] and its iterators.
 
-use std::{borrow::Cow, collections::hash_map, ops};
+use std::{borrow::Cow, collections::hash_map, ops, slice};
 
 use ahash::AHashMap;
 use http::header::{HeaderName, HeaderValue};
@@ -391,6 +391,35 @@ impl HeaderMap {
         };
     }
 
+    /// Gets the entry for `key` for in-place manipulation.
+    ///
+    /// Avoids looking up the key twice when conditionally adding or amending headers.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_http::http::{header::{self, Entry}, HeaderMap, HeaderValue};
+    /// let mut map = HeaderMap::new();
+    ///
+    /// map.entry(header::VARY)
+    ///     .or_insert(HeaderValue::from_static("accept-encoding"));
+    /// assert_eq!(map.get(header::VARY).unwrap(), "accept-encoding");
+    ///
+    /// // existing values are kept
+    /// map.entry(header::VARY).or_insert(HeaderValue::from_static("origin"));
+    /// assert_eq!(map.get(header::VARY).unwrap(), "accept-encoding");
+    ///
+    /// if let Entry::Occupied(mut entry) = map.entry(header::VARY) {
+    ///     entry.append(HeaderValue::from_static("origin"));
+    /// }
+    /// assert_eq!(map.get_all(header::VARY).count(), 2);
+    /// ```
+    pub fn entry(&mut self, key: HeaderName) -> Entry<'_> {
+        match self.inner.entry(key) {
+            hash_map::Entry::Occupied(inner) => Entry::Occupied(OccupiedEntry { inner }),
+            hash_map::Entry::Vacant(inner) => Entry::Vacant(VacantEntry { inner }),
+        }
+    }
+
     /// Removes all headers for a particular header name from the map.
     ///
     /// # Examples
@@ -519,13 +548,14 @@ impl HeaderMap {
         Keys(self.inner.keys())
     }
 
-    /// Clears the map, returning all name-value sets as an iterator.
+    /// Clears the map, returning all name-value pairs as an iterator.
     ///
-    /// Header names will only be yielded for the first value in each set. All items that are
-    /// yielded without a name and after an item with a name are associated with that same name.
-    /// The first item will always contain a name.
+    /// Names will be yielded for each associated value, like [`iter`](Self::iter). Values of the
+    /// same name are yielded in insertion order. If the iterator is dropped before being fully
+    /// consumed, the remaining pairs are dropped.
     ///
     /// Keeps the allocated memory for reuse.
+    ///
     /// # Examples
     /// ```
     /// # use actix_http::http::{header, HeaderMap, HeaderValue};
@@ -539,8 +569,8 @@ impl HeaderMap {
     /// map.append(header::SET_COOKIE, HeaderValue::from_static("two=2"));
     ///
     /// let mut iter = map.drain();
-    /// assert_eq!(iter.next().unwrap(), (Some(header::SET_COOKIE), HeaderValue::from_static("one=1")));
-    /// assert_eq!(iter.next().unwrap(), (None, HeaderValue::from_static("two=2")));
+    /// assert_eq!(iter.next().unwrap(), (header::SET_COOKIE, HeaderValue::from_static("one=1")));
+    /// assert_eq!(iter.next().unwrap(), (header::SET_COOKIE, HeaderValue::from_static("two=2")));
     /// drop(iter);
     ///
     /// assert!(map.is_empty());
@@ -548,6 +578,40 @@ impl HeaderMap {
     pub fn drain(&mut self) -> Drain<'_> {
         Drain::new(self.inner.drain())
     }
+
+    /// Retains only the name-value pairs for which `f` returns true.
+    ///
+    /// `f` is called once for each value, with its name. Names left without any values are
+    /// removed from the map. Values of the same name keep their order.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_http::http::{header, HeaderMap, HeaderValue};
+    /// let mut map = HeaderMap::new();
+    ///
+    /// map.append(header::CONNECTION, HeaderValue::from_static("keep-alive"));
+    /// map.append(header::TRANSFER_ENCODING, HeaderValue::from_static("chunked"));
+    /// map.append(header::SET_COOKIE, HeaderValue::from_static("one=1"));
+    /// map.append(header::SET_COOKIE, HeaderValue::from_static("two=2"));
+    ///
+    /// // strip hop-by-hop headers and some cookies
+    /// map.retain(|name, val| {
+    ///     *name != header::CONNECTION && *name != header::TRANSFER_ENCODING && *val != "two=2"
+    /// });
+    ///
+    /// assert_eq!(map.len(), 1);
+    /// assert_eq!(map.len_keys(), 1);
+    /// assert_eq!(map.get(header::SET_COOKIE).unwrap(), "one=1");
+    /// ```
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&HeaderName, &mut HeaderValue) -> bool,
+    {
+        self.inner.retain(|name, vals| {
+            vals.inner.retain(|val| f(name, val));
+            !vals.inner.is_empty()
+        });
+    }
 }
 
 /// Note that this implementation will clone a [HeaderName] for each value.
@@ -644,6 +708,164 @@ impl Iterator for Removed {
     }
 }
 
+/// A view into a single header name of a [`HeaderMap`], which may be vacant or occupied.
+///
+/// See [`HeaderMap::entry`].
+#[derive(Debug)]
+pub enum Entry<'a> {
+    /// An occupied entry, with one or more values.
+    Occupied(OccupiedEntry<'a>),
+
+    /// A vacant entry.
+    Vacant(VacantEntry<'a>),
+}
+
+impl<'a> Entry<'a> {
+    /// Returns the header name of this entry.
+    pub fn key(&self) -> &HeaderName {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    /// Inserts `value` if the entry is vacant and returns a mutable reference to the first value.
+    pub fn or_insert(self, value: HeaderValue) -> &'a mut HeaderValue {
+        self.or_insert_with(|| value)
+    }
+
+    /// Inserts the value returned by `f` if the entry is vacant and returns a mutable reference
+    /// to the first value.
+    pub fn or_insert_with<F>(self, f: F) -> &'a mut HeaderValue
+    where
+        F: FnOnce() -> HeaderValue,
+    {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(f()),
+        }
+    }
+
+    /// Calls `f` with each value of an occupied entry, allowing in-place mutation.
+    ///
+    /// # Examples
+    /// ```
+    /// # use actix_http::http::{header, HeaderMap, HeaderValue};
+    /// let mut map = HeaderMap::new();
+    ///
+    /// map.entry(header::CACHE_CONTROL)
+    ///     .and_modify(|_| unreachable!())
+    ///     .or_insert(HeaderValue::from_static("no-cache"));
+    ///
+    /// map.entry(header::CACHE_CONTROL)
+    ///     .and_modify(|val| *val = HeaderValue::from_static("no-store"))
+    ///     .or_insert(HeaderValue::from_static("no-cache"));
+    ///
+    /// assert_eq!(map.get(header::CACHE_CONTROL).unwrap(), "no-store");
+    /// ```
+    pub fn and_modify<F>(mut self, mut f: F) -> Self
+    where
+        F: FnMut(&mut HeaderValue),
+    {
+        if let Entry::Occupied(ref mut entry) = self {
+            entry.iter_mut().for_each(&mut f);
+        }
+
+        self
+    }
+
+    /// Appends `value` to the values of the entry, inserting it if the entry is vacant.
+    pub fn append(self, value: HeaderValue) {
+        match self {
+            Entry::Occupied(mut entry) => entry.append(value),
+            Entry::Vacant(entry) => {
+                entry.insert(value);
+            }
+        }
+    }
+}
+
+/// A view into an occupied entry in a [`HeaderMap`].
+///
+/// See [`HeaderMap::entry`].
+#[derive(Debug)]
+pub struct OccupiedEntry<'a> {
+    inner: hash_map::OccupiedEntry<'a, HeaderName, Value>,
+}
+
+impl<'a> OccupiedEntry<'a> {
+    /// Returns the header name of this entry.
+    pub fn key(&self) -> &HeaderName {
+        self.inner.key()
+    }
+
+    /// Returns a reference to the first value of the entry.
+    pub fn get(&self) -> &HeaderValue {
+        self.inner.get().first()
+    }
+
+    /// Returns a mutable reference to the first value of the entry.
+    pub fn get_mut(&mut self) -> &mut HeaderValue {
+        self.inner.get_mut().first_mut()
+    }
+
+    /// Converts the entry into a mutable reference to its first value, bound to the map.
+    pub fn into_mut(self) -> &'a mut HeaderValue {
+        self.inner.into_mut().first_mut()
+    }
+
+    /// Returns an iterator over all values of the entry.
+    pub fn iter(&self) -> GetAll<'_> {
+        GetAll::new(Some(self.inner.get()))
+    }
+
+    /// Returns an iterator over mutable references to all values of the entry.
+    pub fn iter_mut(&mut self) -> slice::IterMut<'_, HeaderValue> {
+        self.inner.get_mut().inner.iter_mut()
+    }
+
+    /// Replaces all values of the entry with `value`, returning the previous values.
+    pub fn insert(&mut self, value: HeaderValue) -> Removed {
+        let value = self.inner.insert(Value::one(value));
+        Removed::new(Some(value))
+    }
+
+    /// Appends `value` to the values of the entry.
+    pub fn append(&mut self, value: HeaderValue) {
+        self.inner.get_mut().append(value)
+    }
+
+    /// Removes the entry from the map, returning its values.
+    pub fn remove(self) -> Removed {
+        Removed::new(Some(self.inner.remove()))
+    }
+}
+
+/// A view into a vacant entry in a [`HeaderMap`].
+///
+/// See [`HeaderMap::entry`].
+#[derive(Debug)]
+pub struct VacantEntry<'a> {
+    inner: hash_map::VacantEntry<'a, HeaderName, Value>,
+}
+
+impl<'a> VacantEntry<'a> {
+    /// Returns the header name of this entry.
+    pub fn key(&self) -> &HeaderName {
+        self.inner.key()
+    }
+
+    /// Takes ownership of the header name.
+    pub fn into_key(self) -> HeaderName {
+        self.inner.into_key()
+    }
+
+    /// Inserts `value` into the map, returning a mutable reference to it.
+    pub fn insert(self, value: HeaderValue) -> &'a mut HeaderValue {
+        self.inner.insert(Value::one(value)).first_mut()
+    }
+}
+
 /// Iterator over all [`HeaderName`]s in the map.
 #[derive(Debug)]
 pub struct Keys<'a>(hash_map::Keys<'a, HeaderName, Value>);
@@ -715,12 +937,11 @@ impl<'a> Iterator for Iter<'a> {
 
 /// Iterator over drained name-value pairs.
 ///
-/// Iterator items are `(Option<HeaderName>, HeaderValue)` to avoid cloning.
+/// Implementation necessarily clones header names for each value after the first.
 #[derive(Debug)]
 pub struct Drain<'a> {
     inner: hash_map::Drain<'a, HeaderName, Value>,
-    multi_inner: Option<(Option<HeaderName>, SmallVec<[HeaderValue; 4]>)>,
-    multi_idx: usize,
+    multi_inner: Option<(HeaderName, smallvec::IntoIter<[HeaderValue; 4]>)>,
 }
 
 impl<'a> Drain<'a> {
@@ -728,31 +949,34 @@ impl<'a> Drain<'a> {
         Self {
             inner: iter,
             multi_inner: None,
-            multi_idx: 0,
         }
     }
 }
 
 impl<'a> Iterator for Drain<'a> {
-    type Item = (Option<HeaderName>, HeaderValue);
+    type Item = (HeaderName, HeaderValue);
 
     fn next(&mut self) -> Option<Self::Item> {
         // handle in-progress multi value iterators first
-        if let Some((ref mut name, ref mut vals)) = self.multi_inner {
-            if !vals.is_empty() {
-                // OPTIMIZE: array removals
-                return Some((name.take(), vals.remove(0)));
-            } else {
-                // no more items in value iterator; reset state
-                self.multi_inner = None;
-                self.multi_idx = 0;
+        if let Some((ref name, ref mut vals)) = self.multi_inner {
+            match vals.next() {
+                // move name out with the last value
+                Some(val) if vals.as_slice().is_empty() => {
+                    let (name, _) = self.multi_inner.take().unwrap();
+                    return Some((name, val));
+                }
+                Some(val) => return Some((name.clone(), val)),
+                None => {
+                    // no more items in value iterator; reset state
+                    self.multi_inner = None;
+                }
             }
         }
 
         let (name, value) = self.inner.next()?;
 
         // set up new inner iter and recurse into it
-        self.multi_inner = Some((Some(name), value.inner));
+        self.multi_inner = Some((name, value.inner.into_iter()));
         self.next()
     }
 
@@ -814,6 +1038,182 @@ impl Iterator for IntoIter {
     }
 }
 
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use std::fmt;
+
+    use serde::{
+        de::{self, MapAccess, SeqAccess, Visitor},
+        ser::{SerializeMap as _, SerializeSeq as _},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use super::*;
+
+    /// Serialized as a map of header names to sequences of values.
+    ///
+    /// Names are sorted so that output is deterministic. Values are serialized as strings, or as
+    /// bytes when they contain opaque (non-visible-ASCII) data. When deserializing, a single value
+    /// may be given in place of a sequence.
+    impl Serialize for HeaderMap {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut entries = self.inner.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+
+            let mut map = serializer.serialize_map(Some(entries.len()))?;
+
+            for (name, vals) in entries {
+                map.serialize_entry(name.as_str(), &SerValues(vals))?;
+            }
+
+            map.end()
+        }
+    }
+
+    impl<'de> Deserialize<'de> for HeaderMap {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_map(HeaderMapVisitor)
+        }
+    }
+
+    struct SerValues<'a>(&'a Value);
+
+    impl Serialize for SerValues<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(self.0.len()))?;
+
+            for val in self.0.iter() {
+                seq.serialize_element(&SerValue(val))?;
+            }
+
+            seq.end()
+        }
+    }
+
+    struct SerValue<'a>(&'a HeaderValue);
+
+    impl Serialize for SerValue<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self.0.to_str() {
+                Ok(val) => serializer.serialize_str(val),
+                Err(_) => serializer.serialize_bytes(self.0.as_bytes()),
+            }
+        }
+    }
+
+    struct HeaderMapVisitor;
+
+    impl<'de> Visitor<'de> for HeaderMapVisitor {
+        type Value = HeaderMap;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a map of header names to header values")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(
+            self,
+            mut access: A,
+        ) -> Result<HeaderMap, A::Error> {
+            let mut map = HeaderMap::with_capacity(access.size_hint().unwrap_or(0));
+
+            while let Some((name, DeValues(vals))) =
+                access.next_entry::<String, DeValues>()?
+            {
+                let name = HeaderName::from_bytes(name.as_bytes())
+                    .map_err(de::Error::custom)?;
+
+                for val in vals {
+                    map.append(name.clone(), val);
+                }
+            }
+
+            Ok(map)
+        }
+    }
+
+    /// One or more header values.
+    struct DeValues(SmallVec<[HeaderValue; 4]>);
+
+    impl<'de> Deserialize<'de> for DeValues {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(DeValuesVisitor)
+        }
+    }
+
+    struct DeValuesVisitor;
+
+    impl<'de> Visitor<'de> for DeValuesVisitor {
+        type Value = DeValues;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a header value or a sequence of header values")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<DeValues, E> {
+            DeValueVisitor
+                .visit_str(val)
+                .map(|val| DeValues(smallvec![val.0]))
+        }
+
+        fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<DeValues, E> {
+            DeValueVisitor
+                .visit_bytes(val)
+                .map(|val| DeValues(smallvec![val.0]))
+        }
+
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DeValues, A::Error> {
+            let mut vals = SmallVec::new();
+
+            while let Some(DeValue(val)) = seq.next_element()? {
+                vals.push(val);
+            }
+
+            Ok(DeValues(vals))
+        }
+    }
+
+    struct DeValue(HeaderValue);
+
+    impl<'de> Deserialize<'de> for DeValue {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            deserializer.deserialize_any(DeValueVisitor)
+        }
+    }
+
+    struct DeValueVisitor;
+
+    impl<'de> Visitor<'de> for DeValueVisitor {
+        type Value = DeValue;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            f.write_str("a header value as a string or bytes")
+        }
+
+        fn visit_str<E: de::Error>(self, val: &str) -> Result<DeValue, E> {
+            HeaderValue::from_str(val)
+                .map(DeValue)
+                .map_err(de::Error::custom)
+        }
+
+        fn visit_bytes<E: de::Error>(self, val: &[u8]) -> Result<DeValue, E> {
+            HeaderValue::from_bytes(val)
+                .map(DeValue)
+                .map_err(de::Error::custom)
+        }
+
+        // formats without a native bytes type, e.g. JSON, represent bytes as integer sequences
+        fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<DeValue, A::Error> {
+            let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+
+            while let Some(byte) = seq.next_element::<u8>()? {
+                bytes.push(byte);
+            }
+
+            self.visit_bytes(&bytes)
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use http::header;
@@ -882,15 +1282,14 @@ mod tests {
         let mut iter = map.drain();
 
         let (name, val) = iter.next().unwrap();
-        assert_eq!(name, Some(header::COOKIE));
+        assert_eq!(name, header::COOKIE);
         vals.push(val);
 
         let (name, val) = iter.next().unwrap();
-        assert!(name.is_none());
+        assert_eq!(name, header::COOKIE);
         vals.push(val);
 
-        assert!(vals.contains(&HeaderValue::from_static("one=1")));
-        assert!(vals.contains(&HeaderValue::from_static("two=2")));
+        assert_eq!(vals, vec!["one=1", "two=2"]);
 
         assert!(iter.next().is_none());
         drop(iter);
@@ -898,6 +1297,38 @@ mod tests {
         assert!(map.is_empty());
     }
 
+    #[test]
+    fn retain() {
+        let mut map = HeaderMap::new();
+
+        map.append(header::HOST, HeaderValue::from_static("duck.com"));
+        map.append(header::COOKIE, HeaderValue::from_static("one=1"));
+        map.append(header::COOKIE, HeaderValue::from_static("two=2"));
+        map.append(header::COOKIE, HeaderValue::from_static("three=3"));
+
+        map.retain(|name, val| {
+            if *name == header::HOST {
+                *val = HeaderValue::from_static("example.com");
+            }
+
+            *val != "two=2"
+        });
+
+        assert_eq!(map.len(), 3);
+        assert_eq!(map.get(header::HOST).unwrap(), "example.com");
+        assert_eq!(
+            map.get_all(header::COOKIE).collect::<Vec<_>>(),
+            vec!["one=1", "three=3"]
+        );
+
+        map.retain(|name, _| *name != header::COOKIE);
+        assert_eq!(map.len(), 1);
+        assert!(!map.contains_key(header::COOKIE));
+
+        map.retain(|_, _| false);
+        assert!(map.is_empty());
+    }
+
     #[test]
     fn entries_into_iter() {
         let mut map = HeaderMap::new();
@@ -945,9 +1376,115 @@ mod tests {
         assert_eq!(vals.next(), removed.next().as_ref());
     }
 
+    #[test]
+    fn entry() {
+        let mut map = HeaderMap::new();
+
+        let val = map
+            .entry(header::ACCEPT)
+            .or_insert(HeaderValue::from_static("text/plain"));
+        assert_eq!(*val, "text/plain");
+
+        match map.entry(header::ACCEPT) {
+            Entry::Occupied(mut entry) => {
+                assert_eq!(*entry.key(), header::ACCEPT);
+                assert_eq!(entry.get(), "text/plain");
+
+                entry.append(HeaderValue::from_static("text/html"));
+                entry
+                    .iter_mut()
+                    .for_each(|val| *val = HeaderValue::from_static("text/csv"));
+                assert_eq!(entry.iter().count(), 2);
+
+                let removed = entry.insert(HeaderValue::from_static("text/xml"));
+                assert_eq!(removed.collect::<Vec<_>>(), vec!["text/csv", "text/csv"]);
+            }
+            Entry::Vacant(_) => panic!("entry should be occupied"),
+        }
+
+        assert_eq!(
+            map.get_all(header::ACCEPT).collect::<Vec<_>>(),
+            vec!["text/xml"]
+        );
+
+        map.entry(header::SET_COOKIE)
+            .append(HeaderValue::from_static("one=1"));
+        map.entry(header::SET_COOKIE)
+            .append(HeaderValue::from_static("two=2"));
+        assert_eq!(
+            map.get_all(header::SET_COOKIE).collect::<Vec<_>>(),
+            vec!["one=1", "two=2"]
+        );
+
+        match map.entry(header::SET_COOKIE) {
+            Entry::Occupied(entry) => assert_eq!(entry.remove().count(), 2),
+            Entry::Vacant(_) => panic!("entry should be occupied"),
+        }
+        assert!(!map.contains_key(header::SET_COOKIE));
+
+        match map.entry(header::HOST) {
+            Entry::Vacant(entry) => {
+                assert_eq!(*entry.key(), header::HOST);
+                *entry.insert(HeaderValue::from_static("a.com")) =
+                    HeaderValue::from_static("b.com");
+            }
+            Entry::Occupied(_) => panic!("entry should be vacant"),
+        }
+        assert_eq!(map.get(header::HOST).unwrap(), "b.com");
+    }
+
+    #[test]
+    fn entry_and_modify() {
+        let mut map = HeaderMap::new();
+
+        map.entry(header::VARY)
+            .and_modify(|_| panic!("entry should be vacant"))
+            .or_insert_with(|| HeaderValue::from_static("origin"));
+        map.append(header::VARY, HeaderValue::from_static("accept"));
+
+        let mut count = 0;
+        map.entry(header::VARY).and_modify(|_| count += 1);
+        assert_eq!(count, 2);
+    }
+
     fn owned_pair<'a>(
         (name, val): (&'a HeaderName, &'a HeaderValue),
     ) -> (HeaderName, HeaderValue) {
         (name.clone(), val.clone())
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_round_trip() {
+        let mut map = HeaderMap::new();
+        map.append(header::SET_COOKIE, HeaderValue::from_static("one=1"));
+        map.append(header::SET_COOKIE, HeaderValue::from_static("two=2"));
+        map.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        map.insert(header::ETAG, HeaderValue::from_bytes(b"\"\xFF\"").unwrap());
+
+        let json = serde_json::to_string(&map).unwrap();
+        assert_eq!(
+            json,
+            r#"{"content-type":["text/plain"],"etag":[[34,255,34]],"set-cookie":["one=1","two=2"]}"#
+        );
+
+        let map2: HeaderMap = serde_json::from_str(&json).unwrap();
+        assert_eq!(map2.len(), 4);
+        assert_eq!(
+            map2.get_all(header::SET_COOKIE).collect::<Vec<_>>(),
+            vec!["one=1", "two=2"]
+        );
+        assert_eq!(map2.get(header::ETAG).unwrap().as_bytes(), b"\"\xFF\"");
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_single_value() {
+        let map: HeaderMap =
+            serde_json::from_str(r#"{"Content-Type": "text/plain"}"#).unwrap();
+        assert_eq!(map.get(header::CONTENT_TYPE).unwrap(), "text/plain");
+
+        assert!(serde_json::from_str::<HeaderMap>(r#"{"bad name": "x"}"#).is_err());
+        assert!(serde_json::from_str::<HeaderMap>(r#"{"x-test": "\n"}"#).is_err());
+    }
 }
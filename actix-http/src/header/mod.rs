@@ -33,22 +33,25 @@ use crate::error::ParseError;
 use crate::HttpMessage;
 
 mod as_name;
+mod audit;
 mod into_pair;
 mod into_value;
 mod utils;
 
 pub(crate) mod map;
 mod shared;
+pub mod structured;
 
 #[doc(hidden)]
 pub use self::shared::*;
 
 pub use self::as_name::AsHeaderName;
+pub use self::audit::{SecurityAudit, SecurityViolation};
 pub use self::into_pair::IntoHeaderPair;
 pub use self::into_value::IntoHeaderValue;
 #[doc(hidden)]
 pub use self::map::GetAll;
-pub use self::map::HeaderMap;
+pub use self::map::{Entry, HeaderMap, OccupiedEntry, VacantEntry};
 pub use self::utils::*;
 
 /// A trait for any object that already represents a valid header field and value.
@@ -67,6 +70,17 @@ impl From<http::HeaderMap> for HeaderMap {
     }
 }
 
+/// Convert our `HeaderMap` to `http::HeaderMap`, e.g. for sending it over HTTP/2.
+impl From<HeaderMap> for http::HeaderMap {
+    fn from(map: HeaderMap) -> http::HeaderMap {
+        let mut res = http::HeaderMap::with_capacity(map.len());
+        for (name, value) in map {
+            res.append(name, value);
+        }
+        res
+    }
+}
+
 /// This encode set is used for HTTP header values and is defined at
 /// https://tools.ietf.org/html/rfc5987#section-3.2.
 pub(crate) const HTTP_VALUE: &AsciiSet = &CONTROLS
@@ -83,8 +97,10 @@ pub(crate) const HTTP_VALUE: &AsciiSet = &CONTROLS
     .add(b';')
     .add(b'<')
     .add(b'-')
+    .add(b'=')
     .add(b'>')
     .add(b'?')
+    .add(b'@')
     .add(b'[')
     .add(b'\\')
     .add(b']')
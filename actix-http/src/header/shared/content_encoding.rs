@@ -111,3 +111,34 @@ impl Header for ContentEncoding {
         from_one_raw_str(msg.headers().get(Self::name()))
     }
 }
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ContentEncoding {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            ContentEncoding::Auto => serializer.serialize_str("auto"),
+            enc => serializer.serialize_str(enc.as_str()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ContentEncoding {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        const VARIANTS: &[&str] = &["auto", "br", "deflate", "gzip", "zstd", "identity"];
+
+        let val = String::deserialize(deserializer)?;
+
+        match val.as_str() {
+            "auto" => Ok(ContentEncoding::Auto),
+            "br" => Ok(ContentEncoding::Br),
+            "deflate" => Ok(ContentEncoding::Deflate),
+            "gzip" => Ok(ContentEncoding::Gzip),
+            "zstd" => Ok(ContentEncoding::Zstd),
+            "identity" => Ok(ContentEncoding::Identity),
+            _ => Err(serde::de::Error::unknown_variant(&val, VARIANTS)),
+        }
+    }
+}
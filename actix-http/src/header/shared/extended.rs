@@ -190,4 +190,17 @@ mod tests {
             format!("{}", extended_value)
         );
     }
+
+    #[test]
+    fn test_fmt_extended_value_escapes_non_attr_chars() {
+        let extended_value = ExtendedValue {
+            charset: Charset::Ext("UTF-8".to_string()),
+            language_tag: None,
+            value: b"a=b@c d'e*f%g.h_i~j".to_vec(),
+        };
+        assert_eq!(
+            "UTF-8''a%3Db%40c%20d%27e%2Af%25g.h_i~j",
+            format!("{}", extended_value)
+        );
+    }
 }
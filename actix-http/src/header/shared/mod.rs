@@ -5,10 +5,14 @@ mod content_encoding;
 mod extended;
 mod httpdate;
 mod quality_item;
+mod te;
+mod trailer;
 
 pub use self::charset::Charset;
 pub use self::content_encoding::ContentEncoding;
 pub use self::extended::{parse_extended_value, ExtendedValue};
 pub use self::httpdate::HttpDate;
 pub use self::quality_item::{q, qitem, Quality, QualityItem};
+pub use self::te::{Te, TransferCoding};
+pub use self::trailer::Trailer;
 pub use language_tags::LanguageTag;
@@ -0,0 +1,175 @@
+use std::{fmt, str::FromStr};
+
+use http::header::InvalidHeaderValue;
+
+use super::{q, Quality, QualityItem};
+use crate::{
+    error::ParseError,
+    header::{
+        self, fmt_comma_delimited, from_comma_delimited, Header, HeaderName,
+        HeaderValue, IntoHeaderValue,
+    },
+    HttpMessage,
+};
+
+/// A transfer coding listed in a [`Te`] header.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum TransferCoding {
+    /// The `trailers` keyword, signalling that the client is willing to accept trailer fields
+    /// in a chunked transfer coding.
+    Trailers,
+
+    /// A named transfer coding such as `gzip` or `deflate`. Stored in lowercase.
+    Coding(String),
+}
+
+impl FromStr for TransferCoding {
+    type Err = ParseError;
+
+    fn from_str(val: &str) -> Result<Self, Self::Err> {
+        let val = val.trim();
+
+        if val.is_empty() || !val.bytes().all(is_tchar) {
+            return Err(ParseError::Header);
+        }
+
+        if val.eq_ignore_ascii_case("trailers") {
+            Ok(TransferCoding::Trailers)
+        } else {
+            Ok(TransferCoding::Coding(val.to_ascii_lowercase()))
+        }
+    }
+}
+
+impl fmt::Display for TransferCoding {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TransferCoding::Trailers => f.write_str("trailers"),
+            TransferCoding::Coding(coding) => f.write_str(coding),
+        }
+    }
+}
+
+/// `TE` header, defined in [RFC 7230 §4.3](https://tools.ietf.org/html/rfc7230#section-4.3).
+///
+/// Indicates what transfer codings, besides chunked, the client is willing to accept in the
+/// response, and whether it is willing to accept trailer fields in a chunked transfer coding.
+///
+/// # ABNF
+/// ```text
+/// TE        = #t-codings
+/// t-codings = "trailers" / ( transfer-coding [ t-ranking ] )
+/// ```
+///
+/// # Examples
+/// ```
+/// use actix_http::{
+///     header::{Header, Te},
+///     test::TestRequest,
+/// };
+///
+/// let req = TestRequest::default()
+///     .insert_header(("te", "trailers, deflate;q=0.5"))
+///     .finish();
+///
+/// let te = Te::parse(&req).unwrap();
+/// assert!(te.accepts_trailers());
+/// assert_eq!(te.to_string(), "trailers, deflate; q=0.5");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Te(pub Vec<QualityItem<TransferCoding>>);
+
+impl Te {
+    /// Returns true if the client is willing to accept trailer fields.
+    pub fn accepts_trailers(&self) -> bool {
+        self.0
+            .iter()
+            .any(|qi| qi.item == TransferCoding::Trailers && qi.quality > q(0u16))
+    }
+
+    /// Returns the quality given to a named transfer coding, or `None` if it is not listed.
+    pub fn quality(&self, coding: &str) -> Option<Quality> {
+        self.0.iter().find_map(|qi| match qi.item {
+            TransferCoding::Coding(ref name) if name.eq_ignore_ascii_case(coding) => {
+                Some(qi.quality)
+            }
+            _ => None,
+        })
+    }
+}
+
+impl fmt::Display for Te {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_comma_delimited(f, &self.0)
+    }
+}
+
+impl IntoHeaderValue for Te {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        self.to_string().try_into_value()
+    }
+}
+
+impl Header for Te {
+    fn name() -> HeaderName {
+        header::TE
+    }
+
+    fn parse<T: HttpMessage>(msg: &T) -> Result<Self, ParseError> {
+        from_comma_delimited(msg.headers().get_all(Self::name())).map(Te)
+    }
+}
+
+/// Returns true for characters allowed in an RFC 7230 token.
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequest;
+
+    #[test]
+    fn parse() {
+        let req = TestRequest::default()
+            .insert_header((header::TE, "gzip;q=0.8, Trailers"))
+            .finish();
+        let te = Te::parse(&req).unwrap();
+
+        assert_eq!(
+            te.0,
+            vec![
+                QualityItem::new(TransferCoding::Coding("gzip".to_owned()), q(0.8)),
+                QualityItem::new(TransferCoding::Trailers, q(1.0)),
+            ]
+        );
+        assert!(te.accepts_trailers());
+        assert_eq!(te.quality("GZIP"), Some(q(0.8)));
+        assert_eq!(te.quality("deflate"), None);
+    }
+
+    #[test]
+    fn no_trailers() {
+        let req = TestRequest::default()
+            .insert_header((header::TE, "deflate"))
+            .finish();
+        assert!(!Te::parse(&req).unwrap().accepts_trailers());
+
+        let req = TestRequest::default().finish();
+        let te = Te::parse(&req).unwrap();
+        assert!(te.0.is_empty());
+        assert!(!te.accepts_trailers());
+    }
+
+    #[test]
+    fn to_value() {
+        let te = Te(vec![
+            QualityItem::new(TransferCoding::Trailers, q(1.0)),
+            QualityItem::new(TransferCoding::Coding("deflate".to_owned()), q(0.5)),
+        ]);
+        assert_eq!(te.try_into_value().unwrap(), "trailers, deflate; q=0.5");
+    }
+}
@@ -0,0 +1,101 @@
+use std::fmt;
+
+use http::header::InvalidHeaderValue;
+
+use crate::{
+    error::ParseError,
+    header::{
+        self, fmt_comma_delimited, from_comma_delimited, Header, HeaderMap, HeaderName,
+        HeaderValue, IntoHeaderValue,
+    },
+    HttpMessage,
+};
+
+/// `Trailer` header, defined in [RFC 7230 §4.4](https://tools.ietf.org/html/rfc7230#section-4.4).
+///
+/// Announces which header fields will be sent as trailer fields after a chunked message body.
+///
+/// # ABNF
+/// ```text
+/// Trailer = 1#field-name
+/// ```
+///
+/// # Examples
+/// ```
+/// use actix_http::header::{self, HeaderMap, HeaderValue, Trailer};
+///
+/// let mut trailers = HeaderMap::new();
+/// trailers.insert(
+///     header::HeaderName::from_static("grpc-status"),
+///     HeaderValue::from_static("0"),
+/// );
+///
+/// let trailer = Trailer::from_fields(&trailers);
+/// assert_eq!(trailer.to_string(), "grpc-status");
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Trailer(pub Vec<HeaderName>);
+
+impl Trailer {
+    /// Creates a `Trailer` header announcing every field name in `trailers`.
+    pub fn from_fields(trailers: &HeaderMap) -> Self {
+        Trailer(trailers.keys().cloned().collect())
+    }
+
+    /// Returns true if the given field is announced as a trailer field.
+    pub fn contains(&self, name: &HeaderName) -> bool {
+        self.0.contains(name)
+    }
+}
+
+impl fmt::Display for Trailer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_comma_delimited(f, &self.0)
+    }
+}
+
+impl IntoHeaderValue for Trailer {
+    type Error = InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        self.to_string().try_into_value()
+    }
+}
+
+impl Header for Trailer {
+    fn name() -> HeaderName {
+        header::TRAILER
+    }
+
+    fn parse<T: HttpMessage>(msg: &T) -> Result<Self, ParseError> {
+        let names = from_comma_delimited(msg.headers().get_all(Self::name()))?;
+
+        if names.is_empty() {
+            Err(ParseError::Header)
+        } else {
+            Ok(Trailer(names))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test::TestRequest;
+
+    #[test]
+    fn parse() {
+        let req = TestRequest::default()
+            .insert_header((header::TRAILER, "Expires, Content-MD5"))
+            .finish();
+        let trailer = Trailer::parse(&req).unwrap();
+
+        assert_eq!(trailer.0.len(), 2);
+        assert!(trailer.contains(&header::EXPIRES));
+        assert!(trailer.contains(&HeaderName::from_static("content-md5")));
+        assert_eq!(trailer.to_string(), "expires, content-md5");
+
+        let req = TestRequest::default().finish();
+        assert!(Trailer::parse(&req).is_err());
+    }
+}
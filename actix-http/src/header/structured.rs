@@ -0,0 +1,1056 @@
+//! Structured Field Values for HTTP, defined in [RFC 8941].
+//!
+//! Structured fields are a common syntax for new header fields, such as `Priority` and
+//! `Signature-Input`, with well-defined parsing and serialization. Field values are one of three
+//! top-level types:
+//! - an [`Item`], parsed with [`parse_item`];
+//! - a [`List`] of items and inner lists, parsed with [`parse_list`];
+//! - a [`Dictionary`] of keys mapped to items and inner lists, parsed with [`parse_dictionary`].
+//!
+//! The type of a field value is defined by the header field's specification; it can not be
+//! inferred from the value itself. Items and inner lists may have [`Parameters`].
+//!
+//! Parsing fails with [`ParseError::Header`] if the value does not match the expected type.
+//! Serialization fails with [`InvalidStructuredValue`] if a value can not be represented, e.g.
+//! integers outside of the allowed range or tokens with invalid characters.
+//!
+//! # Examples
+//! ```
+//! use actix_http::http::header::structured::{self, BareItem, Item};
+//!
+//! let dict = structured::parse_dictionary("u=2, i").unwrap();
+//! assert_eq!(dict[0].0, "u");
+//! assert_eq!(dict[0].1.as_item().unwrap().bare_item, BareItem::Integer(2));
+//! assert_eq!(dict[1].1.as_item().unwrap().bare_item, BareItem::Boolean(true));
+//!
+//! let item = Item::new(BareItem::Token("gzip".to_owned()))
+//!     .param("q", BareItem::Decimal(0.5));
+//! assert_eq!(structured::serialize_item(&item).unwrap(), "gzip;q=0.5");
+//! ```
+//!
+//! [RFC 8941]: https://datatracker.ietf.org/doc/html/rfc8941
+
+use std::fmt::Write as _;
+
+use derive_more::{Display, Error};
+
+use crate::error::ParseError;
+
+const MAX_INTEGER: i64 = 999_999_999_999_999;
+const MAX_DECIMAL_INTEGER_DIGITS: usize = 12;
+
+/// Parameters of an [`Item`] or [`InnerList`], as ordered key-value pairs.
+pub type Parameters = Vec<(String, BareItem)>;
+
+/// Top-level list field value.
+pub type List = Vec<ListEntry>;
+
+/// Top-level dictionary field value, as ordered key-value pairs.
+pub type Dictionary = Vec<(String, ListEntry)>;
+
+/// Error returned when serializing a structured value that can not be represented.
+#[derive(Debug, Display, Error)]
+#[display(fmt = "invalid structured field value")]
+pub struct InvalidStructuredValue;
+
+/// A bare item; the value of an [`Item`] or a parameter.
+#[derive(Debug, Clone, PartialEq)]
+pub enum BareItem {
+    /// Integer, between -999,999,999,999,999 and 999,999,999,999,999.
+    Integer(i64),
+
+    /// Decimal number, with at most 12 integer digits and 3 fractional digits.
+    Decimal(f64),
+
+    /// String of printable ASCII characters.
+    String(String),
+
+    /// Token; an unquoted identifier like `gzip` or `text/html`.
+    Token(String),
+
+    /// Sequence of bytes, serialized as base64.
+    ByteSequence(Vec<u8>),
+
+    /// Boolean.
+    Boolean(bool),
+}
+
+impl BareItem {
+    /// Returns the integer value, if this is an integer.
+    pub fn as_integer(&self) -> Option<i64> {
+        match self {
+            BareItem::Integer(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    /// Returns the decimal value, if this is a decimal.
+    pub fn as_decimal(&self) -> Option<f64> {
+        match self {
+            BareItem::Decimal(val) => Some(*val),
+            _ => None,
+        }
+    }
+
+    /// Returns the string value, if this is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            BareItem::String(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Returns the token value, if this is a token.
+    pub fn as_token(&self) -> Option<&str> {
+        match self {
+            BareItem::Token(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Returns the bytes, if this is a byte sequence.
+    pub fn as_bytes(&self) -> Option<&[u8]> {
+        match self {
+            BareItem::ByteSequence(val) => Some(val),
+            _ => None,
+        }
+    }
+
+    /// Returns the boolean value, if this is a boolean.
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            BareItem::Boolean(val) => Some(*val),
+            _ => None,
+        }
+    }
+}
+
+/// A bare item with parameters.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Item {
+    /// Value of the item.
+    pub bare_item: BareItem,
+
+    /// Parameters of the item.
+    pub params: Parameters,
+}
+
+impl Item {
+    /// Constructs an item without parameters.
+    pub fn new(bare_item: BareItem) -> Self {
+        Item {
+            bare_item,
+            params: Parameters::new(),
+        }
+    }
+
+    /// Appends a parameter.
+    pub fn param(mut self, key: impl Into<String>, value: BareItem) -> Self {
+        self.params.push((key.into(), value));
+        self
+    }
+}
+
+impl From<BareItem> for Item {
+    fn from(bare_item: BareItem) -> Self {
+        Item::new(bare_item)
+    }
+}
+
+/// A list of items with parameters, as a member of a [`List`] or [`Dictionary`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct InnerList {
+    /// Items of the inner list.
+    pub items: Vec<Item>,
+
+    /// Parameters of the inner list.
+    pub params: Parameters,
+}
+
+impl InnerList {
+    /// Constructs an inner list without parameters.
+    pub fn new(items: Vec<Item>) -> Self {
+        InnerList {
+            items,
+            params: Parameters::new(),
+        }
+    }
+
+    /// Appends a parameter.
+    pub fn param(mut self, key: impl Into<String>, value: BareItem) -> Self {
+        self.params.push((key.into(), value));
+        self
+    }
+}
+
+/// A member of a [`List`] or [`Dictionary`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum ListEntry {
+    /// A single item.
+    Item(Item),
+
+    /// An inner list of items.
+    InnerList(InnerList),
+}
+
+impl ListEntry {
+    /// Returns the item, if this is an item.
+    pub fn as_item(&self) -> Option<&Item> {
+        match self {
+            ListEntry::Item(item) => Some(item),
+            ListEntry::InnerList(_) => None,
+        }
+    }
+
+    /// Returns the inner list, if this is an inner list.
+    pub fn as_inner_list(&self) -> Option<&InnerList> {
+        match self {
+            ListEntry::Item(_) => None,
+            ListEntry::InnerList(list) => Some(list),
+        }
+    }
+}
+
+impl From<Item> for ListEntry {
+    fn from(item: Item) -> Self {
+        ListEntry::Item(item)
+    }
+}
+
+impl From<InnerList> for ListEntry {
+    fn from(list: InnerList) -> Self {
+        ListEntry::InnerList(list)
+    }
+}
+
+/// Parses an item field value.
+pub fn parse_item(input: &str) -> Result<Item, ParseError> {
+    Parser::parse(input, Parser::parse_item)
+}
+
+/// Parses a list field value.
+///
+/// Values of header fields that occur multiple times should be joined with `,` before parsing.
+/// An empty value is an empty list.
+pub fn parse_list(input: &str) -> Result<List, ParseError> {
+    Parser::parse(input, Parser::parse_list)
+}
+
+/// Parses a dictionary field value.
+///
+/// Values of header fields that occur multiple times should be joined with `,` before parsing.
+/// An empty value is an empty dictionary. When keys are repeated, the last value is kept at the
+/// position of the first occurrence.
+pub fn parse_dictionary(input: &str) -> Result<Dictionary, ParseError> {
+    Parser::parse(input, Parser::parse_dictionary)
+}
+
+/// Serializes an item field value.
+pub fn serialize_item(item: &Item) -> Result<String, InvalidStructuredValue> {
+    let mut out = String::new();
+    write_item(&mut out, item)?;
+    Ok(out)
+}
+
+/// Serializes a list field value.
+///
+/// Fails for empty lists, since the header field should be omitted instead.
+pub fn serialize_list(list: &[ListEntry]) -> Result<String, InvalidStructuredValue> {
+    if list.is_empty() {
+        return Err(InvalidStructuredValue);
+    }
+
+    let mut out = String::new();
+
+    for (idx, member) in list.iter().enumerate() {
+        if idx > 0 {
+            out.push_str(", ");
+        }
+
+        write_list_entry(&mut out, member)?;
+    }
+
+    Ok(out)
+}
+
+/// Serializes a dictionary field value.
+///
+/// Fails for empty dictionaries, since the header field should be omitted instead. Members that
+/// are items with a value of `true` are serialized as the key alone.
+pub fn serialize_dictionary(
+    dict: &[(String, ListEntry)],
+) -> Result<String, InvalidStructuredValue> {
+    if dict.is_empty() {
+        return Err(InvalidStructuredValue);
+    }
+
+    let mut out = String::new();
+
+    for (idx, (key, member)) in dict.iter().enumerate() {
+        if idx > 0 {
+            out.push_str(", ");
+        }
+
+        write_key(&mut out, key)?;
+
+        match member {
+            ListEntry::Item(item) if item.bare_item == BareItem::Boolean(true) => {
+                write_params(&mut out, &item.params)?;
+            }
+            member => {
+                out.push('=');
+                write_list_entry(&mut out, member)?;
+            }
+        }
+    }
+
+    Ok(out)
+}
+
+fn write_list_entry(
+    out: &mut String,
+    member: &ListEntry,
+) -> Result<(), InvalidStructuredValue> {
+    match member {
+        ListEntry::Item(item) => write_item(out, item),
+        ListEntry::InnerList(list) => {
+            out.push('(');
+
+            for (idx, item) in list.items.iter().enumerate() {
+                if idx > 0 {
+                    out.push(' ');
+                }
+
+                write_item(out, item)?;
+            }
+
+            out.push(')');
+            write_params(out, &list.params)
+        }
+    }
+}
+
+fn write_item(out: &mut String, item: &Item) -> Result<(), InvalidStructuredValue> {
+    write_bare_item(out, &item.bare_item)?;
+    write_params(out, &item.params)
+}
+
+fn write_params(
+    out: &mut String,
+    params: &[(String, BareItem)],
+) -> Result<(), InvalidStructuredValue> {
+    for (key, value) in params {
+        out.push(';');
+        write_key(out, key)?;
+
+        if *value != BareItem::Boolean(true) {
+            out.push('=');
+            write_bare_item(out, value)?;
+        }
+    }
+
+    Ok(())
+}
+
+fn write_key(out: &mut String, key: &str) -> Result<(), InvalidStructuredValue> {
+    let mut bytes = key.bytes();
+
+    match bytes.next() {
+        Some(b) if b.is_ascii_lowercase() || b == b'*' => {}
+        _ => return Err(InvalidStructuredValue),
+    }
+
+    if !bytes.all(is_key_char) {
+        return Err(InvalidStructuredValue);
+    }
+
+    out.push_str(key);
+    Ok(())
+}
+
+fn write_bare_item(
+    out: &mut String,
+    item: &BareItem,
+) -> Result<(), InvalidStructuredValue> {
+    match item {
+        BareItem::Integer(val) => {
+            if !(-MAX_INTEGER..=MAX_INTEGER).contains(val) {
+                return Err(InvalidStructuredValue);
+            }
+
+            let _ = write!(out, "{}", val);
+        }
+
+        BareItem::Decimal(val) => write_decimal(out, *val)?,
+
+        BareItem::String(val) => {
+            out.push('"');
+
+            for c in val.chars() {
+                if !(' '..='~').contains(&c) {
+                    return Err(InvalidStructuredValue);
+                }
+
+                if c == '"' || c == '\\' {
+                    out.push('\\');
+                }
+
+                out.push(c);
+            }
+
+            out.push('"');
+        }
+
+        BareItem::Token(val) => {
+            let mut bytes = val.bytes();
+
+            match bytes.next() {
+                Some(b) if b.is_ascii_alphabetic() || b == b'*' => {}
+                _ => return Err(InvalidStructuredValue),
+            }
+
+            if !bytes.all(|b| is_tchar(b) || b == b':' || b == b'/') {
+                return Err(InvalidStructuredValue);
+            }
+
+            out.push_str(val);
+        }
+
+        BareItem::ByteSequence(val) => {
+            out.push(':');
+            base64::encode_config_buf(val, base64::STANDARD, out);
+            out.push(':');
+        }
+
+        BareItem::Boolean(val) => out.push_str(if *val { "?1" } else { "?0" }),
+    }
+
+    Ok(())
+}
+
+fn write_decimal(out: &mut String, val: f64) -> Result<(), InvalidStructuredValue> {
+    if !val.is_finite() {
+        return Err(InvalidStructuredValue);
+    }
+
+    // round to three fractional digits, with ties to even, based on the shortest decimal
+    // representation of the value; e.g. `2.0005` is a tie although it is stored as slightly more
+    let repr = val.abs().to_string();
+    let (integer, fraction) = match repr.find('.') {
+        Some(idx) => (&repr[..idx], &repr[idx + 1..]),
+        None => (&repr[..], ""),
+    };
+
+    if integer.len() > MAX_DECIMAL_INTEGER_DIGITS {
+        return Err(InvalidStructuredValue);
+    }
+
+    let (head, rest) = fraction.split_at(fraction.len().min(3));
+    let mut thousandths = integer.parse::<u64>().map_err(|_| InvalidStructuredValue)?
+        * 1000
+        + format!("{:0<3}", head).parse::<u64>().unwrap();
+
+    let round_up = match rest.as_bytes().first() {
+        Some(b'6'..=b'9') => true,
+        Some(b'5') => rest[1..].bytes().any(|b| b != b'0') || thousandths % 2 == 1,
+        _ => false,
+    };
+    if round_up {
+        thousandths += 1;
+    }
+
+    let integer = thousandths / 1000;
+    let fraction = thousandths % 1000;
+
+    if integer.to_string().len() > MAX_DECIMAL_INTEGER_DIGITS {
+        return Err(InvalidStructuredValue);
+    }
+
+    if val < 0.0 && thousandths > 0 {
+        out.push('-');
+    }
+
+    let fraction = format!("{:03}", fraction);
+    let fraction = fraction.trim_end_matches('0');
+
+    let _ = write!(
+        out,
+        "{}.{}",
+        integer,
+        if fraction.is_empty() { "0" } else { fraction }
+    );
+
+    Ok(())
+}
+
+fn is_key_char(b: u8) -> bool {
+    b.is_ascii_lowercase()
+        || b.is_ascii_digit()
+        || matches!(b, b'_' | b'-' | b'.' | b'*')
+}
+
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+/// Parser state, following the parsing algorithms of RFC 8941 §4.2.
+struct Parser<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn parse<T>(
+        input: &'a str,
+        parse: impl FnOnce(&mut Self) -> Result<T, ParseError>,
+    ) -> Result<T, ParseError> {
+        if !input.is_ascii() {
+            return Err(ParseError::Header);
+        }
+
+        let mut parser = Parser {
+            input: input.as_bytes(),
+            pos: 0,
+        };
+
+        parser.skip_sp();
+        let val = parse(&mut parser)?;
+        parser.skip_sp();
+
+        if parser.peek().is_some() {
+            return Err(ParseError::Header);
+        }
+
+        Ok(val)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.input.get(self.pos).copied()
+    }
+
+    fn bump(&mut self) -> Option<u8> {
+        let b = self.peek()?;
+        self.pos += 1;
+        Some(b)
+    }
+
+    fn eat(&mut self, b: u8) -> bool {
+        if self.peek() == Some(b) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn skip_sp(&mut self) {
+        while self.eat(b' ') {}
+    }
+
+    fn skip_ows(&mut self) {
+        while self.eat(b' ') || self.eat(b'\t') {}
+    }
+
+    /// Parses separator between members of a list or dictionary; returns false at end of input.
+    fn parse_member_separator(&mut self) -> Result<bool, ParseError> {
+        self.skip_ows();
+
+        if self.peek().is_none() {
+            return Ok(false);
+        }
+
+        if !self.eat(b',') {
+            return Err(ParseError::Header);
+        }
+
+        self.skip_ows();
+
+        // trailing comma
+        if self.peek().is_none() {
+            return Err(ParseError::Header);
+        }
+
+        Ok(true)
+    }
+
+    fn parse_list(&mut self) -> Result<List, ParseError> {
+        let mut list = List::new();
+
+        if self.peek().is_none() {
+            return Ok(list);
+        }
+
+        loop {
+            list.push(self.parse_list_entry()?);
+
+            if !self.parse_member_separator()? {
+                return Ok(list);
+            }
+        }
+    }
+
+    fn parse_dictionary(&mut self) -> Result<Dictionary, ParseError> {
+        let mut dict = Dictionary::new();
+
+        if self.peek().is_none() {
+            return Ok(dict);
+        }
+
+        loop {
+            let key = self.parse_key()?;
+
+            let member = if self.eat(b'=') {
+                self.parse_list_entry()?
+            } else {
+                ListEntry::Item(Item {
+                    bare_item: BareItem::Boolean(true),
+                    params: self.parse_params()?,
+                })
+            };
+
+            match dict.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, existing)) => *existing = member,
+                None => dict.push((key, member)),
+            }
+
+            if !self.parse_member_separator()? {
+                return Ok(dict);
+            }
+        }
+    }
+
+    fn parse_list_entry(&mut self) -> Result<ListEntry, ParseError> {
+        if self.peek() == Some(b'(') {
+            self.parse_inner_list().map(ListEntry::InnerList)
+        } else {
+            self.parse_item().map(ListEntry::Item)
+        }
+    }
+
+    fn parse_inner_list(&mut self) -> Result<InnerList, ParseError> {
+        if !self.eat(b'(') {
+            return Err(ParseError::Header);
+        }
+
+        let mut items = Vec::new();
+
+        loop {
+            self.skip_sp();
+
+            if self.eat(b')') {
+                let params = self.parse_params()?;
+                return Ok(InnerList { items, params });
+            }
+
+            items.push(self.parse_item()?);
+
+            match self.peek() {
+                Some(b' ') | Some(b')') => {}
+                _ => return Err(ParseError::Header),
+            }
+        }
+    }
+
+    fn parse_item(&mut self) -> Result<Item, ParseError> {
+        let bare_item = self.parse_bare_item()?;
+        let params = self.parse_params()?;
+        Ok(Item { bare_item, params })
+    }
+
+    fn parse_params(&mut self) -> Result<Parameters, ParseError> {
+        let mut params = Parameters::new();
+
+        while self.eat(b';') {
+            self.skip_sp();
+
+            let key = self.parse_key()?;
+
+            let value = if self.eat(b'=') {
+                self.parse_bare_item()?
+            } else {
+                BareItem::Boolean(true)
+            };
+
+            match params.iter_mut().find(|(existing, _)| *existing == key) {
+                Some((_, existing)) => *existing = value,
+                None => params.push((key, value)),
+            }
+        }
+
+        Ok(params)
+    }
+
+    fn parse_key(&mut self) -> Result<String, ParseError> {
+        let start = self.pos;
+
+        match self.bump() {
+            Some(b) if b.is_ascii_lowercase() || b == b'*' => {}
+            _ => return Err(ParseError::Header),
+        }
+
+        while self.peek().map_or(false, is_key_char) {
+            self.pos += 1;
+        }
+
+        Ok(self.slice_from(start))
+    }
+
+    fn parse_bare_item(&mut self) -> Result<BareItem, ParseError> {
+        match self.peek() {
+            Some(b'-') => self.parse_number(),
+            Some(b) if b.is_ascii_digit() => self.parse_number(),
+            Some(b'"') => self.parse_string(),
+            Some(b'*') => self.parse_token(),
+            Some(b) if b.is_ascii_alphabetic() => self.parse_token(),
+            Some(b':') => self.parse_byte_sequence(),
+            Some(b'?') => self.parse_boolean(),
+            _ => Err(ParseError::Header),
+        }
+    }
+
+    fn parse_number(&mut self) -> Result<BareItem, ParseError> {
+        let negative = self.eat(b'-');
+
+        if !self.peek().map_or(false, |b| b.is_ascii_digit()) {
+            return Err(ParseError::Header);
+        }
+
+        let start = self.pos;
+        let mut dot = None;
+
+        while let Some(b) = self.peek() {
+            if b.is_ascii_digit() {
+                self.pos += 1;
+            } else if b == b'.' && dot.is_none() {
+                if self.pos - start > MAX_DECIMAL_INTEGER_DIGITS {
+                    return Err(ParseError::Header);
+                }
+
+                dot = Some(self.pos);
+                self.pos += 1;
+            } else {
+                break;
+            }
+
+            let len = self.pos - start;
+            if (dot.is_none() && len > 15) || len > 16 {
+                return Err(ParseError::Header);
+            }
+        }
+
+        let number = self.slice_from(start);
+
+        match dot {
+            None => {
+                let val = number.parse::<i64>().map_err(|_| ParseError::Header)?;
+                Ok(BareItem::Integer(if negative { -val } else { val }))
+            }
+
+            Some(dot) => {
+                let fraction_digits = self.pos - dot - 1;
+                if fraction_digits == 0 || fraction_digits > 3 {
+                    return Err(ParseError::Header);
+                }
+
+                let val = number.parse::<f64>().map_err(|_| ParseError::Header)?;
+                Ok(BareItem::Decimal(if negative { -val } else { val }))
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<BareItem, ParseError> {
+        if !self.eat(b'"') {
+            return Err(ParseError::Header);
+        }
+
+        let mut val = String::new();
+
+        loop {
+            match self.bump() {
+                Some(b'\\') => match self.bump() {
+                    Some(b @ b'"') | Some(b @ b'\\') => val.push(char::from(b)),
+                    _ => return Err(ParseError::Header),
+                },
+                Some(b'"') => return Ok(BareItem::String(val)),
+                Some(b @ 0x20..=0x7e) => val.push(char::from(b)),
+                _ => return Err(ParseError::Header),
+            }
+        }
+    }
+
+    fn parse_token(&mut self) -> Result<BareItem, ParseError> {
+        let start = self.pos;
+
+        match self.bump() {
+            Some(b) if b.is_ascii_alphabetic() || b == b'*' => {}
+            _ => return Err(ParseError::Header),
+        }
+
+        while self
+            .peek()
+            .map_or(false, |b| is_tchar(b) || b == b':' || b == b'/')
+        {
+            self.pos += 1;
+        }
+
+        Ok(BareItem::Token(self.slice_from(start)))
+    }
+
+    fn parse_byte_sequence(&mut self) -> Result<BareItem, ParseError> {
+        if !self.eat(b':') {
+            return Err(ParseError::Header);
+        }
+
+        let start = self.pos;
+
+        loop {
+            match self.bump() {
+                Some(b':') => break,
+                Some(b)
+                    if b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'=') => {}
+                _ => return Err(ParseError::Header),
+            }
+        }
+
+        let encoded = &self.input[start..self.pos - 1];
+        let bytes = base64::decode_config(encoded, base64::STANDARD)
+            .map_err(|_| ParseError::Header)?;
+
+        Ok(BareItem::ByteSequence(bytes))
+    }
+
+    fn parse_boolean(&mut self) -> Result<BareItem, ParseError> {
+        if !self.eat(b'?') {
+            return Err(ParseError::Header);
+        }
+
+        match self.bump() {
+            Some(b'1') => Ok(BareItem::Boolean(true)),
+            Some(b'0') => Ok(BareItem::Boolean(false)),
+            _ => Err(ParseError::Header),
+        }
+    }
+
+    fn slice_from(&self, start: usize) -> String {
+        // input is checked to be ASCII
+        String::from_utf8_lossy(&self.input[start..self.pos]).into_owned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn token(val: &str) -> BareItem {
+        BareItem::Token(val.to_owned())
+    }
+
+    #[test]
+    fn items() {
+        assert_eq!(parse_item("42").unwrap(), Item::new(BareItem::Integer(42)));
+        assert_eq!(
+            parse_item("-42").unwrap(),
+            Item::new(BareItem::Integer(-42))
+        );
+        assert_eq!(
+            parse_item("4.5").unwrap(),
+            Item::new(BareItem::Decimal(4.5))
+        );
+        assert_eq!(
+            parse_item(r#""a \"b\" \\c""#).unwrap(),
+            Item::new(BareItem::String(r#"a "b" \c"#.to_owned()))
+        );
+        assert_eq!(
+            parse_item("foo/bar:baz").unwrap(),
+            Item::new(token("foo/bar:baz"))
+        );
+        assert_eq!(
+            parse_item(":cHJldGVuZCB0aGlzIGlzIGJpbmFyeSBjb250ZW50Lg==:").unwrap(),
+            Item::new(BareItem::ByteSequence(
+                b"pretend this is binary content.".to_vec()
+            ))
+        );
+        assert_eq!(
+            parse_item("?0").unwrap(),
+            Item::new(BareItem::Boolean(false))
+        );
+        assert_eq!(
+            parse_item("  abc;a=1;b ").unwrap(),
+            Item::new(token("abc"))
+                .param("a", BareItem::Integer(1))
+                .param("b", BareItem::Boolean(true))
+        );
+    }
+
+    #[test]
+    fn invalid_items() {
+        for input in &[
+            "",
+            "1234567890123456",
+            "1234567890123.0",
+            "1.2345",
+            "1.",
+            "-",
+            "\"abc",
+            "\"\\a\"",
+            "\"\u{e9}\"",
+            ":abc",
+            ":a$b:",
+            "?2",
+            "abc;A=1",
+            "abc;a=",
+            "a b",
+            "\tabc",
+        ] {
+            assert!(parse_item(input).is_err(), "{:?} should be invalid", input);
+        }
+    }
+
+    #[test]
+    fn lists() {
+        let list = parse_list("sugar, tea, rum").unwrap();
+        assert_eq!(
+            list,
+            vec![
+                Item::new(token("sugar")).into(),
+                Item::new(token("tea")).into(),
+                Item::new(token("rum")).into(),
+            ]
+        );
+
+        let list = parse_list(r#"("foo" "bar");lvl=5 ,	( ), abc;q=0.5"#).unwrap();
+        assert_eq!(
+            list,
+            vec![
+                InnerList::new(vec![
+                    Item::new(BareItem::String("foo".to_owned())),
+                    Item::new(BareItem::String("bar".to_owned())),
+                ])
+                .param("lvl", BareItem::Integer(5))
+                .into(),
+                InnerList::new(vec![]).into(),
+                Item::new(token("abc"))
+                    .param("q", BareItem::Decimal(0.5))
+                    .into(),
+            ]
+        );
+
+        assert!(parse_list("").unwrap().is_empty());
+        assert!(parse_list("a,").is_err());
+        assert!(parse_list("a,,b").is_err());
+        assert!(parse_list("(a b").is_err());
+        assert!(parse_list("(a,b)").is_err());
+    }
+
+    #[test]
+    fn dictionaries() {
+        let dict = parse_dictionary("a=?0, b, c; foo=bar, a=(1 2)").unwrap();
+        assert_eq!(
+            dict,
+            vec![
+                (
+                    "a".to_owned(),
+                    InnerList::new(vec![
+                        Item::new(BareItem::Integer(1)),
+                        Item::new(BareItem::Integer(2)),
+                    ])
+                    .into()
+                ),
+                ("b".to_owned(), Item::new(BareItem::Boolean(true)).into()),
+                (
+                    "c".to_owned(),
+                    Item::new(BareItem::Boolean(true))
+                        .param("foo", token("bar"))
+                        .into()
+                ),
+            ]
+        );
+
+        assert!(parse_dictionary("").unwrap().is_empty());
+        assert!(parse_dictionary("A=1").is_err());
+        assert!(parse_dictionary("a=1,").is_err());
+    }
+
+    #[test]
+    fn serialize() {
+        let item = Item::new(BareItem::String(r#"a "b" \c"#.to_owned()))
+            .param("x", BareItem::Boolean(true))
+            .param("y", BareItem::Boolean(false))
+            .param("z", BareItem::ByteSequence(b"hi".to_vec()));
+        assert_eq!(
+            serialize_item(&item).unwrap(),
+            r#""a \"b\" \\c";x;y=?0;z=:aGk=:"#
+        );
+
+        let list = vec![
+            InnerList::new(vec![
+                Item::new(token("a")),
+                Item::new(BareItem::Integer(-1)),
+            ])
+            .param("p", BareItem::Decimal(1.0))
+            .into(),
+            Item::new(token("*/*")).into(),
+        ];
+        assert_eq!(serialize_list(&list).unwrap(), "(a -1);p=1.0, */*");
+
+        let dict = vec![
+            ("u".to_owned(), Item::new(BareItem::Integer(2)).into()),
+            (
+                "i".to_owned(),
+                Item::new(BareItem::Boolean(true))
+                    .param("a", BareItem::Integer(1))
+                    .into(),
+            ),
+            ("f".to_owned(), Item::new(BareItem::Boolean(false)).into()),
+        ];
+        assert_eq!(serialize_dictionary(&dict).unwrap(), "u=2, i;a=1, f=?0");
+    }
+
+    #[test]
+    fn serialize_decimals() {
+        let decimal = |val| serialize_item(&Item::new(BareItem::Decimal(val)));
+
+        assert_eq!(decimal(0.5).unwrap(), "0.5");
+        assert_eq!(decimal(-1.25).unwrap(), "-1.25");
+        assert_eq!(decimal(1.0).unwrap(), "1.0");
+        assert_eq!(decimal(1.0004).unwrap(), "1.0");
+        assert_eq!(decimal(2.0005).unwrap(), "2.0");
+        assert_eq!(decimal(0.0015).unwrap(), "0.002");
+        assert_eq!(decimal(999_999_999_999.999).unwrap(), "999999999999.999");
+        assert!(decimal(1_000_000_000_000.0).is_err());
+        assert!(decimal(f64::NAN).is_err());
+    }
+
+    #[test]
+    fn serialize_invalid() {
+        let item = |bare_item| serialize_item(&Item::new(bare_item));
+
+        assert!(item(BareItem::Integer(1_000_000_000_000_000)).is_err());
+        assert!(item(BareItem::String("caf\u{e9}".to_owned())).is_err());
+        assert!(item(BareItem::String("a\nb".to_owned())).is_err());
+        assert!(item(token("1abc")).is_err());
+        assert!(item(token("a b")).is_err());
+        assert!(
+            serialize_item(&Item::new(token("a")).param("A", BareItem::Integer(1)))
+                .is_err()
+        );
+        assert!(serialize_list(&[]).is_err());
+        assert!(serialize_dictionary(&[]).is_err());
+    }
+
+    #[test]
+    fn round_trip() {
+        for input in &[
+            "sugar, tea, rum",
+            r#"("foo" "bar");lvl=5, (), abc;q=0.5"#,
+            ":aGk=:;a=?0, ?1, -0.001, 12",
+        ] {
+            let list = parse_list(input).unwrap();
+            assert_eq!(serialize_list(&list).unwrap(), *input);
+        }
+
+        let input = "u=2, i, a=(1 2);x=*foo";
+        let dict = parse_dictionary(input).unwrap();
+        assert_eq!(serialize_dictionary(&dict).unwrap(), input);
+    }
+}
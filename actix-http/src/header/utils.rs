@@ -1,4 +1,4 @@
-use std::{fmt, str::FromStr};
+use std::{borrow::Cow, fmt, iter::FusedIterator, str::FromStr};
 
 use super::HeaderValue;
 use crate::{error::ParseError, header::HTTP_VALUE};
@@ -13,18 +13,169 @@ where
     let mut result = Vec::new();
     for h in all {
         let s = h.to_str().map_err(|_| ParseError::Header)?;
-        result.extend(
-            s.split(',')
-                .filter_map(|x| match x.trim() {
-                    "" => None,
-                    y => Some(y),
-                })
-                .filter_map(|x| x.trim().parse().ok()),
-        )
+        result.extend(split_comma_delimited(s).filter_map(|x| x.parse().ok()))
     }
     Ok(result)
 }
 
+/// Splits a comma-delimited header value into its list elements.
+///
+/// Follows the `#rule` list ABNF from [RFC 7230 §7]: surrounding whitespace is trimmed, empty
+/// elements are skipped and commas inside quoted-strings do not act as delimiters. Elements are
+/// yielded lazily so callers can bound the number of elements they are willing to process.
+///
+/// ```
+/// use actix_http::header::split_comma_delimited;
+///
+/// let items = split_comma_delimited(r#"a, , b;q="x,y" ,c"#).collect::<Vec<_>>();
+/// assert_eq!(items, vec!["a", r#"b;q="x,y""#, "c"]);
+/// ```
+///
+/// [RFC 7230 §7]: https://datatracker.ietf.org/doc/html/rfc7230#section-7
+pub fn split_comma_delimited(val: &str) -> CommaDelimited<'_> {
+    CommaDelimited { rest: val }
+}
+
+/// Iterator over elements of a comma-delimited header value.
+///
+/// Created by [`split_comma_delimited`].
+#[derive(Debug, Clone)]
+pub struct CommaDelimited<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for CommaDelimited<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            let end = find_unquoted(self.rest, b',').unwrap_or(self.rest.len());
+            let item = self.rest[..end].trim();
+            self.rest = self.rest.get(end + 1..).unwrap_or("");
+
+            if !item.is_empty() {
+                return Some(item);
+            }
+        }
+    }
+}
+
+impl FusedIterator for CommaDelimited<'_> {}
+
+/// Splits a header list element into its leading value and `;`-delimited parameters.
+///
+/// Parameter names are returned as-is and parameter values have quoting removed. Parameters
+/// without a value (allowed by some headers) yield an empty value.
+///
+/// ```
+/// use actix_http::header::split_parameters;
+///
+/// let (value, params) = split_parameters(r#"text/html; charset="utf-8"; q=0.5"#);
+/// assert_eq!(value, "text/html");
+///
+/// let params = params.collect::<Vec<_>>();
+/// assert_eq!(params[0], ("charset", "utf-8".into()));
+/// assert_eq!(params[1], ("q", "0.5".into()));
+/// ```
+pub fn split_parameters(val: &str) -> (&str, Parameters<'_>) {
+    let end = find_unquoted(val, b';').unwrap_or(val.len());
+    let rest = val.get(end + 1..).unwrap_or("");
+    (val[..end].trim(), Parameters { rest })
+}
+
+/// Iterator over `;`-delimited parameters of a header list element.
+///
+/// Created by [`split_parameters`].
+#[derive(Debug, Clone)]
+pub struct Parameters<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Iterator for Parameters<'a> {
+    type Item = (&'a str, Cow<'a, str>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.rest.is_empty() {
+                return None;
+            }
+
+            let end = find_unquoted(self.rest, b';').unwrap_or(self.rest.len());
+            let param = self.rest[..end].trim();
+            self.rest = self.rest.get(end + 1..).unwrap_or("");
+
+            if param.is_empty() {
+                continue;
+            }
+
+            return Some(match param.find('=') {
+                Some(idx) => (param[..idx].trim(), unquote(param[idx + 1..].trim())),
+                None => (param, Cow::Borrowed("")),
+            });
+        }
+    }
+}
+
+impl FusedIterator for Parameters<'_> {}
+
+/// Removes quoting from a quoted-string, resolving quoted-pair escapes.
+///
+/// Values that are not enclosed in double quotes are returned unchanged.
+///
+/// ```
+/// use actix_http::header::unquote;
+///
+/// assert_eq!(unquote(r#""a \"b\" c""#), r#"a "b" c"#);
+/// assert_eq!(unquote("token"), "token");
+/// ```
+pub fn unquote(val: &str) -> Cow<'_, str> {
+    if val.len() < 2 || !val.starts_with('"') || !val.ends_with('"') {
+        return Cow::Borrowed(val);
+    }
+
+    let inner = &val[1..val.len() - 1];
+
+    if !inner.contains('\\') {
+        return Cow::Borrowed(inner);
+    }
+
+    let mut unquoted = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+
+    while let Some(ch) = chars.next() {
+        match ch {
+            '\\' => unquoted.extend(chars.next()),
+            ch => unquoted.push(ch),
+        }
+    }
+
+    Cow::Owned(unquoted)
+}
+
+/// Finds position of first `delim` byte that is not part of a quoted-string.
+fn find_unquoted(val: &str, delim: u8) -> Option<usize> {
+    let mut quoted = false;
+    let mut escaped = false;
+
+    for (idx, &byte) in val.as_bytes().iter().enumerate() {
+        if escaped {
+            escaped = false;
+        } else if quoted && byte == b'\\' {
+            escaped = true;
+        } else if byte == b'"' {
+            quoted = !quoted;
+        } else if !quoted && byte == delim {
+            return Some(idx);
+        }
+    }
+
+    None
+}
+
 /// Reads a single string when parsing a header.
 #[inline]
 pub fn from_one_raw_str<T: FromStr>(val: Option<&HeaderValue>) -> Result<T, ParseError> {
@@ -60,3 +211,45 @@ pub fn http_percent_encode(f: &mut fmt::Formatter<'_>, bytes: &[u8]) -> fmt::Res
     let encoded = percent_encoding::percent_encode(bytes, HTTP_VALUE);
     fmt::Display::fmt(&encoded, f)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn comma_delimited_quoted() {
+        let items = split_comma_delimited(r#""a,b", c , "d\",e""#).collect::<Vec<_>>();
+        assert_eq!(items, vec![r#""a,b""#, "c", r#""d\",e""#]);
+
+        assert_eq!(split_comma_delimited(" , ,").next(), None);
+        assert_eq!(split_comma_delimited("").next(), None);
+    }
+
+    #[test]
+    fn comma_delimited_unterminated_quote() {
+        let items = split_comma_delimited(r#"a, "b, c"#).collect::<Vec<_>>();
+        assert_eq!(items, vec!["a", r#""b, c"#]);
+    }
+
+    #[test]
+    fn parameters() {
+        let (val, params) = split_parameters(r#"attachment; filename="a;b.txt";; size"#);
+        assert_eq!(val, "attachment");
+
+        let params = params.collect::<Vec<_>>();
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0], ("filename", "a;b.txt".into()));
+        assert_eq!(params[1], ("size", "".into()));
+
+        let (val, mut params) = split_parameters("gzip");
+        assert_eq!(val, "gzip");
+        assert!(params.next().is_none());
+    }
+
+    #[test]
+    fn from_comma_delimited_skips_invalid() {
+        let hdr = HeaderValue::from_static(r#"1, x, "3", 4"#);
+        let res: Vec<u32> = from_comma_delimited(std::iter::once(&hdr)).unwrap();
+        assert_eq!(res, vec![1, 4]);
+    }
+}
@@ -1,10 +1,22 @@
 use std::io;
 
 use bytes::BufMut;
-use http::Version;
+use http::{
+    header::{HeaderName, AUTHORIZATION, CONTENT_LENGTH, HOST},
+    HeaderValue, Version,
+};
+
+use crate::{
+    config::{CrlfHandling, DuplicateHeaders},
+    header::{split_parameters, HeaderMap},
+    message::ResponseHead,
+};
 
 const DIGITS_START: u8 = b'0';
 
+/// Request headers that must occur at most once.
+const SINGLETON_HEADERS: [HeaderName; 3] = [HOST, CONTENT_LENGTH, AUTHORIZATION];
+
 pub(crate) fn write_status_line<B: BufMut>(version: Version, n: u16, buf: &mut B) {
     match version {
         Version::HTTP_11 => buf.put_slice(b"HTTP/1.1 "),
@@ -43,6 +55,93 @@ pub fn write_content_length<B: BufMut>(n: u64, buf: &mut B) {
     buf.put_slice(b"\r\n");
 }
 
+/// Returns `Content-Type` value with `; charset=utf-8` appended if it is a `text/*` or
+/// `application/json` type without a charset parameter.
+///
+/// Returns `None` if the value should be left unchanged.
+pub(crate) fn with_default_charset(ct: &HeaderValue) -> Option<HeaderValue> {
+    let ct = ct.to_str().ok()?;
+    let (mime, mut params) = split_parameters(ct);
+
+    let textual = mime
+        .get(..5)
+        .map_or(false, |ty| ty.eq_ignore_ascii_case("text/"))
+        || mime.eq_ignore_ascii_case("application/json");
+
+    if !textual || params.any(|(name, _)| name.eq_ignore_ascii_case("charset")) {
+        return None;
+    }
+
+    HeaderValue::from_str(&format!("{}; charset=utf-8", ct.trim_end())).ok()
+}
+
+/// Checks header values and reason phrase of a response for CR or LF characters, which would
+/// split HTTP/1.x responses.
+///
+/// Offending header values are dropped and reason phrases replaced when sanitizing. When
+/// rejecting, the first offending part of the response is returned for logging.
+pub(crate) fn check_crlf(
+    head: &mut ResponseHead,
+    handling: CrlfHandling,
+) -> Result<(), String> {
+    match handling {
+        CrlfHandling::Reject => {
+            if let Some((name, _)) = head
+                .headers
+                .iter()
+                .find(|(_, value)| has_crlf(value.as_bytes()))
+            {
+                return Err(format!("header {}", name));
+            }
+        }
+        CrlfHandling::Sanitize => {
+            head.headers.retain(|_, value| !has_crlf(value.as_bytes()))
+        }
+    }
+
+    if head
+        .reason
+        .map_or(false, |reason| has_crlf(reason.as_bytes()))
+    {
+        match handling {
+            CrlfHandling::Reject => return Err("reason phrase".to_owned()),
+            CrlfHandling::Sanitize => head.reason = None,
+        }
+    }
+
+    Ok(())
+}
+
+fn has_crlf(value: &[u8]) -> bool {
+    value.iter().any(|&b| b == b'\r' || b == b'\n')
+}
+
+/// Applies duplicate headers policy to the singleton headers of a request.
+///
+/// Returns name of the first duplicated header if the request has to be rejected.
+pub(crate) fn dedup_singleton_headers(
+    headers: &mut HeaderMap,
+    policy: DuplicateHeaders,
+) -> Result<(), HeaderName> {
+    for name in SINGLETON_HEADERS.iter() {
+        if headers.get_all(name).nth(1).is_none() {
+            continue;
+        }
+
+        let value = match policy {
+            DuplicateHeaders::Reject => return Err(name.clone()),
+            DuplicateHeaders::FirstWins => headers.get_all(name).next(),
+            DuplicateHeaders::LastWins => headers.get_all(name).last(),
+        };
+
+        if let Some(value) = value.cloned() {
+            headers.insert(name.clone(), value);
+        }
+    }
+
+    Ok(())
+}
+
 /// An `io::Write`r that only requires mutable reference and assumes that there is space available
 /// in the buffer for every write operation or that it can be extended implicitly (like
 /// `bytes::BytesMut`, for example).
@@ -168,4 +267,89 @@ mod tests {
             b"\r\ncontent-length: 4294973728\r\n"[..]
         );
     }
+
+    #[test]
+    fn test_check_crlf() {
+        let mut head = ResponseHead::new(http::StatusCode::OK);
+        head.reason = Some("OK\r\nx-injected: 1");
+        assert_eq!(
+            check_crlf(&mut head, CrlfHandling::Reject),
+            Err("reason phrase".to_owned())
+        );
+
+        assert_eq!(check_crlf(&mut head, CrlfHandling::Sanitize), Ok(()));
+        assert_eq!(head.reason(), "OK");
+
+        head.headers
+            .insert(http::header::SERVER, HeaderValue::from_static("actix"));
+        assert_eq!(check_crlf(&mut head, CrlfHandling::Reject), Ok(()));
+
+        // unchecked header values are only validated in debug builds
+        #[cfg(not(debug_assertions))]
+        {
+            // SAFETY: only the CR and LF characters under test are invalid
+            let value = unsafe {
+                HeaderValue::from_maybe_shared_unchecked("a\r\nx-injected: 1")
+            };
+            head.headers
+                .insert(http::header::HeaderName::from_static("x-value"), value);
+            assert_eq!(
+                check_crlf(&mut head, CrlfHandling::Reject),
+                Err("header x-value".to_owned())
+            );
+
+            assert_eq!(check_crlf(&mut head, CrlfHandling::Sanitize), Ok(()));
+            assert!(!head.headers.contains_key("x-value"));
+            assert!(head.headers.contains_key(http::header::SERVER));
+        }
+    }
+
+    #[test]
+    fn test_dedup_singleton_headers() {
+        let headers = || {
+            let mut headers = HeaderMap::new();
+            headers.append(HOST, HeaderValue::from_static("a.example"));
+            headers.append(HOST, HeaderValue::from_static("b.example"));
+            headers.append(http::header::ACCEPT, HeaderValue::from_static("text/plain"));
+            headers.append(http::header::ACCEPT, HeaderValue::from_static("text/html"));
+            headers
+        };
+
+        assert_eq!(
+            dedup_singleton_headers(&mut headers(), DuplicateHeaders::Reject),
+            Err(HOST)
+        );
+
+        let mut first = headers();
+        dedup_singleton_headers(&mut first, DuplicateHeaders::FirstWins).unwrap();
+        assert_eq!(first.get_all(HOST).collect::<Vec<_>>(), vec!["a.example"]);
+        assert_eq!(first.get_all(http::header::ACCEPT).count(), 2);
+
+        let mut last = headers();
+        dedup_singleton_headers(&mut last, DuplicateHeaders::LastWins).unwrap();
+        assert_eq!(last.get_all(HOST).collect::<Vec<_>>(), vec!["b.example"]);
+    }
+
+    #[test]
+    fn test_default_charset() {
+        let charset = |ct: &'static str| {
+            with_default_charset(&HeaderValue::from_static(ct))
+                .map(|ct| ct.to_str().unwrap().to_owned())
+        };
+
+        assert_eq!(charset("text/plain").unwrap(), "text/plain; charset=utf-8");
+        assert_eq!(
+            charset("Application/JSON").unwrap(),
+            "Application/JSON; charset=utf-8"
+        );
+        assert_eq!(
+            charset("text/html; q=1").unwrap(),
+            "text/html; q=1; charset=utf-8"
+        );
+
+        assert!(charset("text/plain; Charset=latin1").is_none());
+        assert!(charset("application/octet-stream").is_none());
+        assert!(charset("application/jsonp").is_none());
+        assert!(charset("image/png").is_none());
+    }
 }
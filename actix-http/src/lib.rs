@@ -9,6 +9,7 @@
 //! | `compress-gzip`     | Payload compression support: Deflate, Gzip. |
 //! | `compress-zstd`     | Payload compression support: Zstd.          |
 //! | `trust-dns`         | Use [trust-dns] as the client DNS resolver. |
+//! | `serde`             | `Serialize`/`Deserialize` for `HeaderMap`.  |
 //!
 //! [OpenSSL]: https://crates.io/crates/openssl
 //! [rustls]: https://crates.io/crates/rustls
@@ -27,6 +28,8 @@
 #[macro_use]
 extern crate log;
 
+use std::rc::Rc;
+
 pub mod body;
 mod builder;
 pub mod client;
@@ -40,6 +43,8 @@ mod helpers;
 mod http_message;
 mod message;
 mod payload;
+mod prioritizer;
+mod query;
 mod request;
 mod response;
 mod response_builder;
@@ -53,7 +58,10 @@ pub mod test;
 pub mod ws;
 
 pub use self::builder::HttpServiceBuilder;
-pub use self::config::{KeepAlive, ServiceConfig};
+pub use self::config::{
+    BodyLengthMismatch, CrlfHandling, DuplicateHeaders, Http10Expect, KeepAlive,
+    ServiceConfig,
+};
 pub use self::error::Error;
 pub use self::extensions::Extensions;
 pub use self::header::ContentEncoding;
@@ -61,6 +69,8 @@ pub use self::http_message::HttpMessage;
 pub use self::message::ConnectionType;
 pub use self::message::{Message, RequestHead, RequestHeadType, ResponseHead};
 pub use self::payload::{Payload, PayloadStream};
+pub use self::prioritizer::{Fifo, Prioritizer};
+pub use self::query::QueryPairs;
 pub use self::request::Request;
 pub use self::response::Response;
 pub use self::response_builder::ResponseBuilder;
@@ -100,38 +110,86 @@ pub enum Protocol {
 
 type ConnectCallback<IO> = dyn Fn(&IO, &mut Extensions);
 
+/// Connection data inserted by the on-connect callback.
+///
+/// Added to the extensions of every request on the connection, keeping connection data apart
+/// from request-local extensions. Unlike the data merged into the extensions of the first request,
+/// it is also available to later requests on a keep-alive connection.
+///
+/// # Examples
+/// ```
+/// use actix_http::{ConnectionData, HttpMessage, Request};
+///
+/// fn ttl(req: &Request) -> Option<u32> {
+///     req.extensions()
+///         .get::<ConnectionData>()
+///         .and_then(|data| data.get::<u32>())
+///         .copied()
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConnectionData(Rc<Extensions>);
+
+impl ConnectionData {
+    /// Get a reference to a value of type `T` inserted by the on-connect callback.
+    pub fn get<T: 'static>(&self) -> Option<&T> {
+        self.0.get::<T>()
+    }
+
+    /// Check if the on-connect callback inserted a value of type `T`.
+    pub fn contains<T: 'static>(&self) -> bool {
+        self.0.contains::<T>()
+    }
+}
+
+impl From<Extensions> for ConnectionData {
+    fn from(ext: Extensions) -> Self {
+        ConnectionData(Rc::new(ext))
+    }
+}
+
 /// Container for data that extract with ConnectCallback.
 ///
 /// # Implementation Details
 /// Uses Option to reduce necessary allocations when merging with request extensions.
-pub(crate) struct OnConnectData(Option<Extensions>);
-
-impl Default for OnConnectData {
-    fn default() -> Self {
-        Self(None)
-    }
+#[derive(Default)]
+pub(crate) struct OnConnectData {
+    ext: Option<Extensions>,
+    data: Option<ConnectionData>,
 }
 
 impl OnConnectData {
     /// Construct by calling the on-connect callback with the underlying transport I/O.
+    ///
+    /// Extensions can not be cloned so the callback is called twice; once for the extensions
+    /// merged into the first request and once for the [`ConnectionData`] of every request.
     pub(crate) fn from_io<T>(
         io: &T,
         on_connect_ext: Option<&ConnectCallback<T>>,
     ) -> Self {
-        let ext = on_connect_ext.map(|handler| {
+        let call = |handler: &ConnectCallback<T>| {
             let mut extensions = Extensions::new();
             handler(io, &mut extensions);
             extensions
-        });
+        };
 
-        Self(ext)
+        Self {
+            ext: on_connect_ext.map(call),
+            data: on_connect_ext.map(call).map(ConnectionData::from),
+        }
     }
 
-    /// Merge self into given request's extensions.
+    /// Merge self into given request's extensions and add [`ConnectionData`] to them.
     #[inline]
     pub(crate) fn merge_into(&mut self, req: &mut Request) {
-        if let Some(ref mut ext) = self.0 {
-            req.head.extensions.get_mut().drain_from(ext);
+        let extensions = req.head.extensions.get_mut();
+
+        if let Some(ref mut ext) = self.ext {
+            extensions.drain_from(ext);
+        }
+
+        if let Some(ref data) = self.data {
+            extensions.insert(data.clone());
         }
     }
 }
@@ -8,7 +8,7 @@ use bitflags::bitflags;
 
 use crate::{
     header::{self, HeaderMap},
-    Extensions, Method, StatusCode, Uri, Version,
+    Extensions, Method, QueryPairs, StatusCode, Uri, Version,
 };
 
 /// Represents various types of connection
@@ -24,6 +24,35 @@ pub enum ConnectionType {
     Upgrade,
 }
 
+#[cfg(feature = "serde")]
+impl serde::Serialize for ConnectionType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(match self {
+            ConnectionType::Close => "close",
+            ConnectionType::KeepAlive => "keep-alive",
+            ConnectionType::Upgrade => "upgrade",
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ConnectionType {
+    fn deserialize<D: serde::Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        const VARIANTS: &[&str] = &["close", "keep-alive", "upgrade"];
+
+        let val = String::deserialize(deserializer)?;
+
+        match val.as_str() {
+            "close" => Ok(ConnectionType::Close),
+            "keep-alive" => Ok(ConnectionType::KeepAlive),
+            "upgrade" => Ok(ConnectionType::Upgrade),
+            _ => Err(serde::de::Error::unknown_variant(&val, VARIANTS)),
+        }
+    }
+}
+
 bitflags! {
     pub(crate) struct Flags: u8 {
         const CLOSE       = 0b0000_0001;
@@ -32,6 +61,7 @@ bitflags! {
         const EXPECT      = 0b0000_1000;
         const NO_CHUNKING = 0b0001_0000;
         const CAMEL_CASE  = 0b0010_0000;
+        const ABSOLUTE_FORM = 0b0100_0000;
     }
 }
 
@@ -97,6 +127,25 @@ impl RequestHead {
         self.extensions.borrow_mut()
     }
 
+    /// Returns percent-decoded key-value pairs of the request's query string.
+    ///
+    /// The query string is parsed on first use and the result is cached in the request's
+    /// extensions, so repeated calls from different layers are cheap. The cache is refreshed if the
+    /// URI's query string has changed since.
+    pub fn query_pairs(&self) -> Rc<QueryPairs> {
+        let query = self.uri.query().unwrap_or("");
+
+        if let Some(pairs) = self.extensions().get::<Rc<QueryPairs>>() {
+            if pairs.query() == query {
+                return Rc::clone(pairs);
+            }
+        }
+
+        let pairs = Rc::new(QueryPairs::parse(query));
+        self.extensions_mut().insert(Rc::clone(&pairs));
+        pairs
+    }
+
     /// Read the message headers.
     pub fn headers(&self) -> &HeaderMap {
         &self.headers
@@ -189,6 +238,17 @@ impl RequestHead {
     pub(crate) fn set_expect(&mut self) {
         self.flags.insert(Flags::EXPECT);
     }
+
+    #[inline]
+    /// Request target is sent in absolute-form, i.e. to a proxy
+    pub(crate) fn absolute_form(&self) -> bool {
+        self.flags.contains(Flags::ABSOLUTE_FORM)
+    }
+
+    #[inline]
+    pub(crate) fn set_absolute_form(&mut self) {
+        self.flags.insert(Flags::ABSOLUTE_FORM);
+    }
 }
 
 #[derive(Debug)]
@@ -204,6 +264,19 @@ impl RequestHeadType {
             RequestHeadType::Rc(_, headers) => headers.as_ref(),
         }
     }
+
+    /// Returns mutable headers of the outgoing request.
+    ///
+    /// For shared heads, these are the extra headers, which take precedence over the shared head's
+    /// headers of the same name.
+    pub fn headers_mut(&mut self) -> &mut HeaderMap {
+        match self {
+            RequestHeadType::Owned(head) => &mut head.headers,
+            RequestHeadType::Rc(_, headers) => {
+                headers.get_or_insert_with(HeaderMap::new)
+            }
+        }
+    }
 }
 
 impl AsRef<RequestHead> for RequestHeadType {
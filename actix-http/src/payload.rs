@@ -2,10 +2,10 @@ use std::pin::Pin;
 use std::task::{Context, Poll};
 
 use bytes::Bytes;
-use futures_core::Stream;
+use futures_core::{ready, Stream};
 use h2::RecvStream;
 
-use crate::error::PayloadError;
+use crate::{body::Frame, error::PayloadError};
 
 /// Type represent boxed payload
 pub type PayloadStream = Pin<Box<dyn Stream<Item = Result<Bytes, PayloadError>>>>;
@@ -49,6 +49,35 @@ impl<S> Payload<S> {
     }
 }
 
+impl<S> Payload<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+{
+    /// Poll next frame of the payload.
+    ///
+    /// HTTP/1 chunked and HTTP/2 payloads end with a trailers frame if the peer sent trailer
+    /// fields; other payloads only produce data frames.
+    pub fn poll_frame(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Frame, PayloadError>>> {
+        match self {
+            Payload::H1(ref mut pl) => match ready!(pl.readany(cx)) {
+                Some(res) => Poll::Ready(Some(res.map(Frame::Data))),
+                None => Poll::Ready(
+                    pl.take_trailers()
+                        .map(|trailers| Ok(Frame::Trailers(trailers))),
+                ),
+            },
+            Payload::H2(ref mut pl) => pl.poll_frame(cx),
+            _ => match ready!(Pin::new(self).poll_next(cx)) {
+                Some(res) => Poll::Ready(Some(res.map(Frame::Data))),
+                None => Poll::Ready(None),
+            },
+        }
+    }
+}
+
 impl<S> Stream for Payload<S>
 where
     S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
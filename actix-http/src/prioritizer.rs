@@ -0,0 +1,47 @@
+use crate::RequestHead;
+
+/// Chooses which of the requests waiting on a connection is passed to the service next.
+///
+/// Requests wait when they arrive faster than the service becomes ready to take them, e.g.
+/// behind a concurrency limiting middleware. A prioritizer can let health checks or small `GET`
+/// requests overtake bulk uploads.
+///
+/// Only HTTP/2 streams are prioritized. Pipelined HTTP/1.1 requests are always serviced in the
+/// order they were received, since their responses must be sent in that order (see
+/// [RFC 7230 §6.3.2](https://datatracker.ietf.org/doc/html/rfc7230#section-6.3.2)).
+///
+/// # Examples
+/// ```
+/// use actix_http::{http::Method, Prioritizer, RequestHead};
+///
+/// /// Services bodyless requests before others.
+/// struct BodylessFirst;
+///
+/// impl Prioritizer for BodylessFirst {
+///     fn next(&self, pending: &[&RequestHead]) -> usize {
+///         pending
+///             .iter()
+///             .position(|head| head.method == Method::GET || head.method == Method::HEAD)
+///             .unwrap_or(0)
+///     }
+/// }
+/// ```
+pub trait Prioritizer {
+    /// Returns the index in `pending` of the request to service next.
+    ///
+    /// `pending` holds at least two requests, in the order they were received. Indices out of
+    /// range select the first request.
+    fn next(&self, pending: &[&RequestHead]) -> usize;
+}
+
+/// Prioritizer servicing requests in the order they were received.
+///
+/// This is the default.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct Fifo;
+
+impl Prioritizer for Fifo {
+    fn next(&self, _: &[&RequestHead]) -> usize {
+        0
+    }
+}
@@ -0,0 +1,114 @@
+use std::borrow::Cow;
+
+use percent_encoding::percent_decode;
+
+/// Percent-decoded key-value pairs of a request's query string.
+///
+/// Pairs are kept in the order they appear in the query string; keys may occur more than once.
+/// Returned by [`RequestHead::query_pairs`](crate::RequestHead::query_pairs).
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryPairs {
+    query: String,
+    pairs: Vec<(String, String)>,
+}
+
+impl QueryPairs {
+    /// Parse `application/x-www-form-urlencoded` style query string.
+    ///
+    /// `+` is decoded to a space. Invalid UTF-8 sequences are replaced with `U+FFFD`.
+    pub fn parse(query: &str) -> Self {
+        let pairs = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| {
+                let mut parts = pair.splitn(2, '=');
+                let key = decode(parts.next().unwrap_or(""));
+                let value = decode(parts.next().unwrap_or(""));
+                (key, value)
+            })
+            .collect();
+
+        QueryPairs {
+            query: query.to_owned(),
+            pairs,
+        }
+    }
+
+    /// Returns the raw query string the pairs were parsed from.
+    pub fn query(&self) -> &str {
+        &self.query
+    }
+
+    /// Returns value of the first pair with the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns values of all pairs with the given key, in order.
+    pub fn get_all(&self, key: &str) -> impl Iterator<Item = &str> {
+        let key = key.to_owned();
+
+        self.pairs
+            .iter()
+            .filter(move |(k, _)| *k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns true if a pair with the given key exists.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Returns an iterator over all pairs, in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns number of pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Returns true if there are no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+fn decode(input: &str) -> String {
+    let input = if input.contains('+') {
+        Cow::Owned(input.replace('+', " "))
+    } else {
+        Cow::Borrowed(input)
+    };
+
+    percent_decode(input.as_bytes())
+        .decode_utf8_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse() {
+        let pairs =
+            QueryPairs::parse("a=1&b=hello+world&a=%32&&c&d=%F0%9F%A6%80&e%3D=x=y");
+
+        assert_eq!(pairs.len(), 6);
+        assert_eq!(pairs.get("a"), Some("1"));
+        assert_eq!(pairs.get_all("a").collect::<Vec<_>>(), vec!["1", "2"]);
+        assert_eq!(pairs.get("b"), Some("hello world"));
+        assert_eq!(pairs.get("c"), Some(""));
+        assert_eq!(pairs.get("d"), Some("🦀"));
+        assert_eq!(pairs.get("e="), Some("x=y"));
+        assert_eq!(pairs.get("f"), None);
+        assert!(!pairs.contains_key("f"));
+
+        assert!(QueryPairs::parse("").is_empty());
+    }
+}
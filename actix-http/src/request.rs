@@ -199,7 +199,7 @@ impl<P> fmt::Debug for Request<P> {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use std::convert::TryFrom;
+    use std::{convert::TryFrom, rc::Rc};
 
     #[test]
     fn test_basics() {
@@ -218,4 +218,22 @@ mod tests {
         let s = format!("{:?}", req);
         assert!(s.contains("Request HTTP/1.1 GET:/index.html"));
     }
+
+    #[test]
+    fn test_query_pairs() {
+        let mut req = Request::from(Message::new());
+        *req.uri_mut() = Uri::try_from("/?q=hello%20world&tag=a&tag=b").unwrap();
+
+        let pairs = req.head().query_pairs();
+        assert_eq!(pairs.get("q"), Some("hello world"));
+        assert_eq!(pairs.get_all("tag").collect::<Vec<_>>(), vec!["a", "b"]);
+
+        // parsed once and cached
+        assert!(Rc::ptr_eq(&pairs, &req.head().query_pairs()));
+
+        // reparsed after query string changes
+        *req.uri_mut() = Uri::try_from("/?q=other").unwrap();
+        assert_eq!(req.head().query_pairs().get("q"), Some("other"));
+        assert!(!Rc::ptr_eq(&pairs, &req.head().query_pairs()));
+    }
 }
@@ -9,7 +9,7 @@ use std::{
     task::{Context, Poll},
 };
 
-use ::h2::server::{handshake as h2_handshake, Handshake as H2Handshake};
+use ::h2::server::Handshake as H2Handshake;
 use actix_codec::{AsyncRead, AsyncWrite, Framed};
 use actix_rt::net::TcpStream;
 use actix_service::{
@@ -511,7 +511,7 @@ where
         match proto {
             Protocol::Http2 => HttpServiceHandlerResponse {
                 state: State::H2Handshake(Some((
-                    h2_handshake(io),
+                    h2::handshake(io),
                     self.cfg.clone(),
                     self.flow.clone(),
                     on_connect_data,
@@ -2,21 +2,35 @@
 
 use std::{
     cell::{Ref, RefCell},
+    collections::VecDeque,
+    convert::Infallible,
+    fs::{self, File},
+    future::{ready, Future, Ready},
     io::{self, Read, Write},
+    path::Path,
     pin::Pin,
     rc::Rc,
     str::FromStr,
     task::{Context, Poll},
+    time::Duration,
 };
 
 use actix_codec::{AsyncRead, AsyncWrite, ReadBuf};
+use actix_rt::time::{sleep, sleep_until, Instant, Sleep};
+use actix_service::{Service, ServiceFactory};
 use bytes::{Bytes, BytesMut};
+use futures_core::ready;
+use futures_util::{
+    future::{pending, Pending},
+    stream,
+};
 use http::{Method, Uri, Version};
 
 use crate::{
+    body::{AnyBody, BodySize, FrameBody, MessageBody},
     header::{HeaderMap, IntoHeaderPair},
     payload::Payload,
-    Request,
+    Error, Request, Response,
 };
 
 /// Test `Request` builder
@@ -364,3 +378,537 @@ impl AsyncWrite for TestSeqBuffer {
         Poll::Ready(Ok(()))
     }
 }
+
+/// Transport wrapper that records all data read from and written to the wrapped I/O.
+///
+/// Each chunk is written to the recording sink as a line containing the milliseconds elapsed
+/// since the recorder was created, the direction (`r` for data read from the peer, `w` for data
+/// written to it) and the base64 encoded data. Failing to write to the sink is logged and does not
+/// interrupt the connection.
+///
+/// Recorded client conversations can be fed back into a dispatcher using [`Replay`].
+pub struct Recorder<T> {
+    io: T,
+    start: Instant,
+    sink: Box<dyn Write>,
+}
+
+impl<T> Recorder<T> {
+    /// Create recorder that writes recorded data to `sink`.
+    pub fn new<W>(io: T, sink: W) -> Self
+    where
+        W: Write + 'static,
+    {
+        Self {
+            io,
+            start: Instant::now(),
+            sink: Box::new(sink),
+        }
+    }
+
+    /// Create recorder that writes recorded data to a newly created file.
+    pub fn to_file(io: T, path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::create(path)?;
+        Ok(Self::new(io, io::BufWriter::new(file)))
+    }
+
+    /// Returns a reference to the wrapped I/O.
+    pub fn get_ref(&self) -> &T {
+        &self.io
+    }
+
+    /// Returns the wrapped I/O.
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+
+    fn record(&mut self, direction: &str, data: &[u8]) {
+        let elapsed = self.start.elapsed().as_millis();
+        let res = writeln!(
+            self.sink,
+            "{} {} {}",
+            elapsed,
+            direction,
+            base64::encode(data)
+        )
+        .and_then(|_| self.sink.flush());
+
+        if let Err(err) = res {
+            warn!("Failed to record transport data: {}", err);
+        }
+    }
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for Recorder<T> {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let this = self.get_mut();
+        let filled = buf.filled().len();
+
+        ready!(Pin::new(&mut this.io).poll_read(cx, buf))?;
+
+        let data = &buf.filled()[filled..];
+        if !data.is_empty() {
+            this.record("r", data);
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for Recorder<T> {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        let n = ready!(Pin::new(&mut this.io).poll_write(cx, buf))?;
+
+        if n > 0 {
+            this.record("w", &buf[..n]);
+        }
+
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_flush(cx)
+    }
+
+    fn poll_shutdown(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.get_mut().io).poll_shutdown(cx)
+    }
+}
+
+/// Async I/O that replays a conversation captured by a [`Recorder`].
+///
+/// Data recorded as read from the peer is yielded to the reader, by default with the recorded
+/// timing relative to the first read. Once all recorded data has been read, reads return EOF.
+/// Written data is collected so that it can be compared with the recorded writes.
+///
+/// # Examples
+/// ```no_run
+/// use actix_http::test::Replay;
+///
+/// let io = Replay::from_file("tests/fixtures/slowloris.rec").unwrap();
+/// // pass `io.clone()` to a dispatcher then inspect `io.write_buf()`
+/// ```
+#[derive(Clone)]
+pub struct Replay(Rc<RefCell<ReplayInner>>);
+
+struct ReplayInner {
+    reads: VecDeque<(Duration, Bytes)>,
+    recorded_writes: BytesMut,
+    write_buf: BytesMut,
+    timing: bool,
+    start: Option<Instant>,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl Replay {
+    /// Parse recording in the format written by [`Recorder`].
+    pub fn parse(recording: &str) -> io::Result<Self> {
+        let mut reads = VecDeque::new();
+        let mut recorded_writes = BytesMut::new();
+
+        for line in recording.lines().filter(|line| !line.trim().is_empty()) {
+            let invalid = || {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("invalid recording line: {}", line),
+                )
+            };
+
+            let mut parts = line.split_whitespace();
+            let (elapsed, direction, data) =
+                match (parts.next(), parts.next(), parts.next()) {
+                    (Some(elapsed), Some(direction), Some(data)) => {
+                        (elapsed, direction, data)
+                    }
+                    _ => return Err(invalid()),
+                };
+
+            let elapsed = elapsed.parse().map_err(|_| invalid())?;
+            let data = base64::decode(data).map_err(|_| invalid())?;
+
+            match direction {
+                "r" => {
+                    reads.push_back((Duration::from_millis(elapsed), Bytes::from(data)))
+                }
+                "w" => recorded_writes.extend_from_slice(&data),
+                _ => return Err(invalid()),
+            }
+        }
+
+        Ok(Self(Rc::new(RefCell::new(ReplayInner {
+            reads,
+            recorded_writes,
+            write_buf: BytesMut::new(),
+            timing: true,
+            start: None,
+            sleep: None,
+        }))))
+    }
+
+    /// Read and parse recording file.
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    /// Yield recorded data as soon as it is read, ignoring recorded timing.
+    pub fn without_timing(self) -> Self {
+        self.0.borrow_mut().timing = false;
+        self
+    }
+
+    /// Returns data written to the replay I/O.
+    pub fn write_buf(&self) -> Ref<'_, BytesMut> {
+        Ref::map(self.0.borrow(), |inner| &inner.write_buf)
+    }
+
+    /// Returns data that was written to the recorded I/O.
+    pub fn recorded_writes(&self) -> Ref<'_, BytesMut> {
+        Ref::map(self.0.borrow(), |inner| &inner.recorded_writes)
+    }
+}
+
+impl AsyncRead for Replay {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        let mut inner = self.0.borrow_mut();
+        let inner = &mut *inner;
+
+        let at = match inner.reads.front() {
+            Some((at, _)) => *at,
+            None => return Poll::Ready(Ok(())),
+        };
+
+        if inner.timing {
+            let deadline = *inner.start.get_or_insert_with(Instant::now) + at;
+
+            if Instant::now() < deadline {
+                let sleep = inner
+                    .sleep
+                    .get_or_insert_with(|| Box::pin(sleep_until(deadline)));
+                ready!(sleep.as_mut().poll(cx));
+            }
+
+            inner.sleep = None;
+        }
+
+        let (_, data) = inner.reads.front_mut().unwrap();
+        let n = std::cmp::min(buf.remaining(), data.len());
+        buf.put_slice(&data.split_to(n));
+
+        if data.is_empty() {
+            inner.reads.pop_front();
+        }
+
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncWrite for Replay {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.0.borrow_mut().write_buf.extend_from_slice(buf);
+        Poll::Ready(Ok(buf.len()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// Test service that responds to every request with its body.
+///
+/// The request payload is streamed back as the response body chunk by chunk, without buffering,
+/// so back-pressure from the client applies to both directions. Request trailers are echoed as
+/// response trailers.
+///
+/// Implements both [`Service`] and [`ServiceFactory`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EchoService;
+
+impl Service<Request> for EchoService {
+    type Response = Response<AnyBody>;
+    type Error = Error;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    actix_service::always_ready!();
+
+    fn call(&self, mut req: Request) -> Self::Future {
+        let mut payload = req.take_payload();
+        let body = FrameBody::new(stream::poll_fn(move |cx| payload.poll_frame(cx)));
+        ready(Ok(Response::ok().set_body(AnyBody::from_message(body))))
+    }
+}
+
+impl ServiceFactory<Request> for EchoService {
+    type Response = Response<AnyBody>;
+    type Error = Error;
+    type Config = ();
+    type Service = EchoService;
+    type InitError = ();
+    type Future = Ready<Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        ready(Ok(EchoService))
+    }
+}
+
+/// Test service whose response futures never resolve.
+///
+/// Useful for exercising timeouts and behavior of dispatchers with pending requests.
+///
+/// Implements both [`Service`] and [`ServiceFactory`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NeverService;
+
+impl Service<Request> for NeverService {
+    type Response = Response<AnyBody>;
+    type Error = Error;
+    type Future = Pending<Result<Self::Response, Self::Error>>;
+
+    actix_service::always_ready!();
+
+    fn call(&self, _: Request) -> Self::Future {
+        pending()
+    }
+}
+
+impl ServiceFactory<Request> for NeverService {
+    type Response = Response<AnyBody>;
+    type Error = Error;
+    type Config = ();
+    type Service = NeverService;
+    type InitError = ();
+    type Future = Ready<Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        ready(Ok(NeverService))
+    }
+}
+
+/// Test body that yields a chunk after each interval, a fixed number of times.
+///
+/// The first interval starts when the body is first polled, not when it is created.
+///
+/// Useful for exercising slow producers, e.g. timeouts and keep-alive handling while a response
+/// is being written.
+pub struct SlowBody {
+    chunk: Bytes,
+    remaining: usize,
+    interval: Duration,
+    sleep: Option<Pin<Box<Sleep>>>,
+}
+
+impl SlowBody {
+    /// Create body that yields `chunk` `count` times, waiting `interval` before each one.
+    pub fn new(chunk: impl Into<Bytes>, count: usize, interval: Duration) -> Self {
+        SlowBody {
+            chunk: chunk.into(),
+            remaining: count,
+            interval,
+            sleep: None,
+        }
+    }
+}
+
+impl MessageBody for SlowBody {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        let this = self.get_mut();
+
+        if this.remaining == 0 {
+            return Poll::Ready(None);
+        }
+
+        let interval = this.interval;
+        let sleep = this.sleep.get_or_insert_with(|| Box::pin(sleep(interval)));
+        ready!(sleep.as_mut().poll(cx));
+
+        this.remaining -= 1;
+        sleep.as_mut().reset(Instant::now() + interval);
+
+        Poll::Ready(Some(Ok(this.chunk.clone())))
+    }
+}
+
+/// Test body that never ends, yielding the same chunk every time it is polled.
+///
+/// Useful for exercising back-pressure and clients that disconnect mid-response.
+pub struct InfiniteBody(Bytes);
+
+impl InfiniteBody {
+    /// Create body that repeatedly yields `chunk`.
+    ///
+    /// # Panics
+    /// Panics if `chunk` is empty.
+    pub fn new(chunk: impl Into<Bytes>) -> Self {
+        let chunk = chunk.into();
+        assert!(!chunk.is_empty(), "InfiniteBody chunk must not be empty");
+        InfiniteBody(chunk)
+    }
+}
+
+impl MessageBody for InfiniteBody {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        Poll::Ready(Some(Ok(self.0.clone())))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::future::{lazy, poll_fn};
+
+    use super::*;
+    use crate::body::to_bytes;
+
+    #[actix_rt::test]
+    async fn echo_service() {
+        let req = TestRequest::default().set_payload("hello world").finish();
+
+        let res = EchoService.call(req).await.unwrap();
+        let body = to_bytes(res.into_body()).await.unwrap();
+        assert_eq!(body, Bytes::from_static(b"hello world"));
+    }
+
+    #[actix_rt::test]
+    async fn never_service() {
+        let mut fut = NeverService.call(Request::new());
+        assert!(lazy(|cx| Pin::new(&mut fut).poll(cx)).await.is_pending());
+    }
+
+    #[actix_rt::test]
+    async fn slow_body() {
+        let body = SlowBody::new("ab", 3, Duration::from_millis(10));
+
+        // deadline starts on first poll
+        actix_rt::time::sleep(Duration::from_millis(20)).await;
+        let start = Instant::now();
+        assert_eq!(to_bytes(body).await.unwrap(), Bytes::from_static(b"ababab"));
+        assert!(start.elapsed() >= Duration::from_millis(30));
+    }
+
+    #[actix_rt::test]
+    async fn infinite_body() {
+        let mut body = InfiniteBody::new("a");
+
+        for _ in 0..100 {
+            let chunk = poll_fn(|cx| Pin::new(&mut body).poll_next(cx)).await;
+            assert_eq!(chunk.unwrap().unwrap(), Bytes::from_static(b"a"));
+        }
+    }
+
+    #[derive(Clone, Default)]
+    struct SharedSink(Rc<RefCell<Vec<u8>>>);
+
+    impl Write for SharedSink {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.0.borrow_mut().extend_from_slice(buf);
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    async fn read_all(io: &mut (impl AsyncRead + Unpin)) -> Vec<u8> {
+        let mut data = Vec::new();
+
+        loop {
+            let mut chunk = [0; 8];
+            let mut buf = ReadBuf::new(&mut chunk);
+            poll_fn(|cx| Pin::new(&mut *io).poll_read(cx, &mut buf))
+                .await
+                .unwrap();
+
+            if buf.filled().is_empty() {
+                return data;
+            }
+
+            data.extend_from_slice(buf.filled());
+        }
+    }
+
+    #[actix_rt::test]
+    async fn record_and_replay() {
+        let sink = SharedSink::default();
+        let buf = TestBuffer::new("GET / HTTP/1.1\r\n\r\n");
+        let mut io = Recorder::new(buf, sink.clone());
+
+        let mut chunk = [0; 64];
+        let mut read_buf = ReadBuf::new(&mut chunk);
+        poll_fn(|cx| Pin::new(&mut io).poll_read(cx, &mut read_buf))
+            .await
+            .unwrap();
+        poll_fn(|cx| Pin::new(&mut io).poll_write(cx, b"HTTP/1.1 200 OK\r\n\r\n"))
+            .await
+            .unwrap();
+
+        let recording = String::from_utf8(sink.0.borrow().clone()).unwrap();
+        assert_eq!(recording.lines().count(), 2);
+
+        let replay = Replay::parse(&recording).unwrap().without_timing();
+        assert_eq!(
+            &replay.recorded_writes()[..],
+            &b"HTTP/1.1 200 OK\r\n\r\n"[..]
+        );
+
+        let mut io = replay.clone();
+        assert_eq!(read_all(&mut io).await, b"GET / HTTP/1.1\r\n\r\n");
+
+        poll_fn(|cx| Pin::new(&mut io).poll_write(cx, b"HTTP/1.1 200 OK\r\n\r\n"))
+            .await
+            .unwrap();
+        assert_eq!(*replay.write_buf(), *replay.recorded_writes());
+    }
+
+    #[actix_rt::test]
+    async fn replay_timing() {
+        let mut io = Replay::parse("0 r YQ==\n30 r Yg==\n").unwrap();
+
+        let start = Instant::now();
+        assert_eq!(read_all(&mut io).await, b"ab");
+        assert!(start.elapsed() >= Duration::from_millis(30));
+
+        assert!(Replay::parse("0 x YQ==").is_err());
+        assert!(Replay::parse("0 r").is_err());
+    }
+}
@@ -0,0 +1,243 @@
+use std::{
+    convert::TryFrom,
+    pin::Pin,
+    task::{Context, Poll},
+};
+
+use bytes::{Bytes, BytesMut};
+use bytestring::ByteString;
+use futures_core::{ready, Stream};
+use pin_project_lite::pin_project;
+
+use super::{Frame, Item, Message, ProtocolError};
+
+pin_project! {
+    /// Stream adapter that assembles continuation frames into complete messages.
+    ///
+    /// Wraps a stream of decoded [`Frame`]s (e.g. a `Framed<T, ws::Codec>`) and yields
+    /// [`Message`]s. Fragmented messages are buffered until their last frame is received and then
+    /// yielded as a single `Message::Text` or `Message::Binary` item. Control frames received in
+    /// between fragments are passed through immediately.
+    pub struct Aggregated<S> {
+        #[pin]
+        stream: S,
+        max_size: usize,
+        continuation: Option<Continuation>,
+    }
+}
+
+struct Continuation {
+    text: bool,
+    buf: BytesMut,
+}
+
+impl<S> Aggregated<S>
+where
+    S: Stream<Item = Result<Frame, ProtocolError>>,
+{
+    /// Create new continuation aggregating stream.
+    pub fn new(stream: S) -> Self {
+        Aggregated {
+            stream,
+            max_size: 65_536,
+            continuation: None,
+        }
+    }
+
+    /// Set max size of assembled message payload.
+    ///
+    /// By default max size is set to 64kB.
+    pub fn max_size(mut self, size: usize) -> Self {
+        self.max_size = size;
+        self
+    }
+
+    /// Returns a reference to the wrapped frame stream.
+    pub fn get_ref(&self) -> &S {
+        &self.stream
+    }
+
+    /// Returns a mutable reference to the wrapped frame stream.
+    ///
+    /// Care should be taken not to read frames directly while a fragmented message is being
+    /// assembled.
+    pub fn get_mut(&mut self) -> &mut S {
+        &mut self.stream
+    }
+
+    /// Returns the wrapped frame stream.
+    pub fn into_inner(self) -> S {
+        self.stream
+    }
+}
+
+impl<S> Stream for Aggregated<S>
+where
+    S: Stream<Item = Result<Frame, ProtocolError>>,
+{
+    type Item = Result<Message, ProtocolError>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        loop {
+            let this = self.as_mut().project();
+
+            let frame = match ready!(this.stream.poll_next(cx)) {
+                Some(Ok(frame)) => frame,
+                Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                None => return Poll::Ready(None),
+            };
+
+            let msg = match frame {
+                Frame::Text(data) => text_message(data),
+                Frame::Binary(data) => Ok(Message::Binary(data)),
+                Frame::Ping(data) => Ok(Message::Ping(data)),
+                Frame::Pong(data) => Ok(Message::Pong(data)),
+                Frame::Close(reason) => Ok(Message::Close(reason)),
+
+                Frame::Continuation(item) => {
+                    match aggregate(this.continuation, item, *this.max_size) {
+                        Ok(Some(msg)) => Ok(msg),
+                        Ok(None) => continue,
+                        Err(err) => Err(err),
+                    }
+                }
+            };
+
+            return Poll::Ready(Some(msg));
+        }
+    }
+}
+
+/// Appends continuation item to buffered message, returning it once complete.
+fn aggregate(
+    continuation: &mut Option<Continuation>,
+    item: Item,
+    max_size: usize,
+) -> Result<Option<Message>, ProtocolError> {
+    let (data, last) = match item {
+        Item::FirstText(data) => {
+            start(continuation, true)?;
+            (data, false)
+        }
+        Item::FirstBinary(data) => {
+            start(continuation, false)?;
+            (data, false)
+        }
+        Item::Continue(data) => (data, false),
+        Item::Last(data) => (data, true),
+    };
+
+    let cont = continuation
+        .as_mut()
+        .ok_or(ProtocolError::ContinuationNotStarted)?;
+
+    if cont.buf.len() + data.len() > max_size {
+        *continuation = None;
+        return Err(ProtocolError::Overflow);
+    }
+
+    cont.buf.extend_from_slice(&data);
+
+    if !last {
+        return Ok(None);
+    }
+
+    let cont = continuation.take().unwrap();
+    let data = cont.buf.freeze();
+
+    if cont.text {
+        text_message(data).map(Some)
+    } else {
+        Ok(Some(Message::Binary(data)))
+    }
+}
+
+fn start(
+    continuation: &mut Option<Continuation>,
+    text: bool,
+) -> Result<(), ProtocolError> {
+    if continuation.is_some() {
+        return Err(ProtocolError::ContinuationStarted);
+    }
+
+    *continuation = Some(Continuation {
+        text,
+        buf: BytesMut::new(),
+    });
+
+    Ok(())
+}
+
+fn text_message(data: Bytes) -> Result<Message, ProtocolError> {
+    ByteString::try_from(data)
+        .map(Message::Text)
+        .map_err(|_| ProtocolError::InvalidUtf8)
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+
+    fn aggregated(
+        frames: Vec<Frame>,
+    ) -> Aggregated<impl Stream<Item = Result<Frame, ProtocolError>>> {
+        Aggregated::new(stream::iter(frames.into_iter().map(Ok)))
+    }
+
+    #[actix_rt::test]
+    async fn assembles_continuation() {
+        let mut stream = aggregated(vec![
+            Frame::Continuation(Item::FirstText(Bytes::from_static(b"Hello, "))),
+            Frame::Ping(Bytes::from_static(b"p")),
+            Frame::Continuation(Item::Continue(Bytes::from_static(b"World"))),
+            Frame::Continuation(Item::Last(Bytes::from_static(b"!"))),
+            Frame::Binary(Bytes::from_static(b"bin")),
+        ]);
+
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            Message::Ping(Bytes::from_static(b"p"))
+        );
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            Message::Text("Hello, World!".into())
+        );
+        assert_eq!(
+            stream.next().await.unwrap().unwrap(),
+            Message::Binary(Bytes::from_static(b"bin"))
+        );
+        assert!(stream.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn max_size() {
+        let mut stream = aggregated(vec![
+            Frame::Continuation(Item::FirstBinary(Bytes::from_static(b"123"))),
+            Frame::Continuation(Item::Last(Bytes::from_static(b"45"))),
+        ])
+        .max_size(4);
+
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(ProtocolError::Overflow)
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn invalid_text() {
+        let mut stream = aggregated(vec![
+            Frame::Continuation(Item::FirstText(Bytes::from_static(b"\xF0\x9F"))),
+            Frame::Continuation(Item::Last(Bytes::from_static(b"\x98"))),
+        ]);
+
+        assert!(matches!(
+            stream.next().await.unwrap(),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+    }
+}
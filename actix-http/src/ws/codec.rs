@@ -1,3 +1,5 @@
+use std::{cmp, str};
+
 use actix_codec::{Decoder, Encoder};
 use bitflags::bitflags;
 use bytes::{Bytes, BytesMut};
@@ -35,7 +37,8 @@ pub enum Message {
 /// A WebSocket frame.
 #[derive(Debug, PartialEq)]
 pub enum Frame {
-    /// Text frame. Note that the codec does not validate UTF-8 encoding.
+    /// Text frame. Note that the codec does not validate UTF-8 encoding unless
+    /// [`Codec::strict_utf8`] is enabled.
     Text(Bytes),
 
     /// Binary frame.
@@ -68,6 +71,13 @@ pub enum Item {
 pub struct Codec {
     flags: Flags,
     max_size: usize,
+    max_message_size: usize,
+    message_size: usize,
+    fragment_size: Option<usize>,
+    utf8: Option<Utf8Validator>,
+    rsv_allowed: u8,
+    rsv_in: u8,
+    rsv_out: u8,
 }
 
 bitflags! {
@@ -75,6 +85,9 @@ bitflags! {
         const SERVER         = 0b0000_0001;
         const CONTINUATION   = 0b0000_0010;
         const W_CONTINUATION = 0b0000_0100;
+        const NO_MASK        = 0b0000_1000;
+        const STRICT_UTF8    = 0b0001_0000;
+        const STRICT         = 0b0010_0000;
     }
 }
 
@@ -83,6 +96,13 @@ impl Codec {
     pub const fn new() -> Codec {
         Codec {
             max_size: 65_536,
+            max_message_size: usize::MAX,
+            message_size: 0,
+            fragment_size: None,
+            utf8: None,
+            rsv_allowed: 0,
+            rsv_in: 0,
+            rsv_out: 0,
             flags: Flags::SERVER,
         }
     }
@@ -95,6 +115,29 @@ impl Codec {
         self
     }
 
+    /// Set max size of a message assembled from continuation frames.
+    ///
+    /// Decoding a frame that would push the total message payload over this limit results in
+    /// [`ProtocolError::Overflow`]. Control frames are only limited by the max frame size. By
+    /// default message size is not limited.
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+
+    /// Set max payload size of outgoing text and binary frames.
+    ///
+    /// When set, `Message::Text` and `Message::Binary` items with a larger payload are encoded as
+    /// a sequence of continuation frames. By default outgoing messages are not fragmented.
+    ///
+    /// # Panics
+    /// Panics if `size` is zero.
+    pub fn fragment_size(mut self, size: usize) -> Self {
+        assert!(size > 0, "fragment size must be greater than zero");
+        self.fragment_size = Some(size);
+        self
+    }
+
     /// Set decoder to client mode.
     ///
     /// By default decoder works in server mode.
@@ -102,114 +145,141 @@ impl Codec {
         self.flags.remove(Flags::SERVER);
         self
     }
-}
 
-impl Encoder<Message> for Codec {
-    type Error = ProtocolError;
+    /// Set codec to WebSocket over HTTP/2 mode.
+    ///
+    /// In this mode, outgoing frames are never masked and the masking requirements of RFC 6455
+    /// are not enforced for incoming frames. Masked frames are still unmasked when received.
+    pub fn h2_mode(mut self) -> Self {
+        self.flags.insert(Flags::NO_MASK);
+        self
+    }
 
-    fn encode(&mut self, item: Message, dst: &mut BytesMut) -> Result<(), Self::Error> {
-        match item {
-            Message::Text(txt) => Parser::write_message(
-                dst,
-                txt,
-                OpCode::Text,
-                true,
-                !self.flags.contains(Flags::SERVER),
-            ),
-            Message::Binary(bin) => Parser::write_message(
-                dst,
-                bin,
-                OpCode::Binary,
-                true,
-                !self.flags.contains(Flags::SERVER),
-            ),
-            Message::Ping(txt) => Parser::write_message(
-                dst,
-                txt,
-                OpCode::Ping,
-                true,
-                !self.flags.contains(Flags::SERVER),
-            ),
-            Message::Pong(txt) => Parser::write_message(
-                dst,
-                txt,
-                OpCode::Pong,
-                true,
-                !self.flags.contains(Flags::SERVER),
-            ),
-            Message::Close(reason) => {
-                Parser::write_close(dst, reason, !self.flags.contains(Flags::SERVER))
-            }
-            Message::Continuation(cont) => match cont {
-                Item::FirstText(data) => {
-                    if self.flags.contains(Flags::W_CONTINUATION) {
-                        return Err(ProtocolError::ContinuationStarted);
-                    } else {
-                        self.flags.insert(Flags::W_CONTINUATION);
-                        Parser::write_message(
-                            dst,
-                            &data[..],
-                            OpCode::Text,
-                            false,
-                            !self.flags.contains(Flags::SERVER),
-                        )
-                    }
-                }
-                Item::FirstBinary(data) => {
-                    if self.flags.contains(Flags::W_CONTINUATION) {
-                        return Err(ProtocolError::ContinuationStarted);
-                    } else {
-                        self.flags.insert(Flags::W_CONTINUATION);
-                        Parser::write_message(
-                            dst,
-                            &data[..],
-                            OpCode::Binary,
-                            false,
-                            !self.flags.contains(Flags::SERVER),
-                        )
-                    }
-                }
-                Item::Continue(data) => {
-                    if self.flags.contains(Flags::W_CONTINUATION) {
-                        Parser::write_message(
-                            dst,
-                            &data[..],
-                            OpCode::Continue,
-                            false,
-                            !self.flags.contains(Flags::SERVER),
-                        )
-                    } else {
-                        return Err(ProtocolError::ContinuationNotStarted);
-                    }
-                }
-                Item::Last(data) => {
-                    if self.flags.contains(Flags::W_CONTINUATION) {
-                        self.flags.remove(Flags::W_CONTINUATION);
-                        Parser::write_message(
-                            dst,
-                            &data[..],
-                            OpCode::Continue,
-                            true,
-                            !self.flags.contains(Flags::SERVER),
-                        )
-                    } else {
-                        return Err(ProtocolError::ContinuationNotStarted);
-                    }
+    /// Enable strict UTF-8 validation of incoming text frames and close reasons.
+    ///
+    /// In strict mode, decoding a text payload or close reason that is not valid UTF-8 results in
+    /// [`ProtocolError::InvalidUtf8`], which fails the connection with a `1007` close code. Text
+    /// messages split across continuation frames are validated incrementally, so invalid data is
+    /// detected as soon as the offending frame is received.
+    ///
+    /// By default validation is lossy: text frames are passed through unchecked and invalid
+    /// sequences in close reasons are replaced with `U+FFFD`.
+    pub fn strict_utf8(mut self) -> Self {
+        self.flags.insert(Flags::STRICT_UTF8);
+        self
+    }
+
+    /// Enable strict protocol compliance, as required to pass the Autobahn test suite.
+    ///
+    /// In strict mode, frames with reserved bits set, close frames with payloads longer than 125
+    /// bytes and complete data frames received in the middle of a fragmented message are rejected
+    /// with a protocol error, instead of being tolerated. Strict mode also enables
+    /// [strict UTF-8 validation](Self::strict_utf8).
+    ///
+    /// Fragmented control frames are rejected regardless of this setting.
+    pub fn strict(mut self) -> Self {
+        self.flags.insert(Flags::STRICT | Flags::STRICT_UTF8);
+        self
+    }
+
+    /// Allow reserved frame header bits used by negotiated extensions, even in strict mode.
+    pub(crate) fn reserved_bits(mut self, bits: u8) -> Self {
+        self.rsv_allowed = bits;
+        self
+    }
+
+    /// Returns reserved bits of the last decoded frame.
+    pub(crate) fn last_reserved_bits(&self) -> u8 {
+        self.rsv_in
+    }
+
+    /// Encode message, setting the given reserved bits on its first frame.
+    ///
+    /// Reserved bits are not set on close frames.
+    pub(crate) fn encode_with_reserved_bits(
+        &mut self,
+        item: Message,
+        rsv: u8,
+        dst: &mut BytesMut,
+    ) -> Result<(), ProtocolError> {
+        self.rsv_out = rsv;
+        let res = self.encode(item, dst);
+        self.rsv_out = 0;
+        res
+    }
+
+    /// Returns true if outgoing frames should be masked.
+    fn mask_frames(&self) -> bool {
+        !self.flags.intersects(Flags::SERVER | Flags::NO_MASK)
+    }
+
+    /// Write data frame, splitting it into continuation frames if it exceeds fragment size.
+    fn write_data(&self, dst: &mut BytesMut, data: &[u8], op: OpCode) {
+        let mask = self.mask_frames();
+
+        match self.fragment_size {
+            Some(size) if data.len() > size => {
+                let mut op = op;
+                let mut rsv = self.rsv_out;
+                let mut chunks = data.chunks(size).peekable();
+
+                while let Some(chunk) = chunks.next() {
+                    let fin = chunks.peek().is_none();
+                    Parser::write_frame(dst, chunk, op, fin, rsv, mask);
+                    op = OpCode::Continue;
+                    rsv = 0;
                 }
-            },
-            Message::Nop => {}
+            }
+            _ => Parser::write_frame(dst, data, op, true, self.rsv_out, mask),
         }
-        Ok(())
     }
-}
 
-impl Decoder for Codec {
-    type Item = Frame;
-    type Error = ProtocolError;
+    /// Decode next frame. Text payloads are validated separately by `validate_utf8`.
+    fn decode_frame(
+        &mut self,
+        src: &mut BytesMut,
+    ) -> Result<Option<Frame>, ProtocolError> {
+        let server = self.flags.contains(Flags::SERVER);
+        let check_mask = !self.flags.contains(Flags::NO_MASK);
+
+        let strict = self.flags.contains(Flags::STRICT);
+
+        match Parser::parse_frame_rsv(
+            src,
+            server,
+            check_mask,
+            strict,
+            self.rsv_allowed,
+            self.max_size,
+        ) {
+            Ok(Some((finished, rsv, opcode, payload))) => {
+                self.rsv_in = rsv;
+
+                let len = payload.as_ref().map_or(0, |pl| pl.len());
+
+                // track payload size of the message being assembled; control frames are not
+                // part of it and are limited by the frame size only
+                let is_data = match opcode {
+                    OpCode::Continue => {
+                        self.message_size = self.message_size.saturating_add(len);
+                        true
+                    }
+                    OpCode::Text | OpCode::Binary => {
+                        if !self.flags.contains(Flags::CONTINUATION) {
+                            self.message_size = len;
+                        }
+                        true
+                    }
+                    _ => false,
+                };
+
+                if is_data
+                    && (len > self.max_message_size
+                        || self.message_size > self.max_message_size)
+                {
+                    return Err(ProtocolError::Overflow);
+                }
 
-    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
-        match Parser::parse(src, self.flags.contains(Flags::SERVER), self.max_size) {
-            Ok(Some((finished, opcode, payload))) => {
                 // continuation is not supported
                 if !finished {
                     return match opcode {
@@ -255,6 +325,13 @@ impl Decoder for Codec {
                     };
                 }
 
+                // a complete data frame must not interrupt a fragmented message
+                if matches!(opcode, OpCode::Text | OpCode::Binary)
+                    && self.flags.contains(Flags::STRICT | Flags::CONTINUATION)
+                {
+                    return Err(ProtocolError::ContinuationStarted);
+                }
+
                 match opcode {
                     OpCode::Continue => {
                         if self.flags.contains(Flags::CONTINUATION) {
@@ -269,8 +346,25 @@ impl Decoder for Codec {
                     OpCode::Bad => Err(ProtocolError::BadOpCode),
                     OpCode::Close => {
                         if let Some(ref pl) = payload {
+                            if pl.len() == 1 {
+                                return Err(ProtocolError::InvalidLength(1));
+                            }
+
+                            if self.flags.contains(Flags::STRICT_UTF8)
+                                && pl.len() > 2
+                                && str::from_utf8(&pl[2..]).is_err()
+                            {
+                                return Err(ProtocolError::InvalidUtf8);
+                            }
+
                             let close_reason = Parser::parse_close_payload(pl);
-                            Ok(Some(Frame::Close(close_reason)))
+
+                            match close_reason {
+                                Some(ref reason) if !reason.code.is_allowed() => Err(
+                                    ProtocolError::InvalidCloseCode(reason.code.into()),
+                                ),
+                                _ => Ok(Some(Frame::Close(close_reason))),
+                            }
                         } else {
                             Ok(Some(Frame::Close(None)))
                         }
@@ -293,4 +387,459 @@ impl Decoder for Codec {
             Err(e) => Err(e),
         }
     }
+
+    /// Validate UTF-8 encoding of text frame payloads, including text messages that are split
+    /// across continuation frames.
+    fn validate_utf8(&mut self, frame: &Frame) -> Result<(), ProtocolError> {
+        match frame {
+            Frame::Text(data) => str::from_utf8(data)
+                .map(|_| ())
+                .map_err(|_| ProtocolError::InvalidUtf8),
+            Frame::Continuation(Item::FirstText(data)) => {
+                let mut validator = Utf8Validator::default();
+                validator.feed(data)?;
+                self.utf8 = Some(validator);
+                Ok(())
+            }
+            Frame::Continuation(Item::Continue(data)) => match self.utf8 {
+                Some(ref mut validator) => validator.feed(data),
+                None => Ok(()),
+            },
+            Frame::Continuation(Item::Last(data)) => match self.utf8.take() {
+                Some(mut validator) => {
+                    validator.feed(data)?;
+                    validator.finish()
+                }
+                None => Ok(()),
+            },
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Encoder<Message> for Codec {
+    type Error = ProtocolError;
+
+    fn encode(&mut self, item: Message, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        match item {
+            Message::Text(txt) => self.write_data(dst, txt.as_bytes(), OpCode::Text),
+            Message::Binary(bin) => self.write_data(dst, &bin, OpCode::Binary),
+            Message::Ping(txt) => Parser::write_frame(
+                dst,
+                txt,
+                OpCode::Ping,
+                true,
+                self.rsv_out,
+                self.mask_frames(),
+            ),
+            Message::Pong(txt) => Parser::write_frame(
+                dst,
+                txt,
+                OpCode::Pong,
+                true,
+                self.rsv_out,
+                self.mask_frames(),
+            ),
+            Message::Close(reason) => {
+                if let Some(ref reason) = reason {
+                    if !reason.code.is_allowed() {
+                        return Err(ProtocolError::InvalidCloseCode(reason.code.into()));
+                    }
+
+                    // close frame payload is limited to 125 bytes, including the 2 byte code
+                    let len = reason.description.as_ref().map_or(0, |desc| desc.len());
+                    if len > 123 {
+                        return Err(ProtocolError::InvalidLength(len + 2));
+                    }
+                }
+
+                Parser::write_close(dst, reason, self.mask_frames())
+            }
+            Message::Continuation(cont) => match cont {
+                Item::FirstText(data) => {
+                    if self.flags.contains(Flags::W_CONTINUATION) {
+                        return Err(ProtocolError::ContinuationStarted);
+                    } else {
+                        self.flags.insert(Flags::W_CONTINUATION);
+                        Parser::write_frame(
+                            dst,
+                            &data[..],
+                            OpCode::Text,
+                            false,
+                            self.rsv_out,
+                            self.mask_frames(),
+                        )
+                    }
+                }
+                Item::FirstBinary(data) => {
+                    if self.flags.contains(Flags::W_CONTINUATION) {
+                        return Err(ProtocolError::ContinuationStarted);
+                    } else {
+                        self.flags.insert(Flags::W_CONTINUATION);
+                        Parser::write_frame(
+                            dst,
+                            &data[..],
+                            OpCode::Binary,
+                            false,
+                            self.rsv_out,
+                            self.mask_frames(),
+                        )
+                    }
+                }
+                Item::Continue(data) => {
+                    if self.flags.contains(Flags::W_CONTINUATION) {
+                        Parser::write_frame(
+                            dst,
+                            &data[..],
+                            OpCode::Continue,
+                            false,
+                            self.rsv_out,
+                            self.mask_frames(),
+                        )
+                    } else {
+                        return Err(ProtocolError::ContinuationNotStarted);
+                    }
+                }
+                Item::Last(data) => {
+                    if self.flags.contains(Flags::W_CONTINUATION) {
+                        self.flags.remove(Flags::W_CONTINUATION);
+                        Parser::write_frame(
+                            dst,
+                            &data[..],
+                            OpCode::Continue,
+                            true,
+                            self.rsv_out,
+                            self.mask_frames(),
+                        )
+                    } else {
+                        return Err(ProtocolError::ContinuationNotStarted);
+                    }
+                }
+            },
+            Message::Nop => {}
+        }
+        Ok(())
+    }
+}
+
+impl Decoder for Codec {
+    type Item = Frame;
+    type Error = ProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let frame = self.decode_frame(src)?;
+
+        if self.flags.contains(Flags::STRICT_UTF8) {
+            if let Some(ref frame) = frame {
+                self.validate_utf8(frame)?;
+            }
+        }
+
+        Ok(frame)
+    }
+}
+
+/// Incremental UTF-8 validator for text messages split across continuation frames.
+#[derive(Debug, Default, Copy, Clone)]
+struct Utf8Validator {
+    /// Bytes of a code point that is split across frames.
+    partial: [u8; 4],
+    len: usize,
+}
+
+impl Utf8Validator {
+    fn feed(&mut self, mut data: &[u8]) -> Result<(), ProtocolError> {
+        if self.len > 0 {
+            let width = match self.partial[0] {
+                0xC0..=0xDF => 2,
+                0xE0..=0xEF => 3,
+                _ => 4,
+            };
+
+            let n = cmp::min(width - self.len, data.len());
+            self.partial[self.len..self.len + n].copy_from_slice(&data[..n]);
+            self.len += n;
+            data = &data[n..];
+
+            match str::from_utf8(&self.partial[..self.len]) {
+                Ok(_) => self.len = 0,
+                // code point is still incomplete
+                Err(err) if err.error_len().is_none() => return Ok(()),
+                Err(_) => return Err(ProtocolError::InvalidUtf8),
+            }
+        }
+
+        match str::from_utf8(data) {
+            Ok(_) => Ok(()),
+            Err(err) if err.error_len().is_none() => {
+                let rest = &data[err.valid_up_to()..];
+                self.partial[..rest.len()].copy_from_slice(rest);
+                self.len = rest.len();
+                Ok(())
+            }
+            Err(_) => Err(ProtocolError::InvalidUtf8),
+        }
+    }
+
+    fn finish(&self) -> Result<(), ProtocolError> {
+        if self.len == 0 {
+            Ok(())
+        } else {
+            Err(ProtocolError::InvalidUtf8)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ws::CloseCode;
+
+    fn frame(dst: &mut BytesMut, data: &[u8], op: OpCode, fin: bool) {
+        Parser::write_message(dst, data, op, fin, true);
+    }
+
+    #[test]
+    fn test_max_message_size() {
+        let mut codec = Codec::new().max_message_size(8);
+        let mut buf = BytesMut::new();
+
+        frame(&mut buf, b"1234", OpCode::Text, false);
+        frame(&mut buf, b"1234", OpCode::Continue, false);
+        frame(&mut buf, b"1", OpCode::Continue, true);
+
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Ok(Some(Frame::Continuation(Item::FirstText(_))))
+        ));
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Ok(Some(Frame::Continuation(Item::Continue(_))))
+        ));
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_max_message_size_single_frame() {
+        let mut codec = Codec::new().max_message_size(4);
+        let mut buf = BytesMut::new();
+
+        frame(&mut buf, b"1234", OpCode::Binary, true);
+        frame(&mut buf, b"12345", OpCode::Binary, true);
+
+        assert!(matches!(codec.decode(&mut buf), Ok(Some(Frame::Binary(_)))));
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::Overflow)
+        ));
+    }
+
+    #[test]
+    fn test_max_message_size_control_frames() {
+        let mut codec = Codec::new().max_message_size(4);
+        let mut buf = BytesMut::new();
+
+        frame(&mut buf, b"12", OpCode::Text, false);
+        frame(&mut buf, b"123456", OpCode::Ping, true);
+        frame(&mut buf, b"123456", OpCode::Pong, true);
+        frame(&mut buf, b"12", OpCode::Continue, true);
+
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Ok(Some(Frame::Continuation(Item::FirstText(_))))
+        ));
+        assert!(matches!(codec.decode(&mut buf), Ok(Some(Frame::Ping(_)))));
+        assert!(matches!(codec.decode(&mut buf), Ok(Some(Frame::Pong(_)))));
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Ok(Some(Frame::Continuation(Item::Last(_))))
+        ));
+    }
+
+    #[test]
+    fn test_fragment_size() {
+        let mut codec = Codec::new().client_mode().fragment_size(4);
+        let mut buf = BytesMut::new();
+
+        codec
+            .encode(Message::Text("1234567890".into()), &mut buf)
+            .unwrap();
+        codec
+            .encode(Message::Binary(Bytes::from_static(b"1234")), &mut buf)
+            .unwrap();
+
+        let mut codec = Codec::new();
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Continuation(Item::FirstText(Bytes::from_static(
+                b"1234"
+            ))))
+        );
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Continuation(Item::Continue(Bytes::from_static(
+                b"5678"
+            ))))
+        );
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Continuation(Item::Last(Bytes::from_static(b"90"))))
+        );
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Binary(Bytes::from_static(b"1234")))
+        );
+    }
+
+    #[test]
+    fn test_close_code_validation() {
+        let mut codec = Codec::new();
+
+        let mut buf = BytesMut::new();
+        frame(&mut buf, &1000u16.to_be_bytes(), OpCode::Close, true);
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Close(Some(CloseCode::Normal.into())))
+        );
+
+        let mut buf = BytesMut::new();
+        frame(&mut buf, &1006u16.to_be_bytes(), OpCode::Close, true);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidCloseCode(1006))
+        ));
+
+        let mut buf = BytesMut::new();
+        frame(&mut buf, &[0x03], OpCode::Close, true);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidLength(1))
+        ));
+
+        let mut buf = BytesMut::new();
+        assert!(matches!(
+            codec.encode(Message::Close(Some(CloseCode::Abnormal.into())), &mut buf),
+            Err(ProtocolError::InvalidCloseCode(1006))
+        ));
+
+        let reason = (CloseCode::Normal, "a".repeat(124)).into();
+        assert!(matches!(
+            codec.encode(Message::Close(Some(reason)), &mut buf),
+            Err(ProtocolError::InvalidLength(126))
+        ));
+
+        let reason = (CloseCode::Normal, "a".repeat(123)).into();
+        codec
+            .encode(Message::Close(Some(reason)), &mut buf)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_strict_utf8() {
+        let mut buf = BytesMut::new();
+        frame(&mut buf, b"\xF0\x9F\x98", OpCode::Text, true);
+
+        let mut lossy = Codec::new();
+        assert!(matches!(
+            lossy.decode(&mut buf.clone()),
+            Ok(Some(Frame::Text(_)))
+        ));
+
+        let mut codec = Codec::new().strict_utf8();
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+
+        let mut payload = 1000u16.to_be_bytes().to_vec();
+        payload.extend(b"\xFF");
+        let mut buf = BytesMut::new();
+        frame(&mut buf, &payload, OpCode::Close, true);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+    }
+
+    #[test]
+    fn test_strict_utf8_continuation() {
+        let mut codec = Codec::new().strict_utf8();
+        let mut buf = BytesMut::new();
+
+        // code point split over three frames
+        frame(&mut buf, b"a\xF0", OpCode::Text, false);
+        frame(&mut buf, b"\x9F\x98", OpCode::Continue, false);
+        frame(&mut buf, b"\x80b", OpCode::Continue, true);
+
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Continuation(Item::Last(Bytes::from_static(
+                b"\x80b"
+            ))))
+        );
+
+        // invalid continuation byte is rejected before message is complete
+        frame(&mut buf, b"a\xF0", OpCode::Text, false);
+        frame(&mut buf, b"b", OpCode::Continue, false);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+
+        // incomplete code point at end of message
+        let mut codec = Codec::new().strict_utf8();
+        frame(&mut buf, b"a", OpCode::Text, false);
+        frame(&mut buf, b"\xE2\x82", OpCode::Continue, true);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::InvalidUtf8)
+        ));
+    }
+
+    #[test]
+    fn test_strict() {
+        let mut buf = BytesMut::new();
+        frame(&mut buf, b"text", OpCode::Text, true);
+        buf[0] |= 0b0100_0000;
+
+        let mut codec = Codec::new();
+        assert!(codec.decode(&mut buf.clone()).unwrap().is_some());
+
+        let mut codec = Codec::new().strict();
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::ReservedBits(0b100))
+        ));
+
+        // fragmented control frame
+        let mut buf = BytesMut::new();
+        frame(&mut buf, b"ping", OpCode::Ping, false);
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::ContinuationFragment(OpCode::Ping))
+        ));
+
+        // data frame interleaved with fragmented message
+        let mut codec = Codec::new().strict();
+        let mut buf = BytesMut::new();
+        frame(&mut buf, b"1", OpCode::Text, false);
+        frame(&mut buf, b"p", OpCode::Ping, true);
+        frame(&mut buf, b"2", OpCode::Text, true);
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+        assert_eq!(
+            codec.decode(&mut buf).unwrap(),
+            Some(Frame::Ping(Bytes::from_static(b"p")))
+        );
+        assert!(matches!(
+            codec.decode(&mut buf),
+            Err(ProtocolError::ContinuationStarted)
+        ));
+    }
 }
@@ -4,6 +4,7 @@ use std::task::{Context, Poll};
 
 use actix_codec::{AsyncRead, AsyncWrite, Framed};
 use actix_service::{IntoService, Service};
+use futures_core::ready;
 
 use super::{Codec, Frame, Message};
 
@@ -15,6 +16,7 @@ where
 {
     #[pin]
     inner: inner::Dispatcher<S, T, Codec, Message>,
+    closing: Option<inner::DispatcherError<S::Error, Codec, Message>>,
 }
 
 impl<S, T> Dispatcher<S, T>
@@ -27,12 +29,14 @@ where
     pub fn new<F: IntoService<S, Frame>>(io: T, service: F) -> Self {
         Dispatcher {
             inner: inner::Dispatcher::new(Framed::new(io, Codec::new()), service),
+            closing: None,
         }
     }
 
     pub fn with<F: IntoService<S, Frame>>(framed: Framed<T, Codec>, service: F) -> Self {
         Dispatcher {
             inner: inner::Dispatcher::new(framed, service),
+            closing: None,
         }
     }
 }
@@ -47,7 +51,34 @@ where
     type Output = Result<(), inner::DispatcherError<S::Error, Codec, Message>>;
 
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
-        self.project().inner.poll(cx)
+        let mut this = self.project();
+
+        loop {
+            // flush close frame before reporting the error that caused it
+            if this.closing.is_some() {
+                let framed = this.inner.as_mut().framed_pin_mut();
+
+                if !framed.is_write_buf_empty() && framed.flush(cx).is_pending() {
+                    return Poll::Pending;
+                }
+
+                return Poll::Ready(Err(this.closing.take().unwrap()));
+            }
+
+            return match ready!(this.inner.as_mut().poll(cx)) {
+                Err(inner::DispatcherError::Decoder(err)) => match err.close_code() {
+                    Some(code) => {
+                        let msg = Message::Close(Some(code.into()));
+                        let _ = this.inner.as_mut().framed_pin_mut().write(msg);
+
+                        *this.closing = Some(inner::DispatcherError::Decoder(err));
+                        continue;
+                    }
+                    None => Poll::Ready(Err(inner::DispatcherError::Decoder(err))),
+                },
+                res => Poll::Ready(res),
+            };
+        }
     }
 }
 
@@ -282,6 +313,11 @@ mod inner {
             &mut self.framed
         }
 
+        /// Get pinned mutable reference to a framed instance wrapped by `Dispatcher` instance.
+        pub fn framed_pin_mut(self: Pin<&mut Self>) -> Pin<&mut Framed<T, U>> {
+            self.project().framed
+        }
+
         /// Read from framed object.
         fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> bool
         where
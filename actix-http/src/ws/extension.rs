@@ -0,0 +1,541 @@
+use std::fmt;
+
+use actix_codec::{Decoder, Encoder};
+use bytes::BytesMut;
+use http::header;
+
+use super::{Codec, Frame, Message, ProtocolError};
+use crate::{
+    header::{split_comma_delimited, split_parameters},
+    message::RequestHead,
+};
+
+/// Element of a `Sec-WebSocket-Extensions` header: an extension name and its parameters.
+///
+/// # Examples
+/// ```
+/// use actix_http::ws::ExtensionParams;
+///
+/// let params = ExtensionParams::new("permessage-deflate")
+///     .param("client_max_window_bits", "10")
+///     .flag("server_no_context_takeover");
+///
+/// assert_eq!(params.get("client_max_window_bits"), Some("10"));
+/// assert!(params.contains("server_no_context_takeover"));
+/// assert_eq!(
+///     params.to_string(),
+///     "permessage-deflate; client_max_window_bits=10; server_no_context_takeover"
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExtensionParams {
+    name: String,
+    params: Vec<(String, Option<String>)>,
+}
+
+impl ExtensionParams {
+    /// Create element for extension `name` without parameters.
+    pub fn new(name: impl Into<String>) -> Self {
+        ExtensionParams {
+            name: name.into(),
+            params: Vec::new(),
+        }
+    }
+
+    /// Parse all elements of a `Sec-WebSocket-Extensions` header value.
+    ///
+    /// Quoted parameter values are unquoted. Parameters with an empty value are treated as
+    /// parameters without value.
+    pub fn parse(val: &str) -> Vec<ExtensionParams> {
+        split_comma_delimited(val)
+            .map(|elem| {
+                let (name, params) = split_parameters(elem);
+
+                ExtensionParams {
+                    name: name.to_owned(),
+                    params: params
+                        .map(|(name, val)| {
+                            let val = if val.is_empty() {
+                                None
+                            } else {
+                                Some(val.into_owned())
+                            };
+                            (name.to_owned(), val)
+                        })
+                        .collect(),
+                }
+            })
+            .collect()
+    }
+
+    /// Add parameter with a value.
+    pub fn param(mut self, name: impl Into<String>, val: impl Into<String>) -> Self {
+        self.params.push((name.into(), Some(val.into())));
+        self
+    }
+
+    /// Add parameter without a value.
+    pub fn flag(mut self, name: impl Into<String>) -> Self {
+        self.params.push((name.into(), None));
+        self
+    }
+
+    /// Returns extension name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Returns true if a parameter with the given name is present.
+    pub fn contains(&self, name: &str) -> bool {
+        self.params
+            .iter()
+            .any(|(n, _)| n.eq_ignore_ascii_case(name))
+    }
+
+    /// Returns value of first parameter with the given name.
+    ///
+    /// Returns `None` if the parameter is not present or has no value.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .and_then(|(_, val)| val.as_deref())
+    }
+
+    /// Returns iterator over all parameters, in order.
+    pub fn params(&self) -> impl Iterator<Item = (&str, Option<&str>)> {
+        self.params
+            .iter()
+            .map(|(name, val)| (name.as_str(), val.as_deref()))
+    }
+}
+
+impl fmt::Display for ExtensionParams {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)?;
+
+        for (name, val) in &self.params {
+            match val {
+                Some(val) if !val.is_empty() && val.bytes().all(is_tchar) => {
+                    write!(f, "; {}={}", name, val)?
+                }
+                Some(val) => {
+                    // values that are not tokens must be sent as quoted strings
+                    write!(f, "; {}=\"", name)?;
+                    for ch in val.chars() {
+                        if ch == '"' || ch == '\\' {
+                            f.write_str("\\")?;
+                        }
+                        write!(f, "{}", ch)?;
+                    }
+                    f.write_str("\"")?;
+                }
+                None => write!(f, "; {}", name)?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Returns true for characters allowed in an RFC 7230 token.
+fn is_tchar(b: u8) -> bool {
+    b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b)
+}
+
+/// WebSocket extension negotiated through the `Sec-WebSocket-Extensions` header.
+///
+/// Register extensions with [`handshake_with_extensions`](super::handshake_with_extensions).
+/// For each client offer naming an extension, [`negotiate`](Self::negotiate) is called until
+/// one of the offers is accepted.
+pub trait Extension {
+    /// Extension name, as used in the `Sec-WebSocket-Extensions` header.
+    fn name(&self) -> &str;
+
+    /// Reserved frame header bits used by the extension, e.g. `0b100` for RSV1.
+    ///
+    /// Bits are numbered like in [`ProtocolError::ReservedBits`]. Incoming frames with these bits
+    /// set are accepted once the extension is negotiated, even by a strict codec. Offers are
+    /// declined if the bits are already used by another accepted extension. Defaults to none.
+    fn reserved_bits(&self) -> u8 {
+        0
+    }
+
+    /// Negotiate extension from a client offer.
+    ///
+    /// Returns the parameters that are sent back to the client and the transform applied to
+    /// frames of the connection when the offer is accepted, or `None` to decline the offer.
+    fn negotiate(
+        &self,
+        offer: &ExtensionParams,
+    ) -> Option<(ExtensionParams, Box<dyn FrameTransform>)>;
+}
+
+/// Frame transform of a negotiated extension.
+///
+/// Transforms of multiple extensions are applied in negotiation order to outgoing messages and
+/// in reverse order to incoming frames.
+///
+/// Reserved frame header bits are passed as `rsv`, numbered like in
+/// [`ProtocolError::ReservedBits`]. Transforms should only read and set the bits declared by
+/// their [`Extension::reserved_bits`].
+pub trait FrameTransform {
+    /// Transform outgoing message before it is encoded.
+    ///
+    /// Bits set in `rsv` are sent on the first frame of the message. They are not set on close
+    /// frames.
+    fn encode(&mut self, msg: Message, rsv: &mut u8) -> Result<Message, ProtocolError> {
+        let _ = rsv;
+        Ok(msg)
+    }
+
+    /// Transform incoming frame after it is decoded.
+    ///
+    /// `rsv` holds the reserved bits of the frame header.
+    fn decode(&mut self, frame: Frame, rsv: u8) -> Result<Frame, ProtocolError> {
+        let _ = rsv;
+        Ok(frame)
+    }
+}
+
+/// Extensions accepted during a WebSocket handshake.
+#[derive(Default)]
+pub struct Negotiated {
+    accepted: Vec<ExtensionParams>,
+    transforms: Vec<Box<dyn FrameTransform>>,
+    rsv: u8,
+}
+
+impl Negotiated {
+    /// Negotiate extensions offered by the client request.
+    ///
+    /// Offers are considered in the order they are listed by the client. At most one offer is
+    /// accepted per extension; offers for unregistered extensions are ignored.
+    pub fn from_request(req: &RequestHead, extensions: &[&dyn Extension]) -> Self {
+        let mut negotiated = Negotiated::default();
+        let mut used = vec![false; extensions.len()];
+
+        let offers = req
+            .headers()
+            .get_all(header::SEC_WEBSOCKET_EXTENSIONS)
+            .filter_map(|hdr| hdr.to_str().ok())
+            .flat_map(ExtensionParams::parse);
+
+        for offer in offers {
+            let idx = extensions
+                .iter()
+                .position(|ext| ext.name().eq_ignore_ascii_case(offer.name()));
+
+            if let Some(idx) = idx {
+                let rsv = extensions[idx].reserved_bits();
+
+                if used[idx] || negotiated.rsv & rsv != 0 {
+                    continue;
+                }
+
+                if let Some((params, transform)) = extensions[idx].negotiate(&offer) {
+                    used[idx] = true;
+                    negotiated.rsv |= rsv;
+                    negotiated.accepted.push(params);
+                    negotiated.transforms.push(transform);
+                }
+            }
+        }
+
+        negotiated
+    }
+
+    /// Returns parameters of accepted extensions, in negotiation order.
+    pub fn accepted(&self) -> &[ExtensionParams] {
+        &self.accepted
+    }
+
+    /// Returns true if no extension was accepted.
+    pub fn is_empty(&self) -> bool {
+        self.accepted.is_empty()
+    }
+
+    /// Returns `Sec-WebSocket-Extensions` header value for the handshake response.
+    ///
+    /// Returns `None` if no extension was accepted.
+    pub fn header_value(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let val = self
+            .accepted
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        Some(val)
+    }
+
+    /// Returns reserved frame header bits used by accepted extensions.
+    pub fn reserved_bits(&self) -> u8 {
+        self.rsv
+    }
+
+    /// Create codec that applies the transforms of accepted extensions on top of `codec`.
+    ///
+    /// The codec accepts frames with the reserved bits of accepted extensions set.
+    pub fn codec(self, codec: Codec) -> ExtensionCodec {
+        ExtensionCodec {
+            codec: codec.reserved_bits(self.rsv),
+            transforms: self.transforms,
+        }
+    }
+}
+
+impl fmt::Debug for Negotiated {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Negotiated")
+            .field("accepted", &self.accepted)
+            .field("rsv", &self.rsv)
+            .finish()
+    }
+}
+
+/// WebSocket codec that applies the frame transforms of negotiated extensions.
+///
+/// Created with [`Negotiated::codec`].
+pub struct ExtensionCodec {
+    codec: Codec,
+    transforms: Vec<Box<dyn FrameTransform>>,
+}
+
+impl ExtensionCodec {
+    /// Returns a reference to the wrapped codec.
+    pub fn get_ref(&self) -> &Codec {
+        &self.codec
+    }
+}
+
+impl Encoder<Message> for ExtensionCodec {
+    type Error = ProtocolError;
+
+    fn encode(
+        &mut self,
+        mut item: Message,
+        dst: &mut BytesMut,
+    ) -> Result<(), Self::Error> {
+        let mut rsv = 0;
+
+        for transform in self.transforms.iter_mut() {
+            item = transform.encode(item, &mut rsv)?;
+        }
+
+        self.codec.encode_with_reserved_bits(item, rsv, dst)
+    }
+}
+
+impl Decoder for ExtensionCodec {
+    type Item = Frame;
+    type Error = ProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        let mut frame = match self.codec.decode(src)? {
+            Some(frame) => frame,
+            None => return Ok(None),
+        };
+
+        let rsv = self.codec.last_reserved_bits();
+
+        for transform in self.transforms.iter_mut().rev() {
+            frame = transform.decode(frame, rsv)?;
+        }
+
+        Ok(Some(frame))
+    }
+}
+
+impl fmt::Debug for ExtensionCodec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExtensionCodec")
+            .field("codec", &self.codec)
+            .field("transforms", &self.transforms.len())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bytes::Bytes;
+
+    use super::*;
+    use crate::test::TestRequest;
+
+    /// Extension that reverses binary payloads, marking them with RSV1.
+    struct Reverse;
+
+    struct ReverseTransform;
+
+    impl Extension for Reverse {
+        fn name(&self) -> &str {
+            "x-reverse"
+        }
+
+        fn reserved_bits(&self) -> u8 {
+            0b100
+        }
+
+        fn negotiate(
+            &self,
+            offer: &ExtensionParams,
+        ) -> Option<(ExtensionParams, Box<dyn FrameTransform>)> {
+            if offer.contains("unsupported") {
+                return None;
+            }
+
+            let mut params = ExtensionParams::new("x-reverse");
+            if let Some(level) = offer.get("level") {
+                params = params.param("level", level);
+            }
+
+            Some((params, Box::new(ReverseTransform)))
+        }
+    }
+
+    impl FrameTransform for ReverseTransform {
+        fn encode(
+            &mut self,
+            msg: Message,
+            rsv: &mut u8,
+        ) -> Result<Message, ProtocolError> {
+            Ok(match msg {
+                Message::Binary(bin) => {
+                    *rsv |= 0b100;
+                    Message::Binary(bin.iter().rev().copied().collect::<Vec<_>>().into())
+                }
+                msg => msg,
+            })
+        }
+
+        fn decode(&mut self, frame: Frame, rsv: u8) -> Result<Frame, ProtocolError> {
+            Ok(match frame {
+                Frame::Binary(bin) if rsv & 0b100 != 0 => {
+                    Frame::Binary(bin.iter().rev().copied().collect::<Vec<_>>().into())
+                }
+                frame => frame,
+            })
+        }
+    }
+
+    /// Extension that conflicts with `Reverse` over RSV1.
+    struct Conflicting;
+
+    impl Extension for Conflicting {
+        fn name(&self) -> &str {
+            "x-conflicting"
+        }
+
+        fn reserved_bits(&self) -> u8 {
+            0b100
+        }
+
+        fn negotiate(
+            &self,
+            _: &ExtensionParams,
+        ) -> Option<(ExtensionParams, Box<dyn FrameTransform>)> {
+            Some((
+                ExtensionParams::new("x-conflicting"),
+                Box::new(ReverseTransform),
+            ))
+        }
+    }
+
+    #[test]
+    fn parse_params() {
+        let params = ExtensionParams::parse(
+            r#"permessage-deflate; client_max_window_bits, x-foo; a="1,2" ;b=3"#,
+        );
+
+        assert_eq!(params.len(), 2);
+        assert_eq!(params[0].name(), "permessage-deflate");
+        assert!(params[0].contains("client_max_window_bits"));
+        assert_eq!(params[0].get("client_max_window_bits"), None);
+        assert_eq!(params[1].name(), "x-foo");
+        assert_eq!(
+            params[1].params().collect::<Vec<_>>(),
+            vec![("a", Some("1,2")), ("b", Some("3"))]
+        );
+    }
+
+    #[test]
+    fn display_quotes_values() {
+        let params = ExtensionParams::new("x-foo")
+            .param("a", "1,2")
+            .param("b", r#"say "hi"; ok"#)
+            .param("c", "3");
+
+        let val = params.to_string();
+        assert_eq!(val, r#"x-foo; a="1,2"; b="say \"hi\"; ok"; c=3"#);
+        assert_eq!(ExtensionParams::parse(&val), vec![params]);
+    }
+
+    #[test]
+    fn negotiate() {
+        let req = TestRequest::default()
+            .insert_header((
+                header::SEC_WEBSOCKET_EXTENSIONS,
+                "x-unknown, x-reverse; unsupported, x-reverse; level=2, x-reverse",
+            ))
+            .finish();
+
+        let negotiated = Negotiated::from_request(req.head(), &[&Reverse]);
+        assert_eq!(negotiated.accepted().len(), 1);
+        assert_eq!(negotiated.header_value().unwrap(), "x-reverse; level=2");
+
+        // extensions using the same reserved bits are not both accepted
+        let req = TestRequest::default()
+            .insert_header((
+                header::SEC_WEBSOCKET_EXTENSIONS,
+                "x-reverse, x-conflicting",
+            ))
+            .finish();
+        let negotiated = Negotiated::from_request(req.head(), &[&Reverse, &Conflicting]);
+        assert_eq!(negotiated.header_value().unwrap(), "x-reverse");
+        assert_eq!(negotiated.reserved_bits(), 0b100);
+
+        let req = TestRequest::default().finish();
+        let negotiated = Negotiated::from_request(req.head(), &[&Reverse]);
+        assert!(negotiated.is_empty());
+        assert!(negotiated.header_value().is_none());
+    }
+
+    #[test]
+    fn transform_frames() {
+        let req = TestRequest::default()
+            .insert_header((header::SEC_WEBSOCKET_EXTENSIONS, "x-reverse"))
+            .finish();
+
+        let mut server = Negotiated::from_request(req.head(), &[&Reverse])
+            .codec(Codec::new().strict());
+        let mut client = Negotiated::from_request(req.head(), &[&Reverse])
+            .codec(Codec::new().client_mode());
+
+        let mut buf = BytesMut::new();
+        client
+            .encode(Message::Binary(Bytes::from_static(b"abc")), &mut buf)
+            .unwrap();
+
+        // payload on the wire is transformed and marked with RSV1
+        assert_eq!(buf[0] & 0x70, 0b0100_0000);
+        let mut raw = buf.clone();
+        assert_eq!(
+            Codec::new().decode(&mut raw).unwrap().unwrap(),
+            Frame::Binary(Bytes::from_static(b"cba"))
+        );
+        assert!(matches!(
+            Codec::new().strict().decode(&mut buf.clone()),
+            Err(ProtocolError::ReservedBits(0b100))
+        ));
+
+        assert_eq!(
+            server.decode(&mut buf).unwrap().unwrap(),
+            Frame::Binary(Bytes::from_static(b"abc"))
+        );
+    }
+}
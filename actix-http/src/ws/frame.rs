@@ -15,8 +15,11 @@ impl Parser {
     fn parse_metadata(
         src: &[u8],
         server: bool,
+        check_mask: bool,
+        strict: bool,
+        rsv_allowed: u8,
         max_size: usize,
-    ) -> Result<Option<(usize, bool, OpCode, usize, Option<[u8; 4]>)>, ProtocolError>
+    ) -> Result<Option<(usize, bool, u8, OpCode, usize, Option<[u8; 4]>)>, ProtocolError>
     {
         let chunk_len = src.len();
 
@@ -29,11 +32,17 @@ impl Parser {
         let second = src[1];
         let finished = first & 0x80 != 0;
 
+        // reserved bits must not be set unless an extension uses them
+        let rsv = (first & 0x70) >> 4;
+        if strict && rsv & !rsv_allowed != 0 {
+            return Err(ProtocolError::ReservedBits(rsv & !rsv_allowed));
+        }
+
         // check masking
         let masked = second & 0x80 != 0;
-        if !masked && server {
+        if check_mask && !masked && server {
             return Err(ProtocolError::UnmaskedFrame);
-        } else if masked && !server {
+        } else if check_mask && masked && !server {
             return Err(ProtocolError::MaskedFrame);
         }
 
@@ -73,7 +82,7 @@ impl Parser {
             return Err(ProtocolError::Overflow);
         }
 
-        let mask = if server {
+        let mask = if masked {
             if chunk_len < idx + 4 {
                 return Ok(None);
             }
@@ -87,7 +96,7 @@ impl Parser {
             None
         };
 
-        Ok(Some((idx, finished, opcode, length, mask)))
+        Ok(Some((idx, finished, rsv, opcode, length, mask)))
     }
 
     /// Parse the input stream into a frame.
@@ -96,12 +105,50 @@ impl Parser {
         server: bool,
         max_size: usize,
     ) -> Result<Option<(bool, OpCode, Option<BytesMut>)>, ProtocolError> {
+        Parser::parse_frame(src, server, true, false, max_size)
+    }
+
+    /// Parse the input stream into a frame, optionally skipping frame masking checks.
+    ///
+    /// In strict mode, frames with reserved bits set and close frames with payloads longer than
+    /// 125 bytes are rejected instead of being tolerated.
+    pub(crate) fn parse_frame(
+        src: &mut BytesMut,
+        server: bool,
+        check_mask: bool,
+        strict: bool,
+        max_size: usize,
+    ) -> Result<Option<(bool, OpCode, Option<BytesMut>)>, ProtocolError> {
+        let frame =
+            Parser::parse_frame_rsv(src, server, check_mask, strict, 0, max_size)?;
+        Ok(frame.map(|(finished, _, opcode, payload)| (finished, opcode, payload)))
+    }
+
+    /// Parse the input stream into a frame, returning the reserved bits of its header.
+    ///
+    /// Reserved bits in `rsv_allowed` are used by negotiated extensions and are not rejected in
+    /// strict mode. Bits are numbered like in [`ProtocolError::ReservedBits`], RSV1 being `0b100`.
+    #[allow(clippy::type_complexity)]
+    pub(crate) fn parse_frame_rsv(
+        src: &mut BytesMut,
+        server: bool,
+        check_mask: bool,
+        strict: bool,
+        rsv_allowed: u8,
+        max_size: usize,
+    ) -> Result<Option<(bool, u8, OpCode, Option<BytesMut>)>, ProtocolError> {
         // try to parse ws frame metadata
-        let (idx, finished, opcode, length, mask) =
-            match Parser::parse_metadata(src, server, max_size)? {
-                None => return Ok(None),
-                Some(res) => res,
-            };
+        let (idx, finished, rsv, opcode, length, mask) = match Parser::parse_metadata(
+            src,
+            server,
+            check_mask,
+            strict,
+            rsv_allowed,
+            max_size,
+        )? {
+            None => return Ok(None),
+            Some(res) => res,
+        };
 
         // not enough data
         if src.len() < idx + length {
@@ -113,7 +160,7 @@ impl Parser {
 
         // no need for body
         if length == 0 {
-            return Ok(Some((finished, opcode, None)));
+            return Ok(Some((finished, rsv, opcode, None)));
         }
 
         let mut data = src.split_to(length);
@@ -123,9 +170,12 @@ impl Parser {
             OpCode::Ping | OpCode::Pong if length > 125 => {
                 return Err(ProtocolError::InvalidLength(length));
             }
+            OpCode::Close if length > 125 && strict => {
+                return Err(ProtocolError::InvalidLength(length));
+            }
             OpCode::Close if length > 125 => {
                 debug!("Received close frame with payload length exceeding 125. Morphing to protocol close frame.");
-                return Ok(Some((true, OpCode::Close, None)));
+                return Ok(Some((true, rsv, OpCode::Close, None)));
             }
             _ => {}
         }
@@ -135,7 +185,7 @@ impl Parser {
             apply_mask(&mut data, mask);
         }
 
-        Ok(Some((finished, opcode, Some(data))))
+        Ok(Some((finished, rsv, opcode, Some(data))))
     }
 
     /// Parse the payload of a close frame.
@@ -161,13 +211,24 @@ impl Parser {
         op: OpCode,
         fin: bool,
         mask: bool,
+    ) {
+        Parser::write_frame(dst, pl, op, fin, 0, mask)
+    }
+
+    /// Generate binary representation with the given reserved bits set.
+    pub(crate) fn write_frame<B: AsRef<[u8]>>(
+        dst: &mut BytesMut,
+        pl: B,
+        op: OpCode,
+        fin: bool,
+        rsv: u8,
+        mask: bool,
     ) {
         let payload = pl.as_ref();
-        let one: u8 = if fin {
-            0x80 | Into::<u8>::into(op)
-        } else {
-            op.into()
-        };
+        let mut one = ((rsv & 0x07) << 4) | Into::<u8>::into(op);
+        if fin {
+            one |= 0x80;
+        }
         let payload_len = payload.len();
         let (two, p_len) = if mask {
             (0x80, payload_len + 4)
@@ -329,6 +390,21 @@ mod tests {
         assert_eq!(frame.payload, Bytes::from(vec![1u8]));
     }
 
+    #[test]
+    fn test_parse_frame_unchecked_mask() {
+        let mut buf = BytesMut::from(&[0b0000_0001u8, 0b0000_0001u8][..]);
+        buf.extend(&[1u8]);
+        buf.extend(&[0b0000_0001u8, 0b1000_0001u8][..]);
+        buf.extend(&[1u8, 0u8, 0u8, 0u8]);
+        buf.extend(&[1u8]);
+
+        let frame = extract(Parser::parse_frame(&mut buf, true, false, false, 1024));
+        assert_eq!(frame.payload, Bytes::from(vec![1u8]));
+
+        let frame = extract(Parser::parse_frame(&mut buf, true, false, false, 1024));
+        assert_eq!(frame.payload, Bytes::from(vec![0u8]));
+    }
+
     #[test]
     fn test_parse_frame_max_size() {
         let mut buf = BytesMut::from(&[0b0000_0001u8, 0b0000_0010u8][..]);
@@ -379,4 +455,41 @@ mod tests {
         Parser::write_close(&mut buf, None, false);
         assert_eq!(&buf[..], &vec![0x88, 0x00][..]);
     }
+
+    #[test]
+    fn test_parse_strict() {
+        // RSV1 set
+        let mut buf = BytesMut::from(&[0b1100_0001u8, 0b0000_0001u8, b'1'][..]);
+        assert!(
+            extract(Parser::parse_frame(&mut buf, false, true, false, 1024)).finished
+        );
+
+        let mut buf = BytesMut::from(&[0b1100_0001u8, 0b0000_0001u8, b'1'][..]);
+        assert!(matches!(
+            Parser::parse_frame(&mut buf, false, true, true, 1024),
+            Err(ProtocolError::ReservedBits(0b100))
+        ));
+
+        // RSV1 used by an extension
+        let mut buf = BytesMut::from(&[0b1101_0001u8, 0b0000_0001u8, b'1'][..]);
+        assert!(matches!(
+            Parser::parse_frame_rsv(&mut buf, false, true, true, 0b100, 1024),
+            Err(ProtocolError::ReservedBits(0b001))
+        ));
+
+        let mut buf = BytesMut::new();
+        Parser::write_frame(&mut buf, b"1", OpCode::Text, true, 0b101, false);
+        assert!(matches!(
+            Parser::parse_frame_rsv(&mut buf, false, true, true, 0b101, 1024),
+            Ok(Some((true, 0b101, OpCode::Text, _)))
+        ));
+
+        // close frame over 125 bytes
+        let mut buf = BytesMut::from(&[0b1000_1000u8, 126u8, 0u8, 126u8][..]);
+        buf.extend_from_slice(&[0u8; 126]);
+        assert!(matches!(
+            Parser::parse_frame(&mut buf, false, true, true, 1024),
+            Err(ProtocolError::InvalidLength(126))
+        ));
+    }
 }
@@ -0,0 +1,370 @@
+use std::{collections::VecDeque, convert::TryFrom, fmt, rc::Rc};
+
+use actix_codec::{Decoder, Encoder};
+use bytes::BytesMut;
+
+use super::{Codec, Frame, Message, OpCode, ProtocolError};
+
+/// Direction of a frame observed by [`Intercept`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    /// Frame was received from the peer.
+    Inbound,
+
+    /// Frame is sent to the peer.
+    Outbound,
+}
+
+/// Metadata of a frame observed by [`Intercept`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameInfo {
+    /// Whether the frame was received or sent.
+    pub direction: Direction,
+
+    /// Frame opcode.
+    pub opcode: OpCode,
+
+    /// Whether the FIN bit is set, i.e. the frame is the last frame of a message.
+    pub fin: bool,
+
+    /// Payload length in bytes.
+    pub len: usize,
+}
+
+/// WebSocket codec wrapper that calls a function for every encoded and decoded frame.
+///
+/// Useful for collecting metrics or logging WebSocket traffic. Every frame written or consumed by
+/// the wrapped codec is reported, including continuation frames. Messages that are split into
+/// multiple frames, e.g. due to [`Codec::fragment_size`], result in one call per frame. Inbound
+/// frames are reported once the wrapped codec has consumed them, even if it does not return an
+/// item for them.
+///
+/// # Examples
+/// ```
+/// use actix_http::ws;
+///
+/// let codec = ws::Intercept::new(ws::Codec::new(), |frame: &ws::FrameInfo| {
+///     log::trace!(
+///         "{:?} {:?} frame, {} bytes",
+///         frame.direction,
+///         frame.opcode,
+///         frame.len
+///     );
+/// });
+/// ```
+pub struct Intercept<C = Codec> {
+    codec: C,
+    on_frame: Rc<dyn Fn(&FrameInfo)>,
+
+    /// Inbound frames seen in the read buffer but not yet consumed, with their end offsets.
+    inbound: VecDeque<(usize, FrameInfo)>,
+
+    /// Read buffer offset up to which frame headers have been parsed.
+    scanned: usize,
+}
+
+impl<C> Intercept<C> {
+    /// Wrap `codec`, calling `on_frame` for every frame passing through it.
+    pub fn new<F>(codec: C, on_frame: F) -> Self
+    where
+        F: Fn(&FrameInfo) + 'static,
+    {
+        Intercept {
+            codec,
+            on_frame: Rc::new(on_frame),
+            inbound: VecDeque::new(),
+            scanned: 0,
+        }
+    }
+
+    /// Returns a reference to the wrapped codec.
+    pub fn get_ref(&self) -> &C {
+        &self.codec
+    }
+
+    /// Returns the wrapped codec.
+    pub fn into_inner(self) -> C {
+        self.codec
+    }
+}
+
+impl<C: Clone> Clone for Intercept<C> {
+    fn clone(&self) -> Self {
+        Intercept {
+            codec: self.codec.clone(),
+            on_frame: self.on_frame.clone(),
+            inbound: self.inbound.clone(),
+            scanned: self.scanned,
+        }
+    }
+}
+
+impl<C: fmt::Debug> fmt::Debug for Intercept<C> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Intercept")
+            .field("codec", &self.codec)
+            .finish()
+    }
+}
+
+impl<C> Encoder<Message> for Intercept<C>
+where
+    C: Encoder<Message, Error = ProtocolError>,
+{
+    type Error = ProtocolError;
+
+    fn encode(&mut self, item: Message, dst: &mut BytesMut) -> Result<(), Self::Error> {
+        let mut pos = dst.len();
+        self.codec.encode(item, dst)?;
+
+        while let Some((header_len, fin, opcode, len)) = frame_header(&dst[pos..]) {
+            (self.on_frame)(&FrameInfo {
+                direction: Direction::Outbound,
+                opcode,
+                fin,
+                len,
+            });
+
+            pos += header_len + len;
+        }
+
+        Ok(())
+    }
+}
+
+impl<C> Decoder for Intercept<C>
+where
+    C: Decoder<Item = Frame, Error = ProtocolError>,
+{
+    type Item = Frame;
+    type Error = ProtocolError;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Self::Item>, Self::Error> {
+        // parse headers of frames received since the last call
+        while self.scanned < src.len() {
+            match frame_header(&src[self.scanned..]) {
+                Some((header_len, fin, opcode, len)) => {
+                    self.scanned += header_len + len;
+
+                    let info = FrameInfo {
+                        direction: Direction::Inbound,
+                        opcode,
+                        fin,
+                        len,
+                    };
+                    self.inbound.push_back((self.scanned, info));
+                }
+                None => break,
+            }
+        }
+
+        let before = src.len();
+        let res = self.codec.decode(src);
+        let consumed = before - src.len();
+
+        // report frames that were consumed by the wrapped codec
+        while let Some(&(end, info)) = self.inbound.front() {
+            if end > consumed {
+                break;
+            }
+
+            self.inbound.pop_front();
+            (self.on_frame)(&info);
+        }
+
+        for (end, _) in self.inbound.iter_mut() {
+            *end -= consumed;
+        }
+        self.scanned = self.scanned.saturating_sub(consumed);
+
+        res
+    }
+}
+
+/// Parses frame header, returning header length, FIN bit, opcode and payload length.
+///
+/// Returns `None` if `src` does not contain a complete header.
+fn frame_header(src: &[u8]) -> Option<(usize, bool, OpCode, usize)> {
+    if src.len() < 2 {
+        return None;
+    }
+
+    let fin = src[0] & 0x80 != 0;
+    let opcode = OpCode::from(src[0] & 0x0F);
+    let masked = src[1] & 0x80 != 0;
+
+    let (mut header_len, len) = match src[1] & 0x7F {
+        126 => {
+            let len = u16::from_be_bytes(TryFrom::try_from(src.get(2..4)?).unwrap());
+            (4, usize::from(len))
+        }
+        127 => {
+            let len = u64::from_be_bytes(TryFrom::try_from(src.get(2..10)?).unwrap());
+            (10, len as usize)
+        }
+        len => (2, usize::from(len)),
+    };
+
+    if masked {
+        header_len += 4;
+    }
+
+    if src.len() < header_len {
+        return None;
+    }
+
+    Some((header_len, fin, opcode, len))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use bytes::Bytes;
+
+    use super::*;
+
+    fn recorder() -> (Rc<RefCell<Vec<FrameInfo>>>, impl Fn(&FrameInfo)) {
+        let frames = Rc::new(RefCell::new(Vec::new()));
+        let frames2 = frames.clone();
+        (frames, move |frame: &FrameInfo| {
+            frames2.borrow_mut().push(*frame)
+        })
+    }
+
+    #[test]
+    fn outbound_frames() {
+        let (frames, on_frame) = recorder();
+        let mut codec = Intercept::new(Codec::new().fragment_size(2), on_frame);
+
+        let mut buf = BytesMut::new();
+        codec
+            .encode(Message::Text("hello".into()), &mut buf)
+            .unwrap();
+        codec
+            .encode(Message::Binary(Bytes::from(vec![0u8; 300])), &mut buf)
+            .unwrap();
+
+        let outbound = |opcode, fin, len| FrameInfo {
+            direction: Direction::Outbound,
+            opcode,
+            fin,
+            len,
+        };
+
+        assert_eq!(
+            frames.borrow()[..3],
+            [
+                outbound(OpCode::Text, false, 2),
+                outbound(OpCode::Continue, false, 2),
+                outbound(OpCode::Continue, true, 1),
+            ]
+        );
+        assert_eq!(frames.borrow().len(), 3 + 150);
+    }
+
+    #[test]
+    fn inbound_frames() {
+        let (frames, on_frame) = recorder();
+        let mut codec = Intercept::new(Codec::new(), on_frame);
+
+        let mut buf = BytesMut::new();
+        let mut client = Codec::new().client_mode();
+        client
+            .encode(Message::Ping(Bytes::from_static(b"ping")), &mut buf)
+            .unwrap();
+        client
+            .encode(Message::Binary(Bytes::from(vec![0u8; 200])), &mut buf)
+            .unwrap();
+
+        // incomplete frames are not reported
+        let mut partial = BytesMut::from(&buf[..3]);
+        assert!(codec.decode(&mut partial).unwrap().is_none());
+        assert!(frames.borrow().is_empty());
+
+        assert_eq!(
+            codec.decode(&mut buf).unwrap().unwrap(),
+            Frame::Ping(Bytes::from_static(b"ping"))
+        );
+        assert!(codec.decode(&mut buf).unwrap().is_some());
+
+        assert_eq!(
+            *frames.borrow(),
+            [
+                FrameInfo {
+                    direction: Direction::Inbound,
+                    opcode: OpCode::Ping,
+                    fin: true,
+                    len: 4,
+                },
+                FrameInfo {
+                    direction: Direction::Inbound,
+                    opcode: OpCode::Binary,
+                    fin: true,
+                    len: 200,
+                },
+            ]
+        );
+    }
+
+    /// Codec that consumes pong frames without returning them.
+    struct SkipPong(Codec);
+
+    impl Decoder for SkipPong {
+        type Item = Frame;
+        type Error = ProtocolError;
+
+        fn decode(
+            &mut self,
+            src: &mut BytesMut,
+        ) -> Result<Option<Frame>, ProtocolError> {
+            loop {
+                match self.0.decode(src)? {
+                    Some(Frame::Pong(_)) => continue,
+                    frame => return Ok(frame),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn inbound_consumed_frames() {
+        let (frames, on_frame) = recorder();
+        let mut codec = Intercept::new(SkipPong(Codec::new()), on_frame);
+
+        let mut buf = BytesMut::new();
+        let mut client = Codec::new().client_mode().fragment_size(2);
+        client
+            .encode(Message::Pong(Bytes::from_static(b"pong")), &mut buf)
+            .unwrap();
+        client
+            .encode(Message::Text("abc".into()), &mut buf)
+            .unwrap();
+
+        // pong frame is reported although it is not returned
+        let mut partial = buf.split_to(buf.len() - 1);
+        assert!(codec.decode(&mut partial).unwrap().is_some());
+        assert_eq!(frames.borrow().len(), 2);
+        assert!(codec.decode(&mut partial).unwrap().is_none());
+        assert_eq!(frames.borrow().len(), 2);
+
+        partial.unsplit(buf);
+        assert!(codec.decode(&mut partial).unwrap().is_some());
+
+        let inbound = |opcode, fin, len| FrameInfo {
+            direction: Direction::Inbound,
+            opcode,
+            fin,
+            len,
+        };
+
+        assert_eq!(
+            *frames.borrow(),
+            [
+                inbound(OpCode::Pong, true, 4),
+                inbound(OpCode::Text, false, 2),
+                inbound(OpCode::Continue, true, 1),
+            ]
+        );
+    }
+}
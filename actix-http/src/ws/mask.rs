@@ -3,7 +3,7 @@
 /// Mask/unmask a frame.
 #[inline]
 pub fn apply_mask(buf: &mut [u8], mask: [u8; 4]) {
-    apply_mask_fast32(buf, mask)
+    apply_mask_fast64(buf, mask)
 }
 
 /// A safe unoptimized mask application.
@@ -14,33 +14,41 @@ fn apply_mask_fallback(buf: &mut [u8], mask: [u8; 4]) {
     }
 }
 
-/// Faster version of `apply_mask()` which operates on 4-byte blocks.
+/// Faster version of `apply_mask()` which operates on 8-byte blocks.
+///
+/// The loop over the aligned blocks is vectorized by the compiler where SIMD instructions are
+/// available (e.g. SSE2 or NEON).
 #[inline]
-pub fn apply_mask_fast32(buf: &mut [u8], mask: [u8; 4]) {
-    let mask_u32 = u32::from_ne_bytes(mask);
-
+fn apply_mask_fast64(buf: &mut [u8], mask: [u8; 4]) {
     // SAFETY:
     //
-    // buf is a valid slice borrowed mutably from bytes::BytesMut.
+    // buf is a valid slice borrowed mutably from bytes::BytesMut and any bit pattern is a valid
+    // u64.
     //
     // un aligned prefix and suffix would be mask/unmask per byte.
-    // proper aligned middle slice goes into fast path and operates on 4-byte blocks.
-    let (mut prefix, words, mut suffix) = unsafe { buf.align_to_mut::<u32>() };
-    apply_mask_fallback(&mut prefix, mask);
+    // proper aligned middle slice goes into fast path and operates on 8-byte blocks.
+    let (prefix, words, suffix) = unsafe { buf.align_to_mut::<u64>() };
+    apply_mask_fallback(prefix, mask);
+
+    // rotate mask so that it starts at the first byte after the prefix
     let head = prefix.len() & 3;
-    let mask_u32 = if head > 0 {
-        if cfg!(target_endian = "big") {
-            mask_u32.rotate_left(8 * head as u32)
-        } else {
-            mask_u32.rotate_right(8 * head as u32)
-        }
-    } else {
-        mask_u32
-    };
+    let mask = [
+        mask[head],
+        mask[(head + 1) & 3],
+        mask[(head + 2) & 3],
+        mask[(head + 3) & 3],
+    ];
+
+    let mask_u64 = u64::from_ne_bytes([
+        mask[0], mask[1], mask[2], mask[3], mask[0], mask[1], mask[2], mask[3],
+    ]);
+
     for word in words.iter_mut() {
-        *word ^= mask_u32;
+        *word ^= mask_u64;
     }
-    apply_mask_fallback(&mut suffix, mask_u32.to_ne_bytes());
+
+    // blocks are a multiple of the mask length so the rotated mask still lines up
+    apply_mask_fallback(suffix, mask);
 }
 
 #[cfg(test)]
@@ -92,7 +100,7 @@ mod tests {
         for data_len in 0..=unmasked.len() {
             let unmasked = &unmasked[0..data_len];
             // Check masking with different alignment.
-            for off in 0..=3 {
+            for off in 0..=7 {
                 if unmasked.len() < off {
                     continue;
                 }
@@ -100,7 +108,25 @@ mod tests {
                 apply_mask_fallback(&mut masked[off..], mask);
 
                 let mut masked_fast = unmasked.to_vec();
-                apply_mask_fast32(&mut masked_fast[off..], mask);
+                apply_mask_fast64(&mut masked_fast[off..], mask);
+
+                assert_eq!(masked, masked_fast);
+            }
+        }
+    }
+
+    #[test]
+    fn test_apply_mask_long() {
+        let mask = [0x6d, 0xb6, 0xb2, 0x80];
+        let unmasked = (0..=255).collect::<Vec<u8>>();
+
+        for off in 0..=7 {
+            for end in off..=unmasked.len() {
+                let mut masked = unmasked.clone();
+                apply_mask_fallback(&mut masked[off..end], mask);
+
+                let mut masked_fast = unmasked.clone();
+                apply_mask(&mut masked_fast[off..end], mask);
 
                 assert_eq!(masked, masked_fast);
             }
@@ -9,20 +9,35 @@ use derive_more::{Display, Error, From};
 use http::{header, Method, StatusCode};
 
 use crate::{
-    body::AnyBody, header::HeaderValue, message::RequestHead, response::Response,
+    body::AnyBody,
+    header::{split_comma_delimited, HeaderValue, IntoHeaderPair},
+    message::RequestHead,
+    response::Response,
     ResponseBuilder,
 };
 
+mod aggregator;
 mod codec;
 mod dispatcher;
+mod extension;
 mod frame;
+mod intercept;
 mod mask;
 mod proto;
+mod queue;
+mod session;
 
+pub use self::aggregator::Aggregated;
 pub use self::codec::{Codec, Frame, Item, Message};
 pub use self::dispatcher::Dispatcher;
+pub use self::extension::{
+    Extension, ExtensionCodec, ExtensionParams, FrameTransform, Negotiated,
+};
 pub use self::frame::Parser;
+pub use self::intercept::{Direction, FrameInfo, Intercept};
 pub use self::proto::{hash_key, CloseCode, CloseReason, OpCode};
+pub use self::queue::{Drain, SendQueue, SendQueueError};
+pub use self::session::{Service, Session};
 
 /// WebSocket protocol errors.
 #[derive(Debug, Display, Error, From)]
@@ -43,6 +58,11 @@ pub enum ProtocolError {
     #[display(fmt = "Invalid control frame length: {}.", _0)]
     InvalidLength(#[error(not(source))] usize),
 
+    /// Received a frame with reserved bits set while no extension defining them is in use.
+    #[display(fmt = "Reserved bits set: {:#05b}.", _0)]
+    #[from(ignore)]
+    ReservedBits(#[error(not(source))] u8),
+
     /// Bad opcode.
     #[display(fmt = "Bad opcode.")]
     BadOpCode,
@@ -59,6 +79,14 @@ pub enum ProtocolError {
     #[display(fmt = "Received new continuation but it is already started.")]
     ContinuationStarted,
 
+    /// Received or attempted to send a close frame with a code that is not allowed.
+    #[display(fmt = "Invalid close code: {}.", _0)]
+    InvalidCloseCode(#[error(not(source))] u16),
+
+    /// Received text payload that is not valid UTF-8.
+    #[display(fmt = "Received text payload that is not valid UTF-8.")]
+    InvalidUtf8,
+
     /// Unknown continuation fragment.
     #[display(fmt = "Unknown continuation fragment: {}.", _0)]
     ContinuationFragment(#[error(not(source))] OpCode),
@@ -68,6 +96,21 @@ pub enum ProtocolError {
     Io(io::Error),
 }
 
+impl ProtocolError {
+    /// Returns close code that should be sent to the peer when failing the connection due to
+    /// this error.
+    ///
+    /// Returns `None` for errors that leave the connection unable to send a close frame.
+    pub fn close_code(&self) -> Option<CloseCode> {
+        match self {
+            ProtocolError::Overflow => Some(CloseCode::Size),
+            ProtocolError::InvalidUtf8 => Some(CloseCode::Invalid),
+            ProtocolError::Io(_) => None,
+            _ => Some(CloseCode::Protocol),
+        }
+    }
+}
+
 /// WebSocket handshake errors
 #[derive(Debug, PartialEq, Display, Error)]
 pub enum HandshakeError {
@@ -75,6 +118,10 @@ pub enum HandshakeError {
     #[display(fmt = "Method not allowed.")]
     GetMethodRequired,
 
+    /// Only connect method is allowed for WebSocket over HTTP/2.
+    #[display(fmt = "Method not allowed.")]
+    ConnectMethodRequired,
+
     /// Upgrade header if not set to WebSocket.
     #[display(fmt = "WebSocket upgrade is expected.")]
     NoWebsocketUpgrade,
@@ -106,6 +153,13 @@ impl From<&HandshakeError> for Response<AnyBody> {
                 res
             }
 
+            HandshakeError::ConnectMethodRequired => {
+                let mut res = Response::new(StatusCode::METHOD_NOT_ALLOWED);
+                res.headers_mut()
+                    .insert(header::ALLOW, HeaderValue::from_static("CONNECT"));
+                res
+            }
+
             HandshakeError::NoWebsocketUpgrade => {
                 let mut res = Response::bad_request();
                 res.head_mut().reason = Some("No WebSocket Upgrade header found");
@@ -151,6 +205,61 @@ pub fn handshake(req: &RequestHead) -> Result<ResponseBuilder, HandshakeError> {
     Ok(handshake_response(req))
 }
 
+/// Verify WebSocket handshake request and create handshake response with subprotocol
+/// negotiation.
+///
+/// `protocols` is a sequence of subprotocols supported by the server. See
+/// [`handshake_response_with_protocols`] for how the subprotocol is selected.
+pub fn handshake_with_protocols(
+    req: &RequestHead,
+    protocols: &[&str],
+) -> Result<ResponseBuilder, HandshakeError> {
+    verify_handshake(req)?;
+    Ok(handshake_response_with_protocols(req, protocols))
+}
+
+/// Verify WebSocket handshake request and create handshake response with subprotocol and
+/// extension negotiation.
+///
+/// Extensions offered in the request's `Sec-WebSocket-Extensions` header are negotiated with the
+/// registered `extensions`, and the accepted ones are listed in the response header. Use
+/// [`Negotiated::codec`] to apply the frame transforms of accepted extensions once the
+/// connection is upgraded.
+///
+/// # Examples
+/// ```
+/// use actix_http::{http::StatusCode, test::TestRequest, ws};
+///
+/// let req = TestRequest::default()
+///     .insert_header(("upgrade", "websocket"))
+///     .insert_header(("connection", "upgrade"))
+///     .insert_header(("sec-websocket-version", "13"))
+///     .insert_header(("sec-websocket-key", "13"))
+///     .finish();
+///
+/// let (mut res, negotiated) = ws::handshake_with_extensions(req.head(), &[], &[]).unwrap();
+/// assert!(negotiated.is_empty());
+///
+/// assert_eq!(res.finish().status(), StatusCode::SWITCHING_PROTOCOLS);
+/// let _codec = negotiated.codec(ws::Codec::new());
+/// ```
+pub fn handshake_with_extensions(
+    req: &RequestHead,
+    protocols: &[&str],
+    extensions: &[&dyn Extension],
+) -> Result<(ResponseBuilder, Negotiated), HandshakeError> {
+    verify_handshake(req)?;
+
+    let mut res = handshake_response_with_protocols(req, protocols);
+    let negotiated = Negotiated::from_request(req, extensions);
+
+    if let Some(val) = negotiated.header_value() {
+        res.insert_header((header::SEC_WEBSOCKET_EXTENSIONS, val));
+    }
+
+    Ok((res, negotiated))
+}
+
 /// Verify WebSocket handshake request.
 pub fn verify_handshake(req: &RequestHead) -> Result<(), HandshakeError> {
     // WebSocket accepts only GET
@@ -203,20 +312,150 @@ pub fn verify_handshake(req: &RequestHead) -> Result<(), HandshakeError> {
 ///
 /// This function returns handshake `Response`, ready to send to peer.
 pub fn handshake_response(req: &RequestHead) -> ResponseBuilder {
+    handshake_response_with_protocols(req, &[])
+}
+
+/// Create WebSocket handshake response with subprotocol negotiation.
+///
+/// `protocols` is a sequence of subprotocols supported by the server. The first subprotocol
+/// listed in the request's `Sec-WebSocket-Protocol` header that is also contained in `protocols`
+/// is selected and set on the returned response. If there is no match, the header is omitted.
+pub fn handshake_response_with_protocols(
+    req: &RequestHead,
+    protocols: &[&str],
+) -> ResponseBuilder {
     let key = {
         let key = req.headers().get(header::SEC_WEBSOCKET_KEY).unwrap();
         proto::hash_key(key.as_ref())
     };
 
-    Response::build(StatusCode::SWITCHING_PROTOCOLS)
-        .upgrade("websocket")
+    let mut res = Response::build(StatusCode::SWITCHING_PROTOCOLS);
+
+    res.upgrade("websocket")
         .insert_header((header::TRANSFER_ENCODING, "chunked"))
         .insert_header((
             header::SEC_WEBSOCKET_ACCEPT,
             // key is known to be header value safe ascii
             HeaderValue::from_bytes(&key).unwrap(),
-        ))
-        .take()
+        ));
+
+    if let Some(protocol) = select_protocol(req, protocols) {
+        res.insert_header((header::SEC_WEBSOCKET_PROTOCOL, protocol));
+    }
+
+    res
+}
+
+/// Create WebSocket handshake response, calling `f` to customize it.
+///
+/// `f` is called with the request and the response builder after the handshake headers have
+/// been set, which makes it possible to add cookies, negotiated subprotocols or other headers.
+/// Care should be taken not to modify the handshake headers.
+///
+/// # Examples
+/// ```
+/// use actix_http::{http::header, test::TestRequest, ws};
+///
+/// let req = TestRequest::default()
+///     .insert_header(("sec-websocket-key", "13"))
+///     .finish();
+///
+/// let res = ws::handshake_response_with(req.head(), |_req, res| {
+///     res.insert_header((header::SET_COOKIE, "session=1"));
+/// })
+/// .finish();
+///
+/// assert!(res.headers().contains_key(header::SET_COOKIE));
+/// ```
+pub fn handshake_response_with<F>(req: &RequestHead, f: F) -> ResponseBuilder
+where
+    F: FnOnce(&RequestHead, &mut ResponseBuilder),
+{
+    let mut res = handshake_response(req);
+    f(req, &mut res);
+    res
+}
+
+/// Create WebSocket handshake response with additional headers.
+///
+/// Headers are appended after the handshake headers have been set. See
+/// [`handshake_response_with`] for customizing the response in other ways.
+pub fn handshake_response_with_headers<I, H>(
+    req: &RequestHead,
+    headers: I,
+) -> ResponseBuilder
+where
+    I: IntoIterator<Item = H>,
+    H: IntoHeaderPair,
+{
+    handshake_response_with(req, |_, res| {
+        for header in headers {
+            res.append_header(header);
+        }
+    })
+}
+
+/// Verify WebSocket over HTTP/2 handshake request and create handshake response.
+///
+/// See [`verify_handshake_h2`] for the expected request format. The returned response uses
+/// status `200 OK` instead of `101 Switching Protocols`. Frames exchanged over the HTTP/2 stream
+/// should be processed with a codec in [`h2 mode`](Codec::h2_mode).
+pub fn handshake_h2(req: &RequestHead) -> Result<ResponseBuilder, HandshakeError> {
+    verify_handshake_h2(req)?;
+    Ok(handshake_response_h2(req, &[]))
+}
+
+/// Verify WebSocket over HTTP/2 handshake request.
+///
+/// Checks that the request is an extended CONNECT request ([RFC 8441]) with the `:protocol`
+/// pseudo-header set to `websocket` and a supported WebSocket version.
+///
+/// [RFC 8441]: https://datatracker.ietf.org/doc/html/rfc8441
+pub fn verify_handshake_h2(req: &RequestHead) -> Result<(), HandshakeError> {
+    // extended CONNECT is used instead of GET with upgrade headers
+    if req.method != Method::CONNECT {
+        return Err(HandshakeError::ConnectMethodRequired);
+    }
+
+    // check `:protocol` pseudo-header
+    let is_ws = req
+        .extensions()
+        .get::<::h2::ext::Protocol>()
+        .map_or(false, |proto| {
+            proto.as_str().eq_ignore_ascii_case("websocket")
+        });
+    if !is_ws {
+        return Err(HandshakeError::NoWebsocketUpgrade);
+    }
+
+    // check supported version; only version 13 is defined for HTTP/2
+    match req.headers().get(header::SEC_WEBSOCKET_VERSION) {
+        None => Err(HandshakeError::NoVersionHeader),
+        Some(hdr) if hdr == "13" => Ok(()),
+        Some(_) => Err(HandshakeError::UnsupportedVersion),
+    }
+}
+
+/// Create WebSocket over HTTP/2 handshake response with subprotocol negotiation.
+///
+/// See [`handshake_response_with_protocols`] for how the subprotocol is selected.
+pub fn handshake_response_h2(req: &RequestHead, protocols: &[&str]) -> ResponseBuilder {
+    let mut res = Response::build(StatusCode::OK);
+
+    if let Some(protocol) = select_protocol(req, protocols) {
+        res.insert_header((header::SEC_WEBSOCKET_PROTOCOL, protocol));
+    }
+
+    res
+}
+
+/// Selects first subprotocol requested by the client that is also supported by the server.
+fn select_protocol<'a>(req: &RequestHead, protocols: &[&'a str]) -> Option<&'a str> {
+    req.headers()
+        .get_all(header::SEC_WEBSOCKET_PROTOCOL)
+        .filter_map(|hdr| hdr.to_str().ok())
+        .flat_map(split_comma_delimited)
+        .find_map(|req_p| protocols.iter().find(|p| **p == req_p).copied())
 }
 
 #[cfg(test)]
@@ -335,10 +574,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_handshake_response_with_headers() {
+        let req = TestRequest::default()
+            .insert_header((header::SEC_WEBSOCKET_KEY, "13"))
+            .finish();
+
+        let res = handshake_response_with_headers(
+            req.head(),
+            vec![
+                (header::SET_COOKIE, "a=1"),
+                (header::SET_COOKIE, "b=2"),
+                (header::SEC_WEBSOCKET_PROTOCOL, "graphql-ws"),
+            ],
+        )
+        .finish();
+
+        assert_eq!(res.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert!(res.headers().contains_key(header::SEC_WEBSOCKET_ACCEPT));
+        assert_eq!(res.headers().get_all(header::SET_COOKIE).count(), 2);
+        assert_eq!(
+            res.headers().get(header::SEC_WEBSOCKET_PROTOCOL).unwrap(),
+            "graphql-ws"
+        );
+
+        let res = handshake_response_with(req.head(), |req, res| {
+            let key = req
+                .headers()
+                .get(header::SEC_WEBSOCKET_KEY)
+                .unwrap()
+                .clone();
+            res.insert_header(("x-key", key));
+        })
+        .finish();
+        assert_eq!(res.headers().get("x-key").unwrap(), "13");
+    }
+
+    #[test]
+    fn test_handshake_protocols() {
+        let req = TestRequest::default()
+            .insert_header((header::UPGRADE, "websocket"))
+            .insert_header((header::CONNECTION, "upgrade"))
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_KEY, "13"))
+            .insert_header((header::SEC_WEBSOCKET_PROTOCOL, "p1, p2, p3"))
+            .finish();
+
+        let res = handshake_with_protocols(req.head(), &["p3", "p2"])
+            .unwrap()
+            .finish();
+        assert_eq!(
+            res.headers().get(header::SEC_WEBSOCKET_PROTOCOL).unwrap(),
+            "p2"
+        );
+
+        let res = handshake_with_protocols(req.head(), &["p4"])
+            .unwrap()
+            .finish();
+        assert!(!res.headers().contains_key(header::SEC_WEBSOCKET_PROTOCOL));
+
+        let res = handshake(req.head()).unwrap().finish();
+        assert!(!res.headers().contains_key(header::SEC_WEBSOCKET_PROTOCOL));
+    }
+
+    #[test]
+    fn test_handshake_h2() {
+        let req = TestRequest::default().finish();
+        assert_eq!(
+            HandshakeError::ConnectMethodRequired,
+            verify_handshake_h2(req.head()).unwrap_err(),
+        );
+
+        let req = TestRequest::default().method(Method::CONNECT).finish();
+        assert_eq!(
+            HandshakeError::NoWebsocketUpgrade,
+            verify_handshake_h2(req.head()).unwrap_err(),
+        );
+
+        let req = TestRequest::default().method(Method::CONNECT).finish();
+        req.head()
+            .extensions_mut()
+            .insert(::h2::ext::Protocol::from_static("websocket"));
+        assert_eq!(
+            HandshakeError::NoVersionHeader,
+            verify_handshake_h2(req.head()).unwrap_err(),
+        );
+
+        let req = TestRequest::default()
+            .method(Method::CONNECT)
+            .insert_header((header::SEC_WEBSOCKET_VERSION, "13"))
+            .insert_header((header::SEC_WEBSOCKET_PROTOCOL, "p1"))
+            .finish();
+        req.head()
+            .extensions_mut()
+            .insert(::h2::ext::Protocol::from_static("websocket"));
+        let res = handshake_response_h2(req.head(), &["p1"]).finish();
+        assert!(verify_handshake_h2(req.head()).is_ok());
+        assert_eq!(res.status(), StatusCode::OK);
+        assert_eq!(
+            res.headers().get(header::SEC_WEBSOCKET_PROTOCOL).unwrap(),
+            "p1"
+        );
+    }
+
     #[test]
     fn test_ws_error_http_response() {
         let resp: Response<AnyBody> = HandshakeError::GetMethodRequired.into();
         assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
+        let resp: Response<AnyBody> = HandshakeError::ConnectMethodRequired.into();
+        assert_eq!(resp.status(), StatusCode::METHOD_NOT_ALLOWED);
         let resp: Response<AnyBody> = HandshakeError::NoWebsocketUpgrade.into();
         assert_eq!(resp.status(), StatusCode::BAD_REQUEST);
         let resp: Response<AnyBody> = HandshakeError::NoConnectionUpgrade.into();
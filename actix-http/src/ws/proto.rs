@@ -81,6 +81,7 @@ impl From<u8> for OpCode {
 
 /// Status code used to indicate why an endpoint is closing the WebSocket connection.
 #[derive(Debug, Eq, PartialEq, Clone, Copy)]
+#[non_exhaustive]
 pub enum CloseCode {
     /// Indicates a normal closure, meaning that the purpose for which the connection was
     /// established has been fulfilled.
@@ -98,6 +99,10 @@ pub enum CloseCode {
     /// receives a binary message).
     Unsupported,
 
+    /// Indicates that no status code was present in a close frame. Must not be sent in a close
+    /// frame.
+    Status,
+
     /// Indicates an abnormal closure. If the abnormal closure was due to an error, this close code
     /// will not be used. Instead, the `on_error` method of the handler will be called with
     /// the error. However, if the connection is simply dropped, without an error, this close code
@@ -139,6 +144,10 @@ pub enum CloseCode {
     /// an action.
     Again,
 
+    /// Indicates that the server, while acting as a gateway or proxy, received an invalid response
+    /// from the upstream server.
+    BadGateway,
+
     #[doc(hidden)]
     Tls,
 
@@ -155,6 +164,7 @@ impl From<CloseCode> for u16 {
             Away => 1001,
             Protocol => 1002,
             Unsupported => 1003,
+            Status => 1005,
             Abnormal => 1006,
             Invalid => 1007,
             Policy => 1008,
@@ -163,6 +173,7 @@ impl From<CloseCode> for u16 {
             Error => 1011,
             Restart => 1012,
             Again => 1013,
+            BadGateway => 1014,
             Tls => 1015,
             Other(code) => code,
         }
@@ -178,6 +189,7 @@ impl From<u16> for CloseCode {
             1001 => Away,
             1002 => Protocol,
             1003 => Unsupported,
+            1005 => Status,
             1006 => Abnormal,
             1007 => Invalid,
             1008 => Policy,
@@ -186,12 +198,27 @@ impl From<u16> for CloseCode {
             1011 => Error,
             1012 => Restart,
             1013 => Again,
+            1014 => BadGateway,
             1015 => Tls,
             _ => Other(code),
         }
     }
 }
 
+impl CloseCode {
+    /// Returns true if the code may be sent in a close frame.
+    ///
+    /// Codes reserved for local use (1005, 1006 and 1015), unassigned codes below 3000 and codes
+    /// above 4999 are not allowed.
+    pub fn is_allowed(&self) -> bool {
+        match self {
+            CloseCode::Status | CloseCode::Abnormal | CloseCode::Tls => false,
+            CloseCode::Other(code) => (3000..=4999).contains(code),
+            _ => true,
+        }
+    }
+}
+
 #[derive(Debug, Eq, PartialEq, Clone)]
 /// Reason for closing the connection
 pub struct CloseReason {
@@ -319,6 +346,7 @@ mod test {
         assert_eq!(CloseCode::from(1001u16), CloseCode::Away);
         assert_eq!(CloseCode::from(1002u16), CloseCode::Protocol);
         assert_eq!(CloseCode::from(1003u16), CloseCode::Unsupported);
+        assert_eq!(CloseCode::from(1005u16), CloseCode::Status);
         assert_eq!(CloseCode::from(1006u16), CloseCode::Abnormal);
         assert_eq!(CloseCode::from(1007u16), CloseCode::Invalid);
         assert_eq!(CloseCode::from(1008u16), CloseCode::Policy);
@@ -327,6 +355,7 @@ mod test {
         assert_eq!(CloseCode::from(1011u16), CloseCode::Error);
         assert_eq!(CloseCode::from(1012u16), CloseCode::Restart);
         assert_eq!(CloseCode::from(1013u16), CloseCode::Again);
+        assert_eq!(CloseCode::from(1014u16), CloseCode::BadGateway);
         assert_eq!(CloseCode::from(1015u16), CloseCode::Tls);
         assert_eq!(CloseCode::from(2000u16), CloseCode::Other(2000));
     }
@@ -337,6 +366,7 @@ mod test {
         assert_eq!(1001u16, Into::<u16>::into(CloseCode::Away));
         assert_eq!(1002u16, Into::<u16>::into(CloseCode::Protocol));
         assert_eq!(1003u16, Into::<u16>::into(CloseCode::Unsupported));
+        assert_eq!(1005u16, Into::<u16>::into(CloseCode::Status));
         assert_eq!(1006u16, Into::<u16>::into(CloseCode::Abnormal));
         assert_eq!(1007u16, Into::<u16>::into(CloseCode::Invalid));
         assert_eq!(1008u16, Into::<u16>::into(CloseCode::Policy));
@@ -345,7 +375,24 @@ mod test {
         assert_eq!(1011u16, Into::<u16>::into(CloseCode::Error));
         assert_eq!(1012u16, Into::<u16>::into(CloseCode::Restart));
         assert_eq!(1013u16, Into::<u16>::into(CloseCode::Again));
+        assert_eq!(1014u16, Into::<u16>::into(CloseCode::BadGateway));
         assert_eq!(1015u16, Into::<u16>::into(CloseCode::Tls));
         assert_eq!(2000u16, Into::<u16>::into(CloseCode::Other(2000)));
     }
+
+    #[test]
+    fn close_code_is_allowed() {
+        assert!(CloseCode::Normal.is_allowed());
+        assert!(CloseCode::BadGateway.is_allowed());
+        assert!(CloseCode::Other(3000).is_allowed());
+        assert!(CloseCode::Other(4999).is_allowed());
+
+        assert!(!CloseCode::Status.is_allowed());
+        assert!(!CloseCode::Abnormal.is_allowed());
+        assert!(!CloseCode::Tls.is_allowed());
+        assert!(!CloseCode::Other(999).is_allowed());
+        assert!(!CloseCode::Other(1004).is_allowed());
+        assert!(!CloseCode::Other(2999).is_allowed());
+        assert!(!CloseCode::Other(5000).is_allowed());
+    }
 }
@@ -0,0 +1,438 @@
+use std::{
+    cell::RefCell,
+    collections::VecDeque,
+    future::Future,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll, Waker},
+};
+
+use derive_more::{Display, Error};
+use futures_core::ready;
+use futures_util::{future::poll_fn, sink::Sink};
+
+use super::{Item, Message};
+
+/// Error returned by [`SendQueue`] when a message could not be queued.
+///
+/// The rejected message is handed back so that it can be retried or dropped.
+#[derive(Debug, Display, Error)]
+pub enum SendQueueError {
+    /// Queue holds more buffered bytes than allowed.
+    #[display(fmt = "Send queue is full.")]
+    Full(#[error(not(source))] Message),
+
+    /// Queue has been closed or its [`Drain`] has stopped.
+    #[display(fmt = "Send queue is closed.")]
+    Closed(#[error(not(source))] Message),
+}
+
+/// Bounded queue of outgoing WebSocket messages.
+///
+/// Handles can be cloned and used from multiple tasks while a single [`Drain`] future, created
+/// with [`SendQueue::drain`], writes queued messages to the connection. Messages count towards
+/// the buffered bytes limit from the moment they are queued until the connection has flushed
+/// them.
+///
+/// Once the limit is reached, [`try_send`](Self::try_send) fails with
+/// [`SendQueueError::Full`] and [`send`](Self::send) waits. Waiting senders are woken when the
+/// connection has drained the queue down to the low watermark, which avoids waking them for every
+/// flushed message.
+///
+/// A message larger than the limit is accepted when nothing else is buffered.
+///
+/// # Examples
+/// ```
+/// use actix_http::ws;
+/// use futures_util::StreamExt as _;
+///
+/// # async fn test_queue(session: ws::Session<actix_http::test::TestBuffer>) {
+/// let (sink, mut stream) = session.split();
+///
+/// let queue = ws::SendQueue::new(64 * 1024);
+/// actix_rt::spawn(queue.drain(sink));
+///
+/// while let Some(Ok(msg)) = stream.next().await {
+///     if let ws::Message::Text(text) = msg {
+///         if queue.send(ws::Message::Text(text)).await.is_err() {
+///             break;
+///         }
+///     }
+/// }
+/// # }
+/// ```
+pub struct SendQueue {
+    inner: Rc<RefCell<Inner>>,
+}
+
+struct Inner {
+    queue: VecDeque<Message>,
+    buffered: usize,
+    max_buffered: usize,
+    low_watermark: usize,
+    senders: usize,
+    closed: bool,
+    drain_task: Option<Waker>,
+    waiters: Vec<Waker>,
+}
+
+impl Inner {
+    fn accepts(&self, len: usize) -> bool {
+        self.buffered == 0 || self.buffered + len <= self.max_buffered
+    }
+
+    fn wake_drain(&mut self) {
+        if let Some(waker) = self.drain_task.take() {
+            waker.wake();
+        }
+    }
+
+    fn wake_waiters(&mut self) {
+        for waker in self.waiters.drain(..) {
+            waker.wake();
+        }
+    }
+
+    fn close(&mut self) {
+        self.closed = true;
+        self.wake_drain();
+        self.wake_waiters();
+    }
+}
+
+impl SendQueue {
+    /// Create queue that buffers at most `max_buffered` bytes of message payloads.
+    ///
+    /// The low watermark defaults to half of `max_buffered`.
+    pub fn new(max_buffered: usize) -> Self {
+        SendQueue {
+            inner: Rc::new(RefCell::new(Inner {
+                queue: VecDeque::new(),
+                buffered: 0,
+                max_buffered,
+                low_watermark: max_buffered / 2,
+                senders: 1,
+                closed: false,
+                drain_task: None,
+                waiters: Vec::new(),
+            })),
+        }
+    }
+
+    /// Set number of buffered bytes below which waiting senders are woken.
+    ///
+    /// Values above the buffered bytes limit are capped to it.
+    pub fn low_watermark(self, low_watermark: usize) -> Self {
+        {
+            let mut inner = self.inner.borrow_mut();
+            inner.low_watermark = low_watermark.min(inner.max_buffered);
+        }
+        self
+    }
+
+    /// Returns number of payload bytes that are queued or not yet flushed to the connection.
+    pub fn buffered(&self) -> usize {
+        self.inner.borrow().buffered
+    }
+
+    /// Returns true if the queue has been closed or its drain has stopped.
+    pub fn is_closed(&self) -> bool {
+        self.inner.borrow().closed
+    }
+
+    /// Queue message without waiting.
+    pub fn try_send(&self, msg: Message) -> Result<(), SendQueueError> {
+        let mut inner = self.inner.borrow_mut();
+
+        if inner.closed {
+            return Err(SendQueueError::Closed(msg));
+        }
+
+        let len = payload_len(&msg);
+
+        if !inner.accepts(len) {
+            return Err(SendQueueError::Full(msg));
+        }
+
+        inner.buffered += len;
+        inner.queue.push_back(msg);
+        inner.wake_drain();
+
+        Ok(())
+    }
+
+    /// Queue message, waiting for the connection to drain the queue if it is full.
+    pub async fn send(&self, mut msg: Message) -> Result<(), SendQueueError> {
+        loop {
+            match self.try_send(msg) {
+                Err(SendQueueError::Full(rejected)) => msg = rejected,
+                res => return res,
+            }
+
+            let len = payload_len(&msg);
+
+            poll_fn(|cx| {
+                let mut inner = self.inner.borrow_mut();
+
+                if inner.closed || inner.accepts(len) {
+                    Poll::Ready(())
+                } else {
+                    inner.waiters.push(cx.waker().clone());
+                    Poll::Pending
+                }
+            })
+            .await;
+        }
+    }
+
+    /// Close the queue.
+    ///
+    /// Messages that are already queued are still written, after which the drain closes the
+    /// connection sink.
+    pub fn close(&self) {
+        self.inner.borrow_mut().close();
+    }
+
+    /// Create future that writes queued messages to `sink`.
+    ///
+    /// Only one drain should be running for a queue. The drain resolves once the queue has been
+    /// closed, or all handles have been dropped, and all queued messages have been written.
+    pub fn drain<S>(&self, sink: S) -> Drain<S>
+    where
+        S: Sink<Message> + Unpin,
+    {
+        Drain {
+            sink,
+            inner: self.inner.clone(),
+            in_flight: 0,
+        }
+    }
+}
+
+impl Clone for SendQueue {
+    fn clone(&self) -> Self {
+        self.inner.borrow_mut().senders += 1;
+
+        SendQueue {
+            inner: self.inner.clone(),
+        }
+    }
+}
+
+impl Drop for SendQueue {
+    fn drop(&mut self) {
+        let mut inner = self.inner.borrow_mut();
+        inner.senders -= 1;
+
+        if inner.senders == 0 {
+            inner.wake_drain();
+        }
+    }
+}
+
+/// Future that writes messages of a [`SendQueue`] to a sink.
+///
+/// Dropping the drain closes the queue.
+pub struct Drain<S> {
+    sink: S,
+    inner: Rc<RefCell<Inner>>,
+    in_flight: usize,
+}
+
+impl<S> Future for Drain<S>
+where
+    S: Sink<Message> + Unpin,
+{
+    type Output = Result<(), S::Error>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        loop {
+            while !this.inner.borrow().queue.is_empty() {
+                if Pin::new(&mut this.sink).poll_ready(cx)?.is_pending() {
+                    break;
+                }
+
+                let msg = this.inner.borrow_mut().queue.pop_front().unwrap();
+                this.in_flight += payload_len(&msg);
+                Pin::new(&mut this.sink).start_send(msg)?;
+            }
+
+            if this.in_flight > 0 {
+                ready!(Pin::new(&mut this.sink).poll_flush(cx))?;
+
+                let mut inner = this.inner.borrow_mut();
+                inner.buffered -= this.in_flight;
+                this.in_flight = 0;
+
+                if inner.buffered <= inner.low_watermark {
+                    inner.wake_waiters();
+                }
+
+                continue;
+            }
+
+            let mut inner = this.inner.borrow_mut();
+
+            if inner.queue.is_empty() {
+                if inner.closed || inner.senders == 0 {
+                    drop(inner);
+                    return Pin::new(&mut this.sink).poll_close(cx);
+                }
+
+                inner.drain_task = Some(cx.waker().clone());
+            }
+
+            return Poll::Pending;
+        }
+    }
+}
+
+impl<S> Drop for Drain<S> {
+    fn drop(&mut self) {
+        self.inner.borrow_mut().close();
+    }
+}
+
+/// Returns number of payload bytes of a message.
+fn payload_len(msg: &Message) -> usize {
+    match msg {
+        Message::Text(text) => text.len(),
+        Message::Binary(bin) => bin.len(),
+        Message::Continuation(Item::FirstText(data))
+        | Message::Continuation(Item::FirstBinary(data))
+        | Message::Continuation(Item::Continue(data))
+        | Message::Continuation(Item::Last(data)) => data.len(),
+        Message::Ping(data) | Message::Pong(data) => data.len(),
+        Message::Close(Some(reason)) => {
+            2 + reason.description.as_ref().map_or(0, |desc| desc.len())
+        }
+        Message::Close(None) | Message::Nop => 0,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use bytes::Bytes;
+    use futures_util::task::noop_waker_ref;
+
+    use super::*;
+
+    /// Sink that collects messages and only completes flushes when `flushable` is set.
+    struct GatedSink {
+        sent: Rc<RefCell<Vec<Message>>>,
+        flushable: Rc<Cell<bool>>,
+    }
+
+    impl Sink<Message> for GatedSink {
+        type Error = ();
+
+        fn poll_ready(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<(), ()>> {
+            Poll::Ready(Ok(()))
+        }
+
+        fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), ()> {
+            self.sent.borrow_mut().push(item);
+            Ok(())
+        }
+
+        fn poll_flush(
+            self: Pin<&mut Self>,
+            _: &mut Context<'_>,
+        ) -> Poll<Result<(), ()>> {
+            if self.flushable.get() {
+                Poll::Ready(Ok(()))
+            } else {
+                Poll::Pending
+            }
+        }
+
+        fn poll_close(
+            self: Pin<&mut Self>,
+            cx: &mut Context<'_>,
+        ) -> Poll<Result<(), ()>> {
+            self.poll_flush(cx)
+        }
+    }
+
+    fn text(s: &'static str) -> Message {
+        Message::Text(s.into())
+    }
+
+    #[test]
+    fn full_until_flushed() {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+        let flushable = Rc::new(Cell::new(false));
+        let mut cx = Context::from_waker(noop_waker_ref());
+
+        let queue = SendQueue::new(10);
+        queue.try_send(text("hello")).unwrap();
+        queue
+            .try_send(Message::Binary(Bytes::from_static(b"world")))
+            .unwrap();
+
+        match queue.try_send(text("!")) {
+            Err(SendQueueError::Full(msg)) => assert_eq!(msg, text("!")),
+            res => panic!("unexpected result: {:?}", res),
+        }
+
+        let mut drain = queue.drain(GatedSink {
+            sent: sent.clone(),
+            flushable: flushable.clone(),
+        });
+
+        // written to the connection but not yet flushed
+        assert!(Pin::new(&mut drain).poll(&mut cx).is_pending());
+        assert_eq!(sent.borrow().len(), 2);
+        assert_eq!(queue.buffered(), 10);
+        assert!(queue.try_send(text("!")).is_err());
+
+        flushable.set(true);
+        assert!(Pin::new(&mut drain).poll(&mut cx).is_pending());
+        assert_eq!(queue.buffered(), 0);
+        queue.try_send(text("!")).unwrap();
+
+        queue.close();
+        assert!(Pin::new(&mut drain).poll(&mut cx).is_ready());
+        assert_eq!(sent.borrow().len(), 3);
+
+        match queue.try_send(text("?")) {
+            Err(SendQueueError::Closed(_)) => {}
+            res => panic!("unexpected result: {:?}", res),
+        }
+    }
+
+    #[test]
+    fn oversized_message() {
+        let queue = SendQueue::new(4);
+        queue.try_send(text("hello")).unwrap();
+        assert_eq!(queue.buffered(), 5);
+        assert!(queue.try_send(Message::Nop).is_err());
+    }
+
+    #[actix_rt::test]
+    async fn send_waits_for_drain() {
+        let sent = Rc::new(RefCell::new(Vec::new()));
+
+        let queue = SendQueue::new(8).low_watermark(2);
+        let drain = queue.drain(GatedSink {
+            sent: sent.clone(),
+            flushable: Rc::new(Cell::new(true)),
+        });
+        let handle = actix_rt::spawn(drain);
+
+        for _ in 0..4 {
+            queue.send(text("12345")).await.unwrap();
+        }
+
+        drop(queue);
+        handle.await.unwrap().unwrap();
+        assert_eq!(sent.borrow().len(), 4);
+    }
+}
@@ -0,0 +1,258 @@
+use std::{
+    future::{ready, Future, Ready},
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_codec::{AsyncRead, AsyncWrite, Framed};
+use actix_service::ServiceFactory;
+use futures_core::{future::LocalBoxFuture, Stream};
+use futures_util::{sink::Sink, SinkExt as _};
+
+use super::{handshake_with_protocols, Aggregated, Codec, Message, ProtocolError};
+use crate::{
+    body::{AnyBody, BodySize},
+    h1, Error, Request, Response,
+};
+
+/// WebSocket session over an upgraded connection.
+///
+/// Received messages are yielded by the [`Stream`] implementation, with fragmented messages
+/// assembled into complete `Message::Text` and `Message::Binary` items. Messages are sent
+/// through the [`Sink`] implementation. Use `StreamExt::split` to obtain separate halves.
+///
+/// Ping messages are yielded like any other message; replying with a pong is left to the user.
+pub struct Session<T> {
+    inner: Aggregated<Framed<T, Codec>>,
+}
+
+impl<T> Session<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    /// Create session over framed connection that has completed the WebSocket handshake.
+    pub fn new(framed: Framed<T, Codec>) -> Self {
+        Session {
+            inner: Aggregated::new(framed),
+        }
+    }
+
+    /// Set max size of messages assembled from continuation frames.
+    ///
+    /// By default max size is set to 64kB.
+    pub fn max_message_size(self, size: usize) -> Self {
+        Session {
+            inner: self.inner.max_size(size),
+        }
+    }
+
+    /// Returns a reference to the underlying framed connection.
+    pub fn get_ref(&self) -> &Framed<T, Codec> {
+        self.inner.get_ref()
+    }
+
+    /// Returns the underlying framed connection.
+    pub fn into_inner(self) -> Framed<T, Codec> {
+        self.inner.into_inner()
+    }
+}
+
+impl<T> Stream for Session<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    type Item = Result<Message, ProtocolError>;
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.get_mut().inner).poll_next(cx)
+    }
+}
+
+impl<T> Sink<Message> for Session<T>
+where
+    T: AsyncRead + AsyncWrite + Unpin,
+{
+    type Error = ProtocolError;
+
+    fn poll_ready(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Pin::new(self.get_mut().inner.get_mut()).poll_ready(cx)
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: Message) -> Result<(), Self::Error> {
+        Pin::new(self.get_mut().inner.get_mut()).start_send(item)
+    }
+
+    fn poll_flush(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Pin::new(self.get_mut().inner.get_mut()).poll_flush(cx)
+    }
+
+    fn poll_close(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<(), Self::Error>> {
+        Pin::new(self.get_mut().inner.get_mut()).poll_close(cx)
+    }
+}
+
+/// Upgrade service that runs WebSocket sessions.
+///
+/// Performs the WebSocket handshake, switches the connection to the WebSocket [`Codec`] and then
+/// calls the session function with the request and a [`Session`]. Requests that are not valid
+/// WebSocket handshakes are responded to with the appropriate error response.
+///
+/// # Examples
+/// ```
+/// use std::convert::Infallible;
+/// use actix_http::{ws, HttpService, Response};
+/// use futures_util::{SinkExt as _, StreamExt as _};
+///
+/// # actix_rt::System::new().block_on(async {
+/// let srv = HttpService::build()
+///     .upgrade(ws::Service::new(|_req, mut session| async move {
+///         while let Some(msg) = session.next().await {
+///             match msg? {
+///                 ws::Message::Ping(data) => session.send(ws::Message::Pong(data)).await?,
+///                 ws::Message::Close(reason) => {
+///                     session.send(ws::Message::Close(reason)).await?;
+///                     break;
+///                 }
+///                 msg => session.send(msg).await?,
+///             }
+///         }
+///
+///         Ok::<_, ws::ProtocolError>(())
+///     }))
+///     .finish(|_| async { Ok::<_, Infallible>(Response::not_found()) })
+///     .tcp();
+/// # });
+/// ```
+pub struct Service<F> {
+    f: Rc<F>,
+    codec: Codec,
+    protocols: &'static [&'static str],
+    max_message_size: usize,
+}
+
+impl<F> Service<F> {
+    /// Create upgrade service that runs `f` for each WebSocket session.
+    pub fn new<T, Fut, E>(f: F) -> Self
+    where
+        F: Fn(Request, Session<T>) -> Fut,
+        Fut: Future<Output = Result<(), E>>,
+    {
+        Service {
+            f: Rc::new(f),
+            codec: Codec::new(),
+            protocols: &[],
+            max_message_size: 65_536,
+        }
+    }
+
+    /// Set codec used for sessions, e.g. to change frame size limits.
+    pub fn codec(mut self, codec: Codec) -> Self {
+        self.codec = codec;
+        self
+    }
+
+    /// Set subprotocols supported by the server, in order of preference.
+    ///
+    /// See [`handshake_with_protocols`] for how the subprotocol is selected.
+    pub fn protocols(mut self, protocols: &'static [&'static str]) -> Self {
+        self.protocols = protocols;
+        self
+    }
+
+    /// Set max size of messages assembled from continuation frames.
+    ///
+    /// By default max size is set to 64kB.
+    pub fn max_message_size(mut self, size: usize) -> Self {
+        self.max_message_size = size;
+        self
+    }
+}
+
+impl<F> Clone for Service<F> {
+    fn clone(&self) -> Self {
+        Service {
+            f: self.f.clone(),
+            codec: self.codec,
+            protocols: self.protocols,
+            max_message_size: self.max_message_size,
+        }
+    }
+}
+
+impl<T, F, Fut, E> ServiceFactory<(Request, Framed<T, h1::Codec>)> for Service<F>
+where
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
+    F: Fn(Request, Session<T>) -> Fut + 'static,
+    Fut: Future<Output = Result<(), E>> + 'static,
+    E: Into<Error>,
+{
+    type Response = ();
+    type Error = Error;
+    type Config = ();
+    type Service = Service<F>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Service, Self::InitError>>;
+
+    fn new_service(&self, _: ()) -> Self::Future {
+        ready(Ok(self.clone()))
+    }
+}
+
+impl<T, F, Fut, E> actix_service::Service<(Request, Framed<T, h1::Codec>)> for Service<F>
+where
+    T: AsyncRead + AsyncWrite + Unpin + 'static,
+    F: Fn(Request, Session<T>) -> Fut + 'static,
+    Fut: Future<Output = Result<(), E>> + 'static,
+    E: Into<Error>,
+{
+    type Response = ();
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<(), Error>>;
+
+    actix_service::always_ready!();
+
+    fn call(&self, (req, mut framed): (Request, Framed<T, h1::Codec>)) -> Self::Future {
+        let f = self.f.clone();
+        let codec = self.codec;
+        let protocols = self.protocols;
+        let max_message_size = self.max_message_size;
+
+        Box::pin(async move {
+            let res = match handshake_with_protocols(req.head(), protocols) {
+                Ok(mut res) => res.finish().drop_body(),
+                Err(err) => {
+                    let res = Response::<AnyBody>::from(&err).drop_body();
+                    framed
+                        .send((res, BodySize::Empty).into())
+                        .await
+                        .map_err(|err| Error::new_io().with_cause(err))?;
+
+                    return Err(err.into());
+                }
+            };
+
+            framed
+                .send((res, BodySize::None).into())
+                .await
+                .map_err(|err| Error::new_io().with_cause(err))?;
+
+            let session = Session::new(framed.replace_codec(codec))
+                .max_message_size(max_message_size);
+
+            f(req, session).await.map_err(Into::into)
+        })
+    }
+}
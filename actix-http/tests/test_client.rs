@@ -1,4 +1,4 @@
-use std::convert::Infallible;
+use std::{convert::Infallible, time::Duration};
 
 use actix_http::{
     body::AnyBody, http, http::StatusCode, HttpMessage, HttpService, Request, Response,
@@ -156,3 +156,263 @@ async fn test_h1_expect() {
     let response = request.send_body("expect body").await.unwrap();
     assert!(response.status().is_success());
 }
+
+#[actix_rt::test]
+async fn test_h1_expect_timeout() {
+    let srv = test_server(move || {
+        HttpService::build()
+            .expect(|req: Request| async {
+                // accept after the client stopped waiting
+                actix_rt::time::sleep(Duration::from_millis(300)).await;
+
+                if req.headers().contains_key("AUTH") {
+                    Ok(req)
+                } else {
+                    Err(ExpectFailed)
+                }
+            })
+            .h1(|req: Request| async move {
+                let (_, mut body) = req.into_parts();
+                let mut buf = Vec::new();
+                while let Some(Ok(chunk)) = body.next().await {
+                    buf.extend_from_slice(&chunk);
+                }
+                assert_eq!(buf, b"expect body");
+
+                Ok::<_, Infallible>(Response::ok())
+            })
+            .tcp()
+    })
+    .await;
+
+    // body is sent before the interim response, which is skipped
+    let request = srv
+        .request(http::Method::POST, srv.url("/"))
+        .insert_header(("AUTH", "996"))
+        .expect_continue(Duration::from_millis(50));
+
+    let response = request.send_body("expect body").await.unwrap();
+    assert!(response.status().is_success());
+
+    // rejected before the body is sent
+    let request = srv
+        .request(http::Method::POST, srv.url("/"))
+        .expect_continue(Duration::from_secs(5));
+
+    let response = request.send_body("expect body").await.unwrap();
+    assert_eq!(response.status(), StatusCode::EXPECTATION_FAILED);
+}
+
+#[actix_rt::test]
+async fn test_h1_reader_body() {
+    use actix_http::{
+        body::ReaderBody,
+        client::{Connect, Connector},
+        Method, Payload, RequestHead,
+    };
+    use actix_service::Service as _;
+
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1(|mut req: Request| async move {
+                let chunked = req.chunked().unwrap();
+
+                let mut body = Vec::new();
+                let mut payload = req.take_payload();
+                while let Some(chunk) = payload.next().await {
+                    body.extend_from_slice(&chunk.unwrap());
+                }
+
+                let mut res = Response::ok().set_body(AnyBody::from(body));
+                if chunked {
+                    res.headers_mut().insert(
+                        http::header::HeaderName::from_static("x-chunked"),
+                        http::HeaderValue::from_static("1"),
+                    );
+                }
+                Ok::<_, actix_http::Error>(res)
+            })
+            .tcp()
+    })
+    .await;
+
+    let connector = Connector::new().finish();
+
+    for len in [Some(STR.len() as u64), None].iter().copied() {
+        let conn = connector
+            .call(Connect::new(srv.url("/").parse().unwrap()))
+            .await
+            .unwrap();
+
+        let mut head = RequestHead::default();
+        head.method = Method::POST;
+        head.uri = srv.url("/").parse().unwrap();
+
+        let body = AnyBody::from_message(ReaderBody::new(STR.as_bytes(), len));
+        let (res, payload) = conn.send_request(head, body).await.unwrap();
+        assert!(res.status.is_success());
+        assert_eq!(res.headers.contains_key("x-chunked"), len.is_none());
+
+        let mut body = Vec::new();
+        if let Payload::Stream(mut stream) = payload {
+            while let Some(chunk) = stream.next().await {
+                body.extend_from_slice(&chunk.unwrap());
+            }
+        }
+        assert_eq!(body, STR.as_bytes());
+    }
+}
+
+#[actix_rt::test]
+async fn test_h1_transport() {
+    use actix_http::client::{Connect, ConnectError, Connector};
+    use actix_rt::net::TcpStream;
+    use actix_service::{fn_service, Service as _};
+
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1(|req: Request| {
+                let host = req.headers().get(http::header::HOST).unwrap().clone();
+                future::ok::<_, Infallible>(
+                    Response::ok().set_body(host.as_bytes().to_vec()),
+                )
+            })
+            .tcp()
+    })
+    .await;
+
+    let addr = srv.addr();
+    let transport = fn_service(move |req: Connect| async move {
+        assert!(req.addr.is_none());
+        TcpStream::connect(addr).await.map_err(ConnectError::Io)
+    });
+    let connector = Connector::new().transport(transport).finish();
+
+    // hosts are not resolved and https uris are not sent over tls
+    for uri in ["http://mock.invalid/", "https://mock.invalid/"].iter() {
+        let conn = connector
+            .call(Connect::new(uri.parse().unwrap()))
+            .await
+            .unwrap();
+
+        let mut head = actix_http::RequestHead::default();
+        head.uri = uri.parse().unwrap();
+
+        let (res, payload) = conn.send_request(head, ()).await.unwrap();
+        assert!(res.status.is_success());
+
+        let mut body = Vec::new();
+        if let actix_http::Payload::Stream(mut stream) = payload {
+            while let Some(chunk) = stream.next().await {
+                body.extend_from_slice(&chunk.unwrap());
+            }
+        }
+        assert_eq!(body, b"mock.invalid");
+    }
+}
+
+#[actix_rt::test]
+async fn test_h1_request_hook() {
+    use actix_http::client::{Connect, Connector, SendRequestError};
+    use actix_http::{body::BodySize, RequestHeadType};
+    use actix_service::Service as _;
+
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1(|req: Request| {
+                let sig = req.headers().get("x-signature").unwrap().clone();
+                future::ok::<_, Infallible>(
+                    Response::ok().set_body(sig.as_bytes().to_vec()),
+                )
+            })
+            .tcp()
+    })
+    .await;
+
+    let connector = Connector::new()
+        .on_request(|head: &mut RequestHeadType, size: BodySize| {
+            if head.as_ref().uri.path() == "/fail" {
+                return Err(SendRequestError::Timeout);
+            }
+
+            // the host header is set before the hook is called
+            assert!(head.as_ref().headers.contains_key(http::header::HOST));
+            let sig = format!("{:?}", size);
+            head.headers_mut()
+                .insert("x-signature".parse().unwrap(), sig.parse().unwrap());
+            Ok(())
+        })
+        .finish();
+
+    for path in ["/", "/fail"].iter() {
+        let conn = connector
+            .call(Connect::new(srv.url(path).parse().unwrap()))
+            .await
+            .unwrap();
+
+        let mut head = actix_http::RequestHead::default();
+        head.uri = srv.url(path).parse().unwrap();
+
+        let res = conn.send_request(head, "body").await;
+        if *path == "/fail" {
+            assert!(matches!(res, Err(SendRequestError::Timeout)));
+            continue;
+        }
+
+        let (res, payload) = res.unwrap();
+        assert!(res.status.is_success());
+
+        let mut body = Vec::new();
+        if let actix_http::Payload::Stream(mut stream) = payload {
+            while let Some(chunk) = stream.next().await {
+                body.extend_from_slice(&chunk.unwrap());
+            }
+        }
+        assert_eq!(body, b"Sized(4)");
+    }
+}
+
+#[actix_rt::test]
+async fn test_h1_request_timeout() {
+    use actix_http::client::{Connect, Connector, RequestTimeout, SendRequestError};
+    use actix_service::Service as _;
+
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1(|req: Request| async move {
+                if req.path() == "/slow" {
+                    actix_rt::time::sleep(Duration::from_millis(500)).await;
+                }
+                Ok::<_, Infallible>(Response::ok())
+            })
+            .tcp()
+    })
+    .await;
+
+    let connector = Connector::new().finish();
+
+    for path in ["/", "/slow"].iter() {
+        let conn = connector
+            .call(Connect::new(srv.url(path).parse().unwrap()))
+            .await
+            .unwrap();
+
+        let mut head = actix_http::RequestHead::default();
+        head.uri = srv.url(path).parse().unwrap();
+        head.extensions_mut()
+            .insert(RequestTimeout::new(Duration::from_millis(100)));
+
+        let res = conn.send_request(head, ()).await;
+        if *path == "/slow" {
+            assert!(matches!(res, Err(SendRequestError::Timeout)));
+        } else {
+            assert!(res.unwrap().0.status.is_success());
+        }
+    }
+
+    // connections of timed out requests are closed
+    let stats = connector
+        .pool_stats()
+        .host(&srv.addr().to_string().parse().unwrap());
+    assert_eq!(stats.in_flight, 0);
+}
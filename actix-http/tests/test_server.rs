@@ -1,19 +1,28 @@
 use std::{
     convert::Infallible,
     io::{Read, Write},
-    net, thread,
+    net,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    thread,
     time::Duration,
 };
 
 use actix_http::{
-    body::{AnyBody, Body, SizedStream},
-    header, http, Error, HttpMessage, HttpService, KeepAlive, Request, Response,
+    body::{AnyBody, Body, BodySize, Frame, MessageBody, SizedStream},
+    header, http,
+    test::EchoService,
+    ConnectionData, Error, HttpMessage, HttpService, KeepAlive, Request, Response,
     StatusCode,
 };
 use actix_http_test::test_server;
 use actix_rt::time::sleep;
 use actix_service::fn_service;
-use actix_utils::future::{err, ok, ready};
+use actix_utils::future::{err, ok, poll_fn, ready};
 use bytes::Bytes;
 use derive_more::{Display, Error};
 use futures_util::{
@@ -189,6 +198,67 @@ async fn test_chunked_payload() {
     assert_eq!(returned_size, total_size);
 }
 
+#[actix_rt::test]
+async fn test_chunked_payload_trailers() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1(fn_service(|mut request: Request| async move {
+                let mut payload = request.take_payload();
+                let mut size = 0;
+                let mut status = None;
+
+                while let Some(frame) = poll_fn(|cx| payload.poll_frame(cx)).await {
+                    match frame.unwrap() {
+                        Frame::Data(chunk) => size += chunk.len(),
+                        Frame::Trailers(trailers) => {
+                            status = trailers.get("grpc-status").cloned()
+                        }
+                    }
+                }
+
+                let status = status.unwrap();
+                Ok::<_, Error>(Response::ok().set_body(format!(
+                    "size={} status={}",
+                    size,
+                    status.to_str().unwrap()
+                )))
+            }))
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"POST /test HTTP/1.1\r\nTransfer-Encoding: chunked\r\nConnection: close\r\n\r\n\
+        4\r\ndata\r\n0\r\ngrpc-status: 0\r\n\r\n",
+    );
+
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.ends_with("size=4 status=0"), "{}", data);
+}
+
+#[actix_rt::test]
+async fn test_h1_echo_trailers() {
+    let srv = test_server(|| HttpService::build().h1(EchoService).tcp()).await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(
+        b"POST /test HTTP/1.1\r\nTransfer-Encoding: chunked\r\nTE: trailers\r\n\
+        Connection: close\r\n\r\n4\r\ndata\r\n0\r\ngrpc-status: 0\r\n\r\n",
+    );
+
+    let mut data = String::new();
+    let _ = stream.read_to_string(&mut data);
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"), "{}", data);
+    assert!(data.contains("transfer-encoding: chunked\r\n"), "{}", data);
+    assert!(
+        data.ends_with("\r\n\r\n4\r\ndata\r\n0\r\ngrpc-status: 0\r\n\r\n"),
+        "{}",
+        data
+    );
+}
+
 #[actix_rt::test]
 async fn test_slow_request() {
     let srv = test_server(|| {
@@ -636,6 +706,68 @@ async fn test_h1_body_chunked_implicit() {
     assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
 }
 
+struct TrailersBody(Option<Bytes>);
+
+impl MessageBody for TrailersBody {
+    type Error = Infallible;
+
+    fn size(&self) -> BodySize {
+        BodySize::Stream
+    }
+
+    fn poll_next(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, Self::Error>>> {
+        Poll::Ready(self.get_mut().0.take().map(Ok))
+    }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        _: &mut Context<'_>,
+    ) -> Poll<Result<Option<header::HeaderMap>, Self::Error>> {
+        let mut trailers = header::HeaderMap::new();
+        trailers.insert(
+            header::HeaderName::from_static("grpc-status"),
+            header::HeaderValue::from_static("0"),
+        );
+        Poll::Ready(Ok(Some(trailers)))
+    }
+}
+
+#[actix_rt::test]
+async fn test_h1_body_trailers() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .h1(|_| {
+                let body = TrailersBody(Some(Bytes::from_static(b"hello")));
+                ok::<_, Infallible>(Response::ok().set_body(body))
+            })
+            .tcp()
+    })
+    .await;
+
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ =
+        stream.write_all(b"GET / HTTP/1.1\r\nte: trailers\r\nconnection: close\r\n\r\n");
+    let mut data = Vec::new();
+    let _ = stream.read_to_end(&mut data);
+
+    let data = String::from_utf8(data).unwrap();
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(data.ends_with("\r\n5\r\nhello\r\n0\r\ngrpc-status: 0\r\n\r\n"));
+
+    // trailers are not sent to clients that did not ask for them
+    let mut stream = net::TcpStream::connect(srv.addr()).unwrap();
+    let _ = stream.write_all(b"GET / HTTP/1.1\r\nconnection: close\r\n\r\n");
+    let mut data = Vec::new();
+    let _ = stream.read_to_end(&mut data);
+
+    let data = String::from_utf8(data).unwrap();
+    assert!(data.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(data.ends_with("\r\n5\r\nhello\r\n0\r\n\r\n"));
+}
+
 #[actix_rt::test]
 async fn test_h1_response_http_error_handling() {
     let mut srv = test_server(|| {
@@ -708,3 +840,104 @@ async fn test_h1_on_connect() {
     let response = srv.get("/").send().await.unwrap();
     assert!(response.status().is_success());
 }
+
+#[actix_rt::test]
+async fn test_h1_on_connect_data() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .on_connect_ext(|_, data| {
+                data.insert(20isize);
+            })
+            .h1(|req: Request| {
+                let ext = req.extensions();
+                let data = ext.get::<ConnectionData>().unwrap();
+                assert_eq!(data.get::<isize>(), Some(&20));
+                ok::<_, Infallible>(Response::ok())
+            })
+            .tcp()
+    })
+    .await;
+
+    // connection data is available to every request on the connection
+    for _ in 0..2 {
+        let response = srv.get("/").send().await.unwrap();
+        assert!(response.status().is_success());
+    }
+}
+
+/// Service that is not ready until `ready` is set, counting the requests it is called with.
+struct GatedService {
+    ready: Arc<AtomicBool>,
+    calls: Arc<AtomicUsize>,
+}
+
+impl actix_service::Service<Request> for GatedService {
+    type Response = Response<AnyBody>;
+    type Error = Infallible;
+    type Future = std::future::Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        if self.ready.load(Ordering::SeqCst) {
+            Poll::Ready(Ok(()))
+        } else {
+            // poll again until the test opens the gate
+            cx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    fn call(&self, _: Request) -> Self::Future {
+        self.calls.fetch_add(1, Ordering::SeqCst);
+        std::future::ready(Ok(Response::ok()))
+    }
+}
+
+#[actix_rt::test]
+async fn test_h2_pending_streams_on_close() {
+    let ready = Arc::new(AtomicBool::new(true));
+    let calls = Arc::new(AtomicUsize::new(0));
+
+    let srv = test_server({
+        let ready = ready.clone();
+        let calls = calls.clone();
+
+        move || {
+            let ready = ready.clone();
+            let calls = calls.clone();
+
+            HttpService::build()
+                .h2(actix_service::fn_factory(move || {
+                    std::future::ready(Ok::<_, ()>(GatedService {
+                        ready: ready.clone(),
+                        calls: calls.clone(),
+                    }))
+                }))
+                .tcp()
+        }
+    })
+    .await;
+
+    let io = actix_rt::net::TcpStream::connect(srv.addr()).await.unwrap();
+    let (mut client, conn) = h2::client::handshake(io).await.unwrap();
+    let conn = actix_rt::spawn(conn);
+
+    let req = ::http::Request::get("/").body(()).unwrap();
+    let (res, _) = client.send_request(req, true).unwrap();
+    assert!(res.await.unwrap().status().is_success());
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // next request is accepted and waits for the service; then the client goes away
+    ready.store(false, Ordering::SeqCst);
+    let req = ::http::Request::get("/").body(()).unwrap();
+    let (res, _) = client.send_request(req, true).unwrap();
+    sleep(Duration::from_millis(200)).await;
+    conn.abort();
+    drop((client, res));
+    sleep(Duration::from_millis(200)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 1);
+
+    // the waiting stream is still handed to the service once it is ready
+    ready.store(true, Ordering::SeqCst);
+    sleep(Duration::from_millis(200)).await;
+    assert_eq!(calls.load(Ordering::SeqCst), 2);
+}
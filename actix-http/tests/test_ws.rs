@@ -194,3 +194,101 @@ async fn test_simple() {
     let item = framed.next().await.unwrap().unwrap();
     assert_eq!(item, Frame::Close(Some(CloseCode::Normal.into())));
 }
+
+#[actix_rt::test]
+async fn test_unmasked_frame() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .upgrade(fn_factory(|| async {
+                Ok::<_, Infallible>(WsService::new())
+            }))
+            .finish(|_| async { Ok::<_, Infallible>(Response::not_found()) })
+            .tcp()
+    })
+    .await;
+
+    // h2 mode does not mask frames, which clients are required to do over HTTP/1.1
+    let framed = srv.ws().await.unwrap();
+    let mut framed = framed.replace_codec(ws::Codec::new().h2_mode());
+
+    framed.send(Message::Text("text".into())).await.unwrap();
+
+    let item = framed.next().await.unwrap().unwrap();
+    assert_eq!(item, Frame::Close(Some(CloseCode::Protocol.into())));
+}
+
+#[actix_rt::test]
+async fn test_session() {
+    let mut srv = test_server(|| {
+        HttpService::build()
+            .upgrade(ws::Service::new(|_req, mut session| async move {
+                while let Some(msg) = session.next().await {
+                    match msg? {
+                        Message::Text(text) => session.send(Message::Text(text)).await?,
+                        Message::Close(reason) => {
+                            session.send(Message::Close(reason)).await?;
+                            break;
+                        }
+                        _ => {}
+                    }
+                }
+
+                Ok::<_, ws::ProtocolError>(())
+            }))
+            .finish(|_| async { Ok::<_, Infallible>(Response::not_found()) })
+            .tcp()
+    })
+    .await;
+
+    let mut framed = srv.ws().await.unwrap();
+
+    framed
+        .send(Message::Continuation(Item::FirstText("te".into())))
+        .await
+        .unwrap();
+    framed
+        .send(Message::Continuation(Item::Last("xt".into())))
+        .await
+        .unwrap();
+
+    let item = framed.next().await.unwrap().unwrap();
+    assert_eq!(item, Frame::Text(Bytes::from_static(b"text")));
+
+    framed
+        .send(Message::Close(Some(CloseCode::Normal.into())))
+        .await
+        .unwrap();
+
+    let item = framed.next().await.unwrap().unwrap();
+    assert_eq!(item, Frame::Close(Some(CloseCode::Normal.into())));
+}
+
+#[actix_rt::test]
+async fn test_client_ws_connect() {
+    let srv = test_server(|| {
+        HttpService::build()
+            .upgrade(ws::Service::new(|_req, mut session| async move {
+                if let Some(msg) = session.next().await {
+                    session.send(msg?).await?;
+                }
+
+                Ok::<_, ws::ProtocolError>(())
+            }))
+            .finish(|_| async { Ok::<_, Infallible>(Response::not_found()) })
+            .tcp()
+    })
+    .await;
+
+    let (res, mut framed) =
+        actix_http::client::ws_connect(format!("ws://{}/", srv.addr()))
+            .await
+            .unwrap();
+    assert_eq!(
+        res.status,
+        actix_http::http::StatusCode::SWITCHING_PROTOCOLS
+    );
+
+    framed.send(Message::Text("text".into())).await.unwrap();
+    let item = framed.next().await.unwrap().unwrap();
+    assert_eq!(item, Frame::Text(Bytes::from_static(b"text")));
+}
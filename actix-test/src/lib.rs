@@ -47,7 +47,7 @@ use actix_web::{
 use awc::{error::PayloadError, Client, ClientRequest, ClientResponse, Connector};
 use futures_core::Stream;
 
-pub use actix_http_test::unused_addr;
+pub use actix_http_test::{server_url, unused_addr, uri_host};
 pub use actix_web::test::{
     call_service, default_service, init_service, load_stream, ok_service, read_body,
     read_body_json, read_response, read_response_json, TestRequest,
@@ -141,7 +141,11 @@ where
     // run server in separate thread
     thread::spawn(move || {
         let sys = rt::System::new();
-        let tcp = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let tcp = match cfg.listen {
+            Listen::Ipv4 => net::TcpListener::bind("127.0.0.1:0").unwrap(),
+            Listen::Ipv6 => net::TcpListener::bind("[::1]:0").unwrap(),
+            Listen::DualStack => actix_http_test::dual_stack_listener(),
+        };
         let local_addr = tcp.local_addr().unwrap();
         let factory = factory.clone();
         let srv_cfg = cfg.clone();
@@ -330,6 +334,13 @@ enum HttpVer {
     Both,
 }
 
+#[derive(Debug, Clone, Copy)]
+enum Listen {
+    Ipv4,
+    Ipv6,
+    DualStack,
+}
+
 #[derive(Clone)]
 enum StreamType {
     Tcp,
@@ -348,6 +359,7 @@ pub fn config() -> TestServerConfig {
 pub struct TestServerConfig {
     tp: HttpVer,
     stream: StreamType,
+    listen: Listen,
     client_timeout: u64,
 }
 
@@ -363,6 +375,7 @@ impl TestServerConfig {
         TestServerConfig {
             tp: HttpVer::Both,
             stream: StreamType::Tcp,
+            listen: Listen::Ipv4,
             client_timeout: 5000,
         }
     }
@@ -393,6 +406,23 @@ impl TestServerConfig {
         self
     }
 
+    /// Listen on the IPv6 loopback address `[::1]` instead of `127.0.0.1`.
+    ///
+    /// Server URLs use the bracketed address, e.g. `http://[::1]:8080/`.
+    pub fn ipv6(mut self) -> Self {
+        self.listen = Listen::Ipv6;
+        self
+    }
+
+    /// Listen on all addresses, accepting both IPv4 and IPv6 connections.
+    ///
+    /// Server URLs use `localhost`; build URLs for `127.0.0.1` and `[::1]` explicitly with
+    /// [`server_url`] and the port of [`TestServer::addr`].
+    pub fn dual_stack(mut self) -> Self {
+        self.listen = Listen::DualStack;
+        self
+    }
+
     /// Set client timeout in milliseconds for first request.
     pub fn client_timeout(mut self, val: u64) -> Self {
         self.client_timeout = val;
@@ -421,12 +451,7 @@ impl TestServer {
     /// Construct test server url
     pub fn url(&self, uri: &str) -> String {
         let scheme = if self.tls { "https" } else { "http" };
-
-        if uri.starts_with('/') {
-            format!("{}://localhost:{}{}", scheme, self.addr.port(), uri)
-        } else {
-            format!("{}://localhost:{}/{}", scheme, self.addr.port(), uri)
-        }
+        server_url(scheme, self.addr, uri)
     }
 
     /// Create `GET` request.
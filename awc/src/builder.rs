@@ -5,7 +5,10 @@ use std::rc::Rc;
 use std::time::Duration;
 
 use actix_http::{
-    client::{Connector, ConnectorService, TcpConnect, TcpConnectError, TcpConnection},
+    client::{
+        Connector, ConnectorService, HttpProtocol, IpVersion, TcpConnect, TcpConnectError,
+        TcpConnection,
+    },
     http::{self, header, Error as HttpError, HeaderMap, HeaderName, Uri},
 };
 use actix_rt::net::{ActixStream, TcpStream};
@@ -30,7 +33,11 @@ pub struct ClientBuilder<S = (), M = ()> {
     connector: Connector<S>,
     middleware: M,
     local_address: Option<IpAddr>,
+    ip_version: Option<IpVersion>,
+    protocol: Option<HttpProtocol>,
     max_redirects: u8,
+    max_decompressed_size: usize,
+    max_decompressed_chunk_size: usize,
 }
 
 impl ClientBuilder {
@@ -49,11 +56,15 @@ impl ClientBuilder {
             headers: HeaderMap::new(),
             timeout: Some(Duration::from_secs(5)),
             local_address: None,
+            ip_version: None,
+            protocol: None,
             connector: Connector::new(),
             max_http_version: None,
             stream_window_size: None,
             conn_window_size: None,
             max_redirects: 10,
+            max_decompressed_size: usize::MAX,
+            max_decompressed_chunk_size: usize::MAX,
         }
     }
 }
@@ -82,11 +93,15 @@ where
             headers: self.headers,
             timeout: self.timeout,
             local_address: self.local_address,
+            ip_version: self.ip_version,
+            protocol: self.protocol,
             connector,
             max_http_version: self.max_http_version,
             stream_window_size: self.stream_window_size,
             conn_window_size: self.conn_window_size,
             max_redirects: self.max_redirects,
+            max_decompressed_size: self.max_decompressed_size,
+            max_decompressed_chunk_size: self.max_decompressed_chunk_size,
         }
     }
 
@@ -111,6 +126,28 @@ where
         self
     }
 
+    /// Only connect to addresses of the given IP version.
+    ///
+    /// Can be overridden per request with [`ClientRequest::ip_version`].
+    ///
+    /// [`ClientRequest::ip_version`]: crate::ClientRequest::ip_version
+    pub fn ip_version(mut self, ip_version: IpVersion) -> Self {
+        self.ip_version = Some(ip_version);
+        self
+    }
+
+    /// Set HTTP protocol used for connections.
+    ///
+    /// Use [`HttpProtocol::Http2`] to send requests over plain connections with HTTP/2 prior
+    /// knowledge, or [`HttpProtocol::Http1`] to never use HTTP/2. Can be overridden per request
+    /// with [`ClientRequest::protocol`].
+    ///
+    /// [`ClientRequest::protocol`]: crate::ClientRequest::protocol
+    pub fn protocol(mut self, protocol: HttpProtocol) -> Self {
+        self.protocol = Some(protocol);
+        self
+    }
+
     /// Maximum supported HTTP major version.
     ///
     /// Supported versions are HTTP/1.1 and HTTP/2.
@@ -135,6 +172,29 @@ where
         self
     }
 
+    /// Set max size of decompressed response payload.
+    ///
+    /// Reading a compressed response payload that decompresses to more than this size fails with
+    /// [`PayloadError::DecodeOverflow`](crate::error::PayloadError::DecodeOverflow).
+    ///
+    /// By default there is no limit.
+    pub fn max_decompressed_size(mut self, size: usize) -> Self {
+        self.max_decompressed_size = size;
+        self
+    }
+
+    /// Set max size of data decompressed from a single received chunk of a response payload.
+    ///
+    /// Exceeding this size fails with
+    /// [`PayloadError::DecodeOverflow`](crate::error::PayloadError::DecodeOverflow). This guards
+    /// against small payloads with very high compression ratios.
+    ///
+    /// By default there is no limit.
+    pub fn max_decompressed_chunk_size(mut self, size: usize) -> Self {
+        self.max_decompressed_chunk_size = size;
+        self
+    }
+
     /// Indicates the initial window size (in octets) for
     /// HTTP2 stream-level flow control for received data.
     ///
@@ -226,7 +286,11 @@ where
             timeout: self.timeout,
             connector: self.connector,
             local_address: self.local_address,
+            ip_version: self.ip_version,
+            protocol: self.protocol,
             max_redirects: self.max_redirects,
+            max_decompressed_size: self.max_decompressed_size,
+            max_decompressed_chunk_size: self.max_decompressed_chunk_size,
         }
     }
 
@@ -255,6 +319,9 @@ where
     {
         let mut connector = self.connector;
 
+        if let Some(val) = self.protocol {
+            connector = connector.protocol(val);
+        }
         if let Some(val) = self.max_http_version {
             connector = connector.max_http_version(val);
         };
@@ -267,6 +334,9 @@ where
         if let Some(val) = self.local_address {
             connector = connector.local_address(val);
         }
+        if let Some(val) = self.ip_version {
+            connector = connector.ip_version(val);
+        }
 
         let connector = DefaultConnector::new(connector.finish());
         let connector = boxed::rc_service(self.middleware.new_transform(connector));
@@ -275,6 +345,8 @@ where
             headers: Rc::new(self.headers),
             timeout: self.timeout,
             connector,
+            max_decompressed_size: self.max_decompressed_size,
+            max_decompressed_chunk_size: self.max_decompressed_chunk_size,
         })
     }
 }
@@ -0,0 +1,660 @@
+//! Parsing of `206 Partial Content` responses.
+
+use std::{
+    cell::RefCell,
+    convert::TryFrom,
+    fmt,
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+};
+
+use actix_http::{
+    error::PayloadError,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode},
+    HttpMessage,
+};
+use bytes::{Buf as _, Bytes, BytesMut};
+use futures_core::{ready, Stream};
+
+use crate::{error::ByteRangesError, ClientResponse};
+
+/// Max size of the headers of a single part.
+const MAX_HEADERS_SIZE: usize = 8 * 1024;
+
+/// Byte range of a part, as sent in its `Content-Range` header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ByteRange {
+    /// Position of the first byte of the part.
+    pub start: u64,
+
+    /// Position of the last byte of the part, inclusive.
+    pub end: u64,
+
+    /// Length of the complete representation, if known.
+    pub complete_length: Option<u64>,
+}
+
+impl ByteRange {
+    /// Parse `Content-Range` header value, e.g. `bytes 0-499/1234` or `bytes 500-999/*`.
+    fn parse(value: &str) -> Option<Self> {
+        let range = value.trim().strip_prefix("bytes ")?;
+        let (range, complete_length) = split_once(range, '/')?;
+        let (start, end) = split_once(range, '-')?;
+
+        let start = start.parse().ok()?;
+        let end = end.parse().ok()?;
+        let complete_length = match complete_length {
+            "*" => None,
+            len => Some(len.parse().ok()?),
+        };
+
+        if end < start {
+            return None;
+        }
+
+        Some(ByteRange {
+            start,
+            end,
+            complete_length,
+        })
+    }
+}
+
+fn split_once(value: &str, delimiter: char) -> Option<(&str, &str)> {
+    let mut parts = value.splitn(2, delimiter);
+    Some((parts.next()?, parts.next()?))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Skipping data before the first boundary.
+    Preamble,
+
+    /// Reading the rest of a boundary line.
+    Boundary,
+
+    /// Reading headers of a part.
+    Headers,
+
+    /// Reading body of a part, up to the next boundary.
+    Body,
+
+    /// Not yet returned the part of a single range response.
+    Single,
+
+    /// Reading body of a single range response, up to the end of the payload.
+    SingleBody,
+
+    /// All parts have been read.
+    Done,
+}
+
+struct Inner<S> {
+    res: ClientResponse<S>,
+    buf: BytesMut,
+    /// `CRLF "--" boundary`, preceding every boundary after the first.
+    delimiter: Bytes,
+    state: State,
+    eof: bool,
+    /// Sequence number of the part whose body is being read.
+    part: usize,
+}
+
+impl<S> Inner<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+{
+    /// Read next chunk of the payload into buffer.
+    fn poll_read(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), ByteRangesError>> {
+        if self.eof {
+            self.state = State::Done;
+            return Poll::Ready(Err(ByteRangesError::Malformed));
+        }
+
+        match ready!(Pin::new(&mut self.res).poll_next(cx)) {
+            Some(Ok(chunk)) => self.buf.extend_from_slice(&chunk),
+            Some(Err(err)) => {
+                self.state = State::Done;
+                return Poll::Ready(Err(err.into()));
+            }
+            None => self.eof = true,
+        }
+
+        Poll::Ready(Ok(()))
+    }
+
+    /// Read next chunk of the current part's body. Returns `None` at the end of the part.
+    fn poll_body(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<Bytes, ByteRangesError>>> {
+        loop {
+            match self.state {
+                State::Body => {
+                    if let Some(idx) = find(&self.buf, &self.delimiter) {
+                        if idx > 0 {
+                            return Poll::Ready(Some(Ok(self.buf.split_to(idx).freeze())));
+                        }
+
+                        self.buf.advance(self.delimiter.len());
+                        self.state = State::Boundary;
+                        return Poll::Ready(None);
+                    }
+
+                    // keep bytes that may be the start of a delimiter
+                    let keep = self.delimiter.len() - 1;
+                    if self.buf.len() > keep {
+                        let len = self.buf.len() - keep;
+                        return Poll::Ready(Some(Ok(self.buf.split_to(len).freeze())));
+                    }
+                }
+
+                State::SingleBody => {
+                    if !self.buf.is_empty() {
+                        return Poll::Ready(Some(Ok(self.buf.split().freeze())));
+                    }
+
+                    if self.eof {
+                        self.state = State::Done;
+                        return Poll::Ready(None);
+                    }
+                }
+
+                _ => return Poll::Ready(None),
+            }
+
+            if let Err(err) = ready!(self.poll_read(cx)) {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+    }
+
+    /// Read headers of the next part.
+    fn poll_headers(
+        &mut self,
+        cx: &mut Context<'_>,
+    ) -> Poll<Option<Result<(ByteRange, HeaderMap), ByteRangesError>>> {
+        loop {
+            match self.state {
+                State::Preamble => {
+                    let dash_boundary = &self.delimiter[2..];
+
+                    if let Some(idx) = find(&self.buf, dash_boundary) {
+                        self.buf.advance(idx + dash_boundary.len());
+                        self.state = State::Boundary;
+                        continue;
+                    }
+
+                    let keep = dash_boundary.len() - 1;
+                    if self.buf.len() > keep {
+                        let len = self.buf.len() - keep;
+                        self.buf.advance(len);
+                    }
+                }
+
+                State::Boundary => {
+                    if self.buf.starts_with(b"--") {
+                        // close delimiter, epilogue is ignored
+                        self.state = State::Done;
+                        continue;
+                    }
+
+                    // boundary line may end with transport padding
+                    if let Some(idx) = find(&self.buf, b"\r\n") {
+                        if self.buf[..idx].iter().any(|&b| b != b' ' && b != b'\t') {
+                            self.state = State::Done;
+                            return Poll::Ready(Some(Err(ByteRangesError::Malformed)));
+                        }
+
+                        self.buf.advance(idx + 2);
+                        self.state = State::Headers;
+                        continue;
+                    }
+                }
+
+                State::Headers => {
+                    if let Some(idx) = find(&self.buf, b"\r\n\r\n") {
+                        let headers = self.buf.split_to(idx + 4);
+
+                        return match parse_headers(&headers[..idx]) {
+                            Some((range, headers)) => {
+                                self.state = State::Body;
+                                self.part += 1;
+                                Poll::Ready(Some(Ok((range, headers))))
+                            }
+                            None => {
+                                self.state = State::Done;
+                                Poll::Ready(Some(Err(ByteRangesError::Malformed)))
+                            }
+                        };
+                    }
+
+                    if self.buf.len() > MAX_HEADERS_SIZE {
+                        self.state = State::Done;
+                        return Poll::Ready(Some(Err(ByteRangesError::Malformed)));
+                    }
+                }
+
+                // skip rest of the previous part
+                State::Body | State::SingleBody => match ready!(self.poll_body(cx)) {
+                    Some(Ok(_)) => continue,
+                    Some(Err(err)) => return Poll::Ready(Some(Err(err))),
+                    None => continue,
+                },
+
+                State::Single | State::Done => return Poll::Ready(None),
+            }
+
+            if let Err(err) = ready!(self.poll_read(cx)) {
+                return Poll::Ready(Some(Err(err)));
+            }
+        }
+    }
+}
+
+/// Parse part headers; parts without a valid `Content-Range` header are rejected.
+fn parse_headers(data: &[u8]) -> Option<(ByteRange, HeaderMap)> {
+    let mut headers = HeaderMap::new();
+
+    for line in data.split(|&b| b == b'\n') {
+        let line = match line.split_last() {
+            Some((b'\r', line)) => line,
+            _ => line,
+        };
+        let idx = line.iter().position(|&b| b == b':')?;
+
+        let name = HeaderName::try_from(&line[..idx]).ok()?;
+        let value = HeaderValue::from_bytes(trim(&line[idx + 1..])).ok()?;
+        headers.append(name, value);
+    }
+
+    let range = headers
+        .get(header::CONTENT_RANGE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(ByteRange::parse)?;
+
+    Some((range, headers))
+}
+
+fn trim(value: &[u8]) -> &[u8] {
+    let is_ws = |b: &u8| *b == b' ' || *b == b'\t';
+
+    let start = value.iter().position(|b| !is_ws(b)).unwrap_or(value.len());
+    let end = value
+        .iter()
+        .rposition(|b| !is_ws(b))
+        .map_or(start, |idx| idx + 1);
+    &value[start..end]
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+/// Stream of the parts of a `206 Partial Content` response.
+///
+/// Created with [`ClientResponse::byteranges`]. Bodies of `multipart/byteranges` responses are
+/// parsed while they are received; every part is returned with its byte range and headers and
+/// streams its own body. Responses for a single range, with a `Content-Range` header, yield one
+/// part with the whole response body.
+///
+/// The body of a part has to be read before the next part is polled; otherwise the remaining
+/// bytes of the part are skipped.
+///
+/// # Examples
+/// ```no_run
+/// use futures_util::StreamExt as _;
+///
+/// # #[actix_rt::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let res = awc::Client::new()
+///     .get("http://www.rust-lang.org")
+///     .insert_header(("Range", "bytes=0-99,200-299"))
+///     .send()
+///     .await?;
+///
+/// let mut parts = res.byteranges()?;
+///
+/// while let Some(part) = parts.next().await {
+///     let mut part = part?;
+///     println!("range: {:?}", part.range());
+///
+///     while let Some(chunk) = part.next().await {
+///         println!("{} bytes", chunk?.len());
+///     }
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub struct ByteRanges<S> {
+    inner: Rc<RefCell<Inner<S>>>,
+    /// Range of a single range response.
+    single: Option<ByteRange>,
+}
+
+impl<S> ByteRanges<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+{
+    /// Create parts stream for a response.
+    ///
+    /// Fails if the response is not a `206 Partial Content` response with either a
+    /// `multipart/byteranges` body or a `Content-Range` header.
+    pub fn new(res: ClientResponse<S>) -> Result<Self, ByteRangesError> {
+        if res.status() != StatusCode::PARTIAL_CONTENT {
+            return Err(ByteRangesError::NotByteRanges);
+        }
+
+        let mime = res.mime_type().map_err(|_| ByteRangesError::Malformed)?;
+
+        let (boundary, single) = match mime {
+            Some(ref mime)
+                if mime.type_() == mime::MULTIPART && mime.subtype() == "byteranges" =>
+            {
+                let boundary = mime
+                    .get_param(mime::BOUNDARY)
+                    .ok_or(ByteRangesError::Malformed)?;
+                (format!("\r\n--{}", boundary.as_str()), None)
+            }
+            _ => {
+                let range = res
+                    .headers()
+                    .get(header::CONTENT_RANGE)
+                    .ok_or(ByteRangesError::NotByteRanges)?
+                    .to_str()
+                    .ok()
+                    .and_then(ByteRange::parse)
+                    .ok_or(ByteRangesError::Malformed)?;
+                (String::from("\r\n--"), Some(range))
+            }
+        };
+
+        Ok(ByteRanges {
+            inner: Rc::new(RefCell::new(Inner {
+                res,
+                buf: BytesMut::new(),
+                delimiter: Bytes::from(boundary),
+                state: if single.is_some() {
+                    State::Single
+                } else {
+                    State::Preamble
+                },
+                eof: false,
+                part: 0,
+            })),
+            single,
+        })
+    }
+}
+
+impl<S> Stream for ByteRanges<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+{
+    type Item = Result<RangePart<S>, ByteRangesError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+        let mut inner = this.inner.borrow_mut();
+
+        if inner.state == State::Single {
+            inner.state = State::SingleBody;
+            inner.part += 1;
+
+            let mut headers = HeaderMap::new();
+            if let Some(value) = inner.res.headers().get(header::CONTENT_TYPE) {
+                headers.insert(header::CONTENT_TYPE, value.clone());
+            }
+            if let Some(value) = inner.res.headers().get(header::CONTENT_RANGE) {
+                headers.insert(header::CONTENT_RANGE, value.clone());
+            }
+
+            return Poll::Ready(Some(Ok(RangePart {
+                range: this.single.expect("single range response without range"),
+                headers,
+                part: inner.part,
+                inner: this.inner.clone(),
+            })));
+        }
+
+        match ready!(inner.poll_headers(cx)) {
+            Some(Ok((range, headers))) => Poll::Ready(Some(Ok(RangePart {
+                range,
+                headers,
+                part: inner.part,
+                inner: this.inner.clone(),
+            }))),
+            Some(Err(err)) => Poll::Ready(Some(Err(err))),
+            None => Poll::Ready(None),
+        }
+    }
+}
+
+/// Part of a `206 Partial Content` response; a stream of the part's body.
+pub struct RangePart<S> {
+    range: ByteRange,
+    headers: HeaderMap,
+    part: usize,
+    inner: Rc<RefCell<Inner<S>>>,
+}
+
+impl<S> RangePart<S> {
+    /// Returns byte range of the part.
+    pub fn range(&self) -> ByteRange {
+        self.range
+    }
+
+    /// Returns headers of the part.
+    pub fn headers(&self) -> &HeaderMap {
+        &self.headers
+    }
+}
+
+impl<S> Stream for RangePart<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+{
+    type Item = Result<Bytes, ByteRangesError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let mut inner = self.inner.borrow_mut();
+
+        // parts that have been skipped are done
+        if inner.part != self.part {
+            return Poll::Ready(None);
+        }
+
+        inner.poll_body(cx)
+    }
+}
+
+impl<S> fmt::Debug for RangePart<S> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("RangePart")
+            .field("range", &self.range)
+            .field("headers", &self.headers)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_http::{Payload, ResponseHead};
+    use futures_util::{stream, StreamExt as _};
+
+    use super::*;
+
+    fn response(content_type: &str, chunks: Vec<&'static [u8]>) -> ClientResponse {
+        response_with_range(content_type, None, chunks)
+    }
+
+    fn response_with_range(
+        content_type: &str,
+        range: Option<&'static str>,
+        chunks: Vec<&'static [u8]>,
+    ) -> ClientResponse {
+        let mut head = ResponseHead::new(StatusCode::PARTIAL_CONTENT);
+        head.headers.insert(
+            header::CONTENT_TYPE,
+            HeaderValue::from_str(content_type).unwrap(),
+        );
+        if let Some(range) = range {
+            head.headers
+                .insert(header::CONTENT_RANGE, HeaderValue::from_static(range));
+        }
+
+        let payload = stream::iter(
+            chunks
+                .into_iter()
+                .map(|chunk| Ok(Bytes::from_static(chunk))),
+        );
+        ClientResponse::new(head, Payload::Stream(Box::pin(payload)))
+    }
+
+    async fn collect<S>(mut parts: ByteRanges<S>) -> Vec<(ByteRange, Vec<u8>)>
+    where
+        S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+    {
+        let mut res = Vec::new();
+
+        while let Some(part) = parts.next().await {
+            let mut part = part.unwrap();
+            let mut body = Vec::new();
+
+            while let Some(chunk) = part.next().await {
+                body.extend_from_slice(&chunk.unwrap());
+            }
+
+            res.push((part.range(), body));
+        }
+
+        res
+    }
+
+    #[test]
+    fn parse_range() {
+        assert_eq!(
+            ByteRange::parse("bytes 0-499/1234"),
+            Some(ByteRange {
+                start: 0,
+                end: 499,
+                complete_length: Some(1234)
+            })
+        );
+        assert_eq!(
+            ByteRange::parse("bytes 500-999/*"),
+            Some(ByteRange {
+                start: 500,
+                end: 999,
+                complete_length: None
+            })
+        );
+        assert_eq!(ByteRange::parse("bytes */1234"), None);
+        assert_eq!(ByteRange::parse("bytes 9-1/10"), None);
+        assert_eq!(ByteRange::parse("seconds 1-2/3"), None);
+    }
+
+    #[actix_rt::test]
+    async fn multipart() {
+        // chunks are split inside boundaries and headers
+        let res = response(
+            "multipart/byteranges; boundary=THIS_STRING_SEPARATES",
+            vec![
+                b"preamble\r\n--THIS_STRING_SEPARATES\r\nContent-Type: text/plain\r\n",
+                b"Content-Range: bytes 0-4/20\r\n\r\nhello\r\n--THIS_STRING_",
+                b"SEPARATES  \r\nContent-Range: bytes 10-",
+                b"19/20\r\n\r\nworld\r\n--THIS\r\n\r\n--THIS_STRING_SEPARATES--\r\nepilogue",
+            ],
+        );
+
+        let parts = collect(res.byteranges().unwrap()).await;
+
+        assert_eq!(parts.len(), 2);
+        assert_eq!(
+            parts[0],
+            (
+                ByteRange {
+                    start: 0,
+                    end: 4,
+                    complete_length: Some(20)
+                },
+                b"hello".to_vec()
+            )
+        );
+        assert_eq!(parts[1].0.start, 10);
+        assert_eq!(parts[1].1, b"world\r\n--THIS\r\n".to_vec());
+    }
+
+    #[actix_rt::test]
+    async fn skip_unread_parts() {
+        let res = response(
+            "multipart/byteranges; boundary=b",
+            vec![
+                b"--b\r\nContent-Range: bytes 0-2/6\r\n\r\nabc\r\n",
+                b"--b\r\nContent-Range: bytes 3-5/6\r\n\r\ndef\r\n--b--",
+            ],
+        );
+
+        let mut parts = res.byteranges().unwrap();
+
+        let mut first = parts.next().await.unwrap().unwrap();
+        let mut second = parts.next().await.unwrap().unwrap();
+        assert!(first.next().await.is_none());
+        assert_eq!(second.range().start, 3);
+        assert_eq!(&second.next().await.unwrap().unwrap()[..], b"def");
+        assert!(second.next().await.is_none());
+        assert!(parts.next().await.is_none());
+    }
+
+    #[actix_rt::test]
+    async fn malformed() {
+        let res = response(
+            "multipart/byteranges; boundary=b",
+            vec![b"--b\r\nContent-Type: text/plain\r\n\r\nabc\r\n--b--"],
+        );
+        let mut parts = res.byteranges().unwrap();
+        assert!(matches!(
+            parts.next().await,
+            Some(Err(ByteRangesError::Malformed))
+        ));
+
+        // body ends without close delimiter
+        let res = response(
+            "multipart/byteranges; boundary=b",
+            vec![b"--b\r\nContent-Range: bytes 0-2/6\r\n\r\nabc"],
+        );
+        let mut parts = res.byteranges().unwrap();
+        let mut part = parts.next().await.unwrap().unwrap();
+        assert!(matches!(
+            part.next().await,
+            Some(Err(ByteRangesError::Malformed))
+        ));
+    }
+
+    #[actix_rt::test]
+    async fn single_range() {
+        let res = response_with_range("text/plain", Some("bytes 5-9/10"), vec![b"hel", b"lo"]);
+
+        let parts = collect(res.byteranges().unwrap()).await;
+        assert_eq!(
+            parts,
+            vec![(
+                ByteRange {
+                    start: 5,
+                    end: 9,
+                    complete_length: Some(10)
+                },
+                b"hello".to_vec()
+            )]
+        );
+
+        let res = response("text/plain", vec![]);
+        assert!(matches!(
+            res.byteranges(),
+            Err(ByteRangesError::NotByteRanges)
+        ));
+    }
+}
@@ -10,7 +10,8 @@ use actix_codec::Framed;
 use actix_http::{
     body::Body,
     client::{
-        Connect as ClientConnect, ConnectError, Connection, ConnectionIo, SendRequestError,
+        Connect as ClientConnect, ConnectError, Connection, ConnectionIo, HttpProtocol,
+        IpVersion, SendRequestError, Timings,
     },
     h1::ClientCodec,
     Payload, RequestHead, RequestHeadType, ResponseHead,
@@ -85,14 +86,29 @@ where
     fn call(&self, req: ConnectRequest) -> Self::Future {
         // connect to the host
         let fut = match req {
-            ConnectRequest::Client(ref head, .., addr) => self.connector.call(ClientConnect {
-                uri: head.as_ref().uri.clone(),
-                addr,
-            }),
-            ConnectRequest::Tunnel(ref head, addr) => self.connector.call(ClientConnect {
-                uri: head.uri.clone(),
-                addr,
-            }),
+            ConnectRequest::Client(ref head, .., addr) => {
+                let head = head.as_ref();
+                let mut connect = ClientConnect::new(head.uri.clone());
+                connect.addr = addr;
+                connect.ip_version = head.extensions().get::<IpVersion>().copied();
+                connect.protocol = head.extensions().get::<HttpProtocol>().copied();
+
+                // timings of the connection and the request are recorded in the same handle
+                if let Some(timings) = head.extensions().get::<Timings>() {
+                    connect.set_timings(timings.clone());
+                }
+
+                self.connector.call(connect)
+            }
+            ConnectRequest::Tunnel(ref head, addr) => {
+                let mut connect = ClientConnect::new(head.uri.clone());
+                connect.addr = addr;
+                connect.ip_version = head.extensions().get::<IpVersion>().copied();
+                // tunnels are only supported over HTTP/1.1
+                connect.protocol = Some(HttpProtocol::Http1);
+
+                self.connector.call(connect)
+            }
         };
 
         ConnectRequestFuture::Connection {
@@ -1,5 +1,6 @@
 //! HTTP client errors
 
+pub use actix_http::client::WsConnectError as WsClientError;
 pub use actix_http::client::{ConnectError, FreezeRequestError, InvalidUrl, SendRequestError};
 pub use actix_http::error::PayloadError;
 pub use actix_http::http::Error as HttpError;
@@ -8,59 +9,8 @@ pub use actix_http::ws::ProtocolError as WsProtocolError;
 
 use serde_json::error::Error as JsonError;
 
-use actix_http::http::{header::HeaderValue, StatusCode};
 use derive_more::{Display, From};
 
-/// Websocket client error
-#[derive(Debug, Display, From)]
-pub enum WsClientError {
-    /// Invalid response status
-    #[display(fmt = "Invalid response status")]
-    InvalidResponseStatus(StatusCode),
-
-    /// Invalid upgrade header
-    #[display(fmt = "Invalid upgrade header")]
-    InvalidUpgradeHeader,
-
-    /// Invalid connection header
-    #[display(fmt = "Invalid connection header")]
-    InvalidConnectionHeader(HeaderValue),
-
-    /// Missing Connection header
-    #[display(fmt = "Missing Connection header")]
-    MissingConnectionHeader,
-
-    /// Missing Sec-Websocket-Accept header
-    #[display(fmt = "Missing Sec-Websocket-Accept header")]
-    MissingWebSocketAcceptHeader,
-
-    /// Invalid challenge response
-    #[display(fmt = "Invalid challenge response")]
-    InvalidChallengeResponse([u8; 28], HeaderValue),
-
-    /// Protocol error
-    #[display(fmt = "{}", _0)]
-    Protocol(WsProtocolError),
-
-    /// Send request error
-    #[display(fmt = "{}", _0)]
-    SendRequest(SendRequestError),
-}
-
-impl std::error::Error for WsClientError {}
-
-impl From<InvalidUrl> for WsClientError {
-    fn from(err: InvalidUrl) -> Self {
-        WsClientError::SendRequest(err.into())
-    }
-}
-
-impl From<HttpError> for WsClientError {
-    fn from(err: HttpError) -> Self {
-        WsClientError::SendRequest(err.into())
-    }
-}
-
 /// A set of errors that can occur during parsing json payloads
 #[derive(Debug, Display, From)]
 pub enum JsonPayloadError {
@@ -76,3 +26,19 @@ pub enum JsonPayloadError {
 }
 
 impl std::error::Error for JsonPayloadError {}
+
+/// A set of errors that can occur while reading `206 Partial Content` responses.
+#[derive(Debug, Display, From)]
+pub enum ByteRangesError {
+    /// Response is not a partial content response
+    #[display(fmt = "Response is not a partial content response")]
+    NotByteRanges,
+    /// Malformed `multipart/byteranges` body or `Content-Range` header
+    #[display(fmt = "Malformed byte ranges response")]
+    Malformed,
+    /// Payload error
+    #[display(fmt = "Error that occur during reading payload: {}", _0)]
+    Payload(PayloadError),
+}
+
+impl std::error::Error for ByteRangesError {}
@@ -1,5 +1,6 @@
 use std::{convert::TryFrom, error::Error as StdError, net, rc::Rc, time::Duration};
 
+use actix_codec::AsyncRead;
 use bytes::Bytes;
 use futures_core::Stream;
 use serde::Serialize;
@@ -93,6 +94,21 @@ impl FrozenClientRequest {
         )
     }
 
+    /// Send a body read from `reader`, with a `Content-Length` header if `len` is known.
+    pub fn send_reader<R>(&self, reader: R, len: Option<u64>) -> SendClientRequest
+    where
+        R: AsyncRead + Unpin + 'static,
+    {
+        RequestSender::Rc(self.head.clone(), None).send_reader(
+            self.addr,
+            self.response_decompress,
+            self.timeout,
+            &self.config,
+            reader,
+            len,
+        )
+    }
+
     /// Send an empty body.
     pub fn send(&self) -> SendClientRequest {
         RequestSender::Rc(self.head.clone(), None).send(
@@ -222,6 +238,26 @@ impl FrozenSendBuilder {
         )
     }
 
+    /// Complete request construction and send a body read from `reader`, with a
+    /// `Content-Length` header if `len` is known.
+    pub fn send_reader<R>(self, reader: R, len: Option<u64>) -> SendClientRequest
+    where
+        R: AsyncRead + Unpin + 'static,
+    {
+        if let Some(e) = self.err {
+            return e.into();
+        }
+
+        RequestSender::Rc(self.req.head, Some(self.extra_headers)).send_reader(
+            self.req.addr,
+            self.req.response_decompress,
+            self.req.timeout,
+            &self.req.config,
+            reader,
+            len,
+        )
+    }
+
     /// Complete request construction and send an empty body.
     pub fn send(self) -> SendClientRequest {
         if let Some(e) = self.err {
@@ -120,6 +120,7 @@ use actix_rt::net::TcpStream;
 use actix_service::Service;
 
 mod builder;
+mod byteranges;
 mod connect;
 pub mod error;
 mod frozen;
@@ -131,6 +132,7 @@ pub mod test;
 pub mod ws;
 
 pub use self::builder::ClientBuilder;
+pub use self::byteranges::{ByteRange, ByteRanges, RangePart};
 pub use self::connect::{BoxConnectorService, BoxedSocket, ConnectRequest, ConnectResponse};
 pub use self::frozen::{FrozenClientRequest, FrozenSendBuilder};
 pub use self::request::ClientRequest;
@@ -166,6 +168,8 @@ pub(crate) struct ClientConfig {
     pub(crate) connector: BoxConnectorService,
     pub(crate) headers: Rc<HeaderMap>,
     pub(crate) timeout: Option<Duration>,
+    pub(crate) max_decompressed_size: usize,
+    pub(crate) max_decompressed_chunk_size: usize,
 }
 
 impl Default for Client {
@@ -0,0 +1,232 @@
+use std::{future::Future, rc::Rc};
+
+use actix_http::{
+    client::SendRequestError,
+    http::{
+        header::{self, HeaderMap, HeaderValue, InvalidHeaderValue},
+        Error as HttpError,
+    },
+    RequestHead, RequestHeadType,
+};
+use actix_service::Service;
+use futures_core::future::LocalBoxFuture;
+
+use super::Transform;
+
+use crate::connect::{ConnectRequest, ConnectResponse};
+
+/// Credential sent in the `Authorization` header of requests.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Credential {
+    /// HTTP basic authorization.
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+
+    /// HTTP bearer authentication.
+    Bearer(String),
+}
+
+impl Credential {
+    /// Returns `Authorization` header value for this credential.
+    pub fn header_value(&self) -> Result<HeaderValue, InvalidHeaderValue> {
+        match self {
+            Credential::Basic { username, password } => {
+                let auth = match password {
+                    Some(password) => format!("{}:{}", username, password),
+                    None => format!("{}:", username),
+                };
+
+                HeaderValue::from_str(&format!("Basic {}", base64::encode(&auth)))
+            }
+            Credential::Bearer(token) => HeaderValue::from_str(&format!("Bearer {}", token)),
+        }
+    }
+}
+
+/// Middleware that asks an async credential provider for the `Authorization` header of every
+/// request sent by a client.
+///
+/// The provider is called with the head of each request that does not already have an
+/// `Authorization` header, so credentials set on a request or client-wide with `basic_auth` or
+/// `bearer_auth` take precedence. Returning `Ok(None)` sends the request without credentials.
+///
+/// Since the provider is asynchronous, it can refresh expiring tokens; keeping the current token
+/// in shared state lets all clients of an application rotate credentials in one place.
+///
+/// # Examples
+/// ```
+/// use std::{cell::RefCell, rc::Rc};
+///
+/// use awc::middleware::{Credential, Credentials};
+///
+/// let token = Rc::new(RefCell::new(String::from("initial-token")));
+///
+/// let client = awc::Client::builder()
+///     .wrap(Credentials::new(move |_head| {
+///         let token = token.clone();
+///         async move { Ok(Some(Credential::Bearer(token.borrow().clone()))) }
+///     }))
+///     .finish();
+/// ```
+pub struct Credentials<F> {
+    provider: Rc<F>,
+}
+
+impl<F, Fut> Credentials<F>
+where
+    F: Fn(&RequestHead) -> Fut + 'static,
+    Fut: Future<Output = Result<Option<Credential>, SendRequestError>> + 'static,
+{
+    /// Create middleware that gets credentials from `provider`.
+    pub fn new(provider: F) -> Self {
+        Self {
+            provider: Rc::new(provider),
+        }
+    }
+}
+
+impl<S, F, Fut> Transform<S, ConnectRequest> for Credentials<F>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+    F: Fn(&RequestHead) -> Fut + 'static,
+    Fut: Future<Output = Result<Option<Credential>, SendRequestError>> + 'static,
+{
+    type Transform = CredentialsService<S, F>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        CredentialsService {
+            provider: self.provider,
+            connector: Rc::new(service),
+        }
+    }
+}
+
+pub struct CredentialsService<S, F> {
+    provider: Rc<F>,
+    connector: Rc<S>,
+}
+
+impl<S, F, Fut> Service<ConnectRequest> for CredentialsService<S, F>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+    F: Fn(&RequestHead) -> Fut + 'static,
+    Fut: Future<Output = Result<Option<Credential>, SendRequestError>> + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(connector);
+
+    fn call(&self, req: ConnectRequest) -> Self::Future {
+        let has_auth = match req {
+            ConnectRequest::Client(ref head, ..) => {
+                head.as_ref().headers.contains_key(header::AUTHORIZATION)
+                    || head
+                        .extra_headers()
+                        .map_or(false, |headers| headers.contains_key(header::AUTHORIZATION))
+            }
+            ConnectRequest::Tunnel(ref head, _) => {
+                head.headers.contains_key(header::AUTHORIZATION)
+            }
+        };
+
+        if has_auth {
+            return Box::pin(self.connector.call(req));
+        }
+
+        let credential = match req {
+            ConnectRequest::Client(ref head, ..) => (self.provider)(head.as_ref()),
+            ConnectRequest::Tunnel(ref head, _) => (self.provider)(head),
+        };
+        let connector = self.connector.clone();
+
+        Box::pin(async move {
+            let mut req = req;
+
+            if let Some(credential) = credential.await? {
+                let value = credential
+                    .header_value()
+                    .map_err(|err| SendRequestError::Http(HttpError::from(err)))?;
+
+                match req {
+                    ConnectRequest::Client(RequestHeadType::Owned(ref mut head), ..)
+                    | ConnectRequest::Tunnel(ref mut head, _) => {
+                        head.headers.insert(header::AUTHORIZATION, value);
+                    }
+                    ConnectRequest::Client(RequestHeadType::Rc(_, ref mut extra), ..) => {
+                        extra
+                            .get_or_insert_with(HeaderMap::new)
+                            .insert(header::AUTHORIZATION, value);
+                    }
+                }
+            }
+
+            connector.call(req).await
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use actix_web::{web, App, HttpRequest, HttpResponse};
+
+    use super::*;
+    use crate::ClientBuilder;
+
+    async fn authorization(req: HttpRequest) -> HttpResponse {
+        let auth = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .map_or("", |auth| auth.to_str().unwrap());
+        HttpResponse::Ok().body(auth.to_owned())
+    }
+
+    #[test]
+    fn credential_header_value() {
+        let basic = Credential::Basic {
+            username: "username".to_owned(),
+            password: Some("password".to_owned()),
+        };
+        assert_eq!(
+            basic.header_value().unwrap(),
+            "Basic dXNlcm5hbWU6cGFzc3dvcmQ="
+        );
+
+        let bearer = Credential::Bearer("someS3cr3tAutht0k3n".to_owned());
+        assert_eq!(bearer.header_value().unwrap(), "Bearer someS3cr3tAutht0k3n");
+    }
+
+    #[actix_rt::test]
+    async fn provider_credentials() {
+        let srv = actix_test::start(|| App::new().default_service(web::to(authorization)));
+
+        let client = ClientBuilder::new()
+            .wrap(Credentials::new(|head: &RequestHead| {
+                let credential = if head.uri.path() == "/anonymous" {
+                    None
+                } else {
+                    Some(Credential::Bearer("token".to_owned()))
+                };
+                async move { Ok(credential) }
+            }))
+            .finish();
+
+        let mut res = client.get(srv.url("/")).send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "Bearer token");
+
+        let mut res = client.get(srv.url("/anonymous")).send().await.unwrap();
+        assert_eq!(res.body().await.unwrap(), "");
+
+        // explicit credentials take precedence
+        let mut res = client
+            .get(srv.url("/"))
+            .bearer_auth("explicit")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(res.body().await.unwrap(), "Bearer explicit");
+    }
+}
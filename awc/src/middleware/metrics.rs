@@ -0,0 +1,399 @@
+use std::{
+    pin::Pin,
+    rc::Rc,
+    task::{Context, Poll},
+    time::{Duration, Instant},
+};
+
+use actix_http::{
+    body::{BodySize, MessageBody as _},
+    client::{ConnectError, SendRequestError, Timings},
+    error::PayloadError,
+    http::{Method, StatusCode, Uri},
+    Payload, PayloadStream, RequestHead,
+};
+use actix_service::Service;
+use bytes::Bytes;
+use futures_core::{future::LocalBoxFuture, ready, Stream};
+
+use super::Transform;
+
+use crate::connect::{ConnectRequest, ConnectResponse};
+
+/// Receives metrics of requests sent through the [`Metrics`] middleware.
+///
+/// All methods do nothing by default, so recorders only implement the events they report, e.g. to
+/// a metrics registry or tracing system.
+pub trait MetricsRecorder {
+    /// Called before a request is sent.
+    fn request_started(&self, _head: &RequestHead) {}
+
+    /// Called once the response payload of a request is read to the end, fails or is dropped.
+    fn request_completed(&self, _metrics: &RequestMetrics) {}
+
+    /// Called when a request fails without a response.
+    fn request_failed(&self, _metrics: &RequestMetrics, _err: &SendRequestError) {}
+
+    /// Called when connecting to the host of a request fails, before
+    /// [`request_failed`](Self::request_failed).
+    fn connect_error(&self, _uri: &Uri, _err: &ConnectError) {}
+}
+
+/// Class of a response status code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum StatusClass {
+    /// `1xx` status codes.
+    Informational,
+
+    /// `2xx` status codes.
+    Success,
+
+    /// `3xx` status codes.
+    Redirection,
+
+    /// `4xx` status codes.
+    ClientError,
+
+    /// `5xx` status codes.
+    ServerError,
+}
+
+impl StatusClass {
+    /// Returns class of the status code, or `None` for non-standard codes above `599`.
+    pub fn of(status: StatusCode) -> Option<Self> {
+        match status.as_u16() / 100 {
+            1 => Some(StatusClass::Informational),
+            2 => Some(StatusClass::Success),
+            3 => Some(StatusClass::Redirection),
+            4 => Some(StatusClass::ClientError),
+            5 => Some(StatusClass::ServerError),
+            _ => None,
+        }
+    }
+}
+
+/// Metrics of a request, as passed to a [`MetricsRecorder`].
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct RequestMetrics {
+    /// Method of the request.
+    pub method: Method,
+
+    /// URI of the request.
+    pub uri: Uri,
+
+    /// Status of the response, if one was received.
+    pub status: Option<StatusCode>,
+
+    /// Size of the request body, if known before sending it.
+    pub bytes_sent: Option<u64>,
+
+    /// Number of response payload bytes read, before decompression.
+    pub bytes_received: u64,
+
+    /// Time from sending the request until the response payload ended or the request failed.
+    pub duration: Duration,
+
+    /// Time spent acquiring a connection from the client's pool.
+    pub acquire: Option<Duration>,
+}
+
+impl RequestMetrics {
+    /// Returns class of the response status, if one was received.
+    pub fn status_class(&self) -> Option<StatusClass> {
+        self.status.and_then(StatusClass::of)
+    }
+}
+
+/// Middleware that reports metrics of every request sent by a client to a [`MetricsRecorder`].
+///
+/// Requests are reported when they are started and once their response payload is read to the
+/// end, fails or is dropped, so the reported duration and size cover the whole response. Tunnel
+/// requests, e.g. WebSocket handshakes, are not reported.
+///
+/// # Examples
+/// ```
+/// use std::cell::Cell;
+///
+/// use awc::middleware::{Metrics, MetricsRecorder, RequestMetrics, StatusClass};
+///
+/// #[derive(Default)]
+/// struct ServerErrors(Cell<u64>);
+///
+/// impl MetricsRecorder for ServerErrors {
+///     fn request_completed(&self, metrics: &RequestMetrics) {
+///         if metrics.status_class() == Some(StatusClass::ServerError) {
+///             self.0.set(self.0.get() + 1);
+///         }
+///     }
+/// }
+///
+/// let client = awc::Client::builder()
+///     .wrap(Metrics::new(ServerErrors::default()))
+///     .finish();
+/// ```
+pub struct Metrics<R> {
+    recorder: Rc<R>,
+}
+
+impl<R: MetricsRecorder + 'static> Metrics<R> {
+    /// Create middleware reporting to given recorder.
+    pub fn new(recorder: R) -> Self {
+        Self::from_rc(Rc::new(recorder))
+    }
+
+    /// Create middleware reporting to a shared recorder, e.g. to read its state elsewhere.
+    pub fn from_rc(recorder: Rc<R>) -> Self {
+        Self { recorder }
+    }
+}
+
+impl<S, R> Transform<S, ConnectRequest> for Metrics<R>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+    R: MetricsRecorder + 'static,
+{
+    type Transform = MetricsService<S, R>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        MetricsService {
+            recorder: self.recorder,
+            connector: service,
+        }
+    }
+}
+
+pub struct MetricsService<S, R> {
+    recorder: Rc<R>,
+    connector: S,
+}
+
+impl<S, R> Service<ConnectRequest> for MetricsService<S, R>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+    R: MetricsRecorder + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(connector);
+
+    fn call(&self, req: ConnectRequest) -> Self::Future {
+        let (head, body) = match req {
+            ConnectRequest::Client(ref head, ref body, _) => (head.as_ref(), body),
+            ConnectRequest::Tunnel(..) => return Box::pin(self.connector.call(req)),
+        };
+
+        self.recorder.request_started(head);
+
+        // the connector records the pool's acquire time into this handle
+        let timings = Timings::new();
+        head.extensions_mut().insert(timings.clone());
+
+        let metrics = RequestMetrics {
+            method: head.method.clone(),
+            uri: head.uri.clone(),
+            status: None,
+            bytes_sent: match body.size() {
+                BodySize::None | BodySize::Empty => Some(0),
+                BodySize::Sized(len) => Some(len),
+                BodySize::Stream => None,
+            },
+            bytes_received: 0,
+            duration: Duration::from_secs(0),
+            acquire: None,
+        };
+
+        let recorder = self.recorder.clone();
+        let start = Instant::now();
+        let fut = self.connector.call(req);
+
+        Box::pin(async move {
+            match fut.await {
+                Ok(ConnectResponse::Client(res)) => {
+                    let res = res.map_body(|head, payload| {
+                        let metrics = RequestMetrics {
+                            status: Some(head.status),
+                            ..metrics
+                        };
+
+                        let payload: PayloadStream = Box::pin(MeteredPayload {
+                            payload,
+                            recorder: Some(recorder),
+                            metrics,
+                            timings,
+                            start,
+                        });
+                        Payload::Stream(payload)
+                    });
+
+                    Ok(ConnectResponse::Client(res))
+                }
+                Ok(res) => Ok(res),
+                Err(err) => {
+                    if let SendRequestError::Connect(ref err) = err {
+                        recorder.connect_error(&metrics.uri, err);
+                    }
+
+                    let metrics = RequestMetrics {
+                        duration: start.elapsed(),
+                        acquire: timings.acquire(),
+                        ..metrics
+                    };
+                    recorder.request_failed(&metrics, &err);
+
+                    Err(err)
+                }
+            }
+        })
+    }
+}
+
+/// Response payload reporting the request once it ends or is dropped.
+struct MeteredPayload<R: MetricsRecorder> {
+    payload: Payload,
+    recorder: Option<Rc<R>>,
+    metrics: RequestMetrics,
+    timings: Timings,
+    start: Instant,
+}
+
+impl<R: MetricsRecorder> MeteredPayload<R> {
+    fn complete(&mut self) {
+        if let Some(recorder) = self.recorder.take() {
+            self.metrics.duration = self.start.elapsed();
+            self.metrics.acquire = self.timings.acquire();
+            recorder.request_completed(&self.metrics);
+        }
+    }
+}
+
+impl<R: MetricsRecorder> Stream for MeteredPayload<R> {
+    type Item = Result<Bytes, PayloadError>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.get_mut();
+
+        match ready!(Pin::new(&mut this.payload).poll_next(cx)) {
+            Some(Ok(chunk)) => {
+                this.metrics.bytes_received += chunk.len() as u64;
+                Poll::Ready(Some(Ok(chunk)))
+            }
+            item => {
+                this.complete();
+                Poll::Ready(item)
+            }
+        }
+    }
+}
+
+impl<R: MetricsRecorder> Drop for MeteredPayload<R> {
+    fn drop(&mut self) {
+        self.complete();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+
+    use actix_web::{web, App, HttpResponse};
+
+    use super::*;
+    use crate::ClientBuilder;
+
+    #[derive(Default)]
+    struct Recorder {
+        started: RefCell<Vec<Uri>>,
+        completed: RefCell<Vec<RequestMetrics>>,
+        failed: RefCell<Vec<RequestMetrics>>,
+        connect_errors: RefCell<usize>,
+    }
+
+    impl MetricsRecorder for Recorder {
+        fn request_started(&self, head: &RequestHead) {
+            self.started.borrow_mut().push(head.uri.clone());
+        }
+
+        fn request_completed(&self, metrics: &RequestMetrics) {
+            self.completed.borrow_mut().push(metrics.clone());
+        }
+
+        fn request_failed(&self, metrics: &RequestMetrics, _: &SendRequestError) {
+            self.failed.borrow_mut().push(metrics.clone());
+        }
+
+        fn connect_error(&self, _: &Uri, _: &ConnectError) {
+            *self.connect_errors.borrow_mut() += 1;
+        }
+    }
+
+    #[actix_rt::test]
+    async fn test_metrics() {
+        let srv = actix_test::start(|| {
+            App::new()
+                .route(
+                    "/",
+                    web::post().to(|body: String| HttpResponse::Ok().body(body)),
+                )
+                .route("/missing", web::get().to(HttpResponse::NotFound))
+        });
+
+        let recorder = Rc::new(Recorder::default());
+        let client = ClientBuilder::new()
+            .wrap(Metrics::from_rc(recorder.clone()))
+            .finish();
+
+        let mut res = client.post(srv.url("/")).send_body("hello").await.unwrap();
+        // requests complete once the payload is read
+        assert!(recorder.completed.borrow().is_empty());
+        assert_eq!(res.body().await.unwrap(), "hello");
+
+        let res = client.get(srv.url("/missing")).send().await.unwrap();
+        drop(res);
+
+        assert_eq!(recorder.started.borrow().len(), 2);
+
+        {
+            let completed = recorder.completed.borrow();
+            assert_eq!(completed.len(), 2);
+            assert_eq!(completed[0].method, Method::POST);
+            assert_eq!(completed[0].status_class(), Some(StatusClass::Success));
+            assert_eq!(completed[0].bytes_sent, Some(5));
+            assert_eq!(completed[0].bytes_received, 5);
+            assert!(completed[0].acquire.is_some());
+            assert_eq!(completed[1].status, Some(StatusCode::NOT_FOUND));
+            assert_eq!(completed[1].status_class(), Some(StatusClass::ClientError));
+        }
+
+        // nothing listens on the port of a dropped listener
+        let addr = std::net::TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap();
+
+        let url = format!("http://{}/", addr);
+        assert!(client.get(&url).send().await.is_err());
+        assert_eq!(recorder.failed.borrow().len(), 1);
+        assert_eq!(recorder.failed.borrow()[0].status, None);
+        assert_eq!(*recorder.connect_errors.borrow(), 1);
+    }
+
+    #[test]
+    fn test_status_class() {
+        assert_eq!(
+            StatusClass::of(StatusCode::CONTINUE),
+            Some(StatusClass::Informational)
+        );
+        assert_eq!(
+            StatusClass::of(StatusCode::FOUND),
+            Some(StatusClass::Redirection)
+        );
+        assert_eq!(
+            StatusClass::of(StatusCode::BAD_GATEWAY),
+            Some(StatusClass::ServerError)
+        );
+        assert_eq!(StatusClass::of(StatusCode::from_u16(600).unwrap()), None);
+    }
+}
@@ -1,6 +1,12 @@
+mod credentials;
+mod metrics;
 mod redirect;
+mod retry;
 
+pub use self::credentials::{Credential, Credentials};
+pub use self::metrics::{Metrics, MetricsRecorder, RequestMetrics, StatusClass};
 pub use self::redirect::Redirect;
+pub use self::retry::Retry;
 
 use std::marker::PhantomData;
 
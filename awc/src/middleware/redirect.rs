@@ -10,7 +10,7 @@ use std::{
 use actix_http::{
     body::Body,
     client::{InvalidUrl, SendRequestError},
-    http::{header, Method, StatusCode, Uri},
+    http::{header, HeaderMap, HeaderName, Method, StatusCode, Uri},
     RequestHead, RequestHeadType,
 };
 use actix_service::Service;
@@ -22,8 +22,53 @@ use super::Transform;
 use crate::connect::{ConnectRequest, ConnectResponse};
 use crate::ClientResponse;
 
+/// Middleware that follows `301`, `302`, `303`, `307` and `308` redirects.
+///
+/// Headers of the original request are sent with redirected requests, except:
+/// - `Authorization`, `Proxy-Authorization`, `Cookie`, `Host` and any headers added with
+///   [`sensitive_header`](Self::sensitive_header) when the redirect leads to a different
+///   scheme, host or port.
+/// - Body headers such as `Content-Type` when the method is changed to `GET`.
+///
+/// Responses to `307` and `308` redirects keep the method and, if it was sent from memory, the
+/// body of the request. By default, `301`, `302` and `303` redirects change the method to `GET`,
+/// except for `GET` and `HEAD` requests, and drop the body; see
+/// [`rewrite_method`](Self::rewrite_method).
 pub struct Redirect {
     max_redirect_times: u8,
+    policy: Policy,
+}
+
+pub struct Policy {
+    /// Whether `301`, `302` and `303` redirects, respectively, change the method to `GET`.
+    rewrite_method: [bool; 3],
+    sensitive_headers: Vec<HeaderName>,
+}
+
+impl Policy {
+    /// Returns the method to use for a redirect with given status.
+    fn method(&self, status: StatusCode, method: Method) -> Method {
+        let rewrite = match status {
+            StatusCode::MOVED_PERMANENTLY => self.rewrite_method[0],
+            StatusCode::FOUND => self.rewrite_method[1],
+            StatusCode::SEE_OTHER => self.rewrite_method[2],
+            _ => false,
+        };
+
+        match method {
+            Method::GET | Method::HEAD => method,
+            _ if rewrite => Method::GET,
+            _ => method,
+        }
+    }
+
+    /// Returns true if header must not be sent to a different origin.
+    fn is_sensitive(&self, name: &HeaderName) -> bool {
+        matches!(
+            *name,
+            header::AUTHORIZATION | header::PROXY_AUTHORIZATION | header::COOKIE | header::HOST
+        ) || self.sensitive_headers.contains(name)
+    }
 }
 
 impl Default for Redirect {
@@ -36,6 +81,10 @@ impl Redirect {
     pub fn new() -> Self {
         Self {
             max_redirect_times: 10,
+            policy: Policy {
+                rewrite_method: [true; 3],
+                sensitive_headers: Vec::new(),
+            },
         }
     }
 
@@ -43,6 +92,27 @@ impl Redirect {
         self.max_redirect_times = times;
         self
     }
+
+    /// Set whether redirects with given status change the request method to `GET`.
+    ///
+    /// Only affects `301`, `302` and `303` redirects; `307` and `308` redirects always keep the
+    /// method. `GET` and `HEAD` requests are never changed. Requests that keep their method also
+    /// keep their body.
+    pub fn rewrite_method(mut self, status: StatusCode, rewrite: bool) -> Self {
+        match status {
+            StatusCode::MOVED_PERMANENTLY => self.policy.rewrite_method[0] = rewrite,
+            StatusCode::FOUND => self.policy.rewrite_method[1] = rewrite,
+            StatusCode::SEE_OTHER => self.policy.rewrite_method[2] = rewrite,
+            _ => {}
+        }
+        self
+    }
+
+    /// Add header that is removed from requests redirected to a different origin.
+    pub fn sensitive_header(mut self, name: HeaderName) -> Self {
+        self.policy.sensitive_headers.push(name);
+        self
+    }
 }
 
 impl<S> Transform<S, ConnectRequest> for Redirect
@@ -54,6 +124,7 @@ where
     fn new_transform(self, service: S) -> Self::Transform {
         RedirectService {
             max_redirect_times: self.max_redirect_times,
+            policy: Rc::new(self.policy),
             connector: Rc::new(service),
         }
     }
@@ -61,6 +132,7 @@ where
 
 pub struct RedirectService<S> {
     max_redirect_times: u8,
+    policy: Rc<Policy>,
     connector: Rc<S>,
 }
 
@@ -84,11 +156,22 @@ where
                 let connector = self.connector.clone();
                 let max_redirect_times = self.max_redirect_times;
 
-                // backup the uri and method for reuse schema and authority.
-                let (uri, method) = match head {
-                    RequestHeadType::Owned(ref head) => (head.uri.clone(), head.method.clone()),
-                    RequestHeadType::Rc(ref head, ..) => {
-                        (head.uri.clone(), head.method.clone())
+                // backup the uri, method and headers for reuse in redirected requests.
+                let (uri, method, headers) = match head {
+                    RequestHeadType::Owned(ref head) => {
+                        (head.uri.clone(), head.method.clone(), head.headers.clone())
+                    }
+                    RequestHeadType::Rc(ref head, ref extra_headers) => {
+                        let mut headers = head.headers.clone();
+                        if let Some(extra_headers) = extra_headers {
+                            for name in extra_headers.keys() {
+                                headers.remove(name);
+                            }
+                            for (name, value) in extra_headers.iter() {
+                                headers.append(name.clone(), value.clone());
+                            }
+                        }
+                        (head.uri.clone(), head.method.clone(), headers)
                     }
                 };
 
@@ -104,8 +187,10 @@ where
                     max_redirect_times,
                     uri: Some(uri),
                     method: Some(method),
+                    headers: Some(headers),
                     body: body_opt,
                     addr,
+                    policy: self.policy.clone(),
                     connector: Some(connector),
                 }
             }
@@ -127,8 +212,10 @@ pin_project_lite::pin_project! {
             max_redirect_times: u8,
             uri: Option<Uri>,
             method: Option<Method>,
+            headers: Option<HeaderMap>,
             body: Option<Bytes>,
             addr: Option<SocketAddr>,
+            policy: Rc<Policy>,
             connector: Option<Rc<S>>
         }
     }
@@ -148,84 +235,74 @@ where
                 max_redirect_times,
                 uri,
                 method,
+                headers,
                 body,
                 addr,
+                policy,
                 connector,
             } => match ready!(fut.poll(cx))? {
                 ConnectResponse::Client(res) => match res.head().status {
-                    StatusCode::MOVED_PERMANENTLY
-                    | StatusCode::FOUND
-                    | StatusCode::SEE_OTHER
+                    status @ StatusCode::MOVED_PERMANENTLY
+                    | status @ StatusCode::FOUND
+                    | status @ StatusCode::SEE_OTHER
+                    | status @ StatusCode::TEMPORARY_REDIRECT
+                    | status @ StatusCode::PERMANENT_REDIRECT
                         if *max_redirect_times > 0 =>
                     {
                         let org_uri = uri.take().unwrap();
                         // rebuild uri from the location header value.
-                        let uri = rebuild_uri(&res, org_uri)?;
-
-                        // reset method
-                        let method = method.take().unwrap();
-                        let method = match method {
-                            Method::GET | Method::HEAD => method,
-                            _ => Method::GET,
+                        let uri = rebuild_uri(&res, &org_uri)?;
+
+                        let org_method = method.take().unwrap();
+                        let method = policy.method(status, org_method.clone());
+
+                        let mut headers = headers.take().unwrap();
+
+                        // strip credentials before following redirect to another origin
+                        if !is_same_origin(&org_uri, &uri) {
+                            let sensitive = headers
+                                .keys()
+                                .filter(|name| policy.is_sensitive(name))
+                                .cloned()
+                                .collect::<Vec<_>>();
+
+                            for name in sensitive {
+                                headers.remove(name);
+                            }
+                        }
+
+                        // reuse body only if method is kept
+                        let (body, body_new) = if method == org_method {
+                            let body = body.take();
+                            let body_new = match body {
+                                Some(ref bytes) => Body::Bytes(bytes.clone()),
+                                // TODO: should this be Body::Empty or Body::None.
+                                _ => Body::Empty,
+                            };
+                            (body, body_new)
+                        } else {
+                            for name in &[
+                                header::CONTENT_TYPE,
+                                header::CONTENT_LENGTH,
+                                header::CONTENT_ENCODING,
+                                header::TRANSFER_ENCODING,
+                            ] {
+                                headers.remove(name);
+                            }
+                            (None, Body::None)
                         };
 
                         // take ownership of states that could be reused
                         let addr = addr.take();
                         let connector = connector.take();
+                        let policy = policy.clone();
                         let mut max_redirect_times = *max_redirect_times;
 
                         // use a new request head.
                         let mut head = RequestHead::default();
                         head.uri = uri.clone();
                         head.method = method.clone();
-
-                        let head = RequestHeadType::Owned(head);
-
-                        max_redirect_times -= 1;
-
-                        let fut = connector
-                            .as_ref()
-                            .unwrap()
-                            // remove body
-                            .call(ConnectRequest::Client(head, Body::None, addr));
-
-                        self.set(RedirectServiceFuture::Client {
-                            fut,
-                            max_redirect_times,
-                            uri: Some(uri),
-                            method: Some(method),
-                            // body is dropped on 301,302,303
-                            body: None,
-                            addr,
-                            connector,
-                        });
-
-                        self.poll(cx)
-                    }
-                    StatusCode::TEMPORARY_REDIRECT | StatusCode::PERMANENT_REDIRECT
-                        if *max_redirect_times > 0 =>
-                    {
-                        let org_uri = uri.take().unwrap();
-                        // rebuild uri from the location header value.
-                        let uri = rebuild_uri(&res, org_uri)?;
-
-                        // try to reuse body
-                        let body = body.take();
-                        let body_new = match body {
-                            Some(ref bytes) => Body::Bytes(bytes.clone()),
-                            // TODO: should this be Body::Empty or Body::None.
-                            _ => Body::Empty,
-                        };
-
-                        let addr = addr.take();
-                        let method = method.take().unwrap();
-                        let connector = connector.take();
-                        let mut max_redirect_times = *max_redirect_times;
-
-                        // use a new request head.
-                        let mut head = RequestHead::default();
-                        head.uri = uri.clone();
-                        head.method = method.clone();
+                        head.headers = headers.clone();
 
                         let head = RequestHeadType::Owned(head);
 
@@ -241,8 +318,10 @@ where
                             max_redirect_times,
                             uri: Some(uri),
                             method: Some(method),
+                            headers: Some(headers),
                             body,
                             addr,
+                            policy,
                             connector,
                         });
 
@@ -256,7 +335,7 @@ where
     }
 }
 
-fn rebuild_uri(res: &ClientResponse, org_uri: Uri) -> Result<Uri, SendRequestError> {
+fn rebuild_uri(res: &ClientResponse, org_uri: &Uri) -> Result<Uri, SendRequestError> {
     let uri = res
         .headers()
         .get(header::LOCATION)
@@ -281,9 +360,24 @@ fn rebuild_uri(res: &ClientResponse, org_uri: Uri) -> Result<Uri, SendRequestErr
     Ok(uri)
 }
 
+/// Returns true if both URIs have the same scheme, host and port.
+fn is_same_origin(a: &Uri, b: &Uri) -> bool {
+    fn port(uri: &Uri) -> Option<u16> {
+        uri.port_u16().or_else(|| match uri.scheme_str() {
+            Some("http") | Some("ws") => Some(80),
+            Some("https") | Some("wss") => Some(443),
+            _ => None,
+        })
+    }
+
+    a.scheme() == b.scheme()
+        && a.host().map(str::to_ascii_lowercase) == b.host().map(str::to_ascii_lowercase)
+        && port(a) == port(b)
+}
+
 #[cfg(test)]
 mod tests {
-    use actix_web::{web, App, Error, HttpResponse};
+    use actix_web::{web, App, Error, HttpRequest, HttpResponse};
 
     use super::*;
     use crate::ClientBuilder;
@@ -347,4 +441,96 @@ mod tests {
 
         assert_eq!(res.status().as_u16(), 302);
     }
+
+    async fn echo_request(req: HttpRequest, body: Bytes) -> HttpResponse {
+        let auth = req
+            .headers()
+            .get(header::AUTHORIZATION)
+            .map_or("", |auth| auth.to_str().unwrap());
+
+        HttpResponse::Ok().body(format!(
+            "{} {:?} {:?}",
+            req.method(),
+            auth,
+            std::str::from_utf8(&body).unwrap()
+        ))
+    }
+
+    // redirecting handlers read the request body so the connection can be reused for the
+    // redirected request
+    #[actix_rt::test]
+    async fn test_redirect_headers() {
+        let other = actix_test::start(|| App::new().default_service(web::to(echo_request)));
+        let other_url = other.url("/echo");
+
+        let srv = actix_test::start(move || {
+            let other_url = other_url.clone();
+
+            App::new()
+                .service(web::resource("/echo").to(echo_request))
+                .service(web::resource("/same-origin").to(|_: Bytes| async {
+                    Ok::<_, Error>(
+                        HttpResponse::Found()
+                            .append_header(("location", "/echo"))
+                            .finish(),
+                    )
+                }))
+                .service(web::resource("/cross-origin").to(move |_: Bytes| {
+                    let other_url = other_url.clone();
+                    async move {
+                        Ok::<_, Error>(
+                            HttpResponse::TemporaryRedirect()
+                                .append_header(("location", other_url))
+                                .finish(),
+                        )
+                    }
+                }))
+        });
+
+        let client = ClientBuilder::new().finish();
+
+        // credentials are kept on same origin, 302 changes method to GET
+        let mut res = client
+            .post(srv.url("/same-origin"))
+            .bearer_auth("token")
+            .send_body("data")
+            .await
+            .unwrap();
+        let body = res.body().await.unwrap();
+        assert_eq!(body, r#"GET "Bearer token" """#);
+
+        // credentials are stripped on cross origin, 307 keeps method and body
+        let mut res = client
+            .post(srv.url("/cross-origin"))
+            .bearer_auth("token")
+            .send_body("data")
+            .await
+            .unwrap();
+        let body = res.body().await.unwrap();
+        assert_eq!(body, r#"POST "" "data""#);
+    }
+
+    #[actix_rt::test]
+    async fn test_redirect_keep_method() {
+        let client = ClientBuilder::new()
+            .disable_redirects()
+            .wrap(Redirect::new().rewrite_method(StatusCode::FOUND, false))
+            .finish();
+
+        let srv = actix_test::start(|| {
+            App::new()
+                .service(web::resource("/echo").to(echo_request))
+                .service(web::resource("/").to(|_: Bytes| async {
+                    Ok::<_, Error>(
+                        HttpResponse::Found()
+                            .append_header(("location", "/echo"))
+                            .finish(),
+                    )
+                }))
+        });
+
+        let mut res = client.put(srv.url("/")).send_body("data").await.unwrap();
+        let body = res.body().await.unwrap();
+        assert_eq!(body, r#"PUT "" "data""#);
+    }
 }
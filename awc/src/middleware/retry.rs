@@ -0,0 +1,357 @@
+use std::{io, rc::Rc, time::Duration};
+
+use actix_http::{
+    body::Body,
+    client::{ConnectError, SendRequestError},
+    error::ParseError,
+    http::Method,
+    RequestHeadType,
+};
+use actix_rt::time::sleep;
+use actix_service::Service;
+use futures_core::future::LocalBoxFuture;
+
+use super::Transform;
+
+use crate::connect::{ConnectRequest, ConnectResponse};
+
+/// Middleware that retries idempotent requests failing before a response was received.
+///
+/// Requests are retried if connecting to the host is refused, reset or times out, or if the
+/// connection is reset before the response head is read. Other errors, e.g. host name resolution,
+/// proxy, TLS or connection pool timeout errors, are not retried. Only requests with idempotent methods (`GET`, `HEAD`, `OPTIONS`,
+/// `TRACE`, `PUT` and `DELETE`) and a body sent from memory are retried; streaming bodies can not
+/// be sent again.
+///
+/// Before each retry the middleware waits for a backoff period, doubling it with every attempt up
+/// to [`max_backoff`](Self::max_backoff).
+///
+/// # Examples
+/// ```
+/// use std::time::Duration;
+///
+/// use awc::middleware::Retry;
+///
+/// let client = awc::Client::builder()
+///     .wrap(
+///         Retry::new()
+///             .max_attempts(5)
+///             .backoff(Duration::from_millis(50)),
+///     )
+///     .finish();
+/// ```
+#[derive(Debug, Clone)]
+pub struct Retry {
+    max_attempts: u32,
+    backoff: Duration,
+    max_backoff: Duration,
+}
+
+impl Default for Retry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Retry {
+    /// Create retry middleware making at most 3 attempts, with an initial backoff of 100ms.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(100),
+            max_backoff: Duration::from_secs(10),
+        }
+    }
+
+    /// Set max number of attempts per request, including the first one.
+    ///
+    /// Values lower than 1 are treated as 1, i.e. requests are not retried.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts;
+        self
+    }
+
+    /// Set backoff period before the first retry. Default is 100ms.
+    pub fn backoff(mut self, backoff: Duration) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Set max backoff period between retries. Default is 10 seconds.
+    pub fn max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    /// Returns backoff period after given number of failed attempts.
+    fn backoff_after(&self, attempts: u32) -> Duration {
+        let factor = 1u32
+            .checked_shl(attempts.saturating_sub(1))
+            .unwrap_or(u32::MAX);
+
+        self.backoff
+            .checked_mul(factor)
+            .map_or(self.max_backoff, |backoff| backoff.min(self.max_backoff))
+    }
+}
+
+impl<S> Transform<S, ConnectRequest> for Retry
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Transform = RetryService<S>;
+
+    fn new_transform(self, service: S) -> Self::Transform {
+        RetryService {
+            policy: Rc::new(self),
+            connector: Rc::new(service),
+        }
+    }
+}
+
+pub struct RetryService<S> {
+    policy: Rc<Retry>,
+    connector: Rc<S>,
+}
+
+impl<S> Service<ConnectRequest> for RetryService<S>
+where
+    S: Service<ConnectRequest, Response = ConnectResponse, Error = SendRequestError> + 'static,
+{
+    type Response = S::Response;
+    type Error = S::Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    actix_service::forward_ready!(connector);
+
+    fn call(&self, req: ConnectRequest) -> Self::Future {
+        let (head, body, addr) = match req {
+            ConnectRequest::Client(head, body, addr)
+                if self.policy.max_attempts > 1
+                    && is_idempotent(&head.as_ref().method)
+                    && is_replayable(&body) =>
+            {
+                (head, body, addr)
+            }
+            req => return Box::pin(self.connector.call(req)),
+        };
+
+        // owned heads are shared between attempts
+        let head = match head {
+            RequestHeadType::Owned(head) => RequestHeadType::Rc(Rc::new(head), None),
+            head => head,
+        };
+
+        let policy = self.policy.clone();
+        let connector = self.connector.clone();
+
+        Box::pin(async move {
+            let mut attempts = 0;
+
+            loop {
+                let head = match head {
+                    RequestHeadType::Rc(ref head, ref extra_headers) => {
+                        RequestHeadType::Rc(head.clone(), extra_headers.clone())
+                    }
+                    RequestHeadType::Owned(_) => unreachable!(),
+                };
+
+                let req = ConnectRequest::Client(head, replay(&body), addr);
+                attempts += 1;
+
+                match connector.call(req).await {
+                    Err(err) if attempts < policy.max_attempts && is_retryable(&err) => {
+                        log::debug!("Retrying request after error: {}", err);
+                        sleep(policy.backoff_after(attempts)).await;
+                    }
+                    res => return res,
+                }
+            }
+        })
+    }
+}
+
+fn is_idempotent(method: &Method) -> bool {
+    matches!(
+        *method,
+        Method::GET
+            | Method::HEAD
+            | Method::OPTIONS
+            | Method::TRACE
+            | Method::PUT
+            | Method::DELETE
+    )
+}
+
+fn is_replayable(body: &Body) -> bool {
+    matches!(body, Body::None | Body::Empty | Body::Bytes(_))
+}
+
+fn replay(body: &Body) -> Body {
+    match body {
+        Body::None => Body::None,
+        Body::Bytes(bytes) => Body::Bytes(bytes.clone()),
+        _ => Body::Empty,
+    }
+}
+
+/// Returns true if request failed before a response head was received, due to connection errors.
+fn is_retryable(err: &SendRequestError) -> bool {
+    match err {
+        SendRequestError::Connect(ConnectError::Timeout) => true,
+        SendRequestError::Connect(ConnectError::Io(err)) => matches!(
+            err.kind(),
+            io::ErrorKind::ConnectionRefused | io::ErrorKind::ConnectionReset
+        ),
+        SendRequestError::Send(err) | SendRequestError::Response(ParseError::Io(err)) => {
+            matches!(
+                err.kind(),
+                io::ErrorKind::ConnectionReset
+                    | io::ErrorKind::ConnectionAborted
+                    | io::ErrorKind::BrokenPipe
+                    | io::ErrorKind::UnexpectedEof
+            )
+        }
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use actix_http::{client::Connector, RequestHead};
+    use actix_service::fn_service;
+    use actix_web::{web, App, HttpResponse};
+
+    use super::*;
+    use crate::{connect::DefaultConnector, error::InvalidUrl, http::StatusCode};
+
+    fn refused() -> SendRequestError {
+        SendRequestError::Connect(ConnectError::Io(io::ErrorKind::ConnectionRefused.into()))
+    }
+
+    #[test]
+    fn backoff() {
+        let retry = Retry::new()
+            .backoff(Duration::from_millis(100))
+            .max_backoff(Duration::from_millis(500));
+
+        assert_eq!(retry.backoff_after(1), Duration::from_millis(100));
+        assert_eq!(retry.backoff_after(2), Duration::from_millis(200));
+        assert_eq!(retry.backoff_after(3), Duration::from_millis(400));
+        assert_eq!(retry.backoff_after(4), Duration::from_millis(500));
+        assert_eq!(retry.backoff_after(100), Duration::from_millis(500));
+    }
+
+    #[test]
+    fn retryable_errors() {
+        assert!(is_retryable(&refused()));
+        assert!(is_retryable(&SendRequestError::Connect(
+            ConnectError::Timeout
+        )));
+        assert!(!is_retryable(&SendRequestError::Connect(
+            ConnectError::AcquireTimeout
+        )));
+        assert!(!is_retryable(&SendRequestError::Connect(
+            ConnectError::NoRecords
+        )));
+        assert!(!is_retryable(&SendRequestError::Connect(
+            ConnectError::ProxyTunnel(StatusCode::PROXY_AUTHENTICATION_REQUIRED)
+        )));
+        assert!(!is_retryable(&SendRequestError::Connect(
+            ConnectError::Socks5("authentication failed")
+        )));
+        assert!(!is_retryable(&SendRequestError::Url(
+            InvalidUrl::MissingHost
+        )));
+        assert!(is_retryable(&SendRequestError::Send(io::Error::from(
+            io::ErrorKind::ConnectionReset
+        ))));
+        assert!(!is_retryable(&SendRequestError::Timeout));
+        assert!(!is_retryable(&SendRequestError::Send(io::Error::from(
+            io::ErrorKind::InvalidInput
+        ))));
+    }
+
+    #[actix_rt::test]
+    async fn retry_connect_errors() {
+        let srv = actix_test::start(|| App::new().default_service(web::to(HttpResponse::Ok)));
+
+        let attempts = Rc::new(Cell::new(0));
+
+        // first two attempts fail, others are sent to the test server
+        let connector = {
+            let attempts = attempts.clone();
+            let connector = Rc::new(DefaultConnector::new(Connector::new().finish()));
+
+            fn_service(move |req: ConnectRequest| {
+                attempts.set(attempts.get() + 1);
+                let fail = attempts.get() <= 2;
+                let connector = connector.clone();
+
+                async move {
+                    if fail {
+                        Err(refused())
+                    } else {
+                        connector.call(req).await
+                    }
+                }
+            })
+        };
+
+        let service = Retry::new()
+            .backoff(Duration::from_millis(1))
+            .new_transform(connector);
+
+        let request = |method| {
+            let mut head = RequestHead::default();
+            head.method = method;
+            head.uri = srv.url("/").parse().unwrap();
+            ConnectRequest::Client(RequestHeadType::Owned(head), Body::Empty, None)
+        };
+
+        assert!(service.call(request(Method::GET)).await.is_ok());
+        assert_eq!(attempts.get(), 3);
+
+        // non-idempotent requests are not retried
+        attempts.set(0);
+        assert!(service.call(request(Method::POST)).await.is_err());
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[actix_rt::test]
+    async fn retry_limit() {
+        let attempts = Rc::new(Cell::new(0));
+
+        let connector = {
+            let attempts = attempts.clone();
+
+            fn_service(move |_: ConnectRequest| {
+                attempts.set(attempts.get() + 1);
+                async { Err::<ConnectResponse, _>(refused()) }
+            })
+        };
+
+        let service = Retry::new()
+            .max_attempts(4)
+            .backoff(Duration::from_millis(1))
+            .new_transform(connector);
+
+        let mut head = RequestHead::default();
+        head.method = Method::PUT;
+
+        let res = service
+            .call(ConnectRequest::Client(
+                RequestHeadType::Owned(head),
+                Body::Bytes("body".into()),
+                None,
+            ))
+            .await;
+        assert!(matches!(
+            res,
+            Err(SendRequestError::Connect(ConnectError::Io(_)))
+        ));
+        assert_eq!(attempts.get(), 4);
+    }
+}
@@ -1,11 +1,13 @@
 use std::{convert::TryFrom, error::Error as StdError, fmt, net, rc::Rc, time::Duration};
 
+use actix_codec::AsyncRead;
 use bytes::Bytes;
 use futures_core::Stream;
 use serde::Serialize;
 
 use actix_http::{
     body::Body,
+    client::{ExpectContinue, HttpProtocol, IpVersion, PayloadLimits, Timings, Trailers},
     http::{
         header::{self, IntoHeaderPair},
         ConnectionType, Error as HttpError, HeaderMap, HeaderValue, Method, Uri, Version,
@@ -103,6 +105,88 @@ impl ClientRequest {
         self
     }
 
+    /// Only connect to addresses of the given IP version for this request.
+    ///
+    /// Overrides the IP version set on the client's connector.
+    pub fn ip_version(self, ip_version: IpVersion) -> Self {
+        self.head.extensions_mut().insert(ip_version);
+        self
+    }
+
+    /// Set HTTP protocol used for this request.
+    ///
+    /// Overrides the protocol set on the client's connector. Requests forcing a protocol are only
+    /// sent over connections opened with that protocol. The protocol used is reported by the
+    /// response's [`version`](crate::ClientResponse::version).
+    pub fn protocol(self, protocol: HttpProtocol) -> Self {
+        self.head.extensions_mut().insert(protocol);
+        self
+    }
+
+    /// Send trailer fields after the request body.
+    ///
+    /// Fields are read from the handle once the body is sent, so they can be set while it is
+    /// streamed. Over HTTP/1, the body is sent with chunked transfer encoding.
+    pub fn trailers(self, trailers: Trailers) -> Self {
+        self.head.extensions_mut().insert(trailers);
+        self
+    }
+
+    /// Record timing breakdown of this request in `timings`.
+    ///
+    /// The handle is also returned by the response's
+    /// [`timings`](crate::ClientResponse::timings).
+    pub fn timings(self, timings: Timings) -> Self {
+        self.head.extensions_mut().insert(timings);
+        self
+    }
+
+    /// Send request with an `Expect: 100-continue` header, waiting for the server to accept it
+    /// before sending the body.
+    ///
+    /// The body is sent if the server does not respond within `timeout`. If the server responds
+    /// with a final status instead, e.g. because the body is too large, the body is not sent and
+    /// that response is returned. Only applies to HTTP/1 requests with a body.
+    pub fn expect_continue(mut self, timeout: Duration) -> Self {
+        self.head
+            .headers
+            .insert(header::EXPECT, HeaderValue::from_static("100-continue"));
+        self.head
+            .extensions_mut()
+            .insert(ExpectContinue::new(timeout));
+        self
+    }
+
+    /// Limit size of the response payload, before decompression.
+    ///
+    /// Reading a larger payload fails with
+    /// [`PayloadError::TooLarge`](crate::error::PayloadError::TooLarge).
+    pub fn response_size_limit(self, max_size: u64) -> Self {
+        let limits = self.payload_limits();
+        self.head.extensions_mut().insert(limits.max_size(max_size));
+        self
+    }
+
+    /// Limit how long reading the response payload waits for the next chunk.
+    ///
+    /// Reading a payload that stalls for longer fails with
+    /// [`PayloadError::IdleTimeout`](crate::error::PayloadError::IdleTimeout).
+    pub fn response_idle_timeout(self, timeout: Duration) -> Self {
+        let limits = self.payload_limits();
+        self.head
+            .extensions_mut()
+            .insert(limits.idle_timeout(timeout));
+        self
+    }
+
+    fn payload_limits(&self) -> PayloadLimits {
+        self.head
+            .extensions()
+            .get::<PayloadLimits>()
+            .copied()
+            .unwrap_or_default()
+    }
+
     /// Set HTTP method of this request.
     #[inline]
     pub fn method(mut self, method: Method) -> Self {
@@ -420,6 +504,42 @@ impl ClientRequest {
         )
     }
 
+    /// Set a body read from `reader` and generate `ClientRequest`.
+    ///
+    /// When `len` is known the body is sent with a `Content-Length` header, otherwise chunked
+    /// transfer encoding is used. A known `len` must match the number of bytes read.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// # #[actix_rt::main]
+    /// # async fn main() {
+    /// let data: &'static [u8] = b"hello world";
+    ///
+    /// let res = awc::Client::new()
+    ///     .put("http://www.rust-lang.org/upload")
+    ///     .send_reader(data, Some(data.len() as u64))
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn send_reader<R>(self, reader: R, len: Option<u64>) -> SendClientRequest
+    where
+        R: AsyncRead + Unpin + 'static,
+    {
+        let slf = match self.prep_for_sending() {
+            Ok(slf) => slf,
+            Err(e) => return e.into(),
+        };
+
+        RequestSender::Owned(slf.head).send_reader(
+            slf.addr,
+            slf.response_decompress,
+            slf.timeout,
+            &slf.config,
+            reader,
+            len,
+        )
+    }
+
     /// Set an empty body and generate `ClientRequest`.
     pub fn send(self) -> SendClientRequest {
         let slf = match self.prep_for_sending() {
@@ -10,6 +10,7 @@ use std::{
 };
 
 use actix_http::{
+    client::{Timings, Trailers},
     error::PayloadError,
     http::{header, HeaderMap, StatusCode, Version},
     Extensions, HttpMessage, Payload, PayloadStream, ResponseHead,
@@ -21,7 +22,10 @@ use serde::de::DeserializeOwned;
 
 #[cfg(feature = "cookies")]
 use crate::cookie::{Cookie, ParseError as CookieParseError};
-use crate::error::JsonPayloadError;
+use crate::{
+    byteranges::ByteRanges,
+    error::{ByteRangesError, JsonPayloadError},
+};
 
 /// Client Response
 pub struct ClientResponse<S = PayloadStream> {
@@ -114,6 +118,21 @@ impl<S> ClientResponse<S> {
         &self.head().headers
     }
 
+    /// Returns trailer fields sent after the response body.
+    ///
+    /// Trailers are only available once the body is read to the end.
+    pub fn trailers(&self) -> Option<HeaderMap> {
+        self.extensions().get::<Trailers>().and_then(Trailers::get)
+    }
+
+    /// Returns timing breakdown of the request, e.g. for latency metrics.
+    ///
+    /// Only recorded for requests sent with [`ClientRequest::timings`](crate::ClientRequest::timings).
+    /// Connection timings are only recorded for requests that opened a new connection.
+    pub fn timings(&self) -> Option<Timings> {
+        self.extensions().get::<Timings>().cloned()
+    }
+
     /// Set a body and return previous body value
     pub fn map_body<F, U>(mut self, f: F) -> ClientResponse<U>
     where
@@ -215,6 +234,18 @@ where
     }
 }
 
+impl<S> ClientResponse<S>
+where
+    S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
+{
+    /// Returns stream of the parts of a `206 Partial Content` response.
+    ///
+    /// See [`ByteRanges`] for details.
+    pub fn byteranges(self) -> Result<ByteRanges<S>, ByteRangesError> {
+        ByteRanges::new(self)
+    }
+}
+
 impl<S> Stream for ClientResponse<S>
 where
     S: Stream<Item = Result<Bytes, PayloadError>> + Unpin,
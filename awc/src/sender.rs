@@ -8,8 +8,9 @@ use std::{
     time::Duration,
 };
 
+use actix_codec::AsyncRead;
 use actix_http::{
-    body::{Body, BodyStream},
+    body::{Body, BodyStream, ReaderBody},
     http::{
         header::{self, HeaderMap, HeaderName, IntoHeaderValue},
         Error as HttpError,
@@ -76,6 +77,8 @@ pub enum SendClientRequest {
         // FIXME: use a pinned Sleep instead of box.
         Option<Pin<Box<Sleep>>>,
         bool,
+        // max decompressed size and max decompressed chunk size
+        (usize, usize),
     ),
     Err(Option<SendRequestError>),
 }
@@ -85,9 +88,10 @@ impl SendClientRequest {
         send: Pin<Box<dyn Future<Output = Result<ConnectResponse, SendRequestError>>>>,
         response_decompress: bool,
         timeout: Option<Duration>,
+        decompress_limits: (usize, usize),
     ) -> SendClientRequest {
         let delay = timeout.map(|d| Box::pin(sleep(d)));
-        SendClientRequest::Fut(send, delay, response_decompress)
+        SendClientRequest::Fut(send, delay, response_decompress, decompress_limits)
     }
 }
 
@@ -99,7 +103,7 @@ impl Future for SendClientRequest {
         let this = self.get_mut();
 
         match this {
-            SendClientRequest::Fut(send, delay, response_decompress) => {
+            SendClientRequest::Fut(send, delay, response_decompress, limits) => {
                 if let Some(delay) = delay {
                     if delay.as_mut().poll(cx).is_ready() {
                         return Poll::Ready(Err(SendRequestError::Timeout));
@@ -110,7 +114,19 @@ impl Future for SendClientRequest {
                     res.into_client_response()._timeout(delay.take()).map_body(
                         |head, payload| {
                             if *response_decompress {
-                                Payload::Stream(Decoder::from_headers(payload, &head.headers))
+                                let (max_size, max_chunk_size) = *limits;
+
+                                let decoder = Decoder::from_headers(payload, &head.headers)
+                                    .max_size(max_size)
+                                    .max_chunk_size(max_chunk_size);
+
+                                // headers describe the encoded payload; callers get plain bytes
+                                if decoder.is_decoding() {
+                                    head.headers.remove(header::CONTENT_ENCODING);
+                                    head.headers.remove(header::CONTENT_LENGTH);
+                                }
+
+                                Payload::Stream(decoder)
                             } else {
                                 Payload::Stream(Decoder::new(
                                     payload,
@@ -138,7 +154,7 @@ impl Future for SendClientRequest {
     fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
         let this = self.get_mut();
         match this {
-            SendClientRequest::Fut(send, delay, _) => {
+            SendClientRequest::Fut(send, delay, _, _) => {
                 if let Some(delay) = delay {
                     if delay.as_mut().poll(cx).is_ready() {
                         return Poll::Ready(Err(SendRequestError::Timeout));
@@ -211,7 +227,15 @@ impl RequestSender {
 
         let fut = config.connector.call(req);
 
-        SendClientRequest::new(fut, response_decompress, timeout.or(config.timeout))
+        SendClientRequest::new(
+            fut,
+            response_decompress,
+            timeout.or(config.timeout),
+            (
+                config.max_decompressed_size,
+                config.max_decompressed_chunk_size,
+            ),
+        )
     }
 
     pub(crate) fn send_json<T: Serialize>(
@@ -290,6 +314,27 @@ impl RequestSender {
         )
     }
 
+    pub(crate) fn send_reader<R>(
+        self,
+        addr: Option<net::SocketAddr>,
+        response_decompress: bool,
+        timeout: Option<Duration>,
+        config: &ClientConfig,
+        reader: R,
+        len: Option<u64>,
+    ) -> SendClientRequest
+    where
+        R: AsyncRead + Unpin + 'static,
+    {
+        self.send_body(
+            addr,
+            response_decompress,
+            timeout,
+            config,
+            Body::from_message(ReaderBody::new(reader, len)),
+        )
+    }
+
     pub(crate) fn send(
         self,
         addr: Option<net::SocketAddr>,
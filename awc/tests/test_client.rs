@@ -16,6 +16,7 @@ use futures_util::stream;
 use rand::Rng;
 
 use actix_http::{
+    client::Timings,
     http::{self, StatusCode},
     HttpService,
 };
@@ -232,6 +233,71 @@ async fn test_response_timeout() {
     }
 }
 
+#[actix_rt::test]
+async fn test_response_limits() {
+    use futures_util::stream::{once, StreamExt as _};
+
+    let srv = actix_test::start(|| {
+        App::new()
+            .service(web::resource("/").route(web::to(|| HttpResponse::Ok().body(STR))))
+            .service(web::resource("/slow").route(web::to(|| async {
+                Ok::<_, Error>(HttpResponse::Ok().streaming(Box::pin(
+                    once(async { Ok::<_, Error>(Bytes::from_static(b"chunk")) }).chain(once(
+                        async {
+                            actix_rt::time::sleep(Duration::from_millis(200)).await;
+                            Ok::<_, Error>(Bytes::from_static(b"chunk"))
+                        },
+                    )),
+                )))
+            })))
+    });
+
+    let client = awc::Client::new();
+
+    let mut res = client
+        .get(srv.url("/"))
+        .response_size_limit(STR.len() as u64)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        res.body().await.unwrap(),
+        Bytes::from_static(STR.as_bytes())
+    );
+
+    let mut res = client
+        .get(srv.url("/"))
+        .response_size_limit(10)
+        .send()
+        .await
+        .unwrap();
+    assert!(matches!(res.body().await, Err(PayloadError::TooLarge)));
+
+    let mut res = client
+        .get(srv.url("/slow"))
+        .response_idle_timeout(Duration::from_millis(500))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(b"chunkchunk"));
+
+    let mut res = client
+        .get(srv.url("/slow"))
+        .response_idle_timeout(Duration::from_millis(50))
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(
+        res.next().await.unwrap().unwrap(),
+        Bytes::from_static(b"chunk")
+    );
+    assert!(matches!(
+        res.next().await,
+        Some(Err(PayloadError::IdleTimeout))
+    ));
+    assert!(res.next().await.is_none());
+}
+
 #[actix_rt::test]
 async fn test_connection_reuse() {
     let num = Arc::new(AtomicUsize::new(0));
@@ -525,6 +591,10 @@ async fn test_client_gzip_encoding() {
     let mut response = srv.post("/").send().await.unwrap();
     assert!(response.status().is_success());
 
+    // encoding headers are removed from decoded responses
+    assert!(!response.headers().contains_key(header::CONTENT_ENCODING));
+    assert!(!response.headers().contains_key(header::CONTENT_LENGTH));
+
     // read response
     let bytes = response.body().await.unwrap();
     assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
@@ -697,6 +767,32 @@ async fn test_client_streaming_explicit() {
     assert_eq!(bytes, Bytes::from_static(STR.as_ref()));
 }
 
+#[actix_rt::test]
+async fn test_client_reader() {
+    let srv = actix_test::start(|| {
+        App::new().default_service(web::to(|req: HttpRequest, body: Bytes| {
+            let chunked = req.headers().contains_key(header::TRANSFER_ENCODING);
+            HttpResponse::Ok()
+                .insert_header(("x-chunked", chunked.to_string()))
+                .body(body)
+        }))
+    });
+
+    let data: &'static [u8] = STR.as_bytes();
+
+    let mut res = srv
+        .post("/")
+        .send_reader(data, Some(data.len() as u64))
+        .await
+        .unwrap();
+    assert_eq!(res.headers().get("x-chunked").unwrap(), "false");
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(data));
+
+    let mut res = srv.post("/").send_reader(data, None).await.unwrap();
+    assert_eq!(res.headers().get("x-chunked").unwrap(), "true");
+    assert_eq!(res.body().await.unwrap(), Bytes::from_static(data));
+}
+
 #[actix_rt::test]
 async fn test_body_streaming_implicit() {
     let srv = actix_test::start(|| {
@@ -818,6 +914,191 @@ async fn client_unread_response() {
     assert_eq!(bytes, Bytes::from_static(b""));
 }
 
+#[actix_rt::test]
+async fn client_response_head_limits() {
+    let addr = actix_test::unused_addr();
+    let lst = std::net::TcpListener::bind(addr).unwrap();
+
+    std::thread::spawn(move || {
+        for stream in lst.incoming() {
+            let mut stream = stream.unwrap();
+            let mut b = [0; 1000];
+            let _ = stream.read(&mut b).unwrap();
+            let _ = stream.write_all(
+                b"HTTP/1.1 200 OK\r\n\
+                x-a: aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\r\n\
+                x-b: bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\r\n\
+                connection: close\r\n\
+                \r\n",
+            );
+        }
+    });
+
+    let url = format!("http://{}/", addr);
+
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().max_response_head_size(64))
+        .finish();
+    let res = client.get(&url).send().await;
+    assert!(matches!(
+        res,
+        Err(SendRequestError::Response(
+            actix_http::error::ParseError::TooLarge
+        ))
+    ));
+
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().max_response_headers(2))
+        .finish();
+    let res = client.get(&url).send().await;
+    assert!(matches!(
+        res,
+        Err(SendRequestError::Response(
+            actix_http::error::ParseError::TooLarge
+        ))
+    ));
+
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().max_response_headers(3))
+        .finish();
+    let res = client.get(&url).send().await.unwrap();
+    assert!(res.status().is_success());
+}
+
+#[actix_rt::test]
+async fn client_raw_response_head() {
+    use actix_http::{h1::RawResponseHead, HttpMessage as _};
+
+    const HEAD: &[u8] = b"HTTP/1.1 200 OK\r\n\
+        X-Mixed-Case:  spaced value \r\n\
+        content-length: 0\r\n\
+        connection: close\r\n\
+        \r\n";
+
+    let addr = actix_test::unused_addr();
+    let lst = std::net::TcpListener::bind(addr).unwrap();
+
+    std::thread::spawn(move || {
+        for stream in lst.incoming() {
+            let mut stream = stream.unwrap();
+            let mut b = [0; 1000];
+            let _ = stream.read(&mut b).unwrap();
+            let _ = stream.write_all(HEAD);
+        }
+    });
+
+    let url = format!("http://{}/", addr);
+
+    let client = awc::Client::new();
+    let res = client.get(&url).send().await.unwrap();
+    assert!(res.extensions().get::<RawResponseHead>().is_none());
+
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().capture_response_head(true))
+        .finish();
+    let res = client.get(&url).send().await.unwrap();
+    let raw = res.extensions().get::<RawResponseHead>().cloned().unwrap();
+    assert_eq!(raw.as_bytes(), HEAD);
+}
+
+#[actix_rt::test]
+async fn client_trailers() {
+    let addr = actix_test::unused_addr();
+    let lst = std::net::TcpListener::bind(addr).unwrap();
+
+    std::thread::spawn(move || {
+        for stream in lst.incoming() {
+            let mut stream = stream.unwrap();
+
+            // read request up to the end of its trailers
+            let mut req = Vec::new();
+            let mut b = [0; 1000];
+            while !req.ends_with(b"x-checksum: abc\r\n\r\n") {
+                let n = stream.read(&mut b).unwrap();
+                assert_ne!(n, 0);
+                req.extend_from_slice(&b[..n]);
+            }
+            let req = String::from_utf8(req).unwrap();
+            assert!(req.contains("transfer-encoding: chunked\r\n"));
+            assert!(req.ends_with("\r\n5\r\nhello\r\n0\r\nx-checksum: abc\r\n\r\n"));
+
+            let _ = stream.write_all(
+                b"HTTP/1.1 200 OK\r\n\
+                transfer-encoding: chunked\r\n\
+                \r\n\
+                8\r\nwelcome!\r\n\
+                0\r\n\
+                grpc-status: 0\r\n\
+                \r\n",
+            );
+        }
+    });
+
+    let trailers = actix_http::client::Trailers::new();
+    let req = awc::Client::new()
+        .post(format!("http://{}/", addr).as_str())
+        .trailers(trailers.clone());
+
+    let mut map = http::HeaderMap::new();
+    map.insert(
+        header::HeaderName::from_static("x-checksum"),
+        header::HeaderValue::from_static("abc"),
+    );
+    trailers.set(map);
+
+    let mut res = req.send_body("hello").await.unwrap();
+    assert!(res.status().is_success());
+    assert!(res.trailers().is_none());
+
+    let bytes = res.body().await.unwrap();
+    assert_eq!(bytes, Bytes::from_static(b"welcome!"));
+    assert_eq!(res.trailers().unwrap().get("grpc-status").unwrap(), "0");
+}
+
+#[actix_rt::test]
+async fn client_timings() {
+    let srv = actix_test::start(|| {
+        App::new().service(web::resource("/").route(web::to(HttpResponse::Ok)))
+    });
+
+    // timings are opt-in
+    let mut res = srv.get("/").send().await.unwrap();
+    assert!(res.status().is_success());
+    res.body().await.unwrap();
+    assert!(res.timings().is_none());
+
+    let client = awc::Client::new();
+    let mut res = client
+        .get(srv.url("/"))
+        .timings(Timings::new())
+        .send()
+        .await
+        .unwrap();
+    res.body().await.unwrap();
+
+    let timings = res.timings().unwrap();
+    // ip address hosts are not resolved
+    assert!(timings.dns().is_none());
+    assert!(timings.connect().is_some());
+    assert!(timings.tls().is_none());
+    assert!(timings.write().is_some());
+    assert!(timings.first_byte().is_some());
+
+    // pooled connection is reused
+    let mut res = client
+        .get(srv.url("/"))
+        .timings(Timings::new())
+        .send()
+        .await
+        .unwrap();
+    res.body().await.unwrap();
+
+    let timings = res.timings().unwrap();
+    assert!(timings.connect().is_none());
+    assert!(timings.write().is_some());
+    assert!(timings.first_byte().is_some());
+}
+
 #[actix_rt::test]
 async fn client_basic_auth() {
     let srv = actix_test::start(|| {
@@ -904,3 +1185,219 @@ async fn test_local_address() {
 
     assert_eq!(res.status(), 200);
 }
+
+// binding to an interface requires CAP_NET_RAW on kernels before 5.7
+#[cfg(target_os = "linux")]
+#[ignore]
+#[actix_rt::test]
+async fn test_interface() {
+    let ip = IpAddr::V4(Ipv4Addr::new(127, 0, 0, 1));
+
+    let srv = actix_test::start(move || {
+        App::new().service(web::resource("/").route(web::to(
+            move |req: HttpRequest| async move {
+                assert_eq!(req.peer_addr().unwrap().ip(), ip);
+                Ok::<_, Error>(HttpResponse::Ok())
+            },
+        )))
+    });
+
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().interface("lo"))
+        .local_address(ip)
+        .finish();
+
+    let res = client.get(srv.url("/")).send().await.unwrap();
+
+    assert_eq!(res.status(), 200);
+}
+
+#[actix_rt::test]
+async fn test_ip_version() {
+    use actix_http::client::IpVersion;
+    use awc::error::ConnectError;
+
+    let srv = actix_test::start(|| {
+        App::new().service(web::resource("/").route(web::to(HttpResponse::Ok)))
+    });
+
+    let client = awc::Client::builder().ip_version(IpVersion::V4).finish();
+    let res = client.get(srv.url("/")).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+
+    // test server only listens on an IPv4 address
+    match client
+        .get(srv.url("/"))
+        .ip_version(IpVersion::V6)
+        .send()
+        .await
+    {
+        Err(SendRequestError::Connect(ConnectError::NoRecords)) => {}
+        res => panic!("unexpected result: {:?}", res.map(|res| res.status())),
+    }
+}
+
+#[actix_rt::test]
+async fn test_h2_prior_knowledge() {
+    use actix_http::client::HttpProtocol;
+
+    let srv = actix_test::start_with(actix_test::config().h2(), || {
+        App::new().service(web::resource("/").route(web::to(HttpResponse::Ok)))
+    });
+
+    let client = awc::Client::builder()
+        .protocol(HttpProtocol::Http2)
+        .finish();
+    let res = client.get(srv.url("/")).send().await.unwrap();
+    assert_eq!(res.status(), 200);
+    assert_eq!(res.version(), http::Version::HTTP_2);
+
+    // per request selection
+    let client = awc::Client::new();
+    let res = client
+        .get(srv.url("/"))
+        .protocol(HttpProtocol::Http2)
+        .send()
+        .await
+        .unwrap();
+    assert_eq!(res.version(), http::Version::HTTP_2);
+
+    let srv = actix_test::start_with(actix_test::config().h1(), || {
+        App::new().service(web::resource("/").route(web::to(HttpResponse::Ok)))
+    });
+
+    let res = client.get(srv.url("/")).send().await.unwrap();
+    assert_eq!(res.version(), http::Version::HTTP_11);
+}
+
+#[actix_rt::test]
+async fn test_h2_multiplexing() {
+    use actix_http::client::HttpProtocol;
+
+    let active = Arc::new(AtomicUsize::new(0));
+    let max_active = Arc::new(AtomicUsize::new(0));
+
+    let srv = actix_test::start_with(actix_test::config().h2(), {
+        let active = active.clone();
+        let max_active = max_active.clone();
+
+        move || {
+            let active = active.clone();
+            let max_active = max_active.clone();
+
+            App::new().service(web::resource("/").route(web::to(move || {
+                let active = active.clone();
+                let max_active = max_active.clone();
+
+                async move {
+                    let n = active.fetch_add(1, Ordering::SeqCst) + 1;
+                    max_active.fetch_max(n, Ordering::SeqCst);
+                    actix_rt::time::sleep(Duration::from_millis(200)).await;
+                    active.fetch_sub(1, Ordering::SeqCst);
+                    Ok::<_, Error>(HttpResponse::Ok())
+                }
+            })))
+        }
+    });
+
+    // requests do not wait for the single connection to be released
+    let client = awc::Client::builder()
+        .protocol(HttpProtocol::Http2)
+        .connector(
+            awc::Connector::new()
+                .limit_per_host(1)
+                .acquire_timeout(Duration::from_millis(100)),
+        )
+        .finish();
+
+    let reqs = (0..4).map(|_| client.get(srv.url("/")).send());
+    for res in futures_util::future::join_all(reqs).await {
+        assert!(res.unwrap().status().is_success());
+    }
+    assert_eq!(max_active.load(Ordering::SeqCst), 4);
+}
+
+#[actix_rt::test]
+async fn test_custom_resolver() {
+    use std::net::SocketAddr;
+
+    use actix_http::client::Resolve;
+    use futures_core::future::LocalBoxFuture;
+
+    struct StaticResolver(SocketAddr);
+
+    impl Resolve for StaticResolver {
+        fn lookup<'a>(
+            &'a self,
+            host: &'a str,
+            _port: u16,
+        ) -> LocalBoxFuture<'a, Result<Vec<SocketAddr>, Box<dyn std::error::Error>>> {
+            let addrs = if host == "service.internal" {
+                vec![self.0]
+            } else {
+                vec![]
+            };
+            Box::pin(async move { Ok(addrs) })
+        }
+    }
+
+    let srv = actix_test::start(|| {
+        App::new().service(web::resource("/").route(web::to(HttpResponse::Ok)))
+    });
+
+    let client = awc::Client::builder()
+        .connector(awc::Connector::new().resolver(StaticResolver(srv.addr())))
+        .finish();
+
+    let res = client.get("http://service.internal/").send().await.unwrap();
+    assert!(res.status().is_success());
+
+    let err = client
+        .get("http://other.internal/")
+        .send()
+        .await
+        .unwrap_err();
+    assert!(matches!(err, SendRequestError::Connect(_)));
+}
+
+#[actix_rt::test]
+async fn test_ipv6() {
+    let srv = actix_test::start_with(actix_test::config().ipv6(), || {
+        App::new().service(web::resource("/").route(web::to(|req: HttpRequest| {
+            HttpResponse::Ok().body(req.connection_info().host().to_owned())
+        })))
+    });
+
+    assert!(srv.addr().is_ipv6());
+    assert!(srv.url("/").starts_with("http://[::1]:"));
+
+    let mut res = srv.get("/").send().await.unwrap();
+    assert!(res.status().is_success());
+
+    let host = res.body().await.unwrap();
+    assert_eq!(host, format!("[::1]:{}", srv.addr().port()));
+}
+
+#[actix_rt::test]
+async fn test_dual_stack() {
+    use std::net::{Ipv6Addr, SocketAddr};
+
+    let srv = actix_test::start_with(actix_test::config().dual_stack(), || {
+        App::new().service(web::resource("/").route(web::to(HttpResponse::Ok)))
+    });
+
+    let client = awc::Client::new();
+
+    for ip in &[
+        IpAddr::V4(Ipv4Addr::LOCALHOST),
+        IpAddr::V6(Ipv6Addr::LOCALHOST),
+    ] {
+        let addr = SocketAddr::new(*ip, srv.addr().port());
+        let res = client
+            .get(actix_test::server_url("http", addr, "/"))
+            .send()
+            .await
+            .unwrap();
+        assert!(res.status().is_success());
+    }
+}
@@ -89,3 +89,76 @@ async fn test_connection_reuse_h2() {
     // one connection
     assert_eq!(num.load(Ordering::Relaxed), 1);
 }
+
+#[actix_rt::test]
+async fn test_session_resumption() {
+    use actix_http::{
+        http::header::HeaderValue, ConnectionData, HttpMessage as _, Request, Response,
+    };
+    use actix_rt::net::TcpStream;
+    use actix_tls::accept::openssl::TlsStream;
+
+    struct SessionReused(bool);
+
+    let srv = test_server(move || {
+        HttpService::build()
+            .on_connect_ext(|io: &TlsStream<TcpStream>, ext| {
+                ext.insert(SessionReused(io.ssl().session_reused()));
+            })
+            .h1(|req: Request| {
+                let ext = req.extensions();
+                let data = ext.get::<ConnectionData>().unwrap();
+                let reused = data.get::<SessionReused>().unwrap().0;
+
+                let mut res = Response::ok();
+                res.headers_mut().insert(
+                    actix_http::http::header::HeaderName::from_static("x-reused"),
+                    HeaderValue::from_static(if reused { "1" } else { "0" }),
+                );
+                ok::<_, actix_http::Error>(res)
+            })
+            .openssl(tls_config())
+            .map_err(|_| ())
+    })
+    .await;
+
+    let client = awc::Client::builder()
+        .connector(
+            awc::Connector::new()
+                .danger_accept_invalid_certs(true)
+                .max_http_version(Version::HTTP_11),
+        )
+        .finish();
+
+    // new connection for each request
+    for reused in ["0", "1", "1"].iter() {
+        let response = client
+            .get(srv.surl("/"))
+            .force_close()
+            .send()
+            .await
+            .unwrap();
+        assert!(response.status().is_success());
+        assert_eq!(response.headers().get("x-reused").unwrap(), reused);
+    }
+
+    // resumption disabled
+    let client = awc::Client::builder()
+        .connector(
+            awc::Connector::new()
+                .danger_accept_invalid_certs(true)
+                .max_http_version(Version::HTTP_11)
+                .tls_session_cache(0),
+        )
+        .finish();
+
+    for _ in 0..2 {
+        let response = client
+            .get(srv.surl("/"))
+            .force_close()
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(response.headers().get("x-reused").unwrap(), "0");
+    }
+}
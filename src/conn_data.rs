@@ -0,0 +1,137 @@
+use std::{
+    any::type_name,
+    future::{ready, Ready},
+    ops::Deref,
+};
+
+use actix_http::ConnectionData;
+
+use crate::{dev::Payload, error::ErrorInternalServerError, Error, FromRequest, HttpRequest};
+
+/// Connection data extractor.
+///
+/// Connection data is inserted by the callback passed to [`HttpServer::on_connect`] before the
+/// first request on a connection is handled. It is commonly used to pass socket metadata, such as
+/// the TTL or local address of a TCP stream, or the identity from a TLS client certificate to
+/// handlers.
+///
+/// The data is read from the [`ConnectionData`](crate::dev::ConnectionData) request extension
+/// only, so it is available to every request on a keep-alive connection and request-local
+/// extensions of the same type are never returned.
+///
+/// Like [`ReqData`](crate::web::ReqData), only types that `impl Clone` can be extracted and a
+/// clone of the data is returned. Extraction fails with an internal server error naming `T` when
+/// the connect callback did not insert a value of that type; use `Option<ConnData<T>>` if the
+/// data is not guaranteed to be present.
+///
+/// # Examples
+/// ```no_run
+/// use std::any::Any;
+/// use actix_web::{dev::Extensions, rt::net::TcpStream, web, App, HttpServer};
+///
+/// #[derive(Clone)]
+/// struct PeerTtl(Option<u32>);
+///
+/// fn on_connect(conn: &dyn Any, data: &mut Extensions) {
+///     if let Some(sock) = conn.downcast_ref::<TcpStream>() {
+///         data.insert(PeerTtl(sock.ttl().ok()));
+///     }
+/// }
+///
+/// async fn index(ttl: web::ConnData<PeerTtl>) -> String {
+///     format!("ttl: {:?}", ttl.0)
+/// }
+///
+/// # async fn run() -> std::io::Result<()> {
+/// HttpServer::new(|| App::new().default_service(web::to(index)))
+///     .on_connect(on_connect)
+///     .bind(("127.0.0.1", 8080))?
+///     .run()
+///     .await
+/// # }
+/// ```
+///
+/// [`HttpServer::on_connect`]: crate::HttpServer::on_connect
+#[derive(Debug, Clone)]
+pub struct ConnData<T: Clone + 'static>(T);
+
+impl<T: Clone + 'static> ConnData<T> {
+    /// Consumes the `ConnData`, returning its wrapped data.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T: Clone + 'static> Deref for ConnData<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: Clone + 'static> FromRequest for ConnData<T> {
+    type Config = ();
+    type Error = Error;
+    type Future = Ready<Result<Self, Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let data = req
+            .extensions()
+            .get::<ConnectionData>()
+            .and_then(|data| data.get::<T>().cloned());
+
+        if let Some(data) = data {
+            ready(Ok(ConnData(data)))
+        } else {
+            log::debug!(
+                "Failed to construct ConnData extractor. \
+                 Request path: {:?} (type: {})",
+                req.path(),
+                type_name::<T>(),
+            );
+            ready(Err(ErrorInternalServerError(format!(
+                "Missing expected connection data of type {}",
+                type_name::<T>()
+            ))))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{dev::Extensions, test::TestRequest};
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct ClientIdentity(&'static str);
+
+    #[actix_rt::test]
+    async fn conn_data_extractor() {
+        let mut conn_ext = Extensions::new();
+        conn_ext.insert(ClientIdentity("client.example"));
+
+        let req = TestRequest::default().to_http_request();
+        req.extensions_mut().insert(ConnectionData::from(conn_ext));
+
+        let data = ConnData::<ClientIdentity>::extract(&req).await.unwrap();
+        assert_eq!(data.into_inner(), ClientIdentity("client.example"));
+    }
+
+    #[actix_rt::test]
+    async fn missing_conn_data() {
+        let req = TestRequest::default().to_http_request();
+
+        // request-local extensions are not connection data
+        req.extensions_mut()
+            .insert(ClientIdentity("client.example"));
+
+        let err = ConnData::<ClientIdentity>::extract(&req).await.unwrap_err();
+        assert!(err.to_string().contains("ClientIdentity"));
+
+        let data = Option::<ConnData<ClientIdentity>>::extract(&req)
+            .await
+            .unwrap();
+        assert!(data.is_none());
+    }
+}
@@ -18,7 +18,11 @@ pub use actix_http::body::{AnyBody, Body, BodySize, MessageBody, ResponseBody, S
 
 #[cfg(feature = "__compress")]
 pub use actix_http::encoding::Decoder as Decompress;
-pub use actix_http::{Extensions, Payload, PayloadStream, RequestHead, ResponseHead};
+#[cfg(feature = "__compress")]
+pub use actix_http::encoding::{CompressionBudget, CompressionConfig};
+pub use actix_http::{
+    ConnectionData, Extensions, Payload, PayloadStream, RequestHead, ResponseHead,
+};
 pub use actix_router::{Path, ResourceDef, ResourcePath, Url};
 pub use actix_server::Server;
 pub use actix_service::{
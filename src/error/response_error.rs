@@ -108,7 +108,11 @@ impl ResponseError for actix_http::error::BlockingError {}
 impl ResponseError for actix_http::error::PayloadError {
     fn status_code(&self) -> StatusCode {
         match *self {
-            actix_http::error::PayloadError::Overflow => StatusCode::PAYLOAD_TOO_LARGE,
+            actix_http::error::PayloadError::Overflow
+            | actix_http::error::PayloadError::DecodeOverflow => StatusCode::PAYLOAD_TOO_LARGE,
+            actix_http::error::PayloadError::UnsupportedEncoding => {
+                StatusCode::UNSUPPORTED_MEDIA_TYPE
+            }
             _ => StatusCode::BAD_REQUEST,
         }
     }
@@ -2,7 +2,7 @@ use std::cmp::Ordering;
 
 use mime::Mime;
 
-use super::{qitem, QualityItem};
+use super::{q, qitem, QualityItem};
 use crate::http::header;
 
 crate::http::header::common_header! {
@@ -208,6 +208,88 @@ impl Accept {
         let types = self.mime_precedence();
         types.first().cloned()
     }
+
+    /// Chooses the media type of a response from the `available` types, accounting for
+    /// [q-factor weighting] and specificity.
+    ///
+    /// Each available type is weighted with the q-factor of the most specific media range that
+    /// matches it, e.g. `text/html;level=1` over `text/html` over `text/*` over `*/*`. The type with
+    /// the highest weight is chosen; ties are broken by the order of `available`. Types with a
+    /// q-factor of zero or that match no media range are not acceptable.
+    ///
+    /// Returns `None` if no available type is acceptable, in which case a `406 Not Acceptable`
+    /// response may be sent. If the header contains no media ranges, the first available type is
+    /// chosen.
+    ///
+    /// ```
+    /// use actix_web::http::header::{Accept, QualityItem, q, qitem};
+    ///
+    /// let accept = Accept(vec![
+    ///     QualityItem::new(mime::TEXT_STAR, q(0.5)),
+    ///     qitem(mime::APPLICATION_JSON),
+    ///     QualityItem::new(mime::TEXT_CSV, q(0.0)),
+    /// ]);
+    ///
+    /// let available = [mime::TEXT_HTML, mime::APPLICATION_JSON];
+    /// assert_eq!(accept.negotiate(&available), Some(mime::APPLICATION_JSON));
+    /// assert_eq!(accept.negotiate(&[mime::TEXT_CSV, mime::TEXT_HTML]), Some(mime::TEXT_HTML));
+    /// assert_eq!(accept.negotiate(&[mime::IMAGE_PNG]), None);
+    /// ```
+    ///
+    /// [q-factor weighting]: https://tools.ietf.org/html/rfc7231#section-5.3.2
+    pub fn negotiate(&self, available: &[Mime]) -> Option<Mime> {
+        if self.0.is_empty() {
+            return available.first().cloned();
+        }
+
+        let mut best = None;
+
+        for mime in available {
+            let quality = self
+                .0
+                .iter()
+                .filter(|range| media_range_matches(&range.item, mime))
+                .max_by_key(|range| specificity(&range.item))
+                .map(|range| range.quality);
+
+            match (quality, best) {
+                (Some(quality), _) if quality == q(0u16) => {}
+                (Some(quality), None) => best = Some((mime, quality)),
+                (Some(quality), Some((_, best_quality))) if quality > best_quality => {
+                    best = Some((mime, quality))
+                }
+                _ => {}
+            }
+        }
+
+        best.map(|(mime, _)| mime.clone())
+    }
+}
+
+/// Returns true if `mime` is within the media `range`, including its parameters.
+fn media_range_matches(range: &Mime, mime: &Mime) -> bool {
+    if range.type_() != mime::STAR && range.type_() != mime.type_() {
+        return false;
+    }
+
+    if range.subtype() != mime::STAR && range.subtype() != mime.subtype() {
+        return false;
+    }
+
+    range
+        .params()
+        .all(|(name, val)| mime.get_param(name) == Some(val))
+}
+
+/// Ranks media ranges by how specific they are; `*/*` < `type/*` < `type/subtype` < with params.
+fn specificity(range: &Mime) -> usize {
+    if range.type_() == mime::STAR {
+        0
+    } else if range.subtype() == mime::STAR {
+        1
+    } else {
+        2 + range.params().count()
+    }
 }
 
 #[cfg(test)]
@@ -269,4 +351,54 @@ mod tests {
         ]);
         assert_eq!(test.mime_preference(), Some(mime::IMAGE_PNG));
     }
+
+    #[test]
+    fn test_negotiate() {
+        let test = Accept(vec![]);
+        assert_eq!(
+            test.negotiate(&[mime::TEXT_HTML, mime::APPLICATION_JSON]),
+            Some(mime::TEXT_HTML)
+        );
+        assert_eq!(test.negotiate(&[]), None);
+
+        // most specific range determines quality
+        let test = Accept(vec![
+            QualityItem::new(mime::TEXT_STAR, q(0.3)),
+            QualityItem::new(mime::TEXT_HTML, q(0.7)),
+            QualityItem::new("text/html;level=1".parse().unwrap(), q(1.0)),
+            QualityItem::new(mime::STAR_STAR, q(0.5)),
+        ]);
+        let html_level_1: Mime = "text/html;level=1".parse().unwrap();
+        assert_eq!(
+            test.negotiate(&[mime::TEXT_HTML, html_level_1.clone()]),
+            Some(html_level_1)
+        );
+        assert_eq!(
+            test.negotiate(&[mime::TEXT_PLAIN, mime::IMAGE_JPEG]),
+            Some(mime::IMAGE_JPEG)
+        );
+        assert_eq!(
+            test.negotiate(&[mime::TEXT_PLAIN, mime::TEXT_HTML]),
+            Some(mime::TEXT_HTML)
+        );
+
+        // ties are broken by order of available types
+        let test = Accept(vec![qitem(mime::STAR_STAR)]);
+        assert_eq!(
+            test.negotiate(&[mime::APPLICATION_JSON, mime::TEXT_HTML]),
+            Some(mime::APPLICATION_JSON)
+        );
+
+        // zero quality excludes type even if a less specific range matches
+        let test = Accept(vec![
+            qitem(mime::IMAGE_STAR),
+            QualityItem::new(mime::IMAGE_GIF, q(0.0)),
+        ]);
+        assert_eq!(test.negotiate(&[mime::IMAGE_GIF]), None);
+        assert_eq!(
+            test.negotiate(&[mime::IMAGE_GIF, mime::IMAGE_PNG]),
+            Some(mime::IMAGE_PNG)
+        );
+        assert_eq!(test.negotiate(&[mime::TEXT_HTML]), None);
+    }
 }
@@ -1,6 +1,8 @@
+use std::cmp;
+
 use language_tags::LanguageTag;
 
-use super::{QualityItem, ACCEPT_LANGUAGE};
+use super::{q, Preference, Quality, QualityItem, ACCEPT_LANGUAGE};
 
 crate::http::header::common_header! {
     /// `Accept-Language` header, defined in
@@ -20,6 +22,7 @@ crate::http::header::common_header! {
     /// # Example values
     /// * `da, en-gb;q=0.8, en;q=0.7`
     /// * `en-us;q=1.0, en;q=0.5, fr`
+    /// * `fr-CH, fr;q=0.9, en;q=0.8, de;q=0.7, *;q=0.5`
     ///
     /// # Examples
     ///
@@ -31,25 +34,26 @@ crate::http::header::common_header! {
     /// let langtag = LanguageTag::parse("en-US").unwrap();
     /// builder.insert_header(
     ///     AcceptLanguage(vec![
-    ///         qitem(langtag),
+    ///         qitem(langtag.into()),
     ///     ])
     /// );
     /// ```
     ///
     /// ```
     /// use actix_web::HttpResponse;
-    /// use actix_web::http::header::{AcceptLanguage, LanguageTag, QualityItem, q, qitem};
+    /// use actix_web::http::header::{AcceptLanguage, LanguageTag, Preference, QualityItem, q, qitem};
     ///
     /// let mut builder = HttpResponse::Ok();
     /// builder.insert_header(
     ///     AcceptLanguage(vec![
-    ///         qitem(LanguageTag::parse("da").unwrap()),
-    ///         QualityItem::new(LanguageTag::parse("en-GB").unwrap(), q(800)),
-    ///         QualityItem::new(LanguageTag::parse("en").unwrap(), q(700)),
+    ///         qitem(LanguageTag::parse("da").unwrap().into()),
+    ///         QualityItem::new(LanguageTag::parse("en-GB").unwrap().into(), q(800)),
+    ///         QualityItem::new(LanguageTag::parse("en").unwrap().into(), q(700)),
+    ///         QualityItem::new(Preference::Any, q(100)),
     ///     ])
     /// );
     /// ```
-    (AcceptLanguage, ACCEPT_LANGUAGE) => (QualityItem<LanguageTag>)+
+    (AcceptLanguage, ACCEPT_LANGUAGE) => (QualityItem<Preference<LanguageTag>>)+
 
     test_accept_language {
         // From the RFC
@@ -62,5 +66,205 @@ crate::http::header::common_header! {
                 QualityItem::new("en".parse().unwrap(), q(500)),
                 qitem("fr".parse().unwrap()),
         ])));
+        crate::http::header::common_header_test!(
+            test3, vec![b"fr-CH, *; q=0.5"],
+            Some(AcceptLanguage(vec![
+                qitem("fr-CH".parse().unwrap()),
+                QualityItem::new(Preference::Any, q(500)),
+        ])));
+    }
+}
+
+impl AcceptLanguage {
+    /// Returns a list of language ranges, sorted by quality, highest first.
+    ///
+    /// Ranges with equal quality keep their order from the header. Ranges with a quality of zero,
+    /// meaning "not acceptable", are excluded.
+    ///
+    /// ```
+    /// use actix_web::http::header::{AcceptLanguage, LanguageTag, Preference, QualityItem, q, qitem};
+    ///
+    /// let accept = AcceptLanguage(vec![
+    ///     QualityItem::new(LanguageTag::parse("en").unwrap().into(), q(0.5)),
+    ///     QualityItem::new(Preference::Any, q(0.1)),
+    ///     qitem(LanguageTag::parse("fr-CH").unwrap().into()),
+    ///     QualityItem::new(LanguageTag::parse("de").unwrap().into(), q(0.0)),
+    /// ]);
+    ///
+    /// assert_eq!(
+    ///     accept.ranked(),
+    ///     vec![
+    ///         Preference::Specific(LanguageTag::parse("fr-CH").unwrap()),
+    ///         Preference::Specific(LanguageTag::parse("en").unwrap()),
+    ///         Preference::Any,
+    ///     ]
+    /// );
+    /// ```
+    pub fn ranked(&self) -> Vec<Preference<LanguageTag>> {
+        let mut ranges = self
+            .0
+            .iter()
+            .filter(|range| range.quality > q(0u16))
+            .collect::<Vec<_>>();
+
+        // stable sort keeps header order for equal qualities
+        ranges.sort_by_key(|range| cmp::Reverse(range.quality));
+
+        ranges.into_iter().map(|range| range.item.clone()).collect()
+    }
+
+    /// Chooses the language of a response from the `available` languages.
+    ///
+    /// Each available language is weighted with the quality of the most specific language range
+    /// matching it, using the basic filtering scheme of
+    /// [RFC 4647 §3.3.1](https://datatracker.ietf.org/doc/html/rfc4647#section-3.3.1): a range
+    /// matches a language tag if it equals the tag or a prefix of it ending at a `-`, ignoring
+    /// case, and `*` matches all tags. The language with the highest weight is chosen; ties are
+    /// broken by the order of `available`. Languages with a quality of zero or that match no range
+    /// are not acceptable.
+    ///
+    /// Returns `None` if no available language is acceptable, in which case a default language
+    /// is usually used. If the header contains no ranges, the first available language is chosen.
+    ///
+    /// ```
+    /// use actix_web::http::header::{AcceptLanguage, LanguageTag, QualityItem, q, qitem};
+    ///
+    /// let accept = AcceptLanguage(vec![
+    ///     qitem(LanguageTag::parse("fr-CH").unwrap().into()),
+    ///     QualityItem::new(LanguageTag::parse("en").unwrap().into(), q(0.8)),
+    /// ]);
+    ///
+    /// let available = [
+    ///     LanguageTag::parse("de").unwrap(),
+    ///     LanguageTag::parse("en-GB").unwrap(),
+    /// ];
+    /// assert_eq!(accept.preferred(&available), Some(LanguageTag::parse("en-GB").unwrap()));
+    /// ```
+    pub fn preferred(&self, available: &[LanguageTag]) -> Option<LanguageTag> {
+        if self.0.is_empty() {
+            return available.first().cloned();
+        }
+
+        let mut best: Option<(&LanguageTag, Quality)> = None;
+
+        for tag in available {
+            let quality = self
+                .0
+                .iter()
+                .filter_map(|range| {
+                    range_specificity(&range.item, tag).map(|spec| (spec, range.quality))
+                })
+                .max_by_key(|(spec, _)| *spec)
+                .map(|(_, quality)| quality);
+
+            if let Some(quality) = quality {
+                if quality > q(0u16) && best.map_or(true, |(_, best)| quality > best) {
+                    best = Some((tag, quality));
+                }
+            }
+        }
+
+        best.map(|(tag, _)| tag.clone())
+    }
+}
+
+/// Returns how specifically `range` matches `tag`, or `None` if it doesn't match.
+///
+/// The wildcard has a specificity of zero; other ranges are ranked by their length.
+fn range_specificity(range: &Preference<LanguageTag>, tag: &LanguageTag) -> Option<usize> {
+    let range = match range {
+        Preference::Any => return Some(0),
+        Preference::Specific(range) => range.as_str(),
+    };
+
+    let tag = tag.as_str();
+
+    let matches = tag.len() >= range.len()
+        && tag[..range.len()].eq_ignore_ascii_case(range)
+        && (tag.len() == range.len() || tag.as_bytes()[range.len()] == b'-');
+
+    if matches {
+        Some(range.len())
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::qitem;
+
+    fn tag(tag: &str) -> LanguageTag {
+        LanguageTag::parse(tag).unwrap()
+    }
+
+    fn range(range: &str, quality: f32) -> QualityItem<Preference<LanguageTag>> {
+        QualityItem::new(range.parse().unwrap(), q(quality))
+    }
+
+    #[test]
+    fn ranked() {
+        let accept = AcceptLanguage(vec![
+            range("en", 0.5),
+            range("*", 0.5),
+            range("de", 0.0),
+            range("fr-CH", 1.0),
+            range("fr", 0.9),
+        ]);
+
+        assert_eq!(
+            accept.ranked(),
+            vec![
+                Preference::Specific(tag("fr-CH")),
+                Preference::Specific(tag("fr")),
+                Preference::Specific(tag("en")),
+                Preference::Any,
+            ]
+        );
+
+        assert!(AcceptLanguage(vec![]).ranked().is_empty());
+    }
+
+    #[test]
+    fn preferred() {
+        let accept = AcceptLanguage(vec![]);
+        assert_eq!(accept.preferred(&[tag("de"), tag("en")]), Some(tag("de")));
+        assert_eq!(accept.preferred(&[]), None);
+
+        let accept = AcceptLanguage(vec![
+            qitem(tag("fr-CH").into()),
+            range("fr", 0.9),
+            range("en", 0.8),
+        ]);
+        assert_eq!(accept.preferred(&[tag("en"), tag("fr")]), Some(tag("fr")));
+        assert_eq!(
+            accept.preferred(&[tag("fr"), tag("fr-ch")]),
+            Some(tag("fr-ch")),
+            "matching ignores case"
+        );
+        assert_eq!(
+            accept.preferred(&[tag("en-US"), tag("de")]),
+            Some(tag("en-US"))
+        );
+        assert_eq!(accept.preferred(&[tag("de")]), None);
+        assert_eq!(
+            accept.preferred(&[tag("fr-BE")]),
+            Some(tag("fr-BE")),
+            "less specific range matches"
+        );
+        assert_eq!(
+            accept.preferred(&[tag("frr")]),
+            None,
+            "range must match whole subtags"
+        );
+    }
+
+    #[test]
+    fn preferred_wildcard() {
+        let accept = AcceptLanguage(vec![range("*", 0.5), range("de", 0.0), range("en", 0.8)]);
+        assert_eq!(accept.preferred(&[tag("es"), tag("en")]), Some(tag("en")));
+        assert_eq!(accept.preferred(&[tag("de"), tag("es")]), Some(tag("es")));
+        assert_eq!(accept.preferred(&[tag("de-AT")]), None);
     }
 }
@@ -8,9 +8,12 @@
 
 use once_cell::sync::Lazy;
 use regex::Regex;
-use std::fmt::{self, Write};
+use std::{
+    borrow::Cow,
+    fmt::{self, Write},
+};
 
-use super::{ExtendedValue, Header, IntoHeaderValue, Writer};
+use super::{Charset, ExtendedValue, Header, IntoHeaderValue, Writer};
 use crate::http::header;
 
 /// Split at the index of the first `needle` if it exists or at the end.
@@ -298,6 +301,66 @@ pub struct ContentDisposition {
 }
 
 impl ContentDisposition {
+    /// Constructs an attachment disposition with the given file name.
+    ///
+    /// See [`set_filename`](Self::set_filename) for how the file name is encoded.
+    ///
+    /// ```
+    /// use actix_web::http::header::ContentDisposition;
+    ///
+    /// let cd = ContentDisposition::attachment("report.pdf");
+    /// assert_eq!(cd.to_string(), "attachment; filename=\"report.pdf\"");
+    ///
+    /// let cd = ContentDisposition::attachment("€ rates.pdf");
+    /// assert_eq!(
+    ///     cd.to_string(),
+    ///     "attachment; filename=\"_ rates.pdf\"; filename*=UTF-8''%E2%82%AC%20rates.pdf"
+    /// );
+    /// assert_eq!(cd.get_filename_decoded().as_deref(), Some("€ rates.pdf"));
+    /// ```
+    pub fn attachment(filename: impl AsRef<str>) -> Self {
+        let mut cd = ContentDisposition {
+            disposition: DispositionType::Attachment,
+            parameters: Vec::new(),
+        };
+        cd.set_filename(filename.as_ref());
+        cd
+    }
+
+    /// Sets the file name, replacing any existing *filename* and *filename\** parameters.
+    ///
+    /// File names consisting of printable ASCII characters are sent as *filename* only. Other
+    /// file names are sent as UTF-8 encoded *filename\**, as recommended by
+    /// [RFC6266](https://tools.ietf.org/html/rfc6266#appendix-D), preceded by a *filename* in
+    /// which characters outside of printable ASCII are replaced with `_` for recipients that do not
+    /// support extended parameters.
+    ///
+    /// For [`FormData`](DispositionType::FormData), where *filename\** must not be used, the file
+    /// name is always sent as *filename*.
+    pub fn set_filename(&mut self, filename: &str) {
+        self.parameters
+            .retain(|param| !param.is_filename() && !param.is_filename_ext());
+
+        if self.is_form_data() || is_printable_ascii(filename) {
+            self.parameters
+                .push(DispositionParam::Filename(filename.to_owned()));
+            return;
+        }
+
+        let fallback = filename
+            .chars()
+            .map(|c| if is_printable_ascii_char(c) { c } else { '_' })
+            .collect();
+
+        self.parameters.push(DispositionParam::Filename(fallback));
+        self.parameters
+            .push(DispositionParam::FilenameExt(ExtendedValue {
+                charset: Charset::Ext(String::from("UTF-8")),
+                language_tag: None,
+                value: filename.as_bytes().to_vec(),
+            }));
+    }
+
     /// Parse a raw Content-Disposition header value.
     pub fn from_raw(hv: &header::HeaderValue) -> Result<Self, crate::error::ParseError> {
         // `header::from_one_raw_str` invokes `hv.to_str` which assumes `hv` contains only visible
@@ -427,6 +490,17 @@ impl ContentDisposition {
             .find_map(DispositionParam::as_filename_ext)
     }
 
+    /// Return the file name, decoding *filename\** if exists and falling back to *filename*.
+    ///
+    /// As specified in [RFC6266](https://tools.ietf.org/html/rfc6266#section-4.3), *filename\**
+    /// takes precedence when both are present. It is only used if its charset is UTF-8,
+    /// ISO-8859-1 or US-ASCII and its value is valid in that charset.
+    pub fn get_filename_decoded(&self) -> Option<Cow<'_, str>> {
+        self.get_filename_ext()
+            .and_then(decode_ext_value)
+            .or_else(|| self.get_filename().map(Cow::Borrowed))
+    }
+
     /// Return the value of the parameter which the `name` matches.
     pub fn get_unknown(&self, name: impl AsRef<str>) -> Option<&str> {
         let name = name.as_ref();
@@ -440,6 +514,35 @@ impl ContentDisposition {
     }
 }
 
+fn is_printable_ascii_char(c: char) -> bool {
+    c.is_ascii() && !c.is_ascii_control()
+}
+
+fn is_printable_ascii(val: &str) -> bool {
+    val.chars().all(is_printable_ascii_char)
+}
+
+/// Decode value of extended parameter into a string if its charset is supported.
+fn decode_ext_value(ext_value: &ExtendedValue) -> Option<Cow<'_, str>> {
+    match ext_value.charset {
+        Charset::Ext(ref charset) if charset.eq_ignore_ascii_case("UTF-8") => {
+            std::str::from_utf8(&ext_value.value)
+                .ok()
+                .map(Cow::Borrowed)
+        }
+        Charset::Us_Ascii if ext_value.value.is_ascii() => {
+            std::str::from_utf8(&ext_value.value)
+                .ok()
+                .map(Cow::Borrowed)
+        }
+        // ISO-8859-1 code points map directly to the first 256 Unicode code points
+        Charset::Iso_8859_1 => Some(Cow::Owned(
+            ext_value.value.iter().map(|&b| char::from(b)).collect(),
+        )),
+        _ => None,
+    }
+}
+
 impl IntoHeaderValue for ContentDisposition {
     type Error = header::InvalidHeaderValue;
 
@@ -964,4 +1067,65 @@ mod tests {
         assert_eq!(cd.get_unknown_ext("dummy"), None);
         assert_eq!(cd.get_unknown("duMMy"), Some("3"));
     }
+
+    #[test]
+    fn test_set_filename() {
+        let cd = ContentDisposition::attachment("sample.txt");
+        assert_eq!(
+            cd.parameters,
+            vec![DispositionParam::Filename("sample.txt".to_owned())]
+        );
+        assert_eq!(cd.to_string(), "attachment; filename=\"sample.txt\"");
+
+        let mut cd = ContentDisposition::attachment("\u{1f600} \"smile\".svg");
+        assert_eq!(
+            cd.to_string(),
+            "attachment; filename=\"_ \\\"smile\\\".svg\"; \
+             filename*=UTF-8''%F0%9F%98%80%20%22smile%22.svg"
+        );
+
+        // setting again replaces both parameters
+        cd.set_filename("plain.svg");
+        assert_eq!(cd.to_string(), "attachment; filename=\"plain.svg\"");
+
+        let mut cd = ContentDisposition {
+            disposition: DispositionType::FormData,
+            parameters: vec![DispositionParam::Name("upload".to_owned())],
+        };
+        cd.set_filename("文件.txt");
+        assert_eq!(
+            cd.to_string(),
+            "form-data; name=upload; filename=\"文件.txt\""
+        );
+    }
+
+    #[test]
+    fn test_get_filename_decoded() {
+        let a = HeaderValue::from_static(
+            "attachment; filename=\"EURO rates\"; filename*=utf-8''%e2%82%ac%20rates",
+        );
+        let cd = ContentDisposition::from_raw(&a).unwrap();
+        assert_eq!(cd.get_filename_decoded().as_deref(), Some("\u{20ac} rates"));
+
+        let a = HeaderValue::from_static("attachment; filename*=iso-8859-1'en'%A3%20rates");
+        let cd = ContentDisposition::from_raw(&a).unwrap();
+        assert_eq!(cd.get_filename_decoded().as_deref(), Some("\u{a3} rates"));
+
+        // invalid UTF-8 and unsupported charsets fall back to filename
+        let a = HeaderValue::from_static(
+            "attachment; filename*=UTF-8''%A3%20rates; filename=\"rates\"",
+        );
+        let cd = ContentDisposition::from_raw(&a).unwrap();
+        assert_eq!(cd.get_filename_decoded().as_deref(), Some("rates"));
+
+        let a = HeaderValue::from_static(
+            "attachment; filename*=Shift_JIS''%83%65; filename=\"te\"",
+        );
+        let cd = ContentDisposition::from_raw(&a).unwrap();
+        assert_eq!(cd.get_filename_decoded().as_deref(), Some("te"));
+
+        let a = HeaderValue::from_static("inline");
+        let cd = ContentDisposition::from_raw(&a).unwrap();
+        assert_eq!(cd.get_filename_decoded(), None);
+    }
 }
@@ -1,7 +1,9 @@
 use std::fmt::{self, Display, Write};
 use std::str::FromStr;
 
-use super::{HeaderValue, IntoHeaderValue, InvalidHeaderValue, Writer, CONTENT_RANGE};
+use super::{
+    HeaderValue, IntoHeaderValue, InvalidHeaderValue, RangeUnits, Writer, CONTENT_RANGE,
+};
 use crate::error::ParseError;
 
 crate::http::header::common_header! {
@@ -66,6 +68,39 @@ crate::http::header::common_header! {
             vec![b"bytes 1-2-3/500"],
             None::<ContentRange>);
 
+        #[test]
+        fn test_registered() {
+            use crate::http::header::RangeUnit;
+
+            struct Frames;
+
+            impl RangeUnit for Frames {
+                fn validate_content_range(&self, resp: &str) -> bool {
+                    resp.contains('/')
+                }
+            }
+
+            let units = RangeUnits::new().unit("frames", Frames);
+
+            let spec = units.parse_content_range("Frames 1-2/10").unwrap();
+            assert_eq!(
+                spec,
+                ContentRangeSpec::Registered {
+                    unit: "Frames".to_owned(),
+                    resp: "1-2/10".to_owned()
+                }
+            );
+            assert_eq!(spec.unit(), "Frames");
+            assert_eq!(spec.to_string(), "Frames 1-2/10");
+
+            assert!(units.parse_content_range("frames 1-2").is_err());
+
+            let spec = units.parse_content_range("pages 1-2").unwrap();
+            assert!(matches!(spec, ContentRangeSpec::Unregistered { .. }));
+
+            let spec = "frames 1-2".parse::<ContentRangeSpec>().unwrap();
+            assert!(matches!(spec, ContentRangeSpec::Unregistered { .. }));
+        }
     }
 }
 
@@ -109,20 +144,29 @@ pub enum ContentRangeSpec {
         /// other-range-resp
         resp: String,
     },
-}
 
-fn split_in_two(s: &str, separator: char) -> Option<(&str, &str)> {
-    let mut iter = s.splitn(2, separator);
-    match (iter.next(), iter.next()) {
-        (Some(a), Some(b)) => Some((a, b)),
-        _ => None,
-    }
+    /// Custom range, with unit found in a [`RangeUnits`] registry and a range response accepted
+    /// by it
+    Registered {
+        /// other-range-unit
+        unit: String,
+
+        /// other-range-resp
+        resp: String,
+    },
 }
 
-impl FromStr for ContentRangeSpec {
-    type Err = ParseError;
+impl ContentRangeSpec {
+    /// Returns range unit, e.g. `bytes`.
+    pub fn unit(&self) -> &str {
+        match self {
+            ContentRangeSpec::Bytes { .. } => "bytes",
+            ContentRangeSpec::Unregistered { unit, .. }
+            | ContentRangeSpec::Registered { unit, .. } => unit,
+        }
+    }
 
-    fn from_str(s: &str) -> Result<Self, ParseError> {
+    pub(super) fn parse_with(s: &str, units: &RangeUnits) -> Result<Self, ParseError> {
         let res = match split_in_two(s, ' ') {
             Some(("bytes", resp)) => {
                 let (range, instance_length) =
@@ -152,9 +196,18 @@ impl FromStr for ContentRangeSpec {
                     instance_length,
                 }
             }
-            Some((unit, resp)) => ContentRangeSpec::Unregistered {
-                unit: unit.to_owned(),
-                resp: resp.to_owned(),
+            Some((unit, resp)) => match units.get(unit) {
+                Some(range_unit) if range_unit.validate_content_range(resp) => {
+                    ContentRangeSpec::Registered {
+                        unit: unit.to_owned(),
+                        resp: resp.to_owned(),
+                    }
+                }
+                Some(_) => return Err(ParseError::Header),
+                None => ContentRangeSpec::Unregistered {
+                    unit: unit.to_owned(),
+                    resp: resp.to_owned(),
+                },
             },
             _ => return Err(ParseError::Header),
         };
@@ -162,6 +215,22 @@ impl FromStr for ContentRangeSpec {
     }
 }
 
+fn split_in_two(s: &str, separator: char) -> Option<(&str, &str)> {
+    let mut iter = s.splitn(2, separator);
+    match (iter.next(), iter.next()) {
+        (Some(a), Some(b)) => Some((a, b)),
+        _ => None,
+    }
+}
+
+impl FromStr for ContentRangeSpec {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, ParseError> {
+        Self::parse_with(s, &RangeUnits::new())
+    }
+}
+
 impl Display for ContentRangeSpec {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
@@ -185,7 +254,8 @@ impl Display for ContentRangeSpec {
                     f.write_str("*")
                 }
             }
-            ContentRangeSpec::Unregistered { ref unit, ref resp } => {
+            ContentRangeSpec::Unregistered { ref unit, ref resp }
+            | ContentRangeSpec::Registered { ref unit, ref resp } => {
                 f.write_str(unit)?;
                 f.write_str(" ")?;
                 f.write_str(resp)
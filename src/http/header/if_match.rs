@@ -68,3 +68,19 @@ crate::http::header::common_header! {
         crate::http::header::common_header_test!(test3, vec![b"*"], Some(IfMatch::Any));
     }
 }
+
+impl IfMatch {
+    /// Returns true if the header matches the current entity tag of the resource, using the
+    /// strong comparison function.
+    ///
+    /// `Any` matches any resource, whether or not it has an entity tag.
+    pub fn matches(&self, etag: Option<&EntityTag>) -> bool {
+        match (self, etag) {
+            (IfMatch::Any, _) => true,
+            (IfMatch::Items(items), Some(etag)) => {
+                items.iter().any(|item| item.strong_eq(etag))
+            }
+            (IfMatch::Items(_), None) => false,
+        }
+    }
+}
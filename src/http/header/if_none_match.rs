@@ -63,6 +63,23 @@ crate::http::header::common_header! {
     }
 }
 
+impl IfNoneMatch {
+    /// Returns true if the header matches the current entity tag of the resource, using the weak
+    /// comparison function.
+    ///
+    /// `Any` matches any resource, whether or not it has an entity tag. A matching header means
+    /// the request's condition is false.
+    pub fn matches(&self, etag: Option<&EntityTag>) -> bool {
+        match (self, etag) {
+            (IfNoneMatch::Any, _) => true,
+            (IfNoneMatch::Items(items), Some(etag)) => {
+                items.iter().any(|item| item.weak_eq(etag))
+            }
+            (IfNoneMatch::Items(_), None) => false,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::IfNoneMatch;
@@ -91,4 +108,20 @@ mod tests {
         entities.push(weak_etag);
         assert_eq!(if_none_match.ok(), Some(IfNoneMatch::Items(entities)));
     }
+
+    #[test]
+    fn test_if_none_match_matches() {
+        let strong = EntityTag::strong("xyzzy".to_owned());
+        let weak = EntityTag::weak("xyzzy".to_owned());
+        let other = EntityTag::strong("other".to_owned());
+
+        let header = IfNoneMatch::Items(vec![weak.clone()]);
+        assert!(header.matches(Some(&strong)));
+        assert!(header.matches(Some(&weak)));
+        assert!(!header.matches(Some(&other)));
+        assert!(!header.matches(None));
+
+        assert!(IfNoneMatch::Any.matches(None));
+        assert!(IfNoneMatch::Any.matches(Some(&other)));
+    }
 }
@@ -0,0 +1,432 @@
+use std::fmt::{self, Write};
+
+use super::{Header, IntoHeaderValue, Writer};
+use crate::http::header;
+
+/// `Link` header, defined in [RFC 8288](https://datatracker.ietf.org/doc/html/rfc8288#section-3)
+///
+/// The `Link` header field provides a means for serializing one or more links into HTTP headers.
+/// Each link has a target URI and parameters describing its relation to the current context,
+/// such as `rel="next"` for pagination or `rel="preload"` for resource hints sent with
+/// `103 Early Hints` responses.
+///
+/// # ABNF
+///
+/// ```text
+/// Link       = #link-value
+/// link-value = "<" URI-Reference ">" *( OWS ";" OWS link-param )
+/// link-param = token BWS [ "=" BWS ( token / quoted-string ) ]
+/// ```
+///
+/// # Example values
+///
+/// * `<https://example.com/items?page=2>; rel="next"`
+/// * `</style.css>; rel=preload; type="text/css", </app.js>; rel=preload`
+/// * `<https://example.com/about>; rel="author"; title="About us"`
+///
+/// # Examples
+///
+/// ```
+/// use actix_web::HttpResponse;
+/// use actix_web::http::header::{Link, LinkValue};
+///
+/// let mut builder = HttpResponse::Ok();
+/// builder.insert_header(Link(vec![
+///     LinkValue::new("/items?page=1").rel("prev"),
+///     LinkValue::new("/items?page=3").rel("next"),
+/// ]));
+/// ```
+///
+/// ```
+/// use actix_web::HttpResponse;
+/// use actix_web::http::header::{Link, LinkValue};
+///
+/// let mut builder = HttpResponse::Ok();
+/// builder.insert_header(Link(vec![LinkValue::new("/style.css")
+///     .rel("preload")
+///     .media_type("text/css")
+///     .param("as", "style")]));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Link(pub Vec<LinkValue>);
+
+crate::http::header::common_header_deref!(Link => Vec<LinkValue>);
+
+/// A single link of a [`Link`] header.
+///
+/// Parameters other than `rel`, `type` and `title` are kept in order as extension parameters.
+/// When parsing, only the first occurrence of `rel`, `type` and `title` is used.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LinkValue {
+    /// Target URI of the link, as written in the header.
+    pub uri: String,
+
+    /// Relation types of the link, separated by spaces.
+    pub rel: Option<String>,
+
+    /// Hint for the media type of the link target.
+    pub media_type: Option<String>,
+
+    /// Human-readable label of the link.
+    pub title: Option<String>,
+
+    /// Extension parameters as name-value pairs. Parameters without a value have an empty value.
+    pub params: Vec<(String, String)>,
+}
+
+impl LinkValue {
+    /// Constructs a link to `uri` without parameters.
+    pub fn new(uri: impl Into<String>) -> Self {
+        LinkValue {
+            uri: uri.into(),
+            rel: None,
+            media_type: None,
+            title: None,
+            params: Vec::new(),
+        }
+    }
+
+    /// Sets relation types of the link, separated by spaces.
+    pub fn rel(mut self, rel: impl Into<String>) -> Self {
+        self.rel = Some(rel.into());
+        self
+    }
+
+    /// Sets media type hint of the link target.
+    pub fn media_type(mut self, media_type: impl Into<String>) -> Self {
+        self.media_type = Some(media_type.into());
+        self
+    }
+
+    /// Sets human-readable label of the link.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    /// Appends an extension parameter.
+    pub fn param(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.params.push((name.into(), value.into()));
+        self
+    }
+
+    /// Returns true if `rel` is one of the relation types of the link, ignoring ASCII case.
+    pub fn has_rel(&self, rel: &str) -> bool {
+        self.rel.as_deref().map_or(false, |rels| {
+            rels.split_ascii_whitespace()
+                .any(|item| item.eq_ignore_ascii_case(rel))
+        })
+    }
+
+    fn set_param(&mut self, name: &str, value: String) {
+        if name.eq_ignore_ascii_case("rel") {
+            self.rel.get_or_insert(value);
+        } else if name.eq_ignore_ascii_case("type") {
+            self.media_type.get_or_insert(value);
+        } else if name.eq_ignore_ascii_case("title") {
+            self.title.get_or_insert(value);
+        } else {
+            self.params.push((name.to_owned(), value));
+        }
+    }
+}
+
+impl Link {
+    /// Returns the first link with relation type `rel`, ignoring ASCII case.
+    ///
+    /// ```
+    /// use actix_web::http::header::{Link, LinkValue};
+    ///
+    /// let link = Link(vec![
+    ///     LinkValue::new("/items?page=1").rel("prev first"),
+    ///     LinkValue::new("/items?page=3").rel("next"),
+    /// ]);
+    ///
+    /// assert_eq!(link.find_rel("first").unwrap().uri, "/items?page=1");
+    /// assert_eq!(link.find_rel("NEXT").unwrap().uri, "/items?page=3");
+    /// assert!(link.find_rel("last").is_none());
+    /// ```
+    pub fn find_rel(&self, rel: &str) -> Option<&LinkValue> {
+        self.0.iter().find(|link| link.has_rel(rel))
+    }
+
+    /// Parses links from a raw `Link` header value and appends them to `links`.
+    fn parse_value(
+        mut val: &str,
+        links: &mut Vec<LinkValue>,
+    ) -> Result<(), crate::error::ParseError> {
+        loop {
+            val = val.trim_start_matches(|c: char| c == ',' || c.is_ascii_whitespace());
+            if val.is_empty() {
+                return Ok(());
+            }
+
+            let (uri, rest) = val
+                .strip_prefix('<')
+                .and_then(|rest| split_once(rest, '>'))
+                .ok_or(crate::error::ParseError::Header)?;
+
+            let mut link = LinkValue::new(uri.trim());
+            val = rest.trim_start();
+
+            while let Some(rest) = val.strip_prefix(';') {
+                let rest = rest.trim_start();
+
+                let name_end = rest
+                    .find(|c: char| matches!(c, '=' | ';' | ',') || c.is_ascii_whitespace())
+                    .unwrap_or(rest.len());
+                let (name, rest) = rest.split_at(name_end);
+                if name.is_empty() {
+                    return Err(crate::error::ParseError::Header);
+                }
+
+                let rest = rest.trim_start();
+                let (value, rest) = match rest.strip_prefix('=') {
+                    Some(rest) => parse_param_value(rest.trim_start())?,
+                    None => (String::new(), rest),
+                };
+
+                link.set_param(name, value);
+                val = rest.trim_start();
+            }
+
+            if !val.is_empty() && !val.starts_with(',') {
+                return Err(crate::error::ParseError::Header);
+            }
+
+            links.push(link);
+        }
+    }
+}
+
+/// Split at the index of the first `needle`, if it exists.
+fn split_once(haystack: &str, needle: char) -> Option<(&str, &str)> {
+    let idx = haystack.find(needle)?;
+    Some((&haystack[..idx], &haystack[idx + needle.len_utf8()..]))
+}
+
+/// Parses a token or quoted-string parameter value, returning it with the remaining input.
+fn parse_param_value(val: &str) -> Result<(String, &str), crate::error::ParseError> {
+    let quoted = match val.strip_prefix('"') {
+        Some(quoted) => quoted,
+        None => {
+            let end = val
+                .find(|c: char| matches!(c, ';' | ',') || c.is_ascii_whitespace())
+                .unwrap_or(val.len());
+            return Ok((val[..end].to_owned(), &val[end..]));
+        }
+    };
+
+    let mut value = String::new();
+    let mut chars = quoted.char_indices();
+
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '"' => return Ok((value, &quoted[idx + 1..])),
+            '\\' => match chars.next() {
+                Some((_, c)) => value.push(c),
+                None => break,
+            },
+            c => value.push(c),
+        }
+    }
+
+    // unterminated quoted-string
+    Err(crate::error::ParseError::Header)
+}
+
+/// Returns true if `val` can be written as a token, without quoting.
+fn is_token(val: &str) -> bool {
+    !val.is_empty()
+        && val
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+fn fmt_quoted(f: &mut fmt::Formatter<'_>, val: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in val.chars() {
+        if c == '"' || c == '\\' {
+            f.write_char('\\')?;
+        }
+        f.write_char(c)?;
+    }
+    f.write_char('"')
+}
+
+impl fmt::Display for LinkValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<{}>", self.uri)?;
+
+        if let Some(ref rel) = self.rel {
+            f.write_str("; rel=")?;
+            fmt_quoted(f, rel)?;
+        }
+
+        if let Some(ref media_type) = self.media_type {
+            f.write_str("; type=")?;
+            fmt_quoted(f, media_type)?;
+        }
+
+        if let Some(ref title) = self.title {
+            f.write_str("; title=")?;
+            fmt_quoted(f, title)?;
+        }
+
+        for (name, value) in &self.params {
+            write!(f, "; {}", name)?;
+
+            if is_token(value) {
+                write!(f, "={}", value)?;
+            } else if !value.is_empty() {
+                f.write_char('=')?;
+                fmt_quoted(f, value)?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl fmt::Display for Link {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        header::fmt_comma_delimited(f, &self.0)
+    }
+}
+
+impl IntoHeaderValue for Link {
+    type Error = header::InvalidHeaderValue;
+
+    fn try_into_value(self) -> Result<header::HeaderValue, Self::Error> {
+        let mut writer = Writer::new();
+        let _ = write!(&mut writer, "{}", self);
+        header::HeaderValue::from_maybe_shared(writer.take())
+    }
+}
+
+impl Header for Link {
+    fn name() -> header::HeaderName {
+        header::LINK
+    }
+
+    fn parse<T: crate::HttpMessage>(msg: &T) -> Result<Self, crate::error::ParseError> {
+        let mut links = Vec::new();
+
+        for val in msg.headers().get_all(&Self::name()) {
+            let val = val.to_str().map_err(|_| crate::error::ParseError::Header)?;
+            Link::parse_value(val, &mut links)?;
+        }
+
+        if links.is_empty() {
+            return Err(crate::error::ParseError::Header);
+        }
+
+        Ok(Link(links))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{http::header::LINK, test::TestRequest};
+
+    fn parse(val: &'static str) -> Result<Link, crate::error::ParseError> {
+        let req = TestRequest::default()
+            .insert_header((LINK, val))
+            .to_http_request();
+        Link::parse(&req)
+    }
+
+    #[test]
+    fn parse_single() {
+        let link = parse("<https://example.com/items?page=2>; rel=\"next\"").unwrap();
+        assert_eq!(
+            link,
+            Link(vec![
+                LinkValue::new("https://example.com/items?page=2").rel("next")
+            ])
+        );
+    }
+
+    #[test]
+    fn parse_multiple() {
+        let link = parse(
+            "</style.css>; rel=preload; type=\"text/css\"; as=style, \
+             <https://example.com/a,b>;rel=\"prev first\" ; title=\"Page \\\"1\\\", first\"; crossorigin",
+        )
+        .unwrap();
+
+        assert_eq!(
+            link,
+            Link(vec![
+                LinkValue::new("/style.css")
+                    .rel("preload")
+                    .media_type("text/css")
+                    .param("as", "style"),
+                LinkValue::new("https://example.com/a,b")
+                    .rel("prev first")
+                    .title("Page \"1\", first")
+                    .param("crossorigin", ""),
+            ])
+        );
+
+        assert!(link[1].has_rel("FIRST"));
+        assert!(!link[1].has_rel("next"));
+    }
+
+    #[test]
+    fn parse_first_param_wins() {
+        let link = parse("</a>; rel=next; REL=prev; Type=text/html").unwrap();
+        assert_eq!(link[0].rel.as_deref(), Some("next"));
+        assert_eq!(link[0].media_type.as_deref(), Some("text/html"));
+    }
+
+    #[test]
+    fn parse_multiple_header_lines() {
+        let req = TestRequest::default()
+            .append_header((LINK, "</a>; rel=prev"))
+            .append_header((LINK, "</b>; rel=next"))
+            .to_http_request();
+
+        let link = Link::parse(&req).unwrap();
+        assert_eq!(link.len(), 2);
+        assert_eq!(link.find_rel("next").unwrap().uri, "/b");
+    }
+
+    #[test]
+    fn parse_invalid() {
+        assert!(parse("").is_err());
+        assert!(parse("/a; rel=next").is_err());
+        assert!(parse("</a; rel=next").is_err());
+        assert!(parse("</a>; rel=\"next").is_err());
+        assert!(parse("</a>; =next").is_err());
+        assert!(parse("</a> rel=next").is_err());
+    }
+
+    #[test]
+    fn display() {
+        let link = Link(vec![
+            LinkValue::new("/style.css")
+                .rel("preload")
+                .media_type("text/css")
+                .param("as", "style")
+                .param("crossorigin", ""),
+            LinkValue::new("/about")
+                .title("About \"us\"")
+                .param("hreflang", "en us"),
+        ]);
+
+        assert_eq!(
+            link.to_string(),
+            "</style.css>; rel=\"preload\"; type=\"text/css\"; as=style; crossorigin, \
+             </about>; title=\"About \\\"us\\\"\"; hreflang=\"en us\""
+        );
+    }
+
+    #[test]
+    fn roundtrip() {
+        let val = "</a>; rel=\"next\"; title=\"a, \\\\b\"; x=\"1 2\", </b>";
+        let link = parse(val).unwrap();
+        assert_eq!(link.to_string(), val);
+    }
+}
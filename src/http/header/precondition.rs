@@ -0,0 +1,220 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use super::{EntityTag, HttpDate, IfMatch, IfModifiedSince, IfNoneMatch, IfUnmodifiedSince};
+use crate::{
+    http::{Method, StatusCode},
+    HttpMessage, HttpRequest,
+};
+
+/// Evaluates the conditional request headers of `req` against the current validators of the
+/// target resource, following [RFC 7232 §6].
+///
+/// Returns the status code the response should have:
+/// - `412 Precondition Failed` if `If-Match` doesn't match `etag` or, in absence of `If-Match`,
+///   the resource was modified after `If-Unmodified-Since`.
+/// - `304 Not Modified` for GET and HEAD requests if `If-None-Match` matches `etag` or, in
+///   absence of `If-None-Match`, the resource was not modified after `If-Modified-Since`.
+/// - `412 Precondition Failed` for other methods if `If-None-Match` matches `etag`.
+/// - `200 OK` otherwise, meaning the request should be processed normally.
+///
+/// `If-Match` uses the strong comparison function and `If-None-Match` the weak comparison
+/// function. Date based preconditions are ignored if `last_modified` is not known.
+///
+/// ```
+/// use actix_web::{
+///     http::{header::{self, EntityTag}, StatusCode},
+///     test::TestRequest,
+/// };
+///
+/// let etag = EntityTag::strong("xyzzy".to_owned());
+///
+/// let req = TestRequest::default()
+///     .insert_header((header::IF_NONE_MATCH, "W/\"xyzzy\""))
+///     .to_http_request();
+/// assert_eq!(
+///     header::evaluate_preconditions(&req, Some(&etag), None),
+///     StatusCode::NOT_MODIFIED,
+/// );
+/// ```
+///
+/// [RFC 7232 §6]: https://datatracker.ietf.org/doc/html/rfc7232#section-6
+pub fn evaluate_preconditions(
+    req: &HttpRequest,
+    etag: Option<&EntityTag>,
+    last_modified: Option<HttpDate>,
+) -> StatusCode {
+    if let Some(if_match) = req.get_header::<IfMatch>() {
+        if !if_match.matches(etag) {
+            return StatusCode::PRECONDITION_FAILED;
+        }
+    } else if let (Some(modified), Some(IfUnmodifiedSince(since))) =
+        (last_modified, req.get_header())
+    {
+        if is_modified_since(modified, since) {
+            return StatusCode::PRECONDITION_FAILED;
+        }
+    }
+
+    let is_get_or_head = matches!(*req.method(), Method::GET | Method::HEAD);
+
+    if let Some(if_none_match) = req.get_header::<IfNoneMatch>() {
+        if if_none_match.matches(etag) {
+            return if is_get_or_head {
+                StatusCode::NOT_MODIFIED
+            } else {
+                StatusCode::PRECONDITION_FAILED
+            };
+        }
+    } else if let (true, Some(modified), Some(IfModifiedSince(since))) =
+        (is_get_or_head, last_modified, req.get_header())
+    {
+        if !is_modified_since(modified, since) {
+            return StatusCode::NOT_MODIFIED;
+        }
+    }
+
+    StatusCode::OK
+}
+
+/// Compares dates with one second resolution, the precision of HTTP dates.
+fn is_modified_since(modified: HttpDate, since: HttpDate) -> bool {
+    let modified: SystemTime = modified.into();
+    let since: SystemTime = since.into();
+
+    match (
+        modified.duration_since(UNIX_EPOCH),
+        since.duration_since(UNIX_EPOCH),
+    ) {
+        (Ok(modified), Ok(since)) => modified.as_secs() > since.as_secs(),
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{http::header, test::TestRequest};
+
+    fn date(secs: u64) -> HttpDate {
+        (UNIX_EPOCH + Duration::from_secs(secs)).into()
+    }
+
+    #[test]
+    fn no_preconditions() {
+        let req = TestRequest::default().to_http_request();
+        let etag = EntityTag::strong("a".to_owned());
+        assert_eq!(
+            evaluate_preconditions(&req, Some(&etag), Some(date(1000))),
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn if_match() {
+        let strong = EntityTag::strong("a".to_owned());
+        let weak = EntityTag::weak("a".to_owned());
+
+        let req = TestRequest::put()
+            .insert_header((header::IF_MATCH, "\"a\""))
+            .to_http_request();
+        assert_eq!(
+            evaluate_preconditions(&req, Some(&strong), None),
+            StatusCode::OK
+        );
+        assert_eq!(
+            evaluate_preconditions(&req, Some(&weak), None),
+            StatusCode::PRECONDITION_FAILED
+        );
+        assert_eq!(
+            evaluate_preconditions(&req, None, None),
+            StatusCode::PRECONDITION_FAILED
+        );
+
+        // If-Unmodified-Since is ignored when If-Match is present
+        let req = TestRequest::put()
+            .insert_header((header::IF_MATCH, "*"))
+            .insert_header(header::IfUnmodifiedSince(date(1000)))
+            .to_http_request();
+        assert_eq!(
+            evaluate_preconditions(&req, Some(&strong), Some(date(2000))),
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn if_unmodified_since() {
+        let req = TestRequest::put()
+            .insert_header(header::IfUnmodifiedSince(date(1000)))
+            .to_http_request();
+        assert_eq!(
+            evaluate_preconditions(&req, None, Some(date(1000))),
+            StatusCode::OK
+        );
+        assert_eq!(
+            evaluate_preconditions(&req, None, Some(date(1001))),
+            StatusCode::PRECONDITION_FAILED
+        );
+        assert_eq!(evaluate_preconditions(&req, None, None), StatusCode::OK);
+    }
+
+    #[test]
+    fn if_none_match() {
+        let etag = EntityTag::strong("a".to_owned());
+        let other = EntityTag::strong("b".to_owned());
+
+        let req = TestRequest::get()
+            .insert_header((header::IF_NONE_MATCH, "W/\"a\""))
+            .to_http_request();
+        assert_eq!(
+            evaluate_preconditions(&req, Some(&etag), None),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            evaluate_preconditions(&req, Some(&other), None),
+            StatusCode::OK
+        );
+
+        let req = TestRequest::post()
+            .insert_header((header::IF_NONE_MATCH, "*"))
+            .to_http_request();
+        assert_eq!(
+            evaluate_preconditions(&req, Some(&etag), None),
+            StatusCode::PRECONDITION_FAILED
+        );
+
+        // If-Modified-Since is ignored when If-None-Match is present
+        let req = TestRequest::get()
+            .insert_header((header::IF_NONE_MATCH, "\"b\""))
+            .insert_header(header::IfModifiedSince(date(2000)))
+            .to_http_request();
+        assert_eq!(
+            evaluate_preconditions(&req, Some(&etag), Some(date(1000))),
+            StatusCode::OK
+        );
+    }
+
+    #[test]
+    fn if_modified_since() {
+        let req = TestRequest::get()
+            .insert_header(header::IfModifiedSince(date(1000)))
+            .to_http_request();
+        assert_eq!(
+            evaluate_preconditions(&req, None, Some(date(1000))),
+            StatusCode::NOT_MODIFIED
+        );
+        assert_eq!(
+            evaluate_preconditions(&req, None, Some(date(1001))),
+            StatusCode::OK
+        );
+
+        let req = TestRequest::put()
+            .insert_header(header::IfModifiedSince(date(1000)))
+            .to_http_request();
+        assert_eq!(
+            evaluate_preconditions(&req, None, Some(date(1000))),
+            StatusCode::OK
+        );
+    }
+}
@@ -1,8 +1,16 @@
-use std::fmt::{self, Display};
+use std::fmt::{self, Display, Write};
 use std::str::FromStr;
+use std::sync::Arc;
 
-use super::parsing::from_one_raw_str;
-use super::{Header, Raw};
+use ahash::AHashMap;
+
+use super::{
+    from_one_raw_str, ContentRangeSpec, Header, HeaderName, HeaderValue, IntoHeaderValue,
+    InvalidHeaderValue, Writer,
+};
+use crate::error::ParseError;
+use crate::http::header;
+use crate::HttpMessage;
 
 /// `Range` header, defined in [RFC7233](https://tools.ietf.org/html/rfc7233#section-3.1)
 ///
@@ -39,33 +47,40 @@ use super::{Header, Raw};
 /// # Examples
 ///
 /// ```
-/// use hyper::header::{Headers, Range, ByteRangeSpec};
+/// use actix_web::HttpResponse;
+/// use actix_web::http::header::{ByteRangeSpec, Range};
 ///
-/// let mut headers = Headers::new();
-/// headers.set(Range::Bytes(
+/// let mut builder = HttpResponse::Ok();
+/// builder.insert_header(Range::Bytes(
 ///     vec![ByteRangeSpec::FromTo(1, 100), ByteRangeSpec::AllFrom(200)]
 /// ));
 ///
-/// headers.clear();
-/// headers.set(Range::Unregistered("letters".to_owned(), "a-f".to_owned()));
+/// let mut builder = HttpResponse::Ok();
+/// builder.insert_header(Range::Unregistered("letters".to_owned(), "a-f".to_owned()));
 /// ```
 ///
 /// ```
-/// use hyper::header::{Headers, Range};
+/// use actix_web::HttpResponse;
+/// use actix_web::http::header::Range;
 ///
-/// let mut headers = Headers::new();
-/// headers.set(Range::bytes(1, 100));
+/// let mut builder = HttpResponse::Ok();
+/// builder.insert_header(Range::bytes(1, 100));
 ///
-/// headers.clear();
-/// headers.set(Range::bytes_multi(vec![(1, 100), (200, 300)]));
+/// let mut builder = HttpResponse::Ok();
+/// builder.insert_header(Range::bytes_multi(vec![(1, 100), (200, 300)]));
 /// ```
 #[derive(PartialEq, Clone, Debug)]
 pub enum Range {
     /// Byte range
     Bytes(Vec<ByteRangeSpec>),
+
     /// Custom range, with unit not registered at IANA
     /// (`other-range-unit`: String , `other-range-set`: String)
     Unregistered(String, String),
+
+    /// Custom range, with unit found in a [`RangeUnits`] registry and a range set accepted by it
+    /// (`other-range-unit`: String , `other-range-set`: String)
+    Registered(String, String),
 }
 
 /// Each `Range::Bytes` header can contain one or more `ByteRangeSpecs`.
@@ -167,10 +182,18 @@ impl Range {
                 .collect(),
         )
     }
+
+    /// Returns range unit, e.g. `bytes`.
+    pub fn unit(&self) -> &str {
+        match self {
+            Range::Bytes(_) => "bytes",
+            Range::Unregistered(unit, _) | Range::Registered(unit, _) => unit,
+        }
+    }
 }
 
 impl fmt::Display for ByteRangeSpec {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             ByteRangeSpec::FromTo(from, to) => write!(f, "{}-{}", from, to),
             ByteRangeSpec::Last(pos) => write!(f, "-{}", pos),
@@ -180,7 +203,7 @@ impl fmt::Display for ByteRangeSpec {
 }
 
 impl fmt::Display for Range {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match *self {
             Range::Bytes(ref ranges) => {
                 write!(f, "bytes=")?;
@@ -193,7 +216,8 @@ impl fmt::Display for Range {
                 }
                 Ok(())
             }
-            Range::Unregistered(ref unit, ref range_str) => {
+            Range::Unregistered(ref unit, ref range_str)
+            | Range::Registered(ref unit, ref range_str) => {
                 write!(f, "{}={}", unit, range_str)
             }
         }
@@ -201,49 +225,33 @@ impl fmt::Display for Range {
 }
 
 impl FromStr for Range {
-    type Err = ::Error;
+    type Err = ParseError;
 
-    fn from_str(s: &str) -> ::Result<Range> {
-        let mut iter = s.splitn(2, '=');
-
-        match (iter.next(), iter.next()) {
-            (Some("bytes"), Some(ranges)) => {
-                let ranges = from_comma_delimited(ranges);
-                if ranges.is_empty() {
-                    return Err(::Error::Header);
-                }
-                Ok(Range::Bytes(ranges))
-            }
-            (Some(unit), Some(range_str)) if unit != "" && range_str != "" => {
-                Ok(Range::Unregistered(unit.to_owned(), range_str.to_owned()))
-            }
-            _ => Err(::Error::Header),
-        }
+    fn from_str(s: &str) -> Result<Range, ParseError> {
+        RangeUnits::new().parse_range(s)
     }
 }
 
 impl FromStr for ByteRangeSpec {
-    type Err = ::Error;
+    type Err = ParseError;
 
-    fn from_str(s: &str) -> ::Result<ByteRangeSpec> {
+    fn from_str(s: &str) -> Result<ByteRangeSpec, ParseError> {
         let mut parts = s.splitn(2, '-');
 
         match (parts.next(), parts.next()) {
             (Some(""), Some(end)) => end
                 .parse()
-                .or(Err(::Error::Header))
+                .or(Err(ParseError::Header))
                 .map(ByteRangeSpec::Last),
             (Some(start), Some("")) => start
                 .parse()
-                .or(Err(::Error::Header))
+                .or(Err(ParseError::Header))
                 .map(ByteRangeSpec::AllFrom),
             (Some(start), Some(end)) => match (start.parse(), end.parse()) {
-                (Ok(start), Ok(end)) if start <= end => {
-                    Ok(ByteRangeSpec::FromTo(start, end))
-                }
-                _ => Err(::Error::Header),
+                (Ok(start), Ok(end)) if start <= end => Ok(ByteRangeSpec::FromTo(start, end)),
+                _ => Err(ParseError::Header),
             },
-            _ => Err(::Error::Header),
+            _ => Err(ParseError::Header),
         }
     }
 }
@@ -259,35 +267,161 @@ fn from_comma_delimited<T: FromStr>(s: &str) -> Vec<T> {
 }
 
 impl Header for Range {
-    fn header_name() -> &'static str {
-        static NAME: &'static str = "Range";
-        NAME
+    fn name() -> HeaderName {
+        header::RANGE
     }
 
-    fn parse_header(raw: &Raw) -> ::Result<Range> {
-        from_one_raw_str(raw)
+    #[inline]
+    fn parse<T: HttpMessage>(msg: &T) -> Result<Self, ParseError> {
+        from_one_raw_str(msg.headers().get(&Self::name()))
     }
+}
+
+impl IntoHeaderValue for Range {
+    type Error = InvalidHeaderValue;
 
-    fn fmt_header(&self, f: &mut ::header::Formatter) -> fmt::Result {
-        f.fmt_line(self)
+    fn try_into_value(self) -> Result<HeaderValue, Self::Error> {
+        let mut writer = Writer::new();
+        let _ = write!(&mut writer, "{}", self);
+        HeaderValue::from_maybe_shared(writer.take())
+    }
+}
+
+/// Validation of custom range units.
+///
+/// Both methods accept any value by default.
+pub trait RangeUnit: Send + Sync + 'static {
+    /// Returns true if range set (`other-range-set`) of a `Range` header is valid for this unit.
+    fn validate_range(&self, _set: &str) -> bool {
+        true
+    }
+
+    /// Returns true if range response (`other-range-resp`) of a `Content-Range` header is valid
+    /// for this unit.
+    fn validate_content_range(&self, _resp: &str) -> bool {
+        true
+    }
+}
+
+/// Registry of custom range units used to parse `Range` and `Content-Range` headers.
+///
+/// Headers using a registered unit are parsed as the `Registered` variant if the unit accepts
+/// them, and rejected otherwise. Headers using any other unit are parsed as `Unregistered`. Unit
+/// names are case-insensitive.
+///
+/// Parsing through [`FromStr`] or [`Header::parse`] uses an empty registry.
+///
+/// ```
+/// use actix_web::http::header::{ContentRangeSpec, Range, RangeUnit, RangeUnits};
+///
+/// struct Items;
+///
+/// impl RangeUnit for Items {
+///     fn validate_range(&self, set: &str) -> bool {
+///         set.split('-').all(|n| n.parse::<u64>().is_ok())
+///     }
+/// }
+///
+/// let units = RangeUnits::new().unit("items", Items);
+///
+/// let range = units.parse_range("items=0-9").unwrap();
+/// assert_eq!(range, Range::Registered("items".to_owned(), "0-9".to_owned()));
+/// assert!(units.parse_range("items=all").is_err());
+///
+/// let spec = units.parse_content_range("items 0-9/100").unwrap();
+/// assert!(matches!(spec, ContentRangeSpec::Registered { .. }));
+/// ```
+#[derive(Clone, Default)]
+pub struct RangeUnits {
+    units: Option<AHashMap<String, Arc<dyn RangeUnit>>>,
+}
+
+impl RangeUnits {
+    /// Create empty registry.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register custom range unit.
+    ///
+    /// Registering a unit again replaces the previous registration.
+    pub fn unit(mut self, name: &str, unit: impl RangeUnit) -> Self {
+        self.units
+            .get_or_insert_with(AHashMap::new)
+            .insert(name.to_ascii_lowercase(), Arc::new(unit));
+        self
+    }
+
+    pub(super) fn get(&self, name: &str) -> Option<&dyn RangeUnit> {
+        self.units
+            .as_ref()?
+            .get(&name.to_ascii_lowercase())
+            .map(|unit| &**unit)
+    }
+
+    /// Parse `Range` header value.
+    pub fn parse_range(&self, s: &str) -> Result<Range, ParseError> {
+        let mut iter = s.splitn(2, '=');
+
+        match (iter.next(), iter.next()) {
+            (Some("bytes"), Some(ranges)) => {
+                let ranges = from_comma_delimited(ranges);
+                if ranges.is_empty() {
+                    return Err(ParseError::Header);
+                }
+                Ok(Range::Bytes(ranges))
+            }
+            (Some(unit), Some(range_str)) if !unit.is_empty() && !range_str.is_empty() => {
+                match self.get(unit) {
+                    Some(range_unit) if range_unit.validate_range(range_str) => {
+                        Ok(Range::Registered(unit.to_owned(), range_str.to_owned()))
+                    }
+                    Some(_) => Err(ParseError::Header),
+                    None => Ok(Range::Unregistered(unit.to_owned(), range_str.to_owned())),
+                }
+            }
+            _ => Err(ParseError::Header),
+        }
+    }
+
+    /// Parse `Content-Range` header value.
+    pub fn parse_content_range(&self, s: &str) -> Result<ContentRangeSpec, ParseError> {
+        ContentRangeSpec::parse_with(s, self)
+    }
+}
+
+impl fmt::Debug for RangeUnits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut units = f.debug_set();
+        if let Some(ref map) = self.units {
+            units.entries(map.keys());
+        }
+        units.finish()
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use actix_http::test::TestRequest;
+
+    fn parse(value: &str) -> Result<Range, ParseError> {
+        let req = TestRequest::default()
+            .insert_header((header::RANGE, value))
+            .finish();
+        Range::parse(&req)
+    }
 
     #[test]
     fn test_parse_bytes_range_valid() {
-        let r: Range = Header::parse_header(&"bytes=1-100".into()).unwrap();
-        let r2: Range = Header::parse_header(&"bytes=1-100,-".into()).unwrap();
+        let r = parse("bytes=1-100").unwrap();
+        let r2 = parse("bytes=1-100,-").unwrap();
         let r3 = Range::bytes(1, 100);
         assert_eq!(r, r2);
         assert_eq!(r2, r3);
 
-        let r: Range = Header::parse_header(&"bytes=1-100,200-".into()).unwrap();
-        let r2: Range =
-            Header::parse_header(&"bytes= 1-100 , 101-xxx,  200- ".into()).unwrap();
+        let r = parse("bytes=1-100,200-").unwrap();
+        let r2 = parse("bytes= 1-100 , 101-xxx,  200- ").unwrap();
         let r3 = Range::Bytes(vec![
             ByteRangeSpec::FromTo(1, 100),
             ByteRangeSpec::AllFrom(200),
@@ -295,8 +429,8 @@ mod tests {
         assert_eq!(r, r2);
         assert_eq!(r2, r3);
 
-        let r: Range = Header::parse_header(&"bytes=1-100,-100".into()).unwrap();
-        let r2: Range = Header::parse_header(&"bytes=1-100, ,,-100".into()).unwrap();
+        let r = parse("bytes=1-100,-100").unwrap();
+        let r2 = parse("bytes=1-100, ,,-100").unwrap();
         let r3 = Range::Bytes(vec![
             ByteRangeSpec::FromTo(1, 100),
             ByteRangeSpec::Last(100),
@@ -304,71 +438,78 @@ mod tests {
         assert_eq!(r, r2);
         assert_eq!(r2, r3);
 
-        let r: Range = Header::parse_header(&"custom=1-100,-100".into()).unwrap();
+        let r = parse("custom=1-100,-100").unwrap();
         let r2 = Range::Unregistered("custom".to_owned(), "1-100,-100".to_owned());
         assert_eq!(r, r2);
     }
 
     #[test]
     fn test_parse_unregistered_range_valid() {
-        let r: Range = Header::parse_header(&"custom=1-100,-100".into()).unwrap();
+        let r = parse("custom=1-100,-100").unwrap();
         let r2 = Range::Unregistered("custom".to_owned(), "1-100,-100".to_owned());
         assert_eq!(r, r2);
 
-        let r: Range = Header::parse_header(&"custom=abcd".into()).unwrap();
+        let r = parse("custom=abcd").unwrap();
         let r2 = Range::Unregistered("custom".to_owned(), "abcd".to_owned());
         assert_eq!(r, r2);
 
-        let r: Range = Header::parse_header(&"custom=xxx-yyy".into()).unwrap();
+        let r = parse("custom=xxx-yyy").unwrap();
         let r2 = Range::Unregistered("custom".to_owned(), "xxx-yyy".to_owned());
         assert_eq!(r, r2);
     }
 
     #[test]
     fn test_parse_invalid() {
-        let r: ::Result<Range> = Header::parse_header(&"bytes=1-a,-".into());
-        assert_eq!(r.ok(), None);
-
-        let r: ::Result<Range> = Header::parse_header(&"bytes=1-2-3".into());
-        assert_eq!(r.ok(), None);
+        assert!(parse("bytes=1-a,-").is_err());
+        assert!(parse("bytes=1-2-3").is_err());
+        assert!(parse("abc").is_err());
+        assert!(parse("bytes=1-100=").is_err());
+        assert!(parse("bytes=").is_err());
+        assert!(parse("custom=").is_err());
+        assert!(parse("=1-100").is_err());
+    }
 
-        let r: ::Result<Range> = Header::parse_header(&"abc".into());
-        assert_eq!(r.ok(), None);
+    #[test]
+    fn test_parse_registered() {
+        struct Frames;
 
-        let r: ::Result<Range> = Header::parse_header(&"bytes=1-100=".into());
-        assert_eq!(r.ok(), None);
+        impl RangeUnit for Frames {
+            fn validate_range(&self, set: &str) -> bool {
+                set.split('-').all(|n| n.parse::<u64>().is_ok())
+            }
+        }
 
-        let r: ::Result<Range> = Header::parse_header(&"bytes=".into());
-        assert_eq!(r.ok(), None);
+        let units = RangeUnits::new().unit("frames", Frames);
 
-        let r: ::Result<Range> = Header::parse_header(&"custom=".into());
-        assert_eq!(r.ok(), None);
+        let r = units.parse_range("Frames=1-2").unwrap();
+        assert_eq!(r, Range::Registered("Frames".to_owned(), "1-2".to_owned()));
+        assert_eq!(r.unit(), "Frames");
+        assert_eq!(r.to_string(), "Frames=1-2");
 
-        let r: ::Result<Range> = Header::parse_header(&"=1-100".into());
-        assert_eq!(r.ok(), None);
+        assert!(units.parse_range("frames=a-b").is_err());
+        assert_eq!(
+            units.parse_range("pages=a-b").unwrap(),
+            Range::Unregistered("pages".to_owned(), "a-b".to_owned())
+        );
+        assert_eq!(
+            "frames=a-b".parse::<Range>().unwrap(),
+            Range::Unregistered("frames".to_owned(), "a-b".to_owned())
+        );
     }
 
     #[test]
     fn test_fmt() {
-        use header::Headers;
-
-        let mut headers = Headers::new();
-
-        headers.set(Range::Bytes(vec![
+        let range = Range::Bytes(vec![
             ByteRangeSpec::FromTo(0, 1000),
             ByteRangeSpec::AllFrom(2000),
-        ]));
-        assert_eq!(&headers.to_string(), "Range: bytes=0-1000,2000-\r\n");
-
-        headers.clear();
-        headers.set(Range::Bytes(vec![]));
-
-        assert_eq!(&headers.to_string(), "Range: bytes=\r\n");
+        ]);
+        assert_eq!(range.try_into_value().unwrap(), "bytes=0-1000,2000-");
 
-        headers.clear();
-        headers.set(Range::Unregistered("custom".to_owned(), "1-xxx".to_owned()));
+        let range = Range::Bytes(vec![]);
+        assert_eq!(range.try_into_value().unwrap(), "bytes=");
 
-        assert_eq!(&headers.to_string(), "Range: custom=1-xxx\r\n");
+        let range = Range::Unregistered("custom".to_owned(), "1-xxx".to_owned());
+        assert_eq!(range.try_into_value().unwrap(), "custom=1-xxx");
     }
 
     #[test]
@@ -0,0 +1,260 @@
+use super::{split_comma_delimited, HeaderMap, HeaderName, IntoHeaderValue, VARY};
+
+crate::http::header::common_header! {
+    /// `Vary` header, defined in
+    /// [RFC7231](https://tools.ietf.org/html/rfc7231#section-7.1.4)
+    ///
+    /// The `Vary` header field in a response describes what parts of a
+    /// request message, aside from the method, Host header field, and
+    /// request target, might influence the origin server's process for
+    /// selecting and representing this response. The value consists of
+    /// either a single asterisk ("*") or a list of header field names
+    /// (case-insensitive).
+    ///
+    /// # ABNF
+    ///
+    /// ```text
+    /// Vary = "*" / 1#field-name
+    /// ```
+    ///
+    /// # Example values
+    ///
+    /// * `accept-encoding, accept-language`
+    /// * `*`
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use actix_web::HttpResponse;
+    /// use actix_web::http::header::Vary;
+    ///
+    /// let mut builder = HttpResponse::Ok();
+    /// builder.insert_header(Vary::Any);
+    /// ```
+    ///
+    /// ```
+    /// use actix_web::HttpResponse;
+    /// use actix_web::http::header::{self, Vary};
+    ///
+    /// let mut builder = HttpResponse::Ok();
+    /// builder.insert_header(
+    ///     Vary::Items(vec![
+    ///         header::ACCEPT_ENCODING,
+    ///         header::ACCEPT_LANGUAGE,
+    ///     ])
+    /// );
+    /// ```
+    (Vary, VARY) => {Any / (HeaderName)+}
+
+    test_vary {
+        crate::http::header::common_header_test!(test1, vec![b"accept-encoding, fROM, COOKIE"]);
+        crate::http::header::common_header_test!(
+            test2,
+            vec![b"accept-encoding, cookie"],
+            Some(HeaderField::Items(vec![ACCEPT_ENCODING, COOKIE])));
+        crate::http::header::common_header_test!(test3, vec![b"*"], Some(Vary::Any));
+    }
+}
+
+impl Vary {
+    /// Returns true if the selected response may depend on the given request header.
+    ///
+    /// `Any` varies on every request header.
+    pub fn contains(&self, name: &HeaderName) -> bool {
+        match self {
+            Vary::Any => true,
+            Vary::Items(items) => items.contains(name),
+        }
+    }
+
+    /// Adds header names that are not already listed, preserving the existing order.
+    ///
+    /// Merging into `Any` has no effect.
+    pub fn merge<I>(&mut self, names: I)
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        if let Vary::Items(items) = self {
+            for name in names {
+                if !items.contains(&name) {
+                    items.push(name);
+                }
+            }
+        }
+    }
+
+    /// Merges header names into the `Vary` header of a response's header map.
+    ///
+    /// Every existing `Vary` field line is combined with `names` and replaced by a single,
+    /// de-duplicated value. A `*` in any existing line is kept as is and no header is written
+    /// when the resulting list is empty.
+    ///
+    /// Existing field lines that are not valid UTF-8 or contain unparsable list elements are never
+    /// dropped; they are kept untouched and any missing names are appended as a new field line.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{http::header::{self, Vary}, HttpResponse};
+    ///
+    /// let mut res = HttpResponse::Ok()
+    ///     .insert_header((header::VARY, "Accept-Language"))
+    ///     .finish();
+    ///
+    /// let names = vec![header::ACCEPT_ENCODING, header::ACCEPT_LANGUAGE];
+    /// Vary::merge_into(res.headers_mut(), names);
+    /// assert_eq!(
+    ///     res.headers().get(header::VARY).unwrap(),
+    ///     "accept-language, accept-encoding",
+    /// );
+    /// ```
+    pub fn merge_into<I>(headers: &mut HeaderMap, names: I)
+    where
+        I: IntoIterator<Item = HeaderName>,
+    {
+        let (mut vary, complete) = Vary::from_headers(headers);
+
+        if !complete {
+            // existing lines are kept verbatim; only append names not already listed
+            if let Vary::Items(ref existing) = vary {
+                let mut missing = Vary::Items(Vec::new());
+                missing.merge(names.into_iter().filter(|name| !existing.contains(name)));
+
+                if let Vary::Items(ref items) = missing {
+                    if items.is_empty() {
+                        return;
+                    }
+                }
+
+                if let Ok(val) = missing.try_into_value() {
+                    headers.append(VARY, val);
+                }
+            }
+
+            return;
+        }
+
+        vary.merge(names);
+
+        if let Vary::Items(ref items) = vary {
+            if items.is_empty() {
+                return;
+            }
+        }
+
+        if let Ok(val) = vary.try_into_value() {
+            headers.insert(VARY, val);
+        }
+    }
+
+    /// Reads all `Vary` field lines, treating a `*` anywhere in the list as `Any`.
+    ///
+    /// The returned flag is false when a field line is not valid UTF-8 or contains an element
+    /// that is not a header name, in which case only the parsable names are returned.
+    fn from_headers(headers: &HeaderMap) -> (Vary, bool) {
+        let mut items = Vec::new();
+        let mut complete = true;
+
+        for val in headers.get_all(VARY) {
+            let val = match val.to_str() {
+                Ok(val) => val,
+                Err(_) => {
+                    complete = false;
+                    continue;
+                }
+            };
+
+            for item in split_comma_delimited(val) {
+                if item == "*" {
+                    return (Vary::Any, complete);
+                }
+
+                match item.parse::<HeaderName>() {
+                    Ok(name) => {
+                        if !items.contains(&name) {
+                            items.push(name);
+                        }
+                    }
+                    Err(_) => complete = false,
+                }
+            }
+        }
+
+        (Vary::Items(items), complete)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::header::{HeaderValue, ACCEPT_ENCODING, ACCEPT_LANGUAGE, ORIGIN};
+
+    #[test]
+    fn contains() {
+        let vary = Vary::Items(vec![ACCEPT_ENCODING]);
+        assert!(vary.contains(&ACCEPT_ENCODING));
+        assert!(!vary.contains(&ORIGIN));
+
+        assert!(Vary::Any.contains(&ORIGIN));
+    }
+
+    #[test]
+    fn merge() {
+        let mut vary = Vary::Items(vec![ACCEPT_ENCODING]);
+        vary.merge(vec![ORIGIN, ACCEPT_ENCODING, ORIGIN]);
+        assert_eq!(vary, Vary::Items(vec![ACCEPT_ENCODING, ORIGIN]));
+
+        let mut vary = Vary::Any;
+        vary.merge(vec![ORIGIN]);
+        assert_eq!(vary, Vary::Any);
+    }
+
+    #[test]
+    fn merge_into_headers() {
+        let mut headers = HeaderMap::new();
+        Vary::merge_into(&mut headers, vec![ACCEPT_ENCODING]);
+        assert_eq!(headers.get(VARY).unwrap(), "accept-encoding");
+
+        // multiple field lines are combined into one
+        headers.append(VARY, HeaderValue::from_static("Origin, Accept-Encoding"));
+        Vary::merge_into(&mut headers, vec![ACCEPT_LANGUAGE]);
+        assert_eq!(headers.get_all(VARY).count(), 1);
+        assert_eq!(
+            headers.get(VARY).unwrap(),
+            "accept-encoding, origin, accept-language"
+        );
+
+        let mut headers = HeaderMap::new();
+        headers.insert(VARY, HeaderValue::from_static("origin, *"));
+        Vary::merge_into(&mut headers, vec![ACCEPT_ENCODING]);
+        assert_eq!(headers.get(VARY).unwrap(), "*");
+
+        let mut headers = HeaderMap::new();
+        Vary::merge_into(&mut headers, Vec::<HeaderName>::new());
+        assert!(!headers.contains_key(VARY));
+    }
+
+    #[test]
+    fn merge_into_keeps_unparsable() {
+        // non-UTF-8 field lines are kept
+        let mut headers = HeaderMap::new();
+        headers.insert(VARY, HeaderValue::from_bytes(b"origin, x-\xff").unwrap());
+        Vary::merge_into(&mut headers, vec![ACCEPT_ENCODING]);
+        let vals = headers.get_all(VARY).collect::<Vec<_>>();
+        assert_eq!(vals.len(), 2);
+        assert_eq!(vals[0].as_bytes(), b"origin, x-\xff");
+        assert_eq!(vals[1], "accept-encoding");
+
+        // invalid list elements are kept and already listed names are not repeated
+        let mut headers = HeaderMap::new();
+        headers.insert(VARY, HeaderValue::from_static("origin, x(y)"));
+        Vary::merge_into(&mut headers, vec![ORIGIN, ACCEPT_LANGUAGE]);
+        let vals = headers.get_all(VARY).collect::<Vec<_>>();
+        assert_eq!(vals.len(), 2);
+        assert_eq!(vals[0], "origin, x(y)");
+        assert_eq!(vals[1], "accept-language");
+
+        // nothing is appended when every name is already listed
+        Vary::merge_into(&mut headers, vec![ORIGIN]);
+        assert_eq!(headers.get_all(VARY).count(), 2);
+    }
+}
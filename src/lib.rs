@@ -72,6 +72,7 @@
 mod app;
 mod app_service;
 mod config;
+mod conn_data;
 mod data;
 pub mod dev;
 pub mod error;
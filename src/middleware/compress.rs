@@ -1,18 +1,18 @@
 //! For middleware documentation, see [`Compress`].
 
 use std::{
-    cmp,
     future::Future,
     marker::PhantomData,
     pin::Pin,
-    str::FromStr,
+    rc::Rc,
+    slice,
     task::{Context, Poll},
 };
 
 use actix_http::{
     body::{MessageBody, ResponseBody},
-    encoding::Encoder,
-    http::header::{ContentEncoding, ACCEPT_ENCODING},
+    encoding::{negotiate, CompressionBudget, CompressionConfig, Encoder},
+    http::header::{ContentEncoding, HeaderValue, ACCEPT_ENCODING},
 };
 use actix_service::{Service, Transform};
 use actix_utils::future::{ok, Ready};
@@ -39,12 +39,53 @@ use crate::{
 ///     .default_service(web::to(|| HttpResponse::NotFound()));
 /// ```
 #[derive(Debug, Clone)]
-pub struct Compress(ContentEncoding);
+pub struct Compress {
+    encoding: ContentEncoding,
+    budget: Option<CompressionBudget>,
+    config: Rc<CompressionConfig>,
+}
 
 impl Compress {
     /// Create new `Compress` middleware with the specified encoding.
     pub fn new(encoding: ContentEncoding) -> Self {
-        Compress(encoding)
+        Compress {
+            encoding,
+            budget: None,
+            config: Rc::new(CompressionConfig::default()),
+        }
+    }
+
+    /// Limit the number of responses that are compressed concurrently.
+    ///
+    /// Responses that would exceed the budget are sent uncompressed. Since the app factory runs
+    /// once per worker, creating the budget there gives each worker its own budget.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{dev::CompressionBudget, middleware, App};
+    ///
+    /// let app = App::new().wrap(
+    ///     middleware::Compress::default().budget(CompressionBudget::new(16)),
+    /// );
+    /// ```
+    pub fn budget(mut self, budget: CompressionBudget) -> Self {
+        self.budget = Some(budget);
+        self
+    }
+
+    /// Set compression levels used for each algorithm.
+    ///
+    /// # Examples
+    /// ```
+    /// use actix_web::{dev::CompressionConfig, middleware, App};
+    ///
+    /// let app = App::new().wrap(
+    ///     middleware::Compress::default().config(CompressionConfig::new().gzip_level(6)),
+    /// );
+    /// ```
+    pub fn config(mut self, config: CompressionConfig) -> Self {
+        self.config = Rc::new(config);
+        self
     }
 }
 
@@ -68,7 +109,9 @@ where
     fn new_transform(&self, service: S) -> Self::Future {
         ok(CompressMiddleware {
             service,
-            encoding: self.0,
+            encoding: self.encoding,
+            budget: self.budget.clone(),
+            config: Rc::clone(&self.config),
         })
     }
 }
@@ -76,6 +119,8 @@ where
 pub struct CompressMiddleware<S> {
     service: S,
     encoding: ContentEncoding,
+    budget: Option<CompressionBudget>,
+    config: Rc<CompressionConfig>,
 }
 
 impl<S, B> Service<ServiceRequest> for CompressMiddleware<S>
@@ -93,17 +138,24 @@ where
     fn call(&self, req: ServiceRequest) -> Self::Future {
         // negotiate content-encoding
         let encoding = if let Some(val) = req.headers().get(&ACCEPT_ENCODING) {
-            if let Ok(enc) = val.to_str() {
-                AcceptEncoding::parse(enc, self.encoding)
-            } else {
-                ContentEncoding::Identity
-            }
+            val.to_str()
+                .ok()
+                .and_then(|enc| match self.encoding {
+                    ContentEncoding::Auto => self.config.negotiate(enc),
+                    ref preferred => negotiate(enc, slice::from_ref(preferred)),
+                })
+                .unwrap_or(ContentEncoding::Identity)
         } else {
             ContentEncoding::Identity
         };
 
+        let dictionary_ids = req.headers().get("dictionary-id").cloned();
+
         CompressResponse {
             encoding,
+            dictionary_ids,
+            budget: self.budget.clone(),
+            config: Rc::clone(&self.config),
             fut: self.service.call(req),
             _phantom: PhantomData,
         }
@@ -119,6 +171,9 @@ where
     #[pin]
     fut: S::Future,
     encoding: ContentEncoding,
+    dictionary_ids: Option<HeaderValue>,
+    budget: Option<CompressionBudget>,
+    config: Rc<CompressionConfig>,
     _phantom: PhantomData<B>,
 }
 
@@ -140,79 +195,22 @@ where
                     *this.encoding
                 };
 
+                let budget = this.budget.take();
+                let config = Rc::clone(this.config);
+                let dictionary_ids = this.dictionary_ids.take();
+
                 Poll::Ready(Ok(resp.map_body(move |head, body| {
-                    Encoder::response(enc, head, ResponseBody::Body(body))
+                    Encoder::response_with_dictionary_ids(
+                        enc,
+                        head,
+                        ResponseBody::Body(body),
+                        budget.as_ref(),
+                        &config,
+                        dictionary_ids.as_ref(),
+                    )
                 })))
             }
             Err(e) => Poll::Ready(Err(e)),
         }
     }
 }
-
-struct AcceptEncoding {
-    encoding: ContentEncoding,
-    quality: f64,
-}
-
-impl Eq for AcceptEncoding {}
-
-impl Ord for AcceptEncoding {
-    #[allow(clippy::comparison_chain)]
-    fn cmp(&self, other: &AcceptEncoding) -> cmp::Ordering {
-        if self.quality > other.quality {
-            cmp::Ordering::Less
-        } else if self.quality < other.quality {
-            cmp::Ordering::Greater
-        } else {
-            cmp::Ordering::Equal
-        }
-    }
-}
-
-impl PartialOrd for AcceptEncoding {
-    fn partial_cmp(&self, other: &AcceptEncoding) -> Option<cmp::Ordering> {
-        Some(self.cmp(other))
-    }
-}
-
-impl PartialEq for AcceptEncoding {
-    fn eq(&self, other: &AcceptEncoding) -> bool {
-        self.quality == other.quality
-    }
-}
-
-impl AcceptEncoding {
-    fn new(tag: &str) -> Option<AcceptEncoding> {
-        let parts: Vec<&str> = tag.split(';').collect();
-        let encoding = match parts.len() {
-            0 => return None,
-            _ => ContentEncoding::from(parts[0]),
-        };
-        let quality = match parts.len() {
-            1 => encoding.quality(),
-            _ => f64::from_str(parts[1]).unwrap_or(0.0),
-        };
-        Some(AcceptEncoding { encoding, quality })
-    }
-
-    /// Parse a raw Accept-Encoding header value into an ordered list.
-    pub fn parse(raw: &str, encoding: ContentEncoding) -> ContentEncoding {
-        let mut encodings = raw
-            .replace(' ', "")
-            .split(',')
-            .filter_map(|l| AcceptEncoding::new(l))
-            .collect::<Vec<_>>();
-
-        encodings.sort();
-
-        for enc in encodings {
-            if encoding == ContentEncoding::Auto {
-                return enc.encoding;
-            } else if encoding == enc.encoding {
-                return encoding;
-            }
-        }
-
-        ContentEncoding::Identity
-    }
-}
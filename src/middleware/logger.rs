@@ -22,7 +22,7 @@ use time::OffsetDateTime;
 
 use crate::{
     dev::{BodySize, MessageBody},
-    http::{HeaderName, StatusCode},
+    http::{HeaderMap, HeaderName, StatusCode},
     service::{ServiceRequest, ServiceResponse},
     Error, HttpResponse, Result,
 };
@@ -351,6 +351,13 @@ where
             None => Poll::Ready(None),
         }
     }
+
+    fn poll_trailers(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+    ) -> Poll<Result<Option<HeaderMap>, Self::Error>> {
+        Poll::Ready(ready!(self.project().body.poll_trailers(cx)).map_err(Into::into))
+    }
 }
 
 /// A formatting style for the `Logger` consisting of multiple concatenated `FormatText` items.
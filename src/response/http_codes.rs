@@ -64,6 +64,8 @@ impl HttpResponse {
     static_resp!(RangeNotSatisfiable, StatusCode::RANGE_NOT_SATISFIABLE);
     static_resp!(ExpectationFailed, StatusCode::EXPECTATION_FAILED);
     static_resp!(UnprocessableEntity, StatusCode::UNPROCESSABLE_ENTITY);
+    static_resp!(Locked, StatusCode::LOCKED);
+    static_resp!(FailedDependency, StatusCode::FAILED_DEPENDENCY);
     static_resp!(TooManyRequests, StatusCode::TOO_MANY_REQUESTS);
     static_resp!(
         RequestHeaderFieldsTooLarge,
@@ -96,4 +98,18 @@ mod tests {
         let resp = HttpResponse::Ok().body(Body::Empty);
         assert_eq!(resp.status(), StatusCode::OK);
     }
+
+    #[test]
+    fn test_webdav_status() {
+        let resp = HttpResponse::MultiStatus()
+            .content_type("application/xml; charset=utf-8")
+            .body("<d:multistatus xmlns:d=\"DAV:\"/>");
+        assert_eq!(resp.status(), StatusCode::MULTI_STATUS);
+
+        assert_eq!(HttpResponse::Locked().finish().status(), StatusCode::LOCKED);
+        assert_eq!(
+            HttpResponse::FailedDependency().finish().status(),
+            StatusCode::FAILED_DEPENDENCY
+        );
+    }
 }
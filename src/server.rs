@@ -107,6 +107,11 @@ where
     /// It will receive a `&std::any::Any`, which contains underlying connection type and an
     /// [Extensions] container so that request-local data can be passed to middleware and handlers.
     ///
+    /// The function is called a second time to fill the
+    /// [`ConnectionData`](crate::dev::ConnectionData) request extension, which is available to
+    /// every request on the connection and read by the [`ConnData`](crate::web::ConnData)
+    /// extractor.
+    ///
     /// For example:
     /// - `actix_tls::openssl::SslStream<actix_web::rt::net::TcpStream>` when using openssl.
     /// - `actix_tls::rustls::TlsStream<actix_web::rt::net::TcpStream>` when using rustls.